@@ -0,0 +1,60 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ferrumc::world::chunk_format::Chunk;
+use moka::future::Cache;
+use nbt_lib::NBTDeserializeBytes;
+
+const LOOKUPS: u64 = 10_000;
+const CACHED_CHUNKS: u64 = 256;
+
+/// Loads the chunk produced by the `dump_chunk` test.
+/// Can be overridden with the `FERRUMC_BENCH_CHUNK` environment variable.
+fn load_chunk() -> Chunk {
+    let path = std::env::var("FERRUMC_BENCH_CHUNK").unwrap_or_else(|_| "chunk.nbt".to_string());
+    let data = std::fs::read(&path).unwrap_or_else(|_| {
+        panic!("Could not read {path}, run the ignored `dump_chunk` test first")
+    });
+    Chunk::read_from_bytes(&mut Cursor::new(data)).expect("Invalid chunk NBT")
+}
+
+/// Compares cached chunks handed out as copies with cached chunks handed out as shared pointers
+fn benchmark_cache_lookups(c: &mut Criterion) {
+    let chunk = load_chunk();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let owned = Cache::<u64, Chunk>::new(CACHED_CHUNKS);
+    let shared = Cache::<u64, Arc<Chunk>>::new(CACHED_CHUNKS);
+    runtime.block_on(async {
+        for key in 0..CACHED_CHUNKS {
+            owned.insert(key, chunk.clone()).await;
+            shared.insert(key, Arc::new(chunk.clone())).await;
+        }
+    });
+
+    let mut group = c.benchmark_group("chunk cache lookups");
+    group.throughput(Throughput::Elements(LOOKUPS));
+    group.bench_function("Chunk (cloned)", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for key in 0..LOOKUPS {
+                    black_box(owned.get(&(key % CACHED_CHUNKS)).await);
+                }
+            })
+        })
+    });
+    group.bench_function("Arc<Chunk> (shared)", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for key in 0..LOOKUPS {
+                    black_box(shared.get(&(key % CACHED_CHUNKS)).await);
+                }
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(chunk_cache, benchmark_cache_lookups);
+criterion_main!(chunk_cache);
@@ -0,0 +1,88 @@
+use std::io::Cursor;
+
+use bincode::config::standard;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ferrumc::database::encoding::{ChunkCodec, Compression, Versioned};
+use ferrumc::world::chunk_format::Chunk;
+use nbt_lib::NBTDeserializeBytes;
+use rayon::prelude::*;
+
+/// Loads the chunk produced by the `dump_chunk` test.
+/// Can be overridden with the `FERRUMC_BENCH_CHUNK` environment variable.
+fn load_chunk() -> Chunk {
+    let path = std::env::var("FERRUMC_BENCH_CHUNK").unwrap_or_else(|_| "chunk.nbt".to_string());
+    let data = std::fs::read(&path).unwrap_or_else(|_| {
+        panic!("Could not read {path}, run the ignored `dump_chunk` test first")
+    });
+    Chunk::read_from_bytes(&mut Cursor::new(data)).expect("Invalid chunk NBT")
+}
+
+fn benchmark_chunk_decompression(c: &mut Criterion) {
+    let chunk = load_chunk();
+    let encoded = bincode::encode_to_vec(&chunk, standard()).unwrap();
+
+    let mut group = c.benchmark_group("chunk decompression");
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+    for compression in [
+        Compression::Bzip,
+        Compression::Zstd,
+        Compression::Lz4,
+        Compression::None,
+    ] {
+        let compressed = compression.compress(&encoded).unwrap();
+        group.bench_function(format!("{:?}", compression), |b| {
+            b.iter(|| {
+                let bytes = compression.decompress(black_box(&compressed)).unwrap();
+                let decoded: (Chunk, usize) =
+                    bincode::decode_from_slice(&bytes, standard()).unwrap();
+                black_box(decoded);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Decoding the ~400 chunks a player receives when joining, one after the other versus across
+/// the rayon pool like `Database::batch_get` does
+fn benchmark_batch_decompression(c: &mut Criterion) {
+    let chunk = load_chunk();
+    let encoded = bincode::encode_to_vec(&chunk, standard()).unwrap();
+    // Values as stored in the database, prefixed with their format version
+    let mut value = vec![Chunk::VERSION];
+    value.extend(Compression::Bzip.compress(&encoded).unwrap());
+    let values = vec![value; 400];
+
+    let mut group = c.benchmark_group("batch decompression");
+    group.throughput(Throughput::Elements(values.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            let decoded = values
+                .iter()
+                .map(|data| ChunkCodec::decompress_data_sync::<Chunk>(data, Compression::Bzip))
+                .collect::<Vec<_>>();
+            black_box(decoded);
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            let decoded = values
+                .par_iter()
+                .map(|data| ChunkCodec::decompress_data_sync::<Chunk>(data, Compression::Bzip))
+                .collect::<Vec<_>>();
+            black_box(decoded);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_chunk_decompression,
+    benchmark_batch_decompression
+);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ferrumc::ecs::helpers::sparse_set::SparseSet;
+use ferrumc::ecs::world::World;
+use ferrumc::utils::encoding::position::Position;
+
+const COMPONENTS: usize = 100_000;
+
+/// Compares iterating over every component of a type in the sparse set behind the component
+/// storage, in a `HashMap` keyed by entity, and through a query of the world
+fn benchmark_component_iteration(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut sparse_set = SparseSet::new();
+    let mut hash_map = HashMap::new();
+    let world = World::new();
+    runtime.block_on(async {
+        for entity in 0..COMPONENTS {
+            let position = Position::new(entity as i32, 64, -(entity as i32));
+            sparse_set.insert(entity, position.clone());
+            hash_map.insert(entity, position.clone());
+            world.create_entity().await.with(position).build();
+        }
+    });
+
+    let mut group = c.benchmark_group("component iteration");
+    group.throughput(Throughput::Elements(COMPONENTS as u64));
+    group.bench_function("SparseSet", |b| {
+        b.iter(|| {
+            let sum: i64 = sparse_set
+                .iter()
+                .map(|(_, position)| position.x as i64)
+                .sum();
+            black_box(sum)
+        })
+    });
+    group.bench_function("HashMap", |b| {
+        b.iter(|| {
+            let sum: i64 = hash_map.values().map(|position| position.x as i64).sum();
+            black_box(sum)
+        })
+    });
+    group.bench_function("World query", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let mut sum = 0i64;
+                let mut query = world.query::<&Position>();
+                while let Some((_, position)) = query.next().await {
+                    sum += position.x as i64;
+                }
+                black_box(sum)
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(component_storage, benchmark_component_iteration);
+criterion_main!(component_storage);
@@ -30,11 +30,6 @@ pub fn nbt_decode_derive(input: TokenStream) -> TokenStream {
     nbt_decode::decode(input)
 }*/
 
-#[proc_macro]
-pub fn bake_packet_registry(input: TokenStream) -> TokenStream {
-    packet::bake(input)
-}
-
 #[proc_macro_derive(Component)]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     ecs::derive_component(input)
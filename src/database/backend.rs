@@ -0,0 +1,347 @@
+//! Storage backends of the encoded chunks
+//!
+//! Chunks live in the LMDB tables of the world, or in a concurrent map for databases created
+//! with [`Database::new_in_memory`]. Both hold the same encoded values, so the cache, the
+//! encoding and the dirty tracking run the same code whatever the backend.
+
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{spawn_blocking_db, start_threadpool, SharedEnv};
+use crate::database::cache::{build_cache, CacheCounters};
+use crate::database::dimensions::{table_name, DimensionTables};
+use crate::database::dirty::DirtyChunks;
+use crate::database::disk_space::DiskGuard;
+use crate::database::entities::EntityCache;
+use crate::database::keys::table_key;
+use crate::database::metrics::DbMetrics;
+use crate::database::Database;
+use crate::utils::config::Database as DatabaseConfig;
+use crate::utils::error::Error;
+use crate::world::importing::SerializedChunk;
+
+/// Chunk tables kept in memory, keyed like the LMDB tables
+#[derive(Default)]
+pub(crate) struct MemoryStore {
+    tables: DashMap<Arc<str>, BTreeMap<u64, Vec<u8>>>,
+    corrupt: DashMap<u128, Vec<u8>>,
+}
+
+impl MemoryStore {
+    fn missing(table: &str) -> Error {
+        Error::TableMissing(table.to_string())
+    }
+
+    fn get(&self, table: &str, key: u128) -> Result<Option<Vec<u8>>, Error> {
+        let chunks = self.tables.get(table).ok_or_else(|| Self::missing(table))?;
+        Ok(chunks.get(&table_key(key)).cloned())
+    }
+
+    fn put(&self, table: &str, key: u128, data: Vec<u8>) -> Result<(), Error> {
+        let mut chunks = self
+            .tables
+            .get_mut(table)
+            .ok_or_else(|| Self::missing(table))?;
+        chunks.insert(table_key(key), data);
+        Ok(())
+    }
+}
+
+/// Where the encoded chunks are stored
+#[derive(Clone)]
+pub(crate) enum ChunkStore {
+    Lmdb(SharedEnv),
+    Memory(Arc<MemoryStore>),
+}
+
+impl ChunkStore {
+    /// Raw chunk `key` of `table`
+    pub(crate) async fn get(&self, table: &str, key: u128) -> Result<Option<Vec<u8>>, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                // Initialize read transaction and open the dimension table
+                let env = db.get()?;
+                let ro_tx = env.read_txn()?;
+                let database = Database::open_chunk_table(&env, &ro_tx, table)?;
+
+                // Attempt to fetch chunk from table
+                let data = database.get(&ro_tx, &table_key(key))?;
+                Ok(data.map(|data| data.to_vec()))
+            }
+            ChunkStore::Memory(store) => store.get(table, key),
+        }
+    }
+
+    /// Raw chunks of `keys`, in the same order, keys without a table have no chunk
+    pub(crate) async fn get_many(
+        &self,
+        keys: Vec<(u128, Option<Arc<str>>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::get_chunks_from_database(db, &keys)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => keys
+                .iter()
+                .map(|(key, table)| match table {
+                    Some(table) => store.get(table, *key),
+                    None => Ok(None),
+                })
+                .collect(),
+        }
+    }
+
+    /// Every raw chunk of `table`, ordered by key
+    pub(crate) async fn get_table(&self, table: Arc<str>) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::get_table_from_database(db, &table)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => {
+                let chunks = store
+                    .tables
+                    .get(&table)
+                    .ok_or_else(|| MemoryStore::missing(&table))?;
+                Ok(chunks
+                    .iter()
+                    .map(|(key, data)| (*key, data.clone()))
+                    .collect())
+            }
+        }
+    }
+
+    /// Store a single raw chunk
+    pub(crate) async fn put(&self, table: Arc<str>, key: u128, data: Vec<u8>) -> Result<(), Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::insert_chunk_into_database(db, &table, key, &data)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => store.put(&table, key, data),
+        }
+    }
+
+    /// Store raw chunks, all of them or none for LMDB
+    pub(crate) async fn put_many(
+        &self,
+        tables: Arc<DimensionTables>,
+        values: Arc<Vec<SerializedChunk>>,
+    ) -> Result<(), Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::insert_chunks_into_database(db, &tables, &values)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => {
+                for value in values.iter() {
+                    let table = tables.table_of_key(value.hash()).ok_or_else(|| {
+                        Error::TableMissing(format!("dimension of chunk {:X}", value.hash()))
+                    })?;
+                    store.put(&table, value.hash(), value.data().clone())?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace chunk `key` with `data` if it is still `old_data`, returns whether it was
+    pub(crate) async fn replace(
+        &self,
+        table: Arc<str>,
+        key: u128,
+        old_data: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<bool, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    let mut rw_tx = db.write_txn()?;
+                    let database = Database::open_chunk_table(db, &rw_tx, &table)?;
+                    let unchanged =
+                        database.get(&rw_tx, &table_key(key))? == Some(old_data.as_slice());
+                    if unchanged {
+                        database.put(&mut rw_tx, &table_key(key), &data)?;
+                    }
+                    rw_tx.commit()?;
+                    Ok(unchanged)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => {
+                let mut chunks = store
+                    .tables
+                    .get_mut(&table)
+                    .ok_or_else(|| MemoryStore::missing(&table))?;
+                match chunks.get_mut(&table_key(key)) {
+                    Some(stored) if *stored == old_data => {
+                        *stored = data;
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+        }
+    }
+
+    /// Delete the chunks of `keys`, returns for each key whether it was present
+    pub(crate) async fn delete_many(
+        &self,
+        keys: Vec<(u128, Option<Arc<str>>)>,
+    ) -> Result<Vec<bool>, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::delete_chunks_from_database(db, &keys)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => keys
+                .iter()
+                .map(|(key, table)| match table {
+                    Some(table) => Ok(store
+                        .tables
+                        .get_mut(&**table)
+                        .ok_or_else(|| MemoryStore::missing(table))?
+                        .remove(&table_key(*key))
+                        .is_some()),
+                    None => Ok(false),
+                })
+                .collect(),
+        }
+    }
+
+    /// Move chunks out of their table into the corrupt chunks
+    pub(crate) async fn quarantine(&self, chunks: Vec<(Arc<str>, u128)>) -> Result<(), Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                spawn_blocking_db(db.clone(), move |db| {
+                    Database::quarantine_chunks_in_database(db, &chunks)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => {
+                for (table, key) in chunks {
+                    let data = store
+                        .tables
+                        .get_mut(&table)
+                        .and_then(|mut chunks| chunks.remove(&table_key(key)));
+                    if let Some(data) = data {
+                        store.corrupt.insert(key, data);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Create the chunk table of `dimension` and register it
+    pub(crate) async fn create_table(&self, dimension: &str) -> Result<Arc<str>, Error> {
+        match self {
+            ChunkStore::Lmdb(db) => {
+                let name = dimension.to_string();
+                spawn_blocking_db(db.clone(), move |db| {
+                    let mut rw_tx = db.write_txn()?;
+                    let table = DimensionTables::create_table(db, &mut rw_tx, &name)?;
+                    rw_tx.commit()?;
+                    Ok(table)
+                })
+                .await?
+            }
+            ChunkStore::Memory(store) => {
+                let table = Arc::<str>::from(table_name(dimension));
+                store.tables.entry(table.clone()).or_default();
+                Ok(table)
+            }
+        }
+    }
+}
+
+impl Database {
+    /// Create a database whose chunks are only kept in memory
+    ///
+    /// Chunks are stored in a concurrent map instead of LMDB, behind the same chunk methods
+    /// (get, insert, update, batch, exists, delete), so tests and benchmarks exercise the same
+    /// logic without creating a world on disk. Everything is lost once it is dropped.
+    /// Features working on the LMDB files (entities, players, stats, backups, compaction...)
+    /// fail with [`Error::DatabaseError`].
+    pub fn new_in_memory() -> Database {
+        // Unused, but the LMDB only features still need a pool to fail from
+        start_threadpool();
+
+        let config = DatabaseConfig::default();
+        let store = ChunkStore::Memory(Arc::new(MemoryStore::default()));
+        let tables = Arc::new(DimensionTables::default());
+        let metrics = Arc::new(DbMetrics::default());
+        let dirty = Arc::new(DirtyChunks::new(
+            store.clone(),
+            tables.clone(),
+            config.compression,
+            None,
+            metrics.clone(),
+        ));
+        let cache_counters = Arc::new(CacheCounters::default());
+        let cache = build_cache(&config, cache_counters.clone(), Some(dirty.clone()));
+
+        Database {
+            db: SharedEnv::detached(),
+            store,
+            cache: Arc::new(cache),
+            cache_counters,
+            metrics,
+            disk_guard: DiskGuard::new(0),
+            compression: config.compression,
+            tables,
+            write_behind: None,
+            dirty,
+            entities: EntityCache::default(),
+            scan_batch_size: config.scan_batch_size,
+            path: PathBuf::new(),
+            read_only: false,
+        }
+    }
+
+    /// Whether the database was created with [`Database::new_in_memory`]
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self.store, ChunkStore::Memory(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::test_chunk;
+    use crate::database::Database;
+    use crate::world::dimension::{ChunkPos, Dimension};
+
+    #[tokio::test]
+    async fn in_memory_chunks_outlive_the_cache() {
+        let database = Database::new_in_memory();
+        database.insert_chunk(test_chunk(1, 2)).await.unwrap();
+        database.update_chunk(test_chunk(3, 4)).await.unwrap();
+        database.flush().await.unwrap();
+        database.cache.invalidate_all();
+
+        let chunks = database
+            .batch_get(&[ChunkPos::overworld(1, 2), ChunkPos::overworld(3, 4)])
+            .await
+            .unwrap();
+        assert!(chunks.iter().all(Option::is_some));
+        assert!(!database
+            .chunk_exists(&ChunkPos::new(1, 2, Dimension::Nether))
+            .await
+            .unwrap());
+        // Nothing on disk to report on
+        assert!(database.stats().await.is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use heed::{CompactionOption, MdbError};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+use super::LMDB_READER_SYNC;
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// `ENOSPC`, "No space left on device" on both Linux and macOS
+const ENOSPC: i32 = 28;
+
+/// Result of [`Database::backup_to`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// File the backup was written to
+    pub path: PathBuf,
+    /// Size of the backup
+    pub bytes: u64,
+    /// Time spent copying the database
+    pub duration: Duration,
+}
+
+fn is_disk_full(e: &Error) -> bool {
+    match e {
+        Error::Io(e) | Error::LmdbError(heed::Error::Io(e)) => e.raw_os_error() == Some(ENOSPC),
+        Error::LmdbError(heed::Error::Mdb(MdbError::Other(code))) => *code == ENOSPC,
+        _ => false,
+    }
+}
+
+/// Timestamp of a backup created by [`Database::backup_rotate`], parsed from its file name
+fn backup_timestamp(name: &str) -> Option<u128> {
+    name.strip_prefix("backup-")?
+        .strip_suffix(".mdb")?
+        .parse()
+        .ok()
+}
+
+impl Database {
+    /// Write a compacted copy of the database to `path`
+    ///
+    /// The copy runs in a read transaction on a blocking task, so chunk reads and writes keep
+    /// going while it runs. Only map resizes wait for it to finish. Chunks queued by the
+    /// write-behind mode are committed first. Fails if `path` already exists, and removes the
+    /// partial file if the copy fails, e.g. when the disk is full.
+    pub async fn backup_to(&self, path: &Path) -> Result<BackupReport, Error> {
+        if fs::try_exists(path).await? {
+            return Err(Error::DatabaseError(format!(
+                "Backup target {} already exists",
+                path.display()
+            )));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Queued chunks would be missing from the copy otherwise
+        self.flush().await?;
+
+        let db = self.db.clone();
+        let target = path.to_path_buf();
+        let start = Instant::now();
+        let res = tokio::task::spawn_blocking(move || {
+            // The environment can't be resized while the copy holds its read transaction
+            let _read_lock = LMDB_READER_SYNC.read().expect(
+                "Database RWLock has been poisoned. A thread should have crashed somewhere.",
+            );
+            let file = db.get()?.copy_to_file(&target, CompactionOption::Enabled)?;
+            file.sync_all()?;
+            Ok::<_, Error>(file.metadata()?.len())
+        })
+        .await?;
+
+        match res {
+            Ok(bytes) => {
+                let report = BackupReport {
+                    path: path.to_path_buf(),
+                    bytes,
+                    duration: start.elapsed(),
+                };
+                info!(
+                    "Backed up the database to {} ({} bytes in {:?})",
+                    path.display(),
+                    report.bytes,
+                    report.duration
+                );
+                Ok(report)
+            }
+            Err(e) => {
+                // Don't leave a truncated copy behind that could be mistaken for a backup
+                if let Err(remove_err) = fs::remove_file(path).await {
+                    if remove_err.kind() != ErrorKind::NotFound {
+                        warn!(
+                            "Unable to remove incomplete backup {}: {}",
+                            path.display(),
+                            remove_err
+                        );
+                    }
+                }
+                if is_disk_full(&e) {
+                    Err(Error::DatabaseError(format!(
+                        "Not enough disk space to back up the database to {}",
+                        path.display()
+                    )))
+                } else {
+                    Err(Error::DatabaseError(format!(
+                        "Backup to {} failed: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Back up the database into a new timestamped file in `dir`, then delete the oldest
+    /// backups of `dir` so only the `keep` most recent ones remain
+    ///
+    /// Nothing is pruned if the backup fails.
+    pub async fn backup_rotate(&self, dir: &Path, keep: usize) -> Result<BackupReport, Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::Generic(e.to_string()))?
+            .as_millis();
+        let report = self
+            .backup_to(&dir.join(format!("backup-{timestamp}.mdb")))
+            .await?;
+
+        let mut backups = Vec::new();
+        let mut entries = fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            if let Some(timestamp) = name.to_str().and_then(backup_timestamp) {
+                backups.push((timestamp, entry.path()));
+            }
+        }
+        backups.sort_unstable();
+
+        let excess = backups.len().saturating_sub(keep.max(1));
+        for (_, path) in backups.into_iter().take(excess) {
+            debug!("Pruning old backup {}", path.display());
+            fs::remove_file(&path).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Directory of the scheduled backups
+    ///
+    /// Relative paths are resolved against the directory holding the world folder, and each
+    /// world gets its own subfolder.
+    pub fn backup_dir(&self, configured: &str) -> PathBuf {
+        let world = self.path.file_name().unwrap_or_default();
+        self.path
+            .parent()
+            .unwrap_or(&self.path)
+            .join(configured)
+            .join(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::backup_timestamp;
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::database::keys::chunk_key;
+    use crate::database::open_database;
+    use crate::utils::config::Database as DatabaseConfig;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn backup_can_be_reopened() {
+        let database = test_database().await;
+        let chunk = test_chunk(5, -7);
+        let key = chunk_key("overworld", 5, -7);
+        let data = ChunkCodec::compress_data(chunk, database.compression())
+            .await
+            .unwrap();
+        database
+            .batch_insert(vec![SerializedChunk::new(key, data)])
+            .await
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        let report = database.backup_to(&dir.join("data.mdb")).await.unwrap();
+        assert!(report.bytes > 0);
+        assert!(database.backup_to(&report.path).await.is_err());
+
+        let restored = open_database(dir, &DatabaseConfig::default())
+            .await
+            .unwrap();
+        let chunk = restored
+            .get_chunk(&ChunkPos::overworld(5, -7))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((chunk.x_pos, chunk.z_pos), (5, -7));
+    }
+
+    #[tokio::test]
+    async fn backup_rotate_keeps_the_latest() {
+        let database = test_database().await;
+        let dir = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+
+        let mut reports = Vec::new();
+        for _ in 0..3 {
+            reports.push(database.backup_rotate(&dir, 2).await.unwrap());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let mut remaining = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        remaining.sort_by_key(|path| backup_timestamp(path.file_name().unwrap().to_str().unwrap()));
+        assert_eq!(
+            remaining,
+            vec![reports[1].path.clone(), reports[2].path.clone()]
+        );
+    }
+}
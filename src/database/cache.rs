@@ -0,0 +1,170 @@
+use deepsize::DeepSizeOf;
+use futures::FutureExt;
+use moka::future::Cache;
+use moka::notification::RemovalCause;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, trace};
+
+use crate::database::dirty::DirtyChunks;
+use crate::database::Database;
+use crate::utils::config::Database as DatabaseConfig;
+use crate::world::chunk_format::Chunk;
+
+/// How the capacity of the chunk cache is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheMode {
+    /// Capacity is a number of chunks
+    Entries,
+    /// Capacity is the memory used by the cached chunks, in KB
+    #[default]
+    Weighted,
+}
+
+/// Snapshot of the chunk cache counters
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries removed because of capacity or expiry, explicit removals are not counted
+    pub evictions: u64,
+    /// Approximate number of cached chunks
+    pub entry_count: u64,
+    /// Approximate total weight of the cached chunks (bytes in weighted mode, chunks otherwise)
+    pub weighted_size: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheCounters {
+    pub(crate) fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Build the chunk cache
+///
+/// The capacity is in KB for [`CacheMode::Weighted`] and in chunks for [`CacheMode::Entries`]
+/// Dirty chunks are persisted before their removal completes, unless they were replaced.
+pub(crate) fn build_cache(
+    config: &DatabaseConfig,
+    counters: Arc<CacheCounters>,
+    dirty: Option<Arc<DirtyChunks>>,
+) -> Cache<u128, Arc<Chunk>> {
+    let builder = Cache::builder()
+        .async_eviction_listener(move |key, value: Arc<Chunk>, cause: RemovalCause| {
+            let counters = counters.clone();
+            let dirty = dirty.clone();
+            async move {
+                if cause.was_evicted() {
+                    counters.evictions.fetch_add(1, Ordering::Relaxed);
+                    trace!(
+                        "Evicting chunk from cache: {}, {}",
+                        value.x_pos,
+                        value.z_pos
+                    );
+                }
+                // A replaced chunk is still cached in its newer state
+                let Some(dirty) = dirty.filter(|_| cause != RemovalCause::Replaced) else {
+                    return;
+                };
+                if dirty.take(*key) {
+                    if let Err(e) = dirty.persist(vec![(*key, value)]).await {
+                        error!("Unable to persist evicted chunk {:X}: {}", *key, e);
+                    }
+                }
+            }
+            .boxed()
+        })
+        .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu());
+    // Chunks nobody looked at for a while are most likely out of every player's view
+    let builder = match config.cache_idle_timeout {
+        0 => builder,
+        seconds => builder.time_to_idle(Duration::from_secs(seconds)),
+    };
+
+    let capacity = config.cache_size as u64;
+    match config.cache_mode {
+        CacheMode::Entries => builder.max_capacity(capacity).build(),
+        CacheMode::Weighted => builder
+            .weigher(|_, v: &Arc<Chunk>| v.deep_size_of().try_into().unwrap_or(u32::MAX))
+            .max_capacity(capacity * 1024)
+            .build(),
+    }
+}
+
+impl Database {
+    /// Current chunk cache statistics
+    ///
+    /// Only reads counters, so this is cheap enough to be polled every tick.
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache_counters.hits.load(Ordering::Relaxed),
+            misses: self.cache_counters.misses.load(Ordering::Relaxed),
+            evictions: self.cache_counters.evictions.load(Ordering::Relaxed),
+            entry_count: self.cache.entry_count(),
+            weighted_size: self.cache.weighted_size(),
+        }
+    }
+
+    /// Drop every cached chunk whose key isn't in `watched`
+    ///
+    /// `watched` holds the keys of the chunks within the view distance of any player. Nothing
+    /// is lost: dirty chunks are persisted before their eviction completes, and chunks queued by
+    /// the write-behind mode are still served from its queue. Returns the number of dropped chunks.
+    pub async fn evict_unwatched(&self, watched: &HashSet<u128>) -> usize {
+        let keys = self
+            .cache
+            .iter()
+            .filter(|(key, _)| !watched.contains(key))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in keys.iter() {
+            self.cache.invalidate(key).await;
+        }
+        trace!("Evicted {} unwatched chunks from cache", keys.len());
+        keys.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::world::dimension::ChunkPos;
+
+    #[tokio::test]
+    async fn evict_unwatched_keeps_watched_chunks() {
+        let database = test_database().await;
+        for x in 0..3 {
+            database
+                .cache
+                .insert(ChunkPos::overworld(x, 0).key(), Arc::new(test_chunk(x, 0)))
+                .await;
+        }
+
+        let watched = HashSet::from([ChunkPos::overworld(1, 0).key()]);
+        assert_eq!(database.evict_unwatched(&watched).await, 2);
+        assert!(database
+            .cache
+            .contains_key(&ChunkPos::overworld(1, 0).key()));
+        assert!(!database
+            .cache
+            .contains_key(&ChunkPos::overworld(0, 0).key()));
+    }
+}
@@ -1,137 +1,277 @@
-use byteorder::LE;
-use heed::types::Bytes;
-use heed::{types::U64, Env};
+use byteorder::BE;
+use heed::types::{Bytes, U64};
+use heed::Env;
 use moka::future::Cache;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::runtime::Handle;
-use tracing::{trace, warn};
+use tracing::{instrument, trace, trace_span, warn};
 
-use super::spawn_blocking_db;
-use crate::database::encoding::ZstdCodec;
+use crate::database::backend::ChunkStore;
+use crate::database::dimensions::{ChunkTable, DimensionTables};
+use crate::database::encoding::{ChunkCodec, Compression};
 use crate::world::importing::SerializedChunk;
 use crate::{
-    database::Database, utils::error::Error, utils::hash::hash, world::chunk_format::Chunk,
+    database::keys::{chunk_key, chunk_key_position, key_dimension_hash, table_key},
+    database::Database,
+    utils::error::Error,
+    world::chunk_format::Chunk,
+    world::dimension::{ChunkPos, Dimension},
 };
 
 impl Database {
     // Close the database
     pub fn close(self) {
-        let token = self.db.prepare_for_closing();
-        token.wait();
+        let env = self.db.take();
+        drop(self);
+        if let Some(env) = env {
+            env.prepare_for_closing().wait();
+        }
+    }
+
+    /// Compression algorithm used by the values stored in this database
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Open the chunk table of a dimension
+    ///
+    /// Tables are created when their dimension is registered, a missing one means the registry
+    /// and the database disagree.
+    pub(super) fn open_chunk_table(
+        db: &Env,
+        tx: &heed::RoTxn,
+        table: &str,
+    ) -> Result<ChunkTable, Error> {
+        db.open_database::<U64<BE>, Bytes>(tx, Some(table))?
+            .ok_or_else(|| Error::TableMissing(table.to_string()))
+    }
+
+    /// Dimension of a chunk, which every chunk needs to be keyed
+    fn chunk_dimension(chunk: &Chunk) -> Result<&str, Error> {
+        chunk.dimension.as_deref().ok_or_else(|| {
+            Error::InvalidChunk(
+                chunk.x_pos,
+                chunk.z_pos,
+                "Chunk has no dimension".to_string(),
+            )
+        })
     }
 
     /// Fetch chunk from database
-    async fn get_chunk_from_database(db: &Env, key: &u64) -> Result<Option<Chunk>, heed::Error> {
-        let data = {
-            // Initialize read transaction and open chunks table
-            let ro_tx = db.read_txn()?;
-            let database = db
-                .open_database::<U64<LE>, Bytes>(&ro_tx, Some("chunks"))?
-                .expect("No table \"chunks\" found. The database should have been initialized");
-
-            // Attempt to fetch chunk from table
-            let data = database.get(&ro_tx, key)?;
-
-            data.map(|data| data.to_vec())
-        };
+    async fn get_chunk_from_database(
+        store: &ChunkStore,
+        read_only: bool,
+        table: &str,
+        key: &u128,
+        compression: Compression,
+    ) -> Result<Option<Chunk>, Error> {
+        let data = store.get(table, *key).await?;
 
-        // Now, proceed with the async operation without holding `ro_tx`
+        // The read transaction is already closed, decoding may write the chunk back
         if let Some(data) = data {
-            let chunk = ZstdCodec::decompress_data::<Chunk>(data.as_slice())
-                .await
-                .expect("Failed to decompress chunk");
-            Ok(Some(chunk))
+            Self::decode_chunk(store, read_only, table, *key, data.as_slice(), compression).await
         } else {
             Ok(None)
         }
     }
 
-    /// Insert a single chunk into database
-    fn insert_chunk_into_database(db: &Env, chunk: &Chunk) -> Result<(), heed::Error> {
-        // Initialize write transaction and open chunks table
-        let mut rw_tx = db.write_txn()?;
-        let database = db
-            .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-            .expect("No table \"chunks\" found. The database should have been initialized");
+    /// Fetch multiple raw chunks from database using a single read transaction
+    /// Keys without a table belong to a dimension without any chunk
+    pub(super) fn get_chunks_from_database(
+        db: &Env,
+        keys: &[(u128, Option<Arc<str>>)],
+    ) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        // Initialize read transaction, tables are opened as they are needed
+        let ro_tx = db.read_txn()?;
+        let mut tables: HashMap<&str, ChunkTable> = HashMap::new();
 
-        // Calculate key
-        let key = hash((chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos));
+        // Resolve every key within the same transaction, keeping the input ordering
+        keys.iter()
+            .map(|(key, table)| {
+                let Some(table) = table else {
+                    return Ok(None);
+                };
+                let database = match tables.get(&**table) {
+                    Some(database) => *database,
+                    None => {
+                        let database = Self::open_chunk_table(db, &ro_tx, table)?;
+                        tables.insert(&**table, database);
+                        database
+                    }
+                };
+                Ok(database
+                    .get(&ro_tx, &table_key(*key))?
+                    .map(|data| data.to_vec()))
+            })
+            .collect()
+    }
 
-        let chunk = chunk.clone();
-        let chunk = Handle::current().block_on(async {
-            ZstdCodec::compress_data(chunk)
-                .await
-                .expect("Failed to compress chunk")
-        });
+    /// Fetch every raw chunk of a dimension table using a single read transaction
+    pub(super) fn get_table_from_database(
+        db: &Env,
+        table: &str,
+    ) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        // Initialize read transaction and open the dimension table
+        let ro_tx = db.read_txn()?;
+        let database = Self::open_chunk_table(db, &ro_tx, table)?;
+
+        let entries = database
+            .iter(&ro_tx)?
+            .map(|entry| entry.map(|(key, data)| (key, data.to_vec())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Insert a single encoded chunk into database
+    pub(super) fn insert_chunk_into_database(
+        db: &Env,
+        table: &str,
+        key: u128,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        // Initialize write transaction and open the dimension table
+        let mut rw_tx = db.write_txn()?;
+        let database = Self::open_chunk_table(db, &rw_tx, table)?;
 
         // Insert chunk
-        let res = database.put(&mut rw_tx, &key, chunk.as_slice());
+        database.put(&mut rw_tx, &table_key(key), data)?;
         rw_tx.commit()?;
-
-        res
+        Ok(())
     }
 
     /// Insert multiple chunks into database
     /// TODO: Find better name/disambiguation
-    fn insert_chunks_into_database(
+    /// The dimension of every chunk must have been registered beforehand
+    pub(super) fn insert_chunks_into_database(
         db: &Env,
+        tables: &DimensionTables,
         chunks: &[SerializedChunk],
-    ) -> Result<(), heed::Error> {
-        // Initialize write transaction and open chunks table
+    ) -> Result<(), Error> {
+        // Initialize write transaction, tables are opened as they are needed
         let mut rw_tx = db.write_txn()?;
-        let database = db
-            .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-            .expect("No table \"chunks\" found. The database should have been initialized");
+        let mut opened: HashMap<Arc<str>, ChunkTable> = HashMap::new();
 
         // Update page
         for chunk in chunks {
-            // Calculate key
-            // let key = hash((chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos));
+            let table = tables.table_of_key(chunk.hash()).ok_or_else(|| {
+                Error::TableMissing(format!("dimension of chunk {:X}", chunk.hash()))
+            })?;
+            let database = match opened.get(&table) {
+                Some(database) => *database,
+                None => {
+                    let database = Self::open_chunk_table(db, &rw_tx, &table)?;
+                    opened.insert(table, database);
+                    database
+                }
+            };
 
             // Insert chunk
-            database.put(&mut rw_tx, &chunk.hash(), chunk.data())?;
+            database.put(&mut rw_tx, &table_key(chunk.hash()), chunk.data())?;
         }
         // Commit changes
         rw_tx.commit()?;
         Ok(())
     }
 
+    /// Delete multiple chunks from database within a single write transaction
+    /// Returns for each key whether it was present in its table
+    pub(super) fn delete_chunks_from_database(
+        db: &Env,
+        keys: &[(u128, Option<Arc<str>>)],
+    ) -> Result<Vec<bool>, Error> {
+        // Initialize write transaction
+        let mut rw_tx = db.write_txn()?;
+
+        // Delete chunks
+        let deleted = keys
+            .iter()
+            .map(|(key, table)| match table {
+                Some(table) => Ok(Self::open_chunk_table(db, &rw_tx, table)?
+                    .delete(&mut rw_tx, &table_key(*key))?),
+                None => Ok(false),
+            })
+            .collect::<Result<Vec<bool>, Error>>()?;
+
+        // Commit changes
+        rw_tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Fetch a chunk queued by the write-behind mode but not committed yet
+    async fn get_pending_chunk(&self, key: u128) -> Result<Option<Chunk>, Error> {
+        let Some(data) = self
+            .write_behind
+            .as_ref()
+            .and_then(|write_behind| write_behind.pending(key))
+        else {
+            return Ok(None);
+        };
+        let chunk = ChunkCodec::decompress_data::<Chunk>(data.as_slice(), self.compression).await?;
+        Ok(Some(chunk))
+    }
+
+    /// Persist a chunk, either right away or through the write-behind queue
+    async fn persist_chunk(&self, key: u128, value: &Arc<Chunk>) -> Result<(), Error> {
+        let table = self.dimension_table(Self::chunk_dimension(value)?).await?;
+        let (data, uncompressed) =
+            ChunkCodec::compress_data_measured(value.clone(), self.compression).await?;
+        self.metrics.compressed(uncompressed, data.len());
+        self.metrics.written(data.len());
+        if let Some(write_behind) = &self.write_behind {
+            return write_behind.queue(SerializedChunk::new(key, data)).await;
+        }
+
+        self.store.put(table, key, data).await
+    }
+
     #[allow(dead_code)]
-    async fn load_into_cache(&self, key: u64) -> Result<(), Error> {
-        Database::load_into_cache_standalone(self.db.clone(), self.cache.clone(), key).await
+    async fn load_into_cache(&self, key: u128) -> Result<(), Error> {
+        Database::load_into_cache_standalone(
+            self.store.clone(),
+            self.cache.clone(),
+            self.tables.clone(),
+            key,
+            self.compression,
+            self.read_only,
+        )
+        .await
     }
 
     async fn load_into_cache_standalone(
-        db: Env,
-        cache: Arc<Cache<u64, Chunk>>,
-        key: u64,
+        store: ChunkStore,
+        cache: Arc<Cache<u128, Arc<Chunk>>>,
+        tables: Arc<DimensionTables>,
+        key: u128,
+        compression: Compression,
+        read_only: bool,
     ) -> Result<(), Error> {
-        // let tsk_db = db.clone();
-
-        let db = db.clone();
         tokio::task::spawn(async move {
             // Check cache
             if cache.contains_key(&key) {
                 trace!("Chunk already exists in cache: {:X}", key);
             }
             // If not in cache then search in database
-            else if let Ok(chunk) = Self::get_chunk_from_database(&db, &key).await
-            /*spawn_blocking_db(tsk_db, move || Self::get_chunk_from_database(&db, &key))
-            .await
-            .unwrap()*/
-            {
-                if let Some(chunk) = chunk {
-                    cache.insert(key, chunk).await;
-                } else {
-                    warn!(
+            else if let Some(table) = tables.table_of_key(key) {
+                match Self::get_chunk_from_database(&store, read_only, &table, &key, compression)
+                    .await
+                {
+                    Ok(Some(chunk)) => {
+                        cache.insert(key, Arc::new(chunk)).await;
+                    }
+                    Ok(None) => warn!(
                         "Chunk does not exist in db, can't load into cache: {:X}",
                         key,
-                    );
+                    ),
+                    Err(e) => warn!("Error getting chunk {:X}: {}", key, e),
                 }
             }
-            // The chunk don't exist
+            // The dimension doesn't have any chunk
             else {
-                warn!("Error getting chunk: {:X}", key,);
+                warn!(
+                    "Chunk does not exist in db, can't load into cache: {:X}",
+                    key,
+                );
             }
         })
         .await?;
@@ -155,20 +295,18 @@ impl Database {
     /// }
     ///
     /// ```
+    #[instrument(level = "trace", skip_all)]
     pub async fn insert_chunk(&self, value: Chunk) -> Result<(), Error> {
+        let _timer = self.metrics.insert.time();
+        self.check_writable()?;
+        self.disk_guard.check()?;
         // Calculate key of this chunk
         // WARNING: This key wasn't supposed to include value.dimension in the tuple, but it was different from the key used in persistent database most likely a bug.
-        let key = hash((value.dimension.as_ref().unwrap(), value.x_pos, value.z_pos));
+        let key = chunk_key(Self::chunk_dimension(&value)?, value.x_pos, value.z_pos);
 
         // Insert chunk into persistent database
-        let chunk = value.clone();
-        let db = self.db.clone();
-        let tsk_db = self.db.clone();
-        spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunk_into_database(&db, &chunk)
-        })
-        .await
-        .unwrap()?;
+        let value = Arc::new(value);
+        self.persist_chunk(key, &value).await?;
 
         // Insert into cache
         self.cache.insert(key, value).await;
@@ -177,90 +315,222 @@ impl Database {
 
     /// Get a chunk from the database <br>
     /// This will also insert the chunk into the cache <br>
-    /// If the chunk does not exist, it will return None
+    /// If the chunk does not exist, it will return None <br>
+    /// The chunk is shared with the cache instead of being copied, see [`Database::update_chunk`]
+    /// to modify it
     /// # Arguments
-    /// * `x` - The x position of the chunk
-    /// * `z` - The z position of the chunk
-    /// * `dimension` - The dimension of the chunk
+    /// * `pos` - The position of the chunk
     /// # Returns
-    /// * `Result<Option<Chunk>, Error>` - Ok if the chunk was found, Err if the chunk does not exist
+    /// * `Result<Option<Arc<Chunk>>, Error>` - Ok if the chunk was found, Err if the chunk does not exist
     /// # Example
     /// ```ignore
+    /// use std::sync::Arc;
     /// use crate::world::chunkformat::Chunk;
+    /// use crate::world::dimension::ChunkPos;
     /// use crate::database::Database;
     /// use crate::utils::error::Error;
     ///
-    /// async fn get_chunk(database: Database, x: i32, z: i32, dimension: String) -> Result<Option<Chunk>, Error> {
-    ///   database.get_chunk(x, z, dimension).await
+    /// async fn get_chunk(database: Database, pos: ChunkPos) -> Result<Option<Arc<Chunk>>, Error> {
+    ///   database.get_chunk(&pos).await
     /// }
     ///
     /// ```
-    pub async fn get_chunk(
-        &self,
-        x: i32,
-        z: i32,
-        dimension: String,
-    ) -> Result<Option<Chunk>, Error> {
-        // Calculate key of this chunk and clone database pointer
-        let key = hash((dimension, x, z));
-        let db = self.db.clone();
-
-        let res = Self::get_chunk_from_database(&db, &key).await?;
+    #[instrument(level = "trace", skip_all)]
+    pub async fn get_chunk(&self, pos: &ChunkPos) -> Result<Option<Arc<Chunk>>, Error> {
+        let _timer = self.metrics.get.time();
+        // Calculate key of this chunk
+        let key = pos.key();
 
-        Ok(res)
+        // First check cache, only the pointer is cloned
+        if let Some(chunk) = self.cache.get(&key).await {
+            self.cache_counters.hit();
+            return Ok(Some(chunk));
+        }
+        self.cache_counters.miss();
 
-        /* // First check cache
-        if self.cache.contains_key(&key) {
-            Ok(self.cache.get(&key).await)
+        // Chunks waiting in the write-behind queue are newer than the persisted ones
+        if let Some(chunk) = self.get_pending_chunk(key).await? {
+            return Ok(Some(Arc::new(chunk)));
         }
-        // Attempt to get chunk from persistent database
-        else if let Some(chunk) = Self::get_chunk_from_database(&db, &key).await?
-            /*
-            spawn_blocking_db(tsk_db, move || Self::get_chunk_from_database(&db, &key))
+
+        // No table means no chunk was ever saved in this dimension
+        let Some(table) = self.tables.table_of_key(key) else {
+            return Ok(None);
+        };
+        let Some(chunk) = Self::get_chunk_from_database(
+            &self.store,
+            self.read_only,
+            &table,
+            &key,
+            self.compression,
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        // Never replace a chunk cached in the meantime, it may be newer
+        Ok(Some(
+            self.cache
+                .entry(key)
+                .or_insert(Arc::new(chunk))
                 .await
-                .unwrap()?*/
-        {
-            // self.cache.insert(key, chunk.clone()).await;
-            Ok(Some(chunk))
+                .into_value(),
+        ))
+    }
+
+    /// Get multiple chunks from the database <br>
+    /// Cached chunks are served directly from the cache, the remaining ones are fetched
+    /// from the persistent database within a single read transaction and loaded into the cache <br>
+    /// The results are returned in the same order as the requested coordinates
+    /// # Arguments
+    /// * `positions` - The positions of the chunks
+    /// # Returns
+    /// * `Result<Vec<Option<Arc<Chunk>>>, Error>` - One entry per requested chunk, None if the chunk does not exist
+    /// # Example
+    /// ```ignore
+    /// use std::sync::Arc;
+    /// use crate::world::chunkformat::Chunk;
+    /// use crate::world::dimension::ChunkPos;
+    /// use crate::database::Database;
+    /// use crate::utils::error::Error;
+    ///
+    /// async fn batch_get(database: Database, positions: Vec<ChunkPos>) -> Result<Vec<Option<Arc<Chunk>>>, Error> {
+    ///   database.batch_get(&positions).await
+    /// }
+    ///
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub async fn batch_get(
+        &self,
+        positions: &[ChunkPos],
+    ) -> Result<Vec<Option<Arc<Chunk>>>, Error> {
+        let _timer = self.metrics.batch_get.time();
+        // Calculate all keys
+        let keys = positions.iter().map(ChunkPos::key).collect::<Vec<u128>>();
+
+        // First serve what we can from the cache
+        let mut results = Vec::with_capacity(keys.len());
+        let mut missing = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            let mut cached = self.cache.get(key).await;
+            if cached.is_none() {
+                self.cache_counters.miss();
+                cached = self.get_pending_chunk(*key).await?.map(Arc::new);
+                if cached.is_none() {
+                    missing.push((index, *key));
+                }
+            } else {
+                self.cache_counters.hit();
+            }
+            results.push(cached);
         }
-        // Chunk do not exist
-        else {
-            Ok(None)
-        }*/
+
+        if missing.is_empty() {
+            return Ok(results);
+        }
+
+        // Then fetch all the missing chunks from persistent database in one go
+        let missing_keys = missing
+            .iter()
+            .map(|(_, key)| (*key, self.tables.table_of_key(*key)))
+            .collect::<Vec<_>>();
+        let data = self.store.get_many(missing_keys.clone()).await?;
+
+        // Decompress them in parallel, the values were copied out of the transaction so they
+        // outlive it
+        let compression = self.compression;
+        let span = trace_span!("decompress_chunks", chunks = data.len());
+        let (data, decoded) = tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+            let decoded = data
+                .par_iter()
+                .map(|data| {
+                    data.as_ref()
+                        .map(|data| ChunkCodec::decompress_versioned::<Chunk>(data, compression))
+                })
+                .collect::<Vec<_>>();
+            (data, decoded)
+        })
+        .await?;
+
+        // Then load them into cache, in the requested order
+        let entries = missing.into_iter().zip(missing_keys).zip(data).zip(decoded);
+        for ((((index, key), (_, table)), data), decoded) in entries {
+            let (Some(data), Some(table), Some(decoded)) = (data, table, decoded) else {
+                continue;
+            };
+            let Some(chunk) = Self::handle_decoded_chunk(
+                &self.store,
+                self.read_only,
+                &table,
+                key,
+                data.as_slice(),
+                decoded,
+                self.compression,
+            )
+            .await?
+            else {
+                continue;
+            };
+            let chunk = Arc::new(chunk);
+            self.cache.insert(key, chunk.clone()).await;
+            results[index] = Some(chunk);
+        }
+
+        Ok(results)
     }
 
     /// Check if a chunk exists in the database
     /// # Arguments
-    /// * `x` - The x position of the chunk
-    /// * `z` - The z position of the chunk
-    /// * `dimension` - The dimension of the chunk
+    /// * `pos` - The position of the chunk
     /// # Returns
     ///
     /// * `Result<bool, Error>` - Ok if the chunk exists, Err if the chunk does not exist
     /// # Example
     /// ```ignore
+    /// use crate::world::dimension::ChunkPos;
     /// use crate::database::Database;
     /// use crate::utils::error::Error;
     ///
-    /// async fn chunk_exists(database: Database, x: i32, z: i32, dimension: String) -> Result<bool, Error> {
-    ///  database.chunk_exists(x, z, dimension).await
+    /// async fn chunk_exists(database: Database, pos: ChunkPos) -> Result<bool, Error> {
+    ///  database.chunk_exists(&pos).await
     /// }
     ///
     /// ```
-    pub async fn chunk_exists(&self, x: i32, z: i32, dimension: String) -> Result<bool, Error> {
-        // Calculate key and copy database pointer
-        let key = hash((dimension, x, z));
-        let db = self.db.clone();
+    #[instrument(level = "trace", skip_all)]
+    pub async fn chunk_exists(&self, pos: &ChunkPos) -> Result<bool, Error> {
+        let _timer = self.metrics.exists.time();
+        // Calculate key
+        let key = pos.key();
 
         // Check first cache
         if self.cache.contains_key(&key) {
+            self.cache_counters.hit();
             Ok(true)
         // Else check persistent database and load it into cache
         } else {
+            self.cache_counters.miss();
+            if let Some(write_behind) = &self.write_behind {
+                if write_behind.is_pending(key) {
+                    return Ok(true);
+                }
+            }
             /*let res = spawn_blocking_db(tsk_db, move || Self::get_chunk_from_database(&db, &key))
             .await
             .unwrap();*/
-            let Some(res) = Self::get_chunk_from_database(&db, &key).await? else {
+            let Some(table) = self.tables.table_of_key(key) else {
+                return Ok(false);
+            };
+            let Some(res) = Self::get_chunk_from_database(
+                &self.store,
+                self.read_only,
+                &table,
+                &key,
+                self.compression,
+            )
+            .await?
+            else {
                 return Ok(false);
             };
 
@@ -268,7 +538,7 @@ impl Database {
             // This has been replaced by directly loading the queried chunk into cache
 
             // Load chunk into cache
-            self.cache.insert(key, res.clone()).await;
+            self.cache.insert(key, Arc::new(res)).await;
             Ok(true)
 
             /* match res {
@@ -284,44 +554,164 @@ impl Database {
         }
     }
 
-    /// Update a chunk in the database <br>
-    /// This will also update the chunk in the cache <br>
-    /// If the chunk does not exist, it will return an error
+    /// Iterate over every chunk of a dimension <br>
+    /// Only the entries of this dimension are read from the database, and each chunk is only
+    /// decoded when the iterator reaches it <br>
+    /// Chunks queued by the write-behind mode are committed first so they are included
+    /// # Arguments
+    /// * `dimension` - The dimension to iterate over
+    /// # Returns
+    /// * `Result<impl Iterator<Item = Result<(ChunkPos, Chunk), Error>>, Error>` - The chunks and their position, ordered by key
+    /// # Example
+    /// ```ignore
+    /// use crate::database::Database;
+    /// use crate::world::dimension::Dimension;
+    /// use crate::utils::error::Error;
+    ///
+    /// async fn count_chunks(database: Database) -> Result<usize, Error> {
+    ///   Ok(database.iter_dimension(&Dimension::Overworld).await?.count())
+    /// }
+    ///
+    /// ```
+    pub async fn iter_dimension(
+        &self,
+        dimension: &Dimension,
+    ) -> Result<impl Iterator<Item = Result<(ChunkPos, Chunk), Error>>, Error> {
+        self.flush().await?;
+
+        let entries = match self.tables.check(dimension.name())? {
+            Some(table) => self.store.get_table(table).await?,
+            None => Vec::new(),
+        };
+
+        let compression = self.compression;
+        let dimension = dimension.clone();
+        Ok(entries.into_iter().map(move |(key, data)| {
+            let (x, z) = chunk_key_position(key as u128);
+            let chunk = ChunkCodec::decompress_data_sync::<Chunk>(data.as_slice(), compression)?;
+            Ok((ChunkPos::new(x, z, dimension.clone()), chunk))
+        }))
+    }
+
+    /// Update a chunk in the cache and flag it as dirty <br>
+    /// It is persisted by the next [`Database::save_dirty`] or [`Database::flush`], or when it is evicted <br>
+    /// Chunks returned by [`Database::get_chunk`] are shared with the cache and other readers, so
+    /// they are modified copy-on-write: take the chunk with [`Arc::unwrap_or_clone`] (or modify
+    /// it through [`Arc::make_mut`]), which only copies it if it is still shared, then pass the
+    /// new state here. Readers holding the previous state keep it unchanged.
     /// # Arguments
     /// * `value` - The chunk to update
     /// # Returns
     /// * `Result<(), Error>` - Ok if the chunk was updated, Err if the chunk does not exist
     /// # Example
     /// ```ignore
-    /// use crate::world::chunkformat::Chunk;
+    /// use std::sync::Arc;
+    /// use crate::world::dimension::ChunkPos;
     /// use crate::database::Database;
     /// use crate::utils::error::Error;
     ///
-    /// async fn update_chunk(database: Database, chunk: Chunk) -> Result<(), Error> {
+    /// async fn update_chunk(database: Database, pos: ChunkPos) -> Result<(), Error> {
+    ///   let Some(chunk) = database.get_chunk(&pos).await? else {
+    ///     return Ok(());
+    ///   };
+    ///   let mut chunk = Arc::unwrap_or_clone(chunk);
+    ///   chunk.last_update = Some(0);
     ///   database.update_chunk(chunk).await
     /// }
     ///
     /// ```
+    #[instrument(level = "trace", skip_all)]
     pub async fn update_chunk(&self, value: Chunk) -> Result<(), Error> {
+        let _timer = self.metrics.update.time();
+        self.check_writable()?;
         // Calculate key of this chunk
         // WARNING: This key wasn't supposed to include value.dimension in the tuple, but it was different from the key used in persistent database most likely a bug.
-        let key = hash((value.dimension.as_ref().unwrap(), value.x_pos, value.z_pos));
-
-        // Insert new chunk state into persistent database
-        let chunk = value.clone();
-        let db = self.db.clone();
-        let tsk_db = self.db.clone();
-        spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunk_into_database(&db, &chunk)
-        })
-        .await
-        .unwrap()?;
+        let dimension = Self::chunk_dimension(&value)?;
+        let key = chunk_key(dimension, value.x_pos, value.z_pos);
 
-        // Insert new chunk state into cache
-        self.cache.insert(key, value).await;
+        // Make sure the chunk has a table to be saved to
+        self.dimension_table(dimension).await?;
+
+        // Insert new chunk state into cache, it is persisted with the other dirty chunks
+        let value = Arc::new(value);
+        self.cache.insert(key, value.clone()).await;
+        self.dirty.mark(key);
+
+        // Evicted before being flagged, so the eviction didn't persist it
+        if !self.cache.contains_key(&key) && self.dirty.take(key) {
+            self.dirty.persist(vec![(key, value)]).await?;
+        }
         Ok(())
     }
 
+    /// Delete a chunk from the database <br>
+    /// This will also remove the chunk from the cache
+    /// # Arguments
+    /// * `pos` - The position of the chunk
+    /// # Returns
+    /// * `Result<bool, Error>` - Ok(true) if the chunk existed, Ok(false) if there was nothing to delete
+    /// # Example
+    /// ```ignore
+    /// use crate::world::dimension::ChunkPos;
+    /// use crate::database::Database;
+    /// use crate::utils::error::Error;
+    ///
+    /// async fn delete_chunk(database: Database, pos: ChunkPos) -> Result<bool, Error> {
+    ///   database.delete_chunk(&pos).await
+    /// }
+    ///
+    /// ```
+    pub async fn delete_chunk(&self, pos: &ChunkPos) -> Result<bool, Error> {
+        let deleted = self.delete_chunks(std::slice::from_ref(pos)).await?;
+        Ok(deleted[0])
+    }
+
+    /// Batch delete chunks from the database <br>
+    /// This will also remove the chunks from the cache
+    /// # Arguments
+    /// * `positions` - The positions of the chunks
+    /// # Returns
+    /// * `Result<Vec<bool>, Error>` - For each requested chunk, whether it existed
+    /// # Example
+    /// ```ignore
+    /// use crate::world::dimension::ChunkPos;
+    /// use crate::database::Database;
+    /// use crate::utils::error::Error;
+    ///
+    /// async fn delete_chunks(database: Database, positions: Vec<ChunkPos>) -> Result<Vec<bool>, Error> {
+    ///   database.delete_chunks(&positions).await
+    /// }
+    ///
+    /// ```
+    #[instrument(level = "trace", skip_all)]
+    pub async fn delete_chunks(&self, positions: &[ChunkPos]) -> Result<Vec<bool>, Error> {
+        let _timer = self.metrics.delete.time();
+        self.check_writable()?;
+        // Calculate all keys
+        let keys = positions.iter().map(ChunkPos::key).collect::<Vec<u128>>();
+
+        // Commit queued writes first, so they can't bring the chunks back afterwards
+        self.flush().await?;
+
+        // Delete from persistent database
+        let task_keys = keys
+            .iter()
+            .map(|key| (*key, self.tables.table_of_key(*key)))
+            .collect::<Vec<_>>();
+        let persisted = self.store.delete_many(task_keys).await?;
+
+        // Invalidate cache entries
+        let mut deleted = Vec::with_capacity(keys.len());
+        for (key, persisted) in keys.iter().zip(persisted) {
+            // Unflag first, the eviction would persist the chunk again otherwise
+            self.dirty.take(*key);
+            let cached = self.cache.remove(key).await.is_some();
+            deleted.push(persisted || cached);
+        }
+
+        Ok(deleted)
+    }
+
     /// Batch insert chunks into the database <br>
     /// This will also insert the chunks into the cache <br>
     /// If any of the chunks already exist, it will return an error
@@ -340,46 +730,72 @@ impl Database {
     /// }
     ///
     /// ```
+    #[instrument(level = "trace", skip_all)]
     pub async fn batch_insert(&self, values: Vec<SerializedChunk>) -> Result<(), Error> {
-        // Clone database pointer
-        let db = self.db.clone();
-        let tsk_db = self.db.clone();
+        let _timer = self.metrics.batch_insert.time();
+        self.check_writable()?;
+        self.disk_guard.check()?;
 
-        // Calculate all keys
-        /*      let keys = values
-                    .iter()
-                    .map(|v| hash((v.dimension.as_ref().unwrap_or_else(|| panic!("Invalid chunk @ ({},{})", v.x_pos, v.z_pos)), v.x_pos, v.z_pos)))
-                    .collect::<Vec<u64>>();
-        */
-        // let keys = values.iter().map(|v| v.hash()).collect::<Vec<u64>>();
-
-        // WARNING: The previous logic was to first insert in database and then insert in cache using load_into_cache fn.
-        // This has been modified to avoid having to query database while we already have the data available.
-        // First insert into cache
-
-        // TODO: Renable cache. Currently disabled because we only get serialized bytes with the hash.
-        // to save in the database
-        /*for (chunk) in values.iter() {
-            let cache = self.cache.clone();
-            let db = self.db.clone();
-            let key = chunk.hash();
-            let chunk = chunk.data().clone();
-            tokio::spawn(async move {
-                cache.insert(key, chunk).await;
-                if let Err(e) = Database::load_into_cache_standalone(db, cache, key).await {
-                    warn!("Error inserting chunk into database: {:?}", e);
-                }
-            });
+        // Make sure every chunk has a table to go to
+        let mut dimensions = HashSet::new();
+        for chunk in values.iter() {
+            if dimensions.insert(key_dimension_hash(chunk.hash())) {
+                self.table_for_key(chunk.hash()).await?;
+            }
         }
-        */
-        // Then insert into persistent database
-        spawn_blocking_db(tsk_db, move || {
-            Self::insert_chunks_into_database(&db, &values)
+
+        // Commit queued writes first, so older chunk states can't override these afterwards
+        self.flush().await?;
+
+        self.metrics
+            .written(values.iter().map(|value| value.data().len()).sum());
+
+        // Persist in a single transaction while the chunks are decoded into the cache, since we
+        // already have the data there is no need to read it back from the database
+        let values = Arc::new(values);
+        let persist = self.store.put_many(self.tables.clone(), values.clone());
+        let (persisted, cached) = tokio::join!(persist, self.cache_serialized(values));
+
+        if let Err(e) = persisted {
+            // Don't serve chunks that never reached the database
+            for key in cached {
+                self.cache.remove(&key).await;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Decode serialized chunks and insert them into the cache, returns the cached keys
+    ///
+    /// Chunks that can't be decoded are left out, they are dealt with when they are read.
+    async fn cache_serialized(&self, values: Arc<Vec<SerializedChunk>>) -> Vec<u128> {
+        let compression = self.compression;
+        let decoded = tokio::task::spawn_blocking(move || {
+            values
+                .iter()
+                .filter_map(|value| {
+                    match ChunkCodec::decompress_data_sync::<Chunk>(value.data(), compression) {
+                        Ok(chunk) => Some((value.hash(), chunk)),
+                        Err(e) => {
+                            trace!("Not caching chunk {:X}: {}", value.hash(), e);
+                            None
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
         })
         .await
-        .unwrap()?;
+        .unwrap_or_default();
 
-        Ok(())
+        let keys = decoded.iter().map(|(key, _)| *key).collect();
+        futures::future::join_all(
+            decoded
+                .into_iter()
+                .map(|(key, chunk)| self.cache.insert(key, Arc::new(chunk))),
+        )
+        .await;
+        keys
     }
 }
 
@@ -394,12 +810,271 @@ async fn dump_chunk() {
         .await
         .unwrap();
     let chunk = state
-        .database
-        .get_chunk(2, 2, "overworld".to_string())
+        .world
+        .resource::<crate::database::Database>()
+        .await
+        .unwrap()
+        .get_chunk(&ChunkPos::overworld(2, 2))
         .await
         .unwrap()
         .unwrap();
     let outfile = std::fs::File::create("chunk.nbt").unwrap();
     let mut writer = std::io::BufWriter::new(outfile);
     chunk.nbt_serialize(&mut writer).unwrap();
+    // JSON copy that can be edited and loaded back with `Database::import_chunk_json`
+    let outfile = std::fs::File::create("chunk.json").unwrap();
+    serde_json::to_writer_pretty(std::io::BufWriter::new(outfile), &*chunk).unwrap();
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use crate::database::encoding::ChunkCodec;
+    use crate::database::keys::chunk_key;
+    use crate::database::{open_database, Database};
+    use crate::utils::config::Database as DatabaseConfig;
+    use crate::world::chunk_format::Chunk;
+    use crate::world::dimension::{ChunkPos, Dimension};
+    use crate::world::importing::SerializedChunk;
+
+    pub(crate) async fn test_database() -> Database {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        open_database(path, &DatabaseConfig::default())
+            .await
+            .unwrap()
+    }
+
+    /// A database of each storage backend, for the tests whose behavior must not depend on it
+    pub(crate) async fn test_databases() -> [Database; 2] {
+        [test_database().await, Database::new_in_memory()]
+    }
+
+    pub(crate) fn test_chunk(x: i32, z: i32) -> Chunk {
+        Chunk {
+            dimension: Some("overworld".to_string()),
+            status: "full".to_string(),
+            data_version: 3465,
+            heightmaps: None,
+            is_light_on: None,
+            inhabited_time: None,
+            y_pos: -4,
+            x_pos: x,
+            z_pos: z,
+            structures: None,
+            last_update: None,
+            sections: None,
+            block_entities: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_cached_chunk() {
+        for database in test_databases().await {
+            let chunk = test_chunk(1, 1);
+            let key = chunk_key(chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos);
+            database.cache.insert(key, Arc::new(chunk)).await;
+
+            assert!(database
+                .delete_chunk(&ChunkPos::overworld(1, 1))
+                .await
+                .unwrap());
+            assert!(!database.cache.contains_key(&key));
+            assert!(!database
+                .delete_chunk(&ChunkPos::overworld(1, 1))
+                .await
+                .unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_persisted_chunk() {
+        for database in test_databases().await {
+            let chunk = test_chunk(2, 3);
+            let key = chunk_key(chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos);
+            let data = ChunkCodec::compress_data(chunk, database.compression())
+                .await
+                .unwrap();
+            database
+                .batch_insert(vec![SerializedChunk::new(key, data)])
+                .await
+                .unwrap();
+
+            let deleted = database
+                .delete_chunks(&[ChunkPos::overworld(2, 3), ChunkPos::overworld(4, 4)])
+                .await
+                .unwrap();
+            assert_eq!(deleted, vec![true, false]);
+            assert!(!database
+                .chunk_exists(&ChunkPos::overworld(2, 3))
+                .await
+                .unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_stats_count_hits_and_misses() {
+        for database in test_databases().await {
+            let chunk = test_chunk(5, 5);
+            let key = chunk_key(chunk.dimension.as_ref().unwrap(), chunk.x_pos, chunk.z_pos);
+            database.cache.insert(key, Arc::new(chunk)).await;
+
+            let chunks = database
+                .batch_get(&[ChunkPos::overworld(5, 5), ChunkPos::overworld(6, 6)])
+                .await
+                .unwrap();
+            assert!(chunks[0].is_some() && chunks[1].is_none());
+
+            let stats = database.cache_stats();
+            assert_eq!(stats.hits, 1);
+            assert_eq!(stats.misses, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn write_behind_flush() {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        let config = DatabaseConfig {
+            write_behind: true,
+            write_behind_interval: 60_000,
+            ..DatabaseConfig::default()
+        };
+        let database = open_database(path, &config).await.unwrap();
+
+        database.insert_chunk(test_chunk(7, 7)).await.unwrap();
+        // Queued chunks are readable before being committed
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(7, 7))
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(database.write_behind_status().unwrap().pending, 1);
+
+        database.flush().await.unwrap();
+        let status = database.write_behind_status().unwrap();
+        assert_eq!(status.pending, 0);
+        assert_eq!(status.committed, 1);
+        assert_eq!(status.last_error, None);
+    }
+
+    #[tokio::test]
+    async fn iter_dimension_only_yields_its_chunks() {
+        for database in test_databases().await {
+            let mut values = Vec::new();
+            for (x, z, dimension) in [
+                (-3, 8, "overworld"),
+                (1, -1, "overworld"),
+                (1, -1, "the_end"),
+            ] {
+                let mut chunk = test_chunk(x, z);
+                chunk.dimension = Some(dimension.to_string());
+                let data = ChunkCodec::compress_data(chunk, database.compression())
+                    .await
+                    .unwrap();
+                values.push(SerializedChunk::new(
+                    ChunkPos::new(x, z, Dimension::from(dimension)).key(),
+                    data,
+                ));
+            }
+            database.batch_insert(values).await.unwrap();
+
+            let mut positions = database
+                .iter_dimension(&Dimension::Overworld)
+                .await
+                .unwrap()
+                .map(|entry| entry.map(|(pos, chunk)| (pos.x, pos.z, chunk.x_pos, chunk.z_pos)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            positions.sort();
+            assert_eq!(positions, vec![(-3, 8, -3, 8), (1, -1, 1, -1)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_insert_fills_the_cache() {
+        for database in test_databases().await {
+            let mut values = Vec::new();
+            for x in 0..2 {
+                let data = ChunkCodec::compress_data(test_chunk(x, 8), database.compression())
+                    .await
+                    .unwrap();
+                values.push(SerializedChunk::new(ChunkPos::overworld(x, 8).key(), data));
+            }
+            database.batch_insert(values).await.unwrap();
+
+            let chunks = database
+                .batch_get(&[ChunkPos::overworld(0, 8), ChunkPos::overworld(1, 8)])
+                .await
+                .unwrap();
+            assert!(chunks.iter().all(Option::is_some));
+            // Both chunks were served without reading the database
+            let stats = database.cache_stats();
+            assert_eq!(stats.hits, 2);
+            assert_eq!(stats.misses, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn close_positions_are_stored_independently() {
+        for database in test_databases().await {
+            // Positions a hash of the coordinates could easily mix up
+            let positions = [(1, 2), (2, 1), (-1, 2), (1, -2), (-2, -1), (0, 0)];
+            let mut values = Vec::new();
+            for (x, z) in positions {
+                let data = ChunkCodec::compress_data(test_chunk(x, z), database.compression())
+                    .await
+                    .unwrap();
+                values.push(SerializedChunk::new(ChunkPos::overworld(x, z).key(), data));
+            }
+            database.batch_insert(values).await.unwrap();
+            database.cache.invalidate_all();
+
+            let chunks = database
+                .batch_get(
+                    &positions
+                        .iter()
+                        .map(|(x, z)| ChunkPos::overworld(*x, *z))
+                        .collect::<Vec<_>>(),
+                )
+                .await
+                .unwrap();
+            for ((x, z), chunk) in positions.into_iter().zip(chunks) {
+                let chunk = chunk.unwrap();
+                assert_eq!((chunk.x_pos, chunk.z_pos), (x, z));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn corrupted_chunks_are_quarantined() {
+        let database = test_database().await;
+        let chunk = test_chunk(9, 9);
+        let data = ChunkCodec::compress_data(chunk, database.compression())
+            .await
+            .unwrap();
+        database
+            .batch_insert(vec![
+                SerializedChunk::new(ChunkPos::overworld(9, 9).key(), data),
+                SerializedChunk::new(ChunkPos::overworld(10, 10).key(), vec![1, 2, 3]),
+                SerializedChunk::new(ChunkPos::overworld(11, 11).key(), vec![4, 5, 6]),
+            ])
+            .await
+            .unwrap();
+
+        // Reading a corrupted chunk behaves as if it didn't exist
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(10, 10))
+            .await
+            .unwrap()
+            .is_none());
+
+        let report = database.verify_all().await.unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.quarantined, 1);
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(9, 9))
+            .await
+            .unwrap()
+            .is_some());
+    }
 }
@@ -0,0 +1,163 @@
+use heed::types::{Bytes, Str};
+use heed::{CompactionOption, Env};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use super::{open_env, SharedEnv, LMDB_READER_SYNC};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// Result of [`Database::compact`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Size of the database before the compaction, free pages included
+    pub before: u64,
+    /// Size of the database after the compaction
+    pub after: u64,
+    /// Time the database was unavailable
+    pub duration: Duration,
+}
+
+impl CompactReport {
+    /// Bytes given back by the compaction
+    pub fn reclaimed(&self) -> u64 {
+        self.before.saturating_sub(self.after)
+    }
+}
+
+/// Size of the pages used by `env`, including the free ones
+fn used_size(env: &Env) -> Result<u64, Error> {
+    let ro_tx = env.read_txn()?;
+    let meta = env
+        .open_database::<Str, Bytes>(&ro_tx, Some("meta"))?
+        .ok_or_else(|| Error::TableMissing("meta".to_string()))?;
+    let page_size = meta.stat(&ro_tx)?.page_size as u64;
+    Ok((env.info().last_page_number as u64 + 1) * page_size)
+}
+
+/// Write a compacted copy of `env` to `copy`, replacing a leftover of a failed compaction
+fn copy_compacted(env: &Env, copy: &Path) -> Result<(), Error> {
+    if copy.exists() {
+        fs::remove_file(copy)?;
+    }
+    env.copy_to_file(copy, CompactionOption::Enabled)?
+        .sync_all()?;
+    Ok(())
+}
+
+/// Replace the database at `path` by a compacted copy and reopen it
+///
+/// Returns the size of the database before and after.
+fn compact_env(db: &SharedEnv, path: &Path) -> Result<(u64, u64), Error> {
+    // Same gate as map resizes, waits for the running database tasks and holds the next ones
+    let _gate = LMDB_READER_SYNC
+        .write()
+        .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
+    let mut slot = db.0.write().unwrap();
+    let env = slot
+        .as_ref()
+        .ok_or_else(|| Error::DatabaseError("Database environment is closed".to_string()))?;
+    let before = used_size(env)?;
+    let map_size = env.info().map_size;
+
+    let data = path.join("data.mdb");
+    let copy = path.join("data.mdb.compact");
+    if let Err(e) = copy_compacted(env, &copy) {
+        let _ = fs::remove_file(&copy);
+        return Err(e);
+    }
+
+    // An environment can only be opened once, so it is closed before being replaced. Nothing
+    // can get it from the slot we hold, so this only waits for reads already running
+    if let Some(env) = slot.take() {
+        env.prepare_for_closing().wait();
+    }
+    if let Err(e) = fs::rename(&copy, &data) {
+        error!(
+            "Unable to replace the database by its compacted copy: {}",
+            e
+        );
+        let _ = fs::remove_file(&copy);
+    }
+
+    let env = open_env(path, map_size).inspect_err(|e| {
+        error!("Unable to reopen the database after compacting it: {}", e);
+    })?;
+    let after = used_size(&env);
+    *slot = Some(env);
+    Ok((before, after?))
+}
+
+impl Database {
+    /// Rewrite the database without its free pages
+    ///
+    /// LMDB reuses the pages freed by deleted chunks but never gives them back, so the database
+    /// doesn't shrink. The database is copied with compaction next to itself, the copy replaces
+    /// it and the environment is reopened. Every other database operation waits until it's done,
+    /// like during a map resize, so it is better run when the server is quiet. Modified and
+    /// queued chunks are saved first.
+    pub async fn compact(&self) -> Result<CompactReport, Error> {
+        self.check_writable()?;
+        self.flush().await?;
+
+        let db = self.db.clone();
+        let path = self.path.clone();
+        let start = Instant::now();
+        let (before, after) =
+            tokio::task::spawn_blocking(move || compact_env(&db, &path)).await??;
+
+        let report = CompactReport {
+            before,
+            after,
+            duration: start.elapsed(),
+        };
+        info!(
+            "Compacted the database from {} to {} bytes in {:?}",
+            report.before, report.after, report.duration
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn compaction_reclaims_deleted_chunks() {
+        let database = test_database().await;
+        let mut values = Vec::new();
+        let mut positions = Vec::new();
+        for x in 0..2000 {
+            let data = ChunkCodec::compress_data(test_chunk(x, 0), database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(ChunkPos::overworld(x, 0).key(), data));
+            positions.push(ChunkPos::overworld(x, 0));
+        }
+        database.batch_insert(values).await.unwrap();
+        database.delete_chunks(&positions[10..]).await.unwrap();
+
+        let report = database.compact().await.unwrap();
+        assert!(report.reclaimed() > 0);
+
+        // The reopened database still has the remaining chunks
+        database.cache.invalidate_all();
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(5, 0))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(50, 0))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(database.stats().await.unwrap().entries(), 10);
+    }
+}
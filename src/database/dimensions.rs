@@ -0,0 +1,324 @@
+//! Per-dimension chunk tables
+//!
+//! Every dimension stores its chunks in its own table (`chunks_overworld`, `chunks_nether`, ...)
+//! keyed by [`table_key`](crate::database::keys::table_key). The `dimensions` table maps each
+//! dimension name to its table name and is mirrored in memory, so the table of a chunk key can be
+//! resolved without opening a transaction.
+
+use byteorder::BE;
+use heed::types::{Bytes, Str, U64};
+use heed::{Env, RoTxn, RwTxn};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::info;
+
+use super::spawn_blocking_db;
+use crate::database::keys::{dimension_hash, key_dimension_hash};
+use crate::database::recovery::CORRUPT_TABLE;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::dimension::Dimension;
+
+/// Table mapping dimension names to their chunk table
+pub(crate) const REGISTRY_TABLE: &str = "dimensions";
+
+/// Table names that can't be given to a dimension
+const RESERVED_TABLES: [&str; 2] = [CORRUPT_TABLE, "chunks_migration"];
+
+pub(crate) type ChunkTable = heed::Database<U64<BE>, Bytes>;
+
+/// Name of the chunk table of `dimension`
+pub(crate) fn table_name(dimension: &str) -> String {
+    let name = dimension.strip_prefix("minecraft:").unwrap_or(dimension);
+    let name = name.strip_prefix("the_").unwrap_or(name);
+    let name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("chunks_{name}")
+}
+
+/// Open the `dimensions` table
+pub(crate) fn open_registry(env: &Env, tx: &RoTxn) -> Result<heed::Database<Str, Str>, Error> {
+    env.open_database::<Str, Str>(tx, Some(REGISTRY_TABLE))?
+        .ok_or_else(|| Error::TableMissing(REGISTRY_TABLE.to_string()))
+}
+
+/// Dimension hash -> (dimension name, table name)
+type TableMap = HashMap<u32, (Arc<str>, Arc<str>)>;
+
+/// In memory copy of the `dimensions` table, indexed by dimension hash
+#[derive(Default)]
+pub(crate) struct DimensionTables {
+    tables: RwLock<TableMap>,
+}
+
+impl DimensionTables {
+    /// Load the registry from the `dimensions` table
+    pub(crate) fn load(env: &Env, tx: &RoTxn) -> Result<Self, Error> {
+        let registry = open_registry(env, tx)?;
+
+        let mut tables = HashMap::new();
+        for entry in registry.iter(tx)? {
+            let (dimension, table) = entry?;
+            tables.insert(
+                dimension_hash(dimension),
+                (Arc::from(dimension), Arc::from(table)),
+            );
+        }
+        Ok(Self {
+            tables: RwLock::new(tables),
+        })
+    }
+
+    /// Table of the chunk identified by `key`, `None` if its dimension has no table yet
+    pub(crate) fn table_of_key(&self, key: u128) -> Option<Arc<str>> {
+        self.tables
+            .read()
+            .unwrap()
+            .get(&key_dimension_hash(key))
+            .map(|(_, table)| table.clone())
+    }
+
+    /// Every registered dimension with its table
+    pub(crate) fn all_with_dimensions(&self) -> Vec<(Arc<str>, Arc<str>)> {
+        self.tables.read().unwrap().values().cloned().collect()
+    }
+
+    /// Table of `dimension`
+    ///
+    /// Returns `Ok(None)` if the dimension isn't registered yet, and an error if it can't be
+    /// registered because its hash or table name is already used by another dimension.
+    pub(crate) fn check(&self, dimension: &str) -> Result<Option<Arc<str>>, Error> {
+        let tables = self.tables.read().unwrap();
+        if let Some((name, table)) = tables.get(&dimension_hash(dimension)) {
+            return if &**name == dimension {
+                Ok(Some(table.clone()))
+            } else {
+                Err(Error::DatabaseError(format!(
+                    "Dimension \"{dimension}\" collides with dimension \"{name}\""
+                )))
+            };
+        }
+
+        let table = table_name(dimension);
+        if RESERVED_TABLES.contains(&table.as_str())
+            || tables.values().any(|(_, used)| **used == *table)
+        {
+            return Err(Error::DatabaseError(format!(
+                "Table \"{table}\" of dimension \"{dimension}\" is already used"
+            )));
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn insert(&self, dimension: &str, table: Arc<str>) {
+        self.tables
+            .write()
+            .unwrap()
+            .insert(dimension_hash(dimension), (Arc::from(dimension), table));
+    }
+
+    /// Create the chunk table of `dimension` and record it in the `dimensions` table
+    pub(crate) fn create_table(
+        env: &Env,
+        rw_tx: &mut RwTxn,
+        dimension: &str,
+    ) -> Result<Arc<str>, Error> {
+        let table = table_name(dimension);
+        let registry = open_registry(env, rw_tx)?;
+        env.create_database::<U64<BE>, Bytes>(rw_tx, Some(&table))?;
+        registry.put(rw_tx, dimension, &table)?;
+        Ok(Arc::from(table))
+    }
+}
+
+impl Database {
+    /// Table of `dimension`, created on first use
+    pub(crate) async fn dimension_table(&self, dimension: &str) -> Result<Arc<str>, Error> {
+        if let Some(table) = self.tables.check(dimension)? {
+            return Ok(table);
+        }
+        self.check_writable()?;
+
+        let table = self.store.create_table(dimension).await?;
+
+        info!("Created table {} for dimension {}", table, dimension);
+        self.tables.insert(dimension, table.clone());
+        Ok(table)
+    }
+
+    /// Table of the chunk identified by `key`
+    ///
+    /// Vanilla dimensions are registered on first use, custom ones have to be registered with
+    /// [`Database::register_dimension`] beforehand since their name can't be recovered from
+    /// the key.
+    pub(crate) async fn table_for_key(&self, key: u128) -> Result<Arc<str>, Error> {
+        if let Some(table) = self.tables.table_of_key(key) {
+            return Ok(table);
+        }
+        let hash = key_dimension_hash(key);
+        match [Dimension::Overworld, Dimension::Nether, Dimension::End]
+            .into_iter()
+            .find(|dimension| dimension_hash(dimension.name()) == hash)
+        {
+            Some(dimension) => self.dimension_table(dimension.name()).await,
+            None => Err(Error::DatabaseError(format!(
+                "Chunk {key:X} belongs to an unregistered dimension"
+            ))),
+        }
+    }
+
+    /// Create the chunk table of `dimension` if it doesn't exist yet
+    ///
+    /// Required before inserting chunks of a custom dimension with [`Database::batch_insert`].
+    pub async fn register_dimension(&self, dimension: &Dimension) -> Result<(), Error> {
+        self.check_writable()?;
+        self.dimension_table(dimension.name()).await?;
+        Ok(())
+    }
+
+    /// Delete every chunk of `dimension` in a single transaction
+    ///
+    /// The dimension stays registered. Returns the number of chunks deleted from the database.
+    pub async fn drop_dimension(&self, dimension: &Dimension) -> Result<u64, Error> {
+        self.check_writable()?;
+        // Commit queued writes first, so they can't bring the chunks back afterwards
+        self.flush().await?;
+
+        let hash = dimension_hash(dimension.name());
+        let deleted = match self.tables.check(dimension.name())? {
+            Some(table) => {
+                let tsk_db = self.db.clone();
+                spawn_blocking_db(tsk_db, move |db| {
+                    let mut rw_tx = db.write_txn()?;
+                    let chunks = db
+                        .open_database::<U64<BE>, Bytes>(&rw_tx, Some(&table))?
+                        .ok_or_else(|| Error::TableMissing(table.to_string()))?;
+                    let deleted = chunks.len(&rw_tx)?;
+                    chunks.clear(&mut rw_tx)?;
+                    rw_tx.commit()?;
+                    Ok(deleted)
+                })
+                .await
+                .map_err(|_| Error::DatabaseError("Dimension drop was cancelled".to_string()))??
+            }
+            None => 0,
+        };
+
+        // Invalidate cache entries
+        let keys = self
+            .cache
+            .iter()
+            .filter(|(key, _)| key_dimension_hash(**key) == hash)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.dirty.take(key);
+            self.cache.remove(&key).await;
+        }
+
+        info!("Dropped {} chunks of dimension {}", deleted, dimension);
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::BE;
+    use heed::types::{Bytes, Str, U128};
+    use heed::EnvOpenOptions;
+
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::{verify_checksum, ChunkCodec, Compression};
+    use crate::database::keys::chunk_key;
+    use crate::database::open_database;
+    use crate::utils::config::Database as DatabaseConfig;
+    use crate::world::dimension::{ChunkPos, Dimension};
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn drop_dimension_only_clears_its_table() {
+        let database = test_database().await;
+        let mut values = Vec::new();
+        for dimension in ["overworld", "the_nether"] {
+            let mut chunk = test_chunk(4, 2);
+            chunk.dimension = Some(dimension.to_string());
+            let data = ChunkCodec::compress_data(chunk, database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(chunk_key(dimension, 4, 2), data));
+        }
+        database.batch_insert(values).await.unwrap();
+
+        assert_eq!(
+            database.drop_dimension(&Dimension::Nether).await.unwrap(),
+            1
+        );
+        assert!(!database
+            .chunk_exists(&ChunkPos::new(4, 2, Dimension::Nether))
+            .await
+            .unwrap());
+        assert!(database
+            .chunk_exists(&ChunkPos::overworld(4, 2))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn combined_table_is_split() {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+
+        // Database laid out as before the dimension tables and chunk versions
+        let env = unsafe { EnvOpenOptions::new().max_dbs(4).open(&path).unwrap() };
+        let mut rw_tx = env.write_txn().unwrap();
+        let chunks = env
+            .create_database::<U128<BE>, Bytes>(&mut rw_tx, Some("chunks"))
+            .unwrap();
+        let meta = env
+            .create_database::<Str, Bytes>(&mut rw_tx, Some("meta"))
+            .unwrap();
+        meta.put(&mut rw_tx, "compression", &[Compression::None.id()])
+            .unwrap();
+        meta.put(&mut rw_tx, "key_format", &[2]).unwrap();
+        for (x, z, dimension) in [(0, 0, "overworld"), (0, 0, "the_end"), (5, 6, "the_end")] {
+            let mut chunk = test_chunk(x, z);
+            chunk.dimension = Some(dimension.to_string());
+            let data = ChunkCodec::compress_data(chunk, Compression::None)
+                .await
+                .unwrap();
+            // Values had no format version nor checksum back then
+            let payload = verify_checksum(&data).unwrap();
+            chunks
+                .put(&mut rw_tx, &chunk_key(dimension, x, z), &payload[1..])
+                .unwrap();
+        }
+        chunks
+            .put(&mut rw_tx, &chunk_key("overworld", 1, 1), &[1, 2, 3])
+            .unwrap();
+        rw_tx.commit().unwrap();
+        env.prepare_for_closing().wait();
+
+        let database = open_database(path, &DatabaseConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            database
+                .iter_dimension(&Dimension::End)
+                .await
+                .unwrap()
+                .count(),
+            2
+        );
+        assert_eq!(
+            database
+                .iter_dimension(&Dimension::Overworld)
+                .await
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(database.verify_all().await.unwrap().checked, 3);
+    }
+}
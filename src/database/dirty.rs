@@ -0,0 +1,176 @@
+//! Dirty chunk tracking
+//!
+//! [`Database::update_chunk`] only updates the cache and flags the chunk as dirty. Dirty chunks
+//! are persisted together by [`Database::save_dirty`], which runs periodically and on shutdown,
+//! or when they are evicted from the cache.
+
+use dashmap::DashSet;
+use std::sync::Arc;
+
+use crate::database::backend::ChunkStore;
+use crate::database::dimensions::DimensionTables;
+use crate::database::encoding::{ChunkCodec, Compression};
+use crate::database::metrics::DbMetrics;
+use crate::database::write_behind::WriteBehind;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::chunk_format::Chunk;
+use crate::world::importing::SerializedChunk;
+
+/// Keys of the cached chunks modified since they were last persisted
+///
+/// Also holds what's needed to persist them, as the cache eviction listener has no access to
+/// the [`Database`].
+pub(crate) struct DirtyChunks {
+    keys: DashSet<u128>,
+    store: ChunkStore,
+    tables: Arc<DimensionTables>,
+    compression: Compression,
+    write_behind: Option<WriteBehind>,
+    metrics: Arc<DbMetrics>,
+}
+
+impl DirtyChunks {
+    pub(crate) fn new(
+        store: ChunkStore,
+        tables: Arc<DimensionTables>,
+        compression: Compression,
+        write_behind: Option<WriteBehind>,
+        metrics: Arc<DbMetrics>,
+    ) -> Self {
+        Self {
+            keys: DashSet::new(),
+            store,
+            tables,
+            compression,
+            write_behind,
+            metrics,
+        }
+    }
+
+    pub(crate) fn mark(&self, key: u128) {
+        self.keys.insert(key);
+    }
+
+    /// Clear the flag of `key`, returns whether it was dirty
+    pub(crate) fn take(&self, key: u128) -> bool {
+        self.keys.remove(&key).is_some()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Persist `chunks` within a single transaction
+    ///
+    /// Goes through the write-behind queue when enabled, so an older state still queued can't
+    /// override them afterwards.
+    pub(crate) async fn persist(&self, chunks: Vec<(u128, Arc<Chunk>)>) -> Result<(), Error> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let mut values = Vec::with_capacity(chunks.len());
+        for (key, chunk) in chunks {
+            let (data, uncompressed) =
+                ChunkCodec::compress_data_measured(chunk, self.compression).await?;
+            self.metrics.compressed(uncompressed, data.len());
+            self.metrics.written(data.len());
+            values.push(SerializedChunk::new(key, data));
+        }
+
+        if let Some(write_behind) = &self.write_behind {
+            for value in values {
+                write_behind.queue(value).await?;
+            }
+            return write_behind.flush().await;
+        }
+
+        self.store
+            .put_many(self.tables.clone(), Arc::new(values))
+            .await
+    }
+}
+
+impl Database {
+    /// Flag a cached chunk as modified, so it is persisted by the next [`Database::save_dirty`]
+    pub fn mark_dirty(&self, key: u128) -> Result<(), Error> {
+        self.check_writable()?;
+        self.dirty.mark(key);
+        Ok(())
+    }
+
+    /// Number of modified chunks waiting to be persisted
+    pub fn dirty_count(&self) -> usize {
+        self.dirty.len()
+    }
+
+    /// Persist every dirty chunk within a single transaction
+    ///
+    /// Each chunk is read from the cache before its flag is cleared, so a chunk evicted in
+    /// between is persisted by the eviction listener, which still finds it dirty. Chunks
+    /// modified during the save stay dirty. On failure the flags are restored. Returns the
+    /// number of persisted chunks.
+    pub async fn save_dirty(&self) -> Result<usize, Error> {
+        let keys = self.dirty.keys.iter().map(|key| *key).collect::<Vec<_>>();
+        let mut chunks = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            // Chunks evicted already are persisted by the eviction listener
+            let Some(chunk) = self.cache.get(key).await else {
+                continue;
+            };
+            if self.dirty.take(*key) {
+                chunks.push((*key, chunk));
+            }
+        }
+
+        let saved = chunks.len();
+        let saved_keys = chunks.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+        if let Err(e) = self.dirty.persist(chunks).await {
+            for key in saved_keys {
+                self.dirty.mark(key);
+            }
+            return Err(e);
+        }
+        Ok(saved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_databases};
+    use crate::world::dimension::ChunkPos;
+
+    #[tokio::test]
+    async fn dirty_chunks_are_saved() {
+        for database in test_databases().await {
+            database.update_chunk(test_chunk(2, 2)).await.unwrap();
+            assert_eq!(database.dirty_count(), 1);
+
+            assert_eq!(database.save_dirty().await.unwrap(), 1);
+            assert_eq!(database.dirty_count(), 0);
+            database.cache.invalidate_all();
+            assert!(database
+                .chunk_exists(&ChunkPos::overworld(2, 2))
+                .await
+                .unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn evicted_dirty_chunks_are_saved() {
+        for database in test_databases().await {
+            database.update_chunk(test_chunk(3, 3)).await.unwrap();
+
+            // Eviction completes once the chunk is persisted
+            database
+                .cache
+                .invalidate(&ChunkPos::overworld(3, 3).key())
+                .await;
+            assert_eq!(database.dirty_count(), 0);
+            assert!(database
+                .chunk_exists(&ChunkPos::overworld(3, 3))
+                .await
+                .unwrap());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Free space guard of the database volume
+//!
+//! LMDB fails with unhelpful errors once the disk is full, possibly halfway through saving the
+//! world. The space left on the volume is checked every autosave, and below `min_free_space`
+//! new chunks are refused until space is freed, so the chunks already loaded can still be
+//! saved.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tracing::{error, info};
+
+use crate::database::Database;
+use crate::utils::error::Error;
+
+pub(crate) struct DiskGuard {
+    /// Threshold in bytes, 0 disables the guard
+    min_free: u64,
+    available: AtomicU64,
+    low: AtomicBool,
+}
+
+impl DiskGuard {
+    pub(crate) fn new(min_free: u64) -> Self {
+        Self {
+            min_free,
+            available: AtomicU64::new(u64::MAX),
+            low: AtomicBool::new(false),
+        }
+    }
+
+    /// Update the available space, switching in or out of low space mode
+    fn update(&self, available: u64) {
+        self.available.store(available, Ordering::Relaxed);
+        let low = available < self.min_free;
+        if self.low.swap(low, Ordering::Relaxed) == low {
+            return;
+        }
+        if low {
+            error!(
+                "Only {} MiB left on the database volume (minimum {} MiB), new chunks are refused until space is freed",
+                available / 1024 / 1024,
+                self.min_free / 1024 / 1024
+            );
+        } else {
+            info!(
+                "{} MiB available on the database volume again, accepting new chunks",
+                available / 1024 / 1024
+            );
+        }
+    }
+
+    /// Fail with [`Error::DiskFull`] while the volume is low on space
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.low.load(Ordering::Relaxed) {
+            return Err(Error::DiskFull {
+                available: self.available.load(Ordering::Relaxed),
+                required: self.min_free,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Space available to the server on the volume holding `path`
+pub(crate) fn available_space(path: &Path) -> Result<u64, Error> {
+    Ok(fs2::available_space(path)?)
+}
+
+impl Database {
+    /// Check the space left on the database volume, returns the available bytes
+    ///
+    /// Below `min_free_space`, inserting chunks fails with [`Error::DiskFull`] until a later
+    /// check finds enough space again.
+    pub async fn check_disk_space(&self) -> Result<u64, Error> {
+        let path = self.path.clone();
+        let available = tokio::task::spawn_blocking(move || available_space(&path)).await??;
+        self.disk_guard.update(available);
+        Ok(available)
+    }
+
+    /// Whether the last check found the database volume low on space, new chunks should not be
+    /// generated meanwhile
+    pub fn is_low_on_disk_space(&self) -> bool {
+        self.disk_guard.low.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskGuard;
+    use crate::utils::error::Error;
+
+    #[test]
+    fn guard_recovers_once_space_is_freed() {
+        let guard = DiskGuard::new(1000);
+        guard.update(5000);
+        assert!(guard.check().is_ok());
+
+        guard.update(10);
+        assert!(matches!(
+            guard.check(),
+            Err(Error::DiskFull {
+                available: 10,
+                required: 1000
+            })
+        ));
+
+        guard.update(2000);
+        assert!(guard.check().is_ok());
+    }
+}
@@ -1,51 +1,405 @@
 use bincode::config::standard;
 use bincode::{Decode, Encode};
 use heed::{BytesDecode, BytesEncode};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::io::{Read, Write};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
-pub struct Zstd<T>(PhantomData<T>);
+use crate::utils::error::Error;
 
-impl<'a, T: Encode + 'a> BytesEncode<'a> for Zstd<T> {
+/// Compression algorithm applied to values stored in the database
+///
+/// The algorithm is chosen once when the database is created and stored in its `meta` table,
+/// so a database keeps decoding correctly even if the configured algorithm changes later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Bzip,
+    // "best" and "fast" are the names written by older configs
+    #[serde(alias = "best")]
+    Zstd,
+    #[default]
+    #[serde(alias = "fast")]
+    Lz4,
+    None,
+}
+
+impl Compression {
+    /// Identifier of the algorithm as stored in the database metadata
+    pub fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Bzip => 1,
+            Compression::Zstd => 2,
+            Compression::Lz4 => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Bzip),
+            2 => Some(Compression::Zstd),
+            3 => Some(Compression::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Compress raw bytes with this algorithm
+    pub fn compress(self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Bzip => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+                encoder.write_all(bytes).map_err(Error::CompressionError)?;
+                encoder.finish().map_err(Error::CompressionError)
+            }
+            Compression::Zstd => zstd::encode_all(bytes, 3).map_err(Error::CompressionError),
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(bytes)),
+        }
+    }
+
+    /// Decompress bytes previously compressed with this algorithm
+    pub fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Bzip => {
+                let mut decoded = Vec::new();
+                bzip2::read::BzDecoder::new(bytes)
+                    .read_to_end(&mut decoded)
+                    .map_err(Error::CompressionError)?;
+                Ok(decoded)
+            }
+            Compression::Zstd => zstd::decode_all(bytes).map_err(Error::CompressionError),
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(bytes).map_err(|e| {
+                Error::CompressionError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            }),
+        }
+    }
+}
+
+/// First byte of the values followed by a checksum
+///
+/// Values written before checksums were added start with their version (chunks, players) or
+/// with the `B` of their bzip stream (entities) instead.
+const CHECKSUM_MAGIC: u8 = 0xC5;
+
+/// Prefix `payload` with [`CHECKSUM_MAGIC`] and append its CRC32
+pub(crate) fn append_checksum(payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(payload.len() + 5);
+    bytes.push(CHECKSUM_MAGIC);
+    bytes.extend_from_slice(payload);
+    bytes.extend_from_slice(&crc32fast::hash(payload).to_be_bytes());
+    bytes
+}
+
+/// Payload of a value written by [`append_checksum`], once its checksum is verified
+///
+/// Values without the magic byte predate checksums and are returned as is.
+pub(crate) fn verify_checksum(bytes: &[u8]) -> Result<&[u8], Error> {
+    let Some((&CHECKSUM_MAGIC, rest)) = bytes.split_first() else {
+        return Ok(bytes);
+    };
+    if rest.len() < 4 {
+        return Err(Error::DeserializationError(
+            "Value is too short to hold its checksum".to_string(),
+        ));
+    }
+    let (payload, checksum) = rest.split_at(rest.len() - 4);
+    let stored = u32::from_be_bytes(checksum.try_into().unwrap());
+    let computed = crc32fast::hash(payload);
+    if stored != computed {
+        return Err(Error::ChecksumMismatch { stored, computed });
+    }
+    Ok(payload)
+}
+
+/// Marker type selecting a compression algorithm at the type level
+pub trait CompressionCodec {
+    const COMPRESSION: Compression;
+}
+
+pub struct Bzip;
+pub struct Zstd;
+pub struct Lz4;
+pub struct Uncompressed;
+
+impl CompressionCodec for Bzip {
+    const COMPRESSION: Compression = Compression::Bzip;
+}
+impl CompressionCodec for Zstd {
+    const COMPRESSION: Compression = Compression::Zstd;
+}
+impl CompressionCodec for Lz4 {
+    const COMPRESSION: Compression = Compression::Lz4;
+}
+impl CompressionCodec for Uncompressed {
+    const COMPRESSION: Compression = Compression::None;
+}
+
+/// Bincode encoded value compressed with the codec `C`
+pub struct BincodeCompressed<T, C>(PhantomData<(T, C)>);
+
+/// Bincode encoded value compressed with bzip
+pub type BincodeBzip<T> = BincodeCompressed<T, Bzip>;
+
+impl<'a, T: Encode + 'a, C: CompressionCodec> BytesEncode<'a> for BincodeCompressed<T, C> {
     type EItem = T;
 
     fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, heed::BoxedError> {
-        // Compress
         let bytes = bincode::encode_to_vec(item, standard())?;
+        // Compress
+        let bytes = C::COMPRESSION.compress(&bytes)?;
 
-        Ok(Cow::Owned(bytes))
+        Ok(Cow::Owned(append_checksum(&bytes)))
     }
 }
 
-impl<'a, T: Decode + 'a> BytesDecode<'a> for Zstd<T> {
+impl<'a, T: Decode + 'a, C: CompressionCodec> BytesDecode<'a> for BincodeCompressed<T, C> {
     type DItem = T;
 
     fn bytes_decode(bytes: &'a [u8]) -> Result<Self::DItem, heed::BoxedError> {
-        let decoded = bincode::decode_from_slice(bytes, standard())?;
+        let bytes = C::COMPRESSION.decompress(verify_checksum(bytes)?)?;
+        let decoded = bincode::decode_from_slice(&bytes, standard())?;
         Ok(decoded.0)
     }
 }
 
-pub struct ZstdCodec;
+/// Value stored with the version of its layout
+///
+/// Encoded values are prefixed with `VERSION`, so values written before a layout change can
+/// still be decoded. When the layout of a type changes, bump `VERSION`, keep a copy of the
+/// previous definition and convert it to the new one in [`Versioned::upgrade`].
+pub trait Versioned: Sized {
+    const VERSION: u8;
+
+    /// Decode `bytes` (uncompressed bincode) written with an older `version` of the layout
+    fn upgrade(version: u8, bytes: &[u8]) -> crate::Result<Self>;
+}
+
+/// Shared values are stored like the value itself, so cached chunks are saved without a copy
+impl<T: Versioned> Versioned for Arc<T> {
+    const VERSION: u8 = T::VERSION;
+
+    fn upgrade(version: u8, bytes: &[u8]) -> crate::Result<Self> {
+        T::upgrade(version, bytes).map(Arc::new)
+    }
+}
+
+/// Decode a bincode value with the layout of an older version, see [`Versioned::upgrade`]
+pub fn decode_legacy<T: Decode>(bytes: &[u8]) -> crate::Result<T> {
+    let decoded = bincode::decode_from_slice(bytes, standard())?;
+    Ok(decoded.0)
+}
 
-impl ZstdCodec {
-    pub async fn compress_data<T: Encode + Send + 'static>(data: T) -> crate::Result<Vec<u8>> {
-        /*            let mut bytes = Vec::new();
-                    let mut compressor = zstd::Encoder::new(&mut bytes, 3)?;
-                    bincode::encode_into_std_write(&data, &mut compressor, standard())?;
-                    compressor.finish()?;*/
+/// Encodes values stored in the database with bincode, compressed with the [`Compression`] of the
+/// database
+pub struct ChunkCodec;
+
+impl ChunkCodec {
+    pub async fn compress_data<T: Encode + Versioned + Send + 'static>(
+        data: T,
+        compression: Compression,
+    ) -> crate::Result<Vec<u8>> {
+        Ok(Self::compress_data_measured(data, compression).await?.0)
+    }
+
+    /// Same as [`ChunkCodec::compress_data`], also returning the size of the value before
+    /// compression
+    pub async fn compress_data_measured<T: Encode + Versioned + Send + 'static>(
+        data: T,
+        compression: Compression,
+    ) -> crate::Result<(Vec<u8>, usize)> {
         let mut bytes = Vec::new();
         bincode::encode_into_std_write(&data, &mut bytes, standard())?;
-        Ok(bytes)
+        let uncompressed = bytes.len();
+        let compressed = compression.compress(&bytes)?;
+
+        // The version stays uncompressed so it can be checked before decompressing
+        let mut bytes = Vec::with_capacity(compressed.len() + 1);
+        bytes.push(T::VERSION);
+        bytes.extend_from_slice(&compressed);
+        Ok((append_checksum(&bytes), uncompressed))
+    }
+    pub async fn decompress_data<T: Decode + Versioned + Send + 'static>(
+        data: &[u8],
+        compression: Compression,
+    ) -> crate::Result<T> {
+        Self::decompress_data_sync(data, compression)
+    }
+
+    /// Same as [`ChunkCodec::decompress_data`], for use outside of an async context
+    pub fn decompress_data_sync<T: Decode + Versioned>(
+        data: &[u8],
+        compression: Compression,
+    ) -> crate::Result<T> {
+        Ok(Self::decompress_versioned(data, compression)?.0)
     }
-    pub async fn decompress_data<T: Decode + Send + 'static>(data: &[u8]) -> crate::Result<T> {
 
-        // let mut decoder = zstd::Decoder::new(data.as_slice())?;
-        // let decoded = bincode::decode_from_slice(data.as_slice(), standard())?;
-        // let decoded = bincode::decode_from_std_read(&mut decoder, standard())?;
+    /// Decode a value, upgrading it if it was written with an older layout
+    ///
+    /// The returned flag is `true` if the value was upgraded, in which case it should be
+    /// persisted again so the upgrade is only done once.
+    pub fn decompress_versioned<T: Decode + Versioned>(
+        data: &[u8],
+        compression: Compression,
+    ) -> crate::Result<(T, bool)> {
+        let (&version, data) = verify_checksum(data)?
+            .split_first()
+            .ok_or(Error::DeserializationError("Empty value".to_string()))?;
+        let data = compression.decompress(data)?;
 
-        let decoded = bincode::decode_from_slice(data, standard())?;
+        match version.cmp(&T::VERSION) {
+            Ordering::Equal => Ok((decode_legacy(data.as_slice())?, false)),
+            Ordering::Less => Ok((T::upgrade(version, data.as_slice())?, true)),
+            Ordering::Greater => Err(Error::DeserializationError(format!(
+                "Value format version {} is newer than the supported version {}",
+                version,
+                T::VERSION
+            ))),
+        }
+    }
+}
 
-        Ok(decoded.0)
+#[cfg(test)]
+mod tests {
+    use bincode::{Decode, Encode};
+
+    use heed::{BytesDecode, BytesEncode};
+
+    use super::{decode_legacy, BincodeBzip, ChunkCodec, Compression, Versioned};
+    use crate::utils::error::Error;
+    use crate::world::chunk_format::Chunk;
+
+    #[derive(Encode, Decode)]
+    struct ValueV1 {
+        a: i32,
+    }
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    struct Value {
+        a: i32,
+        b: Option<String>,
+    }
+
+    impl Versioned for ValueV1 {
+        const VERSION: u8 = 1;
+
+        fn upgrade(version: u8, _bytes: &[u8]) -> crate::Result<Self> {
+            Err(Error::DeserializationError(format!(
+                "Unknown version {version}"
+            )))
+        }
+    }
+
+    impl Versioned for Value {
+        const VERSION: u8 = 2;
+
+        fn upgrade(version: u8, bytes: &[u8]) -> crate::Result<Self> {
+            match version {
+                1 => {
+                    let old = decode_legacy::<ValueV1>(bytes)?;
+                    Ok(Value { a: old.a, b: None })
+                }
+                _ => Err(Error::DeserializationError(format!(
+                    "Unknown version {version}"
+                ))),
+            }
+        }
+    }
+
+    /// `Chunk` at version 1, uncompressed: the overworld chunk at (1, 2) with status "full",
+    /// data version 3465, y -4 and every optional field empty
+    const CHUNK_V1: &[u8] = &[
+        1, // version
+        1, 9, b'o', b'v', b'e', b'r', b'w', b'o', b'r', b'l', b'd', // dimension
+        4, b'f', b'u', b'l', b'l', // status
+        251, 0x12, 0x1B, // data_version
+        0, 0, 0, // heightmaps, is_light_on, inhabited_time
+        7, 2, 4, // y_pos, x_pos, z_pos
+        0, 0, 0, // structures, last_update, sections
+    ];
+
+    #[tokio::test]
+    async fn older_versions_are_upgraded() {
+        let data = ChunkCodec::compress_data(ValueV1 { a: 42 }, Compression::Lz4)
+            .await
+            .unwrap();
+        let (value, upgraded) =
+            ChunkCodec::decompress_versioned::<Value>(&data, Compression::Lz4).unwrap();
+        assert!(upgraded);
+        assert_eq!(value, Value { a: 42, b: None });
+
+        let data = ChunkCodec::compress_data(value, Compression::Lz4)
+            .await
+            .unwrap();
+        assert!(
+            !ChunkCodec::decompress_versioned::<Value>(&data, Compression::Lz4)
+                .unwrap()
+                .1
+        );
+        // Values written by a newer version can't be read
+        assert!(ChunkCodec::decompress_versioned::<ValueV1>(&data, Compression::Lz4).is_err());
+    }
+
+    #[test]
+    fn chunk_v1_fixture_still_decodes() {
+        let chunk = ChunkCodec::decompress_data_sync::<Chunk>(CHUNK_V1, Compression::None).unwrap();
+        assert_eq!(chunk.dimension.as_deref(), Some("overworld"));
+        assert_eq!(chunk.status, "full");
+        assert_eq!(chunk.data_version, 3465);
+        assert_eq!((chunk.x_pos, chunk.y_pos, chunk.z_pos), (1, -4, 2));
+        assert!(chunk.sections.is_none());
+        assert!(chunk.block_entities.is_none());
+    }
+
+    #[tokio::test]
+    async fn flipped_bytes_fail_the_checksum() {
+        let value = Value {
+            a: 7,
+            b: Some("ferrumc".to_string()),
+        };
+        let mut data = ChunkCodec::compress_data(value, Compression::None)
+            .await
+            .unwrap();
+        data[4] ^= 0x01;
+        assert!(matches!(
+            ChunkCodec::decompress_data_sync::<Value>(&data, Compression::None),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        let mut data = BincodeBzip::<String>::bytes_encode(&"ferrumc".to_string())
+            .unwrap()
+            .into_owned();
+        assert_eq!(
+            BincodeBzip::<String>::bytes_decode(&data).unwrap(),
+            "ferrumc"
+        );
+        let middle = data.len() / 2;
+        data[middle] ^= 0x80;
+        let e = BincodeBzip::<String>::bytes_decode(&data).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<Error>(),
+            Some(Error::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn roundtrip_all_codecs() {
+        let data = b"ferrumc ferrumc ferrumc ferrumc ferrumc".repeat(64);
+        for compression in [
+            Compression::None,
+            Compression::Bzip,
+            Compression::Zstd,
+            Compression::Lz4,
+        ] {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(compression.decompress(&compressed).unwrap(), data);
+            assert_eq!(Compression::from_id(compression.id()), Some(compression));
+        }
     }
 }
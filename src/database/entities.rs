@@ -0,0 +1,281 @@
+//! Entity persistence
+//!
+//! Entities are stored in the `entities` table keyed by their UUID. The `entity_chunks` table
+//! indexes them by chunk: its keys are the chunk key followed by the entity UUID, so the
+//! entities of a chunk are found with a prefix scan.
+
+use bincode::{Decode, Encode};
+use byteorder::BE;
+use dashmap::DashMap;
+use heed::types::{Bytes, Unit, U128};
+use heed::{Env, RoTxn, RwTxn};
+
+use super::spawn_blocking_db;
+use crate::database::encoding::BincodeBzip;
+use crate::database::keys::chunk_key;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::dimension::ChunkPos;
+
+pub(crate) const ENTITY_TABLE: &str = "entities";
+pub(crate) const ENTITY_INDEX_TABLE: &str = "entity_chunks";
+
+type EntityTable = heed::Database<U128<BE>, BincodeBzip<EntityData>>;
+type EntityIndex = heed::Database<Bytes, Unit>;
+
+/// Entities loaded from the database, by UUID
+pub(crate) type EntityCache = DashMap<u128, EntityData>;
+
+/// Persisted state of an entity (player, dropped item, mob, ...)
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct EntityData {
+    /// Entity type, e.g. `minecraft:item`
+    pub kind: String,
+    pub dimension: String,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// State specific to the entity type, e.g. the item of a dropped item
+    pub data: Vec<u8>,
+}
+
+impl EntityData {
+    /// Key of the chunk the entity is in
+    pub fn chunk_key(&self) -> u128 {
+        chunk_key(
+            &self.dimension,
+            (self.x.floor() as i32) >> 4,
+            (self.z.floor() as i32) >> 4,
+        )
+    }
+}
+
+/// Key of the `entity_chunks` table
+fn index_key(chunk: u128, uuid: u128) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[..16].copy_from_slice(&chunk.to_be_bytes());
+    key[16..].copy_from_slice(&uuid.to_be_bytes());
+    key
+}
+
+/// Open the entity tables, `None` if the database was created before they existed
+fn open_entity_tables(
+    db: &Env,
+    tx: &RoTxn,
+) -> Result<Option<(EntityTable, EntityIndex)>, heed::Error> {
+    let entities = db.open_database(tx, Some(ENTITY_TABLE))?;
+    let index = db.open_database(tx, Some(ENTITY_INDEX_TABLE))?;
+    Ok(entities.zip(index))
+}
+
+/// Create the entity tables if they don't exist yet
+pub(super) fn create_entity_tables(db: &Env, rw_tx: &mut RwTxn) -> Result<(), heed::Error> {
+    if db
+        .open_database::<U128<BE>, Bytes>(rw_tx, Some(ENTITY_TABLE))?
+        .is_none()
+    {
+        db.create_database::<U128<BE>, Bytes>(rw_tx, Some(ENTITY_TABLE))?;
+    }
+    if db
+        .open_database::<Bytes, Unit>(rw_tx, Some(ENTITY_INDEX_TABLE))?
+        .is_none()
+    {
+        db.create_database::<Bytes, Unit>(rw_tx, Some(ENTITY_INDEX_TABLE))?;
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Insert or replace entities within a single write transaction, keeping the chunk index
+    /// up to date
+    fn save_entities_in_database(db: &Env, entities: &[(u128, EntityData)]) -> Result<(), Error> {
+        let mut rw_tx = db.write_txn()?;
+        let (table, index) = open_entity_tables(db, &rw_tx)?
+            .ok_or_else(|| Error::TableMissing(ENTITY_TABLE.to_string()))?;
+
+        for (uuid, entity) in entities {
+            // The entity may have moved to another chunk
+            if let Some(previous) = table.get(&rw_tx, uuid)? {
+                index.delete(&mut rw_tx, &index_key(previous.chunk_key(), *uuid))?;
+            }
+            table.put(&mut rw_tx, uuid, entity)?;
+            index.put(&mut rw_tx, &index_key(entity.chunk_key(), *uuid), &())?;
+        }
+
+        rw_tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete an entity and its index entry, returns whether it was present
+    fn delete_entity_from_database(db: &Env, uuid: u128) -> Result<bool, Error> {
+        let mut rw_tx = db.write_txn()?;
+        let (table, index) = open_entity_tables(db, &rw_tx)?
+            .ok_or_else(|| Error::TableMissing(ENTITY_TABLE.to_string()))?;
+
+        let Some(entity) = table.get(&rw_tx, &uuid)? else {
+            return Ok(false);
+        };
+        index.delete(&mut rw_tx, &index_key(entity.chunk_key(), uuid))?;
+        table.delete(&mut rw_tx, &uuid)?;
+
+        rw_tx.commit()?;
+        Ok(true)
+    }
+
+    /// Save the state of an entity
+    pub async fn save_entity(&self, uuid: u128, entity: EntityData) -> Result<(), Error> {
+        self.save_entities(vec![(uuid, entity)]).await
+    }
+
+    /// Save the state of multiple entities within a single transaction
+    pub async fn save_entities(&self, entities: Vec<(u128, EntityData)>) -> Result<(), Error> {
+        self.check_writable()?;
+        let tsk_db = self.db.clone();
+        let values = entities.clone();
+        spawn_blocking_db(tsk_db, move |db| {
+            Self::save_entities_in_database(db, &values)
+        })
+        .await
+        .unwrap()?;
+
+        for (uuid, entity) in entities {
+            self.entities.insert(uuid, entity);
+        }
+        Ok(())
+    }
+
+    /// Get the state of an entity, `None` if it was never saved
+    pub async fn get_entity(&self, uuid: u128) -> Result<Option<EntityData>, Error> {
+        if let Some(entity) = self.entities.get(&uuid) {
+            return Ok(Some(entity.clone()));
+        }
+
+        let tsk_db = self.db.clone();
+        let entity = spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+            match open_entity_tables(db, &ro_tx)? {
+                Some((table, _)) => Ok(table.get(&ro_tx, &uuid)?),
+                None => Ok(None),
+            }
+        })
+        .await
+        .unwrap()?;
+
+        if let Some(entity) = &entity {
+            self.entities.insert(uuid, entity.clone());
+        }
+        Ok(entity)
+    }
+
+    /// Delete an entity, returns whether it existed
+    pub async fn delete_entity(&self, uuid: u128) -> Result<bool, Error> {
+        self.check_writable()?;
+        let tsk_db = self.db.clone();
+        let deleted = spawn_blocking_db(tsk_db, move |db| {
+            Self::delete_entity_from_database(db, uuid)
+        })
+        .await
+        .unwrap()?;
+
+        self.entities.remove(&uuid);
+        Ok(deleted)
+    }
+
+    /// Every entity saved in the chunk at `pos`, with its UUID
+    pub async fn entities_in_chunk(
+        &self,
+        pos: &ChunkPos,
+    ) -> Result<Vec<(u128, EntityData)>, Error> {
+        let chunk = pos.key();
+        let tsk_db = self.db.clone();
+        let entities = spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+            let Some((table, index)) = open_entity_tables(db, &ro_tx)? else {
+                return Ok(Vec::new());
+            };
+
+            let mut entities = Vec::new();
+            for entry in index.prefix_iter(&ro_tx, &chunk.to_be_bytes())? {
+                let (key, _) = entry?;
+                let uuid = u128::from_be_bytes(key[16..].try_into().unwrap());
+                if let Some(entity) = table.get(&ro_tx, &uuid)? {
+                    entities.push((uuid, entity));
+                }
+            }
+            Ok(entities)
+        })
+        .await
+        .unwrap()?;
+
+        for (uuid, entity) in entities.iter() {
+            self.entities.insert(*uuid, entity.clone());
+        }
+        Ok(entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntityData;
+    use crate::database::chunks::tests::test_database;
+    use crate::world::dimension::ChunkPos;
+
+    fn test_entity(x: f64, z: f64) -> EntityData {
+        EntityData {
+            kind: "minecraft:item".to_string(),
+            dimension: "overworld".to_string(),
+            x,
+            y: 64.0,
+            z,
+            yaw: 0.0,
+            pitch: 0.0,
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[tokio::test]
+    async fn entities_are_indexed_by_chunk() {
+        let database = test_database().await;
+        database
+            .save_entities(vec![
+                (1, test_entity(1.0, 1.0)),
+                (2, test_entity(-1.0, 1.0)),
+            ])
+            .await
+            .unwrap();
+        let in_chunk = database
+            .entities_in_chunk(&ChunkPos::overworld(0, 0))
+            .await
+            .unwrap();
+        assert_eq!(in_chunk, vec![(1, test_entity(1.0, 1.0))]);
+
+        // Moving to another chunk updates the index
+        database
+            .save_entity(1, test_entity(20.0, 1.0))
+            .await
+            .unwrap();
+        assert!(database
+            .entities_in_chunk(&ChunkPos::overworld(0, 0))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            database
+                .entities_in_chunk(&ChunkPos::overworld(1, 0))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(database.delete_entity(2).await.unwrap());
+        assert!(database.get_entity(2).await.unwrap().is_none());
+        assert!(database
+            .entities_in_chunk(&ChunkPos::overworld(-1, 0))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
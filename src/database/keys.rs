@@ -0,0 +1,113 @@
+//! Chunk keys
+//!
+//! A key is `dimension_hash: u32 | morton(x, z): u64` stored as a `u128`. The whole key
+//! identifies a chunk in the cache, while the dimension tables only store the morton part as a
+//! big endian `u64`, so neighbouring chunks sit next to each other and the chunk position can be
+//! recovered from the key without decoding the value.
+
+use std::ops::RangeInclusive;
+
+/// Version of the key format, stored in the `meta` table
+pub(crate) const KEY_FORMAT: u8 = 3;
+
+/// Stable 32 bits FNV-1a hash of the dimension name
+///
+/// `DefaultHasher` isn't guaranteed to be stable between Rust releases, which is not an option
+/// for persisted keys.
+pub(crate) fn dimension_hash(dimension: &str) -> u32 {
+    dimension.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Spread the 32 bits of `v` over the even bits of a u64
+fn spread(v: u32) -> u64 {
+    let mut v = v as u64;
+    v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    v = (v | (v << 1)) & 0x5555_5555_5555_5555;
+    v
+}
+
+/// Inverse of [`spread`]
+fn compact(v: u64) -> u32 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v >> 4)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v >> 8)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v >> 16)) & 0x0000_0000_FFFF_FFFF;
+    v as u32
+}
+
+/// Interleave the bits of x and z. The sign bit is flipped so negative coordinates sort before
+/// positive ones.
+fn morton(x: i32, z: i32) -> u64 {
+    spread(x as u32 ^ 0x8000_0000) | (spread(z as u32 ^ 0x8000_0000) << 1)
+}
+
+/// Key of the chunk at `x`, `z` in `dimension`
+pub fn chunk_key(dimension: &str, x: i32, z: i32) -> u128 {
+    ((dimension_hash(dimension) as u128) << 64) | morton(x, z) as u128
+}
+
+/// Chunk position encoded in a key
+pub fn chunk_key_position(key: u128) -> (i32, i32) {
+    let morton = key as u64;
+    (
+        (compact(morton) ^ 0x8000_0000) as i32,
+        (compact(morton >> 1) ^ 0x8000_0000) as i32,
+    )
+}
+
+/// Hash of the dimension encoded in a key
+pub(crate) fn key_dimension_hash(key: u128) -> u32 {
+    (key >> 64) as u32
+}
+
+/// Key of a chunk within its dimension table
+pub(crate) fn table_key(key: u128) -> u64 {
+    key as u64
+}
+
+/// Range covering the keys of every chunk of `dimension`
+pub fn dimension_keys(dimension: &str) -> RangeInclusive<u128> {
+    let prefix = (dimension_hash(dimension) as u128) << 64;
+    prefix..=(prefix | u64::MAX as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_roundtrip() {
+        for (x, z) in [(0, 0), (-1, 1), (i32::MIN, i32::MAX), (12345, -67890)] {
+            let key = chunk_key("overworld", x, z);
+            assert_eq!(chunk_key_position(key), (x, z));
+            assert!(dimension_keys("overworld").contains(&key));
+            assert!(!dimension_keys("the_nether").contains(&key));
+        }
+    }
+
+    #[test]
+    fn keys_never_collide() {
+        let mut keys = std::collections::HashSet::new();
+        for x in -64..64 {
+            for z in -64..64 {
+                assert!(keys.insert(chunk_key("overworld", x, z)));
+            }
+        }
+        for (x, z) in [
+            (i32::MIN, i32::MAX),
+            (i32::MAX, i32::MIN),
+            (i32::MIN, i32::MIN),
+        ] {
+            assert!(keys.insert(chunk_key("overworld", x, z)));
+        }
+        // The same position in another dimension is another chunk
+        assert!(keys.insert(chunk_key("the_nether", 0, 0)));
+    }
+}
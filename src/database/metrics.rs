@@ -0,0 +1,207 @@
+//! Chunk IO metrics
+//!
+//! Counters are relaxed atomics updated by the chunk methods of [`Database`], cheap enough for
+//! the per-player hot path. [`Database::metrics`] takes a snapshot, e.g. to export it to
+//! Prometheus.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::database::Database;
+
+/// Upper bounds of the latency histogram buckets, in microseconds, the last bucket has no bound
+const LATENCY_BUCKETS: [u64; 12] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000,
+];
+
+/// Number of calls and latency histogram of an operation
+#[derive(Default)]
+pub(crate) struct OpMetrics {
+    count: AtomicU64,
+    total_micros: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl OpMetrics {
+    /// Start timing a call, recorded when the returned guard is dropped
+    pub(crate) fn time(&self) -> OpTimer<'_> {
+        OpTimer {
+            metrics: self,
+            start: Instant::now(),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OpStats {
+        let mut cumulative = 0;
+        let buckets = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                let bound = LATENCY_BUCKETS
+                    .get(index)
+                    .map(|micros| Duration::from_micros(*micros));
+                (bound, cumulative)
+            })
+            .collect();
+        OpStats {
+            count: self.count.load(Ordering::Relaxed),
+            total: Duration::from_micros(self.total_micros.load(Ordering::Relaxed)),
+            buckets,
+        }
+    }
+}
+
+/// Guard returned by [`OpMetrics::time`]
+pub(crate) struct OpTimer<'a> {
+    metrics: &'a OpMetrics,
+    start: Instant,
+}
+
+impl Drop for OpTimer<'_> {
+    fn drop(&mut self) {
+        self.metrics.record(self.start.elapsed());
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct DbMetrics {
+    pub(crate) get: OpMetrics,
+    pub(crate) exists: OpMetrics,
+    pub(crate) insert: OpMetrics,
+    pub(crate) update: OpMetrics,
+    pub(crate) delete: OpMetrics,
+    pub(crate) batch_get: OpMetrics,
+    pub(crate) batch_insert: OpMetrics,
+    bytes_written: AtomicU64,
+    compression_input: AtomicU64,
+    compression_output: AtomicU64,
+}
+
+impl DbMetrics {
+    /// Count `bytes` of chunk values sent to the database
+    pub(crate) fn written(&self, bytes: usize) {
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Count a chunk of `uncompressed` bytes compressed to `compressed` bytes
+    pub(crate) fn compressed(&self, uncompressed: usize, compressed: usize) {
+        self.compression_input
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.compression_output
+            .fetch_add(compressed as u64, Ordering::Relaxed);
+    }
+}
+
+/// Snapshot of the metrics of an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpStats {
+    pub count: u64,
+    /// Time spent in the operation, summed over every call
+    pub total: Duration,
+    /// Cumulative latency histogram: number of calls that took at most the bound, the last
+    /// bucket has no bound and counts every call
+    pub buckets: Vec<(Option<Duration>, u64)>,
+}
+
+impl OpStats {
+    /// Average latency, `None` before the first call
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.total.as_micros() as u64 / self.count))
+    }
+}
+
+/// Snapshot of the chunk IO metrics, see [`Database::metrics`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsSnapshot {
+    pub get: OpStats,
+    pub exists: OpStats,
+    pub insert: OpStats,
+    pub update: OpStats,
+    pub delete: OpStats,
+    pub batch_get: OpStats,
+    pub batch_insert: OpStats,
+    /// Size of the chunk values sent to the database
+    pub bytes_written: u64,
+    /// Share of the chunk lookups served by the cache, `None` before the first lookup
+    pub cache_hit_ratio: Option<f64>,
+    /// Uncompressed size divided by compressed size of the chunks compressed so far
+    pub compression_ratio: Option<f64>,
+}
+
+impl Database {
+    /// Snapshot of the chunk IO counters and latencies since the database was opened
+    ///
+    /// Only reads atomics, so it can be polled as often as needed.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        let metrics = &self.metrics;
+        let cache = self.cache_stats();
+        let lookups = cache.hits + cache.misses;
+        let input = metrics.compression_input.load(Ordering::Relaxed);
+        let output = metrics.compression_output.load(Ordering::Relaxed);
+        MetricsSnapshot {
+            get: metrics.get.snapshot(),
+            exists: metrics.exists.snapshot(),
+            insert: metrics.insert.snapshot(),
+            update: metrics.update.snapshot(),
+            delete: metrics.delete.snapshot(),
+            batch_get: metrics.batch_get.snapshot(),
+            batch_insert: metrics.batch_insert.snapshot(),
+            bytes_written: metrics.bytes_written.load(Ordering::Relaxed),
+            cache_hit_ratio: (lookups > 0).then(|| cache.hits as f64 / lookups as f64),
+            compression_ratio: (output > 0).then(|| input as f64 / output as f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::OpMetrics;
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::world::dimension::ChunkPos;
+
+    #[test]
+    fn latencies_fall_into_cumulative_buckets() {
+        let metrics = OpMetrics::default();
+        metrics.record(Duration::from_micros(10));
+        metrics.record(Duration::from_micros(700));
+        metrics.record(Duration::from_secs(1));
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.buckets[0], (Some(Duration::from_micros(50)), 1));
+        assert_eq!(stats.buckets[4], (Some(Duration::from_millis(1)), 2));
+        assert_eq!(stats.buckets.last(), Some(&(None, 3)));
+    }
+
+    #[tokio::test]
+    async fn chunk_operations_are_counted() {
+        let database = test_database().await;
+        database.insert_chunk(test_chunk(1, 1)).await.unwrap();
+        database
+            .get_chunk(&ChunkPos::overworld(1, 1))
+            .await
+            .unwrap();
+
+        let metrics = database.metrics();
+        assert_eq!(metrics.insert.count, 1);
+        assert_eq!(metrics.get.count, 1);
+        assert!(metrics.bytes_written > 0);
+        assert!(metrics.compression_ratio.is_some());
+    }
+}
@@ -1,62 +1,143 @@
-use byteorder::LE;
-use deepsize::DeepSizeOf;
-use futures::FutureExt;
-use heed::types::{Bytes, U64};
-use heed::{Env as LMDBDatabase, Env, EnvFlags, EnvOpenOptions, MdbError};
-use moka::notification::{ListenerFuture, RemovalCause};
+use byteorder::BE;
+use heed::types::{Bytes, Str, U128};
+use heed::{Env, EnvFlags, EnvOpenOptions, MdbError, RwTxn};
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::HashMap;
 use std::env;
 use std::future::Future;
-use std::path::PathBuf;
-use std::sync::{Arc, LazyLock, Mutex, OnceLock, RwLock};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::sync::oneshot;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, error, info, trace_span, warn};
 
-use crate::utils::config::get_global_config;
+use crate::utils::config::{get_global_config, Database as DatabaseConfig};
 use crate::utils::error::Error;
 
+use crate::database::backend::ChunkStore;
+use crate::database::cache::{build_cache, CacheCounters};
+use crate::database::dimensions::{ChunkTable, DimensionTables, REGISTRY_TABLE};
+use crate::database::dirty::DirtyChunks;
+use crate::database::disk_space::DiskGuard;
+use crate::database::encoding::{ChunkCodec, Compression};
+use crate::database::entities::{create_entity_tables, EntityCache};
+use crate::database::keys::{chunk_key, table_key, KEY_FORMAT};
+use crate::database::metrics::DbMetrics;
+use crate::database::players::create_player_table;
+use crate::database::recovery::CORRUPT_TABLE;
+use crate::database::settings::create_settings_table;
+use crate::database::versioning::version_chunk_values;
+use crate::database::warmup::create_hot_keys_table;
+use crate::database::write_behind::WriteBehind;
 use crate::world::chunk_format::Chunk;
+pub mod backend;
+pub mod backup;
+pub mod cache;
 pub mod chunks;
-pub(crate) mod encoding;
+pub mod compact;
+pub mod dimensions;
+pub mod dirty;
+pub mod disk_space;
+pub mod encoding;
+pub mod entities;
+pub mod keys;
+pub mod metrics;
+pub mod players;
+pub mod prefetch;
+pub mod read_only;
+pub mod recovery;
+pub mod scan;
+pub mod settings;
+pub mod stats;
+pub mod trim;
+pub mod versioning;
+pub mod warmup;
+pub mod write_behind;
 
 const LMDB_MIN_PAGE_SIZE: usize = 1800 * 1024usize.pow(2); // 1800MB
-const LMDB_PAGE_SIZE_INCREMENT: usize = 250 * 1024usize.pow(2); // 250MB
-const LMDB_MAX_DBS: u32 = 10;
+const LMDB_MAX_DBS: u32 = 64; // Every dimension has its own table
+// LMDB keeps a slot per thread reading, the blocking pool runs reads on many of them
+const LMDB_MAX_READERS: u32 = 126;
 
 // Database threadpool
 static LMDB_THREADPOOL: OnceLock<ThreadPool> = OnceLock::new();
 
-// Global size
-static LMDB_PAGE_SIZE: LazyLock<Arc<Mutex<usize>>> =
-    LazyLock::new(|| Arc::new(Mutex::new(LMDB_MIN_PAGE_SIZE)));
+// Size the memory map can grow to, set from the config when the database is opened
+static LMDB_MAX_MAP_SIZE: AtomicUsize = AtomicUsize::new(usize::MAX);
 static LMDB_READER_SYNC: LazyLock<Arc<RwLock<()>>> = LazyLock::new(|| Arc::new(RwLock::new(())));
 
+/// Handle to the LMDB environment, shared by everything using the database
+///
+/// [`Database::compact`] replaces the environment, so it is resolved for each transaction
+/// instead of being cloned once and kept around.
+#[derive(Clone)]
+pub(crate) struct SharedEnv(Arc<RwLock<Option<Env>>>);
+
+impl SharedEnv {
+    pub(crate) fn new(env: Env) -> Self {
+        Self(Arc::new(RwLock::new(Some(env))))
+    }
+
+    /// Handle without any environment, used by databases kept in memory
+    pub(crate) fn detached() -> Self {
+        Self(Arc::new(RwLock::new(None)))
+    }
+
+    /// Current environment, only missing if a compaction failed to reopen the database
+    pub(crate) fn get(&self) -> Result<Env, Error> {
+        self.0
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| Error::DatabaseError("Database environment is closed".to_string()))
+    }
+
+    /// Remove the environment so it can be closed, every later transaction fails
+    pub(crate) fn take(&self) -> Option<Env> {
+        self.0.write().unwrap().take()
+    }
+}
+
 /// Global database structure
 ///
 /// Internally contain a handle to the persistent database and a
 /// cache for all in-memory updates
 pub struct Database {
-    db: LMDBDatabase,
-    cache: Arc<moka::future::Cache<u64, Chunk>>,
+    db: SharedEnv,
+    store: ChunkStore,
+    cache: Arc<moka::future::Cache<u128, Arc<Chunk>>>,
+    cache_counters: Arc<CacheCounters>,
+    metrics: Arc<DbMetrics>,
+    disk_guard: DiskGuard,
+    compression: Compression,
+    tables: Arc<DimensionTables>,
+    write_behind: Option<WriteBehind>,
+    dirty: Arc<DirtyChunks>,
+    entities: EntityCache,
+    scan_batch_size: usize,
+    path: PathBuf,
+    read_only: bool,
 }
 
-fn evict_chunk(_key: Arc<u64>, value: Chunk, cause: RemovalCause) -> ListenerFuture {
-    async move {
-        if cause == RemovalCause::Expired {
-            trace!(
-                "Evicting chunk from cache: {}, {}",
-                value.x_pos,
-                value.z_pos
-            );
-        }
-    }
-    .boxed()
+/// Start database threadpool
+fn start_threadpool() {
+    LMDB_THREADPOOL.get_or_init(|| {
+        ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get() / 2)
+            .build()
+            .unwrap()
+    });
 }
 
 /// Start database
 pub async fn start_database() -> Result<Database, Error> {
+    open_database(world_path()?, &get_global_config().database).await
+}
+
+/// Location of the configured world
+pub fn world_path() -> Result<PathBuf, Error> {
     // Parse root directory from environment variable
     let root = if env::var("FERRUMC_ROOT").is_ok() {
         PathBuf::from(env::var("FERRUMC_ROOT").unwrap())
@@ -71,73 +152,396 @@ pub async fn start_database() -> Result<Database, Error> {
 
     // Obtain global config to locate which world folder to load
     let world = get_global_config().world.clone();
-    let world_path = root.join("data").join(world);
-
-    debug!("Opening database at {}", world_path.display());
-
-    if !fs::try_exists(&world_path).await? {
-        fs::create_dir_all(&world_path).await?;
-    }
+    Ok(root.join("data").join(world))
+}
 
+/// Open the LMDB environment located at `world_path` for writing
+fn open_env(world_path: &Path, map_size: usize) -> Result<Env, Error> {
     // Database Options
     let mut opts = EnvOpenOptions::new();
-    opts.max_readers(num_cpus::get() as u32)
-        .map_size(LMDB_MIN_PAGE_SIZE)
+    opts.max_readers(LMDB_MAX_READERS)
+        .map_size(map_size)
         .max_dbs(LMDB_MAX_DBS);
 
     // Open database (This operation is safe as we assume no other process touched the database)
-    let lmdb = unsafe {
+    Ok(unsafe {
         opts.flags(EnvFlags::WRITE_MAP | EnvFlags::NO_SYNC)
-            .open(&world_path)
-            .expect("Unable to open LMDB environment located at {world_path:?}")
-    };
+            .open(world_path)?
+    })
+}
 
-    // Start database threadpool
-    LMDB_THREADPOOL.get_or_init(|| {
-        ThreadPoolBuilder::new()
-            .num_threads(num_cpus::get() / 2)
-            .build()
-            .unwrap()
-    });
+/// Open the database located at `world_path`, creating it if it doesn't exist yet
+///
+/// The configured compression is only used for newly created databases, existing ones keep
+/// the algorithm they were created with.
+pub(crate) async fn open_database(
+    world_path: PathBuf,
+    config: &DatabaseConfig,
+) -> Result<Database, Error> {
+    let compression = config.compression;
+    let start = Instant::now();
+    debug!("Opening database at {}", world_path.display());
+
+    if !fs::try_exists(&world_path).await? {
+        fs::create_dir_all(&world_path).await?;
+    }
+
+    let map_size = config.map_size.max(1) * 1024usize.pow(2);
+    LMDB_MAX_MAP_SIZE.store(
+        (config.max_map_size * 1024usize.pow(2)).max(map_size),
+        Ordering::Relaxed,
+    );
+    let lmdb = open_env(&world_path, map_size)?;
+
+    start_threadpool();
 
     // Check if database is built. Otherwise, initialize it
     let mut rw_tx = lmdb.write_txn()?;
-    if lmdb
-        // .open_database::<U64<LE>, Zstd<Chunk>>(&rw_tx, Some("chunks"))
-        .open_database::<U64<LE>, Bytes>(&rw_tx, Some("chunks"))?
-        .is_none()
-    {
-        lmdb.create_database::<U64<LE>, Bytes>(&mut rw_tx, Some("chunks"))
-            .expect("Unable to create database");
+    // Chunks of every dimension used to share this table
+    let has_combined_table = lmdb
+        .open_database::<Bytes, Bytes>(&rw_tx, Some("chunks"))?
+        .is_some();
+    let is_new = !has_combined_table
+        && lmdb
+            .open_database::<Str, Bytes>(&rw_tx, Some("meta"))?
+            .is_none();
+    Database::ensure_schema(&lmdb, &mut rw_tx)?;
+
+    // Resolve which compression algorithm the stored values use
+    let meta = lmdb
+        .open_database::<Str, Bytes>(&rw_tx, Some("meta"))?
+        .ok_or_else(|| Error::TableMissing("meta".to_string()))?;
+    let stored_compression = match meta.get(&rw_tx, "compression")? {
+        Some(&[id]) => Some(Compression::from_id(id).ok_or(Error::DatabaseError(format!(
+            "Unknown compression id {id} in database metadata"
+        )))?),
+        Some(_) => {
+            return Err(Error::DatabaseError(
+                "Invalid compression entry in database metadata".to_string(),
+            ))
+        }
+        None => None,
+    };
+    let compression = match stored_compression {
+        Some(stored) => {
+            if stored != compression {
+                warn!(
+                    "Database was created with {:?} compression, ignoring configured {:?}",
+                    stored, compression
+                );
+            }
+            stored
+        }
+        None => {
+            // Databases created before compression was configurable store plain bincode
+            let compression = if is_new {
+                compression
+            } else {
+                Compression::None
+            };
+            meta.put(&mut rw_tx, "compression", &[compression.id()])?;
+            compression
+        }
+    };
+
+    // Databases created before the dimension tables need their chunks moved
+    let key_format = match meta.get(&rw_tx, "key_format")? {
+        Some(&[format]) => format,
+        Some(_) => {
+            return Err(Error::DatabaseError(
+                "Invalid key format entry in database metadata".to_string(),
+            ))
+        }
+        None if is_new => KEY_FORMAT,
+        None => 1,
+    };
+    let tables = DimensionTables::load(&lmdb, &rw_tx)?;
+
+    // Chunks stored before the chunk format was versioned have no version prefix
+    if !is_new && meta.get(&rw_tx, "chunk_versions")?.is_none() {
+        version_chunk_values(&lmdb, &mut rw_tx, "chunks")?;
+        for (_, table) in tables.all_with_dimensions() {
+            version_chunk_values(&lmdb, &mut rw_tx, &table)?;
+        }
     }
-    // `entities` table to be added, but needs the type to do so
+    meta.put(&mut rw_tx, "chunk_versions", &[1])?;
+
+    if key_format < KEY_FORMAT && has_combined_table {
+        split_chunk_table(&lmdb, &mut rw_tx, &tables, compression, key_format)?;
+    }
+    meta.put(&mut rw_tx, "key_format", &[KEY_FORMAT])?;
 
     rw_tx.commit()?;
+    let db = SharedEnv::new(lmdb);
+    let tables = Arc::new(tables);
 
-    info!("Database started");
+    info!(
+        "Database started ({:?} compression) in {:?}",
+        compression,
+        start.elapsed()
+    );
 
     info!("Initializing cache");
 
+    let write_behind = config.write_behind.then(|| {
+        info!(
+            "Write-behind enabled (every {}ms or {} chunks)",
+            config.write_behind_interval, config.write_behind_max_pending
+        );
+        WriteBehind::start(
+            db.clone(),
+            tables.clone(),
+            Duration::from_millis(config.write_behind_interval),
+            config.write_behind_max_pending,
+        )
+    });
+
     // Initializing moka cache
-    let cache = moka::future::Cache::builder()
-        .async_eviction_listener(evict_chunk)
-        .weigher(|_, v| v.deep_size_of() as u32)
-        .eviction_policy(moka::policy::EvictionPolicy::tiny_lfu())
-        /*.max_capacity(get_global_config().database.cache_size as u64 * 1024)
-        .initial_capacity(1000)*/
-        .time_to_live(Duration::from_millis(1000))
-        .build();
-
-    Ok(Database {
-        db: lmdb,
+    let metrics = Arc::new(DbMetrics::default());
+    let store = ChunkStore::Lmdb(db.clone());
+    let dirty = Arc::new(DirtyChunks::new(
+        store.clone(),
+        tables.clone(),
+        compression,
+        write_behind.clone(),
+        metrics.clone(),
+    ));
+    let cache_counters = Arc::new(CacheCounters::default());
+    let cache = build_cache(config, cache_counters.clone(), Some(dirty.clone()));
+
+    let database = Database {
+        db,
+        store,
         cache: Arc::new(cache),
-    })
+        cache_counters,
+        metrics,
+        disk_guard: DiskGuard::new(config.min_free_space * 1024 * 1024),
+        compression,
+        tables,
+        write_behind,
+        dirty,
+        entities: EntityCache::default(),
+        scan_batch_size: config.scan_batch_size,
+        path: world_path,
+        read_only: false,
+    };
+    if let Err(e) = database.check_disk_space().await {
+        warn!(
+            "Unable to check the space left on the database volume: {}",
+            e
+        );
+    }
+    Ok(database)
+}
+
+impl Database {
+    /// Create the tables missing from the database and check the registered ones exist
+    ///
+    /// Runs within the transaction opening the database, so either every table is created or
+    /// none is. Chunk tables are only created when their dimension is registered.
+    pub(crate) fn ensure_schema(lmdb: &Env, rw_tx: &mut RwTxn) -> Result<(), Error> {
+        if lmdb
+            .open_database::<Str, Bytes>(rw_tx, Some("meta"))?
+            .is_none()
+        {
+            lmdb.create_database::<Str, Bytes>(rw_tx, Some("meta"))?;
+        }
+        if lmdb
+            .open_database::<Str, Str>(rw_tx, Some(REGISTRY_TABLE))?
+            .is_none()
+        {
+            lmdb.create_database::<Str, Str>(rw_tx, Some(REGISTRY_TABLE))?;
+        }
+        // Undecodable chunks are moved there instead of being served
+        if lmdb
+            .open_database::<U128<BE>, Bytes>(rw_tx, Some(CORRUPT_TABLE))?
+            .is_none()
+        {
+            lmdb.create_database::<U128<BE>, Bytes>(rw_tx, Some(CORRUPT_TABLE))?;
+        }
+        create_entity_tables(lmdb, rw_tx)?;
+        create_player_table(lmdb, rw_tx)?;
+        create_hot_keys_table(lmdb, rw_tx)?;
+        create_settings_table(lmdb, rw_tx)?;
+
+        // Every registered dimension must have its chunk table
+        for (_, table) in DimensionTables::load(lmdb, rw_tx)?.all_with_dimensions() {
+            Database::open_chunk_table(lmdb, rw_tx, &table)?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of entries moved at once by [`split_chunk_table`]
+const MIGRATION_BATCH_SIZE: usize = 1024;
+
+/// Move the chunks of the combined `chunks` table into their dimension table
+///
+/// Format 1 keys are a `hash((dimension, x, z))` that can't be reversed, and format 2 keys only
+/// hold a hash of the dimension name, so every value is decoded to find its dimension and
+/// position. Undecodable entries are quarantined when their key is still meaningful, and
+/// dropped otherwise.
+fn split_chunk_table(
+    lmdb: &Env,
+    rw_tx: &mut RwTxn,
+    tables: &DimensionTables,
+    compression: Compression,
+    key_format: u8,
+) -> Result<(), Error> {
+    info!("Moving chunks to per-dimension tables, this may take a while...");
+
+    let combined = lmdb
+        .open_database::<Bytes, Bytes>(rw_tx, Some("chunks"))?
+        .ok_or_else(|| Error::TableMissing("chunks".to_string()))?;
+    let corrupt = lmdb
+        .open_database::<U128<BE>, Bytes>(rw_tx, Some(CORRUPT_TABLE))?
+        .ok_or_else(|| Error::TableMissing(CORRUPT_TABLE.to_string()))?;
+
+    let mut opened: HashMap<Arc<str>, ChunkTable> = HashMap::new();
+    let (mut migrated, mut quarantined, mut dropped) = (0usize, 0usize, 0usize);
+    loop {
+        // Moved entries are deleted, so the next batch always starts at the beginning
+        let batch = combined
+            .iter(rw_tx)?
+            .take(MIGRATION_BATCH_SIZE)
+            .map(|entry| entry.map(|(key, data)| (key.to_vec(), data.to_vec())))
+            .collect::<Result<Vec<_>, heed::Error>>()?;
+        if batch.is_empty() {
+            break;
+        }
+
+        for (raw_key, data) in batch {
+            combined.delete(rw_tx, &raw_key)?;
+
+            let chunk = match ChunkCodec::decompress_data_sync::<Chunk>(&data, compression) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    if let (2, Ok(key)) = (key_format, <[u8; 16]>::try_from(raw_key.as_slice())) {
+                        warn!(
+                            "Quarantining undecodable chunk ({} bytes): {}",
+                            data.len(),
+                            e
+                        );
+                        corrupt.put(rw_tx, &u128::from_be_bytes(key), &data)?;
+                        quarantined += 1;
+                    } else {
+                        warn!("Dropping undecodable chunk ({} bytes): {}", data.len(), e);
+                        dropped += 1;
+                    }
+                    continue;
+                }
+            };
+
+            let dimension = chunk.dimension.as_deref().unwrap_or("overworld");
+            let table = match tables.check(dimension)? {
+                Some(table) => table,
+                None => {
+                    let table = DimensionTables::create_table(lmdb, rw_tx, dimension)?;
+                    tables.insert(dimension, table.clone());
+                    table
+                }
+            };
+            let database = match opened.get(&table) {
+                Some(database) => *database,
+                None => {
+                    let database = Database::open_chunk_table(lmdb, rw_tx, &table)?;
+                    opened.insert(table, database);
+                    database
+                }
+            };
+
+            let key = chunk_key(dimension, chunk.x_pos, chunk.z_pos);
+            database.put(rw_tx, &table_key(key), &data)?;
+            migrated += 1;
+        }
+    }
+
+    info!(
+        "Moved {} chunks to per-dimension tables ({} quarantined, {} dropped)",
+        migrated, quarantined, dropped
+    );
+    Ok(())
+}
+
+/// Size of the memory map after growing from `old_size`
+///
+/// LMDB doesn't grow the map by itself, so it is doubled each time it's full, up to the
+/// configured maximum. Returns `None` once the maximum is reached.
+fn new_map_size(old_size: usize) -> Option<usize> {
+    let max_size = LMDB_MAX_MAP_SIZE.load(Ordering::Relaxed);
+    (old_size < max_size).then(|| old_size.saturating_mul(2).min(max_size))
+}
+
+/// Grow the memory map of `db` after a transaction failed because it was `full_size` large
+///
+/// Waits for every other database task to finish, as LMDB can't resize the map while a
+/// transaction is active. Returns whether the transaction can be retried.
+fn grow_map(db: &SharedEnv, full_size: usize) -> bool {
+    let _resize_guard = LMDB_READER_SYNC
+        .write()
+        .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
+    let Ok(db) = db.get() else {
+        return false;
+    };
+
+    // Another task may have grown the map while this one was waiting
+    if db.info().map_size > full_size {
+        return true;
+    }
+    let Some(new_size) = new_map_size(full_size) else {
+        error!(
+            "Database reached its maximum size of {} MiB, increase `max_map_size` in the config",
+            full_size / 1024usize.pow(2)
+        );
+        return false;
+    };
+
+    // Safe as the write lock guarantees no transaction is active
+    if let Err(e) = unsafe { db.resize(new_size) } {
+        error!("Unable to resize LMDB environment: {}", e);
+        return false;
+    }
+    info!(
+        "Successfully resized LMDB map from {} MiB to {} MiB",
+        full_size / 1024usize.pow(2),
+        new_size / 1024usize.pow(2)
+    );
+    true
 }
 
-/// LMDB will follow a linear growth as opposed to MDBX which
-/// uses a geometric growth.
-pub(super) fn new_page_size(old_size: usize) -> usize {
-    old_size + LMDB_PAGE_SIZE_INCREMENT
+/// Run `f` with the current environment of `db`, growing the map and retrying once if it is
+/// full
+fn run_db_task<F, R>(db: &SharedEnv, f: &F) -> Result<R, Error>
+where
+    F: Fn(&Env) -> Result<R, Error>,
+{
+    let read_lock = LMDB_READER_SYNC
+        .read()
+        .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
+    // Resolved under the lock, a compaction may swap the environment otherwise
+    let env = db.get()?;
+
+    let res = f(&env);
+    if !matches!(
+        res,
+        Err(Error::LmdbError(heed::Error::Mdb(MdbError::MapFull)))
+    ) {
+        return res;
+    }
+
+    warn!("Database map is full. Resizing...");
+    let full_size = env.info().map_size;
+    drop(env);
+    drop(read_lock);
+
+    // Retry once, in a new transaction
+    if !grow_map(db, full_size) {
+        return res;
+    }
+    let _read_lock = LMDB_READER_SYNC
+        .read()
+        .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
+    f(&db.get()?)
 }
 
 /// Spawn a blocking task to interact with the database
@@ -147,56 +551,85 @@ pub(super) fn new_page_size(old_size: usize) -> usize {
 /// # Arguments
 ///
 /// * `db` - The database environment
-/// * `f` - The function to execute
+/// * `f` - The function to execute, given the environment to use
 ///
 /// # Returns
 ///
 /// A future that resolves to the result of the function
 pub(super) fn spawn_blocking_db<F, R>(
-    db: Env,
+    db: SharedEnv,
     f: F,
-) -> impl Future<Output = Result<Result<R, heed::Error>, oneshot::error::RecvError>>
+) -> impl Future<Output = Result<Result<R, Error>, oneshot::error::RecvError>>
 where
-    F: Fn() -> Result<R, heed::Error> + Send + 'static,
+    F: Fn(&Env) -> Result<R, Error> + Send + 'static,
     R: Send + 'static + std::fmt::Debug,
 {
-    let (tx, res) = oneshot::channel::<Result<R, heed::Error>>();
+    let (tx, res) = oneshot::channel::<Result<R, Error>>();
 
+    // Attributed to the span of the caller, the pool thread has none otherwise
+    let span = trace_span!("db_task");
     let pool = LMDB_THREADPOOL.get().unwrap();
     pool.spawn(move || {
-
-        let read_lock = LMDB_READER_SYNC.read()
-            .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
-
-        let mut res = f();
-        if let Err(heed::Error::Mdb(MdbError::MapFull)) = res {
-
-            warn!("Database page is full. Resizing...");
-
-            drop(read_lock);
-
-            let _resize_guard = LMDB_READER_SYNC.write()
-                .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
-
-            let mut global_size_lock = LMDB_PAGE_SIZE.lock().unwrap();
-            let old_size = *global_size_lock;
-            *global_size_lock = new_page_size(old_size);
-            unsafe { db.resize(*global_size_lock).expect("Unable to resize LMDB environment.") };
-
-            tracing::info!("Successfully resized LMDB page from {} MiB to {} MiB", old_size / 1024usize.pow(2), *global_size_lock / 1024usize.pow(2));
-
-            drop(global_size_lock);
-            drop(_resize_guard);
-
-            res = f();
-        } else {
-            drop(read_lock)
-        }
-
-        if tx.send(res).is_err() {
+        let _span = span.enter();
+        if tx.send(run_db_task(&db, &f)).is_err() {
             warn!("A database task has been unable to send its result because the receiver at other end have closed.")
         }
     });
 
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::database::open_database;
+    use crate::utils::config::Database as DatabaseConfig;
+    use crate::world::dimension::{ChunkPos, Dimension};
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn fresh_database_has_its_schema() {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        let database = open_database(path, &DatabaseConfig::default())
+            .await
+            .unwrap();
+
+        // Nothing was ever saved, but every table can be read
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(0, 0))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(database
+            .get_chunk(&ChunkPos::new(0, 0, Dimension::Nether))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(database.get_entity(1).await.unwrap().is_none());
+        assert!(database.load_player(1).await.unwrap().is_none());
+        assert_eq!(database.stats().await.unwrap().entries(), 0);
+    }
+
+    #[tokio::test]
+    async fn map_grows_when_full() {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        let config = DatabaseConfig {
+            map_size: 1,
+            max_map_size: 64,
+            ..Default::default()
+        };
+        let database = open_database(path, &config).await.unwrap();
+
+        // 3 MB of chunks, which can't fit in the initial 1 MB map
+        for batch in 0..48 {
+            let values = (0..4)
+                .map(|x| SerializedChunk::new(ChunkPos::overworld(x, batch).key(), vec![7; 16384]))
+                .collect();
+            database.batch_insert(values).await.unwrap();
+        }
+
+        let stats = database.stats().await.unwrap();
+        assert_eq!(stats.entries(), 192);
+        assert!(stats.map_size > 1024usize.pow(2));
+        assert!(stats.map_size <= 64 * 1024usize.pow(2));
+    }
+}
@@ -0,0 +1,138 @@
+//! Player persistence
+//!
+//! Players are stored in the `players` table keyed by their UUID, encoded like chunks: bincode,
+//! compressed with the database compression and prefixed with the [`PlayerData`] version.
+
+use bincode::{Decode, Encode};
+use byteorder::BE;
+use heed::types::{Bytes, U128};
+use heed::{Env, RwTxn};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::spawn_blocking_db;
+use crate::database::encoding::{ChunkCodec, Versioned};
+use crate::database::Database;
+use crate::utils::error::Error;
+
+pub(crate) const PLAYER_TABLE: &str = "players";
+
+/// State of a player kept between sessions
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct PlayerData {
+    pub uuid: u128,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub dimension: String,
+    pub gamemode: u8,
+    /// Flags of the player abilities packet (invulnerable, flying, ...)
+    pub abilities: u8,
+    /// When the player was last saved, in milliseconds since the Unix epoch
+    pub last_seen: u64,
+}
+
+impl PlayerData {
+    /// Milliseconds since the Unix epoch, for [`PlayerData::last_seen`]
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default()
+    }
+}
+
+impl Versioned for PlayerData {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, _bytes: &[u8]) -> crate::Result<Self> {
+        Err(Error::DeserializationError(format!(
+            "Unknown player data version {version}"
+        )))
+    }
+}
+
+/// Create the `players` table if it doesn't exist yet
+pub(super) fn create_player_table(db: &Env, rw_tx: &mut RwTxn) -> Result<(), heed::Error> {
+    if db
+        .open_database::<U128<BE>, Bytes>(rw_tx, Some(PLAYER_TABLE))?
+        .is_none()
+    {
+        db.create_database::<U128<BE>, Bytes>(rw_tx, Some(PLAYER_TABLE))?;
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Save the state of a player, replacing the previous one
+    pub async fn save_player(&self, player: &PlayerData) -> Result<(), Error> {
+        self.check_writable()?;
+        let uuid = player.uuid;
+        let data = ChunkCodec::compress_data(player.clone(), self.compression).await?;
+
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move |db| {
+            let mut rw_tx = db.write_txn()?;
+            let players = db
+                .open_database::<U128<BE>, Bytes>(&rw_tx, Some(PLAYER_TABLE))?
+                .ok_or_else(|| Error::TableMissing(PLAYER_TABLE.to_string()))?;
+            players.put(&mut rw_tx, &uuid, &data)?;
+            rw_tx.commit()?;
+            Ok(())
+        })
+        .await
+        .unwrap()?;
+        Ok(())
+    }
+
+    /// Load the state of a player, `None` if the player never played on this world
+    pub async fn load_player(&self, uuid: u128) -> Result<Option<PlayerData>, Error> {
+        let tsk_db = self.db.clone();
+        let data = spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+            // Databases opened read-only may predate the table
+            let Some(players) = db.open_database::<U128<BE>, Bytes>(&ro_tx, Some(PLAYER_TABLE))?
+            else {
+                return Ok(None);
+            };
+            Ok(players.get(&ro_tx, &uuid)?.map(|data| data.to_vec()))
+        })
+        .await
+        .unwrap()?;
+
+        match data {
+            Some(data) => Ok(Some(
+                ChunkCodec::decompress_data::<PlayerData>(&data, self.compression).await?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlayerData;
+    use crate::database::chunks::tests::test_database;
+
+    #[tokio::test]
+    async fn players_are_saved() {
+        let database = test_database().await;
+        assert!(database.load_player(42).await.unwrap().is_none());
+
+        let player = PlayerData {
+            uuid: 42,
+            x: 12.5,
+            y: 70.0,
+            z: -3.0,
+            yaw: 90.0,
+            pitch: 10.0,
+            dimension: "overworld".to_string(),
+            gamemode: 1,
+            abilities: 0x04,
+            last_seen: PlayerData::now(),
+        };
+        database.save_player(&player).await.unwrap();
+        assert_eq!(database.load_player(42).await.unwrap(), Some(player));
+    }
+}
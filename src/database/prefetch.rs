@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, trace};
+
+use super::spawn_blocking_db;
+use crate::database::encoding::ChunkCodec;
+use crate::database::keys::table_key;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::chunk_format::Chunk;
+use crate::world::dimension::ChunkPos;
+
+/// Handle to a prefetch started by [`Database::prefetch_radius`]
+///
+/// Dropping the handle lets the prefetch run to completion, call [`PrefetchHandle::cancel`] to
+/// stop it early, e.g. when the player already moved on.
+pub struct PrefetchHandle {
+    cancelled: Arc<AtomicBool>,
+    task: JoinHandle<Result<usize, Error>>,
+}
+
+impl PrefetchHandle {
+    /// Stop loading chunks as soon as possible. Chunks already loaded stay in the cache
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Wait for the prefetch to end, returns the number of chunks loaded into the cache
+    pub async fn wait(self) -> Result<usize, Error> {
+        self.task.await?
+    }
+}
+
+/// Positions within `radius` chunks of `center`, ring by ring starting from the center
+pub(crate) fn spiral(center: &ChunkPos, radius: i32) -> Vec<ChunkPos> {
+    let radius = radius.max(0);
+    let side = (2 * radius + 1) as usize;
+    let mut positions = Vec::with_capacity(side * side);
+    positions.push(center.clone());
+
+    for ring in 1..=radius {
+        // Walk the ring clockwise from its top left corner
+        let (mut x, mut z) = (-ring, -ring);
+        for (dx, dz) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+            for _ in 0..2 * ring {
+                positions.push(ChunkPos::new(
+                    center.x + x,
+                    center.z + z,
+                    center.dimension.clone(),
+                ));
+                x += dx;
+                z += dz;
+            }
+        }
+    }
+    positions
+}
+
+impl Database {
+    /// Load the chunks within `radius` of `center` into the cache, nearest first
+    ///
+    /// Chunks already cached or waiting in the write-behind queue are skipped, the rest is
+    /// read within a single read transaction on the database threadpool. Runs in the
+    /// background, the returned handle can be used to cancel it or wait for it.
+    pub fn prefetch_radius(&self, center: ChunkPos, radius: i32) -> PrefetchHandle {
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let keys = spiral(&center, radius)
+            .iter()
+            .map(ChunkPos::key)
+            .filter(|key| !self.cache.contains_key(key))
+            .filter(|key| {
+                !self
+                    .write_behind
+                    .as_ref()
+                    .is_some_and(|write_behind| write_behind.is_pending(*key))
+            })
+            .collect::<Vec<u128>>();
+        let table = self.tables.table_of_key(center.key());
+
+        let db = self.db.clone();
+        let cache = self.cache.clone();
+        let compression = self.compression;
+        let task_cancelled = cancelled.clone();
+        let task = tokio::spawn(async move {
+            // Nothing was ever saved in this dimension
+            let Some(table) = table.filter(|_| !keys.is_empty()) else {
+                return Ok(0);
+            };
+
+            let tsk_db = db.clone();
+            let read_cancelled = task_cancelled.clone();
+            let chunks = spawn_blocking_db(tsk_db, move |db| {
+                let ro_tx = db.read_txn()?;
+                let database = Self::open_chunk_table(db, &ro_tx, &table)?;
+
+                let mut chunks = Vec::new();
+                for key in keys.iter() {
+                    if read_cancelled.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let Some(data) = database.get(&ro_tx, &table_key(*key))? else {
+                        continue;
+                    };
+                    // Corrupted chunks are dealt with when they are actually requested
+                    match ChunkCodec::decompress_data_sync::<Chunk>(data, compression) {
+                        Ok(chunk) => chunks.push((*key, chunk)),
+                        Err(e) => trace!("Not prefetching chunk {:X}: {}", key, e),
+                    }
+                }
+                Ok(chunks)
+            })
+            .await
+            .map_err(|_| Error::DatabaseError("Prefetch task was cancelled".to_string()))??;
+
+            let mut loaded = 0;
+            for (key, chunk) in chunks {
+                if task_cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Never replace a chunk cached in the meantime, it may be newer
+                if cache.entry(key).or_insert(Arc::new(chunk)).await.is_fresh() {
+                    loaded += 1;
+                }
+            }
+            debug!("Prefetched {} chunks", loaded);
+            Ok(loaded)
+        });
+
+        PrefetchHandle { cancelled, task }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::spiral;
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[test]
+    fn spiral_starts_from_the_center() {
+        let positions = spiral(&ChunkPos::overworld(10, -10), 2);
+        assert_eq!(positions.len(), 25);
+        assert_eq!(positions[0], ChunkPos::overworld(10, -10));
+        // The first ring is made of the direct neighbours
+        assert!(positions[1..9]
+            .iter()
+            .all(|pos| (pos.x - 10).abs() <= 1 && (pos.z + 10).abs() <= 1));
+        let unique = positions.iter().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 25);
+    }
+
+    #[tokio::test]
+    async fn prefetch_loads_missing_chunks() {
+        let database = test_database().await;
+        let mut values = Vec::new();
+        for pos in spiral(&ChunkPos::overworld(0, 0), 1) {
+            let data = ChunkCodec::compress_data(test_chunk(pos.x, pos.z), database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(pos.key(), data));
+        }
+        database.batch_insert(values).await.unwrap();
+        database.cache.invalidate_all();
+
+        let loaded = database
+            .prefetch_radius(ChunkPos::overworld(0, 0), 2)
+            .wait()
+            .await
+            .unwrap();
+        assert_eq!(loaded, 9);
+        // Everything is cached now
+        let loaded = database
+            .prefetch_radius(ChunkPos::overworld(0, 0), 1)
+            .wait()
+            .await
+            .unwrap();
+        assert_eq!(loaded, 0);
+    }
+}
@@ -0,0 +1,164 @@
+use heed::types::{Bytes, Str};
+use heed::{EnvFlags, EnvOpenOptions};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::debug;
+
+use super::{start_threadpool, SharedEnv, LMDB_MAX_DBS, LMDB_MAX_READERS, LMDB_MIN_PAGE_SIZE};
+use crate::database::backend::ChunkStore;
+use crate::database::cache::{build_cache, CacheCounters};
+use crate::database::dimensions::DimensionTables;
+use crate::database::dirty::DirtyChunks;
+use crate::database::disk_space::DiskGuard;
+use crate::database::encoding::Compression;
+use crate::database::entities::EntityCache;
+use crate::database::keys::KEY_FORMAT;
+use crate::database::metrics::DbMetrics;
+use crate::database::Database;
+use crate::utils::config::Database as DatabaseConfig;
+use crate::utils::error::Error;
+
+impl Database {
+    /// Open the database located at `world_path` without write access
+    ///
+    /// Meant for tooling inspecting a world, even while the server is running. Nothing is
+    /// created or migrated, so the database must have been opened by the server at least once,
+    /// and every method modifying it returns [`Error::ReadOnly`].
+    pub async fn open_read_only(world_path: &Path) -> Result<Database, Error> {
+        debug!("Opening database at {} (read-only)", world_path.display());
+
+        let mut opts = EnvOpenOptions::new();
+        opts.max_readers(LMDB_MAX_READERS)
+            .map_size(LMDB_MIN_PAGE_SIZE)
+            .max_dbs(LMDB_MAX_DBS);
+
+        // Safe as the environment is never written to from this handle
+        let lmdb = unsafe { opts.flags(EnvFlags::READ_ONLY).open(world_path)? };
+
+        start_threadpool();
+
+        let ro_tx = lmdb.read_txn()?;
+        let meta = lmdb
+            .open_database::<Str, Bytes>(&ro_tx, Some("meta"))?
+            .ok_or(Error::DatabaseError(format!(
+                "No database found at {}",
+                world_path.display()
+            )))?;
+        let compression = match meta.get(&ro_tx, "compression")? {
+            Some(&[id]) => Compression::from_id(id).ok_or(Error::DatabaseError(format!(
+                "Unknown compression id {id} in database metadata"
+            )))?,
+            _ => {
+                return Err(Error::DatabaseError(
+                    "Invalid compression entry in database metadata".to_string(),
+                ))
+            }
+        };
+        let up_to_date = meta.get(&ro_tx, "key_format")? == Some(&[KEY_FORMAT][..])
+            && meta.get(&ro_tx, "chunk_versions")?.is_some();
+        if !up_to_date {
+            return Err(Error::DatabaseError(
+                "Database has to be migrated, open it with the server first".to_string(),
+            ));
+        }
+        let tables = Arc::new(DimensionTables::load(&lmdb, &ro_tx)?);
+        drop(ro_tx);
+
+        let config = DatabaseConfig::default();
+        let cache_counters = Arc::new(CacheCounters::default());
+        let cache = build_cache(&config, cache_counters.clone(), None);
+        // Never used as chunks can't be modified
+        let db = SharedEnv::new(lmdb);
+        let store = ChunkStore::Lmdb(db.clone());
+        let metrics = Arc::new(DbMetrics::default());
+        let dirty = Arc::new(DirtyChunks::new(
+            store.clone(),
+            tables.clone(),
+            compression,
+            None,
+            metrics.clone(),
+        ));
+
+        Ok(Database {
+            db,
+            store,
+            cache: Arc::new(cache),
+            cache_counters,
+            metrics,
+            disk_guard: DiskGuard::new(0),
+            compression,
+            tables,
+            write_behind: None,
+            dirty,
+            entities: EntityCache::default(),
+            scan_batch_size: config.scan_batch_size,
+            path: world_path.to_path_buf(),
+            read_only: true,
+        })
+    }
+
+    /// Whether the database was opened with [`Database::open_read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`Error::ReadOnly`] if the database can't be modified
+    pub(crate) fn check_writable(&self) -> Result<(), Error> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::database::Database;
+    use crate::utils::error::Error;
+    use crate::world::dimension::{ChunkPos, Dimension};
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn read_only_rejects_writes() {
+        let database = test_database().await;
+        let data = ChunkCodec::compress_data(test_chunk(3, 3), database.compression())
+            .await
+            .unwrap();
+        database
+            .batch_insert(vec![SerializedChunk::new(
+                ChunkPos::overworld(3, 3).key(),
+                data.clone(),
+            )])
+            .await
+            .unwrap();
+        let path = database.path.clone();
+        database.close();
+
+        let database = Database::open_read_only(&path).await.unwrap();
+        assert!(database
+            .get_chunk(&ChunkPos::overworld(3, 3))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(matches!(
+            database
+                .batch_insert(vec![SerializedChunk::new(
+                    ChunkPos::overworld(4, 4).key(),
+                    data
+                )])
+                .await,
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(
+            database.delete_chunk(&ChunkPos::overworld(3, 3)).await,
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(
+            database.drop_dimension(&Dimension::Overworld).await,
+            Err(Error::ReadOnly)
+        ));
+    }
+}
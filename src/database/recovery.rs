@@ -0,0 +1,180 @@
+use byteorder::BE;
+use heed::types::{Bytes, U128};
+use heed::Env;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::spawn_blocking_db;
+use crate::database::backend::ChunkStore;
+use crate::database::encoding::{ChunkCodec, Compression};
+use crate::database::keys::{chunk_key_position, dimension_hash, table_key};
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::chunk_format::Chunk;
+
+/// Table undecodable chunks are moved to
+pub(crate) const CORRUPT_TABLE: &str = "chunks_corrupt";
+
+/// Result of [`Database::verify_all`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of chunks scanned
+    pub checked: usize,
+    /// Number of undecodable chunks moved to the `chunks_corrupt` table
+    pub quarantined: usize,
+    /// Number of quarantined chunks whose checksum didn't match, most likely damaged on disk
+    pub checksum_mismatches: usize,
+}
+
+fn log_corrupt_chunk(key: u128, len: usize, e: &Error) {
+    let (x, z) = chunk_key_position(key);
+    error!(
+        "Chunk {:X} ({}, {}) is corrupted ({} bytes): {}. Moving it to quarantine",
+        key, x, z, len, e
+    );
+}
+
+impl Database {
+    /// Move chunks from their dimension table to the `chunks_corrupt` table within a single
+    /// write transaction
+    pub(super) fn quarantine_chunks_in_database(
+        db: &Env,
+        chunks: &[(Arc<str>, u128)],
+    ) -> Result<(), Error> {
+        // Initialize write transaction and open the quarantine table
+        let mut rw_tx = db.write_txn()?;
+        let corrupt = db
+            .open_database::<U128<BE>, Bytes>(&rw_tx, Some(CORRUPT_TABLE))?
+            .ok_or_else(|| Error::TableMissing(CORRUPT_TABLE.to_string()))?;
+
+        for (table, key) in chunks {
+            let table = Self::open_chunk_table(db, &rw_tx, table)?;
+            let Some(data) = table
+                .get(&rw_tx, &table_key(*key))?
+                .map(|data| data.to_vec())
+            else {
+                continue;
+            };
+            corrupt.put(&mut rw_tx, key, &data)?;
+            table.delete(&mut rw_tx, &table_key(*key))?;
+        }
+
+        // Commit changes
+        rw_tx.commit()?;
+        Ok(())
+    }
+
+    /// Decode a raw chunk read from the dimension table `table`
+    ///
+    /// Chunks stored in an older format are upgraded and stored again. If it can't be decoded,
+    /// the value is moved to the `chunks_corrupt` table and `Ok(None)` is returned so the chunk
+    /// can be regenerated instead of failing the caller. Nothing is written if `read_only`.
+    pub(super) async fn decode_chunk(
+        store: &ChunkStore,
+        read_only: bool,
+        table: &str,
+        key: u128,
+        data: &[u8],
+        compression: Compression,
+    ) -> Result<Option<Chunk>, Error> {
+        let decoded = ChunkCodec::decompress_versioned::<Chunk>(data, compression);
+        Self::handle_decoded_chunk(store, read_only, table, key, data, decoded, compression).await
+    }
+
+    /// Second half of [`Database::decode_chunk`], for chunks decompressed by the caller
+    pub(super) async fn handle_decoded_chunk(
+        store: &ChunkStore,
+        read_only: bool,
+        table: &str,
+        key: u128,
+        data: &[u8],
+        decoded: crate::Result<(Chunk, bool)>,
+        compression: Compression,
+    ) -> Result<Option<Chunk>, Error> {
+        match decoded {
+            Ok((chunk, false)) => Ok(Some(chunk)),
+            Ok((chunk, true)) => {
+                if read_only {
+                    return Ok(Some(chunk));
+                }
+                Self::store_upgraded_chunk(store, table, key, data, chunk.clone(), compression)
+                    .await;
+                Ok(Some(chunk))
+            }
+            Err(e) => {
+                if read_only {
+                    error!("Chunk {:X} is corrupted ({} bytes): {}", key, data.len(), e);
+                    return Ok(None);
+                }
+                log_corrupt_chunk(key, data.len(), &e);
+                store.quarantine(vec![(Arc::from(table), key)]).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Scan every chunk of the database and quarantine the ones that can't be decoded
+    ///
+    /// Meant as a maintenance task, e.g. after a crash. Chunks queued by the write-behind mode
+    /// are committed first.
+    pub async fn verify_all(&self) -> Result<VerifyReport, Error> {
+        self.check_writable()?;
+        self.flush().await?;
+
+        let tsk_db = self.db.clone();
+        let compression = self.compression;
+        let tables = self.tables.all_with_dimensions();
+        let (checked, checksum_mismatches, corrupt) = spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+
+            let mut checked = 0usize;
+            let mut mismatches = 0usize;
+            let mut corrupt = Vec::new();
+            for (dimension, table) in &tables {
+                let chunks = Self::open_chunk_table(db, &ro_tx, table)?;
+                let hash = (dimension_hash(dimension) as u128) << 64;
+                for entry in chunks.iter(&ro_tx)? {
+                    let (key, data) = entry?;
+                    let key = hash | key as u128;
+                    checked += 1;
+                    if let Err(e) = ChunkCodec::decompress_data_sync::<Chunk>(data, compression) {
+                        if matches!(e, Error::ChecksumMismatch { .. }) {
+                            mismatches += 1;
+                        }
+                        log_corrupt_chunk(key, data.len(), &e);
+                        corrupt.push((table.clone(), key));
+                    }
+                }
+            }
+            Ok((checked, mismatches, corrupt))
+        })
+        .await
+        .unwrap()?;
+
+        if !corrupt.is_empty() {
+            let tsk_db = self.db.clone();
+            let chunks = corrupt.clone();
+            spawn_blocking_db(tsk_db, move |db| {
+                Self::quarantine_chunks_in_database(db, &chunks)
+            })
+            .await
+            .unwrap()?;
+            // Make sure no stale copy survives in the cache
+            for (_, key) in &corrupt {
+                self.cache.remove(key).await;
+            }
+        }
+
+        info!(
+            "Verified {} chunks, {} quarantined ({} checksum mismatches)",
+            checked,
+            corrupt.len(),
+            checksum_mismatches
+        );
+        Ok(VerifyReport {
+            checked,
+            quarantined: corrupt.len(),
+            checksum_mismatches,
+        })
+    }
+}
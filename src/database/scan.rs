@@ -0,0 +1,151 @@
+//! Streaming scan of every chunk
+//!
+//! A blocking task walks the dimension tables a batch at a time and sends the raw values
+//! through a bounded channel, so at most a couple of batches are held in memory and the task
+//! waits while the consumer is slow. Values are only decoded on the consumer side.
+
+use futures::{stream, Stream, StreamExt};
+use std::ops::Bound;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::{SharedEnv, LMDB_READER_SYNC};
+use crate::database::encoding::ChunkCodec;
+use crate::database::keys::dimension_hash;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::chunk_format::Chunk;
+
+type RawEntry = Result<(u128, Vec<u8>), Error>;
+
+/// Read up to `batch_size` entries of `table` following the key `after`
+///
+/// Each batch uses its own read transaction, so the map can be resized between two batches.
+fn read_batch(
+    db: &SharedEnv,
+    table: &str,
+    after: Option<u64>,
+    batch_size: usize,
+) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+    let _read_lock = LMDB_READER_SYNC
+        .read()
+        .expect("Database RWLock has been poisoned. A thread should have crashed somewhere.");
+    let db = db.get()?;
+    let ro_tx = db.read_txn()?;
+    let database = Database::open_chunk_table(&db, &ro_tx, table)?;
+
+    let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+    let entries = database
+        .range(&ro_tx, &(start, Bound::Unbounded))?
+        .take(batch_size)
+        .map(|entry| entry.map(|(key, data)| (key, data.to_vec())))
+        .collect::<Result<_, heed::Error>>()?;
+    Ok(entries)
+}
+
+/// Send every entry of `tables` to `tx`, stops early once the receiver is dropped
+fn scan_tables(
+    db: SharedEnv,
+    tables: Vec<(Arc<str>, Arc<str>)>,
+    batch_size: usize,
+    tx: mpsc::Sender<RawEntry>,
+) {
+    for (dimension, table) in tables {
+        let hash = (dimension_hash(&dimension) as u128) << 64;
+        let mut after = None;
+        loop {
+            let batch = match read_batch(&db, &table, after, batch_size) {
+                Ok(batch) => batch,
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    return;
+                }
+            };
+            let Some((last, _)) = batch.last() else {
+                break;
+            };
+            after = Some(*last);
+            let complete = batch.len() < batch_size;
+
+            for (key, data) in batch {
+                if tx.blocking_send(Ok((hash | key as u128, data))).is_err() {
+                    return;
+                }
+            }
+            if complete {
+                break;
+            }
+        }
+    }
+}
+
+impl Database {
+    /// Stream every persisted chunk with its key, dimension by dimension
+    ///
+    /// Chunks are read `scan_batch_size` at a time, so the whole database is never loaded into
+    /// memory. The scan isn't a snapshot: chunks written while it runs may or may not be
+    /// included. Chunks still in the write-behind queue or only modified in the cache are
+    /// skipped, call [`Database::flush`] first to include them.
+    pub fn scan_chunks(&self) -> impl Stream<Item = Result<(u128, Chunk), Error>> {
+        let batch_size = self.scan_batch_size.max(1);
+        let (tx, rx) = mpsc::channel::<RawEntry>(batch_size);
+
+        let db = self.db.clone();
+        let mut tables = self.tables.all_with_dimensions();
+        tables.sort();
+        tokio::task::spawn_blocking(move || scan_tables(db, tables, batch_size, tx));
+
+        let compression = self.compression;
+        stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|entry| (entry, rx))
+        })
+        .map(move |entry| {
+            let (key, data) = entry?;
+            let chunk = ChunkCodec::decompress_data_sync::<Chunk>(&data, compression)?;
+            Ok((key, chunk))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use crate::database::chunks::tests::test_chunk;
+    use crate::database::encoding::ChunkCodec;
+    use crate::database::keys::chunk_key_position;
+    use crate::database::open_database;
+    use crate::utils::config::Database as DatabaseConfig;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn scan_visits_every_chunk() {
+        let path = std::env::temp_dir().join(format!("ferrumc-test-{}", uuid::Uuid::new_v4()));
+        let config = DatabaseConfig {
+            scan_batch_size: 3,
+            ..DatabaseConfig::default()
+        };
+        let database = open_database(path, &config).await.unwrap();
+        let mut values = Vec::new();
+        for x in 0..10 {
+            let data = ChunkCodec::compress_data(test_chunk(x, -x), database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(ChunkPos::overworld(x, -x).key(), data));
+        }
+        database.batch_insert(values).await.unwrap();
+
+        let mut positions = database
+            .scan_chunks()
+            .map(|entry| {
+                let (key, chunk) = entry.unwrap();
+                assert_eq!(chunk_key_position(key), (chunk.x_pos, chunk.z_pos));
+                chunk.x_pos
+            })
+            .collect::<Vec<_>>()
+            .await;
+        positions.sort();
+        assert_eq!(positions, (0..10).collect::<Vec<_>>());
+    }
+}
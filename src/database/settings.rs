@@ -0,0 +1,79 @@
+//! Server settings changed in game
+//!
+//! Settings players change while playing, like the difficulty, are kept in the `settings` table
+//! so they survive restarts. Values are small raw bytes, each setting picks its own encoding.
+
+use heed::types::{Bytes, Str};
+use heed::{Env, RwTxn};
+
+use super::spawn_blocking_db;
+use crate::database::Database;
+use crate::utils::error::Error;
+
+pub(crate) const SETTINGS_TABLE: &str = "settings";
+
+/// Create the `settings` table if it doesn't exist yet
+pub(super) fn create_settings_table(db: &Env, rw_tx: &mut RwTxn) -> Result<(), heed::Error> {
+    if db
+        .open_database::<Str, Bytes>(rw_tx, Some(SETTINGS_TABLE))?
+        .is_none()
+    {
+        db.create_database::<Str, Bytes>(rw_tx, Some(SETTINGS_TABLE))?;
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Save the setting `key`, replacing its previous value
+    pub async fn save_setting(&self, key: &'static str, value: Vec<u8>) -> Result<(), Error> {
+        self.check_writable()?;
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move |db| {
+            let mut rw_tx = db.write_txn()?;
+            let settings = db
+                .open_database::<Str, Bytes>(&rw_tx, Some(SETTINGS_TABLE))?
+                .ok_or_else(|| Error::TableMissing(SETTINGS_TABLE.to_string()))?;
+            settings.put(&mut rw_tx, key, &value)?;
+            rw_tx.commit()?;
+            Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Value of the setting `key`, `None` if it was never saved
+    pub async fn load_setting(&self, key: &'static str) -> Result<Option<Vec<u8>>, Error> {
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+            // Databases opened read-only may predate the table
+            let Some(settings) = db.open_database::<Str, Bytes>(&ro_tx, Some(SETTINGS_TABLE))?
+            else {
+                return Ok(None);
+            };
+            Ok(settings.get(&ro_tx, key)?.map(|value| value.to_vec()))
+        })
+        .await
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::test_database;
+
+    #[tokio::test]
+    async fn settings_are_saved() {
+        let database = test_database().await;
+        assert_eq!(database.load_setting("difficulty").await.unwrap(), None);
+
+        database
+            .save_setting("difficulty", vec![3, 1])
+            .await
+            .unwrap();
+        assert_eq!(
+            database.load_setting("difficulty").await.unwrap(),
+            Some(vec![3, 1])
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use byteorder::BE;
+use heed::types::{Bytes, Str, U64};
+use std::fmt;
+use std::sync::Arc;
+
+use super::spawn_blocking_db;
+use crate::database::disk_space::available_space;
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// Size and usage of the database, see [`Database::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatabaseStats {
+    /// Number of chunks stored in each dimension
+    pub dimensions: Vec<(Arc<str>, u64)>,
+    /// Size of the memory map, the maximum size of the database before it has to grow
+    pub map_size: usize,
+    pub page_size: u32,
+    /// Pages in use, including the free ones
+    pub used_pages: usize,
+    /// Pages that are not used anymore but haven't been reused yet
+    pub free_pages: usize,
+    /// Size of the database file
+    pub disk_size: u64,
+    /// Space left on the volume holding the database
+    pub disk_available: u64,
+}
+
+impl DatabaseStats {
+    /// Number of chunks of every dimension
+    pub fn entries(&self) -> u64 {
+        self.dimensions.iter().map(|(_, entries)| entries).sum()
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.used_pages as u64 * self.page_size as u64
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.free_pages as u64 * self.page_size as u64
+    }
+}
+
+impl fmt::Display for DatabaseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: f64 = 1024.0 * 1024.0;
+        writeln!(f, "{} chunks", self.entries())?;
+        for (dimension, entries) in &self.dimensions {
+            writeln!(f, "  {}: {} chunks", dimension, entries)?;
+        }
+        writeln!(
+            f,
+            "{:.1} MiB used ({:.1} MiB free) of a {:.1} MiB map",
+            self.used_bytes() as f64 / MIB,
+            self.free_bytes() as f64 / MIB,
+            self.map_size as f64 / MIB
+        )?;
+        write!(
+            f,
+            "{:.1} MiB on disk ({:.1} MiB available on the volume)",
+            self.disk_size as f64 / MIB,
+            self.disk_available as f64 / MIB
+        )
+    }
+}
+
+impl Database {
+    /// Gather the size and usage of the database
+    ///
+    /// Only reads the LMDB statistics within a read transaction, the chunks aren't scanned.
+    pub async fn stats(&self) -> Result<DatabaseStats, Error> {
+        let tsk_db = self.db.clone();
+        let dimensions = self.tables.all_with_dimensions();
+        let mut stats = spawn_blocking_db(tsk_db, move |db| {
+            let ro_tx = db.read_txn()?;
+            let mut stats = DatabaseStats::default();
+
+            for (dimension, table) in &dimensions {
+                let entries = db
+                    .open_database::<U64<BE>, Bytes>(&ro_tx, Some(table))?
+                    .map(|table| table.len(&ro_tx))
+                    .transpose()?
+                    .unwrap_or_default();
+                stats.dimensions.push((dimension.clone(), entries));
+            }
+
+            let meta = db
+                .open_database::<Str, Bytes>(&ro_tx, Some("meta"))?
+                .ok_or_else(|| Error::TableMissing("meta".to_string()))?;
+            stats.page_size = meta.stat(&ro_tx)?.page_size;
+            // The page count opens its own read transaction, a thread can't have two
+            drop(ro_tx);
+
+            let info = db.info();
+            stats.map_size = info.map_size;
+            stats.used_pages = info.last_page_number + 1;
+
+            let data_bytes = db.non_free_pages_size()? as usize;
+            stats.free_pages = stats
+                .used_pages
+                .saturating_sub(data_bytes / stats.page_size.max(1) as usize);
+            stats.disk_size = db.real_disk_size()?;
+
+            Ok(stats)
+        })
+        .await
+        .unwrap()?;
+
+        let path = self.path.clone();
+        stats.disk_available =
+            tokio::task::spawn_blocking(move || available_space(&path)).await??;
+        stats
+            .dimensions
+            .sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn stats_count_chunks() {
+        let database = test_database().await;
+        let mut values = Vec::new();
+        for x in 0..4 {
+            let data = ChunkCodec::compress_data(test_chunk(x, 0), database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(ChunkPos::overworld(x, 0).key(), data));
+        }
+        database.batch_insert(values).await.unwrap();
+
+        let stats = database.stats().await.unwrap();
+        assert_eq!(stats.entries(), 4);
+        assert_eq!(stats.dimensions.len(), 1);
+        assert!(stats.used_bytes() > 0);
+        assert!(stats.map_size as u64 >= stats.used_bytes());
+        assert!(stats.disk_available > 0);
+    }
+}
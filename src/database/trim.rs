@@ -0,0 +1,227 @@
+//! Deletion of the chunks outside a world border
+//!
+//! The dimension table is walked in key order, a bounded number of chunks per write
+//! transaction, so trimming millions of chunks never builds one huge transaction.
+
+use std::ops::Bound;
+use tracing::info;
+
+use super::spawn_blocking_db;
+use crate::database::keys::{chunk_key_position, dimension_hash, key_dimension_hash};
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::dimension::Dimension;
+
+/// Number of chunks checked, and at most deleted, by each trim transaction
+const TRIM_BATCH_SIZE: usize = 1000;
+
+/// Result of [`Database::trim_outside`] and [`Database::trim_outside_circle`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrimReport {
+    /// Number of deleted chunks
+    pub deleted: u64,
+    /// Size of the deleted chunks, the database only gets smaller once compacted
+    pub reclaimed: u64,
+}
+
+/// Area whose chunks are kept
+#[derive(Debug, Clone, Copy)]
+enum Border {
+    Square { center: (i32, i32), radius: i32 },
+    Circle { center: (i32, i32), radius: i32 },
+}
+
+impl Border {
+    fn contains(&self, (x, z): (i32, i32)) -> bool {
+        match *self {
+            Border::Square { center, radius } => {
+                (x as i64 - center.0 as i64).abs() <= radius as i64
+                    && (z as i64 - center.1 as i64).abs() <= radius as i64
+            }
+            Border::Circle { center, radius } => {
+                let (dx, dz) = (x as i64 - center.0 as i64, z as i64 - center.1 as i64);
+                dx * dx + dz * dz <= radius as i64 * radius as i64
+            }
+        }
+    }
+}
+
+impl Database {
+    /// Delete every chunk of `dimension` outside the square of `radius_chunks` chunks around
+    /// the chunk `center`
+    ///
+    /// Chunks are deleted a thousand at most per transaction, from the database and the cache.
+    /// Returns the number of deleted chunks and their approximate size.
+    pub async fn trim_outside(
+        &self,
+        dimension: &Dimension,
+        center: (i32, i32),
+        radius_chunks: i32,
+    ) -> Result<TrimReport, Error> {
+        let border = Border::Square {
+            center,
+            radius: radius_chunks,
+        };
+        self.trim(dimension, border).await
+    }
+
+    /// Like [`Database::trim_outside`], keeping a circle instead of a square
+    pub async fn trim_outside_circle(
+        &self,
+        dimension: &Dimension,
+        center: (i32, i32),
+        radius_chunks: i32,
+    ) -> Result<TrimReport, Error> {
+        let border = Border::Circle {
+            center,
+            radius: radius_chunks,
+        };
+        self.trim(dimension, border).await
+    }
+
+    async fn trim(&self, dimension: &Dimension, border: Border) -> Result<TrimReport, Error> {
+        self.check_writable()?;
+        // Commit queued writes first, so they can't bring the chunks back afterwards
+        self.flush().await?;
+
+        let dimension_hash = dimension_hash(dimension.name());
+        let hash = (dimension_hash as u128) << 64;
+        let mut report = TrimReport::default();
+        if let Some(table) = self.tables.check(dimension.name())? {
+            let mut after = None;
+            loop {
+                let tsk_db = self.db.clone();
+                let task_table = table.clone();
+                let (deleted, last) = spawn_blocking_db(tsk_db, move |db| {
+                    let mut rw_tx = db.write_txn()?;
+                    let database = Self::open_chunk_table(db, &rw_tx, &task_table)?;
+
+                    let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+                    let mut last = None;
+                    let mut outside = Vec::new();
+                    for entry in database
+                        .range(&rw_tx, &(start, Bound::Unbounded))?
+                        .take(TRIM_BATCH_SIZE)
+                    {
+                        let (key, data) = entry?;
+                        last = Some(key);
+                        if !border.contains(chunk_key_position(hash | key as u128)) {
+                            outside.push((key, data.len() as u64));
+                        }
+                    }
+                    for (key, _) in outside.iter() {
+                        database.delete(&mut rw_tx, key)?;
+                    }
+
+                    rw_tx.commit()?;
+                    Ok((outside, last))
+                })
+                .await??;
+
+                for (key, size) in deleted {
+                    let key = hash | key as u128;
+                    // Unflag first, the eviction would persist the chunk again otherwise
+                    self.dirty.take(key);
+                    self.cache.remove(&key).await;
+                    report.deleted += 1;
+                    report.reclaimed += size;
+                }
+                match last {
+                    Some(last) => after = Some(last),
+                    None => break,
+                }
+            }
+        }
+
+        // Chunks only modified in the cache were never persisted
+        let cached = self
+            .cache
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|key| key_dimension_hash(*key) == dimension_hash)
+            .filter(|key| !border.contains(chunk_key_position(*key)))
+            .collect::<Vec<u128>>();
+        for key in cached {
+            self.dirty.take(key);
+            if self.cache.remove(&key).await.is_some() {
+                report.deleted += 1;
+            }
+        }
+
+        info!(
+            "Trimmed {} chunks ({} bytes) outside of {:?} in {}",
+            report.deleted,
+            report.reclaimed,
+            border,
+            dimension.name()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::database::Database;
+    use crate::world::dimension::{ChunkPos, Dimension};
+    use crate::world::importing::SerializedChunk;
+
+    async fn insert_grid(database: &Database, radius: i32) {
+        let mut values = Vec::new();
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                let data = ChunkCodec::compress_data(test_chunk(x, z), database.compression())
+                    .await
+                    .unwrap();
+                values.push(SerializedChunk::new(ChunkPos::overworld(x, z).key(), data));
+            }
+        }
+        database.batch_insert(values).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn trim_keeps_the_square() {
+        let database = test_database().await;
+        insert_grid(&database, 3).await;
+
+        let report = database
+            .trim_outside(&Dimension::Overworld, (0, 0), 1)
+            .await
+            .unwrap();
+        assert_eq!(report.deleted, 40);
+        assert!(report.reclaimed > 0);
+
+        database.cache.invalidate_all();
+        assert_eq!(database.stats().await.unwrap().entries(), 9);
+        assert!(!database
+            .chunk_exists(&ChunkPos::overworld(2, 0))
+            .await
+            .unwrap());
+        assert!(database
+            .chunk_exists(&ChunkPos::overworld(1, -1))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn trim_keeps_the_circle() {
+        let database = test_database().await;
+        insert_grid(&database, 2).await;
+
+        let report = database
+            .trim_outside_circle(&Dimension::Overworld, (0, 0), 1)
+            .await
+            .unwrap();
+        // Only the center and its direct neighbours are within one chunk
+        assert_eq!(report.deleted, 20);
+        assert!(!database
+            .chunk_exists(&ChunkPos::overworld(1, 1))
+            .await
+            .unwrap());
+        assert!(database
+            .chunk_exists(&ChunkPos::overworld(0, 1))
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+//! Chunk format versions
+//!
+//! Every stored chunk is prefixed with its [`Versioned::VERSION`]. Chunks written with an older
+//! layout are upgraded when they are read, then stored again in the current layout so the
+//! upgrade is only done once.
+
+use heed::types::Bytes;
+use heed::{Env, RwTxn};
+use std::ops::Bound;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::database::backend::ChunkStore;
+use crate::database::encoding::{ChunkCodec, Compression, Versioned};
+use crate::database::Database;
+use crate::world::chunk_format::Chunk;
+
+/// Number of entries rewritten at once by [`version_chunk_values`]
+const MIGRATION_BATCH_SIZE: usize = 1024;
+
+/// Prefix every value of `table` with the first chunk format version
+///
+/// Chunks were stored without any version before the format was versioned. Returns the number
+/// of values rewritten, 0 if the table doesn't exist.
+pub(super) fn version_chunk_values(
+    lmdb: &Env,
+    rw_tx: &mut RwTxn,
+    table: &str,
+) -> Result<usize, heed::Error> {
+    let Some(database) = lmdb.open_database::<Bytes, Bytes>(rw_tx, Some(table))? else {
+        return Ok(0);
+    };
+
+    let mut count = 0;
+    let mut last: Option<Vec<u8>> = None;
+    loop {
+        let lower = match &last {
+            Some(key) => Bound::Excluded(key.as_slice()),
+            None => Bound::Unbounded,
+        };
+        let mut batch = database
+            .range(rw_tx, &(lower, Bound::Unbounded))?
+            .take(MIGRATION_BATCH_SIZE)
+            .map(|entry| {
+                entry.map(|(key, data)| {
+                    let mut versioned = Vec::with_capacity(data.len() + 1);
+                    versioned.push(1);
+                    versioned.extend_from_slice(data);
+                    (key.to_vec(), versioned)
+                })
+            })
+            .collect::<Result<Vec<_>, heed::Error>>()?;
+
+        for (key, data) in &batch {
+            database.put(rw_tx, key, data)?;
+        }
+        count += batch.len();
+
+        if batch.len() < MIGRATION_BATCH_SIZE {
+            break;
+        }
+        last = batch.pop().map(|(key, _)| key);
+    }
+
+    info!("Added format version to {} chunks of {}", count, table);
+    Ok(count)
+}
+
+impl Database {
+    /// Store a chunk that was upgraded when read, in the current format
+    ///
+    /// Only replaces the stored value if it is still `old_data`, so a chunk saved in the
+    /// meantime is never overwritten. Failures are only logged since the chunk was read fine.
+    pub(super) async fn store_upgraded_chunk(
+        store: &ChunkStore,
+        table: &str,
+        key: u128,
+        old_data: &[u8],
+        chunk: Chunk,
+        compression: Compression,
+    ) {
+        let data = match ChunkCodec::compress_data(chunk, compression).await {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Unable to encode upgraded chunk {:X}: {}", key, e);
+                return;
+            }
+        };
+
+        let res = store
+            .replace(Arc::from(table), key, old_data.to_vec(), data)
+            .await;
+
+        match res {
+            Ok(true) => debug!(
+                "Upgraded chunk {:X} to format version {}",
+                key,
+                Chunk::VERSION
+            ),
+            Ok(false) => {}
+            Err(e) => warn!("Unable to store upgraded chunk {:X}: {}", key, e),
+        }
+    }
+}
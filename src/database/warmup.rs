@@ -0,0 +1,216 @@
+//! Cache warming
+//!
+//! The keys of the cached chunks are saved in the `hot_keys` table when the server stops, and
+//! loaded back into the cache in the background when it starts again, so the first players to
+//! join don't have to wait for every chunk to be read from the disk.
+
+use byteorder::BE;
+use heed::types::{Unit, U128};
+use heed::{Env, RwTxn};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, trace};
+
+use super::spawn_blocking_db;
+use crate::database::encoding::ChunkCodec;
+use crate::database::keys::table_key;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::chunk_format::Chunk;
+
+pub(crate) const HOT_KEYS_TABLE: &str = "hot_keys";
+
+type HotKeysTable = heed::Database<U128<BE>, Unit>;
+
+/// Number of chunks read by a single warmup transaction
+const WARMUP_BATCH_SIZE: usize = 64;
+
+/// Create the `hot_keys` table if it doesn't exist yet
+pub(super) fn create_hot_keys_table(db: &Env, rw_tx: &mut RwTxn) -> Result<(), heed::Error> {
+    if db
+        .open_database::<U128<BE>, Unit>(rw_tx, Some(HOT_KEYS_TABLE))?
+        .is_none()
+    {
+        db.create_database::<U128<BE>, Unit>(rw_tx, Some(HOT_KEYS_TABLE))?;
+    }
+    Ok(())
+}
+
+/// Warmup tasks running at once, a fraction of the database threadpool so requests for chunks
+/// always find a free thread
+fn warmup_concurrency() -> usize {
+    (num_cpus::get() / 4).max(1)
+}
+
+impl Database {
+    /// Replace the saved hot keys by the keys of the cached chunks
+    ///
+    /// Called when the server stops, for [`Database::warm_cache`] to load the same chunks on the
+    /// next start. Returns the number of saved keys.
+    pub async fn save_hot_keys(&self) -> Result<usize, Error> {
+        self.check_writable()?;
+        let capacity = self.cache.policy().max_capacity().unwrap_or(u64::MAX) as usize;
+        let keys = self
+            .cache
+            .iter()
+            .map(|(key, _)| *key)
+            .take(capacity)
+            .collect::<Vec<u128>>();
+        let saved = keys.len();
+
+        let tsk_db = self.db.clone();
+        spawn_blocking_db(tsk_db, move |db| {
+            let mut rw_tx = db.write_txn()?;
+            let table: HotKeysTable = db
+                .open_database(&rw_tx, Some(HOT_KEYS_TABLE))?
+                .ok_or_else(|| Error::TableMissing(HOT_KEYS_TABLE.to_string()))?;
+            table.clear(&mut rw_tx)?;
+            for key in keys.iter() {
+                table.put(&mut rw_tx, key, &())?;
+            }
+            rw_tx.commit()?;
+            Ok(())
+        })
+        .await??;
+
+        debug!("Saved {} hot chunk keys", saved);
+        Ok(saved)
+    }
+
+    /// Load the chunks saved by [`Database::save_hot_keys`] into the cache, in the background
+    ///
+    /// Chunks are read in small batches with bounded concurrency, so chunk requests arriving
+    /// meanwhile are served between them. Chunks already cached or waiting in the write-behind
+    /// queue are left alone, and keys whose chunk was deleted since are skipped. The returned
+    /// handle resolves to the number of chunks loaded.
+    pub fn warm_cache(&self) -> JoinHandle<Result<usize, Error>> {
+        let db = self.db.clone();
+        let cache = self.cache.clone();
+        let tables = self.tables.clone();
+        let write_behind = self.write_behind.clone();
+        let compression = self.compression;
+
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let tsk_db = db.clone();
+            let keys = spawn_blocking_db(tsk_db, move |db| {
+                let ro_tx = db.read_txn()?;
+                // Databases opened read-only may predate the table
+                let Some(table) =
+                    db.open_database::<U128<BE>, Unit>(&ro_tx, Some(HOT_KEYS_TABLE))?
+                else {
+                    return Ok(Vec::new());
+                };
+                let keys = table
+                    .iter(&ro_tx)?
+                    .map(|entry| entry.map(|(key, _)| key))
+                    .collect::<Result<Vec<u128>, heed::Error>>()?;
+                Ok(keys)
+            })
+            .await??;
+            if keys.is_empty() {
+                return Ok(0);
+            }
+
+            let mut loaded = 0;
+            for batches in keys.chunks(WARMUP_BATCH_SIZE * warmup_concurrency()) {
+                let mut tasks = Vec::new();
+                for batch in batches.chunks(WARMUP_BATCH_SIZE) {
+                    let batch = batch
+                        .iter()
+                        .copied()
+                        .filter(|key| !cache.contains_key(key))
+                        .filter(|key| {
+                            !write_behind
+                                .as_ref()
+                                .is_some_and(|write_behind| write_behind.is_pending(*key))
+                        })
+                        .filter_map(|key| Some((key, tables.table_of_key(key)?)))
+                        .collect::<Vec<_>>();
+                    tasks.push(spawn_blocking_db(db.clone(), move |db| {
+                        let ro_tx = db.read_txn()?;
+                        let mut chunks = Vec::new();
+                        for (key, table) in batch.iter() {
+                            let database = Self::open_chunk_table(db, &ro_tx, table)?;
+                            let Some(data) = database.get(&ro_tx, &table_key(*key))? else {
+                                continue;
+                            };
+                            // Corrupted chunks are dealt with when they are actually requested
+                            match ChunkCodec::decompress_data_sync::<Chunk>(data, compression) {
+                                Ok(chunk) => chunks.push((*key, chunk)),
+                                Err(e) => trace!("Not warming chunk {:X}: {}", key, e),
+                            }
+                        }
+                        Ok(chunks)
+                    }));
+                }
+
+                for task in tasks {
+                    for (key, chunk) in task.await?? {
+                        // Never replace a chunk requested in the meantime, it may be newer
+                        if cache.entry(key).or_insert(Arc::new(chunk)).await.is_fresh() {
+                            loaded += 1;
+                        }
+                    }
+                }
+                tokio::task::yield_now().await;
+            }
+
+            info!(
+                "Warmed the cache with {} of {} chunks in {:?}",
+                loaded,
+                keys.len(),
+                start.elapsed()
+            );
+            Ok(loaded)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::chunks::tests::{test_chunk, test_database};
+    use crate::database::encoding::ChunkCodec;
+    use crate::world::dimension::ChunkPos;
+    use crate::world::importing::SerializedChunk;
+
+    #[tokio::test]
+    async fn warmup_loads_the_hot_chunks() {
+        let database = test_database().await;
+        let mut values = Vec::new();
+        for x in 0..4 {
+            let data = ChunkCodec::compress_data(test_chunk(x, 0), database.compression())
+                .await
+                .unwrap();
+            values.push(SerializedChunk::new(ChunkPos::overworld(x, 0).key(), data));
+        }
+        database.batch_insert(values).await.unwrap();
+        database.cache.invalidate_all();
+
+        // Checking a chunk loads it into the cache
+        for x in 0..3 {
+            assert!(database
+                .chunk_exists(&ChunkPos::overworld(x, 0))
+                .await
+                .unwrap());
+        }
+        database.cache.run_pending_tasks().await;
+        assert_eq!(database.save_hot_keys().await.unwrap(), 3);
+
+        // A hot chunk deleted before the next start is skipped
+        database
+            .delete_chunks(&[ChunkPos::overworld(2, 0)])
+            .await
+            .unwrap();
+        database.cache.invalidate_all();
+
+        assert_eq!(database.warm_cache().await.unwrap().unwrap(), 2);
+        assert!(database
+            .cache
+            .contains_key(&ChunkPos::overworld(0, 0).key()));
+        assert!(!database
+            .cache
+            .contains_key(&ChunkPos::overworld(3, 0).key()));
+    }
+}
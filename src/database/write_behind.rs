@@ -0,0 +1,232 @@
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error};
+
+use super::{spawn_blocking_db, SharedEnv};
+use crate::database::dimensions::DimensionTables;
+use crate::database::Database;
+use crate::utils::error::Error;
+use crate::world::importing::SerializedChunk;
+
+enum WriteCommand {
+    Insert(SerializedChunk),
+    Flush(oneshot::Sender<Result<(), Error>>),
+}
+
+/// Snapshot of the write-behind writer state
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteBehindStatus {
+    /// Chunks queued but not yet committed
+    pub pending: usize,
+    /// Chunks committed by the writer since the database was opened
+    pub committed: u64,
+    /// Number of commits that failed. Failed chunks stay queued and are retried
+    pub failed_commits: u64,
+    /// Error of the last commit, cleared once a commit succeeds again
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct WriterStatus {
+    committed: AtomicU64,
+    failed_commits: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+/// Queue persisting chunks in batches instead of one write transaction per insert
+///
+/// Chunks are compressed by the caller and kept in `pending` until they are committed, so
+/// reads can still find them in between.
+#[derive(Clone)]
+pub(crate) struct WriteBehind {
+    sender: mpsc::Sender<WriteCommand>,
+    pending: Arc<DashMap<u128, Vec<u8>>>,
+    status: Arc<WriterStatus>,
+}
+
+impl WriteBehind {
+    /// Spawn the writer task, committing every `interval` or as soon as `max_pending` chunks
+    /// are queued
+    pub(crate) fn start(
+        db: SharedEnv,
+        tables: Arc<DimensionTables>,
+        interval: Duration,
+        max_pending: usize,
+    ) -> Self {
+        let max_pending = max_pending.max(1);
+        let (sender, receiver) = mpsc::channel(max_pending);
+        let pending = Arc::new(DashMap::new());
+        let status = Arc::new(WriterStatus::default());
+
+        tokio::spawn(run_writer(
+            db,
+            tables,
+            receiver,
+            interval,
+            max_pending,
+            pending.clone(),
+            status.clone(),
+        ));
+
+        Self {
+            sender,
+            pending,
+            status,
+        }
+    }
+
+    pub(crate) async fn queue(&self, chunk: SerializedChunk) -> Result<(), Error> {
+        self.pending.insert(chunk.hash(), chunk.data().clone());
+        self.sender
+            .send(WriteCommand::Insert(chunk))
+            .await
+            .map_err(|_| Error::DatabaseError("Write-behind writer has stopped".to_string()))
+    }
+
+    /// Compressed data of a chunk that is queued but not committed yet
+    pub(crate) fn pending(&self, key: u128) -> Option<Vec<u8>> {
+        self.pending.get(&key).map(|data| data.clone())
+    }
+
+    pub(crate) fn is_pending(&self, key: u128) -> bool {
+        self.pending.contains_key(&key)
+    }
+
+    pub(crate) async fn flush(&self) -> Result<(), Error> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(WriteCommand::Flush(tx))
+            .await
+            .map_err(|_| Error::DatabaseError("Write-behind writer has stopped".to_string()))?;
+        rx.await
+            .map_err(|_| Error::DatabaseError("Write-behind writer has stopped".to_string()))?
+    }
+
+    pub(crate) fn status(&self) -> WriteBehindStatus {
+        WriteBehindStatus {
+            pending: self.pending.len(),
+            committed: self.status.committed.load(Ordering::Relaxed),
+            failed_commits: self.status.failed_commits.load(Ordering::Relaxed),
+            last_error: self.status.last_error.lock().unwrap().clone(),
+        }
+    }
+}
+
+async fn run_writer(
+    db: SharedEnv,
+    tables: Arc<DimensionTables>,
+    mut receiver: mpsc::Receiver<WriteCommand>,
+    interval: Duration,
+    max_pending: usize,
+    pending: Arc<DashMap<u128, Vec<u8>>>,
+    status: Arc<WriterStatus>,
+) {
+    // Latest state of every queued chunk, so repeated updates are only written once
+    let mut buffer: HashMap<u128, SerializedChunk> = HashMap::new();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            command = receiver.recv() => match command {
+                Some(WriteCommand::Insert(chunk)) => {
+                    buffer.insert(chunk.hash(), chunk);
+                    if buffer.len() >= max_pending {
+                        let _ = commit(&db, &tables, &mut buffer, &pending, &status).await;
+                    }
+                }
+                Some(WriteCommand::Flush(reply)) => {
+                    let res = commit(&db, &tables, &mut buffer, &pending, &status).await;
+                    let _ = reply.send(res);
+                }
+                // Database dropped, commit what is left and stop
+                None => {
+                    let _ = commit(&db, &tables, &mut buffer, &pending, &status).await;
+                    break;
+                }
+            },
+            _ = ticker.tick() => {
+                let _ = commit(&db, &tables, &mut buffer, &pending, &status).await;
+            }
+        }
+    }
+}
+
+/// Commit the buffered chunks within a single write transaction
+///
+/// On failure the chunks are put back into the buffer to be retried on the next commit.
+async fn commit(
+    db: &SharedEnv,
+    tables: &Arc<DimensionTables>,
+    buffer: &mut HashMap<u128, SerializedChunk>,
+    pending: &DashMap<u128, Vec<u8>>,
+    status: &WriterStatus,
+) -> Result<(), Error> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+
+    let batch = Arc::new(buffer.drain().map(|(_, chunk)| chunk).collect::<Vec<_>>());
+    let tsk_db = db.clone();
+    let tsk_batch = batch.clone();
+    let tables = tables.clone();
+    let res = spawn_blocking_db(tsk_db, move |db| {
+        Database::insert_chunks_into_database(db, &tables, &tsk_batch)
+    })
+    .await
+    .map_err(|_| Error::DatabaseError("Write-behind commit was cancelled".to_string()))
+    .and_then(|res| res);
+
+    match res {
+        Ok(()) => {
+            debug!("Write-behind committed {} chunks", batch.len());
+            for chunk in batch.iter() {
+                // Only forget the chunk if it wasn't updated again in the meantime
+                pending.remove_if(&chunk.hash(), |_, data| data == chunk.data());
+            }
+            status
+                .committed
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+            *status.last_error.lock().unwrap() = None;
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "Write-behind failed to commit {} chunks: {}",
+                batch.len(),
+                e
+            );
+            status.failed_commits.fetch_add(1, Ordering::Relaxed);
+            *status.last_error.lock().unwrap() = Some(e.to_string());
+            let batch = Arc::try_unwrap(batch).unwrap_or_else(|batch| {
+                batch
+                    .iter()
+                    .map(|chunk| SerializedChunk::new(chunk.hash(), chunk.data().clone()))
+                    .collect()
+            });
+            buffer.extend(batch.into_iter().map(|chunk| (chunk.hash(), chunk)));
+            Err(e)
+        }
+    }
+}
+
+impl Database {
+    /// Persist the dirty chunks and commit every chunk queued by the write-behind mode
+    ///
+    /// Must be awaited before shutting down to guarantee all the chunks have been persisted.
+    pub async fn flush(&self) -> Result<(), Error> {
+        self.save_dirty().await?;
+        match &self.write_behind {
+            Some(write_behind) => write_behind.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    /// State of the write-behind writer, `None` if write-behind is disabled
+    pub fn write_behind_status(&self) -> Option<WriteBehindStatus> {
+        self.write_behind.as_ref().map(WriteBehind::status)
+    }
+}
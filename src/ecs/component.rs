@@ -1,11 +1,16 @@
 use crate::utils::prelude::*;
 use std::any::TypeId;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
+use crate::ecs::entity::{Entity, EntityKey, EntityManager};
 use crate::ecs::error::Error;
 use crate::ecs::helpers::sparse_set::SparseSet;
+use crate::ecs::query::ComponentAccess;
 use dashmap::DashMap;
+use parking_lot::Mutex;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 /// A trait for components in the ECS.
@@ -59,19 +64,73 @@ impl<'id, T: Component> std::ops::DerefMut for ComponentRefMut<'id, T> {
 }
 
 /// A storage structure for components in the ECS.
+///
+/// Each component type has a [`SparseSet`] of its own, keyed by its [`TypeId`], whose
+/// components sit next to each other, so iterating over all those of a type stays
+/// cache-friendly.
+///
+/// Components are keyed by the slot of their entity, see [`EntityKey`]. Accesses through an
+/// [`Entity`] handle of an entity its [`EntityManager`] despawned fail with
+/// [`Error::StaleEntity`] instead of reaching the entity now living in its slot.
 pub struct ComponentStorage {
     storages: DashMap<TypeId, SparseSet<RwLock<Box<dyn Component>>>>,
+    /// Entities the components belong to, handles are checked against their generations
+    entities: EntityManager,
+    /// Component types accessed by the live queries, with the number of those reading them, or
+    /// -1 for the one writing them
+    borrows: Mutex<HashMap<TypeId, isize>>,
+}
+
+/// Accesses of a live query, registered by [`ComponentStorage::borrow`] until it is dropped
+pub struct Borrows<'a> {
+    storage: &'a ComponentStorage,
+    accesses: Vec<ComponentAccess>,
+}
+
+impl Drop for Borrows<'_> {
+    fn drop(&mut self) {
+        let mut borrows = self.storage.borrows.lock();
+        for access in &self.accesses {
+            if let Entry::Occupied(mut entry) = borrows.entry(access.type_id()) {
+                let count = entry.get_mut();
+                *count = if access.is_mutable() { 0 } else { *count - 1 };
+                if *count == 0 {
+                    entry.remove();
+                }
+            }
+        }
+    }
 }
 
 // New + Insert
 impl ComponentStorage {
-    /// Creates a new instance of `ComponentStorage`.
+    /// Creates a new instance of `ComponentStorage`, for entities of its own, see
+    /// [`ComponentStorage::for_entities`].
     pub fn new() -> Self {
+        Self::for_entities(&EntityManager::new())
+    }
+
+    /// Creates a new instance of `ComponentStorage` for the entities of `entity_manager`
+    pub fn for_entities(entity_manager: &EntityManager) -> Self {
         Self {
             storages: DashMap::new(),
+            entities: entity_manager.clone(),
+            borrows: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Slot of `entity_id`, if the handle still refers to the entity living in it
+    pub(crate) fn resolve(&self, entity_id: impl EntityKey) -> Result<usize> {
+        let index = entity_id.index().ok_or(Error::ConversionError)?;
+        if let Some(generation) = entity_id.generation() {
+            let id = u32::try_from(index).map_err(|_| Error::ConversionError)?;
+            if !self.entities.is_alive(Entity { id, generation }) {
+                return Err(Error::StaleEntity(index))?;
+            }
+        }
+        Ok(index)
+    }
+
     /// Inserts a component for a given entity.
     ///
     /// # Examples
@@ -79,11 +138,11 @@ impl ComponentStorage {
     /// let storage = ComponentStorage::new();
     /// storage.insert(0, Position { x: 0.0, y: 0.0 });
     /// ```
-    pub fn insert<T: Component>(&self, entity_id: impl TryInto<usize>, component: T) -> &Self {
-        let entity_id = entity_id
-            .try_into()
-            .map_err(|_| Error::ConversionError)
-            .unwrap();
+    ///
+    /// # Panics
+    /// If `entity_id` is out of range, or a handle of a despawned entity.
+    pub fn insert<T: Component>(&self, entity_id: impl EntityKey, component: T) -> &Self {
+        let entity_id = self.resolve(entity_id).unwrap();
         let type_id = TypeId::of::<T>();
         let mut storage = self.storages.entry(type_id).or_insert_with(SparseSet::new);
         storage.insert(entity_id, RwLock::new(Box::new(component)));
@@ -108,10 +167,10 @@ impl ComponentStorage {
     /// ```
     pub async fn get<'a, T: Component + 'a>(
         &self,
-        entity_id: impl TryInto<usize>,
+        entity_id: impl EntityKey,
     ) -> Result<ComponentRef<'a, T>> {
         let type_id = TypeId::of::<T>();
-        let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
+        let entity_id = self.resolve(entity_id)?;
         let storage = self
             .storages
             .get(&type_id)
@@ -140,10 +199,10 @@ impl ComponentStorage {
     /// ```
     pub async fn get_mut<T: Component>(
         &self,
-        entity_id: impl TryInto<usize>,
+        entity_id: impl EntityKey,
     ) -> Result<ComponentRefMut<T>> {
         let type_id = TypeId::of::<T>();
-        let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
+        let entity_id = self.resolve(entity_id)?;
         let storage = self
             .storages
             .get(&type_id)
@@ -166,14 +225,72 @@ impl ComponentStorage {
     }
 }
 
+// Contains + Len + Indices, used by queries to pick the storage they iterate over
+impl ComponentStorage {
+    /// Whether the entity in slot `entity_id` has a component of type `T`, without locking it
+    pub fn contains<T: Component>(&self, entity_id: usize) -> bool {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .is_some_and(|storage| storage.get(entity_id).is_some())
+    }
+
+    /// Number of components of the type `type_id`
+    pub(crate) fn len_of(&self, type_id: TypeId) -> usize {
+        self.storages
+            .get(&type_id)
+            .map_or(0, |storage| storage.len())
+    }
+
+    /// Slots of the entities with a component of the type `type_id`, in ascending order
+    pub(crate) fn indices_of(&self, type_id: TypeId) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .storages
+            .get(&type_id)
+            .map(|storage| storage.iter().map(|(index, _)| *index).collect())
+            .unwrap_or_default();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+// Borrow
+impl ComponentStorage {
+    /// Registers the accesses of a query for as long as it lives, fails with
+    /// [`Error::BorrowConflict`] if one of them conflicts with those of a live query, whose
+    /// component locks the query would wait on, forever if both are held by the same task
+    pub(crate) fn borrow(&self, accesses: &[ComponentAccess]) -> Result<Borrows<'_>> {
+        let mut borrows = self.borrows.lock();
+        let conflict = accesses.iter().find(|access| {
+            borrows
+                .get(&access.type_id())
+                .is_some_and(|&count| access.is_mutable() || count < 0)
+        });
+        if let Some(access) = conflict {
+            return Err(Error::BorrowConflict(access.name()))?;
+        }
+        for access in accesses {
+            let count = borrows.entry(access.type_id()).or_insert(0);
+            *count = if access.is_mutable() { -1 } else { *count + 1 };
+        }
+        Ok(Borrows {
+            storage: self,
+            accesses: accesses.to_vec(),
+        })
+    }
+}
+
 // GetOrInsertWith + GetMutOrInsertWith
+//
+// Both panic on a handle of a despawned entity, like `insert`.
 impl ComponentStorage {
     pub async fn get_or_insert_with<'a, T: Component + 'a>(
         &self,
-        entity_id: impl Into<usize>,
+        entity_id: impl EntityKey,
         f: impl FnOnce() -> T,
     ) -> ComponentRef<'a, T> {
-        let entity_id = entity_id.into();
+        let entity_id = self
+            .resolve(entity_id)
+            .expect("Failed to resolve entity_id. This is a BUG!");
 
         if let Ok(component) = self.get::<T>(entity_id).await {
             return component;
@@ -188,13 +305,12 @@ impl ComponentStorage {
     }
     pub async fn get_mut_or_insert_with<T: Component>(
         &self,
-        entity_id: impl TryInto<usize>,
+        entity_id: impl EntityKey,
         f: impl FnOnce() -> T,
     ) -> ComponentRefMut<T> {
-        let entity_id = entity_id
-            .try_into()
-            .ok()
-            .expect("Failed to convert entity_id to usize. This is a BUG!");
+        let entity_id = self
+            .resolve(entity_id)
+            .expect("Failed to resolve entity_id. This is a BUG!");
 
         if let Ok(component) = self.get_mut::<T>(entity_id).await {
             return component;
@@ -217,9 +333,9 @@ impl ComponentStorage {
     /// ```ignore
     /// storage.remove::<Position>(0);
     /// ```
-    pub fn remove<T: Component>(&self, entity_id: impl Into<usize>) -> Result<()> {
+    pub fn remove<T: Component>(&self, entity_id: impl EntityKey) -> Result<()> {
         let type_id = TypeId::of::<T>();
-        let entity_id = entity_id.into();
+        let entity_id = self.resolve(entity_id)?;
         if let Some(mut storage) = self.storages.get_mut(&type_id) {
             let component = storage.get(entity_id);
             let Some(component) = component else {
@@ -233,10 +349,12 @@ impl ComponentStorage {
 
         Ok(())
     }
-    pub fn remove_all(&self, entity_id: impl Into<usize>) {
-        let entity_id = entity_id.into();
+
+    /// Removes every component in the slot `index`, of an entity being despawned, see
+    /// [`World::delete_entity`](crate::ecs::world::World::delete_entity)
+    pub(crate) fn remove_all(&self, index: usize) {
         for mut storage in self.storages.iter_mut() {
-            storage.remove(entity_id);
+            storage.remove(index);
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use tokio::sync::RwLock;
+use parking_lot::RwLock;
 
 /// Represents an entity in the ECS.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -15,7 +15,60 @@ impl From<Entity> for usize {
     }
 }
 
+/// Key of an entity in the component storage.
+///
+/// A bare index refers to whichever entity lives in the slot, while an [`Entity`] only refers to
+/// the entity it was handed out for, and stops resolving once that entity is despawned.
+pub trait EntityKey {
+    /// Slot of the entity, `None` if it doesn't fit in a `usize`
+    fn index(&self) -> Option<usize>;
+    /// Generation the key was handed out for, `None` for a bare index
+    fn generation(&self) -> Option<u32>;
+}
+
+impl EntityKey for Entity {
+    fn index(&self) -> Option<usize> {
+        Some(self.id as usize)
+    }
+
+    fn generation(&self) -> Option<u32> {
+        Some(self.generation)
+    }
+}
+
+macro_rules! impl_entity_key_for_index {
+    ($($T:ty),*) => {
+        $(
+            impl EntityKey for $T {
+                fn index(&self) -> Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+
+                fn generation(&self) -> Option<u32> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+impl_entity_key_for_index!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, isize);
+
+impl EntityKey for usize {
+    fn index(&self) -> Option<usize> {
+        Some(*self)
+    }
+
+    fn generation(&self) -> Option<u32> {
+        None
+    }
+}
+
 /// Manages entity creation, deletion, and lifecycle.
+///
+/// Clones share the same entities. The generations it keeps are the ones the
+/// [`ComponentStorage`](crate::ecs::component::ComponentStorage) of its entities checks handles
+/// against.
 pub struct EntityManager {
     inner: Arc<RwLock<EntityManagerInner>>,
 }
@@ -23,6 +76,9 @@ pub struct EntityManager {
 struct EntityManagerInner {
     generations: Vec<u32>,
     free_ids: Vec<u32>,
+    /// Slots of despawned entities whose components are still being removed, see
+    /// [`EntityManager::retire`]
+    retired_ids: Vec<u32>,
 }
 
 impl EntityManager {
@@ -32,6 +88,7 @@ impl EntityManager {
             inner: Arc::new(RwLock::new(EntityManagerInner {
                 generations: Vec::new(),
                 free_ids: Vec::new(),
+                retired_ids: Vec::new(),
             })),
         }
     }
@@ -44,7 +101,7 @@ impl EntityManager {
     /// let entity = manager.create_entity();
     /// ```
     pub async fn create_entity(&self) -> Entity {
-        let mut inner = self.inner.write().await;
+        let mut inner = self.inner.write();
         if let Some(id) = inner.free_ids.pop() {
             let generation = inner.generations[id as usize];
             Entity { id, generation }
@@ -66,20 +123,45 @@ impl EntityManager {
     /// assert!(manager.delete_entity(entity));
     /// ```
     pub async fn delete_entity(&self, entity: impl Into<usize>) -> bool {
-        let entity = entity.into();
-        let mut inner = self.inner.write().await;
-
-        if inner.free_ids.contains(&(entity as u32)) {
+        let Ok(id) = u32::try_from(entity.into()) else {
             return false;
+        };
+        let mut inner = self.inner.write();
+        match inner.generations.get(id as usize) {
+            Some(&generation) => inner.despawn(Entity { id, generation }),
+            None => false,
         }
+    }
 
-        if entity < inner.generations.len() {
-            inner.generations[entity] += 1;
-            inner.free_ids.push(entity as u32);
-            true
-        } else {
-            false
-        }
+    /// Despawns `entity`, its handles stop resolving and its slot is given to the next entity
+    /// created, with the next generation.
+    ///
+    /// Returns `false` if the handle is stale, the entity being despawned already.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut manager = EntityManager::new();
+    /// let entity = manager.create_entity();
+    /// assert!(manager.despawn(entity));
+    /// assert!(!manager.despawn(entity));
+    /// ```
+    pub async fn despawn(&self, entity: Entity) -> bool {
+        self.inner.write().despawn(entity)
+    }
+
+    /// Despawns `entity` like [`EntityManager::despawn`], but keeps its slot from the entities
+    /// created until [`EntityManager::release`] is called, so that its components can be removed
+    /// without removing those of the next entity.
+    ///
+    /// Returns `false` if the handle is stale.
+    pub(crate) fn retire(&self, entity: Entity) -> bool {
+        self.inner.write().retire(entity)
+    }
+
+    /// Gives the slot `id` of an entity [retired](EntityManager::retire) to the next entity
+    /// created
+    pub(crate) fn release(&self, id: u32) {
+        self.inner.write().release(id);
     }
 
     /// Checks if an entity exists.
@@ -91,22 +173,38 @@ impl EntityManager {
     /// assert!(manager.entity_exists(entity));
     /// ```
     pub async fn entity_exists(&self, entity: Entity) -> bool {
-        let inner = self.inner.read().await;
-        (entity.id as usize) < inner.generations.len()
-            && inner.generations[entity.id as usize] == entity.generation
+        self.is_alive(entity)
+    }
+
+    /// Whether `entity` still refers to the entity living in its slot, without waiting
+    pub(crate) fn is_alive(&self, entity: Entity) -> bool {
+        self.inner.read().generations.get(entity.id as usize) == Some(&entity.generation)
+    }
+
+    /// Slots of the living entities, in ascending order, without the free and retired ones
+    pub(crate) fn live_indices(&self) -> Vec<usize> {
+        let inner = self.inner.read();
+        let mut dead = vec![false; inner.generations.len()];
+        for &id in inner.free_ids.iter().chain(&inner.retired_ids) {
+            dead[id as usize] = true;
+        }
+        (0..inner.generations.len())
+            .filter(|&index| !dead[index])
+            .collect()
     }
 
     /// Returns the number of active entities.
     pub async fn entity_count(&self) -> usize {
-        let inner = self.inner.read().await;
-        inner.generations.len() - inner.free_ids.len()
+        let inner = self.inner.read();
+        inner.generations.len() - inner.free_ids.len() - inner.retired_ids.len()
     }
 
     /// Removes all entities from the manager.
     pub async fn clear(&self) {
-        let mut inner = self.inner.write().await;
+        let mut inner = self.inner.write();
         inner.generations.clear();
         inner.free_ids.clear();
+        inner.retired_ids.clear();
     }
 
     /// Retrieves an entity by its ID.
@@ -120,7 +218,7 @@ impl EntityManager {
     /// assert!(manager.get_entity(entity.id).is_some());
     /// ```
     pub async fn get_entity(&self, id: u32) -> Option<Entity> {
-        let inner = self.inner.read().await;
+        let inner = self.inner.read();
         if (id as usize) < inner.generations.len() {
             Some(Entity {
                 id,
@@ -133,17 +231,48 @@ impl EntityManager {
 
     /// Returns the total number of entity slots (including deleted entities).
     pub async fn len(&self) -> usize {
-        let inner = self.inner.read().await;
+        let inner = self.inner.read();
         inner.generations.len()
     }
 
     /// Returns bool if total number of entity slots (including deleted entities) is empty.
     pub async fn is_empty(&self) -> bool {
-        let inner = self.inner.read().await;
+        let inner = self.inner.read();
         inner.generations.is_empty()
     }
 }
 
+impl EntityManagerInner {
+    fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.retire(entity) {
+            return false;
+        }
+        self.release(entity.id);
+        true
+    }
+
+    fn retire(&mut self, entity: Entity) -> bool {
+        let index = entity.id as usize;
+        // The generation of a free or retired slot is already the one of its next entity
+        if self.generations.get(index) != Some(&entity.generation)
+            || self.free_ids.contains(&entity.id)
+            || self.retired_ids.contains(&entity.id)
+        {
+            return false;
+        }
+        self.generations[index] = entity.generation.wrapping_add(1);
+        self.retired_ids.push(entity.id);
+        true
+    }
+
+    fn release(&mut self, id: u32) {
+        if let Some(position) = self.retired_ids.iter().position(|retired| *retired == id) {
+            self.retired_ids.swap_remove(position);
+            self.free_ids.push(id);
+        }
+    }
+}
+
 impl Default for EntityManager {
     fn default() -> Self {
         Self::new()
@@ -252,4 +381,41 @@ mod tests {
         assert_ne!(e5.generation, e2.generation);
         assert_eq!(manager.entity_count().await, 3);
     }
+
+    #[tokio::test]
+    async fn test_despawn() {
+        let manager = EntityManager::new();
+        let entity = manager.create_entity().await;
+        assert!(manager.despawn(entity).await);
+        assert!(!manager.despawn(entity).await);
+        assert!(!manager.delete_entity(entity).await);
+
+        let respawned = manager.create_entity().await;
+        assert_eq!(respawned.id, entity.id);
+        assert_eq!(respawned.generation, entity.generation + 1);
+        // The old handle doesn't despawn the entity now living in its slot
+        assert!(!manager.despawn(entity).await);
+        assert!(manager.entity_exists(respawned).await);
+        assert!(manager.despawn(respawned).await);
+        assert_eq!(manager.entity_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retire_and_release() {
+        let manager = EntityManager::new();
+        let entity = manager.create_entity().await;
+        assert!(manager.retire(entity));
+        assert!(!manager.entity_exists(entity).await);
+        assert!(!manager.delete_entity(entity).await);
+        assert_eq!(manager.entity_count().await, 0);
+
+        // The slot isn't reused before it is released
+        let other = manager.create_entity().await;
+        assert_ne!(other.id, entity.id);
+        assert_eq!(manager.live_indices(), vec![other.id as usize]);
+        manager.release(entity.id);
+        assert_eq!(manager.live_indices(), vec![other.id as usize]);
+        assert_eq!(manager.create_entity().await.id, entity.id);
+        assert_eq!(manager.live_indices().len(), 2);
+    }
 }
@@ -2,10 +2,37 @@
 pub enum Error {
     #[error("Entity {0} not found")]
     EntityNotFound(usize),
+    #[error("Entity {0} was despawned")]
+    StaleEntity(usize),
     #[error("Component not found")]
     ComponentNotFound,
     #[error("Couldn't remove component since it's locked")]
     ComponentLocked,
+    #[error("Resource {0} not found")]
+    ResourceNotFound(&'static str),
+    #[error("{0} is accessed mutably more than once, or both mutably and immutably")]
+    AliasedAccess(&'static str),
+    #[error("A live query already accesses {0}, one of them mutably")]
+    BorrowConflict(&'static str),
+    #[error("Query expected a single entity, found none or several")]
+    NotSingle,
+    #[error("System {0} is already scheduled")]
+    DuplicateSystem(&'static str),
+    #[error("Systems {first} and {second} both access {component}, one of them mutably, without being ordered")]
+    SystemConflict {
+        first: &'static str,
+        second: &'static str,
+        component: &'static str,
+    },
+    #[error("System {system} is ordered with {label}, which isn't in its stage")]
+    UnknownSystem {
+        system: &'static str,
+        label: &'static str,
+    },
+    #[error("Systems {} are ordered in a cycle", .0.join(", "))]
+    SystemCycle(Vec<&'static str>),
+    #[error("System {0} panicked")]
+    SystemPanicked(&'static str),
     #[error("Conversion error from usize to entity id")]
     ConversionError,
 }
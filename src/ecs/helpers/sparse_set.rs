@@ -115,6 +115,25 @@ impl<T> SparseSet<T> {
             .and_then(|&dense_index| dense_index.map(|di| &mut self.dense[di].1))
     }
 
+    /// Returns the number of values in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use crateecs::dsa::sparse_set::SparseSet;
+    /// let mut set = SparseSet::new();
+    /// set.insert(5, "value");
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
     /// Returns an iterator over the values in the set.
     ///
     /// # Examples
@@ -6,10 +6,13 @@ pub mod entity;
 pub mod error;
 pub mod helpers;
 pub mod query;
+pub mod resources;
+pub mod scheduler;
 #[cfg(test)]
 pub mod test;
 #[cfg(test)]
 pub mod tests;
+pub mod tick_driver;
 pub mod world;
 
 #[cfg(test)]
@@ -1,9 +1,64 @@
+use std::any::{type_name, TypeId};
 use std::marker::PhantomData;
 
-use crate::ecs::component::{Component, ComponentRef, ComponentRefMut, ComponentStorage};
-use crate::ecs::entity::EntityManager;
+use crate::ecs::component::{Borrows, Component, ComponentRef, ComponentRefMut, ComponentStorage};
+use crate::ecs::entity::{EntityKey, EntityManager};
+use crate::ecs::error::Error;
 use crate::utils::prelude::*;
 
+/// Component type a query item reads or writes, or resource a system reads or writes, see
+/// [`SystemDescriptor`](crate::ecs::scheduler::SystemDescriptor)
+#[derive(Debug, Clone, Copy)]
+pub struct ComponentAccess {
+    type_id: TypeId,
+    name: &'static str,
+    mutable: bool,
+}
+
+impl ComponentAccess {
+    pub fn of<T: ?Sized + 'static>(mutable: bool) -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            mutable,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    /// Whether both accesses are to the same type, and one of them mutable
+    pub fn conflicts_with(&self, other: &ComponentAccess) -> bool {
+        self.type_id == other.type_id && (self.mutable || other.mutable)
+    }
+}
+
+/// Fails with [`Error::AliasedAccess`] if a type is accessed mutably and a second time, which
+/// would wait on its own lock
+pub(crate) fn check_aliasing(accesses: &[ComponentAccess]) -> Result<()> {
+    for (index, access) in accesses.iter().enumerate() {
+        let aliased = accesses[index + 1..]
+            .iter()
+            .any(|other| other.conflicts_with(access));
+        if aliased {
+            return Err(Error::AliasedAccess(access.name).into());
+        }
+    }
+    Ok(())
+}
+
+/// Storage of the components an entity must have to match, and its length
+pub type StorageSize = (usize, TypeId);
+
 #[allow(async_fn_in_trait)]
 /// Trait for items that can be queried in the ECS.
 pub trait QueryItem {
@@ -12,6 +67,13 @@ pub trait QueryItem {
         entity_id: impl Into<usize>,
         storage: &'a ComponentStorage,
     ) -> Result<Self::Item<'a>>;
+
+    /// Adds the component types the item locks to `accesses`
+    fn accesses(accesses: &mut Vec<ComponentAccess>);
+
+    /// Smallest storage the entities matching the item are all in, `None` if the item matches
+    /// entities without any component
+    fn smallest_storage(storage: &ComponentStorage) -> Option<StorageSize>;
 }
 
 // Implement QueryItem for immutable references
@@ -22,7 +84,16 @@ impl<T: Component> QueryItem for &T {
         entity_id: impl Into<usize>,
         storage: &'a ComponentStorage,
     ) -> Result<Self::Item<'a>> {
-        storage.get::<T>(entity_id).await
+        storage.get::<T>(entity_id.into()).await
+    }
+
+    fn accesses(accesses: &mut Vec<ComponentAccess>) {
+        accesses.push(ComponentAccess::of::<T>(false));
+    }
+
+    fn smallest_storage(storage: &ComponentStorage) -> Option<StorageSize> {
+        let type_id = TypeId::of::<T>();
+        Some((storage.len_of(type_id), type_id))
     }
 }
 
@@ -34,33 +105,76 @@ impl<T: Component> QueryItem for &mut T {
         entity_id: impl Into<usize>,
         storage: &'a ComponentStorage,
     ) -> Result<Self::Item<'a>> {
-        storage.get_mut::<T>(entity_id).await
+        storage.get_mut::<T>(entity_id.into()).await
+    }
+
+    fn accesses(accesses: &mut Vec<ComponentAccess>) {
+        accesses.push(ComponentAccess::of::<T>(true));
+    }
+
+    fn smallest_storage(storage: &ComponentStorage) -> Option<StorageSize> {
+        let type_id = TypeId::of::<T>();
+        Some((storage.len_of(type_id), type_id))
     }
 }
 
 /// Struct for querying components in the ECS.
-#[derive(Clone, Copy)]
+///
+/// Entities are found by going through the smallest storage of the components they must have,
+/// and probing the others. A query accessing a component type mutably and a second time would
+/// wait on its own locks, so it is rejected when created, as is a query conflicting with a live
+/// one, even of another task: the live query must be dropped first.
 pub struct Query<'a, Q: QueryItem> {
     entity_manager: &'a EntityManager,
     component_storage: &'a ComponentStorage,
-    current_id: usize,
+    /// Entities left to go through by `next`, found by its first call
+    remaining: Option<std::vec::IntoIter<usize>>,
+    _borrows: Borrows<'a>,
     _marker: PhantomData<Q>,
 }
 
 impl<'a, Q: QueryItem> Query<'a, Q> {
     /// Creates a new Query.
     ///
+    /// # Panics
+    /// If the query accesses a component type mutably and a second time, or conflicts with a
+    /// live query, see [`Query::try_new`].
+    ///
     /// # Examples
     ///
     /// ```ignore
     /// let query = Query::<&Position>::new(&entity_manager, &component_storage);
     /// ```
     pub fn new(entity_manager: &'a EntityManager, component_storage: &'a ComponentStorage) -> Self {
-        Self {
+        Self::try_new(entity_manager, component_storage).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new Query, fails with [`Error::AliasedAccess`] if it accesses a component type
+    /// mutably and a second time, like `(&mut Position, &Position)`, and with
+    /// [`Error::BorrowConflict`] if a live query accesses mutably a type it accesses, or accesses
+    /// a type it accesses mutably.
+    pub fn try_new(
+        entity_manager: &'a EntityManager,
+        component_storage: &'a ComponentStorage,
+    ) -> Result<Self> {
+        let mut accesses = Vec::new();
+        Q::accesses(&mut accesses);
+        check_aliasing(&accesses)?;
+        Ok(Self {
             entity_manager,
             component_storage,
-            current_id: 0,
+            remaining: None,
+            _borrows: component_storage.borrow(&accesses)?,
             _marker: PhantomData,
+        })
+    }
+
+    /// Slots of the entities that may match, those of the smallest storage the matching entities
+    /// are all in, or those of every living entity
+    fn candidates(&self) -> Vec<usize> {
+        match Q::smallest_storage(self.component_storage) {
+            Some((_, type_id)) => self.component_storage.indices_of(type_id),
+            None => self.entity_manager.live_indices(),
         }
     }
 
@@ -83,10 +197,9 @@ impl<'a, Q: QueryItem> Query<'a, Q> {
     /// }
     /// ```
     pub async fn iter(&'a self) -> impl Iterator<Item = (usize, Q::Item<'a>)> + 'a {
-        let max_entity_id = self.entity_manager.len().await;
         let mut results = vec![];
 
-        for entity_id in 0..=max_entity_id {
+        for entity_id in self.candidates() {
             if let Ok(item) = Q::fetch(entity_id, self.component_storage).await {
                 results.push((entity_id, item));
             }
@@ -110,22 +223,57 @@ impl<'a, Q: QueryItem> Query<'a, Q> {
     /// }
     /// # }
     /// ```
-    pub async fn next<'b>(&mut self) -> Option<(usize, Q::Item<'b>)>
-    where
-        'a: 'b, // 'a must outlive 'b
-    {
-        let max_entity_id = self.entity_manager.len().await;
-        while self.current_id <= max_entity_id {
-            if let Ok(item) = Q::fetch(self.current_id, self.component_storage).await {
-                let result = Some((self.current_id, item));
-                self.current_id += 1;
-                return result;
+    pub async fn next(&mut self) -> Option<(usize, Q::Item<'_>)> {
+        if self.remaining.is_none() {
+            self.remaining = Some(self.candidates().into_iter());
+        }
+        while let Some(entity_id) = self.remaining.as_mut().and_then(Iterator::next) {
+            if let Ok(item) = Q::fetch(entity_id, self.component_storage).await {
+                return Some((entity_id, item));
             }
-            self.current_id += 1;
         }
-        self.current_id = 0;
+        // Start over on the next call
+        self.remaining = None;
         None
     }
+
+    /// Returns the result of the query for one entity, an error if it doesn't match
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let query = Query::<(&Position, Without<Velocity>)>::new(&entity_manager, &storage);
+    /// let (position, _) = query.get(entity).await?;
+    /// ```
+    pub async fn get(&self, entity_id: impl EntityKey) -> Result<Q::Item<'_>> {
+        let entity_id = self.component_storage.resolve(entity_id)?;
+        Q::fetch(entity_id, self.component_storage).await
+    }
+
+    /// Returns the only result of the query, an error if there is none or several
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let query = Query::<(&mut Position, With<Player>)>::new(&entity_manager, &storage);
+    /// let (entity_id, (mut position, _)) = query.single().await?;
+    /// ```
+    pub async fn single(&self) -> Result<(usize, Q::Item<'_>)> {
+        let mut found = None;
+        for entity_id in self.candidates() {
+            let Ok(item) = Q::fetch(entity_id, self.component_storage).await else {
+                continue;
+            };
+            if found.is_some() {
+                return Err(Error::NotSingle)?;
+            }
+            found = Some((entity_id, item));
+        }
+        let Some(found) = found else {
+            return Err(Error::NotSingle)?;
+        };
+        Ok(found)
+    }
 }
 
 // Macro to automatically generate tuples
@@ -145,6 +293,19 @@ macro_rules! impl_query_item_tuple {
                     )*
                 ))
             }
+
+            fn accesses(accesses: &mut Vec<ComponentAccess>) {
+                $(
+                    $T::accesses(accesses);
+                )*
+            }
+
+            fn smallest_storage(storage: &ComponentStorage) -> Option<StorageSize> {
+                [$($T::smallest_storage(storage),)*]
+                    .into_iter()
+                    .flatten()
+                    .min_by_key(|(len, _)| *len)
+            }
         }
     };
 }
@@ -156,6 +317,24 @@ impl_query_item_tuple!(A, B, C, D);
 impl_query_item_tuple!(A, B, C, D, E);
 impl_query_item_tuple!(A, B, C, D, E, F);
 
+/// Filter of a query matching the entities with a component of type `T`, without locking it
+///
+/// # Examples
+///
+/// ```ignore
+/// let query = world.query::<(&mut Position, With<Player>)>();
+/// ```
+pub struct With<T>(PhantomData<T>);
+
+/// Filter of a query matching the entities without a component of type `T`
+///
+/// # Examples
+///
+/// ```ignore
+/// let query = world.query::<(&mut Position, &Velocity, Without<Frozen>)>();
+/// ```
+pub struct Without<T>(PhantomData<T>);
+
 mod helpers {
     use super::*;
 
@@ -171,9 +350,58 @@ mod helpers {
             let component = T::fetch(entity_id, storage).await;
             Ok(component.ok())
         }
+
+        fn accesses(accesses: &mut Vec<ComponentAccess>) {
+            T::accesses(accesses);
+        }
+
+        fn smallest_storage(_: &ComponentStorage) -> Option<StorageSize> {
+            None
+        }
     }
 
     impl<T: Component> Component for Option<T> {}
+
+    impl<T: Component> QueryItem for With<T> {
+        type Item<'a> = ();
+
+        async fn fetch<'a>(
+            entity_id: impl Into<usize>,
+            storage: &'a ComponentStorage,
+        ) -> Result<Self::Item<'a>> {
+            if !storage.contains::<T>(entity_id.into()) {
+                return Err(Error::ComponentNotFound)?;
+            }
+            Ok(())
+        }
+
+        fn accesses(_: &mut Vec<ComponentAccess>) {}
+
+        fn smallest_storage(storage: &ComponentStorage) -> Option<StorageSize> {
+            let type_id = TypeId::of::<T>();
+            Some((storage.len_of(type_id), type_id))
+        }
+    }
+
+    impl<T: Component> QueryItem for Without<T> {
+        type Item<'a> = ();
+
+        async fn fetch<'a>(
+            entity_id: impl Into<usize>,
+            storage: &'a ComponentStorage,
+        ) -> Result<Self::Item<'a>> {
+            if storage.contains::<T>(entity_id.into()) {
+                return Err(Error::ComponentNotFound)?;
+            }
+            Ok(())
+        }
+
+        fn accesses(_: &mut Vec<ComponentAccess>) {}
+
+        fn smallest_storage(_: &ComponentStorage) -> Option<StorageSize> {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -216,6 +444,7 @@ mod tests {
         }
 
         // Log the results
+        drop(query);
         let query = Query::<&Position>::new(&entity_manager, &storage);
         for (entity_id, pos) in query.iter().await {
             println!("Entity {}: {:?}", entity_id, *pos);
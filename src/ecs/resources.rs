@@ -0,0 +1,259 @@
+//! Values the world holds a single one of, like the database handle or the server config
+//!
+//! Resources are keyed by their type, and locked like components so systems can share them.
+//! Systems declare the resources they use as [`SystemParam`]s, see
+//! [`SystemDescriptor::params`](crate::ecs::scheduler::SystemDescriptor::params).
+
+use std::any::{type_name, Any, TypeId};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tokio::sync::{
+    OwnedRwLockMappedWriteGuard, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock,
+};
+
+use crate::ecs::error::Error;
+use crate::ecs::query::ComponentAccess;
+use crate::ecs::world::World;
+use crate::utils::prelude::*;
+
+/// A value the world can hold as a resource, any shareable type
+pub trait Resource: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Resource for T {}
+
+type BoxedResource = Box<dyn Any + Send + Sync>;
+
+/// A shared reference to the resource `T`, it can't be written while the reference lives
+///
+/// # Examples
+/// ```ignore
+/// let time: Res<TimeOfDay> = world.resource::<TimeOfDay>().await?;
+/// let age = time.world_age();
+/// ```
+pub struct Res<T: Resource> {
+    guard: OwnedRwLockReadGuard<BoxedResource, T>,
+}
+
+/// A mutable reference to the resource `T`, it can't be read while the reference lives
+pub struct ResMut<T: Resource> {
+    guard: OwnedRwLockMappedWriteGuard<BoxedResource, T>,
+}
+
+impl<T: Resource> std::ops::Deref for Res<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: Resource> std::ops::Deref for ResMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: Resource> std::ops::DerefMut for ResMut<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+/// Resources of the world, by type
+#[derive(Default)]
+pub struct Resources {
+    resources: DashMap<TypeId, Arc<RwLock<BoxedResource>>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert `resource`, replacing the resource of that type once it isn't referenced anymore
+    pub fn insert<T: Resource>(&self, resource: T) {
+        self.resources
+            .insert(TypeId::of::<T>(), Arc::new(RwLock::new(Box::new(resource))));
+    }
+
+    pub fn contains<T: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Remove the resource `T`, returns whether there was one. References to it stay valid.
+    pub fn remove<T: Resource>(&self) -> bool {
+        self.resources.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    fn lock_of<T: Resource>(&self) -> Result<Arc<RwLock<BoxedResource>>> {
+        // Cloned out so waiting for the lock doesn't hold the map
+        let lock = self
+            .resources
+            .get(&TypeId::of::<T>())
+            .ok_or(Error::ResourceNotFound(type_name::<T>()))?
+            .clone();
+        Ok(lock)
+    }
+
+    /// Wait for the resource `T` to be free of writers, fails with [`Error::ResourceNotFound`]
+    /// if the world has none
+    pub async fn get<T: Resource>(&self) -> Result<Res<T>> {
+        let guard = self.lock_of::<T>()?.read_owned().await;
+        let guard = OwnedRwLockReadGuard::try_map(guard, |resource| resource.downcast_ref::<T>())
+            .map_err(|_| Error::ResourceNotFound(type_name::<T>()))?;
+        Ok(Res { guard })
+    }
+
+    /// Wait for the resource `T` to be free of readers and writers, fails with
+    /// [`Error::ResourceNotFound`] if the world has none
+    pub async fn get_mut<T: Resource>(&self) -> Result<ResMut<T>> {
+        let guard = self.lock_of::<T>()?.write_owned().await;
+        let guard = OwnedRwLockWriteGuard::try_map(guard, |resource| resource.downcast_mut::<T>())
+            .map_err(|_| Error::ResourceNotFound(type_name::<T>()))?;
+        Ok(ResMut { guard })
+    }
+}
+
+/// Data a system asks the world for, like a [`Res`] or a [`ResMut`], or a tuple of them
+#[allow(async_fn_in_trait)]
+pub trait SystemParam {
+    type Item<'w>;
+
+    async fn fetch(world: &World) -> Result<Self::Item<'_>>;
+
+    /// Adds the types the parameter locks to `accesses`
+    fn accesses(accesses: &mut Vec<ComponentAccess>);
+}
+
+impl<T: Resource> SystemParam for Res<T> {
+    type Item<'w> = Res<T>;
+
+    async fn fetch(world: &World) -> Result<Self::Item<'_>> {
+        world.resource::<T>().await
+    }
+
+    fn accesses(accesses: &mut Vec<ComponentAccess>) {
+        accesses.push(ComponentAccess::of::<T>(false));
+    }
+}
+
+impl<T: Resource> SystemParam for ResMut<T> {
+    type Item<'w> = ResMut<T>;
+
+    async fn fetch(world: &World) -> Result<Self::Item<'_>> {
+        world.resource_mut::<T>().await
+    }
+
+    fn accesses(accesses: &mut Vec<ComponentAccess>) {
+        accesses.push(ComponentAccess::of::<T>(true));
+    }
+}
+
+/// The parameter `P` wrapped in an `Option`, `None` when the resource is missing
+impl<P: SystemParam> SystemParam for Option<P> {
+    type Item<'w> = Option<P::Item<'w>>;
+
+    async fn fetch(world: &World) -> Result<Self::Item<'_>> {
+        Ok(P::fetch(world).await.ok())
+    }
+
+    fn accesses(accesses: &mut Vec<ComponentAccess>) {
+        P::accesses(accesses);
+    }
+}
+
+macro_rules! impl_system_param_for_tuple {
+    ($($T:ident),*) => {
+        impl<$($T: SystemParam),*> SystemParam for ($($T,)*) {
+            type Item<'w> = ($($T::Item<'w>,)*);
+
+            async fn fetch(world: &World) -> Result<Self::Item<'_>> {
+                Ok(($($T::fetch(world).await?,)*))
+            }
+
+            fn accesses(accesses: &mut Vec<ComponentAccess>) {
+                $($T::accesses(accesses);)*
+            }
+        }
+    };
+}
+
+impl_system_param_for_tuple!(A);
+impl_system_param_for_tuple!(A, B);
+impl_system_param_for_tuple!(A, B, C);
+impl_system_param_for_tuple!(A, B, C, D);
+impl_system_param_for_tuple!(A, B, C, D, E);
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Res, ResMut};
+    use crate::ecs::world::World;
+
+    #[derive(Debug, PartialEq)]
+    struct TickCounter(u64);
+
+    struct MaxPlayers(u32);
+
+    #[tokio::test]
+    async fn resources_are_read_and_written_by_type() {
+        let world = World::new();
+        world.insert_resource(TickCounter(0));
+        world.insert_resource(MaxPlayers(20));
+
+        world.resource_mut::<TickCounter>().await.unwrap().0 += 1;
+        assert_eq!(
+            *world.resource::<TickCounter>().await.unwrap(),
+            TickCounter(1)
+        );
+
+        let (mut ticks, max_players) = world
+            .fetch::<(ResMut<TickCounter>, Res<MaxPlayers>)>()
+            .await
+            .unwrap();
+        ticks.0 += u64::from(max_players.0);
+        drop(ticks);
+        assert_eq!(world.resource::<TickCounter>().await.unwrap().0, 21);
+    }
+
+    #[tokio::test]
+    async fn missing_resources_are_named_in_the_error() {
+        let world = World::new();
+        let error = world.resource::<MaxPlayers>().await.err().unwrap();
+        assert!(error.to_string().contains("MaxPlayers"));
+        assert!(world
+            .fetch::<Option<Res<MaxPlayers>>>()
+            .await
+            .unwrap()
+            .is_none());
+        // Fetching a resource mutably and a second time would wait for itself
+        world.insert_resource(MaxPlayers(20));
+        let error = world
+            .fetch::<(ResMut<MaxPlayers>, Res<MaxPlayers>)>()
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("MaxPlayers is accessed mutably"));
+    }
+
+    #[tokio::test]
+    async fn writers_wait_for_readers() {
+        let world = std::sync::Arc::new(World::new());
+        world.insert_resource(TickCounter(0));
+        let reader = world.resource::<TickCounter>().await.unwrap();
+
+        let writer = tokio::spawn({
+            let world = world.clone();
+            async move { world.resource_mut::<TickCounter>().await.unwrap().0 += 1 }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(reader.0, 0);
+        drop(reader);
+        writer.await.unwrap();
+        assert_eq!(world.resource::<TickCounter>().await.unwrap().0, 1);
+    }
+}
@@ -0,0 +1,471 @@
+//! Runs the systems of a tick in order, concurrently when they don't touch the same data
+//!
+//! Systems declare the components and resources they read and write. Within a [`Stage`], systems
+//! run one batch after the other, the systems of a batch running concurrently. Two systems
+//! touching the same data must be ordered with [`SystemDescriptor::after`] or
+//! [`SystemDescriptor::before`], which is checked when the second one is added.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tracing::{debug_span, warn, Instrument};
+
+use crate::ecs::error::Error;
+use crate::ecs::query::{ComponentAccess, QueryItem};
+use crate::ecs::resources::SystemParam;
+use crate::utils::prelude::*;
+
+/// Name of a system, unique in a schedule
+pub type SystemLabel = &'static str;
+
+/// Part of a tick, the stages run one after the other in this order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    PreUpdate,
+    #[default]
+    Update,
+    PostUpdate,
+}
+
+type SystemFn<C> = Arc<dyn Fn(C) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// A system and what the scheduler needs to know to run it, given the context `C` every system
+/// of the schedule takes, like the server state
+pub struct SystemDescriptor<C> {
+    label: SystemLabel,
+    stage: Stage,
+    run: SystemFn<C>,
+    accesses: Vec<ComponentAccess>,
+    after: Vec<SystemLabel>,
+    before: Vec<SystemLabel>,
+}
+
+impl<C: Send + 'static> SystemDescriptor<C> {
+    fn with_run(label: SystemLabel, run: SystemFn<C>) -> Self {
+        Self {
+            label,
+            stage: Stage::default(),
+            run,
+            accesses: Vec::new(),
+            after: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    /// System running on the tokio runtime
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let digs = SystemDescriptor::new("digs", |state: GlobalState| async move {
+    ///     DigTracker::tick(&state).await
+    /// })
+    /// .queries::<(&Player, &mut Digging)>()
+    /// .after("time");
+    /// ```
+    pub fn new<F, Fut>(label: SystemLabel, system: F) -> Self
+    where
+        F: Fn(C) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self::with_run(label, Arc::new(move |context| system(context).boxed()))
+    }
+
+    /// System doing pure computations, running on the rayon pool so it doesn't hold up the
+    /// tokio workers
+    pub fn compute<F>(label: SystemLabel, system: F) -> Self
+    where
+        F: Fn(C) -> Result<()> + Send + Sync + 'static,
+    {
+        let system = Arc::new(system);
+        Self::with_run(
+            label,
+            Arc::new(move |context| {
+                let system = system.clone();
+                async move {
+                    let (sender, receiver) = tokio::sync::oneshot::channel();
+                    rayon::spawn(move || {
+                        let result = catch_unwind(AssertUnwindSafe(|| system(context)))
+                            .unwrap_or_else(|_| Err(Error::SystemPanicked(label).into()));
+                        // The schedule only stops waiting when the task running it is aborted
+                        let _ = sender.send(result);
+                    });
+                    receiver.await.map_err(|_| Error::SystemPanicked(label))?
+                }
+                .boxed()
+            }),
+        )
+    }
+
+    pub fn in_stage(mut self, stage: Stage) -> Self {
+        self.stage = stage;
+        self
+    }
+
+    /// Declare that the system reads the component or resource `T`, like a component it gets
+    /// without querying it. Resources are better declared with [`Self::params`].
+    pub fn reads<T: ?Sized + 'static>(mut self) -> Self {
+        self.accesses.push(ComponentAccess::of::<T>(false));
+        self
+    }
+
+    /// Declare that the system writes the component or resource `T`, see [`Self::reads`]
+    pub fn writes<T: ?Sized + 'static>(mut self) -> Self {
+        self.accesses.push(ComponentAccess::of::<T>(true));
+        self
+    }
+
+    /// Declare the components the query `Q` of the system reads and writes
+    pub fn queries<Q: QueryItem>(mut self) -> Self {
+        Q::accesses(&mut self.accesses);
+        self
+    }
+
+    /// Declare the resources the parameter `P` of the system reads and writes, like
+    /// `(ResMut<TimeOfDay>, Res<Config>)`
+    pub fn params<P: SystemParam>(mut self) -> Self {
+        P::accesses(&mut self.accesses);
+        self
+    }
+
+    /// Run the system after the system `label` of the same stage
+    pub fn after(mut self, label: SystemLabel) -> Self {
+        self.after.push(label);
+        self
+    }
+
+    /// Run the system before the system `label` of the same stage
+    pub fn before(mut self, label: SystemLabel) -> Self {
+        self.before.push(label);
+        self
+    }
+
+    /// Type the system and `other` both access, one of them mutably
+    fn conflict(&self, other: &SystemDescriptor<C>) -> Option<&'static str> {
+        self.accesses.iter().find_map(|access| {
+            other
+                .accesses
+                .iter()
+                .any(|other| access.conflicts_with(other))
+                .then(|| access.name())
+        })
+    }
+}
+
+/// Systems running after each system of `systems`, by label
+fn successors<'a, C>(
+    systems: impl Iterator<Item = &'a SystemDescriptor<C>>,
+) -> HashMap<SystemLabel, Vec<SystemLabel>>
+where
+    C: 'a,
+{
+    let mut successors: HashMap<SystemLabel, Vec<SystemLabel>> = HashMap::new();
+    for system in systems {
+        for &before in &system.after {
+            successors.entry(before).or_default().push(system.label);
+        }
+        for &after in &system.before {
+            successors.entry(system.label).or_default().push(after);
+        }
+    }
+    successors
+}
+
+/// Whether `to` runs after `from` in the order given by `successors`
+fn reaches(
+    successors: &HashMap<SystemLabel, Vec<SystemLabel>>,
+    from: SystemLabel,
+    to: SystemLabel,
+) -> bool {
+    let mut seen = HashSet::new();
+    let mut stack = vec![from];
+    while let Some(label) = stack.pop() {
+        if label == to {
+            return true;
+        }
+        if seen.insert(label) {
+            stack.extend(successors.get(label).into_iter().flatten());
+        }
+    }
+    false
+}
+
+/// Systems to build a [`Schedule`] from
+pub struct Scheduler<C> {
+    systems: Vec<SystemDescriptor<C>>,
+}
+
+impl<C: Clone + Send + Sync + 'static> Scheduler<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `system`, fails if its label is taken, or if it accesses the same data as a system of
+    /// its stage without being ordered with it
+    pub fn add_system(&mut self, system: SystemDescriptor<C>) -> Result<&mut Self> {
+        if self.systems.iter().any(|other| other.label == system.label) {
+            return Err(Error::DuplicateSystem(system.label))?;
+        }
+
+        let stage: Vec<_> = self
+            .systems
+            .iter()
+            .filter(|other| other.stage == system.stage)
+            .collect();
+        let successors = successors(stage.iter().copied().chain([&system]));
+        for other in stage {
+            let Some(component) = system.conflict(other) else {
+                continue;
+            };
+            if !reaches(&successors, system.label, other.label)
+                && !reaches(&successors, other.label, system.label)
+            {
+                return Err(Error::SystemConflict {
+                    first: other.label,
+                    second: system.label,
+                    component,
+                })?;
+            }
+        }
+
+        self.systems.push(system);
+        Ok(self)
+    }
+
+    /// Order the systems of each stage into batches, fails if a system is ordered with a system
+    /// missing from its stage, or if the order has a cycle
+    pub fn build(self) -> Result<Schedule<C>> {
+        let mut stages: BTreeMap<Stage, Vec<SystemDescriptor<C>>> = BTreeMap::new();
+        for system in self.systems {
+            stages.entry(system.stage).or_default().push(system);
+        }
+
+        let mut batches = Vec::new();
+        for systems in stages.into_values() {
+            let labels: HashSet<SystemLabel> = systems.iter().map(|system| system.label).collect();
+            for system in &systems {
+                if let Some(&label) = system
+                    .after
+                    .iter()
+                    .chain(&system.before)
+                    .find(|label| !labels.contains(*label))
+                {
+                    return Err(Error::UnknownSystem {
+                        system: system.label,
+                        label,
+                    })?;
+                }
+            }
+
+            // Batches of the systems whose predecessors all ran in the previous batches
+            let successors = successors(systems.iter());
+            let mut predecessors: HashMap<SystemLabel, usize> =
+                labels.iter().map(|&label| (label, 0)).collect();
+            for &successor in successors.values().flatten() {
+                *predecessors.entry(successor).or_default() += 1;
+            }
+            let mut remaining = systems;
+            while !remaining.is_empty() {
+                let (batch, rest): (Vec<_>, Vec<_>) = remaining
+                    .into_iter()
+                    .partition(|system| predecessors[system.label] == 0);
+                if batch.is_empty() {
+                    let mut cycle: Vec<_> = rest.iter().map(|system| system.label).collect();
+                    cycle.sort_unstable();
+                    return Err(Error::SystemCycle(cycle))?;
+                }
+                for system in &batch {
+                    for successor in successors.get(system.label).into_iter().flatten() {
+                        *predecessors.get_mut(successor).unwrap() -= 1;
+                    }
+                }
+                batches.push(
+                    batch
+                        .into_iter()
+                        .map(|system| (system.label, system.run))
+                        .collect(),
+                );
+                remaining = rest;
+            }
+        }
+        Ok(Schedule { batches })
+    }
+}
+
+impl<C> Default for Scheduler<C> {
+    fn default() -> Self {
+        Self {
+            systems: Vec::new(),
+        }
+    }
+}
+
+/// Systems of every stage, in batches of systems running concurrently
+pub struct Schedule<C> {
+    batches: Vec<Vec<(SystemLabel, SystemFn<C>)>>,
+}
+
+impl<C: Clone + Send + Sync + 'static> Schedule<C> {
+    /// Labels of the systems of each batch, in the order they run
+    pub fn batches(&self) -> Vec<Vec<SystemLabel>> {
+        self.batches
+            .iter()
+            .map(|batch| batch.iter().map(|(label, _)| *label).collect())
+            .collect()
+    }
+
+    /// Run every system once, a system failing doesn't stop the others
+    pub async fn run(&self, context: &C) {
+        for batch in &self.batches {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|(label, run)| {
+                    let system = run(context.clone()).instrument(debug_span!("sys", name = %label));
+                    (label, tokio::spawn(system))
+                })
+                .collect();
+            for (label, handle) in handles {
+                match handle.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!("System {} failed: {}", label, e),
+                    Err(e) => warn!("System {} panicked: {}", label, e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use super::{Scheduler, Stage, SystemDescriptor};
+    use crate::ecs::resources::{Res, ResMut};
+    use crate::utils::encoding::position::Position;
+    use crate::utils::encoding::velocity::Velocity;
+
+    /// Labels of the systems in the order they ran
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    fn system(label: &'static str) -> SystemDescriptor<Log> {
+        SystemDescriptor::new(label, move |log: Log| async move {
+            log.lock().push(label);
+            Ok(())
+        })
+    }
+
+    #[tokio::test]
+    async fn disjoint_systems_share_a_batch() {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(system("movement").queries::<(&mut Position, &Velocity)>())
+            .unwrap()
+            .add_system(system("render").reads::<Position>().after("movement"))
+            .unwrap()
+            .add_system(system("physics").reads::<Velocity>())
+            .unwrap()
+            .add_system(system("setup").in_stage(Stage::PreUpdate))
+            .unwrap();
+        let schedule = scheduler.build().unwrap();
+        assert_eq!(
+            schedule.batches(),
+            [vec!["setup"], vec!["movement", "physics"], vec!["render"]]
+        );
+
+        let log = Log::default();
+        schedule.run(&log).await;
+        let log = log.lock();
+        assert_eq!(log[0], "setup");
+        assert_eq!(log[3], "render");
+    }
+
+    #[test]
+    fn unordered_conflicting_systems_are_rejected() {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(system("movement").writes::<Position>())
+            .unwrap();
+        let error = scheduler
+            .add_system(system("render").reads::<Position>())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Position"));
+        // Ordered with a system in between, or in another stage
+        scheduler
+            .add_system(system("collide").after("movement"))
+            .unwrap()
+            .add_system(system("render").reads::<Position>().after("collide"))
+            .unwrap()
+            .add_system(
+                system("save")
+                    .reads::<Position>()
+                    .in_stage(Stage::PostUpdate),
+            )
+            .unwrap();
+        assert!(scheduler.add_system(system("save")).is_err());
+    }
+
+    #[test]
+    fn resource_params_are_declared_accesses() {
+        struct TickCounter;
+
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(system("count").params::<ResMut<TickCounter>>())
+            .unwrap();
+        assert!(scheduler
+            .add_system(system("log").params::<(Res<TickCounter>,)>())
+            .is_err());
+        scheduler
+            .add_system(
+                system("log")
+                    .params::<Option<Res<TickCounter>>>()
+                    .after("count"),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn cycles_and_unknown_labels_are_reported() {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(system("a").after("c"))
+            .unwrap()
+            .add_system(system("b").after("a"))
+            .unwrap()
+            .add_system(system("c").after("b"))
+            .unwrap()
+            .add_system(system("d").before("a"))
+            .unwrap();
+        let error = scheduler.build().err().unwrap();
+        assert!(error.to_string().contains("a, b, c"));
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_system(system("a").after("missing")).unwrap();
+        assert!(scheduler.build().is_err());
+    }
+
+    #[tokio::test]
+    async fn compute_systems_run_on_the_rayon_pool() {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(SystemDescriptor::compute("sum", |log: Log| {
+                assert!(rayon::current_thread_index().is_some());
+                log.lock().push("sum");
+                Ok(())
+            }))
+            .unwrap()
+            .add_system(system("after").after("sum"))
+            .unwrap()
+            .add_system(SystemDescriptor::compute("panics", |_: Log| panic!()))
+            .unwrap();
+        let log = Log::default();
+        scheduler.build().unwrap().run(&log).await;
+        assert_eq!(*log.lock(), ["sum", "after"]);
+    }
+}
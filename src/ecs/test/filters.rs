@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use crate::ecs::component::{Component, ComponentStorage};
+    use crate::ecs::entity::{Entity, EntityManager};
+    use crate::ecs::query::{Query, With, Without};
+    use crate::utils::encoding::position::Position;
+    use crate::utils::encoding::velocity::Velocity;
+
+    #[derive(Debug)]
+    struct Frozen;
+    impl Component for Frozen {}
+
+    /// A moving entity, a frozen moving entity, and an entity that doesn't move
+    async fn setup() -> (EntityManager, ComponentStorage, [Entity; 3]) {
+        let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
+
+        let moving = entity_manager.create_entity().await;
+        storage.insert(moving, Position { x: 1, y: 0, z: 0 });
+        storage.insert(moving, Velocity { x: 1, y: 0, z: 0 });
+
+        let frozen = entity_manager.create_entity().await;
+        storage.insert(frozen, Position { x: 2, y: 0, z: 0 });
+        storage.insert(frozen, Velocity { x: 1, y: 0, z: 0 });
+        storage.insert(frozen, Frozen);
+
+        let still = entity_manager.create_entity().await;
+        storage.insert(still, Position { x: 3, y: 0, z: 0 });
+
+        (entity_manager, storage, [moving, frozen, still])
+    }
+
+    fn ids(entities: &[Entity]) -> Vec<usize> {
+        entities.iter().map(|entity| entity.id as usize).collect()
+    }
+
+    #[tokio::test]
+    async fn test_with() {
+        let (entity_manager, storage, [_, frozen, _]) = setup().await;
+
+        let query = Query::<(&Position, With<Frozen>)>::new(&entity_manager, &storage);
+        let results: Vec<_> = query.iter().await.map(|(id, _)| id).collect();
+        assert_eq!(results, ids(&[frozen]));
+    }
+
+    #[tokio::test]
+    async fn test_without() {
+        let (entity_manager, storage, [moving, _, still]) = setup().await;
+
+        let query = Query::<(&Position, Without<Frozen>)>::new(&entity_manager, &storage);
+        let results: Vec<_> = query.iter().await.map(|(id, _)| id).collect();
+        assert_eq!(results, ids(&[moving, still]));
+    }
+
+    #[tokio::test]
+    async fn test_with_and_without() {
+        let (entity_manager, storage, [moving, _, _]) = setup().await;
+
+        let mut query = Query::<(&mut Position, With<Velocity>, Without<Frozen>)>::new(
+            &entity_manager,
+            &storage,
+        );
+        while let Some((_, (mut position, _, _))) = query.next().await {
+            position.x += 10;
+        }
+        drop(query);
+
+        let query = Query::<&Position>::new(&entity_manager, &storage);
+        let results: Vec<_> = query.iter().await.map(|(_, pos)| pos.x).collect();
+        assert_eq!(results, [11, 2, 3]);
+        let query = Query::<(With<Velocity>, Without<Frozen>)>::new(&entity_manager, &storage);
+        let results: Vec<_> = query.iter().await.map(|(id, _)| id).collect();
+        assert_eq!(results, ids(&[moving]));
+    }
+
+    #[tokio::test]
+    async fn test_only_without() {
+        let (entity_manager, storage, [moving, _, still]) = setup().await;
+
+        // Nothing to go through but every entity
+        let query = Query::<Without<Frozen>>::new(&entity_manager, &storage);
+        let results: Vec<_> = query.iter().await.map(|(id, _)| id).collect();
+        assert_eq!(results, ids(&[moving, still]));
+
+        // Despawned without its components being removed
+        entity_manager.despawn(still).await;
+        let results: Vec<_> = query.iter().await.map(|(id, _)| id).collect();
+        assert_eq!(results, ids(&[moving]));
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let (entity_manager, storage, [moving, frozen, _]) = setup().await;
+
+        let query = Query::<(&Velocity, Without<Frozen>)>::new(&entity_manager, &storage);
+        assert_eq!(query.get(moving).await.unwrap().0.x, 1);
+        assert!(query.get(frozen).await.is_err());
+
+        // Despawned without its components being removed
+        entity_manager.despawn(moving).await;
+        assert!(query.get(moving).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_single() {
+        let (entity_manager, storage, [_, frozen, _]) = setup().await;
+
+        let query = Query::<(&mut Position, With<Frozen>)>::new(&entity_manager, &storage);
+        let (id, (mut position, _)) = query.single().await.unwrap();
+        assert_eq!(id, frozen.id as usize);
+        position.x = 0;
+        drop(position);
+        drop(query);
+
+        let query = Query::<(&Position, With<Velocity>)>::new(&entity_manager, &storage);
+        assert!(query.single().await.is_err());
+        let query = Query::<&Frozen>::new(&entity_manager, &storage);
+        storage.remove::<Frozen>(frozen).unwrap();
+        assert!(query.single().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_aliased_access() {
+        let (entity_manager, storage, _) = setup().await;
+
+        assert!(Query::<(&mut Position, &Position)>::try_new(&entity_manager, &storage).is_err());
+        assert!(
+            Query::<(&Velocity, Option<&mut Velocity>)>::try_new(&entity_manager, &storage)
+                .is_err()
+        );
+        assert!(Query::<(&Position, &Position)>::try_new(&entity_manager, &storage).is_ok());
+        // Filters don't lock the components
+        assert!(
+            Query::<(&mut Position, With<Position>)>::try_new(&entity_manager, &storage).is_ok()
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "mutably more than once")]
+    async fn test_aliased_access_panics() {
+        let (entity_manager, storage, _) = setup().await;
+        Query::<(&mut Velocity, &mut Velocity)>::new(&entity_manager, &storage);
+    }
+
+    #[tokio::test]
+    async fn test_live_query_conflicts() {
+        let (entity_manager, storage, _) = setup().await;
+
+        let writing = Query::<&mut Position>::new(&entity_manager, &storage);
+        assert!(Query::<&Position>::try_new(&entity_manager, &storage).is_err());
+        assert!(Query::<(&Velocity, With<Position>)>::try_new(&entity_manager, &storage).is_ok());
+        drop(writing);
+
+        let reading = Query::<&Position>::new(&entity_manager, &storage);
+        assert!(Query::<&Position>::try_new(&entity_manager, &storage).is_ok());
+        assert!(Query::<&mut Position>::try_new(&entity_manager, &storage).is_err());
+        drop(reading);
+        assert!(Query::<&mut Position>::try_new(&entity_manager, &storage).is_ok());
+    }
+}
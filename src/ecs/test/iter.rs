@@ -9,6 +9,7 @@ mod tests {
     use crate::ecs::component::{Component, ComponentStorage};
     use crate::ecs::entity::EntityManager;
     use crate::ecs::query::Query;
+    use crate::ecs::test::query_when_free;
     use crate::utils::encoding::position::Position;
     use crate::utils::encoding::velocity::Velocity;
 
@@ -18,8 +19,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_basic_query() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity = entity_manager.create_entity().await;
         storage.insert(entity, Position { x: 1, y: 2, z: 0 });
@@ -37,8 +38,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_multi_component_query() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity1 = entity_manager.create_entity().await;
         let entity2 = entity_manager.create_entity().await;
@@ -59,8 +60,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_mutable_query() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity = entity_manager.create_entity().await;
         storage.insert(entity, Position { x: 1, y: 2, z: 0 });
@@ -69,6 +70,7 @@ mod tests {
         for (_, mut pos) in query.iter().await {
             pos.x += 1;
         }
+        drop(query);
 
         let query = Query::<&Position>::new(&entity_manager, &storage);
         let results: Vec<_> = query.iter().await.collect();
@@ -77,8 +79,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_concurrent_reads() {
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         for i in 0..1000 {
             let entity = entity_manager.create_entity().await;
@@ -108,8 +110,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_concurrent_writes() {
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         for i in 0..1000 {
             let entity = entity_manager.create_entity().await;
@@ -127,7 +129,10 @@ mod tests {
 
                 tokio::spawn(async move {
                     barrier_clone.wait().await;
-                    let query = Query::<&mut Position>::new(&entity_manager_clone, &storage_clone);
+                    let query = query_when_free(|| {
+                        Query::<&mut Position>::try_new(&entity_manager_clone, &storage_clone)
+                    })
+                    .await;
                     for (_, mut pos) in query.iter().await {
                         pos.x += 1;
                     }
@@ -148,8 +153,8 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_mixed_queries() {
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         for i in 0..1000 {
             let entity = entity_manager.create_entity().await;
@@ -227,8 +232,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_edge_cases() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         // Test query on empty storage
         {
@@ -305,8 +310,8 @@ mod tests {
         impl Component for DropCounter {}
 
         {
-            let storage = ComponentStorage::new();
             let entity_manager = EntityManager::new();
+            let storage = ComponentStorage::for_entities(&entity_manager);
 
             let rc = Arc::new(());
             let weak = Arc::downgrade(&rc);
@@ -332,8 +337,8 @@ mod tests {
         use std::sync::atomic::{AtomicBool, Ordering};
         use tokio::time::{sleep, Duration};
 
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         // Create entities with positions
         for i in 0..10 {
@@ -352,7 +357,10 @@ mod tests {
             tokio::spawn(async move {
                 let mut iteration = 0;
                 while running_clone.load(Ordering::Relaxed) {
-                    let query = Query::<&mut Position>::new(&entity_manager_clone, &storage_clone);
+                    let query = query_when_free(|| {
+                        Query::<&mut Position>::try_new(&entity_manager_clone, &storage_clone)
+                    })
+                    .await;
                     let results: Vec<_> = query.iter().await.collect();
                     results.into_par_iter().for_each(|(entity_id, mut pos)| {
                         pos.x += 1;
@@ -376,7 +384,10 @@ mod tests {
                 tokio::spawn(async move {
                     let mut iteration = 0;
                     while running_clone.load(Ordering::Relaxed) {
-                        let query = Query::<&Position>::new(&entity_manager_clone, &storage_clone);
+                        let query = query_when_free(|| {
+                            Query::<&Position>::try_new(&entity_manager_clone, &storage_clone)
+                        })
+                        .await;
                         let results: Vec<_> = query.iter().await.collect();
                         results.into_par_iter().for_each(|(entity_id, pos)| {
                             println!(
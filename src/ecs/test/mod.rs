@@ -1,5 +1,22 @@
+use crate::ecs::query::{Query, QueryItem};
+use crate::utils::prelude::*;
+
+mod filters;
 mod iter;
 mod multi_threaded_state;
 mod next;
 mod stress;
 mod with_rayon;
+
+/// Creates a query with `try_query` once the live queries of other tasks it conflicts with are
+/// dropped
+async fn query_when_free<'a, Q: QueryItem>(
+    try_query: impl Fn() -> Result<Query<'a, Q>>,
+) -> Query<'a, Q> {
+    loop {
+        match try_query() {
+            Ok(query) => return query,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    }
+}
@@ -5,13 +5,14 @@ mod tests {
     use crate::ecs::component::ComponentStorage;
     use crate::ecs::entity::EntityManager;
     use crate::ecs::query::Query;
+    use crate::ecs::test::query_when_free;
     use crate::utils::encoding::position::Position;
     use crate::utils::encoding::velocity::Velocity;
 
     #[tokio::test]
     async fn test_next_basic() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity1 = entity_manager.create_entity().await;
         let entity2 = entity_manager.create_entity().await;
@@ -21,23 +22,28 @@ mod tests {
 
         let mut query = Query::<&Position>::new(&entity_manager, &storage);
 
-        let (id1, pos1) = query.next().await.unwrap();
-        assert_eq!(id1, entity1.id as usize);
-        assert_eq!(pos1.x, 1);
-        assert_eq!(pos1.y, 2);
+        // Items borrow the query until the next call
+        {
+            let (id1, pos1) = query.next().await.unwrap();
+            assert_eq!(id1, entity1.id as usize);
+            assert_eq!(pos1.x, 1);
+            assert_eq!(pos1.y, 2);
+        }
 
-        let (id2, pos2) = query.next().await.unwrap();
-        assert_eq!(id2, entity2.id as usize);
-        assert_eq!(pos2.x, 3);
-        assert_eq!(pos2.y, 4);
+        {
+            let (id2, pos2) = query.next().await.unwrap();
+            assert_eq!(id2, entity2.id as usize);
+            assert_eq!(pos2.x, 3);
+            assert_eq!(pos2.y, 4);
+        }
 
         assert!(query.next().await.is_none());
     }
 
     #[tokio::test]
     async fn test_next_multi_component() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity1 = entity_manager.create_entity().await;
         let entity2 = entity_manager.create_entity().await;
@@ -48,18 +54,20 @@ mod tests {
 
         let mut query = Query::<(&Position, &Velocity)>::new(&entity_manager, &storage);
 
-        let (id1, (pos1, vel1)) = query.next().await.unwrap();
-        assert_eq!(id1, entity1.id as usize);
-        assert_eq!(pos1.x, 1);
-        assert_eq!(vel1.x, 3);
+        {
+            let (id1, (pos1, vel1)) = query.next().await.unwrap();
+            assert_eq!(id1, entity1.id as usize);
+            assert_eq!(pos1.x, 1);
+            assert_eq!(vel1.x, 3);
+        }
 
         assert!(query.next().await.is_none());
     }
 
     #[tokio::test]
     async fn test_next_mutable() {
-        let storage = ComponentStorage::new();
         let entity_manager = EntityManager::new();
+        let storage = ComponentStorage::for_entities(&entity_manager);
 
         let entity = entity_manager.create_entity().await;
         storage.insert(entity, Position { x: 1, y: 2, z: 0 });
@@ -73,6 +81,7 @@ mod tests {
         drop(pos); // Explicitly drop the RwLockWriteGuard.
 
         assert!(query.next().await.is_none());
+        drop(query);
 
         let query = Query::<&Position>::new(&entity_manager, &storage);
         let results: Vec<_> = query.iter().await.collect();
@@ -81,8 +90,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_next_concurrent() {
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         for i in 0..100 {
             let entity = entity_manager.create_entity().await;
@@ -117,8 +126,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_next_with_reads_and_writes() {
-        let storage = Arc::new(ComponentStorage::new());
         let entity_manager = EntityManager::new();
+        let storage = Arc::new(ComponentStorage::for_entities(&entity_manager));
 
         for i in 0..100 {
             let entity = entity_manager.create_entity().await;
@@ -131,7 +140,10 @@ mod tests {
             let storage_clone = storage.clone();
             let entity_manager_clone = entity_manager.clone();
             async move {
-                let mut query = Query::<&Position>::new(&entity_manager_clone, &storage_clone);
+                let mut query = query_when_free(|| {
+                    Query::<&Position>::try_new(&entity_manager_clone, &storage_clone)
+                })
+                .await;
                 let mut count = 0;
                 while (query.next().await).is_some() {
                     count += 1;
@@ -145,7 +157,10 @@ mod tests {
             let storage_clone = storage.clone();
             let entity_manager_clone = entity_manager.clone();
             async move {
-                let mut query = Query::<&mut Position>::new(&entity_manager_clone, &storage_clone);
+                let mut query = query_when_free(|| {
+                    Query::<&mut Position>::try_new(&entity_manager_clone, &storage_clone)
+                })
+                .await;
                 let mut count = 0;
                 while let Some((_, mut pos)) = query.next().await {
                     pos.x += 1;
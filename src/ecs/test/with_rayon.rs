@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::ecs::component::Component;
+    use crate::ecs::test::query_when_free;
     use crate::ecs::world::World;
     use crate::utils::encoding::position::Position;
     use crate::utils::encoding::velocity::Velocity;
@@ -86,6 +87,7 @@ mod tests {
         });
 
         // Update the actual components
+        drop(query);
         let mut query = world.query::<&mut Position>();
         let mut i = 0;
         while let Some((_, mut position)) = query.next().await {
@@ -98,7 +100,8 @@ mod tests {
     // System 2: Damage over time system
     async fn damage_system(world: Arc<RwLock<World>>) {
         let world = world.read().await;
-        let mut query = world.query::<(&mut Health, &DamageOverTime)>();
+        let mut query =
+            query_when_free(|| world.try_query::<(&mut Health, &DamageOverTime)>()).await;
 
         let mut healths = Vec::new();
         while let Some((_, (health, dot))) = query.next().await {
@@ -110,7 +113,8 @@ mod tests {
         });
 
         // Update the actual components
-        let mut query = world.query::<&mut Health>();
+        drop(query);
+        let mut query = query_when_free(|| world.try_query::<&mut Health>()).await;
         let mut i = 0;
         while let Some((_, mut health)) = query.next().await {
             health.current = healths[i].0;
@@ -121,7 +125,7 @@ mod tests {
     // System 3: Healing system
     async fn healing_system(world: Arc<RwLock<World>>) {
         let world = world.read().await;
-        let mut query = world.query::<(&mut Health, &Healer)>();
+        let mut query = query_when_free(|| world.try_query::<(&mut Health, &Healer)>()).await;
 
         let mut healths = Vec::new();
         while let Some((_, (health, healer))) = query.next().await {
@@ -133,7 +137,8 @@ mod tests {
         });
 
         // Update the actual components
-        let mut query = world.query::<&mut Health>();
+        drop(query);
+        let mut query = query_when_free(|| world.try_query::<&mut Health>()).await;
         let mut i = 0;
         while let Some((_, mut health)) = query.next().await {
             health.current = healths[i].0;
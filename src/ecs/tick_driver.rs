@@ -0,0 +1,84 @@
+//! Runs a [`Schedule`] at a fixed rate
+
+use std::time::Duration;
+
+use tokio::time::{sleep_until, Instant};
+use tracing::warn;
+
+use crate::ecs::scheduler::Schedule;
+
+/// Ticks run late without waiting before the driver gives up catching up, so a slow tick doesn't
+/// make every following tick late too
+pub const MAX_CATCH_UP_TICKS: u32 = 10;
+
+pub struct TickDriver {
+    interval: Duration,
+    /// When the next tick is due
+    next_tick: Instant,
+}
+
+impl TickDriver {
+    /// Driver running `tps` ticks per second, starting now
+    pub fn new(tps: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / tps.max(1),
+            next_tick: Instant::now(),
+        }
+    }
+
+    /// Schedule the tick after the one due, given the time `now` it ended at. Late ticks run
+    /// without waiting, up to [`MAX_CATCH_UP_TICKS`] behind, after which the late ticks are
+    /// skipped.
+    fn advance(&mut self, now: Instant) {
+        self.next_tick += self.interval;
+        let behind = now.saturating_duration_since(self.next_tick);
+        if behind > self.interval * MAX_CATCH_UP_TICKS {
+            warn!(
+                "Ticks are running {}ms late, skipping {} ticks",
+                behind.as_millis(),
+                behind.as_nanos() / self.interval.as_nanos()
+            );
+            self.next_tick = now;
+        }
+    }
+
+    /// Run `schedule` every tick, forever
+    pub async fn run<C: Clone + Send + Sync + 'static>(
+        &mut self,
+        schedule: &Schedule<C>,
+        context: C,
+    ) {
+        loop {
+            sleep_until(self.next_tick).await;
+            schedule.run(&context).await;
+            self.advance(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TickDriver, MAX_CATCH_UP_TICKS};
+
+    #[test]
+    fn late_ticks_catch_up_up_to_a_bound() {
+        let mut driver = TickDriver::new(20);
+        let start = driver.next_tick;
+        let interval = Duration::from_millis(50);
+
+        // On time, the next tick is one interval later
+        driver.advance(start + Duration::from_millis(10));
+        assert_eq!(driver.next_tick, start + interval);
+
+        // Three ticks late, the next ones run right away to catch up
+        driver.advance(start + interval * 4);
+        assert_eq!(driver.next_tick, start + interval * 2);
+
+        // Too late to catch up, the late ticks are skipped
+        let now = start + interval * (MAX_CATCH_UP_TICKS + 5);
+        driver.advance(now);
+        assert_eq!(driver.next_tick, now);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::ecs::component::{Component, ComponentRef, ComponentRefMut, ComponentStorage};
-use crate::ecs::entity::EntityManager;
+use crate::ecs::entity::{Entity, EntityKey, EntityManager};
 use crate::ecs::error::Error;
 use crate::ecs::helpers::entity_builder::EntityBuilder;
-use crate::ecs::query::Query;
+use crate::ecs::query::{check_aliasing, Query};
+use crate::ecs::resources::{Res, ResMut, Resource, Resources, SystemParam};
 
 use crate::utils::prelude::*;
 
@@ -41,7 +42,8 @@ use crate::utils::prelude::*;
 ///         pos.y += vel.y;
 ///     }
 ///
-///     // Check for collisions
+///     // Check for collisions, once the query writing the positions is dropped
+///     drop(query);
 ///     let mut collision_query = world.query::<(&Position, Option<&Player>)>();
 ///     for (entity, (pos, player)) in collision_query.iter().await {
 ///         // Handle collisions...
@@ -58,6 +60,7 @@ use crate::utils::prelude::*;
 pub struct World {
     entity_manager: EntityManager,
     component_storage: ComponentStorage,
+    resources: Resources,
 }
 
 impl World {
@@ -70,9 +73,11 @@ impl World {
     /// let world = World::new();
     /// ```
     pub fn new() -> Self {
+        let entity_manager = EntityManager::new();
         Self {
-            entity_manager: EntityManager::new(),
-            component_storage: ComponentStorage::new(),
+            component_storage: ComponentStorage::for_entities(&entity_manager),
+            entity_manager,
+            resources: Resources::new(),
         }
     }
 
@@ -100,14 +105,29 @@ impl World {
         EntityBuilder::new(entity, &self.component_storage)
     }
 
-    pub async fn delete_entity(&self, entity_id: impl TryInto<usize>) -> Result<()> {
-        let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
+    /// Creates a new entity without components and returns its handle
+    ///
+    /// Unlike the bare id returned by [`EntityBuilder::build`], the handle stops resolving once
+    /// the entity is deleted, even if another entity takes its slot.
+    pub async fn spawn(&self) -> Entity {
+        self.entity_manager.create_entity().await
+    }
 
-        if !self.entity_manager.delete_entity(entity_id).await {
-            return Err(Error::EntityNotFound(entity_id))?;
+    /// Deletes an entity and all its components, fails for a handle of an entity deleted already
+    pub async fn delete_entity(&self, entity_id: impl EntityKey) -> Result<()> {
+        let index = entity_id.index().ok_or(Error::ConversionError)?;
+        let id = u32::try_from(index).map_err(|_| Error::ConversionError)?;
+        let entity = match entity_id.generation() {
+            Some(generation) => Some(Entity { id, generation }),
+            None => self.entity_manager.get_entity(id).await,
+        };
+        if !entity.is_some_and(|entity| self.entity_manager.retire(entity)) {
+            return Err(Error::EntityNotFound(index))?;
         }
 
-        self.component_storage.remove_all(entity_id);
+        // The slot isn't given to another entity before its components are removed
+        self.component_storage.remove_all(index);
+        self.entity_manager.release(id);
 
         Ok(())
     }
@@ -133,7 +153,8 @@ impl World {
     ///     println!("Entity {} at {:?} moving with velocity {:?}", entity_id, position, velocity);
     /// }
     ///
-    /// // Query with mutable components
+    /// // Query with mutable components, once the queries reading them are dropped
+    /// drop(query);
     /// let mut update_query = world.query::<(&mut Position, &Velocity)>();
     /// for (_, (mut position, velocity)) in update_query.iter().await {
     ///     position.x += velocity.x;
@@ -148,21 +169,73 @@ impl World {
         Query::<Q>::new(&self.entity_manager, &self.component_storage)
     }
 
+    /// Creates a new query for components, fails instead of panicking if it accesses a component
+    /// type mutably and a second time, see [`Query::try_new`]
+    pub fn try_query<Q>(&self) -> Result<Query<Q>>
+    where
+        Q: crate::ecs::query::QueryItem,
+    {
+        Query::<Q>::try_new(&self.entity_manager, &self.component_storage)
+    }
+
+    /// Attaches `component` to an entity, replacing its component of that type
+    ///
+    /// Unlike [`ComponentStorage::insert`], fails instead of panicking for a handle of a deleted
+    /// entity.
+    pub fn insert<T: Component>(&self, entity_id: impl EntityKey, component: T) -> Result<()> {
+        let entity_id = self.component_storage.resolve(entity_id)?;
+        self.component_storage.insert(entity_id, component);
+        Ok(())
+    }
+
+    /// Detaches the component of type `T` from an entity
+    pub fn remove<T: Component>(&self, entity_id: impl EntityKey) -> Result<()> {
+        self.component_storage.remove::<T>(entity_id)
+    }
+
     pub async fn get_component<T: Component>(
         &self,
-        entity_id: impl TryInto<usize>,
+        entity_id: impl EntityKey,
     ) -> Result<ComponentRef<'_, T>> {
-        let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
         self.get_component_storage().get::<T>(entity_id).await
     }
     pub async fn get_component_mut<T: Component>(
         &self,
-        entity_id: impl TryInto<usize>,
+        entity_id: impl EntityKey,
     ) -> Result<ComponentRefMut<'_, T>> {
-        let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
         self.get_component_storage().get_mut::<T>(entity_id).await
     }
 
+    /// Makes `resource` the resource of its type, see [`Resources`]
+    pub fn insert_resource<T: Resource>(&self, resource: T) {
+        self.resources.insert(resource);
+    }
+
+    /// Waits for the resource `T` to be free of writers, fails with
+    /// [`Error::ResourceNotFound`] naming `T` if the world has none
+    pub async fn resource<T: Resource>(&self) -> Result<Res<T>> {
+        self.resources.get::<T>().await
+    }
+
+    /// Waits for the resource `T` to be free of readers and writers, fails with
+    /// [`Error::ResourceNotFound`] naming `T` if the world has none
+    pub async fn resource_mut<T: Resource>(&self) -> Result<ResMut<T>> {
+        self.resources.get_mut::<T>().await
+    }
+
+    /// Fetches the resources of the parameter `P`, like `(ResMut<TimeOfDay>, Res<Config>)`.
+    /// Fails with [`Error::AliasedAccess`] if it accesses a type mutably and a second time.
+    pub async fn fetch<P: SystemParam>(&self) -> Result<P::Item<'_>> {
+        let mut accesses = Vec::new();
+        P::accesses(&mut accesses);
+        check_aliasing(&accesses)?;
+        P::fetch(self).await
+    }
+
+    pub fn get_resources(&self) -> &Resources {
+        &self.resources
+    }
+
     /// <p style="color:#9C27B0;">Returns a reference to the ComponentStorage</p>
     ///
     /// This method provides direct access to the component storage.
@@ -195,14 +268,11 @@ mod multiple_components {
     use super::*;
 
     impl World {
-        pub async fn get_components<T>(
-            &self,
-            entity_id: impl TryInto<usize>,
-        ) -> Result<T::Output<'_>>
+        pub async fn get_components<T>(&self, entity_id: impl EntityKey) -> Result<T::Output<'_>>
         where
             T: GetComponents,
         {
-            let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
+            let entity_id = self.component_storage.resolve(entity_id)?;
             T::get_components(self, entity_id).await
         }
     }
@@ -256,3 +326,69 @@ mod multiple_components {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::world::World;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn stale_handles_no_longer_resolve() {
+        let world = World::new();
+        let entity = world.spawn().await;
+        world
+            .get_component_storage()
+            .insert(entity, Position::new(1, 2, 3));
+        assert_eq!(world.get_component::<Position>(entity).await.unwrap().x, 1);
+
+        world.delete_entity(entity).await.unwrap();
+        assert!(world.get_component::<Position>(entity).await.is_err());
+        assert!(world.delete_entity(entity).await.is_err());
+
+        let respawned = world.spawn().await;
+        assert_eq!(respawned.id, entity.id);
+        world
+            .get_component_storage()
+            .insert(respawned, Position::new(4, 5, 6));
+        // The old handle doesn't reach the entity now living in its slot
+        assert!(world.get_component::<Position>(entity).await.is_err());
+        assert!(world.get_component_mut::<Position>(entity).await.is_err());
+        assert!(world
+            .get_component_storage()
+            .remove::<Position>(entity)
+            .is_err());
+        assert_eq!(
+            world.get_component::<Position>(respawned).await.unwrap().x,
+            4
+        );
+        // A bare index refers to whichever entity lives in the slot
+        let index = entity.id as usize;
+        assert_eq!(world.get_component::<Position>(index).await.unwrap().x, 4);
+    }
+
+    #[tokio::test]
+    async fn generations_are_those_of_the_entity_manager() {
+        let world = World::new();
+        let entity = world.spawn().await;
+        world.insert(entity, Position::new(1, 2, 3)).unwrap();
+
+        // Despawned without going through the world
+        assert!(world.entity_manager.despawn(entity).await);
+        assert!(world.get_component::<Position>(entity).await.is_err());
+        assert!(world.delete_entity(entity).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn components_are_inserted_and_removed_by_type() {
+        let world = World::new();
+        let entity = world.spawn().await;
+        world.insert(entity, Position::new(1, 2, 3)).unwrap();
+        world.insert(entity, Position::new(4, 5, 6)).unwrap();
+        assert_eq!(world.get_component::<Position>(entity).await.unwrap().x, 4);
+
+        world.remove::<Position>(entity).unwrap();
+        assert!(world.get_component::<Position>(entity).await.is_err());
+        world.delete_entity(entity).await.unwrap();
+        assert!(world.insert(entity, Position::new(7, 8, 9)).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::database::Database;
+use crate::net::packets::incoming::player_action::DiggingStatus;
+use crate::net::packets::incoming::use_item_on::BlockFace;
+use crate::net::utils::block_changes::BlockChangeBatcher;
+use crate::net::utils::digging::DigTracker;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::constants::{WORLD_MAX_Y, WORLD_MIN_Y};
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::{Chunk, Palette};
+use crate::world::conversions::block_id;
+use crate::world::dimension::ChunkPos;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Placed by every use of an item on a block until held items are tracked
+const PLACED_BLOCK: &str = "minecraft:stone";
+
+/// A player dug a block, dropped or swapped items, see
+/// [`PlayerAction`](crate::net::packets::incoming::player_action::PlayerAction)
+#[derive(Constructor)]
+pub struct PlayerActionEvent {
+    pub entity_id: u32,
+    pub status: DiggingStatus,
+    pub location: Position,
+    pub face: i8,
+}
+
+/// A player used an item on the `face` of the block at `location`, see
+/// [`UseItemOn`](crate::net::packets::incoming::use_item_on::UseItemOn)
+#[derive(Constructor)]
+pub struct BlockPlaceEvent {
+    pub entity_id: u32,
+    pub location: Position,
+    pub face: BlockFace,
+}
+
+#[event_handler(priority = "normal")]
+async fn on_player_action(event: Arc<PlayerActionEvent>, state: GlobalState) {
+    if let Err(e) = break_block(&event, &state).await {
+        warn!("Failed to break block at {}: {}", event.location, e);
+    }
+}
+
+#[event_handler(priority = "normal")]
+async fn on_block_place(event: Arc<BlockPlaceEvent>, state: GlobalState) {
+    if let Err(e) = place_block(&event, &state).await {
+        warn!("Failed to place block against {}: {}", event.location, e);
+    }
+}
+
+/// Game mode of `entity_id`, survival if it has none
+async fn gamemode(entity_id: u32, state: &GlobalState) -> u8 {
+    state
+        .world
+        .get_component::<Gamemode>(entity_id)
+        .await
+        .map_or(0, |gamemode| gamemode.mode)
+}
+
+/// Creative players break blocks as soon as they start digging, survival players once they dug
+/// long enough, see [`DigTracker`]
+async fn break_block(event: &PlayerActionEvent, state: &GlobalState) -> Result<()> {
+    match (gamemode(event.entity_id, state).await, event.status) {
+        (1, DiggingStatus::Started | DiggingStatus::Finished) => {
+            replace_block(state, &event.location, 0, |block| block != 0).await?;
+        }
+        (0, DiggingStatus::Started) => {
+            DigTracker::start(event.entity_id, &event.location, state).await?;
+        }
+        (0, DiggingStatus::Cancelled) => DigTracker::cancel(event.entity_id, state).await?,
+        (0, DiggingStatus::Finished) => {
+            DigTracker::finish(event.entity_id, &event.location, state).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Places a block against the clicked face, unless the spot is taken or out of the world
+async fn place_block(event: &BlockPlaceEvent, state: &GlobalState) -> Result<()> {
+    // Adventure and spectator players can't build
+    if gamemode(event.entity_id, state).await > 1 {
+        return Ok(());
+    }
+    let (x, y, z) = event.face.placement(&event.location);
+    if !(WORLD_MIN_Y..WORLD_MAX_Y).contains(&y) {
+        debug!("Ignoring block placed outside the world height at y {}", y);
+        return Ok(());
+    }
+
+    let block = Palette {
+        name: PLACED_BLOCK.to_string(),
+        properties: None,
+    };
+    let id = block_id(&block)
+        .ok_or_else(|| Error::Generic(format!("Block {} has no id", PLACED_BLOCK)))?;
+    let location = Position::new(x, y as i16, z);
+    if !replace_block(state, &location, id, |block| block == 0).await? {
+        debug!(
+            "Block placement at {} rejected, the spot is taken",
+            location
+        );
+    }
+    Ok(())
+}
+
+/// Chunk of the overworld holding `location`
+async fn chunk_at(state: &GlobalState, location: &Position) -> Result<Arc<Chunk>> {
+    let (chunk_x, chunk_z) = (location.x >> 4, location.z >> 4);
+    state
+        .world
+        .resource::<Database>()
+        .await?
+        .get_chunk(&ChunkPos::overworld(chunk_x, chunk_z))
+        .await?
+        .ok_or(Error::ChunkNotFound(chunk_x, chunk_z))
+}
+
+/// Id of the block at `location`
+pub(crate) async fn block_at(state: &GlobalState, location: &Position) -> Result<i32> {
+    chunk_at(state, location)
+        .await?
+        .get_block_id(location.x, location.y as i32, location.z)
+}
+
+/// Replaces the block at `location` with the block of id `id` if `replaces` accepts the current
+/// block, then records the change for the players, returns whether the block was replaced
+pub(crate) async fn replace_block(
+    state: &GlobalState,
+    location: &Position,
+    id: i32,
+    replaces: impl FnOnce(i32) -> bool,
+) -> Result<bool> {
+    let chunk = chunk_at(state, location).await?;
+    let (x, y, z) = (location.x, location.y as i32, location.z);
+    if !replaces(chunk.get_block_id(x, y, z)?) {
+        return Ok(false);
+    }
+
+    let mut chunk = Arc::unwrap_or_clone(chunk);
+    chunk.set_block_id(x, y, z, id)?;
+    // Marks the chunk dirty, it is written with the next flush
+    state
+        .world
+        .resource::<Database>()
+        .await?
+        .update_chunk(chunk)
+        .await?;
+
+    state
+        .world
+        .resource::<BlockChangeBatcher>()
+        .await?
+        .record(location, id);
+    Ok(true)
+}
@@ -0,0 +1,25 @@
+use crate::state::GlobalState;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::info;
+
+/// A player sent a chat message, dispatched with
+/// [`dispatch_mutable_event`](crate::events::creation::dispatcher::EventDispatcher::dispatch_mutable_event)
+/// before it is broadcast
+///
+/// Handlers can rewrite `message`, or set `cancelled` so that no one sees it.
+#[derive(Constructor, Clone)]
+pub struct ChatEvent {
+    pub entity_id: u32,
+    pub username: String,
+    pub message: String,
+    pub cancelled: bool,
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_chat(event: Arc<parking_lot::RwLock<ChatEvent>>, _state: GlobalState) {
+    let event = event.read();
+    if !event.cancelled {
+        info!("<{}> {}", event.username, event.message);
+    }
+}
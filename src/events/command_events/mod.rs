@@ -0,0 +1,24 @@
+pub mod node;
+pub mod registry;
+
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::info;
+
+/// A player ran a command, `command` is the raw command line without the leading slash
+#[derive(Constructor)]
+pub struct CommandEvent {
+    pub entity_id: u32,
+    pub command: String,
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_command(event: Arc<CommandEvent>, state: GlobalState) {
+    let username = match state.world.get_component::<Player>(event.entity_id).await {
+        Ok(player) => player.username.clone(),
+        Err(_) => format!("Entity {}", event.entity_id),
+    };
+    info!("{} issued server command: /{}", username, event.command);
+}
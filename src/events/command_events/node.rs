@@ -0,0 +1,137 @@
+//! Syntax of the commands, as a tree of literals and arguments
+//!
+//! Clients parse and highlight what players type with it, see
+//! [`Commands`](crate::net::packets::outgoing::commands::Commands). It only describes the
+//! syntax, commands are still run by listening to [`CommandEvent`](super::CommandEvent).
+
+/// Suggestions of an argument asked to the server, see
+/// [`CommandSuggestionsRequest`](crate::net::packets::incoming::command_suggestions_request::CommandSuggestionsRequest)
+pub const ASK_SERVER: &str = "minecraft:ask_server";
+
+/// How much of the input a string argument reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    /// Until the next space
+    SingleWord,
+    /// A single word, or a phrase in quotes
+    QuotablePhrase,
+    /// The rest of the input
+    GreedyPhrase,
+}
+
+/// Parser of an argument, one of the brigadier and minecraft argument types known to clients
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parser {
+    Bool,
+    Double {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Integer {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    String(StringKind),
+    /// Selector or name, `single` for at most one entity
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    /// Coordinates, possibly relative
+    Vec3,
+}
+
+impl Parser {
+    /// Id of the parser in the `command_argument_type` registry of 1.20.1
+    pub fn id(&self) -> i32 {
+        match self {
+            Parser::Bool => 0,
+            Parser::Double { .. } => 2,
+            Parser::Integer { .. } => 3,
+            Parser::String(_) => 5,
+            Parser::Entity { .. } => 6,
+            Parser::Vec3 => 10,
+        }
+    }
+}
+
+/// Whether a node is matched by its name or parsed by its parser
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Literal(String),
+    Argument {
+        name: String,
+        parser: Parser,
+        /// Identifier of the suggestions of the argument, e.g. [`ASK_SERVER`]
+        suggestions: Option<String>,
+    },
+}
+
+/// Node of the command tree, with the nodes that can follow it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    /// The command can be run when the input ends at this node
+    pub executable: bool,
+    pub children: Vec<CommandNode>,
+    /// Names of the nodes leading to the node the input goes on with, from the root, empty to
+    /// go back to the root
+    pub redirect: Option<Vec<String>>,
+}
+
+impl CommandNode {
+    fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            executable: false,
+            children: Vec::new(),
+            redirect: None,
+        }
+    }
+
+    pub fn literal(name: impl Into<String>) -> Self {
+        Self::new(NodeKind::Literal(name.into()))
+    }
+
+    pub fn argument(name: impl Into<String>, parser: Parser) -> Self {
+        Self::new(NodeKind::Argument {
+            name: name.into(),
+            parser,
+            suggestions: None,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            NodeKind::Literal(name) | NodeKind::Argument { name, .. } => name,
+        }
+    }
+
+    pub fn executes(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Go on with the node at `path`, e.g. `["teleport"]` for an alias of `/teleport`
+    pub fn redirect(mut self, path: &[&str]) -> Self {
+        self.redirect = Some(path.iter().map(|name| name.to_string()).collect());
+        self
+    }
+
+    /// Suggest values of the argument from `suggestions`, ignored on literals
+    pub fn suggests(mut self, suggestions: &str) -> Self {
+        if let NodeKind::Argument {
+            suggestions: current,
+            ..
+        } = &mut self.kind
+        {
+            *current = Some(suggestions.to_string());
+        }
+        self
+    }
+}
@@ -0,0 +1,140 @@
+//! Names and syntax of the commands players can run
+//!
+//! Commands are still run by listening to [`CommandEvent`](super::CommandEvent), the registry
+//! only tells clients which commands exist, e.g. to complete them.
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+
+use crate::events::command_events::node::CommandNode;
+
+struct RegisteredCommand {
+    description: Option<String>,
+    /// Literal node named after the command
+    node: CommandNode,
+}
+
+#[derive(Default)]
+pub struct CommandRegistry {
+    /// Every command, by name
+    commands: DashMap<String, RegisteredCommand>,
+    /// Woken by every change, to send the new tree to the players
+    changes: Notify,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a command `name` without arguments, without the leading slash. Replaces the
+    /// previous command of that name.
+    pub fn register(&self, name: impl Into<String>, description: Option<&str>) {
+        let name = name.into();
+        self.register_node(CommandNode::literal(name).executes(), description);
+    }
+
+    /// Register the command of the literal `node`, with the syntax of its children. Replaces
+    /// the previous command of that name.
+    pub fn register_node(&self, node: CommandNode, description: Option<&str>) {
+        self.commands.insert(
+            node.name().to_string(),
+            RegisteredCommand {
+                description: description.map(str::to_string),
+                node,
+            },
+        );
+        self.changes.notify_one();
+    }
+
+    /// Forget command `name`, returns whether it was registered
+    pub fn unregister(&self, name: &str) -> bool {
+        let removed = self.commands.remove(name).is_some();
+        if removed {
+            self.changes.notify_one();
+        }
+        removed
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Wait for the next change of the commands
+    pub async fn changed(&self) {
+        self.changes.notified().await;
+    }
+
+    /// Nodes of every command, the children of the root of the tree, sorted by name
+    pub fn nodes(&self) -> Vec<CommandNode> {
+        let mut nodes: Vec<CommandNode> = self
+            .commands
+            .iter()
+            .map(|command| command.value().node.clone())
+            .collect();
+        nodes.sort_unstable_by(|a, b| a.name().cmp(b.name()));
+        nodes
+    }
+
+    /// Commands starting with `prefix` and their description, sorted by name
+    pub fn complete(&self, prefix: &str) -> Vec<(String, Option<String>)> {
+        let mut matches: Vec<_> = self
+            .commands
+            .iter()
+            .filter(|command| command.key().starts_with(prefix))
+            .map(|command| (command.key().clone(), command.value().description.clone()))
+            .collect();
+        matches.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandRegistry;
+    use crate::events::command_events::node::{CommandNode, Parser};
+
+    #[test]
+    fn completes_registered_names() {
+        let registry = CommandRegistry::new();
+        registry.register("time", Some("Changes the time"));
+        registry.register("tp", None);
+        registry.register("help", None);
+
+        let matches = registry.complete("t");
+        assert_eq!(
+            matches,
+            vec![
+                ("time".to_string(), Some("Changes the time".to_string())),
+                ("tp".to_string(), None),
+            ]
+        );
+        assert!(registry.unregister("tp"));
+        assert_eq!(registry.complete("tp"), Vec::new());
+    }
+
+    #[tokio::test]
+    async fn changes_are_notified() {
+        let registry = CommandRegistry::new();
+        registry.register_node(
+            CommandNode::literal("kill").then(
+                CommandNode::argument(
+                    "targets",
+                    Parser::Entity {
+                        single: false,
+                        players_only: false,
+                    },
+                )
+                .executes(),
+            ),
+            None,
+        );
+        // The change before waiting isn't missed
+        registry.changed().await;
+
+        let nodes = registry.nodes();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].children[0].name(), "targets");
+        assert!(!registry.unregister("help"));
+    }
+}
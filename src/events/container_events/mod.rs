@@ -0,0 +1,38 @@
+use crate::state::GlobalState;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::debug;
+
+/// A player typed `name` in the anvil of the window `window_id`, see
+/// [`RenameItem`](crate::net::packets::incoming::rename_item::RenameItem)
+#[derive(Constructor)]
+pub struct RenameItemEvent {
+    pub entity_id: u32,
+    pub window_id: u8,
+    pub name: String,
+}
+
+/// A player picked the trade `slot` in the window `window_id`, see
+/// [`SelectTrade`](crate::net::packets::incoming::select_trade::SelectTrade)
+#[derive(Constructor)]
+pub struct SelectTradeEvent {
+    pub entity_id: u32,
+    pub window_id: u8,
+    pub slot: i32,
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_rename_item(event: Arc<RenameItemEvent>, _state: GlobalState) {
+    debug!(
+        "Entity {} renamed the item of window {} to {:?}",
+        event.entity_id, event.window_id, event.name
+    );
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_select_trade(event: Arc<SelectTradeEvent>, _state: GlobalState) {
+    debug!(
+        "Entity {} selected trade {} of window {}",
+        event.entity_id, event.slot, event.window_id
+    );
+}
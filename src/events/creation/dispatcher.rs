@@ -14,4 +14,16 @@ impl EventDispatcher {
         let event = Arc::new(event);
         dispatch_event::<T>(event, state).await;
     }
+
+    /// Dispatch an event the handlers can change, as a `parking_lot::RwLock<T>`, and return it
+    /// once they all ran
+    pub async fn dispatch_mutable_event<T: 'static + Any + Send + Sync + Clone>(&self, event: T, state: GlobalState) -> T {
+        let event = Arc::new(parking_lot::RwLock::new(event));
+        dispatch_event(Arc::clone(&event), state).await;
+        match Arc::try_unwrap(event) {
+            Ok(event) => event.into_inner(),
+            // A handler kept the event around
+            Err(event) => event.read().clone(),
+        }
+    }
 }
\ No newline at end of file
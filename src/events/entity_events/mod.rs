@@ -0,0 +1,88 @@
+use crate::net::packets::incoming::swing_arm::Hand;
+use crate::net::packets::outgoing::entity_animation::{
+    EntityAnimation, SWING_MAIN_ARM, SWING_OFFHAND,
+};
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::net::utils::metadata::update_health;
+use crate::state::GlobalState;
+use crate::utils::components::health::Health;
+use crate::utils::components::vehicle_input::VehicleInput;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Damage of a bare handed hit, until held items are taken into account
+const ATTACK_DAMAGE: f32 = 1.0;
+
+/// A player swung one of its arms
+#[derive(Constructor)]
+pub struct SwingArmEvent {
+    pub entity_id: u32,
+    pub hand: Hand,
+}
+
+/// A player right clicked the `target` entity, `at` is the clicked point relative to the target
+#[derive(Constructor)]
+pub struct EntityInteractEvent {
+    pub entity_id: u32,
+    pub target: u32,
+    pub hand: Hand,
+    pub at: Option<(f32, f32, f32)>,
+    pub sneaking: bool,
+}
+
+/// A player hit the `target` entity
+#[derive(Constructor)]
+pub struct EntityAttackEvent {
+    pub entity_id: u32,
+    pub target: u32,
+    pub sneaking: bool,
+}
+
+/// A player asked to leave the vehicle it rides
+#[derive(Constructor)]
+pub struct DismountEvent {
+    pub entity_id: u32,
+}
+
+/// Shows the swing to the players around
+#[event_handler(priority = "normal")]
+async fn on_swing_arm(event: Arc<SwingArmEvent>, state: GlobalState) {
+    let animation = match event.hand {
+        Hand::Main => SWING_MAIN_ARM,
+        Hand::Off => SWING_OFFHAND,
+    };
+    let packet = EntityAnimation::new(event.entity_id as i32, animation);
+    if let Err(e) = broadcast_to_trackers(packet, event.entity_id as usize, &state).await {
+        warn!(
+            "Failed to broadcast the arm swing of {}: {}",
+            event.entity_id, e
+        );
+    }
+}
+
+/// Damages the target, if it has health
+#[event_handler(priority = "normal")]
+async fn on_entity_attack(event: Arc<EntityAttackEvent>, state: GlobalState) {
+    {
+        let Ok(mut health) = state.world.get_component_mut::<Health>(event.target).await else {
+            return;
+        };
+        if health.damage(ATTACK_DAMAGE) {
+            debug!("Entity {} killed entity {}", event.entity_id, event.target);
+        }
+    }
+    if let Err(e) = update_health(event.target, &state).await {
+        warn!("Failed to show the health of {}: {}", event.target, e);
+    }
+}
+
+/// Forgets the steering input, vehicles don't exist yet so there is nothing else to leave
+#[event_handler(priority = "normal")]
+async fn on_dismount(event: Arc<DismountEvent>, state: GlobalState) {
+    debug!("Entity {} dismounted", event.entity_id);
+    let _ = state
+        .world
+        .get_component_storage()
+        .remove::<VehicleInput>(event.entity_id as usize);
+}
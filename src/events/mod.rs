@@ -1,2 +1,8 @@
+pub mod block_events;
+pub mod chat_events;
+pub mod command_events;
+pub mod container_events;
 pub mod creation;
+pub mod entity_events;
+pub mod player_events;
 pub mod world_events;
\ No newline at end of file
@@ -0,0 +1,32 @@
+use crate::state::GlobalState;
+use crate::utils::components::resource_pack::ResourcePackStatus;
+use ferrumc_macros::{event_handler, Constructor};
+use std::sync::Arc;
+use tracing::debug;
+
+/// A player answered the resource pack offered by the server
+#[derive(Constructor)]
+pub struct ResourcePackStatusEvent {
+    pub entity_id: u32,
+    pub status: ResourcePackStatus,
+}
+
+/// A player changed the language of its client, `locale` is like `en_us`
+#[derive(Constructor)]
+pub struct LocaleChangeEvent {
+    pub entity_id: u32,
+    pub locale: String,
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_resource_pack_status(event: Arc<ResourcePackStatusEvent>, _state: GlobalState) {
+    debug!(
+        "Entity {} resource pack status: {:?}",
+        event.entity_id, event.status
+    );
+}
+
+#[event_handler(priority = "slowest")]
+async fn on_locale_change(event: Arc<LocaleChangeEvent>, _state: GlobalState) {
+    debug!("Entity {} locale: {}", event.entity_id, event.locale);
+}
@@ -3,11 +3,27 @@
 use std::sync::{atomic::AtomicU32, Arc};
 
 use dashmap::DashMap;
+use database::Database;
 use ecs::world::World;
+use events::command_events::registry::CommandRegistry;
+use net::packets::registry::PacketRegistry;
+use net::authentication::ServerKeys;
+use net::plugin_channels::PluginChannelRegistry;
+use net::rate_limit::RateLimits;
+use net::status::load_favicon;
+use net::utils::block_changes::BlockChangeBatcher;
+use net::utils::boss_bar::BossBarRegistry;
 use net::ConnectionList;
 use state::{GlobalState, ServerState};
 use tokio::net::TcpListener;
+use tracing::warn;
+use utils::config::{get_global_config, Config};
 use utils::prelude::*;
+use world::border::WorldBorders;
+use world::difficulty::WorldDifficulty;
+use world::tick_rate::TickRate;
+use world::time::TimeOfDay;
+use world::weather::Weather;
 use crate::events::creation::dispatcher::EventDispatcher;
 
 extern crate core;
@@ -26,15 +42,49 @@ pub mod state;
 pub mod world;
 pub mod events;
 
+/// World of the server, holding `database` and the other resources its systems share: the
+/// config, the time and weather, the tick rate and the blocks changed during the tick
+pub fn create_world(database: Database) -> World {
+    let world = World::new();
+    world.insert_resource(database);
+    world.insert_resource(Config::new(get_global_config()));
+    world.insert_resource(TimeOfDay::default());
+    world.insert_resource(TickRate::default());
+    world.insert_resource(Weather::new(&mut rand::thread_rng()));
+    world.insert_resource(BlockChangeBatcher::default());
+    world
+}
+
 pub async fn create_state(tcp_listener: TcpListener) -> Result<GlobalState> {
+    let database = database::start_database().await?;
+    let difficulty = WorldDifficulty::load(&database).await;
+    let favicon = load_favicon(&get_global_config().status.favicon)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Not showing the favicon: {}", e);
+            None
+        });
+    let server_keys = if get_global_config().authentication.online_mode {
+        Some(ServerKeys::generate()?)
+    } else {
+        None
+    };
     Ok(Arc::new(ServerState {
-        world: Arc::new(World::new()),
+        world: Arc::new(create_world(database)),
         connections: ConnectionList {
             connections: DashMap::new(),
             connection_count: AtomicU32::new(0),
         },
-        database: database::start_database().await?,
         server_stream: tcp_listener,
         event_dispatcher: Arc::new(EventDispatcher::new()),
+        packets: PacketRegistry::new(get_global_config().unknown_packets),
+        plugin_channels: PluginChannelRegistry::new(),
+        commands: CommandRegistry::new(),
+        difficulty,
+        world_borders: WorldBorders::default(),
+        rate_limits: RateLimits::default(),
+        boss_bars: BossBarRegistry::new(),
+        favicon,
+        server_keys,
     }))
 }
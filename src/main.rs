@@ -1,6 +1,9 @@
 use std::env;
 use std::process::exit;
+use std::time::Instant;
 
+use ferrumc::database::{world_path, Database};
+use ferrumc::state::GlobalState;
 use ferrumc::{create_state, setup, utils, world};
 use tokio::net::TcpListener;
 use tokio::select;
@@ -24,9 +27,16 @@ async fn entry() -> Result<()> {
         return Ok(());
     }
 
+    // Inspect the world without starting the server, works while another instance runs it
+    if env::args().any(|arg| arg == "--db-stats") {
+        let database = Database::open_read_only(&world_path()?).await?;
+        info!("Database stats:\n{}", database.stats().await?);
+        return Ok(());
+    }
+
     info!("Initializing server...");
 
-    let server_handle = start_server().await?;
+    let (server_handle, state) = start_server().await?;
 
     let need_to_kill = select! {
         server_result = server_handle => {
@@ -51,6 +61,15 @@ async fn entry() -> Result<()> {
         kill_all_systems().await?;
     }
 
+    let database = state.world.resource::<Database>().await?;
+    // Remember which chunks were in use, to load them back on the next start
+    if let Err(e) = database.save_hot_keys().await {
+        error!("Unable to save the hot chunk keys: {}", e);
+    }
+
+    // Make sure chunks queued by the write-behind mode reach the disk
+    database.flush().await?;
+
     info!("Exiting server;");
 
     Ok(())
@@ -59,8 +78,9 @@ async fn entry() -> Result<()> {
 /// Starts the server. Sets up the sockets and listens for incoming connections
 ///
 /// The actual management of connections tx/rx is handled by [net::systems::connection_handler]
-async fn start_server() -> Result<JoinHandle<Result<()>>> {
+async fn start_server() -> Result<(JoinHandle<Result<()>>, GlobalState)> {
     let config = get_global_config();
+    let start = Instant::now();
     trace!("Starting server on {}:{}", config.host, config.port);
 
     let tcp_addr = format!("{}:{}", config.host, config.port);
@@ -86,11 +106,15 @@ async fn start_server() -> Result<JoinHandle<Result<()>>> {
         exit(0);
     }
 
-    info!("Server started on {}", addr);
+    info!("Server started on {} in {:?}", addr, start.elapsed());
+
+    // Load the chunks used before the last shutdown while the first players connect
+    state.world.resource::<Database>().await?.warm_cache();
 
     // Start all systems (separate task)
+    let systems_state = state.clone();
     let handle = tokio::task::spawn(async {
-        let all_systems = tokio::task::spawn(start_all_systems(state));
+        let all_systems = tokio::task::spawn(start_all_systems(systems_state));
 
         // Wait for all systems to finish
         all_systems.await??;
@@ -100,5 +124,5 @@ async fn start_server() -> Result<JoinHandle<Result<()>>> {
         Ok(())
     });
 
-    Ok(handle)
+    Ok((handle, state))
 }
@@ -0,0 +1,202 @@
+//! Authentication of the players of online mode servers
+//!
+//! The login goes:
+//! 1. The client sends its username, the server answers an encryption request holding its
+//!    public key, generated at startup, and a random verify token.
+//! 2. The client generates a shared secret, tells the session server it is joining with the
+//!    [`server_hash`] of the secret and key, then sends the secret and the verify token,
+//!    encrypted with the public key.
+//! 3. The server checks the token, encrypts the connection with the secret, and asks the session
+//!    server whether the player joined with the same hash. The profile it answers is the one the
+//!    player logs in with.
+//!
+//! In offline mode, players are trusted with their username and get an [`offline_uuid`].
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use ferrumc_macros::Component;
+use md5::{Digest as _, Md5};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use serde::Deserialize;
+use sha1::Sha1;
+
+use crate::utils::components::profile::ProfileProperty;
+use crate::utils::prelude::*;
+
+/// Size of the key pair generated at startup, as vanilla
+pub const KEY_BITS: usize = 1024;
+/// Vanilla servers send an empty server id
+pub const SERVER_ID: &str = "";
+
+pub const INVALID_SESSION: &str = "Failed to verify username!";
+pub const AUTH_SERVERS_DOWN: &str =
+    "Authentication servers are down. Please try again later, sorry!";
+pub const INVALID_ENCRYPTION: &str = "Invalid encryption response";
+
+/// RSA key pair of the server, the client encrypts the shared secret with the public key
+pub struct ServerKeys {
+    private_key: RsaPrivateKey,
+    /// The public key, in the DER format sent to the clients
+    public_key: Vec<u8>,
+}
+
+impl ServerKeys {
+    pub fn generate() -> Result<Self> {
+        let private_key = RsaPrivateKey::new(&mut OsRng, KEY_BITS)
+            .map_err(|e| Error::Generic(format!("Failed to generate the server keys: {}", e)))?;
+        let public_key = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .map_err(|e| Error::Generic(format!("Failed to encode the public key: {}", e)))?
+            .into_vec();
+        Ok(Self {
+            private_key,
+            public_key,
+        })
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Decrypt `data` sent by a client, encrypted with the public key
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|e| Error::Generic(format!("Failed to decrypt: {}", e)))
+    }
+}
+
+/// Login of a player waiting for its encryption response
+#[derive(Component, Debug, Clone)]
+pub struct PendingAuthentication {
+    pub username: String,
+    pub verify_token: [u8; 4],
+}
+
+/// Profile of a player, as answered by the session server
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameProfile {
+    /// UUID without dashes
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl GameProfile {
+    pub fn uuid(&self) -> Result<u128> {
+        u128::from_str_radix(&self.id, 16)
+            .map_err(|_| Error::Generic(format!("Invalid UUID in the profile: {}", self.id)))
+    }
+}
+
+/// Hash identifying a login to the session server: the SHA-1 of the server id, the shared secret
+/// and the public key, printed as a signed hexadecimal number like Java's `BigInteger`
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut hash: [u8; 20] = Sha1::new()
+        .chain_update(server_id)
+        .chain_update(shared_secret)
+        .chain_update(public_key)
+        .finalize()
+        .into();
+
+    let negative = hash[0] & 0x80 != 0;
+    if negative {
+        // Two's complement, to print the magnitude
+        let mut carry = true;
+        for byte in hash.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                (*byte, carry) = byte.overflowing_add(1);
+            }
+        }
+    }
+
+    let digits: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let digits = digits.trim_start_matches('0');
+    if negative {
+        format!("-{}", digits)
+    } else {
+        digits.to_string()
+    }
+}
+
+/// UUID of a player of an offline mode server, derived from its username like vanilla: the MD5
+/// of `OfflinePlayer:<username>`, made a version 3 UUID
+pub fn offline_uuid(username: &str) -> u128 {
+    let hash: [u8; 16] = Md5::new()
+        .chain_update(format!("OfflinePlayer:{}", username))
+        .finalize()
+        .into();
+    uuid::Builder::from_md5_bytes(hash).into_uuid().as_u128()
+}
+
+/// Ask the session server whether `username` joined with `server_hash`, returns its profile, or
+/// `None` if it didn't
+pub async fn has_joined(
+    session_server: &str,
+    username: &str,
+    server_hash: &str,
+) -> Result<Option<GameProfile>> {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    let client = CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default()
+    });
+
+    let response = client
+        .get(format!("{}/session/minecraft/hasJoined", session_server))
+        .query(&[("username", username), ("serverId", server_hash)])
+        .send()
+        .await
+        .map_err(|e| Error::Generic(format!("Session server unreachable: {}", e)))?;
+    match response.status() {
+        reqwest::StatusCode::OK => response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| Error::Generic(format!("Invalid profile: {}", e))),
+        reqwest::StatusCode::NO_CONTENT => Ok(None),
+        status => Err(Error::Generic(format!(
+            "Session server answered {}",
+            status
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{offline_uuid, server_hash};
+
+    #[test]
+    fn server_hashes_are_signed_hexadecimal() {
+        // The examples of the protocol documentation, hashing a single name
+        assert_eq!(
+            server_hash("Notch", &[], &[]),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            server_hash("jeb_", &[], &[]),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            server_hash("simon", &[], &[]),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn offline_uuids_are_derived_from_the_name() {
+        assert_eq!(
+            uuid::Uuid::from_u128(offline_uuid("Notch")).to_string(),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+        assert_ne!(offline_uuid("Notch"), offline_uuid("jeb_"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Framing of packets once compression is enabled
+//!
+//! After [`SetCompression`](crate::net::packets::outgoing::set_compression::SetCompression),
+//! every packet is framed as its length, the length of its uncompressed data, then its data.
+//! The data is zlib compressed when it is at least as long as the threshold, its uncompressed
+//! length is 0 otherwise.
+
+use std::io::{Read, Write};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+use crate::utils::prelude::*;
+
+/// Largest data a compressed packet may inflate to, the limit of vanilla
+pub const MAX_DECOMPRESSED_SIZE: usize = 8 * 1024 * 1024;
+
+/// Frame `packet`, its id followed by its fields, with the compressed framing
+pub async fn compress_packet(packet: &[u8], threshold: usize) -> Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(packet.len() + 5);
+    if packet.len() < threshold {
+        VarInt::new(0).write(&mut body).await?;
+        body.extend_from_slice(packet);
+    } else {
+        VarInt::new(packet.len() as i32).write(&mut body).await?;
+        let mut encoder = ZlibEncoder::new(body, flate2::Compression::default());
+        encoder.write_all(packet).map_err(Error::CompressionError)?;
+        body = encoder.finish().map_err(Error::CompressionError)?;
+    }
+
+    let mut frame = Vec::with_capacity(body.len() + 5);
+    VarInt::new(body.len() as i32).write(&mut frame).await?;
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+/// Reframe `frames`, packets each prefixed by their length, with the compressed framing
+///
+/// Queues of packets are encoded as a single buffer, this compresses each packet in it.
+pub async fn compress_frames(mut frames: &[u8], threshold: usize) -> Result<Vec<u8>> {
+    let mut compressed = Vec::with_capacity(frames.len());
+    while !frames.is_empty() {
+        let len = VarInt::read(&mut frames).await?.get_val() as usize;
+        if len > frames.len() {
+            return Err(Error::Generic(format!(
+                "Packet of {} bytes is longer than its buffer",
+                len
+            )));
+        }
+        let (packet, rest) = frames.split_at(len);
+        compressed.extend_from_slice(&compress_packet(packet, threshold).await?);
+        frames = rest;
+    }
+    Ok(compressed)
+}
+
+/// Read the packet framed in `frame`, what follows the packet length, returns its id followed
+/// by its fields
+///
+/// Packets compressed below the threshold or inflating past [`MAX_DECOMPRESSED_SIZE`] are
+/// refused, as vanilla does.
+pub async fn decompress_packet(mut frame: &[u8], threshold: usize) -> Result<Vec<u8>> {
+    let data_length = VarInt::read(&mut frame).await?.get_val();
+    if data_length == 0 {
+        return Ok(frame.to_vec());
+    }
+
+    let data_length = usize::try_from(data_length)
+        .map_err(|_| Error::BadlyCompressed(format!("negative size {}", data_length)))?;
+    if data_length < threshold {
+        return Err(Error::BadlyCompressed(format!(
+            "size of {} is below the threshold of {}",
+            data_length, threshold
+        )));
+    }
+    if data_length > MAX_DECOMPRESSED_SIZE {
+        return Err(Error::BadlyCompressed(format!(
+            "size of {} is larger than the maximum of {}",
+            data_length, MAX_DECOMPRESSED_SIZE
+        )));
+    }
+
+    let mut packet = Vec::with_capacity(data_length);
+    // Reading one byte more than announced is enough to tell it lied
+    ZlibDecoder::new(frame)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut packet)
+        .map_err(Error::CompressionError)?;
+    if packet.len() != data_length {
+        return Err(Error::BadlyCompressed(format!(
+            "inflated to {} bytes instead of {}",
+            packet.len(),
+            data_length
+        )));
+    }
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use ferrumc_codec::network_types::varint::VarInt;
+    use flate2::write::ZlibEncoder;
+    use tokio::io::AsyncReadExt;
+
+    use super::{compress_frames, compress_packet, decompress_packet, MAX_DECOMPRESSED_SIZE};
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::tests::{test_connection, test_state};
+
+    /// Read a VarInt off `bytes`
+    async fn read_varint(bytes: &mut &[u8]) -> i32 {
+        VarInt::read(bytes).await.unwrap().get_val()
+    }
+
+    /// Split the packet length off `frame`
+    async fn body(mut frame: &[u8]) -> &[u8] {
+        let len = read_varint(&mut frame).await as usize;
+        assert_eq!(len, frame.len());
+        frame
+    }
+
+    /// Frame `packet` compressed, announcing `data_length` as its uncompressed length
+    async fn compress(data_length: usize, packet: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        VarInt::new(data_length as i32)
+            .write(&mut frame)
+            .await
+            .unwrap();
+        let mut encoder = ZlibEncoder::new(frame, flate2::Compression::default());
+        encoder.write_all(packet).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn small_packets_are_sent_uncompressed() {
+        let packet = [0x23, 1, 2, 3, 4, 5, 6, 7, 8];
+        let frame = compress_packet(&packet, 256).await.unwrap();
+        // Packet length, then a data length of 0
+        assert_eq!(frame[..2], [10, 0]);
+        assert_eq!(frame[2..], packet);
+        assert_eq!(
+            decompress_packet(body(&frame).await, 256).await.unwrap(),
+            packet
+        );
+    }
+
+    #[tokio::test]
+    async fn large_packets_are_compressed() {
+        let packet = vec![0x24; 4096];
+        let frame = compress_packet(&packet, 256).await.unwrap();
+        assert!(frame.len() < packet.len());
+
+        let mut data = body(&frame).await;
+        assert_eq!(read_varint(&mut data).await, 4096);
+        assert_eq!(
+            decompress_packet(body(&frame).await, 256).await.unwrap(),
+            packet
+        );
+    }
+
+    #[tokio::test]
+    async fn queued_packets_are_compressed_one_by_one() {
+        let large = vec![0x24; 300];
+        let mut frames = vec![2, 0x23, 7];
+        VarInt::new(large.len() as i32)
+            .write(&mut frames)
+            .await
+            .unwrap();
+        frames.extend_from_slice(&large);
+
+        let compressed = compress_frames(&frames, 256).await.unwrap();
+        let (small, mut rest) = compressed.split_at(4);
+        assert_eq!(small, [3, 0, 0x23, 7]);
+        let len = read_varint(&mut rest).await as usize;
+        assert_eq!(len, rest.len());
+        assert_eq!(decompress_packet(rest, 256).await.unwrap(), large);
+    }
+
+    #[tokio::test]
+    async fn badly_compressed_packets_are_refused() {
+        // Compressed below the threshold
+        let packet = [0x23; 16];
+        assert!(decompress_packet(&compress(16, &packet).await, 256)
+            .await
+            .is_err());
+        assert!(decompress_packet(&compress(16, &packet).await, 16)
+            .await
+            .is_ok());
+        // Inflating past what it announced
+        let bomb = vec![0; 1024 * 1024];
+        assert!(decompress_packet(&compress(256, &bomb).await, 256)
+            .await
+            .is_err());
+        // Announcing more than the maximum
+        assert!(
+            decompress_packet(&compress(MAX_DECOMPRESSED_SIZE + 1, &bomb).await, 256)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn packets_after_set_compression_are_compressed() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        {
+            let conn = state.connections.get_connection(entity_id).unwrap();
+            let conn = conn.read().await;
+            conn.enable_compression(256).await.unwrap();
+            conn.send_packet(KeepAlivePacketOut::new_auto(7))
+                .await
+                .unwrap();
+        }
+
+        // Set Compression itself is sent uncompressed
+        let mut set_compression = [0; 4];
+        client.read_exact(&mut set_compression).await.unwrap();
+        assert_eq!(set_compression, [3, 0x03, 0x80, 0x02]);
+
+        let mut keep_alive = [0; 11];
+        client.read_exact(&mut keep_alive).await.unwrap();
+        let mut packet = vec![0x23];
+        packet.extend_from_slice(&7i64.to_be_bytes());
+        assert_eq!(keep_alive[..2], [10, 0]);
+        assert_eq!(
+            decompress_packet(&keep_alive[1..], 256).await.unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Encryption of the connection streams
+//!
+//! Once the login of an online mode server exchanged a shared secret, both directions are
+//! encrypted with AES-128 in CFB8 mode, the secret serving as both key and IV. The streams of a
+//! [`Connection`](crate::net::Connection) are wrapped in an [`EncryptedReader`] and an
+//! [`EncryptedWriter`], which pass the bytes through until their cipher is set.
+
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::Aes128;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+use crate::utils::prelude::*;
+
+pub type Encryptor = cfb8::Encryptor<Aes128>;
+pub type Decryptor = cfb8::Decryptor<Aes128>;
+
+/// Cipher of an [`EncryptedReader`], shared so that it can be set while the reader waits for
+/// data
+pub type SharedDecryptor = Arc<parking_lot::Mutex<Option<Decryptor>>>;
+
+/// The ciphers of both directions for `shared_secret`
+pub fn ciphers(shared_secret: &[u8]) -> Result<(Encryptor, Decryptor)> {
+    let invalid = |_| {
+        Error::Generic(format!(
+            "Invalid shared secret of {} bytes",
+            shared_secret.len()
+        ))
+    };
+    Ok((
+        Encryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?,
+        Decryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?,
+    ))
+}
+
+fn encrypt(cipher: &mut Encryptor, bytes: &mut [u8]) {
+    for byte in bytes.chunks_mut(1) {
+        cipher.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+    }
+}
+
+fn decrypt(cipher: &mut Decryptor, bytes: &mut [u8]) {
+    for byte in bytes.chunks_mut(1) {
+        cipher.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+    }
+}
+
+/// Read half of a connection, decrypting what it reads once its cipher is set
+pub struct EncryptedReader {
+    inner: OwnedReadHalf,
+    cipher: SharedDecryptor,
+}
+
+impl EncryptedReader {
+    pub fn new(inner: OwnedReadHalf) -> Self {
+        Self {
+            inner,
+            cipher: SharedDecryptor::default(),
+        }
+    }
+
+    /// Handle to set the cipher of the reader
+    pub fn decryptor(&self) -> SharedDecryptor {
+        self.cipher.clone()
+    }
+
+    /// Peek at the bytes waiting to be read, only meaningful before encryption is enabled
+    pub async fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.peek(buf).await
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+impl AsyncRead for EncryptedReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let already_filled = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        if let Some(cipher) = this.cipher.lock().as_mut() {
+            decrypt(cipher, &mut buf.filled_mut()[already_filled..]);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Write half of a connection, encrypting what it writes once its cipher is set
+///
+/// Encrypting advances the cipher, so encrypted bytes are kept until the socket accepts them:
+/// a flush is needed to make sure everything was sent.
+pub struct EncryptedWriter {
+    inner: OwnedWriteHalf,
+    cipher: Option<Encryptor>,
+    pending: Vec<u8>,
+}
+
+impl EncryptedWriter {
+    pub fn new(inner: OwnedWriteHalf) -> Self {
+        Self {
+            inner,
+            cipher: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Encrypt everything written from now on with `cipher`
+    pub fn set_encryptor(&mut self, cipher: Encryptor) {
+        self.cipher = Some(cipher);
+    }
+
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.pending.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.pending.drain(..written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for EncryptedWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.cipher.is_none() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+        ready!(this.poll_write_pending(cx))?;
+
+        let start = this.pending.len();
+        this.pending.extend_from_slice(buf);
+        if let Some(cipher) = this.cipher.as_mut() {
+            encrypt(cipher, &mut this.pending[start..]);
+        }
+        // The bytes are accepted either way, what isn't written yet is on the next write or flush
+        if let Poll::Ready(Err(e)) = this.poll_write_pending(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::{ciphers, encrypt, EncryptedReader, EncryptedWriter};
+
+    #[tokio::test]
+    async fn encrypted_streams_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let (read, _) = server.into_split();
+        let (_, write) = client.into_split();
+        let mut reader = EncryptedReader::new(read);
+        let mut writer = EncryptedWriter::new(write);
+
+        writer.write_all(b"plain").await.unwrap();
+        let mut plain = [0; 5];
+        reader.read_exact(&mut plain).await.unwrap();
+        assert_eq!(&plain, b"plain");
+
+        let secret = [7; 16];
+        let (encryptor, decryptor) = ciphers(&secret).unwrap();
+        writer.set_encryptor(encryptor);
+        *reader.decryptor().lock() = Some(decryptor);
+        // The cipher carries on from one write to the next
+        writer.write_all(b"secret").await.unwrap();
+        writer.write_all(b" message").await.unwrap();
+        writer.flush().await.unwrap();
+
+        let mut message = [0; 14];
+        reader.read_exact(&mut message).await.unwrap();
+        assert_eq!(&message, b"secret message");
+
+        let mut encrypted = *b"secret";
+        encrypt(&mut ciphers(&secret).unwrap().0, &mut encrypted);
+        assert_ne!(&encrypted, b"secret");
+    }
+}
@@ -0,0 +1,227 @@
+//! Player info forwarding of proxies
+//!
+//! Behind a proxy, players connect to the proxy, which connects to the server on their behalf.
+//! The proxy forwards the address, UUID and profile properties (skin) of each player, used for
+//! the login instead of the ones the proxy sent as a client:
+//! - BungeeCord appends them to the server address of the handshake, null separated.
+//! - Velocity answers a login plugin request on [`VELOCITY_CHANNEL`], signed with a secret shared
+//!   with the server.
+//!
+//! Logins without the expected forwarding are refused, anyone could connect directly otherwise.
+
+use std::io::Cursor;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::Component;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::net::drop_conn;
+use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::profile::ProfileProperty;
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+/// Version of the Velocity forwarding requested, the first one carries everything needed
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+/// Size of the HMAC-SHA256 signature heading the Velocity forwarding data
+const SIGNATURE_LEN: usize = 32;
+
+pub const BUNGEECORD_REQUIRED: &str =
+    "This server requires you to connect through BungeeCord with IP forwarding enabled";
+pub const VELOCITY_REQUIRED: &str = "This server requires you to connect through Velocity";
+pub const VELOCITY_INVALID: &str = "Unable to verify the player details forwarded by the proxy";
+
+/// Proxy the server runs behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardingMode {
+    /// Players connect directly
+    #[default]
+    None,
+    Bungeecord,
+    Velocity,
+}
+
+/// Player details forwarded by a proxy
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardedPlayer {
+    /// Address of the player, as seen by the proxy
+    pub address: String,
+    pub uuid: u128,
+    /// Only forwarded by Velocity, BungeeCord keeps the one of the login
+    pub username: Option<String>,
+    pub properties: Vec<ProfileProperty>,
+}
+
+/// Login of a player waiting for the forwarding data of Velocity
+#[derive(Component, Debug, Clone)]
+pub struct PendingForward {
+    pub message_id: i32,
+    pub username: String,
+    pub uuid: u128,
+}
+
+/// Parse the fields BungeeCord appends to the server address: the address of the player, its
+/// UUID without dashes, then the JSON of its profile properties
+pub fn parse_bungeecord(markers: &[String]) -> Option<ForwardedPlayer> {
+    let [address, uuid, rest @ ..] = markers else {
+        return None;
+    };
+    if uuid.len() != 32 || !uuid.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let uuid = u128::from_str_radix(uuid, 16).ok()?;
+    let properties = match rest.iter().find(|marker| marker.starts_with('[')) {
+        Some(json) => serde_json::from_str(json).ok()?,
+        None => Vec::new(),
+    };
+    Some(ForwardedPlayer {
+        address: address.clone(),
+        uuid,
+        username: None,
+        properties,
+    })
+}
+
+/// Check the signature heading the Velocity forwarding `data`, returns the signed payload
+///
+/// Nothing is accepted with an empty `secret`, anyone could sign with it.
+pub fn verify_velocity<'a>(secret: &[u8], data: &'a [u8]) -> Option<&'a [u8]> {
+    if secret.is_empty() || data.len() < SIGNATURE_LEN {
+        return None;
+    }
+    let (signature, payload) = data.split_at(SIGNATURE_LEN);
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).ok()?;
+    mac.update(payload);
+    mac.verify_slice(signature).ok()?;
+    Some(payload)
+}
+
+/// Parse the payload of the Velocity forwarding, once verified
+pub async fn parse_velocity(payload: &[u8]) -> Result<ForwardedPlayer> {
+    let mut cursor = Cursor::new(payload);
+
+    let version = VarInt::read(&mut cursor).await?.get_val();
+    if version < VELOCITY_FORWARDING_VERSION as i32 {
+        return Err(Error::Generic(format!(
+            "Unsupported Velocity forwarding version {}",
+            version
+        )));
+    }
+    let address = *String::net_decode(&mut cursor).await?;
+    let uuid = *u128::net_decode(&mut cursor).await?;
+    let username = *String::net_decode(&mut cursor).await?;
+
+    let count = VarInt::read(&mut cursor).await?.get_val();
+    let mut properties = Vec::new();
+    for _ in 0..count {
+        let name = *String::net_decode(&mut cursor).await?;
+        let value = *String::net_decode(&mut cursor).await?;
+        let signature = if *bool::net_decode(&mut cursor).await? {
+            Some(*String::net_decode(&mut cursor).await?)
+        } else {
+            None
+        };
+        properties.push(ProfileProperty {
+            name,
+            value,
+            signature,
+        });
+    }
+
+    Ok(ForwardedPlayer {
+        address,
+        uuid,
+        username: Some(username),
+        properties,
+    })
+}
+
+/// Refuse the login of `conn_id`, showing it `reason`
+pub async fn reject_login(conn_id: ConnectionId, reason: &str, state: GlobalState) -> Result<()> {
+    {
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(LoginDisconnect::from_text(reason)).await?;
+    }
+    drop_conn(conn_id, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::{parse_bungeecord, parse_velocity, verify_velocity};
+    use crate::utils::components::profile::ProfileProperty;
+
+    #[test]
+    fn bungeecord_fields_are_parsed() {
+        let markers = [
+            "192.168.1.20".to_string(),
+            "069a79f444e94726a5befca90e38aaf5".to_string(),
+            r#"[{"name":"textures","value":"e30=","signature":"c2ln"}]"#.to_string(),
+        ];
+        let forwarded = parse_bungeecord(&markers).unwrap();
+        assert_eq!(forwarded.address, "192.168.1.20");
+        assert_eq!(forwarded.uuid, 0x069a79f444e94726a5befca90e38aaf5);
+        assert_eq!(
+            forwarded.properties,
+            vec![ProfileProperty {
+                name: "textures".to_string(),
+                value: "e30=".to_string(),
+                signature: Some("c2ln".to_string()),
+            }]
+        );
+
+        // Forge markers without forwarding
+        assert_eq!(parse_bungeecord(&["FML2".to_string()]), None);
+    }
+
+    #[tokio::test]
+    async fn velocity_data_is_verified() {
+        // Version, address, UUID, username and a single unsigned property
+        let mut payload = vec![1, 9];
+        payload.extend_from_slice(b"127.0.0.1");
+        payload.extend_from_slice(&7u128.to_be_bytes());
+        payload.push(5);
+        payload.extend_from_slice(b"Steve");
+        payload.extend_from_slice(&[1, 8]);
+        payload.extend_from_slice(b"textures");
+        payload.push(4);
+        payload.extend_from_slice(b"e30=");
+        payload.push(0);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(&payload);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(&payload);
+
+        assert_eq!(verify_velocity(b"other secret", &data), None);
+        let verified = verify_velocity(b"secret", &data).unwrap();
+        let forwarded = parse_velocity(verified).await.unwrap();
+        assert_eq!(forwarded.address, "127.0.0.1");
+        assert_eq!(forwarded.uuid, 7);
+        assert_eq!(forwarded.username.as_deref(), Some("Steve"));
+        assert_eq!(forwarded.properties[0].signature, None);
+
+        data[40] ^= 1;
+        assert_eq!(verify_velocity(b"secret", &data), None);
+    }
+
+    #[test]
+    fn velocity_data_signed_with_an_empty_secret_is_rejected() {
+        let payload = [1, 0];
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"").unwrap();
+        mac.update(&payload);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(&payload);
+
+        assert_eq!(verify_velocity(b"", &data), None);
+    }
+}
@@ -1,21 +1,39 @@
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Display};
 use std::io::Cursor;
+use std::ops::RangeInclusive;
 use std::sync::atomic::AtomicU32;
-use std::sync::{atomic, Arc};
-use std::time::Duration;
+use std::sync::{atomic, Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 use ferrumc_codec::enc::NetEncode;
 use ferrumc_codec::network_types::varint::VarInt;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use tracing::{debug, error, trace};
+use tokio::task::AbortHandle;
+use tracing::{debug, error, trace, warn};
 
 use ferrumc_macros::Component;
 
-use crate::net::packets::handle_packet;
+use crate::net::encryption::{EncryptedReader, EncryptedWriter, SharedDecryptor};
+use crate::net::forwarding::ForwardedPlayer;
+use crate::net::packets::incoming::handshake::answer_legacy_ping;
+use crate::net::packets::outgoing::clear_titles::ClearTitles;
+use crate::net::packets::outgoing::disconnect::Disconnect;
+use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
+use crate::net::packets::outgoing::set_action_bar_text::SetActionBarText;
+use crate::net::packets::outgoing::set_compression::SetCompression;
+use crate::net::rate_limit::{
+    disconnect_offender, RateLimiter, PACKET_TOO_LARGE, TOO_MANY_PACKETS,
+};
+use crate::net::systems::player_save_system::save_player;
+use crate::net::utils::tab_list::TabList;
+use crate::net::utils::title::TitleBuilder;
 use crate::state::GlobalState;
+use crate::utils::chat::ChatComponent;
+use crate::utils::components::player::Player;
+use crate::utils::constants::PROTOCOL_VERSION;
 
 use super::utils::config::get_global_config;
 use super::utils::prelude::*;
@@ -35,11 +53,22 @@ impl Debug for ConnectionWrapper {
 unsafe impl Send for ConnectionWrapper {}
 unsafe impl Sync for ConnectionWrapper {}
 
+pub mod authentication;
+pub mod compression;
+pub mod encryption;
+pub mod forwarding;
+pub mod movement;
 pub mod packets;
+pub mod plugin_channels;
+pub mod rate_limit;
+pub mod status;
 pub mod systems;
 mod test_ecs;
 pub mod the_dimension_codec;
 
+/// Protocol versions of the clients that can join, others are disconnected during login
+pub const SUPPORTED_PROTOCOL_RANGE: RangeInclusive<i32> = PROTOCOL_VERSION..=PROTOCOL_VERSION;
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum State {
     Unknown,
@@ -97,6 +126,8 @@ impl ConnectionList {
 /// - `state`: The current state of the connection ([State]).
 /// - `metadata`: Metadata for the connection ([ConnectionMetadata]).
 /// - `drop`: Whether to drop and clean up the connection after this network tick.
+/// - `keep_alive`: The task sending keep alives to the player, aborted with the connection.
+/// - `compression_threshold`: The threshold of the compressed framing, once enabled during login.
 pub struct Connection {
     pub id: u32,
     // pub socket: tokio::net::TcpStream,
@@ -105,17 +136,27 @@ pub struct Connection {
     pub state: State,
     pub metadata: ConnectionMetadata,
     pub drop: bool,
+    pub keep_alive: Option<AbortHandle>,
+    pub compression_threshold: OnceLock<usize>,
 }
 
 pub struct NetStream {
-    pub in_stream: Mutex<tokio::net::tcp::OwnedReadHalf>,
-    pub out_stream: Mutex<tokio::net::tcp::OwnedWriteHalf>,
+    pub in_stream: Mutex<EncryptedReader>,
+    pub out_stream: Mutex<EncryptedWriter>,
+    /// Sets the cipher of `in_stream`, without waiting for the reader to release it
+    pub decryptor: SharedDecryptor,
 }
 
 #[derive(Debug, Default)]
 pub struct ConnectionMetadata {
     pub protocol_version: i32,
     pub entity: usize,
+    /// Address the client connected to, as sent in its handshake
+    pub server_address: String,
+    /// Markers appended to the server address by modded clients, such as `FML2` for Forge
+    pub address_markers: Vec<String>,
+    /// Player details BungeeCord appended to the server address
+    pub forwarded: Option<ForwardedPlayer>,
 }
 
 pub fn setup_tracer() {
@@ -128,20 +169,46 @@ pub fn setup_tracer() {
 ///
 /// Creates a new [Connection] and adds it to the [ConnectionList]. Passes the connection to [manage_conn].
 pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState) -> Result<()> {
+    let conn = register_connection(socket, &state).await;
+    let entity_id = conn.read().await.id;
+
+    let res = manage_conn(conn.clone(), state.clone()).await;
+
+    if let Err(e) = res {
+        error!(
+            "Error occurred in {:?}: {:?}, dropping connection",
+            entity_id, e
+        );
+        drop_conn(entity_id, state).await?;
+    }
+
+    Ok(())
+}
+
+/// Creates the entity of a new connection and adds the connection to the [ConnectionList].
+pub(crate) async fn register_connection(
+    socket: tokio::net::TcpStream,
+    state: &GlobalState,
+) -> Arc<RwLock<Connection>> {
     let entity_id = state.world.create_entity().await.build() as u32;
 
     let (in_stream, out_stream) = socket.into_split();
+    let in_stream = EncryptedReader::new(in_stream);
+    let decryptor = in_stream.decryptor();
 
     let conn = Connection {
         id: entity_id,
         stream: NetStream {
             in_stream: Mutex::new(in_stream),
-            out_stream: Mutex::new(out_stream),
+            out_stream: Mutex::new(EncryptedWriter::new(out_stream)),
+            decryptor,
         },
         player_uuid: None,
         state: State::Handshake,
         metadata: ConnectionMetadata::default(),
         drop: false,
+        keep_alive: None,
+        compression_threshold: OnceLock::new(),
     };
 
     let conn = Arc::new(RwLock::new(conn));
@@ -171,46 +238,65 @@ pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState)
         entity_id, current_amount
     );
 
-    let res = manage_conn(conn.clone(), state.clone()).await;
-
-    if let Err(e) = res {
-        error!(
-            "Error occurred in {:?}: {:?}, dropping connection",
-            entity_id, e
-        );
-        drop_conn(entity_id, state).await?;
-    }
-
-    Ok(())
+    conn
 }
 
 /// Manages a connection. This is the main loop for a connection.
 ///
 /// - `conn`: The connection to manage ([Arc<RwLock<Connection>>]).
 ///
-/// Reads packets from the connection and passes them to [handle_packet]. The handle_packet function
-/// is generated at compile time by [ferrumc_macros::bake_packet_registry].
+/// Reads packets from the connection and passes them to their handler in the
+/// [packet registry](crate::net::packets::registry::PacketRegistry).
 pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> Result<()> {
-    {
-        let local_addr = conn
-            .read()
-            .await
-            .stream
-            .in_stream
-            .lock()
-            .await
-            .peer_addr()?;
-        debug!("Starting receiver for the addr: {:?}", local_addr);
+    let peer_addr = conn
+        .read()
+        .await
+        .stream
+        .in_stream
+        .lock()
+        .await
+        .peer_addr()?;
+    debug!("Starting receiver for the addr: {:?}", peer_addr);
+
+    let legacy_ping = answer_legacy_ping(&*conn.read().await, &state).await?;
+    if legacy_ping {
+        let conn_id = conn.read().await.id;
+        return drop_conn(conn_id, state).await;
     }
 
+    let limits = &get_global_config().rate_limit;
+    let mut rate_limiter = RateLimiter::new(limits, Instant::now());
+
     loop {
         // Get the length of the packet
         let conn_read = conn.read().await;
+        let conn_id = conn_read.id;
 
         trace!("Reading length buffer");
 
-        let (packet_length, buffer) = get_packet_length_and_buffer(&conn_read).await?;
-        let (conn_id, conn_state) = (conn_read.id, conn_read.state.clone());
+        let (packet_length, buffer) =
+            match get_packet_length_and_buffer(&conn_read, limits.max_packet_length).await {
+                Err(Error::PacketTooLarge(length)) => {
+                    drop(conn_read);
+                    debug!("Entity {} declared a packet of {} bytes", conn_id, length);
+                    state.rate_limits.record_oversized();
+                    return disconnect_offender(
+                        conn_id,
+                        peer_addr.ip(),
+                        PACKET_TOO_LARGE,
+                        limits,
+                        state,
+                    )
+                    .await;
+                }
+                result => result?,
+            };
+        let conn_state = conn_read.state.clone();
+        // Badly compressed packets are protocol violations, they drop the connection
+        let buffer = match conn_read.compression_threshold.get() {
+            Some(&threshold) => compression::decompress_packet(&buffer, threshold).await?,
+            None => buffer,
+        };
         // drop the handle to the write lock. to allow other tasks to write/read
         // mainly cuz the packet tries to access ECS component. And some system tries to access connection turns into a deadlock!!
         drop(conn_read);
@@ -225,11 +311,23 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
 
         let packet_id = packet_id.get_val() as u8;
 
+        if !rate_limiter.allow(&conn_state, packet_id, Instant::now()) {
+            debug!(
+                "Entity {} exceeded the budget of packet {:#04x}",
+                conn_id, packet_id
+            );
+            state.rate_limits.record_limited();
+            return disconnect_offender(conn_id, peer_addr.ip(), TOO_MANY_PACKETS, limits, state)
+                .await;
+        }
+
         let state_clone = state.clone();
         tokio::spawn(async move {
-            handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state_clone).await
+            state_clone
+                .packets
+                .handle(packet_id, conn_id, &conn_state, cursor, state_clone.clone())
+                .await
         });
-        // handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state.clone()).await?;
 
         drop_conn_if_flagged(conn.clone(), state.clone()).await?;
 
@@ -244,12 +342,19 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
     #[allow(unreachable_code)]
     Ok(())
 }
+/// Reads the next packet, refusing to allocate more than `max_length` bytes for it
 async fn get_packet_length_and_buffer(
     conn: &RwLockReadGuard<'_, Connection>,
+    max_length: usize,
 ) -> Result<(VarInt, Vec<u8>)> {
     let mut conn = conn.get_in_stream().await;
     let packet_length = VarInt::read(&mut *conn).await?;
-    let mut buffer = vec![0u8; packet_length.get_val() as usize];
+    let length = usize::try_from(packet_length.get_val())
+        .map_err(|_| Error::PacketTooLarge(packet_length.get_val() as i64))?;
+    if length > max_length {
+        return Err(Error::PacketTooLarge(length as i64));
+    }
+    let mut buffer = vec![0u8; length];
     conn.read_exact(&mut buffer).await?;
     Ok((packet_length, buffer))
 }
@@ -265,6 +370,26 @@ async fn drop_conn_if_flagged(conn: Arc<RwLock<Connection>>, state: GlobalState)
 
     Ok(())
 }
+/// Disconnects a player logging in or playing, showing them `reason`
+///
+/// The disconnect is flushed before the socket is closed, so the client shows it. Dropping the
+/// connection locks it, so it must not be locked by the caller.
+pub async fn kick_conn(
+    connection_id: u32,
+    reason: impl Into<ChatComponent>,
+    state: GlobalState,
+) -> Result<()> {
+    {
+        let conn = state.connections.get_connection(connection_id)?;
+        let conn = conn.read().await;
+        match conn.state {
+            State::Login => conn.send_packet(LoginDisconnect::new(reason)).await?,
+            _ => conn.send_packet(Disconnect::new(reason)).await?,
+        }
+    }
+    drop_conn(connection_id, state).await
+}
+
 pub async fn drop_conn(connection_id: u32, state: GlobalState) -> Result<()> {
     debug!("Dropping connection with id: {}", connection_id);
     let connection = state.connections.connections.remove(&connection_id);
@@ -279,20 +404,72 @@ pub async fn drop_conn(connection_id: u32, state: GlobalState) -> Result<()> {
     {
         let read_lock = conn_arc.read().await;
         let entity_id = read_lock.id;
+        // Connections that never logged in have nothing to save
+        let uuid = state
+            .world
+            .get_component::<Player>(entity_id)
+            .await
+            .map(|player| player.uuid)
+            .ok();
+        if uuid.is_some() {
+            if let Err(e) = save_player(entity_id, &state).await {
+                warn!("Failed to save player {}: {}", entity_id, e);
+            }
+        }
         state.world.delete_entity(entity_id).await?;
+        if let Some(uuid) = uuid {
+            if let Err(e) = TabList::leave(uuid, &state).await {
+                warn!(
+                    "Failed to remove player {} from the tab list: {}",
+                    entity_id, e
+                );
+            }
+        }
     }
 
     // drop the connection in the end, just in case it errors out
     let conn = conn_arc.read().await;
-    let mut conn = conn.get_out_stream().await;
-    conn.shutdown().await?;
+    let shutdown = conn.get_out_stream().await.shutdown().await;
+    // Last, as it may be the task dropping the connection
+    if let Some(keep_alive) = &conn.keep_alive {
+        keep_alive.abort();
+    }
+    shutdown?;
     Ok(())
 }
 
 impl Connection {
     pub async fn send_packet(&self, packet: impl NetEncode) -> Result<()> {
         let mut out_stream = self.get_out_stream().await;
-        packet.net_encode(&mut *out_stream).await?;
+        match self.compression_threshold.get() {
+            Some(&threshold) => {
+                let mut frames = Vec::new();
+                packet.net_encode(&mut frames).await?;
+                let frames = compression::compress_frames(&frames, threshold).await?;
+                out_stream.write_all(&frames).await?;
+            }
+            None => packet.net_encode(&mut *out_stream).await?,
+        }
+        // Encrypted bytes are only written for sure once flushed
+        out_stream.flush().await?;
+        Ok(())
+    }
+
+    /// Send [SetCompression] and switch to the compressed framing, packets with data of at
+    /// least `threshold` bytes are then compressed
+    ///
+    /// Only needs a read lock, the reader of the connection holds one while waiting for packets.
+    pub async fn enable_compression(&self, threshold: usize) -> Result<()> {
+        let mut out_stream = self.get_out_stream().await;
+        // Before the client can get it and answer compressed, and while holding the stream so
+        // that no packet is sent uncompressed after it
+        self.compression_threshold
+            .set(threshold)
+            .map_err(|_| Error::Generic("Compression is already enabled".to_string()))?;
+        SetCompression::new(threshold)
+            .net_encode(&mut *out_stream)
+            .await?;
+        out_stream.flush().await?;
         Ok(())
     }
 
@@ -301,15 +478,38 @@ impl Connection {
         self.send_packet(packets).await
     }
 
-    pub async fn get_in_stream(&self) -> MutexGuard<'_, tokio::net::tcp::OwnedReadHalf> {
+    pub async fn get_in_stream(&self) -> MutexGuard<'_, EncryptedReader> {
         self.stream.in_stream.lock().await
     }
 
-    pub async fn get_out_stream(&self) -> MutexGuard<'_, tokio::net::tcp::OwnedWriteHalf> {
+    pub async fn get_out_stream(&self) -> MutexGuard<'_, EncryptedWriter> {
         self.stream.out_stream.lock().await
     }
 
+    /// Encrypt both directions with `shared_secret`, from the next byte on
+    pub async fn enable_encryption(&self, shared_secret: &[u8]) -> Result<()> {
+        let (encryptor, decryptor) = encryption::ciphers(shared_secret)?;
+        *self.stream.decryptor.lock() = Some(decryptor);
+        self.get_out_stream().await.set_encryptor(encryptor);
+        Ok(())
+    }
+
     pub async fn drop_connection(&self, state: GlobalState) -> Result<()> {
         drop_conn(self.id, state).await
     }
+
+    /// Show `title` in the middle of the screen of the player
+    pub async fn send_title(&self, title: &TitleBuilder) -> Result<()> {
+        self.send_packets(title.packets().await?).await
+    }
+
+    /// Show `text` above the hotbar of the player
+    pub async fn send_action_bar(&self, text: impl Into<ChatComponent>) -> Result<()> {
+        self.send_packet(SetActionBarText::new(text)).await
+    }
+
+    /// Clear the title shown to the player, and reset its subtitle and times if `reset` is set
+    pub async fn clear_title(&self, reset: bool) -> Result<()> {
+        self.send_packet(ClearTitles::new(reset)).await
+    }
 }
@@ -0,0 +1,145 @@
+//! Validation of the moves sent by players
+//!
+//! The movement packets go through [`validate_position`] and [`validate_rotation`] before
+//! anything is applied. Coordinates that aren't numbers disconnect the player, heights are
+//! clamped into the world, and moves longer than the configured limit or further than
+//! [`BORDER_ALLOWANCE`] beyond the world border send the player back instead. Refused moves
+//! are counted by the [`MovementTracker`] of the player.
+
+use tracing::{debug, warn};
+
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::movement::{MoveCheck, MovementTracker};
+use crate::utils::components::pending_teleports::PendingTeleports;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Distance players may walk beyond the world border, in blocks
+const BORDER_ALLOWANCE: f64 = 5.0;
+
+/// Check the position sent by `conn_id`, returns where to move it, or `None` if the move was
+/// refused
+pub async fn validate_position(
+    conn_id: ConnectionId,
+    position: (f64, f64, f64),
+    state: &GlobalState,
+) -> Result<Option<(f64, f64, f64)>> {
+    let component_storage = state.world.get_component_storage();
+
+    let current = component_storage.get::<Position>(conn_id).await?.clone();
+    let last_teleport = component_storage
+        .get::<PendingTeleports>(conn_id)
+        .await
+        .map_or(0, |teleports| teleports.last_issued());
+    let (check, from) = {
+        let mut tracker = component_storage
+            .get_mut_or_insert_with::<MovementTracker>(conn_id, Default::default)
+            .await;
+        let from = tracker.last().unwrap_or((
+            current.x as f64 + 0.5,
+            current.y as f64,
+            current.z as f64 + 0.5,
+        ));
+        let check = tracker.check(
+            position,
+            &current,
+            last_teleport,
+            &get_global_config().movement,
+        );
+        (check, from)
+    };
+
+    match check {
+        MoveCheck::Accepted { x, y, z } => {
+            let border = state
+                .world_borders
+                .get(&Player::dimension(conn_id, state).await);
+            let outside = -border.distance_inside(x, z);
+            // Players left outside by a shrinking border can still walk back
+            if outside > BORDER_ALLOWANCE && outside > -border.distance_inside(from.0, from.2) {
+                debug!(
+                    "Entity {} moved beyond the world border to {:?}, sending it back",
+                    conn_id, position
+                );
+                send_back(conn_id, from, state).await?;
+                return Ok(None);
+            }
+            Ok(Some((x, y, z)))
+        }
+        MoveCheck::Invalid => {
+            warn!(
+                "Entity {} sent an invalid position: {:?}",
+                conn_id, position
+            );
+            kick(conn_id, state).await?;
+            Ok(None)
+        }
+        MoveCheck::TooFar { x, y, z } => {
+            debug!(
+                "Entity {} moved too far to {:?}, sending it back",
+                conn_id, position
+            );
+            send_back(conn_id, (x, y, z), state).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Teleport `conn_id` back to `(x, y, z)`, where its next moves are measured from
+async fn send_back(
+    conn_id: ConnectionId,
+    (x, y, z): (f64, f64, f64),
+    state: &GlobalState,
+) -> Result<()> {
+    let component_storage = state.world.get_component_storage();
+    let teleport_id = component_storage
+        .get_mut_or_insert_with::<PendingTeleports>(conn_id, Default::default)
+        .await
+        .issue();
+    component_storage
+        .get_mut_or_insert_with::<MovementTracker>(conn_id, Default::default)
+        .await
+        .sent_to((x, y, z), teleport_id);
+
+    let conn = state.connections.get_connection(conn_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(SynchronizePlayerPosition::position_only(
+        x,
+        y,
+        z,
+        teleport_id,
+    ))
+    .await
+}
+
+/// Check the rotation sent by `conn_id`, returns whether it can be applied
+pub async fn validate_rotation(
+    conn_id: ConnectionId,
+    yaw: f32,
+    pitch: f32,
+    state: &GlobalState,
+) -> Result<bool> {
+    let valid = state
+        .world
+        .get_component_storage()
+        .get_mut_or_insert_with::<MovementTracker>(conn_id, Default::default)
+        .await
+        .check_rotation(yaw, pitch);
+    if !valid {
+        warn!(
+            "Entity {} sent an invalid rotation: {}, {}",
+            conn_id, yaw, pitch
+        );
+        kick(conn_id, state).await?;
+    }
+    Ok(valid)
+}
+
+async fn kick(conn_id: ConnectionId, state: &GlobalState) -> Result<()> {
+    kick_conn(conn_id, "Invalid movement", state.clone()).await
+}
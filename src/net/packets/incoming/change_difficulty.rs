@@ -0,0 +1,113 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::change_difficulty::ChangeDifficulty;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::components::operator::{op_level, GAMEMASTER_OP_LEVEL};
+use crate::world::difficulty::Difficulty;
+
+/// The change difficulty packet is sent by the client when an operator picks a difficulty in
+/// the pause menu.
+///
+/// Every player is sent the new difficulty with a
+/// [ChangeDifficulty](crate::net::packets::outgoing::change_difficulty::ChangeDifficulty).
+#[derive(NetDecode)]
+#[packet(packet_id = 0x02, state = "play")]
+pub struct ChangeDifficultyRequest {
+    pub difficulty: u8,
+}
+
+impl IncomingPacket for ChangeDifficultyRequest {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ChangeDifficulty packet received: {}", self.difficulty);
+
+        let Some(difficulty) = Difficulty::from_id(self.difficulty) else {
+            warn!(
+                "Kicking entity {}, it sent the invalid difficulty {}",
+                conn_id, self.difficulty
+            );
+            return kick_conn(conn_id, "Invalid difficulty", state.clone()).await;
+        };
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+
+        if op_level(conn_id, &state).await < GAMEMASTER_OP_LEVEL {
+            debug!(
+                "Entity {} changed the difficulty without permission",
+                conn_id
+            );
+            // Puts the menu of the sender back to the actual difficulty
+            return conn
+                .send_packet(ChangeDifficulty::new(&state.difficulty))
+                .await;
+        }
+        drop(conn);
+
+        if !state.difficulty.set(difficulty) {
+            return Ok(());
+        }
+        debug!(
+            "Entity {} changed the difficulty to {:?}",
+            conn_id, difficulty
+        );
+        let database = state.world.resource::<Database>().await?;
+        if let Err(e) = state.difficulty.save(&database).await {
+            warn!("Failed to save the difficulty: {}", e);
+        }
+        broadcast(ChangeDifficulty::new(&state.difficulty), &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::ChangeDifficultyRequest;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::operator::{Operator, MAX_OP_LEVEL};
+    use crate::utils::components::player::Player;
+    use crate::world::difficulty::Difficulty;
+
+    #[tokio::test]
+    async fn only_operators_change_the_difficulty() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Player::new(1, "Alex".to_string()));
+
+        ChangeDifficultyRequest { difficulty: 3 }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        // Length, id of the packet, difficulty, then whether it is locked
+        let mut packet = [0u8; 4];
+        client.read_exact(&mut packet).await.unwrap();
+        assert_eq!(packet, [3, 0x0C, 2, 0]);
+        assert_eq!(state.difficulty.difficulty(), Difficulty::Normal);
+
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Operator::new(MAX_OP_LEVEL));
+        ChangeDifficultyRequest { difficulty: 3 }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        client.read_exact(&mut packet).await.unwrap();
+        assert_eq!(packet, [3, 0x0C, 3, 0]);
+        assert_eq!(state.difficulty.difficulty(), Difficulty::Hard);
+    }
+}
@@ -0,0 +1,112 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::command_events::CommandEvent;
+use crate::net::kick_conn;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Longest command line a client may send, longer ones get it kicked
+pub const MAX_COMMAND_LENGTH: usize = 256;
+/// Most signed arguments a command may carry
+const MAX_ARGUMENT_SIGNATURES: usize = 8;
+
+/// Signature of a command argument, only checked when secure chat is enforced
+pub struct ArgumentSignature {
+    pub argument_name: String,
+    pub signature: [u8; 256],
+}
+
+impl crate::utils::impls::packet_impls::NetDecode for ArgumentSignature {
+    async fn net_decode<T>(bytes: &mut T) -> crate::utils::prelude::Result<Box<Self>>
+    where
+        T: tokio::io::AsyncRead + Unpin,
+    {
+        Ok(Box::new(ArgumentSignature {
+            argument_name: *String::net_decode(bytes).await?,
+            signature: *<[u8; 256]>::net_decode(bytes).await?,
+        }))
+    }
+}
+
+/// The chat command packet is sent by the client when the player runs a command, without the
+/// leading slash. Chat messages have their own packet.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x04, state = "play")]
+pub struct ChatCommand {
+    pub command: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub argument_signatures: Vec<ArgumentSignature>,
+    pub message_count: VarInt,
+    pub acknowledged: [u8; 3],
+}
+
+impl IncomingPacket for ChatCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ChatCommand packet received: /{}", self.command);
+
+        if self.command.chars().count() > MAX_COMMAND_LENGTH {
+            warn!(
+                "Kicking entity {}, its command is longer than {} characters",
+                conn_id, MAX_COMMAND_LENGTH
+            );
+            return kick_conn(conn_id, "Command too long", state.clone()).await;
+        }
+        // The server doesn't enforce secure chat, signatures are unused
+        if self.argument_signatures.len() > MAX_ARGUMENT_SIGNATURES {
+            warn!(
+                "Ignoring command with {} signed arguments",
+                self.argument_signatures.len()
+            );
+            return Ok(());
+        }
+        if self.command.trim().is_empty() {
+            return Ok(());
+        }
+
+        let event = CommandEvent::new(conn_id, self.command);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::ChatCommand;
+
+    #[tokio::test]
+    async fn decode_signed_command() {
+        let mut data = vec![11];
+        data.extend_from_slice(b"gamemode 1 ");
+        data.extend_from_slice(&1_700_000_000_000i64.to_be_bytes());
+        data.extend_from_slice(&42i64.to_be_bytes());
+        // One signed argument
+        data.push(1);
+        data.push(4);
+        data.extend_from_slice(b"mode");
+        data.extend_from_slice(&[7; 256]);
+        data.extend_from_slice(&[0, 0, 0, 0]);
+
+        let packet = ChatCommand::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.command, "gamemode 1 ");
+        assert_eq!(packet.salt, 42);
+        assert_eq!(packet.argument_signatures.len(), 1);
+        assert_eq!(packet.argument_signatures[0].argument_name, "mode");
+        assert_eq!(packet.argument_signatures[0].signature, [7; 256]);
+        assert_eq!(packet.message_count.get_val(), 0);
+    }
+}
@@ -1,10 +1,23 @@
-use tracing::debug;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, trace, warn};
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::events::chat_events::ChatEvent;
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::player_chat_message::{PlayerChatMessage, CHAT_TYPE_CHAT};
+use crate::net::packets::outgoing::system_chat_message::SystemChatMessage;
 use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast;
 use crate::state::GlobalState;
+use crate::utils::chat::{ChatComponent, HoverEvent};
+use crate::utils::components::chat_session::{LastChatMessage, SentMessages};
 use crate::utils::components::player::Player;
+use crate::utils::config::{get_global_config, Chat, DEFAULT_CHAT_FORMAT};
+
+/// Longest chat message a client may send, longer ones get it kicked
+pub const MAX_MESSAGE_LENGTH: usize = 256;
 
 #[derive(NetDecode)]
 #[packet(packet_id = 0x05, state = "play")]
@@ -13,18 +26,170 @@ pub struct PacketChatMessage {
     pub timestamp: i64,
 }
 
+/// Whether `c` may be sent in chat, vanilla kicks for section signs and control characters
+fn is_allowed_character(c: char) -> bool {
+    c != '§' && c >= ' ' && c != '\u{7F}'
+}
+
+/// Why `message` is refused, `None` if it is valid
+fn invalid_message(message: &str) -> Option<&'static str> {
+    if message.chars().count() > MAX_MESSAGE_LENGTH {
+        Some("Chat message too long")
+    } else if !message.chars().all(is_allowed_character) {
+        Some("Illegal characters in chat")
+    } else {
+        None
+    }
+}
+
+/// Line shown for `message` sent by `name`, following the configured format
+fn format_message(config: &Chat, name: &ChatComponent, message: &str) -> ChatComponent {
+    let message = ChatComponent::text(message);
+    let mut line = ChatComponent::text("");
+    let mut rest = config.format.as_str();
+    // The placeholders of the format only, a message mentioning `{name}` is shown as typed
+    while let Some((start, placeholder, component)) = [("{name}", name), ("{message}", &message)]
+        .into_iter()
+        .filter_map(|(placeholder, component)| {
+            rest.find(placeholder)
+                .map(|start| (start, placeholder, component))
+        })
+        .min_by_key(|(start, ..)| *start)
+    {
+        if start > 0 {
+            line = line.extra(&rest[..start]);
+        }
+        line = line.extra(component.clone());
+        rest = &rest[start + placeholder.len()..];
+    }
+    if !rest.is_empty() {
+        line = line.extra(rest);
+    }
+    line
+}
+
 impl IncomingPacket for PacketChatMessage {
     async fn handle(
         self,
         conn_id: ConnectionId,
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
-        let my_id = conn_id;
+        trace!("ChatMessage packet received: {}", self.message);
+
+        if let Some(reason) = invalid_message(&self.message) {
+            warn!("Kicking entity {}: {}", conn_id, reason);
+            return kick_conn(conn_id, reason, state.clone()).await;
+        }
+        if self.message.trim().is_empty() {
+            return Ok(());
+        }
+
+        let config = &get_global_config().chat;
+        let cooldown = Duration::from_millis(config.cooldown_ms);
+        if !cooldown.is_zero() {
+            if let Ok(last) = state.world.get_component::<LastChatMessage>(conn_id).await {
+                if last.sent_at.elapsed() < cooldown {
+                    debug!(
+                        "Dropping the chat message of entity {}, sent too soon",
+                        conn_id
+                    );
+                    return Ok(());
+                }
+            }
+            state.world.get_component_storage().insert(
+                conn_id,
+                LastChatMessage {
+                    sent_at: Instant::now(),
+                },
+            );
+        }
+
+        let (uuid, username) = {
+            let player = state.world.get_component::<Player>(conn_id).await?;
+            (player.uuid, player.username.clone())
+        };
+        let event = ChatEvent::new(conn_id, username, self.message, false);
+        let event = state
+            .event_dispatcher
+            .dispatch_mutable_event(event, state.clone())
+            .await;
+        if event.cancelled {
+            return Ok(());
+        }
+
+        let name = ChatComponent::text(&event.username).on_hover(HoverEvent::show_entity(
+            "minecraft:player",
+            uuid,
+            Some(ChatComponent::text(&event.username)),
+        ));
+        if config.format != DEFAULT_CHAT_FORMAT {
+            let line = format_message(config, &name, &event.message);
+            return broadcast(SystemChatMessage::new(&line, false), &state).await;
+        }
 
-        let my_player = state.world.get_component::<Player>(my_id).await?;
+        // Attributed to the sender, the client decorates it like the default format
+        let index = state
+            .world
+            .get_component_storage()
+            .get_mut_or_insert_with::<SentMessages>(conn_id, Default::default)
+            .await
+            .next_index();
+        let packet = PlayerChatMessage::unsigned(
+            uuid,
+            index,
+            event.message,
+            self.timestamp,
+            CHAT_TYPE_CHAT,
+            &name,
+        );
+        broadcast(packet, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_message, invalid_message, MAX_MESSAGE_LENGTH};
+    use crate::utils::chat::ChatComponent;
+    use crate::utils::config::Chat;
+
+    #[test]
+    fn messages_are_validated_like_vanilla() {
+        assert_eq!(invalid_message("Hello, world!"), None);
+        assert_eq!(invalid_message(&"é".repeat(MAX_MESSAGE_LENGTH)), None);
+        assert_eq!(
+            invalid_message(&"a".repeat(MAX_MESSAGE_LENGTH + 1)),
+            Some("Chat message too long")
+        );
+        assert_eq!(invalid_message("§cred"), Some("Illegal characters in chat"));
+        assert_eq!(
+            invalid_message("line\nbreak"),
+            Some("Illegal characters in chat")
+        );
+        assert_eq!(
+            invalid_message("\u{7F}"),
+            Some("Illegal characters in chat")
+        );
+    }
 
-        debug!("[{}]: {}", my_player.username, self.message);
+    #[test]
+    fn messages_follow_the_format() {
+        let config = Chat::default();
+        let steve = ChatComponent::text("Steve");
+        let line = format_message(&config, &steve, "hi");
+        assert_eq!(line.to_plain_text(), "<Steve> hi");
+        assert_eq!(line.extra[1], steve);
+        assert_eq!(
+            format_message(&config, &steve, "I am {name}").to_plain_text(),
+            "<Steve> I am {name}"
+        );
 
-        Ok(())
+        let config = Chat {
+            format: "[{name}] says: {message}".to_string(),
+            cooldown_ms: 0,
+        };
+        assert_eq!(
+            format_message(&config, &ChatComponent::text("Alex"), "hello").to_plain_text(),
+            "[Alex] says: hello"
+        );
     }
 }
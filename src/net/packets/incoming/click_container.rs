@@ -0,0 +1,293 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::net::packets::outgoing::set_container_slot::{
+    SetContainerSlot, CARRIED_SLOT, CARRIED_WINDOW,
+};
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::components::inventory::{
+    ClickMode, Inventory, MAIN_INVENTORY_START, OFFHAND_SLOT,
+};
+use crate::utils::components::open_window::OpenWindow;
+use crate::utils::components::window_manager::WindowManager;
+use crate::utils::encoding::slot::Slot;
+
+/// Window of the player inventory, always open
+pub const PLAYER_WINDOW: u8 = 0;
+/// Most slots a single click may change
+const MAX_CHANGED_SLOTS: usize = 128;
+
+/// Content of a slot as predicted by the client after its click
+pub struct ChangedSlot {
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl crate::utils::impls::packet_impls::NetDecode for ChangedSlot {
+    async fn net_decode<T>(bytes: &mut T) -> crate::utils::prelude::Result<Box<Self>>
+    where
+        T: tokio::io::AsyncRead + Unpin,
+    {
+        Ok(Box::new(ChangedSlot {
+            slot: *i16::net_decode(bytes).await?,
+            item: *Slot::net_decode(bytes).await?,
+        }))
+    }
+}
+
+/// The click container packet is sent by the client when the player clicks a slot of an open
+/// window, with the slots it expects the click to change.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0B, state = "play")]
+pub struct ClickContainer {
+    pub window_id: u8,
+    /// Last state id the client received
+    pub state_id: VarInt,
+    pub slot: i16,
+    pub button: i8,
+    pub mode: VarInt,
+    pub changed_slots: Vec<ChangedSlot>,
+    pub carried: Slot,
+}
+
+/// Every slot of the player inventory, for a client whose inventory diverged
+pub fn resync_inventory(inventory: &mut Inventory) -> SetContainerContent {
+    let state_id = inventory.next_state_id();
+    SetContainerContent::new(
+        PLAYER_WINDOW,
+        state_id,
+        inventory.slots.clone(),
+        inventory.carried.clone(),
+    )
+}
+
+/// Every slot of `window`, followed by the main inventory and hotbar of the player, for a client
+/// whose window diverged
+pub fn resync_window(window: &mut OpenWindow, inventory: &Inventory) -> SetContainerContent {
+    let state_id = window.next_state_id();
+    let mut slots = window.slots.clone();
+    slots.extend_from_slice(&inventory.slots[MAIN_INVENTORY_START..OFFHAND_SLOT]);
+    SetContainerContent::new(window.window_id, state_id, slots, inventory.carried.clone())
+}
+
+impl IncomingPacket for ClickContainer {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "ClickContainer packet received: window {}, slot {}, button {}, mode {}",
+            self.window_id,
+            self.slot,
+            self.button,
+            self.mode
+        );
+
+        let mode = ClickMode::from_id(self.mode.get_val());
+        let Some(mode) = mode.filter(|_| self.changed_slots.len() <= MAX_CHANGED_SLOTS) else {
+            warn!("Kicking entity {}, it sent an invalid click", conn_id);
+            return kick_conn(conn_id, "Invalid click", state.clone()).await;
+        };
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        if self.window_id != PLAYER_WINDOW {
+            let resync = {
+                let mut windows = state
+                    .world
+                    .get_component_storage()
+                    .get_mut_or_insert_with::<WindowManager>(conn_id, Default::default)
+                    .await;
+                let Some(window) = windows.window_mut(self.window_id) else {
+                    debug!(
+                        "Rejecting click of entity {} in window {}, it isn't open",
+                        conn_id, self.window_id
+                    );
+                    return Ok(());
+                };
+                let inventory = state
+                    .world
+                    .get_component_storage()
+                    .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                    .await;
+                resync_window(window, &inventory)
+            };
+            // Clicks in containers don't do anything yet, undo the prediction of the client
+            return conn.send_packet(resync).await;
+        }
+
+        let mut packet_queue = PacketQueue::new();
+        {
+            let mut inventory = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                .await;
+
+            if self.state_id.get_val() != inventory.state_id
+                || !inventory.click(self.slot, self.button, mode)
+            {
+                debug!(
+                    "Resynchronizing the inventory of entity {} after a {:?} click",
+                    conn_id, mode
+                );
+                packet_queue.queue(resync_inventory(&mut inventory)).await?;
+            } else {
+                // The client predicted the click, only correct the slots it got wrong
+                let wrong_slots: Vec<_> = self
+                    .changed_slots
+                    .iter()
+                    .filter_map(|changed| {
+                        let slot = inventory.slots.get(usize::try_from(changed.slot).ok()?)?;
+                        (*slot != changed.item).then(|| (changed.slot, slot.clone()))
+                    })
+                    .collect();
+                let wrong_carried = self.carried != inventory.carried;
+
+                if !wrong_slots.is_empty() || wrong_carried {
+                    let state_id = inventory.next_state_id();
+                    for (slot, item) in wrong_slots {
+                        packet_queue
+                            .queue(SetContainerSlot::new(
+                                PLAYER_WINDOW as i8,
+                                state_id,
+                                slot,
+                                item,
+                            ))
+                            .await?;
+                    }
+                    if wrong_carried {
+                        packet_queue
+                            .queue(SetContainerSlot::new(
+                                CARRIED_WINDOW,
+                                state_id,
+                                CARRIED_SLOT,
+                                inventory.carried.clone(),
+                            ))
+                            .await?;
+                    }
+                }
+            }
+        }
+        conn.send_packets(packet_queue).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::{ChangedSlot, ClickContainer};
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::inventory::Inventory;
+    use crate::utils::components::open_window::{OpenWindow, WindowType};
+    use crate::utils::components::window_manager::WindowManager;
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    fn stack(count: i8) -> Slot {
+        Slot {
+            item: Some(ItemStack::new(1, count)),
+        }
+    }
+
+    #[tokio::test]
+    async fn predicted_click_is_applied() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        let mut inventory = Inventory::default();
+        inventory.slots[9] = stack(3);
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, inventory);
+
+        ClickContainer {
+            window_id: 0,
+            state_id: VarInt::from(0),
+            slot: 9,
+            button: 0,
+            mode: VarInt::from(0),
+            changed_slots: vec![ChangedSlot {
+                slot: 9,
+                item: Slot::empty(),
+            }],
+            carried: stack(3),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+
+        let inventory = state.world.get_component::<Inventory>(entity_id).await;
+        let inventory = inventory.unwrap();
+        assert_eq!(inventory.carried, stack(3));
+        assert_eq!(inventory.slots[9], Slot::empty());
+        assert_eq!(inventory.state_id, 0);
+    }
+
+    #[tokio::test]
+    async fn outdated_state_resynchronizes() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        ClickContainer {
+            window_id: 0,
+            state_id: VarInt::from(7),
+            slot: 9,
+            button: 0,
+            mode: VarInt::from(0),
+            changed_slots: Vec::new(),
+            carried: Slot::empty(),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+
+        // Length, then the id of the set container content packet
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1], 0x12);
+        let inventory = state.world.get_component::<Inventory>(entity_id).await;
+        assert_eq!(inventory.unwrap().state_id, 1);
+    }
+
+    #[tokio::test]
+    async fn clicks_in_windows_that_are_not_open_are_rejected() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        let mut windows = WindowManager::default();
+        windows.open = Some(OpenWindow::new(5, WindowType::Anvil));
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, windows);
+
+        for window_id in [4, 5] {
+            ClickContainer {
+                window_id,
+                state_id: VarInt::from(0),
+                slot: 0,
+                button: 0,
+                mode: VarInt::from(0),
+                changed_slots: Vec::new(),
+                carried: Slot::empty(),
+            }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        }
+
+        // Only the open window is resynchronized, with its 3 slots and the 36 of the player
+        let mut header = [0u8; 5];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1..], [0x12, 5, 1, 39]);
+    }
+}
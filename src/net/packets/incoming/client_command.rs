@@ -0,0 +1,137 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::award_statistics::AwardStatistics;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::metadata::update_health;
+use crate::state::GlobalState;
+use crate::utils::components::food::Food;
+use crate::utils::components::health::Health;
+use crate::utils::components::player::{respawn_player, Player};
+use crate::utils::components::rotation::Rotation;
+use crate::utils::constants::init;
+use crate::utils::encoding::position::Position;
+use crate::world::dimension::Dimension;
+
+const PERFORM_RESPAWN: i32 = 0;
+const REQUEST_STATS: i32 = 1;
+
+/// The client command packet is sent by the client to respawn after dying, or to open the
+/// statistics menu.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x07, state = "play")]
+pub struct ClientCommand {
+    pub action_id: VarInt,
+}
+
+impl IncomingPacket for ClientCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ClientCommand packet received: action {}", self.action_id);
+
+        match self.action_id.get_val() {
+            PERFORM_RESPAWN => respawn(conn_id, &state).await,
+            REQUEST_STATS => {
+                // Statistics aren't tracked yet
+                let conn = state.connections.get_connection(conn_id)?;
+                let conn = conn.read().await;
+                conn.send_packet(AwardStatistics::new(vec![])).await
+            }
+            action => {
+                warn!("Unknown client command action: {}", action);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Brings a dead player back at the world spawn, with full health and food
+async fn respawn(entity_id: u32, state: &GlobalState) -> crate::utils::prelude::Result<()> {
+    let dead = state
+        .world
+        .get_component::<Health>(entity_id)
+        .await
+        .is_ok_and(|health| health.is_dead());
+    if !dead {
+        debug!(
+            "Ignoring respawn request of entity {}, it isn't dead",
+            entity_id
+        );
+        return Ok(());
+    }
+
+    // Shown by the recovery compass
+    let death_location = (
+        Player::dimension(entity_id, state).await,
+        state
+            .world
+            .get_component::<Position>(entity_id)
+            .await?
+            .clone(),
+    );
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, Health::default())
+        .insert(entity_id, Food::default());
+    // Trackers see the new health now, the client once it respawned
+    update_health(entity_id, state).await?;
+
+    respawn_player(
+        entity_id,
+        Dimension::Overworld,
+        Position::new(
+            init::DEFAULT_SPAWN_X_POS,
+            init::DEFAULT_SPAWN_Y_POS,
+            init::DEFAULT_SPAWN_Z_POS,
+        ),
+        Rotation::new(init::DEFAULT_SPAWN_YAW, init::DEFAULT_SPAWN_PITCH),
+        0,
+        Some(death_location),
+        state,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientCommand;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::health::{Health, MAX_HEALTH};
+    use crate::utils::constants::init;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn only_dead_players_respawn() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Health::new(5.0))
+            .insert(entity_id, Position::new(100, 70, 100));
+        let respawn = || ClientCommand {
+            action_id: 0.into(),
+        };
+
+        respawn().handle(entity_id, state.clone()).await.unwrap();
+        let position = state.world.get_component::<Position>(entity_id).await;
+        assert_eq!(position.unwrap().x, 100);
+
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Health::new(0.0));
+        respawn().handle(entity_id, state.clone()).await.unwrap();
+        let position = state.world.get_component::<Position>(entity_id).await;
+        assert_eq!(position.unwrap().x, init::DEFAULT_SPAWN_X_POS);
+        let health = state.world.get_component::<Health>(entity_id).await;
+        assert_eq!(health.unwrap().health, MAX_HEALTH);
+    }
+}
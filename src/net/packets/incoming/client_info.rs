@@ -1,10 +1,19 @@
-use tracing::trace;
+use tracing::{debug, trace};
 
 use ferrumc_macros::{packet, Component, NetDecode};
 
+use crate::events::player_events::LocaleChangeEvent;
+use crate::net::packets::outgoing::entity_metadata::{
+    MetadataValue, MAIN_HAND_INDEX, SKIN_PARTS_INDEX,
+};
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::utils::metadata::update_metadata;
 use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Fewest chunks a client asks for around it
+pub const MIN_VIEW_DISTANCE: i8 = 2;
 
 #[derive(NetDecode, Component, Clone, Debug)]
 #[packet(packet_id = 0x08, state = "play")]
@@ -17,9 +26,14 @@ pub struct ClientInfo {
     pub main_hand: i8,
 }
 
+/// View distance of a client asking for `requested`, on a server sending at most `max`
+pub fn clamp_view_distance(requested: i8, max: i8) -> i8 {
+    requested.clamp(MIN_VIEW_DISTANCE, max.max(MIN_VIEW_DISTANCE))
+}
+
 impl IncomingPacket for ClientInfo {
     async fn handle(
-        self,
+        mut self,
         entity_id: ConnectionId,
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
@@ -31,12 +45,68 @@ impl IncomingPacket for ClientInfo {
         trace!("Displayed Skin Parts: {}", self.displayed_skin_parts);
         trace!("Main Hand: {}", self.main_hand);
 
+        let max_view_distance = get_global_config().max_view_distance;
+        self.view_distance = clamp_view_distance(self.view_distance, max_view_distance);
+        let previous = state
+            .world
+            .get_component::<ClientInfo>(entity_id)
+            .await
+            .ok()
+            .map(|previous| previous.clone());
         // ClientInfo is a packet & also a component.
-        state.world.get_component_storage().insert(entity_id, self);
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, self.clone());
+
+        if previous
+            .as_ref()
+            .is_none_or(|previous| previous.locale != self.locale)
+        {
+            let event = LocaleChangeEvent::new(entity_id, self.locale.clone());
+            state
+                .event_dispatcher
+                .dispatch_event(event, state.clone())
+                .await;
+        }
+
+        // Skin layers and main hand, as seen by the other players
+        update_metadata(entity_id, &state, |metadata| {
+            metadata
+                .set(
+                    SKIN_PARTS_INDEX,
+                    MetadataValue::Byte(self.displayed_skin_parts),
+                )
+                .set(MAIN_HAND_INDEX, MetadataValue::Byte(self.main_hand as u8));
+        })
+        .await?;
 
-        // Send chunks again
-        ChunkSender::send_chunks_to_player(state.clone(), entity_id).await?;
+        let previous_view_distance =
+            previous.map(|previous| clamp_view_distance(previous.view_distance, max_view_distance));
+        // Unloads the chunks out of a shorter view distance, or sends those in a longer one
+        if previous_view_distance != Some(self.view_distance) {
+            if let Some(previous) = previous_view_distance {
+                debug!(
+                    "Entity {} view distance changed from {} to {}",
+                    entity_id, previous, self.view_distance
+                );
+            }
+            ChunkSender::send_chunks_to_player(state.clone(), entity_id).await?;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::clamp_view_distance;
+
+    #[test]
+    fn view_distance_is_clamped() {
+        assert_eq!(clamp_view_distance(32, 16), 16);
+        assert_eq!(clamp_view_distance(8, 16), 8);
+        assert_eq!(clamp_view_distance(0, 16), 2);
+        assert_eq!(clamp_view_distance(-5, 16), 2);
+    }
+}
@@ -0,0 +1,51 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::incoming::click_container::PLAYER_WINDOW;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::window_manager::WindowManager;
+
+/// The close container packet is sent by the client when the player closes a window, including
+/// its own inventory.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0C, state = "play")]
+pub struct CloseContainer {
+    pub window_id: u8,
+}
+
+impl IncomingPacket for CloseContainer {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("CloseContainer packet received: window {}", self.window_id);
+
+        if self.window_id != PLAYER_WINDOW {
+            if let Ok(mut windows) = state
+                .world
+                .get_component_mut::<WindowManager>(conn_id)
+                .await
+            {
+                if windows.is_open(self.window_id) {
+                    windows.open = None;
+                }
+            }
+            return Ok(());
+        }
+        let Ok(mut inventory) = state.world.get_component_mut::<Inventory>(conn_id).await else {
+            return Ok(());
+        };
+        // Dropped items don't exist yet, so a stack without room is lost
+        if !inventory.stow_carried() {
+            debug!(
+                "No room for the stack carried by entity {}, it was lost",
+                conn_id
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::command_suggestions_response::{
+    CommandSuggestionsResponse, Suggestion,
+};
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Longest text the client may ask completions for
+const MAX_TEXT_LENGTH: usize = 32500;
+
+/// The command suggestions request packet is sent by the client when the player presses tab
+/// while typing a command. `text` is everything before the cursor, with the leading slash.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x09, state = "play")]
+pub struct CommandSuggestionsRequest {
+    pub transaction_id: VarInt,
+    pub text: String,
+}
+
+impl IncomingPacket for CommandSuggestionsRequest {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "CommandSuggestionsRequest packet received: {} {}",
+            self.transaction_id,
+            self.text
+        );
+
+        if self.text.chars().count() > MAX_TEXT_LENGTH {
+            warn!(
+                "Ignoring completion of entity {}, its text is longer than {} characters",
+                conn_id, MAX_TEXT_LENGTH
+            );
+            return Ok(());
+        }
+
+        // Only command names can be completed, arguments aren't known yet
+        let name = self.text.strip_prefix('/').unwrap_or(&self.text);
+        let matches = if name.contains(' ') {
+            Vec::new()
+        } else {
+            state
+                .commands
+                .complete(name)
+                .into_iter()
+                .map(|(name, description)| Suggestion::new(name, description.as_deref()))
+                .collect()
+        };
+        // The client counts in UTF-16 code units
+        let start = (self.text.len() - name.len()) as i32;
+        let length = name.encode_utf16().count() as i32;
+        let packet = CommandSuggestionsResponse::new(self.transaction_id, start, length, matches);
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::CommandSuggestionsRequest;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    #[tokio::test]
+    async fn completes_command_names() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        state.commands.register("tp", None);
+        state.commands.register("time", None);
+        state.commands.register("help", None);
+
+        CommandSuggestionsRequest {
+            transaction_id: VarInt::from(42),
+            text: "/t".to_string(),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+
+        // Length, id, transaction id, start, length, then both matches without tooltips
+        let mut response = [0u8; 16];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            response,
+            [15, 0x0F, 42, 1, 1, 2, 4, b't', b'i', b'm', b'e', 0, 2, b't', b'p', 0]
+        );
+    }
+}
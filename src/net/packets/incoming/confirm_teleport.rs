@@ -0,0 +1,51 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::pending_teleports::{PendingTeleports, TeleportConfirmation};
+
+/// Confirmations of teleports that were never issued tolerated before kicking the player
+const MAX_UNKNOWN_CONFIRMATIONS: u32 = 3;
+
+/// The confirm teleportation packet is sent by the client once it moved to the position of a
+/// [SynchronizePlayerPosition](crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition).
+#[derive(NetDecode)]
+#[packet(packet_id = 0x00, state = "play")]
+pub struct ConfirmTeleport {
+    pub teleport_id: VarInt,
+}
+
+impl IncomingPacket for ConfirmTeleport {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ConfirmTeleport packet received: {}", self.teleport_id);
+
+        let unknown_confirmations = {
+            let mut teleports = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<PendingTeleports>(conn_id, Default::default)
+                .await;
+            if teleports.confirm(self.teleport_id.get_val()) != TeleportConfirmation::Unknown {
+                return Ok(());
+            }
+            teleports.unknown_confirmations()
+        };
+
+        warn!(
+            "Entity {} confirmed teleport {}, which was never issued",
+            conn_id, self.teleport_id
+        );
+        if unknown_confirmations >= MAX_UNKNOWN_CONFIRMATIONS {
+            return kick_conn(conn_id, "Invalid teleport confirmation", state.clone()).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use std::io::Cursor;
+
+use simdnbt::owned::{Nbt, NbtCompound};
+use tokio::io::AsyncReadExt;
+use tracing::{debug, trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::incoming::click_container::PLAYER_WINDOW;
+use crate::net::packets::outgoing::set_container_slot::SetContainerSlot;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::inventory::{Inventory, HOTBAR_START, OFFHAND_SLOT};
+use crate::utils::components::player::Player;
+use crate::utils::encoding::slot::ItemStack;
+use crate::utils::prelude::*;
+use crate::world::blocks::write_nbt;
+use crate::world::items::item_id;
+
+/// Most pages a book can hold
+pub const MAX_PAGES: usize = 100;
+/// Longest page, in bytes
+pub const MAX_PAGE_LENGTH: usize = 1024;
+/// Longest title of a signed book, in bytes
+pub const MAX_TITLE_LENGTH: usize = 32;
+/// Slot of the offhand in the packet, the hotbar slots are 0 to 8
+const OFFHAND: i32 = 40;
+
+const WRITABLE_BOOK: &str = "minecraft:writable_book";
+const WRITTEN_BOOK: &str = "minecraft:written_book";
+
+/// Pages and title of an [`EditBook`], read without allocating more than the limits allow
+#[derive(Debug, PartialEq)]
+pub enum BookContent {
+    Valid {
+        pages: Vec<String>,
+        /// Set when the book is signed
+        title: Option<String>,
+    },
+    /// Too many pages, or a page or title too long, the rest of the packet is left unread
+    Oversized,
+}
+
+/// Read a string of at most `max_length` bytes, `None` if its length is above it
+async fn read_bounded_string<T>(bytes: &mut T, max_length: usize) -> Result<Option<String>>
+where
+    T: AsyncRead + Unpin,
+{
+    let length = VarInt::read(bytes).await?.get_val();
+    let Some(length) = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= max_length)
+    else {
+        return Ok(None);
+    };
+    let mut string = vec![0u8; length];
+    bytes.read_exact(&mut string).await?;
+    Ok(Some(String::from_utf8(string)?))
+}
+
+impl crate::utils::impls::packet_impls::NetDecode for BookContent {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let count = VarInt::read(bytes).await?.get_val();
+        let Some(count) = usize::try_from(count)
+            .ok()
+            .filter(|count| *count <= MAX_PAGES)
+        else {
+            return Ok(Box::new(BookContent::Oversized));
+        };
+        let mut pages = Vec::with_capacity(count);
+        for _ in 0..count {
+            match read_bounded_string(bytes, MAX_PAGE_LENGTH).await? {
+                Some(page) => pages.push(page),
+                None => return Ok(Box::new(BookContent::Oversized)),
+            }
+        }
+
+        let title = if bytes.read_u8().await? != 0 {
+            match read_bounded_string(bytes, MAX_TITLE_LENGTH).await? {
+                Some(title) => Some(title),
+                None => return Ok(Box::new(BookContent::Oversized)),
+            }
+        } else {
+            None
+        };
+        Ok(Box::new(BookContent::Valid { pages, title }))
+    }
+}
+
+/// The edit book packet is sent by the client when the player is done writing in a book and
+/// quill, or signs it.
+///
+/// Signing turns it into a written book, with the player as author.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0E, state = "play")]
+pub struct EditBook {
+    /// Hotbar slot of the book, or 40 for the offhand
+    pub slot: VarInt,
+    pub content: BookContent,
+}
+
+/// NBT of the book once edited, keeping the other tags of `nbt`
+///
+/// Written books hold their pages as JSON text, along with their title and author.
+fn book_nbt(
+    nbt: Option<&[u8]>,
+    pages: Vec<String>,
+    signed: Option<(String, String)>,
+) -> Result<Vec<u8>> {
+    let mut compound = match nbt {
+        Some(nbt) => match simdnbt::owned::read(&mut Cursor::new(nbt))? {
+            Nbt::Some(nbt) => nbt.into_inner(),
+            Nbt::None => NbtCompound::new(),
+        },
+        None => NbtCompound::new(),
+    };
+    for tag in ["pages", "title", "author", "generation", "resolved"] {
+        compound.remove(tag);
+    }
+
+    match signed {
+        Some((title, author)) => {
+            let pages: Vec<String> = pages
+                .iter()
+                .map(|page| serde_json::json!({ "text": page }).to_string())
+                .collect();
+            compound.insert("pages", pages);
+            compound.insert("title", title.as_str());
+            compound.insert("author", author.as_str());
+            compound.insert("generation", 0i32);
+            compound.insert("resolved", 1i8);
+        }
+        None => compound.insert("pages", pages),
+    }
+    Ok(write_nbt(compound))
+}
+
+impl IncomingPacket for EditBook {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("EditBook packet received: slot {}", self.slot);
+
+        let index = match self.slot.get_val() {
+            slot @ 0..=8 => Some(HOTBAR_START + slot as usize),
+            OFFHAND => Some(OFFHAND_SLOT),
+            _ => None,
+        };
+        let (Some(index), BookContent::Valid { pages, title }) = (index, self.content) else {
+            warn!(
+                "Kicking entity {}, it sent an invalid or oversized book",
+                conn_id
+            );
+            return kick_conn(conn_id, "Invalid book", state.clone()).await;
+        };
+        let (Some(writable_book), Some(written_book)) =
+            (item_id(WRITABLE_BOOK), item_id(WRITTEN_BOOK))
+        else {
+            return Ok(());
+        };
+
+        let signed = match title {
+            Some(title) => {
+                let author = state
+                    .world
+                    .get_component::<Player>(conn_id)
+                    .await?
+                    .username
+                    .clone();
+                Some((title, author))
+            }
+            None => None,
+        };
+        let packet = {
+            let Ok(mut inventory) = state.world.get_component_mut::<Inventory>(conn_id).await
+            else {
+                return Ok(());
+            };
+            let slot = &mut inventory.slots[index];
+            let Some(book) = slot
+                .item
+                .as_mut()
+                .filter(|item| item.item_id.get_val() == writable_book)
+            else {
+                debug!(
+                    "Entity {} edited a book in slot {}, which holds none",
+                    conn_id, self.slot
+                );
+                return Ok(());
+            };
+
+            let is_signed = signed.is_some();
+            let nbt = book_nbt(book.nbt.as_deref(), pages, signed)?;
+            *book = ItemStack {
+                item_id: if is_signed {
+                    VarInt::from(written_book)
+                } else {
+                    VarInt::from(writable_book)
+                },
+                count: book.count,
+                nbt: Some(nbt),
+            };
+            let item = slot.clone();
+            let state_id = inventory.next_state_id();
+            SetContainerSlot::new(PLAYER_WINDOW as i8, state_id, index as i16, item)
+        };
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{book_nbt, BookContent, MAX_PAGES};
+    use crate::utils::impls::packet_impls::NetDecode;
+
+    fn varint(value: i32) -> Vec<u8> {
+        let mut value = value as u32;
+        let mut bytes = Vec::new();
+        loop {
+            if value & !0x7F == 0 {
+                bytes.push(value as u8);
+                return bytes;
+            }
+            bytes.push((value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+    }
+
+    #[tokio::test]
+    async fn books_within_the_limits_are_read() {
+        let mut data = varint(2);
+        data.extend_from_slice(&[5, b'H', b'e', b'l', b'l', b'o', 0]);
+        data.extend_from_slice(&[1, 6, b'T', b'i', b't', b'l', b'e', b'!']);
+
+        let content = BookContent::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(
+            *content,
+            BookContent::Valid {
+                pages: vec!["Hello".to_string(), String::new()],
+                title: Some("Title!".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_books_are_refused_before_allocating() {
+        // A page claiming a gigabyte, without the bytes to back it: reading it would either
+        // allocate the gigabyte or fail at the end of the data
+        let mut data = varint(1);
+        data.extend_from_slice(&varint(1 << 30));
+        let mut cursor = Cursor::new(data);
+        let content = BookContent::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(*content, BookContent::Oversized);
+        // Nothing was read past the length of the page
+        assert_eq!(cursor.position(), 1 + 5);
+
+        // Too many pages, refused before reading any
+        let mut cursor = Cursor::new(varint(i32::MAX));
+        let content = BookContent::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(*content, BookContent::Oversized);
+        let mut cursor = Cursor::new(varint(MAX_PAGES as i32 + 1));
+        let content = BookContent::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(*content, BookContent::Oversized);
+
+        // A title above 32 bytes
+        let mut data = varint(0);
+        data.push(1);
+        data.extend_from_slice(&varint(33));
+        data.extend_from_slice(&[b'a'; 33]);
+        let content = BookContent::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(*content, BookContent::Oversized);
+    }
+
+    #[test]
+    fn signing_records_the_author() {
+        let nbt = book_nbt(
+            None,
+            vec!["Once upon a time".to_string()],
+            Some(("Story".to_string(), "Steve".to_string())),
+        )
+        .unwrap();
+        let nbt = simdnbt::owned::read(&mut Cursor::new(nbt.as_slice()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(nbt.string("title").unwrap().to_str(), "Story");
+        assert_eq!(nbt.string("author").unwrap().to_str(), "Steve");
+        let pages = nbt.list("pages").unwrap().strings().unwrap();
+        assert_eq!(pages[0].to_str(), r#"{"text":"Once upon a time"}"#);
+
+        // Editing again keeps the other tags but replaces the pages
+        let mut signed = Vec::new();
+        nbt.write(&mut signed);
+        let nbt = book_nbt(Some(&signed), vec!["Draft".to_string()], None).unwrap();
+        let nbt = simdnbt::owned::read(&mut Cursor::new(nbt.as_slice()))
+            .unwrap()
+            .unwrap();
+        assert!(nbt.string("title").is_none());
+        let pages = nbt.list("pages").unwrap().strings().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].to_str(), "Draft");
+    }
+}
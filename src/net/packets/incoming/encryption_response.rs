@@ -0,0 +1,90 @@
+use tracing::{debug, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::authentication::{
+    has_joined, server_hash, PendingAuthentication, AUTH_SERVERS_DOWN, INVALID_ENCRYPTION,
+    INVALID_SESSION, SERVER_ID,
+};
+use crate::net::forwarding::reject_login;
+use crate::net::packets::incoming::login_start::LoginStart;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// The encryption response answers the
+/// [EncryptionRequest](crate::net::packets::outgoing::encryption_request::EncryptionRequest) of
+/// online mode servers, with the shared secret and the verify token encrypted with the public
+/// key of the server.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x01, state = "login")]
+pub struct EncryptionResponse {
+    pub shared_secret: Vec<u8>,
+    pub verify_token: Vec<u8>,
+}
+
+impl IncomingPacket for EncryptionResponse {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        let pending = match state
+            .world
+            .get_component::<PendingAuthentication>(conn_id)
+            .await
+        {
+            Ok(pending) => pending.clone(),
+            Err(_) => {
+                debug!("Entity {} sent an unrequested encryption response", conn_id);
+                return Ok(());
+            }
+        };
+        state
+            .world
+            .get_component_storage()
+            .remove::<PendingAuthentication>(conn_id as usize)?;
+
+        let Some(keys) = &state.server_keys else {
+            return reject_login(conn_id, INVALID_ENCRYPTION, state).await;
+        };
+        let decrypted = keys
+            .decrypt(&self.shared_secret)
+            .and_then(|secret| Ok((secret, keys.decrypt(&self.verify_token)?)));
+        let shared_secret = match decrypted {
+            Ok((secret, token)) if token == pending.verify_token => secret,
+            _ => {
+                warn!("{} sent an invalid encryption response", pending.username);
+                return reject_login(conn_id, INVALID_ENCRYPTION, state).await;
+            }
+        };
+
+        {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            if let Err(e) = conn.enable_encryption(&shared_secret).await {
+                warn!("{} sent an invalid shared secret: {}", pending.username, e);
+                drop(conn);
+                return reject_login(conn_id, INVALID_ENCRYPTION, state).await;
+            }
+        }
+
+        let hash = server_hash(SERVER_ID, &shared_secret, keys.public_key());
+        let session_server = &get_global_config().authentication.session_server;
+        let profile = match has_joined(session_server, &pending.username, &hash).await {
+            Ok(Some(profile)) => profile,
+            Ok(None) => {
+                warn!("{} has no valid session", pending.username);
+                return reject_login(conn_id, INVALID_SESSION, state).await;
+            }
+            Err(e) => {
+                warn!("Failed to authenticate {}: {}", pending.username, e);
+                return reject_login(conn_id, AUTH_SERVERS_DOWN, state).await;
+            }
+        };
+
+        debug!("Authenticated {} as {}", pending.username, profile.id);
+        let login = LoginStart {
+            uuid: profile.uuid()?,
+            username: profile.name,
+        };
+        login.login(conn_id, profile.properties, state).await
+    }
+}
@@ -1,12 +1,26 @@
 use ferrumc_codec::network_types::varint::VarInt;
+use rand::prelude::IndexedRandom;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, warn};
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::forwarding::parse_bungeecord;
+use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
 use crate::net::packets::{ConnectionId, IncomingPacket};
-use crate::net::State;
+use crate::net::{drop_conn, Connection, State, SUPPORTED_PROTOCOL_RANGE};
 use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::{GAME_VERSION, PROTOCOL_VERSION};
 use crate::utils::prelude::*;
 
+/// First byte of the server list ping of clients older than 1.7, sent instead of a handshake
+const LEGACY_PING: u8 = 0xFE;
+/// Id of the kick packet of legacy clients, which carries the status
+const LEGACY_KICK: u8 = 0xFF;
+/// Channel of the plugin message 1.6 clients append to their ping
+const LEGACY_PING_CHANNEL: &str = "MC|PingHost";
+
 /// The first packet sent by the client to the server.
 ///
 /// This packet is used to negotiate the protocol version, server address, server port, and the next state.
@@ -21,12 +35,17 @@ pub struct Handshake {
 
 impl IncomingPacket for Handshake {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
-        let Some(conn) = state.connections.connections.get(&conn_id) else {
-            return Err(Error::ConnectionNotFound(conn_id));
-        };
-
+        let conn = state.connections.get_connection(conn_id)?;
         let mut conn = conn.write().await;
 
+        let (server_address, markers) = split_server_address(&self.server_address);
+        if !markers.is_empty() {
+            debug!("Entity {} connected with markers {:?}", conn_id, markers);
+        }
+        conn.metadata.server_address = server_address.to_string();
+        conn.metadata.forwarded = parse_bungeecord(&markers);
+        conn.metadata.address_markers = markers;
+
         conn.metadata.protocol_version = self.protocol_version.get_val();
         conn.state = match self.next_state.get_val() {
             1 => State::Status,
@@ -34,6 +53,238 @@ impl IncomingPacket for Handshake {
             s => return Err(Error::InvalidState(s)),
         };
 
+        // Status requests are answered with the supported version instead
+        let protocol_version = self.protocol_version.get_val();
+        if conn.state == State::Login && !SUPPORTED_PROTOCOL_RANGE.contains(&protocol_version) {
+            warn!(
+                "Entity {} tried to join with unsupported protocol {}",
+                conn_id, protocol_version
+            );
+            let reason = unsupported_version_message(protocol_version);
+            conn.send_packet(LoginDisconnect::from_text(&reason))
+                .await?;
+            drop(conn);
+            return drop_conn(conn_id, state).await;
+        }
+
         Ok(())
     }
 }
+
+/// Reason shown to clients of an unsupported protocol, worded like vanilla
+fn unsupported_version_message(protocol_version: i32) -> String {
+    if protocol_version < *SUPPORTED_PROTOCOL_RANGE.start() {
+        format!("Outdated client! Please use {}", GAME_VERSION)
+    } else {
+        format!("Outdated server! I'm still on {}", GAME_VERSION)
+    }
+}
+
+/// Split the null separated markers modded clients append to the server address, e.g.
+/// `localhost\0FML2\0` for Forge
+fn split_server_address(address: &str) -> (&str, Vec<String>) {
+    let mut parts = address.split('\0');
+    let address = parts.next().unwrap_or_default();
+    let markers = parts
+        .filter(|marker| !marker.is_empty())
+        .map(String::from)
+        .collect();
+    (address, markers)
+}
+
+/// Server list ping of clients older than 1.7
+#[derive(Debug, Default, PartialEq)]
+pub struct LegacyPing {
+    /// Sent by 1.6 clients only, along with the address they pinged
+    pub protocol_version: Option<u8>,
+    pub server_address: Option<String>,
+    pub server_port: Option<i32>,
+}
+
+impl LegacyPing {
+    /// Parse the ping in `bytes`, `None` if they don't start with one
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (&first, mut rest) = bytes.split_first()?;
+        if first != LEGACY_PING {
+            return None;
+        }
+        let mut ping = LegacyPing::default();
+
+        // Before 1.4 the ping is a single byte, 1.4 and 1.5 add a 1, 1.6 adds a plugin message
+        let Some((&1, after)) = rest.split_first() else {
+            return Some(ping);
+        };
+        rest = after;
+        let Some((&0xFA, after)) = rest.split_first() else {
+            return Some(ping);
+        };
+        rest = after;
+        if read_legacy_string(&mut rest).as_deref() != Some(LEGACY_PING_CHANNEL) {
+            return Some(ping);
+        }
+        // Length of the rest of the message
+        take(&mut rest, 2)?;
+        ping.protocol_version = take(&mut rest, 1).map(|protocol| protocol[0]);
+        ping.server_address = read_legacy_string(&mut rest);
+        ping.server_port =
+            take(&mut rest, 4).map(|port| i32::from_be_bytes([port[0], port[1], port[2], port[3]]));
+        Some(ping)
+    }
+
+    /// Kick packet carrying the status of the server, in the format of 1.4 and later
+    pub fn response(motd: &str, online: u32, max_players: i32) -> Vec<u8> {
+        let status = format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            PROTOCOL_VERSION, GAME_VERSION, motd, online, max_players
+        );
+        let status: Vec<u16> = status.encode_utf16().collect();
+
+        let mut response = Vec::with_capacity(3 + status.len() * 2);
+        response.push(LEGACY_KICK);
+        response.extend_from_slice(&(status.len() as u16).to_be_bytes());
+        for unit in status {
+            response.extend_from_slice(&unit.to_be_bytes());
+        }
+        response
+    }
+}
+
+/// Remove the first `len` bytes of `bytes`, `None` if there are less
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(taken)
+}
+
+/// Read a string of legacy packets: its length in chars, then UTF-16BE chars
+fn read_legacy_string(bytes: &mut &[u8]) -> Option<String> {
+    let len = take(bytes, 2)?;
+    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+    let chars: Vec<u16> = take(bytes, len * 2)?
+        .chunks_exact(2)
+        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+        .collect();
+    String::from_utf16(&chars).ok()
+}
+
+/// Answer the legacy server list ping `conn` starts with, returns whether it started with one
+///
+/// Legacy clients close the connection once they read the status, so it has to be dropped
+/// afterwards.
+pub async fn answer_legacy_ping(conn: &Connection, state: &GlobalState) -> Result<bool> {
+    let mut request = [0u8; 512];
+    let read = {
+        let mut in_stream = conn.get_in_stream().await;
+        let peeked = in_stream.peek(&mut request[..1]).await?;
+        if peeked == 0 || request[0] != LEGACY_PING {
+            return Ok(false);
+        }
+        in_stream.read(&mut request).await?
+    };
+    let ping = LegacyPing::parse(&request[..read]).unwrap_or_default();
+    debug!("Entity {} sent a legacy ping: {:?}", conn.id, ping);
+
+    let config = get_global_config();
+    let motd = config.motd.choose(&mut rand::thread_rng()).cloned();
+    // Without this connection
+    let online = state
+        .connections
+        .connection_count
+        .load(std::sync::atomic::Ordering::Relaxed)
+        .saturating_sub(1);
+    let response = LegacyPing::response(&motd.unwrap_or_default(), online, config.max_players);
+
+    let mut out_stream = conn.get_out_stream().await;
+    out_stream.write_all(&response).await?;
+    out_stream.flush().await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+    use tokio::io::AsyncReadExt;
+
+    use super::{split_server_address, Handshake, LegacyPing};
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+
+    #[tokio::test]
+    async fn unsupported_clients_are_disconnected_during_login() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        Handshake {
+            // 1.8
+            protocol_version: VarInt::new(47),
+            server_address: "localhost".to_string(),
+            server_port: 25565,
+            next_state: VarInt::new(2),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+        assert!(state.connections.get_connection(entity_id).is_err());
+
+        let mut disconnect = Vec::new();
+        client.read_to_end(&mut disconnect).await.unwrap();
+        // Length, then the id of the login disconnect packet
+        assert_eq!(disconnect[1], 0x00);
+        let reason = String::from_utf8_lossy(&disconnect);
+        assert!(reason.contains("Outdated client! Please use 1.20.1"));
+    }
+
+    #[test]
+    fn forge_markers_are_stripped_from_the_address() {
+        assert_eq!(
+            split_server_address("localhost\0FML2\0"),
+            ("localhost", vec!["FML2".to_string()])
+        );
+        assert_eq!(
+            split_server_address("play.example.com"),
+            ("play.example.com", vec![])
+        );
+    }
+
+    #[test]
+    fn legacy_pings_are_parsed() {
+        // Sent by a 1.6.4 client pinging localhost:25565
+        let ping = [
+            0xFE, 0x01, 0xFA, 0x00, 0x0B, 0x00, 0x4D, 0x00, 0x43, 0x00, 0x7C, 0x00, 0x50, 0x00,
+            0x69, 0x00, 0x6E, 0x00, 0x67, 0x00, 0x48, 0x00, 0x6F, 0x00, 0x73, 0x00, 0x74, 0x00,
+            0x19, 0x4E, 0x00, 0x09, 0x00, 0x6C, 0x00, 0x6F, 0x00, 0x63, 0x00, 0x61, 0x00, 0x6C,
+            0x00, 0x68, 0x00, 0x6F, 0x00, 0x73, 0x00, 0x74, 0x00, 0x00, 0x63, 0xDD,
+        ];
+        assert_eq!(
+            LegacyPing::parse(&ping),
+            Some(LegacyPing {
+                protocol_version: Some(78),
+                server_address: Some("localhost".to_string()),
+                server_port: Some(25565),
+            })
+        );
+        // Sent by 1.4 and 1.5 clients
+        assert_eq!(
+            LegacyPing::parse(&[0xFE, 0x01]),
+            Some(LegacyPing::default())
+        );
+        // A modern handshake
+        assert_eq!(LegacyPing::parse(&[0x10, 0x00, 0xFB, 0x05]), None);
+    }
+
+    #[test]
+    fn legacy_status_is_a_kick_packet() {
+        let response = LegacyPing::response("A", 1, 20);
+        // §1, protocol, version, motd, online and max players, null separated
+        let status = "§1\u{0}763\u{0}1.20.1\u{0}A\u{0}1\u{0}20";
+        assert_eq!(response[0], 0xFF);
+        assert_eq!(
+            u16::from_be_bytes([response[1], response[2]]) as usize,
+            status.encode_utf16().count()
+        );
+        assert_eq!(response[3..7], [0x00, 0xA7, 0x00, 0x31]);
+    }
+}
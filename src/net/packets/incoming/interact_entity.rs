@@ -0,0 +1,205 @@
+use tokio::io::AsyncRead;
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::packet;
+
+use crate::events::entity_events::{EntityAttackEvent, EntityInteractEvent};
+use crate::net::packets::incoming::swing_arm::Hand;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+/// Farthest a player can reach an entity from, in blocks
+pub const MAX_REACH: f64 = 6.0;
+
+/// What the player does to the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interaction {
+    Interact {
+        hand: Hand,
+    },
+    Attack,
+    /// Interacting with a specific point of the entity, relative to its position
+    InteractAt {
+        x: f32,
+        y: f32,
+        z: f32,
+        hand: Hand,
+    },
+}
+
+/// The interact packet is sent by the client when the player right or left clicks an entity.
+/// Its fields depend on the interaction, so it is decoded by hand.
+#[packet(packet_id = 0x10, state = "play")]
+pub struct InteractEntity {
+    pub entity_id: VarInt,
+    pub interaction: Interaction,
+    pub sneaking: bool,
+}
+
+impl InteractEntity {
+    pub async fn net_decode<T>(bytes: &mut T) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let entity_id = *VarInt::net_decode(bytes).await?;
+        let kind = VarInt::net_decode(bytes).await?.get_val();
+        let interaction = match kind {
+            0 => Interaction::Interact {
+                hand: Self::decode_hand(bytes).await?,
+            },
+            1 => Interaction::Attack,
+            2 => Interaction::InteractAt {
+                x: *f32::net_decode(bytes).await?,
+                y: *f32::net_decode(bytes).await?,
+                z: *f32::net_decode(bytes).await?,
+                hand: Self::decode_hand(bytes).await?,
+            },
+            _ => return Err(Error::Generic(format!("Invalid interaction type {}", kind))),
+        };
+        let sneaking = *bool::net_decode(bytes).await?;
+
+        Ok(Self {
+            entity_id,
+            interaction,
+            sneaking,
+        })
+    }
+
+    async fn decode_hand<T>(bytes: &mut T) -> Result<Hand>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let hand = VarInt::net_decode(bytes).await?.get_val();
+        Hand::from_id(hand).ok_or_else(|| Error::Generic(format!("Invalid hand {}", hand)))
+    }
+}
+
+impl IncomingPacket for InteractEntity {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!(
+            "InteractEntity packet received: {:?} on {}",
+            self.interaction,
+            self.entity_id
+        );
+
+        let target = match u32::try_from(self.entity_id.get_val()) {
+            Ok(target) if target != conn_id => target,
+            _ => {
+                debug!(
+                    "Entity {} interacted with invalid entity {}",
+                    conn_id, self.entity_id
+                );
+                return Ok(());
+            }
+        };
+        let Some(distance) = distance(conn_id, target, &state).await else {
+            debug!(
+                "Entity {} interacted with unknown entity {}",
+                conn_id, target
+            );
+            return Ok(());
+        };
+        if distance > MAX_REACH {
+            debug!(
+                "Entity {} interacted with entity {} out of reach ({:.1} blocks)",
+                conn_id, target, distance
+            );
+            return Ok(());
+        }
+
+        match self.interaction {
+            Interaction::Attack => {
+                let event = EntityAttackEvent::new(conn_id, target, self.sneaking);
+                state
+                    .event_dispatcher
+                    .dispatch_event(event, state.clone())
+                    .await;
+            }
+            Interaction::Interact { hand } => {
+                let event = EntityInteractEvent::new(conn_id, target, hand, None, self.sneaking);
+                state
+                    .event_dispatcher
+                    .dispatch_event(event, state.clone())
+                    .await;
+            }
+            Interaction::InteractAt { x, y, z, hand } => {
+                let event =
+                    EntityInteractEvent::new(conn_id, target, hand, Some((x, y, z)), self.sneaking);
+                state
+                    .event_dispatcher
+                    .dispatch_event(event, state.clone())
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Distance between two entities, `None` if the target isn't in the world
+async fn distance(entity_id: u32, target: u32, state: &GlobalState) -> Option<f64> {
+    let target = state
+        .world
+        .get_component::<Position>(target)
+        .await
+        .ok()?
+        .clone();
+    let position = state
+        .world
+        .get_component::<Position>(entity_id)
+        .await
+        .ok()?;
+    let (x, y, z) = (
+        (target.x - position.x) as f64,
+        (target.y - position.y) as f64,
+        (target.z - position.z) as f64,
+    );
+    Some((x * x + y * y + z * z).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{InteractEntity, Interaction};
+    use crate::net::packets::incoming::swing_arm::Hand;
+
+    #[tokio::test]
+    async fn decode_interactions() {
+        // Attack entity 5 while sneaking
+        let packet = InteractEntity::net_decode(&mut Cursor::new(vec![5, 1, 1]))
+            .await
+            .unwrap();
+        assert_eq!(packet.entity_id.get_val(), 5);
+        assert_eq!(packet.interaction, Interaction::Attack);
+        assert!(packet.sneaking);
+
+        // Interact at (0.5, 1, 0) of entity 3 with the offhand
+        let mut data = vec![3, 2];
+        for coordinate in [0.5f32, 1.0, 0.0] {
+            data.extend_from_slice(&coordinate.to_be_bytes());
+        }
+        data.extend_from_slice(&[1, 0]);
+        let packet = InteractEntity::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(
+            packet.interaction,
+            Interaction::InteractAt {
+                x: 0.5,
+                y: 1.0,
+                z: 0.0,
+                hand: Hand::Off
+            }
+        );
+        assert!(!packet.sneaking);
+
+        // Unknown interaction type
+        assert!(InteractEntity::net_decode(&mut Cursor::new(vec![3, 7, 0]))
+            .await
+            .is_err());
+    }
+}
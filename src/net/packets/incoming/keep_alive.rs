@@ -1,10 +1,13 @@
-use tracing::debug;
+use tracing::{trace, warn};
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::kick_conn;
 use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::tab_list::TabList;
 use crate::state::GlobalState;
 use crate::utils::components::keep_alive::KeepAlive;
+use crate::utils::components::latency::Latency;
 
 #[derive(NetDecode, Debug)]
 #[packet(packet_id = 0x12, state = "play")]
@@ -15,21 +18,68 @@ pub struct KeepAlivePacketIn {
 impl IncomingPacket for KeepAlivePacketIn {
     async fn handle(
         self,
-        conn: ConnectionId,
+        conn_id: ConnectionId,
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
-        debug!("KeepAlivePacketIn: {:?}", self);
+        trace!("KeepAlivePacketIn: {:?}", self);
 
-        let player = conn;
+        let component_storage = state.world.get_component_storage();
 
-        debug!("Player: {:?}", player);
+        let rtt = component_storage
+            .get_mut_or_insert_with::<KeepAlive>(conn_id, Default::default)
+            .await
+            .answer(self.keep_alive_id);
 
-        let mut keep_alive = state.world.get_component_mut::<KeepAlive>(player).await?;
+        let Some(rtt) = rtt else {
+            warn!(
+                "Entity {} answered keep alive {}, which isn't awaited",
+                conn_id, self.keep_alive_id
+            );
+            return kick_conn(conn_id, "Invalid keep alive", state.clone()).await;
+        };
 
-        debug!("KeepAlive for player: {:?}", *keep_alive);
+        trace!("Entity {} round trip time: {:?}", conn_id, rtt);
+        component_storage
+            .get_mut_or_insert_with::<Latency>(conn_id, Default::default)
+            .await
+            .record(rtt);
 
-        keep_alive.last_received = std::time::Instant::now();
+        TabList::update_latency(conn_id, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeepAlivePacketIn;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::keep_alive::KeepAlive;
+    use crate::utils::components::latency::Latency;
+
+    #[tokio::test]
+    async fn answers_measure_the_latency_and_mismatches_kick() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+
+        let mut keep_alive = KeepAlive::default();
+        let id = keep_alive.issue();
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, keep_alive);
+
+        KeepAlivePacketIn { keep_alive_id: id }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        let latency = state.world.get_component::<Latency>(entity_id).await;
+        assert!(latency.unwrap().rtt.is_some());
 
-        Ok(())
+        // Already answered
+        KeepAlivePacketIn { keep_alive_id: id }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        assert!(state.connections.get_connection(entity_id).is_err());
     }
 }
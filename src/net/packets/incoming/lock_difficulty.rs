@@ -0,0 +1,54 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::packets::outgoing::change_difficulty::ChangeDifficulty;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::components::operator::{op_level, GAMEMASTER_OP_LEVEL};
+
+/// The lock difficulty packet is sent by the client when an operator locks the difficulty in
+/// the pause menu, it can't be changed anymore.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x13, state = "play")]
+pub struct LockDifficulty {
+    pub locked: bool,
+}
+
+impl IncomingPacket for LockDifficulty {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("LockDifficulty packet received: {}", self.locked);
+
+        if op_level(conn_id, &state).await < GAMEMASTER_OP_LEVEL {
+            debug!(
+                "Entity {} locked the difficulty without permission",
+                conn_id
+            );
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            return conn
+                .send_packet(ChangeDifficulty::new(&state.difficulty))
+                .await;
+        }
+
+        if state.difficulty.is_locked() == self.locked {
+            return Ok(());
+        }
+        state.difficulty.set_locked(self.locked);
+        debug!(
+            "Entity {} set the difficulty lock to {}",
+            conn_id, self.locked
+        );
+        let database = state.world.resource::<Database>().await?;
+        if let Err(e) = state.difficulty.save(&database).await {
+            warn!("Failed to save the difficulty: {}", e);
+        }
+        broadcast(ChangeDifficulty::new(&state.difficulty), &state).await
+    }
+}
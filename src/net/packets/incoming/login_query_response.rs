@@ -0,0 +1,86 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{debug, error, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::forwarding::{
+    parse_velocity, reject_login, verify_velocity, PendingForward, VELOCITY_INVALID,
+    VELOCITY_REQUIRED,
+};
+use crate::net::packets::incoming::login_start::LoginStart;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::remaining_bytes::RemainingBytes;
+use crate::utils::prelude::*;
+
+/// The login plugin response answers a
+/// [LoginQueryRequest](crate::net::packets::outgoing::login_query_request::LoginQueryRequest) of
+/// the server, the only one sent asks Velocity for the player info it forwards.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x02, state = "login")]
+pub struct LoginQueryResponse {
+    pub message_id: VarInt,
+    /// Whether the client understood the request, the data is empty otherwise
+    pub successful: bool,
+    pub data: RemainingBytes,
+}
+
+impl IncomingPacket for LoginQueryResponse {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("LoginQueryResponse packet received: {}", self.message_id);
+
+        let pending = match state.world.get_component::<PendingForward>(conn_id).await {
+            Ok(pending) if pending.message_id == self.message_id.get_val() => pending.clone(),
+            _ => {
+                debug!(
+                    "Entity {} answered unknown login query {}",
+                    conn_id, self.message_id
+                );
+                return Ok(());
+            }
+        };
+        state
+            .world
+            .get_component_storage()
+            .remove::<PendingForward>(conn_id as usize)?;
+
+        if !self.successful {
+            warn!("Entity {} didn't connect through Velocity", conn_id);
+            return reject_login(conn_id, VELOCITY_REQUIRED, state).await;
+        }
+        let secret = get_global_config().forwarding.secret.as_bytes();
+        if secret.is_empty() {
+            error!(
+                "Rejecting the login of entity {}: Velocity forwarding needs `forwarding.secret` \
+                 to be set in the config",
+                conn_id
+            );
+            return reject_login(conn_id, VELOCITY_INVALID, state).await;
+        }
+        let Some(payload) = verify_velocity(secret, &self.data.0) else {
+            warn!(
+                "Entity {} sent forwarding data with an invalid signature",
+                conn_id
+            );
+            return reject_login(conn_id, VELOCITY_INVALID, state).await;
+        };
+        let forwarded = match parse_velocity(payload).await {
+            Ok(forwarded) => forwarded,
+            Err(e) => {
+                warn!("Entity {} sent invalid forwarding data: {}", conn_id, e);
+                return reject_login(conn_id, VELOCITY_INVALID, state).await;
+            }
+        };
+
+        debug!(
+            "Velocity forwarded {} from {}",
+            pending.username, forwarded.address
+        );
+        let login = LoginStart {
+            username: forwarded.username.unwrap_or(pending.username),
+            uuid: forwarded.uuid,
+        };
+        login.login(conn_id, forwarded.properties, state).await
+    }
+}
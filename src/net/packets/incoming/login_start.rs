@@ -1,29 +1,55 @@
-use std::time::Instant;
 
 use ferrumc_codec::network_types::varint::VarInt;
 use rand::random;
-use tracing::{debug};
+use tracing::{debug, warn};
 use uuid::Uuid;
 
 use ferrumc_macros::{packet, NetDecode};
+use crate::database::players::PlayerData;
+use crate::database::Database;
 use crate::events::world_events::PlayerJoinWorldEvent;
+use crate::net::authentication::{offline_uuid, PendingAuthentication, SERVER_ID};
+use crate::net::forwarding::{
+    reject_login, ForwardingMode, PendingForward, BUNGEECORD_REQUIRED, VELOCITY_CHANNEL,
+    VELOCITY_FORWARDING_VERSION,
+};
+use crate::net::packets::outgoing::change_difficulty::ChangeDifficulty;
+use crate::net::packets::outgoing::commands::Commands;
 use crate::net::packets::outgoing::default_spawn_position::DefaultSpawnPosition;
+use crate::net::packets::outgoing::encryption_request::EncryptionRequest;
+use crate::net::packets::outgoing::entity_event::EntityEvent;
 use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
 use crate::net::packets::outgoing::login_plugin_request::LoginPluginRequest;
+use crate::net::packets::outgoing::login_query_request::LoginQueryRequest;
 use crate::net::packets::outgoing::login_success::LoginSuccess;
+use crate::net::packets::outgoing::player_abilities::PlayerAbilities;
 use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::systems::time_system::TimeSystem;
+use crate::net::utils::keep_alive::spawn_keep_alive;
+use crate::net::utils::metadata::update_health;
 use crate::net::utils::packet_queue::PacketQueue;
+use crate::net::utils::tab_list::TabList;
 use crate::net::Connection;
 use crate::net::State::Play;
 use crate::state::GlobalState;
+use crate::utils::components::abilities::Abilities;
+use crate::utils::components::food::Food;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::health::Health;
 use crate::utils::components::keep_alive::KeepAlive;
+use crate::utils::components::operator::{Operator, MAX_OP_LEVEL};
+use crate::utils::components::pending_teleports::PendingTeleports;
 use crate::utils::components::player::Player;
+use crate::utils::components::profile::{ProfileProperties, ProfileProperty};
 use crate::utils::components::rotation::Rotation;
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::config::get_global_config;
 use crate::utils::constants::init;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
 
 /// The login start packet is sent by the client to the server to start the login process.
 ///
@@ -44,33 +70,106 @@ pub struct LoginStart {
 // The NBT encoded data for the dimension codec. Using flate_include cos the codec file is like 40kb
 #[cfg(not(test))]
 // flate!(pub static NBT_CODEC: [u8] from "./.etc/nbt_codec.nbt");
-const NBT_CODEC: &[u8] = include_bytes!("../../../../.etc/nbt_codec.nbt");
+pub(crate) const NBT_CODEC: &[u8] = include_bytes!("../../../../.etc/nbt_codec.nbt");
 
 #[cfg(test)]
-const NBT_CODEC: &[u8] = &[0u8; 1];
+pub(crate) const NBT_CODEC: &[u8] = &[0u8; 1];
 
 impl IncomingPacket for LoginStart {
     async fn handle(mut self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
         self.username = self.username.trim().to_string();
 
+        match get_global_config().forwarding.mode {
+            ForwardingMode::None if get_global_config().authentication.online_mode => {
+                self.request_encryption(conn_id, &state).await
+            }
+            ForwardingMode::None => {
+                self.uuid = offline_uuid(&self.username);
+                self.login(conn_id, Vec::new(), state).await
+            }
+            ForwardingMode::Bungeecord => {
+                let forwarded = {
+                    let conn = state.connections.get_connection(conn_id)?;
+                    let conn = conn.read().await;
+                    conn.metadata.forwarded.clone()
+                };
+                let Some(forwarded) = forwarded else {
+                    warn!("{} didn't connect through BungeeCord", self.username);
+                    return reject_login(conn_id, BUNGEECORD_REQUIRED, state).await;
+                };
+                debug!(
+                    "BungeeCord forwarded {} from {}",
+                    self.username, forwarded.address
+                );
+                self.uuid = forwarded.uuid;
+                self.login(conn_id, forwarded.properties, state).await
+            }
+            ForwardingMode::Velocity => self.request_velocity_forwarding(conn_id, &state).await,
+        }
+    }
+}
+
+impl LoginStart {
+    /// Log the player in with its profile, once forwarded by the proxy if there is one
+    pub async fn login(
+        self,
+        conn_id: ConnectionId,
+        properties: Vec<ProfileProperty>,
+        state: GlobalState,
+    ) -> Result<()> {
         let conn = state.connections.get_connection(conn_id)?;
         // let conn = conn.read().await;
 
+        let compression = &get_global_config().compression;
+        if compression.enabled {
+            conn.read()
+                .await
+                .enable_compression(compression.threshold)
+                .await?;
+        }
+
         let mut packet_queue = PacketQueue::new();
 
-        self.send_login_success(&mut packet_queue).await?;
-        self.send_login_play(&mut packet_queue).await?;
+        let saved = self.load_saved_player(&state).await;
+
+        self.send_login_success(&mut packet_queue, &properties)
+            .await?;
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, ProfileProperties { properties });
+        self.send_login_play(&mut packet_queue, conn_id, saved.as_ref())
+            .await?;
+        packet_queue
+            .queue(ChangeDifficulty::new(&state.difficulty))
+            .await?;
+        packet_queue
+            .queue(
+                state
+                    .world_borders
+                    .get(&Dimension::Overworld)
+                    .initialize_packet(),
+            )
+            .await?;
+        TimeSystem::queue_world_state(&mut packet_queue, &state).await?;
         self.send_spawn_position(&mut packet_queue).await?;
 
-        let data: i64 = random();
-        let mut keep_alive = KeepAlive::new(Instant::now(), Instant::now(), data);
+        let mut keep_alive = KeepAlive::default();
+        keep_alive.issue();
         self.send_keep_alive(&mut packet_queue, &mut keep_alive)
             .await?;
-        self.update_world_state(&*conn.read().await, keep_alive, state.clone())
+        self.update_world_state(&*conn.read().await, keep_alive, saved, state.clone())
             .await?;
+        let abilities = state.world.get_component::<Abilities>(conn_id).await?.clone();
+        packet_queue.queue(PlayerAbilities::new(&abilities)).await?;
 
         self.synchronize_player_position(state.clone(), &*conn.read().await, &mut packet_queue)
             .await?;
+        self.send_op_level(&mut packet_queue, conn_id, &state)
+            .await?;
+        packet_queue
+            .queue(Commands::from_registry(&state.commands))
+            .await?;
 
         let packet = LoginPluginRequest::server_brand("🦀".repeat(100)).await;
         // conn.send_packet(packet).await?;
@@ -84,34 +183,101 @@ impl IncomingPacket for LoginStart {
         conn.send_packets(packet_queue).await?;
 
         conn.state = Play;
+        conn.keep_alive = Some(spawn_keep_alive(conn_id, state.clone()));
 
         let entity = conn.id;
 
         // Drop connection to avoid deadlock with chunk sender since it also needs to write to the connection
         drop(conn);
 
+        TabList::join(conn_id, &state).await?;
+        update_health(conn_id, &state).await?;
+        state.boss_bars.resend(conn_id, &state).await?;
+        // Everyone knows its profile now, it can be spawned for them
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, TrackedEntities::default());
         ChunkSender::send_chunks_to_player(state.clone(), entity).await?;
 
         Ok(())
     }
-}
 
-impl LoginStart {
-    async fn send_login_success(&self, packet_queue: &mut PacketQueue) -> Result<()> {
+    /// Ask the client to encrypt the connection, the login goes on once the session server
+    /// authenticated it
+    async fn request_encryption(self, conn_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        let Some(keys) = &state.server_keys else {
+            return Err(Error::Generic(
+                "Online mode is enabled but the server has no keys".to_string(),
+            ));
+        };
+        let verify_token: [u8; 4] = random();
+        state.world.get_component_storage().insert(
+            conn_id,
+            PendingAuthentication {
+                username: self.username,
+                verify_token,
+            },
+        );
+
+        let request = EncryptionRequest::new(SERVER_ID, keys.public_key(), &verify_token);
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(request).await
+    }
+
+    /// Ask Velocity for the player info it forwards, the login goes on once it answers
+    async fn request_velocity_forwarding(
+        self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let message_id = random();
+        state.world.get_component_storage().insert(
+            conn_id,
+            PendingForward {
+                message_id,
+                username: self.username,
+                uuid: self.uuid,
+            },
+        );
+
+        let request = LoginQueryRequest::new(
+            message_id,
+            VELOCITY_CHANNEL,
+            vec![VELOCITY_FORWARDING_VERSION],
+        );
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(request).await
+    }
+
+    /// State saved when the player last left, `None` for new players
+    async fn load_saved_player(&self, state: &GlobalState) -> Option<PlayerData> {
+        let saved = match state.world.resource::<Database>().await {
+            Ok(database) => database.load_player(self.uuid).await,
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!("Failed to load saved state of {}: {}", self.username, e);
+                None
+            }
+        }
+    }
+
+    async fn send_login_success(
+        &self,
+        packet_queue: &mut PacketQueue,
+        properties: &[ProfileProperty],
+    ) -> Result<()> {
         debug!("LoginStart packet received");
         debug!("Username: {}", self.username);
         let uuid = Uuid::from_u128(self.uuid);
         debug!("UUID: {uuid}");
 
-        let namespace_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, "OfflinePlayer".as_bytes());
-        let uuid = Uuid::new_v3(&namespace_uuid, self.username.as_bytes());
-
-        let response = LoginSuccess::new_auto(
-            uuid.as_bytes().into(),
-            "OfflinePlayer".to_string(),
-            VarInt::new(0),
-            vec![],
-        );
+        let response = LoginSuccess::new(self.uuid, self.username.clone(), properties);
 
         packet_queue.queue(response).await?;
 
@@ -122,12 +288,17 @@ impl LoginStart {
         Ok(())
     }
 
-    async fn send_login_play(&self, packet_queue: &mut PacketQueue) -> Result<()> {
+    async fn send_login_play(
+        &self,
+        packet_queue: &mut PacketQueue,
+        conn_id: ConnectionId,
+        saved: Option<&PlayerData>,
+    ) -> Result<()> {
         let play_packet = crate::net::packets::outgoing::login_play::LoginPlay {
             packet_id: VarInt::from(0x28),
-            entity_id: 0,
+            entity_id: conn_id as i32,
             hardcore: false,
-            gamemode: 1,
+            gamemode: saved.map_or(init::DEFAULT_GAMEMODE, |player| player.gamemode),
             previous_gamemode: -1,
             dimension_length: VarInt::new(1),
             dimension_names: vec!["minecraft:overworld".to_string()],
@@ -136,7 +307,7 @@ impl LoginStart {
             dimension_name: "minecraft:overworld".to_string(),
             seed_hash: 0,
             max_players: VarInt::new(20),
-            view_distance: VarInt::new(10),
+            view_distance: VarInt::new(get_global_config().max_view_distance as i32),
             simulation_distance: VarInt::new(10),
             reduced_debug_info: false,
             enable_respawn_screen: true,
@@ -181,30 +352,77 @@ impl LoginStart {
         &self,
         conn: &Connection,
         keep_alive: KeepAlive,
+        saved: Option<PlayerData>,
         state: GlobalState,
     ) -> Result<()> {
         let entity = conn.id;
 
         let component_storage = state.world.get_component_storage();
 
-        component_storage
-            .insert(
-                entity,
+        // Rejoining players appear where they left
+        let (position, rotation, gamemode, abilities) = match saved {
+            Some(player) => (
+                Position::new(
+                    player.x.floor() as i32,
+                    player.y.floor() as i16,
+                    player.z.floor() as i32,
+                ),
+                Rotation::new(player.yaw, player.pitch),
+                player.gamemode,
+                player.abilities,
+            ),
+            None => (
                 Position::new(
                     init::DEFAULT_SPAWN_X_POS,
                     init::DEFAULT_SPAWN_Y_POS,
                     init::DEFAULT_SPAWN_Z_POS,
                 ),
-            )
-            .insert(
-                entity,
                 Rotation::new(init::DEFAULT_SPAWN_YAW, init::DEFAULT_SPAWN_PITCH),
-            )
+                init::DEFAULT_GAMEMODE,
+                0,
+            ),
+        };
+
+        component_storage
+            .insert(entity, position)
+            .insert(entity, rotation)
+            .insert(entity, Gamemode::new(gamemode))
+            .insert(entity, Abilities::restored(gamemode, abilities))
+            .insert(entity, Health::default())
+            .insert(entity, Food::default())
             .insert(entity, keep_alive)
             .insert(entity, Player::new(self.uuid, self.username.clone()));
 
         Ok(())
     }
+    /// Grant the operator flag to players listed in the config, and tell the client its level
+    async fn send_op_level(
+        &self,
+        packet_queue: &mut PacketQueue,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let is_op = get_global_config()
+            .ops
+            .iter()
+            .any(|op| op.eq_ignore_ascii_case(&self.username));
+        let level = if is_op {
+            debug!("{} is an operator", self.username);
+            state
+                .world
+                .get_component_storage()
+                .insert(conn_id, Operator::new(MAX_OP_LEVEL));
+            MAX_OP_LEVEL
+        } else {
+            0
+        };
+
+        packet_queue
+            .queue(EntityEvent::op_level(conn_id as i32, level))
+            .await?;
+        Ok(())
+    }
+
     async fn synchronize_player_position(
         &self,
         state: GlobalState,
@@ -217,7 +435,12 @@ impl LoginStart {
         let position = component_storage.get::<Position>(entity).await?;
         let rotation = component_storage.get::<Rotation>(entity).await?;
 
-        let packet = SynchronizePlayerPosition::new(&position, &rotation);
+        let teleport_id = component_storage
+            .get_mut_or_insert_with::<PendingTeleports>(entity, Default::default)
+            .await
+            .issue();
+
+        let packet = SynchronizePlayerPosition::new(&position, &rotation, teleport_id);
 
         packet_queue.queue(packet).await?;
 
@@ -0,0 +1,37 @@
+use tracing::trace;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::chat_session::ChatAcknowledgment;
+
+/// The message acknowledgment packet is sent by the client when it has seen signed chat
+/// messages without answering them yet. This protocol version only sends the number of such
+/// messages, the offset and bitset of seen messages come with chat messages and commands.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x03, state = "play")]
+pub struct MessageAcknowledgment {
+    pub message_count: VarInt,
+}
+
+impl IncomingPacket for MessageAcknowledgment {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "MessageAcknowledgment packet received: {}",
+            self.message_count
+        );
+
+        // The server doesn't enforce secure chat, the count is kept but not checked
+        state.world.get_component_storage().insert(
+            conn_id,
+            ChatAcknowledgment::new(self.message_count.get_val()),
+        );
+        Ok(())
+    }
+}
@@ -1,11 +1,43 @@
+pub mod change_difficulty;
+pub mod chat_command;
 pub mod chat_message;
+pub mod click_container;
+pub mod client_command;
 pub mod client_info;
+pub mod close_container;
+pub mod command_suggestions_request;
+pub mod confirm_teleport;
+pub mod edit_book;
+pub mod encryption_response;
 pub mod handshake;
+pub mod interact_entity;
 pub mod keep_alive;
+pub mod lock_difficulty;
+pub mod login_query_response;
 pub mod login_start;
+pub mod message_acknowledgment;
+pub mod pick_item;
 pub mod ping;
 pub mod player_abilities;
+pub mod player_action;
+pub mod player_command;
+pub mod player_input;
+pub mod player_session;
+pub mod plugin_message;
+pub mod pong_play;
+pub mod program_command_block;
+pub mod query_block_entity;
+pub mod query_entity;
+pub mod rename_item;
+pub mod resource_pack_response;
+pub mod select_trade;
+pub mod set_creative_slot;
+pub mod set_held_item;
 pub mod set_player_pos_and_rotate;
 pub mod set_player_position;
 pub mod set_player_rotation;
+pub mod set_structure_block;
 pub mod status;
+pub mod swing_arm;
+pub mod update_sign;
+pub mod use_item_on;
@@ -0,0 +1,199 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::incoming::click_container::PLAYER_WINDOW;
+use crate::net::packets::outgoing::set_container_slot::SetContainerSlot;
+use crate::net::packets::outgoing::set_held_item::SetHeldItemOut;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+use crate::world::blocks::raycast;
+use crate::world::conversions::block_state;
+use crate::world::items::block_item;
+
+/// Farthest block a player can pick, in blocks from its eyes
+const PICK_REACH: f64 = 5.0;
+/// Height of the eyes of a standing player
+const EYE_HEIGHT: f64 = 1.62;
+/// Slots of the main inventory and hotbar, as numbered by the packet: the hotbar is 0 to 8
+const PICK_SLOTS: i32 = 36;
+const HOTBAR_SIZE: i32 = 9;
+const CREATIVE: u8 = 1;
+
+/// The pick item packet is sent by the client when the player middle clicks a block, to get
+/// that block in its hand.
+///
+/// The server finds the block the player looks at, brings its item to the hotbar, then sends
+/// the changed slots and the slot to select.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x1A, state = "play")]
+pub struct PickItem {
+    /// Slot to use, the picked item lands there when it is a hotbar slot
+    pub slot: VarInt,
+}
+
+impl IncomingPacket for PickItem {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("PickItem packet received: slot {}", self.slot);
+
+        let slot = self.slot.get_val();
+        if !(0..PICK_SLOTS).contains(&slot) {
+            warn!("Kicking entity {}, it picked into slot {}", conn_id, slot);
+            return kick_conn(conn_id, "Invalid pick slot", state.clone()).await;
+        }
+
+        let world = &state.world;
+        let position = world.get_component::<Position>(conn_id).await?.clone();
+        let direction = world.get_component::<Rotation>(conn_id).await?.direction();
+        let eyes = (
+            position.x as f64 + 0.5,
+            position.y as f64 + EYE_HEIGHT,
+            position.z as f64 + 0.5,
+        );
+        let Some((block, id)) = raycast(&state, eyes, direction, PICK_REACH).await? else {
+            return Ok(());
+        };
+        let Some(item_id) = block_state(id).and_then(|block| block_item(&block.name)) else {
+            debug!(
+                "Entity {} picked block {} at {:?}, it has no known item",
+                conn_id, id, block
+            );
+            return Ok(());
+        };
+
+        let creative = world
+            .get_component::<Gamemode>(conn_id)
+            .await
+            .is_ok_and(|gamemode| gamemode.mode == CREATIVE);
+        let hotbar = if slot < HOTBAR_SIZE {
+            slot as usize
+        } else {
+            world
+                .get_component::<HeldItem>(conn_id)
+                .await
+                .map_or(0, |held| held.slot as usize)
+        };
+
+        let mut packet_queue = PacketQueue::new();
+        let selected = {
+            let mut inventory = world
+                .get_component_storage()
+                .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                .await;
+            let Some((selected, changed)) = inventory.pick(item_id, hotbar, creative) else {
+                return Ok(());
+            };
+            if !changed.is_empty() {
+                let state_id = inventory.next_state_id();
+                for index in changed {
+                    packet_queue
+                        .queue(SetContainerSlot::new(
+                            PLAYER_WINDOW as i8,
+                            state_id,
+                            index as i16,
+                            inventory.slots[index].clone(),
+                        ))
+                        .await?;
+                }
+            }
+            selected
+        };
+        packet_queue
+            .queue(SetHeldItemOut::new(selected as i8))
+            .await?;
+        world
+            .get_component_storage()
+            .insert(conn_id, HeldItem::new(selected as u8));
+
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packets(packet_queue).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+    use tokio::io::AsyncReadExt;
+
+    use super::PickItem;
+    use crate::database::chunks::tests::test_chunk;
+    use crate::database::Database;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::gamemode::Gamemode;
+    use crate::utils::components::inventory::{Inventory, HOTBAR_START};
+    use crate::utils::components::rotation::Rotation;
+    use crate::utils::encoding::position::Position;
+    use crate::world::chunk_format::{Palette, Section};
+    use crate::world::conversions::block_id;
+
+    #[tokio::test]
+    async fn creative_pick_fills_the_hotbar() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        let mut section = Section {
+            block_states: None,
+            biomes: None,
+            y: 4,
+            block_light: None,
+            sky_light: None,
+        };
+        section.set_empty();
+        let mut chunk = test_chunk(0, 0);
+        chunk.sections = Some(vec![section]);
+        let stone = block_id(&Palette {
+            name: "minecraft:stone".to_string(),
+            properties: None,
+        })
+        .unwrap();
+        chunk.set_block_id(0, 65, 3, stone).unwrap();
+        state
+            .world
+            .resource::<Database>()
+            .await
+            .unwrap()
+            .insert_chunk(chunk)
+            .await
+            .unwrap();
+        // Looking south, along +z
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(0, 64, 0))
+            .insert(entity_id, Rotation::new(0.0, 0.0))
+            .insert(entity_id, Gamemode::new(1));
+
+        PickItem {
+            slot: VarInt::new(2),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+        // Length and id of the slot update, then of the held item
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1], 0x14);
+        let mut rest = vec![0u8; header[0] as usize - 1];
+        client.read_exact(&mut rest).await.unwrap();
+        let mut held = [0u8; 3];
+        client.read_exact(&mut held).await.unwrap();
+        assert_eq!(held, [2, 0x4D, 2]);
+
+        let inventory = state.world.get_component::<Inventory>(entity_id).await;
+        let stack = inventory.unwrap().slots[HOTBAR_START + 2].item.clone();
+        assert_eq!(stack.unwrap().item_id.get_val(), 1);
+    }
+}
@@ -1,10 +1,18 @@
-use tracing::trace;
+use tracing::{debug, trace, warn};
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::player_abilities::PlayerAbilities as PlayerAbilitiesOut;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::state::GlobalState;
+use crate::utils::components::abilities::{Abilities, FLYING};
+use crate::utils::config::get_global_config;
 
+/// The player abilities packet is sent by the client when the player starts or stops flying.
+///
+/// The other abilities are granted by the server, a client claiming any of them, or flying
+/// without being allowed to, is sent its abilities back.
 #[derive(NetDecode)]
 #[packet(packet_id = 0x1C, state = "play")]
 pub struct PlayerAbilities {
@@ -14,11 +22,40 @@ pub struct PlayerAbilities {
 impl IncomingPacket for PlayerAbilities {
     async fn handle(
         self,
-        _: ConnectionId,
-        _state: GlobalState,
+        conn_id: ConnectionId,
+        state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
         trace!("PlayerAbilities packet received");
         trace!("Flags: {}", self.flags);
-        Ok(())
+
+        let (violations, packet) = {
+            let Ok(mut abilities) = state.world.get_component_mut::<Abilities>(conn_id).await
+            else {
+                return Ok(());
+            };
+            if abilities.accepts(self.flags) {
+                // Kept so they are saved with the player
+                abilities.set_flying(self.flags & FLYING != 0);
+                return Ok(());
+            }
+            abilities.violations += 1;
+            (abilities.violations, PlayerAbilitiesOut::new(&abilities))
+        };
+
+        let kick_after = get_global_config().ability_checks.kick_after;
+        if kick_after > 0 && violations >= kick_after {
+            warn!(
+                "Kicking entity {}, it claimed abilities it doesn't have {} times",
+                conn_id, violations
+            );
+            return kick_conn(conn_id, "Invalid abilities", state.clone()).await;
+        }
+        debug!(
+            "Entity {} claimed the abilities {:#04x}, sending its own back",
+            conn_id, self.flags
+        );
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
     }
 }
@@ -0,0 +1,122 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::block_events::PlayerActionEvent;
+use crate::net::packets::outgoing::acknowledge_block_change::AcknowledgeBlockChange;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+
+/// What the player is doing in a [`PlayerAction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiggingStatus {
+    Started,
+    Cancelled,
+    Finished,
+    DropItemStack,
+    DropItem,
+    /// Shooting an arrow, finishing eating...
+    ReleaseUseItem,
+    SwapItemInHand,
+}
+
+impl DiggingStatus {
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => DiggingStatus::Started,
+            1 => DiggingStatus::Cancelled,
+            2 => DiggingStatus::Finished,
+            3 => DiggingStatus::DropItemStack,
+            4 => DiggingStatus::DropItem,
+            5 => DiggingStatus::ReleaseUseItem,
+            6 => DiggingStatus::SwapItemInHand,
+            _ => return None,
+        })
+    }
+
+    /// Whether the status is about digging a block, which the client waits an acknowledgement for
+    pub fn is_digging(&self) -> bool {
+        matches!(
+            self,
+            DiggingStatus::Started | DiggingStatus::Cancelled | DiggingStatus::Finished
+        )
+    }
+}
+
+/// The player action packet is sent by the client when it digs a block, drops or swaps items.
+/// The location and face are only meaningful while digging.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x1D, state = "play")]
+pub struct PlayerAction {
+    pub status: VarInt,
+    pub location: Position,
+    pub face: i8,
+    pub sequence: VarInt,
+}
+
+impl IncomingPacket for PlayerAction {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "PlayerAction packet received: status {} at {}",
+            self.status,
+            self.location
+        );
+
+        let Some(status) = DiggingStatus::from_id(self.status.get_val()) else {
+            warn!("Unknown player action status: {}", self.status);
+            return Ok(());
+        };
+
+        let event = PlayerActionEvent::new(conn_id, status, self.location, self.face);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+
+        // Sent after the block updates, or the client rolls back to its predicted block
+        if status.is_digging() {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(AcknowledgeBlockChange::new(self.sequence))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{DiggingStatus, PlayerAction};
+
+    #[tokio::test]
+    async fn decode_finished_digging() {
+        // Status 2, position (1, 64, -1), face 1 (top), sequence 300
+        let position: u64 = (1 << 38) | ((-1i64 as u64 & 0x3FFFFFF) << 12) | 64;
+        let mut data = vec![0x02];
+        data.extend_from_slice(&position.to_be_bytes());
+        data.extend_from_slice(&[0x01, 0xAC, 0x02]);
+
+        let packet = PlayerAction::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(
+            DiggingStatus::from_id(packet.status.get_val()),
+            Some(DiggingStatus::Finished)
+        );
+        assert_eq!(
+            (packet.location.x, packet.location.y, packet.location.z),
+            (1, 64, -1)
+        );
+        assert_eq!(packet.face, 1);
+        assert_eq!(packet.sequence.get_val(), 300);
+    }
+}
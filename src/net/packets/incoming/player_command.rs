@@ -0,0 +1,148 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::entity_metadata::{MetadataValue, FLAGS_INDEX, POSE_INDEX};
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::metadata::update_metadata;
+use crate::state::GlobalState;
+use crate::utils::components::entity_state::EntityState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerCommandAction {
+    StartSneaking,
+    StopSneaking,
+    LeaveBed,
+    StartSprinting,
+    StopSprinting,
+    StartHorseJump,
+    StopHorseJump,
+    OpenVehicleInventory,
+    StartElytraFlying,
+}
+
+impl PlayerCommandAction {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(PlayerCommandAction::StartSneaking),
+            1 => Some(PlayerCommandAction::StopSneaking),
+            2 => Some(PlayerCommandAction::LeaveBed),
+            3 => Some(PlayerCommandAction::StartSprinting),
+            4 => Some(PlayerCommandAction::StopSprinting),
+            5 => Some(PlayerCommandAction::StartHorseJump),
+            6 => Some(PlayerCommandAction::StopHorseJump),
+            7 => Some(PlayerCommandAction::OpenVehicleInventory),
+            8 => Some(PlayerCommandAction::StartElytraFlying),
+            _ => None,
+        }
+    }
+}
+
+/// The player command packet is sent by the client when the player starts or stops sneaking or
+/// sprinting, and for a few vehicle actions.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x1E, state = "play")]
+pub struct PlayerCommand {
+    pub entity_id: VarInt,
+    pub action_id: VarInt,
+    /// Strength of a horse jump, 0 to 100
+    pub jump_boost: VarInt,
+}
+
+impl IncomingPacket for PlayerCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "PlayerCommand packet received: entity {}, action {}",
+            self.entity_id,
+            self.action_id
+        );
+
+        let action = PlayerCommandAction::from_id(self.action_id.get_val());
+        let Some(action) = action.filter(|_| self.entity_id.get_val() == conn_id as i32) else {
+            warn!(
+                "Kicking entity {}, it sent action {} for entity {}",
+                conn_id, self.action_id, self.entity_id
+            );
+            return kick_conn(conn_id, "Invalid player command", state.clone()).await;
+        };
+
+        let (flags, pose) = {
+            let mut entity_state = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<EntityState>(conn_id, Default::default)
+                .await;
+            match action {
+                PlayerCommandAction::StartSneaking => entity_state.sneaking = true,
+                PlayerCommandAction::StopSneaking => entity_state.sneaking = false,
+                PlayerCommandAction::StartSprinting => entity_state.sprinting = true,
+                PlayerCommandAction::StopSprinting => entity_state.sprinting = false,
+                _ => {
+                    // No beds, vehicles or elytras yet
+                    trace!("Ignoring {:?} of entity {}", action, conn_id);
+                    return Ok(());
+                }
+            }
+            (entity_state.flags(), entity_state.pose())
+        };
+        update_metadata(conn_id, &state, |metadata| {
+            metadata
+                .set(FLAGS_INDEX, MetadataValue::Byte(flags))
+                .set(POSE_INDEX, MetadataValue::Pose(pose));
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlayerCommand;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::entity_state::EntityState;
+    use crate::utils::encoding::position::Position;
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    fn command(entity_id: u32, action_id: i32) -> PlayerCommand {
+        PlayerCommand {
+            entity_id: VarInt::from(entity_id as i32),
+            action_id: VarInt::from(action_id),
+            jump_boost: VarInt::from(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn sneaking_is_stored() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(0, 64, 0));
+
+        command(entity_id, 0)
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        let entity_state = state.world.get_component::<EntityState>(entity_id).await;
+        assert!(entity_state.unwrap().sneaking);
+    }
+
+    #[tokio::test]
+    async fn foreign_entity_disconnects() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+
+        command(entity_id + 1, 3)
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        assert!(state.connections.get_connection(entity_id).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::entity_events::DismountEvent;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::vehicle_input::VehicleInput;
+
+const JUMP_FLAG: u8 = 0x01;
+const UNMOUNT_FLAG: u8 = 0x02;
+
+/// The player input packet is sent by the client every tick while the player rides a vehicle,
+/// with the steering keys held.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x1F, state = "play")]
+pub struct PlayerInput {
+    pub sideways: f32,
+    pub forward: f32,
+    pub flags: u8,
+}
+
+/// Clamp a steering value to -1..=1, anything not finite counts as no input
+fn clamp_input(value: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+impl IncomingPacket for PlayerInput {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "PlayerInput packet received: {} {} {:#04x}",
+            self.sideways,
+            self.forward,
+            self.flags
+        );
+
+        if self.flags & UNMOUNT_FLAG != 0 {
+            let event = DismountEvent::new(conn_id);
+            state
+                .event_dispatcher
+                .dispatch_event(event, state.clone())
+                .await;
+            return Ok(());
+        }
+
+        // Sent every tick, the component is only allocated once
+        let mut input = state
+            .world
+            .get_component_storage()
+            .get_mut_or_insert_with::<VehicleInput>(conn_id, Default::default)
+            .await;
+        input.sideways = clamp_input(self.sideways);
+        input.forward = clamp_input(self.forward);
+        input.jump = self.flags & JUMP_FLAG != 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlayerInput;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::test_state;
+    use crate::utils::components::vehicle_input::VehicleInput;
+
+    #[tokio::test]
+    async fn input_is_clamped() {
+        let state = test_state().await;
+        let input = PlayerInput {
+            sideways: 3.0,
+            forward: f32::NAN,
+            flags: 0x01,
+        };
+        input.handle(1, state.clone()).await.unwrap();
+
+        let input = state.world.get_component::<VehicleInput>(1).await.unwrap();
+        assert_eq!(
+            (input.sideways, input.forward, input.jump),
+            (1.0, 0.0, true)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::players::PlayerData;
+use crate::net::kick_conn;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::chat_session::ChatSession;
+
+/// Longest public key a session may carry, in bytes
+pub const MAX_PUBLIC_KEY_LENGTH: usize = 512;
+/// Longest public key signature a session may carry, in bytes
+pub const MAX_KEY_SIGNATURE_LENGTH: usize = 4096;
+
+/// The player session packet is sent by the client when it joins, and whenever its chat signing
+/// key is renewed.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x06, state = "play")]
+pub struct PlayerSession {
+    pub session_id: u128,
+    pub expires_at: i64,
+    pub public_key: Vec<u8>,
+    pub key_signature: Vec<u8>,
+}
+
+impl IncomingPacket for PlayerSession {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("PlayerSession packet received: {:x}", self.session_id);
+
+        if self.public_key.len() > MAX_PUBLIC_KEY_LENGTH
+            || self.key_signature.len() > MAX_KEY_SIGNATURE_LENGTH
+        {
+            warn!(
+                "Kicking entity {}, its chat session key is {} bytes with a {} bytes signature",
+                conn_id,
+                self.public_key.len(),
+                self.key_signature.len()
+            );
+            return kick_conn(conn_id, "Invalid chat session", state.clone()).await;
+        }
+        // Secure chat isn't enforced, an expired key is only worth a note
+        if self.expires_at < PlayerData::now() as i64 {
+            debug!("Entity {} sent an expired chat session key", conn_id);
+        }
+
+        state.world.get_component_storage().insert(
+            conn_id,
+            ChatSession::new(
+                self.session_id,
+                self.expires_at,
+                self.public_key,
+                self.key_signature,
+            ),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlayerSession, MAX_PUBLIC_KEY_LENGTH};
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::chat_session::ChatSession;
+
+    #[tokio::test]
+    async fn session_is_stored() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+
+        PlayerSession {
+            session_id: 7,
+            expires_at: i64::MAX,
+            public_key: vec![1; MAX_PUBLIC_KEY_LENGTH],
+            key_signature: vec![2; 256],
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+        let session = state.world.get_component::<ChatSession>(entity_id).await;
+        let session = session.unwrap();
+        assert_eq!(session.session_id, 7);
+        assert_eq!(session.public_key.len(), MAX_PUBLIC_KEY_LENGTH);
+    }
+}
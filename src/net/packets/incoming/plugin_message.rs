@@ -0,0 +1,55 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::remaining_bytes::RemainingBytes;
+
+/// The plugin message packet carries a custom payload on a namespaced channel, handled by the
+/// [PluginChannelRegistry](crate::net::plugin_channels::PluginChannelRegistry).
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0D, state = "play")]
+pub struct PluginMessage {
+    pub channel: String,
+    pub data: RemainingBytes,
+}
+
+impl IncomingPacket for PluginMessage {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "PluginMessage packet received on {} ({} bytes)",
+            self.channel,
+            self.data.0.len()
+        );
+
+        state
+            .plugin_channels
+            .handle(conn_id, &self.channel, self.data.0, state.clone())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::PluginMessage;
+
+    #[tokio::test]
+    async fn payload_runs_to_the_end_of_the_packet() {
+        let mut data = vec![15];
+        data.extend_from_slice(b"minecraft:brand");
+        data.extend_from_slice(&[7, b'v', b'a', b'n', b'i', b'l', b'l', b'a']);
+
+        let packet = PluginMessage::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.channel, "minecraft:brand");
+        assert_eq!(packet.data.0, b"\x07vanilla");
+    }
+}
@@ -0,0 +1,66 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::latency::Latency;
+
+/// The pong packet is sent by the client to answer a play state
+/// [PlayPing](crate::net::packets::outgoing::play_ping::PlayPing), with the id of the ping.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x20, state = "play")]
+pub struct PongPlay {
+    pub id: i32,
+}
+
+impl IncomingPacket for PongPlay {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("Pong packet received: {}", self.id);
+
+        let rtt = match state.world.get_component_mut::<Latency>(conn_id).await {
+            Ok(mut latency) => latency.complete(self.id),
+            Err(_) => None,
+        };
+        match rtt {
+            Some(rtt) => trace!("Entity {} round trip time: {:?}", conn_id, rtt),
+            None => debug!("Entity {} answered unknown ping {}", conn_id, self.id),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::PongPlay;
+    use crate::net::packets::IncomingPacket;
+    use crate::net::utils::ping::send_ping;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::latency::Latency;
+
+    #[tokio::test]
+    async fn pong_measures_the_latency() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        send_ping(entity_id, &state).await.unwrap();
+        // Length, id of the ping packet, then the ping id
+        let mut ping = [0u8; 6];
+        client.read_exact(&mut ping).await.unwrap();
+        assert_eq!(ping[..2], [5, 0x32]);
+        let id = i32::from_be_bytes([ping[2], ping[3], ping[4], ping[5]]);
+
+        PongPlay { id }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        let latency = state.world.get_component::<Latency>(entity_id).await;
+        assert!(latency.unwrap().rtt.is_some());
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::block_entity_data::BlockEntityData;
+use crate::net::packets::outgoing::block_update::BlockUpdate;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::components::operator::can_use_game_master_blocks;
+use crate::utils::encoding::position::Position;
+use crate::world::chunk_format::{BlockEntity, CommandBlockData, Palette};
+use crate::world::conversions::{block_id, block_state};
+use crate::world::dimension::ChunkPos;
+
+/// Longest command a command block accepts, as vanilla
+pub const MAX_COMMAND_LENGTH: usize = 32500;
+/// Farthest a player can program a command block from, in blocks
+const COMMAND_BLOCK_REACH: f64 = 8.0;
+
+/// Flags of the packet
+const TRACK_OUTPUT: i8 = 0x01;
+const CONDITIONAL: i8 = 0x02;
+const AUTOMATIC: i8 = 0x04;
+
+/// Block of each mode: sequence, auto and redstone
+const COMMAND_BLOCKS: [&str; 3] = [
+    "minecraft:chain_command_block",
+    "minecraft:repeating_command_block",
+    "minecraft:command_block",
+];
+
+/// The program command block packet is sent by the client when an operator is done editing a
+/// command block.
+///
+/// The mode and whether the block is conditional are part of its block state, the command and
+/// the other flags are stored in its block entity.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x29, state = "play")]
+pub struct ProgramCommandBlock {
+    pub location: Position,
+    pub command: String,
+    /// 0 sequence (chain), 1 auto (repeating), 2 redstone (impulse)
+    pub mode: VarInt,
+    pub flags: i8,
+}
+
+/// `block` of the command block of `mode`, keeping its facing
+fn command_block_state(block: &Palette, mode: usize, conditional: bool) -> Palette {
+    let mut properties = block.properties.clone().unwrap_or_default();
+    properties.insert("conditional".to_string(), conditional.to_string());
+    Palette {
+        name: COMMAND_BLOCKS[mode].to_string(),
+        properties: Some(properties),
+    }
+}
+
+impl IncomingPacket for ProgramCommandBlock {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ProgramCommandBlock packet received: {}", self.location);
+
+        let mode = usize::try_from(self.mode.get_val())
+            .ok()
+            .filter(|mode| *mode < COMMAND_BLOCKS.len());
+        let Some(mode) = mode.filter(|_| self.command.chars().count() <= MAX_COMMAND_LENGTH) else {
+            warn!(
+                "Kicking entity {}, it sent an invalid command block program",
+                conn_id
+            );
+            return kick_conn(conn_id, "Invalid command block", state.clone()).await;
+        };
+
+        if !can_use_game_master_blocks(conn_id, &state).await {
+            debug!(
+                "Entity {} programmed a command block without permission",
+                conn_id
+            );
+            return Ok(());
+        }
+
+        let (x, y, z) = (self.location.x, self.location.y as i32, self.location.z);
+        let position = state
+            .world
+            .get_component::<Position>(conn_id)
+            .await?
+            .clone();
+        let (dx, dy, dz) = (
+            x as f64 + 0.5 - position.x as f64,
+            y as f64 + 0.5 - position.y as f64,
+            z as f64 + 0.5 - position.z as f64,
+        );
+        if (dx * dx + dy * dy + dz * dz).sqrt() > COMMAND_BLOCK_REACH {
+            debug!(
+                "Entity {} programmed the command block at {} out of reach",
+                conn_id, self.location
+            );
+            return Ok(());
+        }
+
+        let database = state.world.resource::<Database>().await?;
+        let chunk_pos = ChunkPos::overworld(x >> 4, z >> 4);
+        let Some(chunk) = database.get_chunk(&chunk_pos).await? else {
+            return Ok(());
+        };
+        let block = block_state(chunk.get_block_id(x, y, z)?);
+        let Some(block) = block.filter(|block| COMMAND_BLOCKS.contains(&block.name.as_str()))
+        else {
+            debug!(
+                "Entity {} programmed the command block at {}, but it holds {:?}",
+                conn_id,
+                self.location,
+                block.map(|block| &block.name)
+            );
+            return Ok(());
+        };
+
+        let conditional = self.flags & CONDITIONAL != 0;
+        let Some(new_block) = block_id(&command_block_state(block, mode, conditional)) else {
+            return Ok(());
+        };
+        let mut command_block = chunk
+            .block_entity(x, y, z)
+            .cloned()
+            .unwrap_or_else(|| BlockEntity::new("minecraft:command_block", x, y, z));
+        command_block.command_block = Some(CommandBlockData {
+            command: self.command,
+            track_output: (self.flags & TRACK_OUTPUT != 0) as i8,
+            auto: (self.flags & AUTOMATIC != 0) as i8,
+        });
+
+        // The client of the sender doesn't change the block itself either
+        let mut packets = Vec::new();
+        BlockUpdate::new(self.location.clone(), new_block)
+            .net_encode(&mut packets)
+            .await?;
+        BlockEntityData::command_block(&command_block)
+            .net_encode(&mut packets)
+            .await?;
+
+        let mut chunk = Arc::unwrap_or_clone(chunk);
+        chunk.set_block_id(x, y, z, new_block)?;
+        chunk.set_block_entity(command_block);
+        // Marks the chunk dirty, it is written with the next flush
+        database.update_chunk(chunk).await?;
+        debug!(
+            "Entity {} programmed the command block at {}",
+            conn_id, self.location
+        );
+
+        {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(packets.clone()).await?;
+        }
+        broadcast_to_trackers(packets, conn_id as usize, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::command_block_state;
+    use crate::world::chunk_format::Palette;
+
+    #[test]
+    fn mode_changes_the_block_but_not_its_facing() {
+        let impulse = Palette {
+            name: "minecraft:command_block".to_string(),
+            properties: Some(
+                [("conditional", "false"), ("facing", "up")]
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ),
+        };
+        let chain = command_block_state(&impulse, 0, true);
+        assert_eq!(chain.name, "minecraft:chain_command_block");
+        let properties = chain.properties.unwrap();
+        assert_eq!(properties["conditional"], "true");
+        assert_eq!(properties["facing"], "up");
+    }
+}
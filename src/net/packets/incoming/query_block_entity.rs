@@ -0,0 +1,56 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::packets::outgoing::tag_query_response::TagQueryResponse;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::operator::{op_level, GAMEMASTER_OP_LEVEL};
+use crate::utils::encoding::position::Position;
+use crate::world::dimension::ChunkPos;
+
+/// The query block entity tag packet is sent by the client when an operator copies the data of
+/// a block with F3+I.
+///
+/// Server responds with [TagQueryResponse], empty for players who aren't allowed to query.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x01, state = "play")]
+pub struct QueryBlockEntityTag {
+    pub transaction_id: VarInt,
+    pub location: Position,
+}
+
+impl IncomingPacket for QueryBlockEntityTag {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("QueryBlockEntityTag packet received: {}", self.location);
+
+        let nbt = if op_level(conn_id, &state).await < GAMEMASTER_OP_LEVEL {
+            debug!(
+                "Entity {} queried the block at {} without permission",
+                conn_id, self.location
+            );
+            None
+        } else {
+            let (x, y, z) = (self.location.x, self.location.y as i32, self.location.z);
+            let chunk_pos = ChunkPos::overworld(x >> 4, z >> 4);
+            state
+                .world
+                .resource::<Database>()
+                .await?
+                .get_chunk(&chunk_pos)
+                .await?
+                .and_then(|chunk| chunk.block_entity(x, y, z).map(|entity| entity.to_nbt()))
+        };
+
+        let packet = TagQueryResponse::new(self.transaction_id, nbt);
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
+    }
+}
@@ -0,0 +1,170 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use simdnbt::owned::{NbtCompound, NbtList, NbtTag};
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::tag_query_response::TagQueryResponse;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::food::Food;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::grounded::Grounded;
+use crate::utils::components::health::Health;
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::operator::{op_level, GAMEMASTER_OP_LEVEL};
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+
+/// The query entity tag packet is sent by the client when an operator copies the data of an
+/// entity with F3+I.
+///
+/// Server responds with [TagQueryResponse], empty for players who aren't allowed to query.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0F, state = "play")]
+pub struct QueryEntityTag {
+    pub transaction_id: VarInt,
+    pub entity_id: VarInt,
+}
+
+/// UUID as the 4 ints vanilla stores, most significant first
+fn uuid_ints(uuid: u128) -> Vec<i32> {
+    (0..4)
+        .rev()
+        .map(|index| (uuid >> (index * 32)) as u32 as i32)
+        .collect()
+}
+
+/// Snapshot of the components of `entity_id`, named like vanilla saves them, `None` if the
+/// entity doesn't exist
+pub async fn entity_nbt(entity_id: ConnectionId, state: &GlobalState) -> Option<NbtCompound> {
+    let world = &state.world;
+    let position = world.get_component::<Position>(entity_id).await.ok()?;
+
+    let mut nbt = NbtCompound::new();
+    nbt.insert(
+        "Pos",
+        NbtList::from(vec![
+            position.x as f64,
+            position.y as f64,
+            position.z as f64,
+        ]),
+    );
+    drop(position);
+    if let Ok(rotation) = world.get_component::<Rotation>(entity_id).await {
+        nbt.insert(
+            "Rotation",
+            NbtList::from(vec![rotation.yaw, rotation.pitch]),
+        );
+    }
+    if let Ok(grounded) = world.get_component::<Grounded>(entity_id).await {
+        nbt.insert("OnGround", grounded.is_grounded);
+    }
+    if let Ok(health) = world.get_component::<Health>(entity_id).await {
+        nbt.insert("Health", health.health);
+    }
+    if let Ok(player) = world.get_component::<Player>(entity_id).await {
+        nbt.insert("UUID", NbtTag::IntArray(uuid_ints(player.uuid)));
+    }
+    if let Ok(gamemode) = world.get_component::<Gamemode>(entity_id).await {
+        nbt.insert("playerGameType", gamemode.mode as i32);
+    }
+    if let Ok(food) = world.get_component::<Food>(entity_id).await {
+        nbt.insert("foodLevel", food.food);
+        nbt.insert("foodSaturationLevel", food.saturation);
+    }
+    if let Ok(held_item) = world.get_component::<HeldItem>(entity_id).await {
+        nbt.insert("SelectedItemSlot", held_item.slot as i32);
+    }
+    Some(nbt)
+}
+
+impl IncomingPacket for QueryEntityTag {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "QueryEntityTag packet received: {}",
+            self.entity_id.get_val()
+        );
+
+        let nbt = if op_level(conn_id, &state).await < GAMEMASTER_OP_LEVEL {
+            debug!(
+                "Entity {} queried entity {} without permission",
+                conn_id,
+                self.entity_id.get_val()
+            );
+            None
+        } else {
+            match u32::try_from(self.entity_id.get_val()) {
+                Ok(entity_id) => entity_nbt(entity_id, &state).await,
+                Err(_) => None,
+            }
+        };
+
+        let packet = TagQueryResponse::new(self.transaction_id, nbt);
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+    use tokio::io::AsyncReadExt;
+
+    use super::{entity_nbt, QueryEntityTag};
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::player::Player;
+    use crate::utils::components::rotation::Rotation;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn non_operators_get_an_empty_response() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(0, 64, 0));
+
+        QueryEntityTag {
+            transaction_id: VarInt::new(7),
+            entity_id: VarInt::new(entity_id as i32),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+        // Length, id of the response, transaction id, then a lone TAG_End
+        let mut response = [0u8; 4];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(response, [3, 0x66, 7, 0]);
+    }
+
+    #[tokio::test]
+    async fn snapshot_holds_the_components() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        assert!(entity_nbt(entity_id, &state).await.is_none());
+
+        let uuid = 0x0000_0001_0000_0002_0000_0003_0000_0004;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(1, 64, -3))
+            .insert(entity_id, Rotation::new(90.0, 0.0))
+            .insert(entity_id, Player::new(uuid, "Steve".to_string()));
+
+        let nbt = entity_nbt(entity_id, &state).await.unwrap();
+        let pos = nbt.list("Pos").and_then(|pos| pos.doubles()).unwrap();
+        assert_eq!(pos, [1.0, 64.0, -3.0]);
+        assert_eq!(nbt.int_array("UUID"), Some([1, 2, 3, 4].as_slice()));
+        assert!(nbt.list("Rotation").is_some());
+        assert!(nbt.float("Health").is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::container_events::RenameItemEvent;
+use crate::net::packets::incoming::click_container::resync_window;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::window_manager::WindowManager;
+
+/// Longest name an item can be given in an anvil, in characters
+pub const MAX_ITEM_NAME_LENGTH: usize = 50;
+
+/// The rename item packet is sent by the client whenever the player edits the name typed in an
+/// anvil.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x23, state = "play")]
+pub struct RenameItem {
+    pub item_name: String,
+}
+
+/// `name` without control characters and formatting codes, cut to [`MAX_ITEM_NAME_LENGTH`]
+fn item_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control() && *c != '§')
+        .take(MAX_ITEM_NAME_LENGTH)
+        .collect()
+}
+
+impl IncomingPacket for RenameItem {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("RenameItem packet received: {:?}", self.item_name);
+
+        let name = item_name(&self.item_name);
+        let resync = {
+            let mut windows = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<WindowManager>(conn_id, Default::default)
+                .await;
+            let Some(window) = windows.open.as_mut() else {
+                debug!("Entity {} renamed an item without a window open", conn_id);
+                return Ok(());
+            };
+            window.pending_rename = Some(name.clone());
+            let inventory = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                .await;
+            resync_window(window, &inventory)
+        };
+        let window_id = resync.window_id;
+
+        {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(resync).await?;
+        }
+
+        let event = RenameItemEvent::new(conn_id, window_id, name);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{item_name, MAX_ITEM_NAME_LENGTH};
+
+    #[test]
+    fn names_are_cleaned_and_capped() {
+        assert_eq!(item_name("§cRed\u{7} sword\n"), "cRed sword");
+        let long = "a".repeat(60);
+        assert_eq!(item_name(&long).len(), MAX_ITEM_NAME_LENGTH);
+        assert_eq!(item_name(&"é".repeat(60)).chars().count(), 50);
+    }
+}
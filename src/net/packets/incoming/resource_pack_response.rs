@@ -0,0 +1,89 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::player_events::ResourcePackStatusEvent;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::resource_pack::{ResourcePack, ResourcePackStatus};
+
+/// The resource pack response packet is sent by the client as it handles the resource pack
+/// offered by the server. This protocol version offers a single pack at a time, so the answer
+/// carries no pack id.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x24, state = "play")]
+pub struct ResourcePackResponse {
+    pub result: VarInt,
+}
+
+impl IncomingPacket for ResourcePackResponse {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ResourcePackResponse packet received: {}", self.result);
+
+        let Some(status) = ResourcePackStatus::from_id(self.result.get_val()) else {
+            warn!(
+                "Entity {} sent unknown resource pack status {}",
+                conn_id, self.result
+            );
+            return Ok(());
+        };
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, ResourcePack::new(status));
+
+        let event = ResourcePackStatusEvent::new(conn_id, status);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::ResourcePackResponse;
+    use crate::net::packets::outgoing::resource_pack::ResourcePack as ResourcePackPacket;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::resource_pack::{ResourcePack, ResourcePackStatus};
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    #[tokio::test]
+    async fn declined_pack_is_stored() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        let packet = ResourcePackPacket::new(
+            "https://example.com/pack.zip".to_string(),
+            String::new(),
+            true,
+            Some("Required"),
+        );
+        {
+            let conn = state.connections.get_connection(entity_id).unwrap();
+            let conn = conn.read().await;
+            conn.send_packet(packet).await.unwrap();
+        }
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1], 0x40);
+
+        ResourcePackResponse {
+            result: VarInt::from(1),
+        }
+        .handle(entity_id, state.clone())
+        .await
+        .unwrap();
+        let pack = state.world.get_component::<ResourcePack>(entity_id).await;
+        assert_eq!(pack.unwrap().status, ResourcePackStatus::Declined);
+    }
+}
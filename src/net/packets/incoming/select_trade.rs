@@ -0,0 +1,67 @@
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::container_events::SelectTradeEvent;
+use crate::net::packets::incoming::click_container::resync_window;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::window_manager::WindowManager;
+
+/// The select trade packet is sent by the client when the player picks a trade in the list of
+/// a villager.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x26, state = "play")]
+pub struct SelectTrade {
+    pub selected_slot: VarInt,
+}
+
+impl IncomingPacket for SelectTrade {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("SelectTrade packet received: slot {}", self.selected_slot);
+
+        let slot = self.selected_slot.get_val();
+        if slot < 0 {
+            debug!("Entity {} selected the invalid trade {}", conn_id, slot);
+            return Ok(());
+        }
+        let resync = {
+            let mut windows = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<WindowManager>(conn_id, Default::default)
+                .await;
+            let Some(window) = windows.open.as_mut() else {
+                debug!("Entity {} selected a trade without a window open", conn_id);
+                return Ok(());
+            };
+            window.selected_trade = Some(slot);
+            let inventory = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+                .await;
+            resync_window(window, &inventory)
+        };
+        let window_id = resync.window_id;
+
+        {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(resync).await?;
+        }
+
+        let event = SelectTradeEvent::new(conn_id, window_id, slot);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::inventory::{
+    Inventory, CRAFTING_RESULT_SLOT, INVENTORY_SIZE, MAX_STACK_SIZE,
+};
+use crate::utils::encoding::slot::Slot;
+
+/// Slot of an item thrown out of the creative inventory
+const DROP_SLOT: i16 = -1;
+/// Game mode allowed to set slots
+const CREATIVE: u8 = 1;
+
+/// The set creative mode slot packet is sent by the client when a creative player puts any item
+/// in its inventory, the client decides the content of the slot.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x2B, state = "play")]
+pub struct SetCreativeSlot {
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl IncomingPacket for SetCreativeSlot {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("SetCreativeSlot packet received: slot {}", self.slot);
+
+        if self.slot == DROP_SLOT {
+            // Dropped items don't exist yet
+            return Ok(());
+        }
+        let Some(index) = usize::try_from(self.slot)
+            .ok()
+            .filter(|index| *index != CRAFTING_RESULT_SLOT && *index < INVENTORY_SIZE)
+        else {
+            warn!("Kicking entity {}, it set slot {}", conn_id, self.slot);
+            return kick_conn(conn_id, "Invalid inventory slot", state.clone()).await;
+        };
+
+        let gamemode = state
+            .world
+            .get_component::<Gamemode>(conn_id)
+            .await
+            .map_or(0, |gamemode| gamemode.mode);
+        if gamemode != CREATIVE {
+            warn!(
+                "Entity {} set slot {} without being in creative mode",
+                conn_id, self.slot
+            );
+            return Ok(());
+        }
+        if let Some(stack) = &self.item.item {
+            if !(1..=MAX_STACK_SIZE).contains(&stack.count) {
+                debug!(
+                    "Ignoring stack of {} items set by entity {}",
+                    stack.count, conn_id
+                );
+                return Ok(());
+            }
+        }
+
+        let mut inventory = state
+            .world
+            .get_component_storage()
+            .get_mut_or_insert_with::<Inventory>(conn_id, Default::default)
+            .await;
+        inventory.slots[index] = self.item;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetCreativeSlot;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::gamemode::Gamemode;
+    use crate::utils::components::inventory::Inventory;
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    fn set_slot() -> SetCreativeSlot {
+        SetCreativeSlot {
+            slot: 36,
+            item: Slot {
+                item: Some(ItemStack::new(1, 16)),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn only_creative_players_set_slots() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+
+        set_slot().handle(entity_id, state.clone()).await.unwrap();
+        assert!(state
+            .world
+            .get_component::<Inventory>(entity_id)
+            .await
+            .is_err());
+
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Gamemode::new(1));
+        set_slot().handle(entity_id, state.clone()).await.unwrap();
+        let inventory = state.world.get_component::<Inventory>(entity_id).await;
+        assert_eq!(inventory.unwrap().slots[36], set_slot().item);
+    }
+}
@@ -0,0 +1,122 @@
+use tracing::{trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::set_equipment::{SetEquipment, MAIN_HAND};
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::inventory::Inventory;
+
+/// The set held item packet is sent by the client when the player selects another hotbar slot.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x28, state = "play")]
+pub struct SetHeldItem {
+    pub slot: i16,
+}
+
+impl IncomingPacket for SetHeldItem {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("SetHeldItem packet received: slot {}", self.slot);
+
+        if !(0..=8).contains(&self.slot) {
+            warn!("Kicking entity {}, it selected slot {}", conn_id, self.slot);
+            return kick_conn(conn_id, "Invalid hotbar slot", state.clone()).await;
+        }
+
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, HeldItem::new(self.slot as u8));
+
+        let held = state
+            .world
+            .get_component::<Inventory>(conn_id)
+            .await
+            .map(|inventory| inventory.hotbar(self.slot as u8).clone())
+            .unwrap_or_default();
+        let packet = SetEquipment::new(conn_id as i32, vec![(MAIN_HAND, held)]);
+        broadcast_to_trackers(packet, conn_id as usize, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::SetHeldItem;
+    use crate::net::packets::IncomingPacket;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::held_item::HeldItem;
+    use crate::utils::components::inventory::{Inventory, HOTBAR_START};
+    use crate::utils::components::player::Player;
+    use crate::utils::encoding::position::Position;
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    #[tokio::test]
+    async fn selected_slot_is_stored() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(0, 64, 0));
+
+        SetHeldItem { slot: 4 }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        let held_item = state.world.get_component::<HeldItem>(entity_id).await;
+        assert_eq!(held_item.unwrap().slot, 4);
+    }
+
+    #[tokio::test]
+    async fn held_stack_is_shown_to_trackers() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        let (tracker, mut tracker_client) = test_connection(&state).await;
+        let mut inventory = Inventory::default();
+        inventory.slots[HOTBAR_START + 2] = Slot {
+            item: Some(ItemStack::new(1, 64)),
+        };
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(0, 64, 0))
+            .insert(entity_id, inventory)
+            .insert(tracker, Player::new(2, "Alex".to_string()))
+            .insert(tracker, Position::new(8, 64, 8));
+
+        SetHeldItem { slot: 2 }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+
+        let mut bytes = [0u8; 8];
+        tracker_client.read_exact(&mut bytes).await.unwrap();
+        assert_eq!(bytes, [7, 0x55, entity_id as u8, 0x00, 1, 1, 64, 0]);
+    }
+
+    #[tokio::test]
+    async fn out_of_range_slot_disconnects() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        SetHeldItem { slot: 9 }
+            .handle(entity_id, state.clone())
+            .await
+            .unwrap();
+        assert!(state.connections.get_connection(entity_id).is_err());
+
+        // Length, then the id of the disconnect packet
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1], 0x1A);
+    }
+}
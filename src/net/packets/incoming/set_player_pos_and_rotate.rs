@@ -1,6 +1,8 @@
+use crate::net::movement::{validate_position, validate_rotation};
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
 use crate::state::GlobalState;
+use crate::utils::components::pending_teleports::PendingTeleports;
 use crate::utils::components::rotation::Rotation;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
@@ -20,6 +22,25 @@ pub struct SetPlayerPosAndRotate {
 
 impl IncomingPacket for SetPlayerPosAndRotate {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        // Sent from before the last teleport
+        if state
+            .world
+            .get_component::<PendingTeleports>(conn_id)
+            .await
+            .is_ok_and(|teleports| teleports.is_awaiting())
+        {
+            trace!("Ignoring movement of entity {} until it teleports", conn_id);
+            return Ok(());
+        }
+
+        if !validate_rotation(conn_id, self.yaw, self.pitch, &state).await? {
+            return Ok(());
+        }
+        let Some((x, y, z)) = validate_position(conn_id, (self.x, self.y, self.z), &state).await?
+        else {
+            return Ok(());
+        };
+
         let my_entity_id = conn_id;
 
         let component_storage = state.world.get_component_storage();
@@ -30,14 +51,14 @@ impl IncomingPacket for SetPlayerPosAndRotate {
         ChunkSender::send_chunks_to_player_if_needed(
             state.clone(),
             my_entity_id,
-            (position.x >> 4, position.z >> 4),
+            (x as i32 >> 4, z as i32 >> 4),
         )
         .await?;
 
         *position = Position {
-            x: self.x as i32,
-            y: self.y as i16,
-            z: self.z as i32,
+            x: x as i32,
+            y: y as i16,
+            z: z as i32,
         };
 
         *rotation = Rotation {
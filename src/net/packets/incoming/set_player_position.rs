@@ -2,9 +2,11 @@ use tracing::trace;
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::movement::validate_position;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
 use crate::state::GlobalState;
+use crate::utils::components::pending_teleports::PendingTeleports;
 use crate::utils::encoding::position::Position;
 
 /// The set player position packet is sent by the client to the server to update the player's position.
@@ -24,10 +26,27 @@ impl IncomingPacket for SetPlayerPosition {
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
         trace!("SetPlayerPosition packet received");
+
+        // Sent from before the last teleport
+        if state
+            .world
+            .get_component::<PendingTeleports>(conn_id)
+            .await
+            .is_ok_and(|teleports| teleports.is_awaiting())
+        {
+            trace!("Ignoring movement of entity {} until it teleports", conn_id);
+            return Ok(());
+        }
+
         trace!("X: {}", self.x);
         trace!("Y: {}", self.y);
         trace!("Z: {}", self.z);
 
+        let Some((x, y, z)) = validate_position(conn_id, (self.x, self.y, self.z), &state).await?
+        else {
+            return Ok(());
+        };
+
         let my_entity_id = conn_id;
 
         let component_storage = state.world.get_component_storage();
@@ -37,7 +56,7 @@ impl IncomingPacket for SetPlayerPosition {
         ChunkSender::send_chunks_to_player_if_needed(
             state.clone(),
             my_entity_id,
-            (position.x >> 4, position.z >> 4),
+            (x as i32 >> 4, z as i32 >> 4),
         )
         .await?;
 
@@ -56,9 +75,9 @@ impl IncomingPacket for SetPlayerPosition {
         }*/
 
         *position = Position {
-            x: self.x as i32,
-            y: self.y as i16,
-            z: self.z as i32,
+            x: x as i32,
+            y: y as i16,
+            z: z as i32,
         };
 
         Ok(())
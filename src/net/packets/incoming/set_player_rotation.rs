@@ -1,5 +1,6 @@
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::movement::validate_rotation;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::state::GlobalState;
 use crate::utils::components::rotation::Rotation;
@@ -18,6 +19,10 @@ impl IncomingPacket for SetPlayerRotation {
         conn_id: ConnectionId,
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
+        if !validate_rotation(conn_id, self.yaw, self.pitch, &state).await? {
+            return Ok(());
+        }
+
         let my_entity_id = conn_id;
 
         let component_storage = state.world.get_component_storage();
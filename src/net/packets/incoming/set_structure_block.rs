@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::block_entity_data::BlockEntityData;
+use crate::net::packets::outgoing::block_update::BlockUpdate;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::components::operator::can_use_game_master_blocks;
+use crate::utils::components::player::Player;
+use crate::utils::encoding::position::Position;
+use crate::world::chunk_format::{BlockEntity, Palette, StructureBlockData};
+use crate::world::conversions::{block_id, block_state};
+use crate::world::dimension::ChunkPos;
+
+/// Largest structure a structure block covers, in blocks along each axis, and farthest it can
+/// be offset from it
+pub const MAX_STRUCTURE_SIZE: i8 = 48;
+/// Farthest a player can edit a structure block from, in blocks
+const STRUCTURE_BLOCK_REACH: f64 = 8.0;
+
+const STRUCTURE_BLOCK: &str = "minecraft:structure_block";
+
+/// Action of the packets only updating the settings
+const UPDATE_DATA: i32 = 0;
+
+/// Names of the modes, mirrors and rotations in the NBT, by their id in the packet
+const MODES: [&str; 4] = ["SAVE", "LOAD", "CORNER", "DATA"];
+const MIRRORS: [&str; 3] = ["NONE", "LEFT_RIGHT", "FRONT_BACK"];
+const ROTATIONS: [&str; 4] = [
+    "NONE",
+    "CLOCKWISE_90",
+    "CLOCKWISE_180",
+    "COUNTERCLOCKWISE_90",
+];
+
+/// Flags of the packet
+const IGNORE_ENTITIES: i8 = 0x01;
+const SHOW_AIR: i8 = 0x02;
+const SHOW_BOUNDING_BOX: i8 = 0x04;
+
+/// The set structure block packet is sent by the client when an operator is done editing a
+/// structure block, or pressed one of its buttons.
+///
+/// The settings are stored in its block entity, and its mode in its block state. Saving and
+/// loading structures isn't supported, only the settings are kept.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x2D, state = "play")]
+pub struct SetStructureBlock {
+    pub location: Position,
+    /// 0 update data, 1 save, 2 load, 3 detect size
+    pub action: VarInt,
+    pub mode: VarInt,
+    pub name: String,
+    pub offset_x: i8,
+    pub offset_y: i8,
+    pub offset_z: i8,
+    pub size_x: i8,
+    pub size_y: i8,
+    pub size_z: i8,
+    pub mirror: VarInt,
+    pub rotation: VarInt,
+    pub metadata: String,
+    pub integrity: f32,
+    pub seed: Varlong,
+    pub flags: i8,
+}
+
+/// `names[id]`, `None` for ids out of range
+fn name_of(names: &[&'static str], id: &VarInt) -> Option<&'static str> {
+    usize::try_from(id.get_val())
+        .ok()
+        .and_then(|id| names.get(id).copied())
+}
+
+impl SetStructureBlock {
+    /// Whether the offsets and sizes are within [`MAX_STRUCTURE_SIZE`]
+    fn is_within_size(&self) -> bool {
+        let offset = -MAX_STRUCTURE_SIZE..=MAX_STRUCTURE_SIZE;
+        let size = 0..=MAX_STRUCTURE_SIZE;
+        [self.offset_x, self.offset_y, self.offset_z]
+            .iter()
+            .all(|offset_axis| offset.contains(offset_axis))
+            && [self.size_x, self.size_y, self.size_z]
+                .iter()
+                .all(|size_axis| size.contains(size_axis))
+    }
+
+    /// The settings of the packet, `None` if any is invalid
+    fn structure_block_data(&self, author: String) -> Option<StructureBlockData> {
+        if !self.is_within_size() {
+            return None;
+        }
+        let mut data = StructureBlockData {
+            name: self.name.clone(),
+            author,
+            metadata: self.metadata.clone(),
+            mode: name_of(&MODES, &self.mode)?.to_string(),
+            pos_x: self.offset_x as i32,
+            pos_y: self.offset_y as i32,
+            pos_z: self.offset_z as i32,
+            size_x: self.size_x as i32,
+            size_y: self.size_y as i32,
+            size_z: self.size_z as i32,
+            mirror: name_of(&MIRRORS, &self.mirror)?.to_string(),
+            rotation: name_of(&ROTATIONS, &self.rotation)?.to_string(),
+            integrity_bits: 0,
+            seed: self.seed.0,
+            ignore_entities: (self.flags & IGNORE_ENTITIES != 0) as i8,
+            show_air: (self.flags & SHOW_AIR != 0) as i8,
+            show_bounding_box: (self.flags & SHOW_BOUNDING_BOX != 0) as i8,
+        };
+        // NaN is clamped to 1, like vanilla
+        let integrity = if self.integrity.is_nan() {
+            1.0
+        } else {
+            self.integrity.clamp(0.0, 1.0)
+        };
+        data.set_integrity(integrity);
+        Some(data)
+    }
+}
+
+impl IncomingPacket for SetStructureBlock {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("SetStructureBlock packet received: {}", self.location);
+
+        let author = state
+            .world
+            .get_component::<Player>(conn_id)
+            .await
+            .map(|player| player.username.clone())
+            .unwrap_or_default();
+        let Some(mut data) = self.structure_block_data(author) else {
+            warn!(
+                "Kicking entity {}, it sent invalid structure block settings",
+                conn_id
+            );
+            return kick_conn(conn_id, "Invalid structure block", state.clone()).await;
+        };
+
+        if !can_use_game_master_blocks(conn_id, &state).await {
+            debug!(
+                "Entity {} edited a structure block without permission",
+                conn_id
+            );
+            return Ok(());
+        }
+
+        let (x, y, z) = (self.location.x, self.location.y as i32, self.location.z);
+        let position = state
+            .world
+            .get_component::<Position>(conn_id)
+            .await?
+            .clone();
+        let (dx, dy, dz) = (
+            x as f64 + 0.5 - position.x as f64,
+            y as f64 + 0.5 - position.y as f64,
+            z as f64 + 0.5 - position.z as f64,
+        );
+        if (dx * dx + dy * dy + dz * dz).sqrt() > STRUCTURE_BLOCK_REACH {
+            debug!(
+                "Entity {} edited the structure block at {} out of reach",
+                conn_id, self.location
+            );
+            return Ok(());
+        }
+
+        let database = state.world.resource::<Database>().await?;
+        let chunk_pos = ChunkPos::overworld(x >> 4, z >> 4);
+        let Some(chunk) = database.get_chunk(&chunk_pos).await? else {
+            return Ok(());
+        };
+        let block = block_state(chunk.get_block_id(x, y, z)?);
+        let Some(block) = block.filter(|block| block.name == STRUCTURE_BLOCK) else {
+            debug!(
+                "Entity {} edited the structure block at {}, but it holds {:?}",
+                conn_id,
+                self.location,
+                block.map(|block| &block.name)
+            );
+            return Ok(());
+        };
+        if self.action.get_val() != UPDATE_DATA {
+            debug!(
+                "Entity {} used action {} of the structure block at {}, which isn't supported",
+                conn_id,
+                self.action.get_val(),
+                self.location
+            );
+        }
+
+        let mut properties = block.properties.clone().unwrap_or_default();
+        properties.insert("mode".to_string(), data.mode.to_lowercase());
+        let new_block = block_id(&Palette {
+            name: STRUCTURE_BLOCK.to_string(),
+            properties: Some(properties),
+        });
+        let Some(new_block) = new_block else {
+            return Ok(());
+        };
+        let mut structure_block = chunk
+            .block_entity(x, y, z)
+            .cloned()
+            .unwrap_or_else(|| BlockEntity::new(STRUCTURE_BLOCK, x, y, z));
+        // The author is whoever placed the block
+        if let Some(previous) = &structure_block.structure_block {
+            data.author = previous.author.clone();
+        }
+        structure_block.structure_block = Some(data);
+
+        // The client of the sender doesn't change the block itself either
+        let mut packets = Vec::new();
+        BlockUpdate::new(self.location.clone(), new_block)
+            .net_encode(&mut packets)
+            .await?;
+        BlockEntityData::structure_block(&structure_block)
+            .net_encode(&mut packets)
+            .await?;
+
+        let mut chunk = Arc::unwrap_or_clone(chunk);
+        chunk.set_block_id(x, y, z, new_block)?;
+        chunk.set_block_entity(structure_block);
+        // Marks the chunk dirty, it is written with the next flush
+        database.update_chunk(chunk).await?;
+        debug!(
+            "Entity {} edited the structure block at {}",
+            conn_id, self.location
+        );
+
+        {
+            let conn = state.connections.get_connection(conn_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(packets.clone()).await?;
+        }
+        broadcast_to_trackers(packets, conn_id as usize, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+    use ferrumc_codec::network_types::varlong::Varlong;
+
+    use super::SetStructureBlock;
+    use crate::utils::encoding::position::Position;
+
+    fn packet() -> SetStructureBlock {
+        SetStructureBlock {
+            location: Position::new(0, 64, 0),
+            action: VarInt::new(0),
+            mode: VarInt::new(1),
+            name: "minecraft:house".to_string(),
+            offset_x: 0,
+            offset_y: 1,
+            offset_z: 0,
+            size_x: 48,
+            size_y: 10,
+            size_z: 5,
+            mirror: VarInt::new(2),
+            rotation: VarInt::new(3),
+            metadata: String::new(),
+            integrity: 2.0,
+            seed: Varlong::new(7),
+            flags: 0x05,
+        }
+    }
+
+    #[test]
+    fn settings_are_named_like_vanilla() {
+        let data = packet().structure_block_data("Steve".to_string()).unwrap();
+        assert_eq!(data.mode, "LOAD");
+        assert_eq!(data.mirror, "FRONT_BACK");
+        assert_eq!(data.rotation, "COUNTERCLOCKWISE_90");
+        assert_eq!(data.integrity(), 1.0);
+        assert_eq!(data.size_x, 48);
+        assert_eq!(
+            (data.ignore_entities, data.show_air, data.show_bounding_box),
+            (1, 0, 1)
+        );
+    }
+
+    #[test]
+    fn structures_are_at_most_48_blocks() {
+        let mut too_large = packet();
+        too_large.size_y = 49;
+        assert!(too_large.structure_block_data(String::new()).is_none());
+
+        let mut too_far = packet();
+        too_far.offset_x = -49;
+        assert!(too_far.structure_block_data(String::new()).is_none());
+
+        let mut unknown_mode = packet();
+        unknown_mode.mode = VarInt::new(4);
+        assert!(unknown_mode.structure_block_data(String::new()).is_none());
+    }
+}
@@ -0,0 +1,55 @@
+use tracing::{trace, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::entity_events::SwingArmEvent;
+use crate::net::kick_conn;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Hand a player acts with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Main,
+    Off,
+}
+
+impl Hand {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Hand::Main),
+            1 => Some(Hand::Off),
+            _ => None,
+        }
+    }
+}
+
+/// The swing arm packet is sent by the client when the player swings one of its arms.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x2F, state = "play")]
+pub struct SwingArm {
+    pub hand: VarInt,
+}
+
+impl IncomingPacket for SwingArm {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("SwingArm packet received: hand {}", self.hand);
+
+        let Some(hand) = Hand::from_id(self.hand.get_val()) else {
+            warn!("Kicking entity {}, it swung hand {}", conn_id, self.hand);
+            return kick_conn(conn_id, "Invalid hand", state.clone()).await;
+        };
+
+        let event = SwingArmEvent::new(conn_id, hand);
+        state
+            .event_dispatcher
+            .dispatch_event(event, state.clone())
+            .await;
+        Ok(())
+    }
+}
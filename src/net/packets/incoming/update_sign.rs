@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use tracing::{debug, trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::database::Database;
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::block_entity_data::BlockEntityData;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::world::chunk_format::{BlockEntity, SignText};
+use crate::world::conversions::block_state;
+use crate::world::dimension::ChunkPos;
+
+/// Longest line a sign accepts, in characters
+pub const MAX_LINE_LENGTH: usize = 384;
+/// Farthest a player can edit a sign from, in blocks
+const SIGN_REACH: f64 = 8.0;
+
+/// The update sign packet is sent by the client when the player is done editing a side of a
+/// sign.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x2E, state = "play")]
+pub struct UpdateSign {
+    pub location: Position,
+    pub is_front_text: bool,
+    pub line1: String,
+    pub line2: String,
+    pub line3: String,
+    pub line4: String,
+}
+
+/// Line as a JSON text component, without the control characters
+fn sign_line(line: &str) -> String {
+    let text: String = line.chars().filter(|c| !c.is_control()).collect();
+    serde_json::json!({ "text": text }).to_string()
+}
+
+impl IncomingPacket for UpdateSign {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("UpdateSign packet received: {}", self.location);
+
+        let lines = [self.line1, self.line2, self.line3, self.line4];
+        if lines
+            .iter()
+            .any(|line| line.chars().count() > MAX_LINE_LENGTH)
+        {
+            warn!(
+                "Kicking entity {}, its sign line is longer than {} characters",
+                conn_id, MAX_LINE_LENGTH
+            );
+            return kick_conn(conn_id, "Sign line too long", state.clone()).await;
+        }
+
+        let (x, y, z) = (self.location.x, self.location.y as i32, self.location.z);
+        let position = state
+            .world
+            .get_component::<Position>(conn_id)
+            .await?
+            .clone();
+        let (dx, dy, dz) = (
+            x as f64 + 0.5 - position.x as f64,
+            y as f64 + 0.5 - position.y as f64,
+            z as f64 + 0.5 - position.z as f64,
+        );
+        if (dx * dx + dy * dy + dz * dz).sqrt() > SIGN_REACH {
+            debug!(
+                "Entity {} edited the sign at {} out of reach",
+                conn_id, self.location
+            );
+            return Ok(());
+        }
+
+        let database = state.world.resource::<Database>().await?;
+        let chunk_pos = ChunkPos::overworld(x >> 4, z >> 4);
+        let Some(chunk) = database.get_chunk(&chunk_pos).await? else {
+            return Ok(());
+        };
+        let block = block_state(chunk.get_block_id(x, y, z)?).map(|block| block.name.as_str());
+        let Some(block) = block.filter(|block| block.ends_with("_sign")) else {
+            debug!(
+                "Entity {} edited the sign at {}, but it holds {:?}",
+                conn_id, self.location, block
+            );
+            return Ok(());
+        };
+
+        let mut sign = chunk.block_entity(x, y, z).cloned().unwrap_or_else(|| {
+            let id = if block.ends_with("hanging_sign") {
+                "minecraft:hanging_sign"
+            } else {
+                "minecraft:sign"
+            };
+            BlockEntity {
+                is_waxed: Some(0),
+                ..BlockEntity::new(id, x, y, z)
+            }
+        });
+        if sign.is_waxed == Some(1) {
+            debug!(
+                "Entity {} edited the waxed sign at {}",
+                conn_id, self.location
+            );
+            return Ok(());
+        }
+        let side = if self.is_front_text {
+            &mut sign.front_text
+        } else {
+            &mut sign.back_text
+        };
+        let (color, has_glowing_text) = match side.take() {
+            Some(text) => (text.color, text.has_glowing_text),
+            None => (Some("black".to_string()), Some(0)),
+        };
+        *side = Some(SignText {
+            messages: lines.iter().map(|line| sign_line(line)).collect(),
+            color,
+            has_glowing_text,
+        });
+
+        let packet = BlockEntityData::sign(&sign);
+        let mut chunk = Arc::unwrap_or_clone(chunk);
+        chunk.set_block_entity(sign);
+        // Marks the chunk dirty, it is written with the next flush
+        database.update_chunk(chunk).await?;
+
+        broadcast_to_trackers(packet, conn_id as usize, &state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sign_line;
+
+    #[test]
+    fn control_characters_are_stripped() {
+        assert_eq!(sign_line("Hello\u{7}\nworld"), r#"{"text":"Helloworld"}"#);
+        assert_eq!(sign_line(""), r#"{"text":""}"#);
+    }
+}
@@ -0,0 +1,116 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::{trace, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::block_events::BlockPlaceEvent;
+use crate::net::packets::outgoing::acknowledge_block_change::AcknowledgeBlockChange;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+
+/// Face of a block clicked by the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFace {
+    Bottom,
+    Top,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl BlockFace {
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => BlockFace::Bottom,
+            1 => BlockFace::Top,
+            2 => BlockFace::North,
+            3 => BlockFace::South,
+            4 => BlockFace::West,
+            5 => BlockFace::East,
+            _ => return None,
+        })
+    }
+
+    /// Offset from a block to its neighbour on this face
+    pub fn offset(&self) -> (i32, i32, i32) {
+        match self {
+            BlockFace::Bottom => (0, -1, 0),
+            BlockFace::Top => (0, 1, 0),
+            BlockFace::North => (0, 0, -1),
+            BlockFace::South => (0, 0, 1),
+            BlockFace::West => (-1, 0, 0),
+            BlockFace::East => (1, 0, 0),
+        }
+    }
+
+    /// Position of the block placed against this face of `location`
+    pub fn placement(&self, location: &Position) -> (i32, i32, i32) {
+        let (x, y, z) = self.offset();
+        (location.x + x, location.y as i32 + y, location.z + z)
+    }
+}
+
+/// The use item on packet is sent by the client when it right clicks a block, e.g. to place
+/// the block it is holding against it.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x31, state = "play")]
+pub struct UseItemOn {
+    pub hand: VarInt,
+    pub location: Position,
+    pub face: VarInt,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    pub inside_block: bool,
+    pub sequence: VarInt,
+}
+
+impl IncomingPacket for UseItemOn {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!(
+            "UseItemOn packet received: face {} of {}",
+            self.face,
+            self.location
+        );
+
+        if let Some(face) = BlockFace::from_id(self.face.get_val()) {
+            let event = BlockPlaceEvent::new(conn_id, self.location, face);
+            state
+                .event_dispatcher
+                .dispatch_event(event, state.clone())
+                .await;
+        } else {
+            warn!("Unknown block face: {}", self.face);
+        }
+
+        // The client waits for it whether the block was placed or not
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(AcknowledgeBlockChange::new(self.sequence))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockFace;
+    use crate::utils::encoding::position::Position;
+
+    #[test]
+    fn placement_follows_the_clicked_face() {
+        let location = Position::new(-1, 64, 7);
+        assert_eq!(BlockFace::Top.placement(&location), (-1, 65, 7));
+        assert_eq!(BlockFace::Bottom.placement(&location), (-1, 63, 7));
+        assert_eq!(BlockFace::North.placement(&location), (-1, 64, 6));
+        assert_eq!(BlockFace::West.placement(&location), (-2, 64, 7));
+        assert_eq!(BlockFace::from_id(6), None);
+    }
+}
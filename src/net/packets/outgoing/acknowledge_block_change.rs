@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Acknowledges the block changes the client predicted up to `sequence_id`, so it stops
+/// holding them back and applies the block updates sent by the server
+#[derive(NetEncode)]
+pub struct AcknowledgeBlockChange {
+    #[encode(default = VarInt::from(0x06))]
+    pub packet_id: VarInt,
+    pub sequence_id: VarInt,
+}
+
+impl AcknowledgeBlockChange {
+    pub fn new(sequence_id: VarInt) -> Self {
+        Self::new_auto(sequence_id)
+    }
+}
@@ -0,0 +1,25 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+#[derive(NetEncode)]
+pub struct Statistic {
+    pub category_id: VarInt,
+    pub statistic_id: VarInt,
+    pub value: VarInt,
+}
+
+/// The award statistics packet is sent by the server as the answer to a statistics request.
+#[derive(NetEncode)]
+pub struct AwardStatistics {
+    #[encode(default = VarInt::from(0x05))]
+    pub packet_id: VarInt,
+    pub count: VarInt,
+    pub statistics: Vec<Statistic>,
+}
+
+impl AwardStatistics {
+    pub fn new(statistics: Vec<Statistic>) -> Self {
+        Self::new_auto(VarInt::new(statistics.len() as i32), statistics)
+    }
+}
@@ -0,0 +1,66 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::position::Position;
+use crate::world::blocks::write_nbt;
+use crate::world::chunk_format::BlockEntity;
+
+/// Block entity types, as numbered by the `block_entity_type` registry
+pub const SIGN: i32 = 7;
+pub const HANGING_SIGN: i32 = 8;
+pub const STRUCTURE_BLOCK: i32 = 20;
+pub const COMMAND_BLOCK: i32 = 22;
+
+/// Registry number of the block entity `id`, `None` for the types the server doesn't know
+pub fn type_id(id: &str) -> Option<i32> {
+    match id {
+        "minecraft:sign" => Some(SIGN),
+        "minecraft:hanging_sign" => Some(HANGING_SIGN),
+        "minecraft:structure_block" => Some(STRUCTURE_BLOCK),
+        "minecraft:command_block" => Some(COMMAND_BLOCK),
+        _ => None,
+    }
+}
+
+/// The block entity data packet is sent by the server to update the data of a block entity,
+/// like the text of a sign.
+#[derive(NetEncode)]
+pub struct BlockEntityData {
+    #[encode(default = VarInt::from(0x08))]
+    pub packet_id: VarInt,
+    pub location: Position,
+    pub type_id: VarInt,
+    /// NBT of the block entity, the coordinates and id are already known from the packet
+    pub data: Vec<u8>,
+}
+
+impl BlockEntityData {
+    /// Text of the sign `sign`
+    pub fn sign(sign: &BlockEntity) -> Self {
+        let type_id = if sign.id.ends_with("hanging_sign") {
+            HANGING_SIGN
+        } else {
+            SIGN
+        };
+        Self::of_type(sign, type_id)
+    }
+
+    /// Program of the command block `command_block`
+    pub fn command_block(command_block: &BlockEntity) -> Self {
+        Self::of_type(command_block, COMMAND_BLOCK)
+    }
+
+    /// Settings of the structure block `structure_block`
+    pub fn structure_block(structure_block: &BlockEntity) -> Self {
+        Self::of_type(structure_block, STRUCTURE_BLOCK)
+    }
+
+    fn of_type(block_entity: &BlockEntity, type_id: i32) -> Self {
+        Self::new_auto(
+            Position::new(block_entity.x, block_entity.y as i16, block_entity.z),
+            type_id.into(),
+            write_nbt(block_entity.data_nbt()),
+        )
+    }
+}
@@ -0,0 +1,20 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::position::Position;
+
+/// The block update packet is sent by the server to change a single block on the client.
+#[derive(NetEncode)]
+pub struct BlockUpdate {
+    #[encode(default = VarInt::from(0x0A))]
+    pub packet_id: VarInt,
+    pub location: Position,
+    pub block_id: VarInt,
+}
+
+impl BlockUpdate {
+    pub fn new(location: Position, block_id: i32) -> Self {
+        Self::new_auto(location, block_id.into())
+    }
+}
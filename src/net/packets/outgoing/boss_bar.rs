@@ -0,0 +1,182 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::utils::chat::ChatComponent;
+
+/// Flag of a boss bar darkening the sky
+pub const DARKEN_SKY: u8 = 0x01;
+/// Flag of a boss bar playing the music of the dragon fight
+pub const PLAY_BOSS_MUSIC: u8 = 0x02;
+/// Flag of a boss bar adding fog around the player
+pub const CREATE_FOG: u8 = 0x04;
+
+/// Color of a boss bar, numbered like the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BossBarColor {
+    #[default]
+    Pink = 0,
+    Blue = 1,
+    Red = 2,
+    Green = 3,
+    Yellow = 4,
+    Purple = 5,
+    White = 6,
+}
+
+/// Notches of a boss bar, numbered like the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BossBarStyle {
+    #[default]
+    Progress = 0,
+    Notched6 = 1,
+    Notched10 = 2,
+    Notched12 = 3,
+    Notched20 = 4,
+}
+
+/// Change of a boss bar, each action only sends its own fields
+#[derive(Debug, Clone, PartialEq)]
+pub enum BossBarAction {
+    Add {
+        title: ChatComponent,
+        health: f32,
+        color: BossBarColor,
+        style: BossBarStyle,
+        flags: u8,
+    },
+    Remove,
+    UpdateHealth(f32),
+    UpdateTitle(ChatComponent),
+    UpdateStyle {
+        color: BossBarColor,
+        style: BossBarStyle,
+    },
+    UpdateFlags(u8),
+}
+
+impl BossBarAction {
+    fn id(&self) -> i32 {
+        match self {
+            BossBarAction::Add { .. } => 0,
+            BossBarAction::Remove => 1,
+            BossBarAction::UpdateHealth(_) => 2,
+            BossBarAction::UpdateTitle(_) => 3,
+            BossBarAction::UpdateStyle { .. } => 4,
+            BossBarAction::UpdateFlags(_) => 5,
+        }
+    }
+}
+
+/// The boss bar packet is sent by the server to show, change or hide a boss bar at the top of
+/// the screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBarPacket {
+    pub uuid: u128,
+    pub action: BossBarAction,
+}
+
+impl BossBarPacket {
+    const PACKET_ID: i32 = 0x0B;
+
+    pub fn new(uuid: u128, action: BossBarAction) -> Self {
+        Self { uuid, action }
+    }
+
+    /// Packet id and fields, without the length
+    async fn encode_data(&self, writer: &mut Vec<u8>) -> ferrumc_codec::Result<()> {
+        VarInt::from(Self::PACKET_ID).net_encode(writer).await?;
+        self.uuid.net_encode(writer).await?;
+        VarInt::from(self.action.id()).net_encode(writer).await?;
+        match &self.action {
+            BossBarAction::Add {
+                title,
+                health,
+                color,
+                style,
+                flags,
+            } => {
+                title.to_json().net_encode(writer).await?;
+                health.net_encode(writer).await?;
+                VarInt::from(*color as i32).net_encode(writer).await?;
+                VarInt::from(*style as i32).net_encode(writer).await?;
+                flags.net_encode(writer).await?;
+            }
+            BossBarAction::Remove => {}
+            BossBarAction::UpdateHealth(health) => health.net_encode(writer).await?,
+            BossBarAction::UpdateTitle(title) => title.to_json().net_encode(writer).await?,
+            BossBarAction::UpdateStyle { color, style } => {
+                VarInt::from(*color as i32).net_encode(writer).await?;
+                VarInt::from(*style as i32).net_encode(writer).await?;
+            }
+            BossBarAction::UpdateFlags(flags) => flags.net_encode(writer).await?,
+        }
+        Ok(())
+    }
+}
+
+impl NetEncode for BossBarPacket {
+    /// Encodes the uuid and the action, then the fields of the action, behind the length of
+    /// the packet
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut data = Vec::new();
+        self.encode_data(&mut data).await?;
+        VarInt::from(data.len() as i32).net_encode(writer).await?;
+        writer.write_all(&data).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{BossBarAction, BossBarColor, BossBarPacket, BossBarStyle, CREATE_FOG};
+
+    async fn encode(action: BossBarAction) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        BossBarPacket::new(5, action)
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn actions_only_send_their_fields() {
+        let bytes = encode(BossBarAction::Add {
+            title: "Boss".into(),
+            health: 1.0,
+            color: BossBarColor::Red,
+            style: BossBarStyle::Notched10,
+            flags: CREATE_FOG,
+        })
+        .await;
+        let mut expected = vec![0x0B];
+        expected.extend_from_slice(&5u128.to_be_bytes());
+        expected.extend_from_slice(&[0, 15]);
+        expected.extend_from_slice(br#"{"text":"Boss"}"#);
+        expected.extend_from_slice(&1f32.to_be_bytes());
+        expected.extend_from_slice(&[2, 2, 0x04]);
+        assert_eq!(bytes[0] as usize, expected.len());
+        assert_eq!(bytes[1..], expected);
+
+        let bytes = encode(BossBarAction::Remove).await;
+        assert_eq!(bytes.len(), 1 + 18);
+        assert_eq!(bytes[18], 1);
+
+        let bytes = encode(BossBarAction::UpdateHealth(0.5)).await;
+        assert_eq!(bytes[18], 2);
+        assert_eq!(bytes[19..], 0.5f32.to_be_bytes());
+
+        let bytes = encode(BossBarAction::UpdateStyle {
+            color: BossBarColor::White,
+            style: BossBarStyle::Progress,
+        })
+        .await;
+        assert_eq!(bytes[18..], [4, 6, 0]);
+    }
+}
@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::world::difficulty::WorldDifficulty;
+
+/// The change difficulty packet is sent by the server to tell the clients the difficulty of the
+/// world, and whether it is locked.
+#[derive(NetEncode)]
+pub struct ChangeDifficulty {
+    #[encode(default = VarInt::from(0x0C))]
+    pub packet_id: VarInt,
+    pub difficulty: u8,
+    pub locked: bool,
+}
+
+impl ChangeDifficulty {
+    pub fn new(difficulty: &WorldDifficulty) -> Self {
+        Self::new_auto(difficulty.difficulty().id(), difficulty.is_locked())
+    }
+}
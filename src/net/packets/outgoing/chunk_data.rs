@@ -0,0 +1,447 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use simdnbt::owned::NbtCompound;
+
+use crate::database::Database;
+use crate::net::packets::outgoing::block_entity_data;
+use crate::state::GlobalState;
+use crate::utils::constants::WORLD_MIN_Y;
+use crate::utils::encoding::bitset::BitSet;
+use crate::utils::error::Error;
+use crate::utils::prelude::*;
+use crate::world::blocks::{write_nbt, SECTION_BLOCKS};
+use crate::world::chunk_format::{Chunk, Palette, Section};
+use crate::world::conversions::{biome_count, biome_id, block_id};
+use crate::world::dimension::ChunkPos;
+
+/// Lowest section of the world, and number of sections holding blocks
+pub const MIN_SECTION: i32 = WORLD_MIN_Y >> 4;
+pub const SECTION_COUNT: usize = 24;
+/// Sections with light, one more below and above those holding blocks
+const LIGHT_SECTION_COUNT: usize = SECTION_COUNT + 2;
+/// Bytes of the light of a section, 4 bits per block
+const LIGHT_ARRAY_LENGTH: usize = 2048;
+/// Biomes of a section, one for each 4x4x4 cube
+const SECTION_BIOMES: usize = 4 * 4 * 4;
+/// Longs of a heightmap, 256 heights of 9 bits, 7 per long
+const HEIGHTMAP_LONGS: usize = 37;
+
+/// Blocks that don't count towards the blocks of a section
+const AIR_BLOCKS: [&str; 3] = ["minecraft:air", "minecraft:cave_air", "minecraft:void_air"];
+
+/// How a paletted container is encoded depending on the number of distinct values it holds
+struct ContainerKind {
+    /// Fewest bits per entry of a container with a palette
+    min_indirect_bits: usize,
+    /// Most bits per entry of a container with a palette, above it the values are stored as is
+    max_indirect_bits: usize,
+    /// Bits per entry of the values stored as is
+    direct_bits: usize,
+}
+
+const BLOCK_STATES: ContainerKind = ContainerKind {
+    min_indirect_bits: 4,
+    max_indirect_bits: 8,
+    direct_bits: 15,
+};
+
+/// Bits needed to tell `count` values apart, at least 1
+fn bits_for(count: usize) -> usize {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()).max(1) as usize
+}
+
+impl ContainerKind {
+    fn biomes() -> Self {
+        ContainerKind {
+            min_indirect_bits: 1,
+            max_indirect_bits: 3,
+            direct_bits: bits_for(biome_count()),
+        }
+    }
+}
+
+/// Pack `entries` of `bits` bits each into longs, an entry never spans two longs
+fn pack(entries: impl Iterator<Item = u64>, count: usize, bits: usize) -> Vec<i64> {
+    let per_long = 64 / bits;
+    let mut data = vec![0u64; count.div_ceil(per_long)];
+    for (index, entry) in entries.enumerate() {
+        data[index / per_long] |= entry << ((index % per_long) * bits);
+    }
+    data.into_iter().map(|long| long as i64).collect()
+}
+
+/// Write `values` as a paletted container of `kind`: a single value, values indexing a palette,
+/// or the values themselves
+async fn write_paletted_container(
+    values: &[i32],
+    kind: &ContainerKind,
+    data: &mut Vec<u8>,
+) -> Result<()> {
+    let mut palette: Vec<i32> = Vec::new();
+    for value in values {
+        if !palette.contains(value) {
+            palette.push(*value);
+        }
+    }
+
+    if palette.len() <= 1 {
+        0u8.net_encode(data).await?;
+        VarInt::from(palette.first().copied().unwrap_or_default())
+            .net_encode(data)
+            .await?;
+        VarInt::from(0).net_encode(data).await?;
+        return Ok(());
+    }
+
+    let indirect_bits = bits_for(palette.len()).max(kind.min_indirect_bits);
+    let longs = if indirect_bits <= kind.max_indirect_bits {
+        (indirect_bits as u8).net_encode(data).await?;
+        VarInt::from(palette.len() as i32).net_encode(data).await?;
+        for value in &palette {
+            VarInt::from(*value).net_encode(data).await?;
+        }
+        let entries = values.iter().map(|value| {
+            palette
+                .iter()
+                .position(|entry| entry == value)
+                .unwrap_or_default() as u64
+        });
+        pack(entries, values.len(), indirect_bits)
+    } else {
+        (kind.direct_bits as u8).net_encode(data).await?;
+        let entries = values.iter().map(|value| *value as u64);
+        pack(entries, values.len(), kind.direct_bits)
+    };
+    VarInt::from(longs.len() as i32).net_encode(data).await?;
+    for long in longs {
+        long.net_encode(data).await?;
+    }
+    Ok(())
+}
+
+/// Write the blocks and biomes of `section`, an empty section if `None`
+async fn write_section(section: Option<&Section>, data: &mut Vec<u8>) -> Result<()> {
+    let blocks = match section {
+        Some(section) => section.block_ids()?,
+        None => vec![0; SECTION_BLOCKS],
+    };
+    let air: Vec<i32> = AIR_BLOCKS
+        .iter()
+        .filter_map(|name| {
+            block_id(&Palette {
+                name: name.to_string(),
+                properties: None,
+            })
+        })
+        .collect();
+    let block_count = blocks.iter().filter(|id| !air.contains(id)).count();
+    (block_count as i16).net_encode(data).await?;
+    write_paletted_container(&blocks, &BLOCK_STATES, data).await?;
+
+    // Only the palette of the biomes is kept, the first one fills the section
+    let biome = section
+        .and_then(|section| section.biomes.as_ref())
+        .and_then(|biomes| biomes.palette.first())
+        .and_then(|biome| biome_id(biome))
+        .unwrap_or_default();
+    write_paletted_container(&[biome; SECTION_BIOMES], &ContainerKind::biomes(), data).await
+}
+
+/// Block entity of a [`ChunkData`], its coordinates relative to the chunk
+#[derive(NetEncode)]
+pub struct ChunkBlockEntity {
+    /// `(x << 4) | z`, within the chunk
+    pub packed_xz: u8,
+    pub y: i16,
+    pub type_id: VarInt,
+    pub data: Vec<u8>,
+}
+
+/// Light of a section, 4 bits per block
+#[derive(NetEncode)]
+pub struct LightArray {
+    #[encode(raw_bytes(prepend_length = true))]
+    pub data: Vec<u8>,
+}
+
+/// Sky and block light of the sections of a [`ChunkData`], from one below the world to one
+/// above it
+///
+/// The sections with light are set in the masks, those known to be dark in the empty masks.
+#[derive(NetEncode)]
+pub struct LightData {
+    pub sky_light_mask: BitSet,
+    pub block_light_mask: BitSet,
+    pub empty_sky_light_mask: BitSet,
+    pub empty_block_light_mask: BitSet,
+    pub sky_light_array_count: VarInt,
+    pub sky_light_arrays: Vec<LightArray>,
+    pub block_light_array_count: VarInt,
+    pub block_light_arrays: Vec<LightArray>,
+}
+
+impl LightData {
+    fn new(chunk: &Chunk) -> Self {
+        let mut light = LightData {
+            sky_light_mask: BitSet::new(LIGHT_SECTION_COUNT),
+            block_light_mask: BitSet::new(LIGHT_SECTION_COUNT),
+            empty_sky_light_mask: BitSet::new(LIGHT_SECTION_COUNT),
+            empty_block_light_mask: BitSet::new(LIGHT_SECTION_COUNT),
+            sky_light_array_count: VarInt::from(0),
+            sky_light_arrays: Vec::new(),
+            block_light_array_count: VarInt::from(0),
+            block_light_arrays: Vec::new(),
+        };
+        for index in 0..LIGHT_SECTION_COUNT {
+            let y = MIN_SECTION - 1 + index as i32;
+            let section = chunk
+                .sections
+                .iter()
+                .flatten()
+                .find(|section| section.y as i32 == y);
+            let light_array = |light: Option<&Vec<i8>>| {
+                light
+                    .filter(|light| light.len() == LIGHT_ARRAY_LENGTH)
+                    .map(|light| LightArray {
+                        data: light.iter().map(|byte| *byte as u8).collect(),
+                    })
+            };
+
+            match light_array(section.and_then(|section| section.sky_light.as_ref())) {
+                Some(array) => {
+                    light.sky_light_mask.set(index);
+                    light.sky_light_arrays.push(array);
+                }
+                None => light.empty_sky_light_mask.set(index),
+            }
+            match light_array(section.and_then(|section| section.block_light.as_ref())) {
+                Some(array) => {
+                    light.block_light_mask.set(index);
+                    light.block_light_arrays.push(array);
+                }
+                None => light.empty_block_light_mask.set(index),
+            }
+        }
+        light.sky_light_array_count = VarInt::from(light.sky_light_arrays.len() as i32);
+        light.block_light_array_count = VarInt::from(light.block_light_arrays.len() as i32);
+        light
+    }
+}
+
+/// The chunk data and update light packet is sent by the server to load a chunk on the client:
+/// its blocks, biomes, block entities and light.
+#[derive(NetEncode)]
+pub struct ChunkData {
+    #[encode(default = VarInt::from(0x24))]
+    pub packet_id: VarInt,
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    /// NBT of the heightmaps
+    pub heightmaps: Vec<u8>,
+    /// Blocks and biomes of every section, from the bottom of the world up
+    #[encode(raw_bytes(prepend_length = true))]
+    pub data: Vec<u8>,
+    pub block_entity_count: VarInt,
+    pub block_entities: Vec<ChunkBlockEntity>,
+    pub light: LightData,
+}
+
+impl ChunkData {
+    /// Packet of the chunk at `chunk_x`, `chunk_z` of the overworld
+    pub async fn load(state: &GlobalState, chunk_x: i32, chunk_z: i32) -> Result<Self> {
+        Self::load_at(state, &ChunkPos::overworld(chunk_x, chunk_z)).await
+    }
+
+    /// Packet of the chunk at `pos`, in any dimension
+    pub async fn load_at(state: &GlobalState, pos: &ChunkPos) -> Result<Self> {
+        let chunk = state
+            .world
+            .resource::<Database>()
+            .await?
+            .get_chunk(pos)
+            .await?
+            .ok_or(Error::ChunkNotFound(pos.x, pos.z))?;
+        Self::from_chunk(&chunk).await
+    }
+
+    /// Packet of `chunk`, which must be in network mode, see [`Chunk::convert_to_net_mode`]
+    pub async fn from_chunk(chunk: &Chunk) -> Result<Self> {
+        let mut heightmaps = NbtCompound::new();
+        let stored = chunk.heightmaps.as_ref();
+        let heightmap = |heightmap: Option<&Vec<i64>>| {
+            heightmap
+                .filter(|heightmap| heightmap.len() == HEIGHTMAP_LONGS)
+                .cloned()
+                .unwrap_or_else(|| vec![0; HEIGHTMAP_LONGS])
+        };
+        heightmaps.insert(
+            "MOTION_BLOCKING",
+            heightmap(stored.and_then(|stored| stored.motion_blocking.as_ref())),
+        );
+        heightmaps.insert(
+            "WORLD_SURFACE",
+            heightmap(stored.and_then(|stored| stored.world_surface.as_ref())),
+        );
+
+        let mut data = Vec::new();
+        for y in MIN_SECTION..MIN_SECTION + SECTION_COUNT as i32 {
+            let section = chunk
+                .sections
+                .iter()
+                .flatten()
+                .find(|section| section.y as i32 == y);
+            write_section(section, &mut data).await?;
+        }
+
+        // The client can't make sense of the block entities of unknown types
+        let block_entities: Vec<ChunkBlockEntity> = chunk
+            .block_entities
+            .iter()
+            .flatten()
+            .filter_map(|block_entity| {
+                Some(ChunkBlockEntity {
+                    packed_xz: (((block_entity.x & 15) << 4) | (block_entity.z & 15)) as u8,
+                    y: block_entity.y as i16,
+                    type_id: VarInt::from(block_entity_data::type_id(&block_entity.id)?),
+                    data: write_nbt(block_entity.data_nbt()),
+                })
+            })
+            .collect();
+
+        Ok(ChunkData::new_auto(
+            chunk.x_pos,
+            chunk.z_pos,
+            write_nbt(heightmaps),
+            data,
+            VarInt::from(block_entities.len() as i32),
+            block_entities,
+            LightData::new(chunk),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    use super::{
+        write_paletted_container, write_section, ContainerKind, BLOCK_STATES, SECTION_BIOMES,
+    };
+    use crate::world::blocks::SECTION_BLOCKS;
+    use crate::world::chunk_format::{BlockStates, Section};
+
+    /// Section of the network mode with `blocks`, indexed by `(y * 16 + z) * 16 + x`
+    fn section(blocks: &[i32]) -> Section {
+        let mut palette: Vec<i32> = Vec::new();
+        for block in blocks {
+            if !palette.contains(block) {
+                palette.push(*block);
+            }
+        }
+        let bits = ((palette.len() as f32).log2().ceil() as usize).max(4);
+        let per_long = 64 / bits;
+        let mut data = vec![0u64; SECTION_BLOCKS.div_ceil(per_long)];
+        for (index, block) in blocks.iter().enumerate() {
+            let entry = palette.iter().position(|entry| entry == block).unwrap() as u64;
+            data[index / per_long] |= entry << ((index % per_long) * bits);
+        }
+        Section {
+            block_states: Some(BlockStates {
+                non_air_blocks: None,
+                bits_per_block: Some(bits as i8),
+                data: Some(data.into_iter().map(|long| long as i64).collect()),
+                palette: None,
+                net_palette: Some(palette.into_iter().map(VarInt::from).collect()),
+            }),
+            biomes: None,
+            y: 0,
+            block_light: None,
+            sky_light: None,
+        }
+    }
+
+    /// The biomes of the sections of the tests, all the first biome
+    const BIOMES: [u8; 3] = [0x00, 0x00, 0x00];
+
+    #[tokio::test]
+    async fn empty_section() {
+        let mut data = Vec::new();
+        write_section(None, &mut data).await.unwrap();
+        // No blocks, air as single value without data, then the biomes
+        let mut expected = vec![0x00, 0x00, 0x00, 0x00, 0x00];
+        expected.extend_from_slice(&BIOMES);
+        assert_eq!(data, expected);
+    }
+
+    #[tokio::test]
+    async fn single_block_section() {
+        let mut data = Vec::new();
+        write_section(Some(&section(&[1; SECTION_BLOCKS])), &mut data)
+            .await
+            .unwrap();
+        // 4096 blocks, stone as single value without data, then the biomes
+        let mut expected = vec![0x10, 0x00, 0x00, 0x01, 0x00];
+        expected.extend_from_slice(&BIOMES);
+        assert_eq!(data, expected);
+    }
+
+    #[tokio::test]
+    async fn mixed_section() {
+        // A floor of stone, air above
+        let mut blocks = vec![0; SECTION_BLOCKS];
+        blocks[..256].fill(1);
+        let mut data = Vec::new();
+        write_section(Some(&section(&blocks)), &mut data)
+            .await
+            .unwrap();
+
+        // 256 blocks, 4 bits per entry indexing a palette of stone then air
+        let mut expected = vec![0x01, 0x00, 0x04, 0x02, 0x01, 0x00];
+        // 256 longs of 16 entries
+        expected.extend_from_slice(&[0x80, 0x02]);
+        expected.extend_from_slice(&[0x00; 16 * 8]);
+        expected.extend_from_slice(&[0x11; 240 * 8]);
+        expected.extend_from_slice(&BIOMES);
+        assert_eq!(data, expected);
+    }
+
+    #[tokio::test]
+    async fn entries_never_span_two_longs() {
+        // 17 distinct blocks need 5 bits, 12 entries per long and 4 bits of padding
+        let blocks: Vec<i32> = (0..SECTION_BLOCKS as i32).map(|index| index % 17).collect();
+        let mut data = Vec::new();
+        write_paletted_container(&blocks, &BLOCK_STATES, &mut data)
+            .await
+            .unwrap();
+        assert_eq!(data[0], 5);
+        // Bits, palette length and palette, then 342 longs
+        let longs = &data[1 + 1 + 17..];
+        assert_eq!(longs[..2], [0xD6, 0x02]);
+        assert_eq!(longs.len(), 2 + 342 * 8);
+        // Entries 0 to 11 of the first long, big endian
+        let first = i64::from_be_bytes(longs[2..10].try_into().unwrap()) as u64;
+        assert_eq!(first >> 60, 0);
+        assert_eq!(first & 0x1F, 0);
+        assert_eq!((first >> 55) & 0x1F, 11);
+
+        // Above 8 bits, the block ids are stored as is on 15 bits
+        let blocks: Vec<i32> = (0..SECTION_BLOCKS as i32).collect();
+        let mut data = Vec::new();
+        write_paletted_container(&blocks, &BLOCK_STATES, &mut data)
+            .await
+            .unwrap();
+        assert_eq!(data[0], 15);
+        // 1024 longs of 4 entries
+        assert_eq!(data[1..3], [0x80, 0x08]);
+        let first = i64::from_be_bytes(data[3..11].try_into().unwrap()) as u64;
+        assert_eq!(first, (3 << 45) | (2 << 30) | (1 << 15));
+
+        // Biomes of a single value
+        let mut data = Vec::new();
+        write_paletted_container(&[7; SECTION_BIOMES], &ContainerKind::biomes(), &mut data)
+            .await
+            .unwrap();
+        assert_eq!(data, [0x00, 0x07, 0x00]);
+    }
+}
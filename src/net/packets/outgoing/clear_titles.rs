@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The clear titles packet is sent by the server to hide the title shown.
+#[derive(NetEncode)]
+pub struct ClearTitles {
+    #[encode(default = VarInt::from(0x0E))]
+    pub packet_id: VarInt,
+    /// Also reset the subtitle and times to their defaults
+    pub reset: bool,
+}
+
+impl ClearTitles {
+    pub fn new(reset: bool) -> Self {
+        Self::new_auto(reset)
+    }
+}
@@ -0,0 +1,17 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The close container packet is sent by the server to close a window the player has open.
+#[derive(NetEncode)]
+pub struct CloseContainer {
+    #[encode(default = VarInt::from(0x11))]
+    pub packet_id: VarInt,
+    pub window_id: u8,
+}
+
+impl CloseContainer {
+    pub fn new(window_id: u8) -> Self {
+        Self::new_auto(window_id)
+    }
+}
@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+#[derive(NetEncode)]
+pub struct Suggestion {
+    pub text: String,
+    pub has_tooltip: bool,
+    /// JSON text component, only encoded when `has_tooltip` is set
+    pub tooltip: Option<String>,
+}
+
+impl Suggestion {
+    pub fn new(text: String, tooltip: Option<&str>) -> Self {
+        Self {
+            text,
+            has_tooltip: tooltip.is_some(),
+            tooltip: tooltip.map(|tooltip| serde_json::json!({ "text": tooltip }).to_string()),
+        }
+    }
+}
+
+/// The command suggestions response packet is sent by the server to complete the text between
+/// `start` and `start + length` of a command being typed.
+#[derive(NetEncode)]
+pub struct CommandSuggestionsResponse {
+    #[encode(default = VarInt::from(0x0F))]
+    pub packet_id: VarInt,
+    /// Id of the request, the client ignores responses to other requests
+    pub transaction_id: VarInt,
+    pub start: VarInt,
+    pub length: VarInt,
+    pub count: VarInt,
+    pub matches: Vec<Suggestion>,
+}
+
+impl CommandSuggestionsResponse {
+    pub fn new(transaction_id: VarInt, start: i32, length: i32, matches: Vec<Suggestion>) -> Self {
+        Self::new_auto(
+            transaction_id,
+            start.into(),
+            length.into(),
+            VarInt::new(matches.len() as i32),
+            matches,
+        )
+    }
+}
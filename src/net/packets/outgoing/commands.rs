@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::warn;
+
+use crate::events::command_events::node::{CommandNode, NodeKind, Parser, StringKind};
+use crate::events::command_events::registry::CommandRegistry;
+
+/// Type of a node, the low bits of its flags
+const ROOT: u8 = 0x00;
+const LITERAL: u8 = 0x01;
+const ARGUMENT: u8 = 0x02;
+const EXECUTABLE: u8 = 0x04;
+const HAS_REDIRECT: u8 = 0x08;
+const HAS_SUGGESTIONS: u8 = 0x10;
+
+/// Flags of the bounds of numeric arguments
+const HAS_MIN: u8 = 0x01;
+const HAS_MAX: u8 = 0x02;
+/// Flags of entity arguments
+const SINGLE: u8 = 0x01;
+const PLAYERS_ONLY: u8 = 0x02;
+
+/// Node of the flattened tree, referring to the other nodes by index
+#[derive(Debug, Clone, PartialEq)]
+struct FlatNode {
+    flags: u8,
+    children: Vec<i32>,
+    redirect: Option<i32>,
+    /// `None` for the root
+    kind: Option<NodeKind>,
+}
+
+/// The commands packet is sent by the server to tell the client the syntax of the commands,
+/// which it uses to parse, highlight and complete what the player types.
+///
+/// The tree is sent as an array of nodes, each referring to its children and redirect by index.
+/// The root comes first, then the nodes depth first.
+pub struct Commands {
+    nodes: Vec<FlatNode>,
+}
+
+impl Commands {
+    const PACKET_ID: i32 = 0x10;
+    const ROOT_INDEX: i32 = 0;
+
+    /// Tree of the commands of `registry`
+    pub fn from_registry(registry: &CommandRegistry) -> Self {
+        Self::new(&registry.nodes())
+    }
+
+    /// Tree whose root has the children `commands`
+    ///
+    /// Redirects to a path that doesn't exist are left out.
+    pub fn new(commands: &[CommandNode]) -> Self {
+        let mut nodes = vec![FlatNode {
+            flags: ROOT,
+            children: Vec::new(),
+            redirect: None,
+            kind: None,
+        }];
+        let mut paths = HashMap::from([(Vec::new(), Self::ROOT_INDEX)]);
+        let mut redirects = Vec::new();
+        let children = commands
+            .iter()
+            .map(|command| Self::flatten(command, &[], &mut nodes, &mut paths, &mut redirects))
+            .collect();
+        nodes[Self::ROOT_INDEX as usize].children = children;
+
+        for (index, path) in redirects {
+            let Some(&target) = paths.get(&path) else {
+                warn!("Command redirect to unknown /{}", path.join(" "));
+                continue;
+            };
+            let node = &mut nodes[index as usize];
+            node.flags |= HAS_REDIRECT;
+            node.redirect = Some(target);
+        }
+        Self { nodes }
+    }
+
+    /// Append `node` and its children, returns the index of `node`
+    fn flatten(
+        node: &CommandNode,
+        parent: &[String],
+        nodes: &mut Vec<FlatNode>,
+        paths: &mut HashMap<Vec<String>, i32>,
+        redirects: &mut Vec<(i32, Vec<String>)>,
+    ) -> i32 {
+        let index = nodes.len() as i32;
+        let mut flags = match &node.kind {
+            NodeKind::Literal(_) => LITERAL,
+            NodeKind::Argument { suggestions, .. } if suggestions.is_some() => {
+                ARGUMENT | HAS_SUGGESTIONS
+            }
+            NodeKind::Argument { .. } => ARGUMENT,
+        };
+        if node.executable {
+            flags |= EXECUTABLE;
+        }
+        nodes.push(FlatNode {
+            flags,
+            children: Vec::new(),
+            redirect: None,
+            kind: Some(node.kind.clone()),
+        });
+
+        let mut path = parent.to_vec();
+        path.push(node.name().to_string());
+        if let Some(redirect) = &node.redirect {
+            redirects.push((index, redirect.clone()));
+        }
+        let children = node
+            .children
+            .iter()
+            .map(|child| Self::flatten(child, &path, nodes, paths, redirects))
+            .collect();
+        nodes[index as usize].children = children;
+        paths.insert(path, index);
+        index
+    }
+
+    /// Packet id and fields, without the length
+    async fn encode_data(&self, writer: &mut Vec<u8>) -> ferrumc_codec::Result<()> {
+        VarInt::from(Self::PACKET_ID).net_encode(writer).await?;
+        VarInt::from(self.nodes.len() as i32)
+            .net_encode(writer)
+            .await?;
+        for node in &self.nodes {
+            node.flags.net_encode(writer).await?;
+            VarInt::from(node.children.len() as i32)
+                .net_encode(writer)
+                .await?;
+            for &child in &node.children {
+                VarInt::from(child).net_encode(writer).await?;
+            }
+            if let Some(redirect) = node.redirect {
+                VarInt::from(redirect).net_encode(writer).await?;
+            }
+            match &node.kind {
+                None => {}
+                Some(NodeKind::Literal(name)) => name.net_encode(writer).await?,
+                Some(NodeKind::Argument {
+                    name,
+                    parser,
+                    suggestions,
+                }) => {
+                    name.net_encode(writer).await?;
+                    VarInt::from(parser.id()).net_encode(writer).await?;
+                    encode_properties(parser, writer).await?;
+                    if let Some(suggestions) = suggestions {
+                        suggestions.net_encode(writer).await?;
+                    }
+                }
+            }
+        }
+        VarInt::from(Self::ROOT_INDEX).net_encode(writer).await
+    }
+}
+
+/// Properties of the parser of an argument, after its id
+async fn encode_properties(parser: &Parser, writer: &mut Vec<u8>) -> ferrumc_codec::Result<()> {
+    match parser {
+        Parser::Bool | Parser::Vec3 => {}
+        Parser::Double { min, max } => {
+            let flags = min.map_or(0, |_| HAS_MIN) | max.map_or(0, |_| HAS_MAX);
+            flags.net_encode(writer).await?;
+            min.net_encode(writer).await?;
+            max.net_encode(writer).await?;
+        }
+        Parser::Integer { min, max } => {
+            let flags = min.map_or(0, |_| HAS_MIN) | max.map_or(0, |_| HAS_MAX);
+            flags.net_encode(writer).await?;
+            min.net_encode(writer).await?;
+            max.net_encode(writer).await?;
+        }
+        Parser::String(kind) => {
+            let kind = match kind {
+                StringKind::SingleWord => 0,
+                StringKind::QuotablePhrase => 1,
+                StringKind::GreedyPhrase => 2,
+            };
+            VarInt::from(kind).net_encode(writer).await?;
+        }
+        Parser::Entity {
+            single,
+            players_only,
+        } => {
+            let mut flags = 0;
+            if *single {
+                flags |= SINGLE;
+            }
+            if *players_only {
+                flags |= PLAYERS_ONLY;
+            }
+            flags.net_encode(writer).await?;
+        }
+    }
+    Ok(())
+}
+
+impl NetEncode for Commands {
+    /// Encodes the nodes, then the index of the root, behind the length of the packet
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut data = Vec::new();
+        self.encode_data(&mut data).await?;
+        VarInt::from(data.len() as i32).net_encode(writer).await?;
+        writer.write_all(&data).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ferrumc_codec::enc::NetEncode;
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    use super::{Commands, ARGUMENT, EXECUTABLE, HAS_REDIRECT, HAS_SUGGESTIONS, LITERAL, ROOT};
+    use crate::events::command_events::node::{CommandNode, Parser, StringKind, ASK_SERVER};
+    use crate::utils::impls::packet_impls::NetDecode;
+
+    /// Node read back from the packet
+    #[derive(Debug, PartialEq)]
+    struct DecodedNode {
+        flags: u8,
+        children: Vec<i32>,
+        redirect: Option<i32>,
+        name: Option<String>,
+        parser: Option<i32>,
+        suggestions: Option<String>,
+    }
+
+    async fn varint(cursor: &mut Cursor<Vec<u8>>) -> i32 {
+        VarInt::net_decode(cursor).await.unwrap().get_val()
+    }
+
+    /// Read the nodes and the root index of an encoded packet, skipping the known properties
+    async fn decode(packet: Commands) -> (Vec<DecodedNode>, i32) {
+        let mut bytes = Vec::new();
+        packet.net_encode(&mut bytes).await.unwrap();
+        let mut cursor = Cursor::new(bytes);
+        let length = varint(&mut cursor).await as usize;
+        assert_eq!(length, cursor.get_ref().len() - cursor.position() as usize);
+        assert_eq!(varint(&mut cursor).await, 0x10);
+
+        let mut nodes = Vec::new();
+        for _ in 0..varint(&mut cursor).await {
+            let flags = *u8::net_decode(&mut cursor).await.unwrap();
+            let mut children = Vec::new();
+            for _ in 0..varint(&mut cursor).await {
+                children.push(varint(&mut cursor).await);
+            }
+            let redirect = if flags & HAS_REDIRECT != 0 {
+                Some(varint(&mut cursor).await)
+            } else {
+                None
+            };
+            let name = if flags & 0x03 != ROOT {
+                Some(*String::net_decode(&mut cursor).await.unwrap())
+            } else {
+                None
+            };
+            let mut parser = None;
+            if flags & 0x03 == ARGUMENT {
+                let id = varint(&mut cursor).await;
+                match id {
+                    // Integer bounds
+                    3 => {
+                        let bounds = *u8::net_decode(&mut cursor).await.unwrap();
+                        for _ in 0..bounds.count_ones() {
+                            i32::net_decode(&mut cursor).await.unwrap();
+                        }
+                    }
+                    5 => {
+                        varint(&mut cursor).await;
+                    }
+                    6 => {
+                        u8::net_decode(&mut cursor).await.unwrap();
+                    }
+                    _ => {}
+                }
+                parser = Some(id);
+            }
+            let suggestions = if flags & HAS_SUGGESTIONS != 0 {
+                Some(*String::net_decode(&mut cursor).await.unwrap())
+            } else {
+                None
+            };
+            nodes.push(DecodedNode {
+                flags,
+                children,
+                redirect,
+                name,
+                parser,
+                suggestions,
+            });
+        }
+        let root = varint(&mut cursor).await;
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+        (nodes, root)
+    }
+
+    #[tokio::test]
+    async fn redirects_refer_to_their_target_by_index() {
+        let teleport = CommandNode::literal("teleport").then(
+            CommandNode::argument(
+                "target",
+                Parser::Entity {
+                    single: true,
+                    players_only: false,
+                },
+            )
+            .executes()
+            .then(CommandNode::argument("location", Parser::Vec3).executes()),
+        );
+        let tp = CommandNode::literal("tp").redirect(&["teleport"]);
+        let execute = CommandNode::literal("execute")
+            .then(CommandNode::literal("run").redirect(&[]))
+            .then(CommandNode::literal("missing").redirect(&["nowhere"]));
+        let (nodes, root) = decode(Commands::new(&[execute, teleport, tp])).await;
+
+        assert_eq!(root, 0);
+        assert_eq!(nodes.len(), 8);
+        let index = |name: &str| {
+            nodes
+                .iter()
+                .position(|node| node.name.as_deref() == Some(name))
+                .unwrap() as i32
+        };
+        assert_eq!(nodes[0].flags, ROOT);
+        assert_eq!(
+            nodes[0].children,
+            [index("execute"), index("teleport"), index("tp")]
+        );
+
+        let execute = &nodes[index("execute") as usize];
+        assert_eq!(execute.children, [index("run"), index("missing")]);
+        // Back to the root
+        let run = &nodes[index("run") as usize];
+        assert_eq!(run.flags, LITERAL | HAS_REDIRECT);
+        assert_eq!(run.redirect, Some(0));
+        // An unknown target is left out
+        let missing = &nodes[index("missing") as usize];
+        assert_eq!((missing.flags, missing.redirect), (LITERAL, None));
+
+        let tp = &nodes[index("tp") as usize];
+        assert_eq!(tp.redirect, Some(index("teleport")));
+        assert!(tp.children.is_empty());
+        let target = &nodes[index("target") as usize];
+        assert_eq!(target.flags, ARGUMENT | EXECUTABLE);
+        assert_eq!(target.parser, Some(6));
+        assert_eq!(target.children, [index("location")]);
+        assert_eq!(nodes[index("location") as usize].parser, Some(10));
+    }
+
+    #[tokio::test]
+    async fn parser_properties_follow_the_parser() {
+        let command = CommandNode::literal("give").then(
+            CommandNode::argument(
+                "count",
+                Parser::Integer {
+                    min: Some(1),
+                    max: None,
+                },
+            )
+            .then(
+                CommandNode::argument("reason", Parser::String(StringKind::GreedyPhrase))
+                    .suggests(ASK_SERVER)
+                    .executes(),
+            ),
+        );
+        let mut bytes = Vec::new();
+        Commands::new(std::slice::from_ref(&command))
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+
+        let mut count = vec![ARGUMENT, 1, 3, 5];
+        count.extend_from_slice(b"count");
+        // Integer, with a minimum of 1
+        count.extend_from_slice(&[3, 0x01, 0, 0, 0, 1]);
+        assert!(bytes.windows(count.len()).any(|window| window == count));
+        let mut reason = vec![ARGUMENT | EXECUTABLE | HAS_SUGGESTIONS, 0, 6];
+        reason.extend_from_slice(b"reason");
+        // Greedy string
+        reason.extend_from_slice(&[5, 2, ASK_SERVER.len() as u8]);
+        reason.extend_from_slice(ASK_SERVER.as_bytes());
+        assert!(bytes.windows(reason.len()).any(|window| window == reason));
+
+        let (nodes, _) = decode(Commands::new(&[command])).await;
+        assert_eq!(nodes[3].suggestions.as_deref(), Some(ASK_SERVER));
+    }
+}
@@ -0,0 +1,26 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The disconnect packet is sent by the server to kick a client in the play state.
+/// The reason is a JSON text component.
+#[derive(NetEncode)]
+pub struct Disconnect {
+    #[encode(default = VarInt::from(0x1A))]
+    pub packet_id: VarInt,
+    pub reason: String,
+}
+
+impl Disconnect {
+    /// Disconnect showing `reason`
+    pub fn new(reason: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(reason.into().to_json())
+    }
+
+    /// Disconnect with a plain text reason
+    pub fn from_text(reason: &str) -> Self {
+        Self::new(reason)
+    }
+}
@@ -0,0 +1,30 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::update_objectives::MAX_OBJECTIVE_NAME_LENGTH;
+use crate::utils::chat::truncate;
+
+/// Where a [`DisplayObjective`] shows its objective
+pub const LIST_SLOT: i8 = 0;
+pub const SIDEBAR_SLOT: i8 = 1;
+pub const BELOW_NAME_SLOT: i8 = 2;
+
+/// The display objective packet is sent by the server to show an objective in a slot of the
+/// screen, an empty name clears the slot.
+#[derive(NetEncode)]
+pub struct DisplayObjective {
+    #[encode(default = VarInt::from(0x51))]
+    pub packet_id: VarInt,
+    pub position: i8,
+    pub objective_name: String,
+}
+
+impl DisplayObjective {
+    pub fn new(position: i8, objective_name: &str) -> Self {
+        Self::new_auto(
+            position,
+            truncate(objective_name, MAX_OBJECTIVE_NAME_LENGTH).to_string(),
+        )
+    }
+}
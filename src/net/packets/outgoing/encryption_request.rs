@@ -0,0 +1,30 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The encryption request is sent by online mode servers after the login start, with the public
+/// key the client encrypts the shared secret with, answered with an
+/// [EncryptionResponse](crate::net::packets::incoming::encryption_response::EncryptionResponse).
+#[derive(NetEncode)]
+pub struct EncryptionRequest {
+    #[encode(default = VarInt::from(0x01))]
+    pub packet_id: VarInt,
+    pub server_id: String,
+    pub public_key_length: VarInt,
+    /// DER encoded
+    pub public_key: Vec<u8>,
+    pub verify_token_length: VarInt,
+    pub verify_token: Vec<u8>,
+}
+
+impl EncryptionRequest {
+    pub fn new(server_id: &str, public_key: &[u8], verify_token: &[u8]) -> Self {
+        Self::new_auto(
+            server_id.to_string(),
+            VarInt::new(public_key.len() as i32),
+            public_key.to_vec(),
+            VarInt::new(verify_token.len() as i32),
+            verify_token.to_vec(),
+        )
+    }
+}
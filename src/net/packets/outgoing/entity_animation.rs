@@ -0,0 +1,22 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+pub const SWING_MAIN_ARM: u8 = 0;
+pub const SWING_OFFHAND: u8 = 3;
+
+/// The entity animation packet is sent by the server to play an animation of an entity, e.g. a
+/// player swinging its arm.
+#[derive(NetEncode)]
+pub struct EntityAnimation {
+    #[encode(default = VarInt::from(0x04))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub animation: u8,
+}
+
+impl EntityAnimation {
+    pub fn new(entity_id: i32, animation: u8) -> Self {
+        Self::new_auto(entity_id.into(), animation)
+    }
+}
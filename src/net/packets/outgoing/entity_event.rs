@@ -0,0 +1,28 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Status of the entity event setting the permission level of the player to 0, levels 1 to 4
+/// follow it
+pub const OP_LEVEL_0: i8 = 24;
+
+/// The entity event packet is sent by the server to trigger an effect of an entity, like its
+/// death animation. Sent to a player about itself, it also sets its permission level.
+#[derive(NetEncode)]
+pub struct EntityEvent {
+    #[encode(default = VarInt::from(0x1C))]
+    pub packet_id: VarInt,
+    pub entity_id: i32,
+    pub status: i8,
+}
+
+impl EntityEvent {
+    pub fn new(entity_id: i32, status: i8) -> Self {
+        Self::new_auto(entity_id, status)
+    }
+
+    /// Permission level `level` (0 to 4) of the player `entity_id`, sent to that player
+    pub fn op_level(entity_id: i32, level: u8) -> Self {
+        Self::new(entity_id, OP_LEVEL_0 + level.min(4) as i8)
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+use ferrumc_macros::{Component, NetEncode};
+use tokio::io::AsyncWrite;
+
+use crate::net::packets::outgoing::particle::ParticleData;
+use crate::utils::chat::ChatComponent;
+use crate::utils::encoding::position::Position;
+use crate::utils::encoding::slot::Slot;
+
+/// Index of the flags byte shared by every entity
+pub const FLAGS_INDEX: u8 = 0;
+/// Index of the custom name shown above an entity
+pub const CUSTOM_NAME_INDEX: u8 = 2;
+/// Index of whether the custom name is shown without looking at the entity
+pub const CUSTOM_NAME_VISIBLE_INDEX: u8 = 3;
+/// Index of the pose shared by every entity
+pub const POSE_INDEX: u8 = 6;
+/// Index of the health of a living entity
+pub const HEALTH_INDEX: u8 = 9;
+/// Index of the skin layers a player displays
+pub const SKIN_PARTS_INDEX: u8 = 17;
+/// Index of the main hand of a player, 0 left and 1 right
+pub const MAIN_HAND_INDEX: u8 = 18;
+
+/// Ends the metadata entries
+const END_OF_METADATA: u8 = 0xFF;
+
+/// Value of a metadata entry, with the types of the 1.20.1 entity data serializers
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Byte(u8),
+    VarInt(i32),
+    VarLong(i64),
+    Float(f32),
+    String(String),
+    Chat(ChatComponent),
+    OptionalChat(Option<ChatComponent>),
+    Slot(Slot),
+    Boolean(bool),
+    /// Rotation around the x, y and z axes, in degrees
+    Rotation(f32, f32, f32),
+    Position(Position),
+    OptionalPosition(Option<Position>),
+    /// Down, up, north, south, west or east
+    Direction(i32),
+    OptionalUuid(Option<u128>),
+    BlockState(i32),
+    /// Air when absent
+    OptionalBlockState(Option<i32>),
+    Particle(ParticleData),
+    /// Type, profession and level of a villager
+    VillagerData(i32, i32, i32),
+    OptionalVarInt(Option<i32>),
+    Pose(i32),
+    Vector3(f32, f32, f32),
+    Quaternion(f32, f32, f32, f32),
+}
+
+impl MetadataValue {
+    fn type_id(&self) -> i32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::VarInt(_) => 1,
+            MetadataValue::VarLong(_) => 2,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::String(_) => 4,
+            MetadataValue::Chat(_) => 5,
+            MetadataValue::OptionalChat(_) => 6,
+            MetadataValue::Slot(_) => 7,
+            MetadataValue::Boolean(_) => 8,
+            MetadataValue::Rotation(..) => 9,
+            MetadataValue::Position(_) => 10,
+            MetadataValue::OptionalPosition(_) => 11,
+            MetadataValue::Direction(_) => 12,
+            MetadataValue::OptionalUuid(_) => 13,
+            MetadataValue::BlockState(_) => 14,
+            MetadataValue::OptionalBlockState(_) => 15,
+            MetadataValue::Particle(_) => 17,
+            MetadataValue::VillagerData(..) => 18,
+            MetadataValue::OptionalVarInt(_) => 19,
+            MetadataValue::Pose(_) => 20,
+            MetadataValue::Vector3(..) => 26,
+            MetadataValue::Quaternion(..) => 27,
+        }
+    }
+
+    /// Encodes the value alone, optional values behind whether they are present
+    async fn encode_value<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match self {
+            MetadataValue::Byte(value) => value.net_encode(writer).await,
+            MetadataValue::VarInt(value)
+            | MetadataValue::Direction(value)
+            | MetadataValue::BlockState(value)
+            | MetadataValue::Pose(value) => VarInt::from(*value).net_encode(writer).await,
+            MetadataValue::VarLong(value) => Varlong::from(*value).net_encode(writer).await,
+            MetadataValue::Float(value) => value.net_encode(writer).await,
+            MetadataValue::String(value) => value.net_encode(writer).await,
+            MetadataValue::Chat(value) => value.to_json().net_encode(writer).await,
+            MetadataValue::OptionalChat(value) => {
+                value.is_some().net_encode(writer).await?;
+                value
+                    .as_ref()
+                    .map(ChatComponent::to_json)
+                    .net_encode(writer)
+                    .await
+            }
+            MetadataValue::Slot(value) => value.net_encode(writer).await,
+            MetadataValue::Boolean(value) => value.net_encode(writer).await,
+            MetadataValue::Rotation(x, y, z) | MetadataValue::Vector3(x, y, z) => {
+                x.net_encode(writer).await?;
+                y.net_encode(writer).await?;
+                z.net_encode(writer).await
+            }
+            MetadataValue::Position(value) => value.net_encode(writer).await,
+            MetadataValue::OptionalPosition(value) => {
+                value.is_some().net_encode(writer).await?;
+                value.net_encode(writer).await
+            }
+            MetadataValue::OptionalUuid(value) => {
+                value.is_some().net_encode(writer).await?;
+                value.net_encode(writer).await
+            }
+            // 0 stands for air, and for no block
+            MetadataValue::OptionalBlockState(value) => {
+                VarInt::from(value.unwrap_or(0)).net_encode(writer).await
+            }
+            MetadataValue::Particle(value) => {
+                VarInt::from(value.id()).net_encode(writer).await?;
+                value.net_encode(writer).await
+            }
+            MetadataValue::VillagerData(kind, profession, level) => {
+                VarInt::from(*kind).net_encode(writer).await?;
+                VarInt::from(*profession).net_encode(writer).await?;
+                VarInt::from(*level).net_encode(writer).await
+            }
+            // Shifted by one, 0 is absent
+            MetadataValue::OptionalVarInt(value) => {
+                VarInt::from(value.map_or(0, |value| value + 1))
+                    .net_encode(writer)
+                    .await
+            }
+            MetadataValue::Quaternion(x, y, z, w) => {
+                x.net_encode(writer).await?;
+                y.net_encode(writer).await?;
+                z.net_encode(writer).await?;
+                w.net_encode(writer).await
+            }
+        }
+    }
+}
+
+/// Metadata entry at `index`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataEntry {
+    pub index: u8,
+    pub value: MetadataValue,
+}
+
+impl NetEncode for MetadataEntry {
+    /// Encodes the index, then the type and the value
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.index.net_encode(writer).await?;
+        VarInt::from(self.value.type_id())
+            .net_encode(writer)
+            .await?;
+        self.value.encode_value(writer).await
+    }
+}
+
+/// The set entity metadata packet is sent by the server to update some of the metadata of an
+/// entity, like its flags or pose.
+#[derive(NetEncode)]
+pub struct EntityMetadata {
+    #[encode(default = VarInt::from(0x52))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub entries: Vec<MetadataEntry>,
+    pub end: u8,
+}
+
+impl EntityMetadata {
+    pub fn new(entity_id: i32, entries: Vec<MetadataEntry>) -> Self {
+        Self::new_auto(entity_id.into(), entries, END_OF_METADATA)
+    }
+}
+
+/// Metadata of an entity, remembering which entries changed since they were last sent
+///
+/// The whole metadata is sent to the players the entity spawns for, and only the changed
+/// entries to those already seeing it.
+#[derive(Debug, Default, Clone, Component)]
+pub struct EntityMetadataBuilder {
+    values: BTreeMap<u8, MetadataValue>,
+    dirty: BTreeSet<u8>,
+}
+
+impl EntityMetadataBuilder {
+    /// Set the entry at `index`, it only needs to be sent if its value changed
+    pub fn set(&mut self, index: u8, value: MetadataValue) -> &mut Self {
+        if self.values.get(&index) != Some(&value) {
+            self.values.insert(index, value);
+            self.dirty.insert(index);
+        }
+        self
+    }
+
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.values.get(&index)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Entries changed since the last call, `None` if nothing changed
+    pub fn take_changes(&mut self, entity_id: i32) -> Option<EntityMetadata> {
+        if self.dirty.is_empty() {
+            return None;
+        }
+        let entries = std::mem::take(&mut self.dirty)
+            .into_iter()
+            .map(|index| MetadataEntry {
+                index,
+                value: self.values[&index].clone(),
+            })
+            .collect();
+        Some(EntityMetadata::new(entity_id, entries))
+    }
+
+    /// Every entry, changed or not
+    pub fn full(&self, entity_id: i32) -> EntityMetadata {
+        let entries = self
+            .values
+            .iter()
+            .map(|(&index, value)| MetadataEntry {
+                index,
+                value: value.clone(),
+            })
+            .collect();
+        EntityMetadata::new(entity_id, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{
+        EntityMetadataBuilder, MetadataValue, CUSTOM_NAME_INDEX, FLAGS_INDEX, HEALTH_INDEX,
+        POSE_INDEX,
+    };
+    use crate::utils::chat::ChatComponent;
+
+    #[tokio::test]
+    async fn only_changed_entries_are_sent() {
+        let mut metadata = EntityMetadataBuilder::default();
+        metadata
+            .set(FLAGS_INDEX, MetadataValue::Byte(0))
+            .set(POSE_INDEX, MetadataValue::Pose(0));
+        assert!(metadata.take_changes(1).is_some());
+        assert!(metadata.take_changes(1).is_none());
+
+        // Same value, nothing to send
+        metadata.set(FLAGS_INDEX, MetadataValue::Byte(0));
+        assert!(!metadata.is_dirty());
+
+        metadata
+            .set(POSE_INDEX, MetadataValue::Pose(5))
+            .set(FLAGS_INDEX, MetadataValue::Byte(0x02));
+        let mut bytes = Vec::new();
+        metadata
+            .take_changes(1)
+            .unwrap()
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // By index, each with its type, then the end marker
+        assert_eq!(bytes, [9, 0x52, 1, 0, 0, 0x02, 6, 20, 5, 0xFF]);
+
+        assert_eq!(metadata.full(1).entries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn optional_values_are_prefixed() {
+        let mut metadata = EntityMetadataBuilder::default();
+        metadata
+            .set(
+                CUSTOM_NAME_INDEX,
+                MetadataValue::OptionalChat(Some(ChatComponent::from("Bob"))),
+            )
+            .set(HEALTH_INDEX, MetadataValue::Float(20.0));
+        let mut bytes = Vec::new();
+        metadata
+            .take_changes(1)
+            .unwrap()
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+
+        let mut expected = vec![2, 6, 1, 14];
+        expected.extend_from_slice(br#"{"text":"Bob"}"#);
+        expected.extend_from_slice(&[9, 3]);
+        expected.extend_from_slice(&20f32.to_be_bytes());
+        expected.push(0xFF);
+        assert_eq!(bytes[3..], expected);
+    }
+}
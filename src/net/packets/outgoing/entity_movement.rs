@@ -0,0 +1,216 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use tokio::io::AsyncWrite;
+
+use crate::utils::components::last_broadcast::LastBroadcast;
+
+/// The update entity position packet is sent by the server when an entity moves less than 8
+/// blocks, relative to its last position in 1/4096 of a block.
+#[derive(NetEncode)]
+pub struct UpdateEntityPosition {
+    #[encode(default = VarInt::from(0x2B))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub on_ground: bool,
+}
+
+/// The update entity position and rotation packet is sent by the server when an entity moves
+/// less than 8 blocks and turns.
+#[derive(NetEncode)]
+pub struct UpdateEntityPositionAndRotation {
+    #[encode(default = VarInt::from(0x2C))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+/// The update entity rotation packet is sent by the server when an entity turns without moving.
+#[derive(NetEncode)]
+pub struct UpdateEntityRotation {
+    #[encode(default = VarInt::from(0x2D))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+/// The teleport entity packet is sent by the server when an entity moves too far for the
+/// relative moves.
+#[derive(NetEncode)]
+pub struct TeleportEntity {
+    #[encode(default = VarInt::from(0x68))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+/// Smallest of the movement packets taking an entity from one broadcast position to the next
+pub enum EntityMovement {
+    Position(UpdateEntityPosition),
+    PositionAndRotation(UpdateEntityPositionAndRotation),
+    Rotation(UpdateEntityRotation),
+    Teleport(TeleportEntity),
+}
+
+impl EntityMovement {
+    /// Movement of `entity_id` from `last` to `now`, `None` if it neither moved nor turned
+    ///
+    /// Deltas that don't fit the relative moves, 8 blocks or more, teleport the entity instead.
+    pub fn between(
+        entity_id: i32,
+        last: &LastBroadcast,
+        now: &LastBroadcast,
+        on_ground: bool,
+    ) -> Option<Self> {
+        let moved = (now.x, now.y, now.z) != (last.x, last.y, last.z);
+        let turned = (now.yaw, now.pitch) != (last.yaw, last.pitch);
+        if !moved && !turned {
+            return None;
+        }
+        let deltas = (
+            i16::try_from(now.x - last.x),
+            i16::try_from(now.y - last.y),
+            i16::try_from(now.z - last.z),
+        );
+        let movement = match deltas {
+            _ if !moved => Self::Rotation(UpdateEntityRotation::new_auto(
+                entity_id.into(),
+                now.yaw,
+                now.pitch,
+                on_ground,
+            )),
+            (Ok(delta_x), Ok(delta_y), Ok(delta_z)) if turned => {
+                Self::PositionAndRotation(UpdateEntityPositionAndRotation::new_auto(
+                    entity_id.into(),
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    now.yaw,
+                    now.pitch,
+                    on_ground,
+                ))
+            }
+            (Ok(delta_x), Ok(delta_y), Ok(delta_z)) => {
+                Self::Position(UpdateEntityPosition::new_auto(
+                    entity_id.into(),
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    on_ground,
+                ))
+            }
+            _ => {
+                let (x, y, z) = now.position();
+                Self::Teleport(TeleportEntity::new_auto(
+                    entity_id.into(),
+                    x,
+                    y,
+                    z,
+                    now.yaw,
+                    now.pitch,
+                    on_ground,
+                ))
+            }
+        };
+        Some(movement)
+    }
+}
+
+impl NetEncode for EntityMovement {
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match self {
+            Self::Position(packet) => packet.net_encode(writer).await,
+            Self::PositionAndRotation(packet) => packet.net_encode(writer).await,
+            Self::Rotation(packet) => packet.net_encode(writer).await,
+            Self::Teleport(packet) => packet.net_encode(writer).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntityMovement;
+    use crate::utils::components::last_broadcast::LastBroadcast;
+
+    fn between(last: &LastBroadcast, now: &LastBroadcast) -> Option<EntityMovement> {
+        EntityMovement::between(1, last, now, true)
+    }
+
+    #[test]
+    fn the_smallest_packet_is_picked() {
+        let start = LastBroadcast::new((0.0, 64.0, 0.0), 0.0, 0.0);
+        assert!(between(&start, &start).is_none());
+
+        let turned = LastBroadcast::new((0.0, 64.0, 0.0), 90.0, 0.0);
+        assert!(matches!(
+            between(&start, &turned),
+            Some(EntityMovement::Rotation(packet)) if packet.yaw == 64
+        ));
+
+        let moved = LastBroadcast::new((1.0, 63.5, -0.25), 0.0, 0.0);
+        let Some(EntityMovement::Position(packet)) = between(&start, &moved) else {
+            panic!("expected a relative move");
+        };
+        assert_eq!(
+            (packet.delta_x, packet.delta_y, packet.delta_z),
+            (4096, -2048, -1024)
+        );
+
+        let both = LastBroadcast::new((1.0, 64.0, 0.0), 90.0, 0.0);
+        assert!(matches!(
+            between(&start, &both),
+            Some(EntityMovement::PositionAndRotation(_))
+        ));
+    }
+
+    #[test]
+    fn moves_of_8_blocks_or_more_teleport() {
+        let start = LastBroadcast::new((0.0, 64.0, 0.0), 0.0, 0.0);
+        let near = LastBroadcast::new((7.99, 64.0, 0.0), 0.0, 0.0);
+        assert!(matches!(
+            between(&start, &near),
+            Some(EntityMovement::Position(_))
+        ));
+
+        let far = LastBroadcast::new((0.0, 64.0, -8.5), 0.0, 0.0);
+        let Some(EntityMovement::Teleport(packet)) = between(&start, &far) else {
+            panic!("expected a teleport");
+        };
+        assert_eq!((packet.x, packet.y, packet.z), (0.0, 64.0, -8.5));
+    }
+
+    #[test]
+    fn coalesced_moves_add_up() {
+        let start = LastBroadcast::new((0.0, 0.0, 0.0), 0.0, 0.0);
+        let first = LastBroadcast::new((0.1, 0.0, 0.0), 0.0, 0.0);
+        let second = LastBroadcast::new((0.2, 0.0, 0.0), 0.0, 0.0);
+
+        let mut total = 0;
+        for (last, now) in [(&start, &first), (&first, &second)] {
+            let Some(EntityMovement::Position(packet)) = between(last, now) else {
+                panic!("expected a relative move");
+            };
+            total += packet.delta_x as i64;
+        }
+        // Rounded once, not once per move
+        assert_eq!(total, second.x);
+    }
+}
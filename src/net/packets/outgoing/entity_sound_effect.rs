@@ -0,0 +1,31 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::registries::{Sound, SoundCategory};
+
+/// The entity sound effect packet is sent by the server to play a sound following an entity.
+#[derive(NetEncode)]
+pub struct EntitySoundEffect {
+    #[encode(default = VarInt::from(0x61))]
+    pub packet_id: VarInt,
+    pub sound: Sound,
+    pub category: SoundCategory,
+    pub entity_id: VarInt,
+    pub volume: f32,
+    pub pitch: f32,
+    pub seed: i64,
+}
+
+impl EntitySoundEffect {
+    pub fn new(
+        sound: Sound,
+        category: SoundCategory,
+        entity_id: i32,
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) -> Self {
+        Self::new_auto(sound, category, entity_id.into(), volume, pitch, seed)
+    }
+}
@@ -0,0 +1,45 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Events of a [`GameEvent`]
+pub const BEGIN_RAINING: u8 = 1;
+pub const END_RAINING: u8 = 2;
+/// Its value is the new game mode
+pub const CHANGE_GAMEMODE: u8 = 3;
+/// Its value is 1 to roll the credits, 0 to respawn at once
+pub const WIN_GAME: u8 = 4;
+/// Its value is the rain level, from 0.0 to 1.0
+pub const RAIN_LEVEL_CHANGE: u8 = 7;
+
+/// The game event packet is sent by the server for changes of the game state of the player, like
+/// its game mode or the weather.
+#[derive(NetEncode)]
+pub struct GameEvent {
+    #[encode(default = VarInt::from(0x1F))]
+    pub packet_id: VarInt,
+    pub event: u8,
+    pub value: f32,
+}
+
+impl GameEvent {
+    pub fn change_gamemode(gamemode: u8) -> Self {
+        Self::new_auto(CHANGE_GAMEMODE, gamemode as f32)
+    }
+
+    pub fn begin_raining() -> Self {
+        Self::new_auto(BEGIN_RAINING, 0.0)
+    }
+
+    pub fn end_raining() -> Self {
+        Self::new_auto(END_RAINING, 0.0)
+    }
+
+    pub fn win_game(roll_credits: bool) -> Self {
+        Self::new_auto(WIN_GAME, if roll_credits { 1.0 } else { 0.0 })
+    }
+
+    pub fn rain_level(level: f32) -> Self {
+        Self::new_auto(RAIN_LEVEL_CHANGE, level)
+    }
+}
@@ -0,0 +1,46 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// The initialize world border packet is sent by the server to set every property of the
+/// border, on join and when the player changes dimension.
+#[derive(NetEncode)]
+pub struct InitializeWorldBorder {
+    #[encode(default = VarInt::from(0x22))]
+    pub packet_id: VarInt,
+    pub x: f64,
+    pub z: f64,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// Time left to reach the new diameter, in milliseconds
+    pub speed: Varlong,
+    /// Farthest coordinate nether portals teleport to
+    pub portal_teleport_boundary: VarInt,
+    pub warning_blocks: VarInt,
+    /// In seconds
+    pub warning_time: VarInt,
+}
+
+impl InitializeWorldBorder {
+    pub fn new(
+        (x, z): (f64, f64),
+        old_diameter: f64,
+        new_diameter: f64,
+        speed: i64,
+        portal_teleport_boundary: i32,
+        warning_blocks: i32,
+        warning_time: i32,
+    ) -> Self {
+        Self::new_auto(
+            x,
+            z,
+            old_diameter,
+            new_diameter,
+            Varlong::from(speed),
+            VarInt::from(portal_teleport_boundary),
+            VarInt::from(warning_blocks),
+            VarInt::from(warning_time),
+        )
+    }
+}
@@ -2,6 +2,8 @@ use ferrumc_codec::network_types::varint::VarInt;
 
 use ferrumc_macros::NetEncode;
 
+use crate::utils::chat::ChatComponent;
+
 /// The login disconnect packet is sent by the server to the client to disconnect the client.
 /// Used to cancel the login process.
 #[derive(NetEncode)]
@@ -10,3 +12,15 @@ pub struct LoginDisconnect {
     pub packet_id: VarInt,
     pub reason: String,
 }
+
+impl LoginDisconnect {
+    /// Disconnect showing `reason`
+    pub fn new(reason: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(reason.into().to_json())
+    }
+
+    /// Disconnect with a plain text reason
+    pub fn from_text(reason: &str) -> Self {
+        Self::new(reason)
+    }
+}
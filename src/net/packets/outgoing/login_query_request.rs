@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The login plugin request is sent by the server during login to ask the client for a custom
+/// payload on a namespaced channel, answered with a
+/// [LoginQueryResponse](crate::net::packets::incoming::login_query_response::LoginQueryResponse).
+#[derive(NetEncode)]
+pub struct LoginQueryRequest {
+    #[encode(default = VarInt::from(0x04))]
+    pub packet_id: VarInt,
+    pub message_id: VarInt,
+    pub channel: String,
+    pub data: Vec<u8>,
+}
+
+impl LoginQueryRequest {
+    pub fn new(message_id: i32, channel: impl Into<String>, data: Vec<u8>) -> Self {
+        Self::new_auto(VarInt::from(message_id), channel.into(), data)
+    }
+}
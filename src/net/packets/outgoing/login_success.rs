@@ -2,6 +2,8 @@ use ferrumc_codec::network_types::varint::VarInt;
 
 use ferrumc_macros::NetEncode;
 
+use crate::utils::components::profile::ProfileProperty;
+
 /// Sent by the server to the client to start the play state.
 #[derive(NetEncode)]
 pub struct LoginSuccess {
@@ -9,9 +11,7 @@ pub struct LoginSuccess {
     pub packet_id: VarInt,
     pub uuid: Vec<u8>,
     pub username: String,
-    // Just set this to 0
     pub property_count: VarInt,
-    // TODO: Figure out how what in the everloving fuck this is
     pub properties: Vec<Property>,
 }
 
@@ -21,5 +21,26 @@ pub struct Property {
     pub value: String,
     pub is_signed: bool,
     // Only if is_signed is true
-    pub signature: String,
+    pub signature: Option<String>,
+}
+
+impl LoginSuccess {
+    /// Login success with the profile of the player
+    pub fn new(uuid: u128, username: String, properties: &[ProfileProperty]) -> Self {
+        let properties: Vec<Property> = properties
+            .iter()
+            .map(|property| Property {
+                name: property.name.clone(),
+                value: property.value.clone(),
+                is_signed: property.signature.is_some(),
+                signature: property.signature.clone(),
+            })
+            .collect();
+        Self::new_auto(
+            uuid.to_be_bytes().to_vec(),
+            username,
+            VarInt::new(properties.len() as i32),
+            properties,
+        )
+    }
 }
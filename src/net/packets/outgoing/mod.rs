@@ -1,11 +1,69 @@
-pub mod chunk_and_light_data;
+pub mod acknowledge_block_change;
+pub mod award_statistics;
+pub mod block_entity_data;
+pub mod block_update;
+pub mod boss_bar;
+pub mod change_difficulty;
+pub mod chunk_data;
+pub mod clear_titles;
+pub mod close_container;
+pub mod command_suggestions_response;
+pub mod commands;
 pub mod default_spawn_position;
+pub mod disconnect;
+pub mod display_objective;
+pub mod encryption_request;
+pub mod entity_animation;
+pub mod entity_event;
+pub mod entity_metadata;
+pub mod entity_movement;
+pub mod entity_sound_effect;
+pub mod game_event;
+pub mod initialize_world_border;
 pub mod keep_alive;
 pub mod login_disconnect;
 pub mod login_play;
 pub mod login_plugin_request;
+pub mod login_query_request;
 pub mod login_success;
+pub mod open_screen;
+pub mod particle;
 pub mod ping;
+pub mod play_ping;
+pub mod player_abilities;
+pub mod player_chat_message;
+pub mod player_info;
+pub mod remove_entities;
+pub mod resource_pack;
+pub mod respawn;
+pub mod section_blocks_update;
+pub mod set_action_bar_text;
+pub mod set_block_destroy_stage;
+pub mod set_border_center;
+pub mod set_border_lerp_size;
+pub mod set_border_size;
+pub mod set_border_warning_delay;
+pub mod set_border_warning_distance;
 pub mod set_center_chunk;
+pub mod set_compression;
+pub mod set_container_content;
+pub mod set_container_slot;
+pub mod set_equipment;
+pub mod set_head_rotation;
+pub mod set_held_item;
+pub mod set_subtitle_text;
+pub mod set_tab_list_header_footer;
+pub mod set_title_animation_times;
+pub mod set_title_text;
+pub mod sound_effect;
+pub mod spawn_entity;
+pub mod spawn_player;
 pub mod status;
 pub mod synchronize_player_position;
+pub mod system_chat_message;
+pub mod tag_query_response;
+pub mod unload_chunk;
+pub mod update_objectives;
+pub mod update_score;
+pub mod update_teams;
+pub mod update_time;
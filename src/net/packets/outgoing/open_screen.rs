@@ -0,0 +1,52 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+use crate::utils::components::open_window::WindowType;
+
+/// The open screen packet is sent by the server to open a window over the player inventory, like
+/// a chest or a crafting table. Its slots follow in a set container content packet.
+#[derive(NetEncode)]
+pub struct OpenScreen {
+    #[encode(default = VarInt::from(0x30))]
+    pub packet_id: VarInt,
+    pub window_id: VarInt,
+    /// Id of the window type in the `menu` registry
+    pub window_type: VarInt,
+    /// JSON text component
+    pub title: String,
+}
+
+impl OpenScreen {
+    pub fn new(window_id: u8, window_type: WindowType, title: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(
+            VarInt::from(window_id as i32),
+            VarInt::from(window_type.id()),
+            title.into().to_json(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::OpenScreen;
+    use crate::utils::components::open_window::WindowType;
+
+    #[tokio::test]
+    async fn title_follows_the_window_type() {
+        let mut bytes = Vec::new();
+        OpenScreen::new(3, WindowType::Crafting, "Craft")
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert_eq!(bytes[1..4], [0x30, 3, 11]);
+        // Length of the JSON, then the JSON itself
+        let title = &bytes[5..];
+        assert_eq!(title.len(), bytes[4] as usize);
+        assert!(std::str::from_utf8(title).unwrap().contains("Craft"));
+        assert_eq!(bytes[0] as usize, bytes.len() - 1);
+    }
+}
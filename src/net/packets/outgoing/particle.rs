@@ -0,0 +1,180 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::slot::Slot;
+use crate::utils::registries::{
+    ParticleType, BLOCK_MARKER_PARTICLE, BLOCK_PARTICLE, DUST_PARTICLE, FALLING_DUST_PARTICLE,
+    ITEM_PARTICLE,
+};
+
+/// Particle shown by a [`Particle`] packet, with its data
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParticleData {
+    Simple(ParticleType),
+    /// Breaking block, by block state
+    Block(i32),
+    /// Barrier-like marker, by block state
+    BlockMarker(i32),
+    /// Colored dust, channels between 0.0 and 1.0, scale between 0.01 and 4.0
+    Dust {
+        red: f32,
+        green: f32,
+        blue: f32,
+        scale: f32,
+    },
+    /// Dust falling from a block, by block state
+    FallingDust(i32),
+    /// Breaking item
+    Item(Slot),
+}
+
+impl ParticleData {
+    /// Id of the particle in the particle type registry
+    pub fn id(&self) -> i32 {
+        match self {
+            ParticleData::Simple(particle) => *particle as i32,
+            ParticleData::Block(_) => BLOCK_PARTICLE,
+            ParticleData::BlockMarker(_) => BLOCK_MARKER_PARTICLE,
+            ParticleData::Dust { .. } => DUST_PARTICLE,
+            ParticleData::FallingDust(_) => FALLING_DUST_PARTICLE,
+            ParticleData::Item(_) => ITEM_PARTICLE,
+        }
+    }
+}
+
+impl NetEncode for ParticleData {
+    /// Encodes the data of the particle only, its id comes first in the packet
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match self {
+            ParticleData::Simple(_) => Ok(()),
+            ParticleData::Block(state_id)
+            | ParticleData::BlockMarker(state_id)
+            | ParticleData::FallingDust(state_id) => {
+                VarInt::from(*state_id).net_encode(writer).await
+            }
+            ParticleData::Dust {
+                red,
+                green,
+                blue,
+                scale,
+            } => {
+                red.net_encode(writer).await?;
+                green.net_encode(writer).await?;
+                blue.net_encode(writer).await?;
+                scale.net_encode(writer).await
+            }
+            ParticleData::Item(item) => item.net_encode(writer).await,
+        }
+    }
+}
+
+/// The particle packet is sent by the server to show particles around a position.
+#[derive(NetEncode)]
+pub struct Particle {
+    #[encode(default = VarInt::from(0x26))]
+    pub packet_id: VarInt,
+    pub particle_id: VarInt,
+    /// Shown up to 65536 blocks away instead of 256
+    pub long_distance: bool,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// Spread of the particles on each axis, scaled by a gaussian
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub offset_z: f32,
+    pub max_speed: f32,
+    pub count: i32,
+    pub data: ParticleData,
+}
+
+impl Particle {
+    /// `count` particles exactly at `x, y, z`
+    pub fn new(data: ParticleData, (x, y, z): (f64, f64, f64), count: i32) -> Self {
+        Self::new_auto(
+            VarInt::from(data.id()),
+            false,
+            x,
+            y,
+            z,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            count,
+            data,
+        )
+    }
+
+    /// Spread the particles by `offset` on each axis, moving at up to `max_speed`
+    pub fn with_spread(mut self, (x, y, z): (f32, f32, f32), max_speed: f32) -> Self {
+        (self.offset_x, self.offset_y, self.offset_z) = (x, y, z);
+        self.max_speed = max_speed;
+        self
+    }
+
+    pub fn long_distance(mut self) -> Self {
+        self.long_distance = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{Particle, ParticleData};
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+    use crate::utils::registries::ParticleType;
+
+    async fn encode(data: ParticleData) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        Particle::new(data, (0.0, 0.0, 0.0), 1)
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn data_follows_the_particle_fields() {
+        // Length, packet id, particle id, long distance, position, spread, speed and count
+        let fields = 1 + 1 + 1 + 1 + 3 * 8 + 3 * 4 + 4 + 4;
+
+        let bytes = encode(ParticleData::Simple(ParticleType::Heart)).await;
+        assert_eq!(bytes.len(), fields);
+        assert_eq!(bytes[2], 38);
+
+        let bytes = encode(ParticleData::Block(300)).await;
+        assert_eq!(bytes[2], 2);
+        // 300 as a VarInt
+        assert_eq!(bytes[fields..], [0xAC, 0x02]);
+
+        let bytes = encode(ParticleData::Dust {
+            red: 1.0,
+            green: 0.0,
+            blue: 0.5,
+            scale: 2.0,
+        })
+        .await;
+        assert_eq!(bytes[2], 14);
+        let mut expected = Vec::new();
+        for value in [1f32, 0.0, 0.5, 2.0] {
+            expected.extend_from_slice(&value.to_be_bytes());
+        }
+        assert_eq!(bytes[fields..], expected);
+
+        let bytes = encode(ParticleData::Item(Slot {
+            item: Some(ItemStack::new(1, 1)),
+        }))
+        .await;
+        assert_eq!(bytes[2], 40);
+        assert_eq!(bytes[fields..], [1, 1, 1, 0]);
+    }
+}
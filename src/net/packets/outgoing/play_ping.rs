@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The play ping packet is sent by the server to measure the latency, the client answers with a
+/// Pong carrying the same id.
+#[derive(NetEncode)]
+pub struct PlayPing {
+    #[encode(default = VarInt::from(0x32))]
+    pub packet_id: VarInt,
+    pub id: i32,
+}
+
+impl PlayPing {
+    pub fn new(id: i32) -> Self {
+        Self::new_auto(id)
+    }
+}
@@ -0,0 +1,27 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::abilities::Abilities;
+
+/// The player abilities packet is sent by the server to tell the client what its player may do:
+/// fly, break blocks instantly... and how fast it moves.
+#[derive(NetEncode)]
+pub struct PlayerAbilities {
+    #[encode(default = VarInt::from(0x34))]
+    pub packet_id: VarInt,
+    pub flags: u8,
+    pub flying_speed: f32,
+    /// Field of view modifier, the walking speed
+    pub walking_speed: f32,
+}
+
+impl PlayerAbilities {
+    pub fn new(abilities: &Abilities) -> Self {
+        Self::new_auto(
+            abilities.flags,
+            abilities.flying_speed,
+            abilities.walking_speed,
+        )
+    }
+}
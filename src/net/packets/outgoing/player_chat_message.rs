@@ -0,0 +1,108 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// Chat type of the registry codec decorating messages like vanilla chat, `<name> message`
+pub const CHAT_TYPE_CHAT: i32 = 0;
+/// The message is shown as is, not filtered
+const PASS_THROUGH: i32 = 0;
+
+/// The player chat message packet is sent by the server to show a chat message of a player,
+/// attributed to them so that the client can hide the messages of the players it blocked.
+///
+/// The server doesn't sign messages, only the unsigned variant is sent: no signature and no
+/// previous messages. The client shows them as not secure.
+#[derive(NetEncode)]
+pub struct PlayerChatMessage {
+    #[encode(default = VarInt::from(0x35))]
+    pub packet_id: VarInt,
+    pub sender: u128,
+    /// Number of messages the sender sent before this one
+    pub index: VarInt,
+    pub has_signature: bool,
+    pub message: String,
+    /// When the message was sent, in milliseconds since the Unix epoch
+    pub timestamp: i64,
+    pub salt: i64,
+    pub previous_messages: VarInt,
+    pub has_unsigned_content: bool,
+    /// JSON text component shown instead of the message
+    pub unsigned_content: Option<String>,
+    pub filter_type: VarInt,
+    pub chat_type: VarInt,
+    /// JSON text component of the sender
+    pub sender_name: String,
+    pub has_target_name: bool,
+    pub target_name: Option<String>,
+}
+
+impl PlayerChatMessage {
+    /// Unsigned `message` of `sender`, decorated by the chat type `chat_type`
+    pub fn unsigned(
+        sender: u128,
+        index: i32,
+        message: String,
+        timestamp: i64,
+        chat_type: i32,
+        sender_name: &ChatComponent,
+    ) -> Self {
+        Self::new_auto(
+            sender,
+            index.into(),
+            false,
+            message,
+            timestamp,
+            0,
+            VarInt::new(0),
+            false,
+            None,
+            VarInt::new(PASS_THROUGH),
+            chat_type.into(),
+            sender_name.to_json(),
+            false,
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{PlayerChatMessage, CHAT_TYPE_CHAT};
+    use crate::utils::chat::ChatComponent;
+
+    #[tokio::test]
+    async fn unsigned_messages_have_no_signature() {
+        let mut bytes = Vec::new();
+        PlayerChatMessage::unsigned(
+            7,
+            3,
+            "hi".to_string(),
+            1000,
+            CHAT_TYPE_CHAT,
+            &ChatComponent::from("Bob"),
+        )
+        .net_encode(&mut bytes)
+        .await
+        .unwrap();
+
+        let mut expected = vec![0x35];
+        expected.extend_from_slice(&7u128.to_be_bytes());
+        // Index, no signature, the message
+        expected.extend_from_slice(&[3, 0, 2]);
+        expected.extend_from_slice(b"hi");
+        expected.extend_from_slice(&1000i64.to_be_bytes());
+        expected.extend_from_slice(&0i64.to_be_bytes());
+        // No previous messages, no unsigned content, not filtered, the chat type
+        expected.extend_from_slice(&[0, 0, 0, 0]);
+        let name = br#"{"text":"Bob"}"#;
+        expected.push(name.len() as u8);
+        expected.extend_from_slice(name);
+        expected.push(0);
+        assert_eq!(bytes[0] as usize, expected.len());
+        assert_eq!(bytes[1..], expected);
+    }
+}
@@ -0,0 +1,192 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::net::packets::outgoing::login_success::Property;
+use crate::utils::components::profile::ProfileProperty;
+
+/// Actions of a [`PlayerInfoUpdate`], the fields of the entries they send
+pub const ADD_PLAYER: u8 = 0x01;
+pub const UPDATE_GAMEMODE: u8 = 0x04;
+pub const UPDATE_LISTED: u8 = 0x08;
+pub const UPDATE_LATENCY: u8 = 0x10;
+pub const UPDATE_DISPLAY_NAME: u8 = 0x20;
+
+/// Player of a [`PlayerInfoUpdate`], only the fields of its actions are sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerInfoEntry {
+    pub uuid: u128,
+    pub name: String,
+    /// Properties of the profile, the skin is only shown if their signatures are kept as is
+    pub properties: Vec<ProfileProperty>,
+    pub gamemode: u8,
+    /// Shown in the tab list
+    pub listed: bool,
+    /// Round trip time, in milliseconds
+    pub latency: i32,
+    /// JSON text component shown instead of the name
+    pub display_name: Option<String>,
+}
+
+/// The player info update packet is sent by the server to add players to the tab list, or to
+/// update some of their info.
+pub struct PlayerInfoUpdate {
+    /// Which fields of the entries are sent
+    pub actions: u8,
+    pub entries: Vec<PlayerInfoEntry>,
+}
+
+impl PlayerInfoUpdate {
+    const PACKET_ID: i32 = 0x3A;
+
+    pub fn new(actions: u8, entries: Vec<PlayerInfoEntry>) -> Self {
+        Self { actions, entries }
+    }
+
+    /// Packet id and fields, without the length
+    async fn encode_data(&self, writer: &mut Vec<u8>) -> ferrumc_codec::Result<()> {
+        VarInt::from(Self::PACKET_ID).net_encode(writer).await?;
+        self.actions.net_encode(writer).await?;
+        VarInt::from(self.entries.len() as i32)
+            .net_encode(writer)
+            .await?;
+        for entry in &self.entries {
+            entry.uuid.net_encode(writer).await?;
+            if self.actions & ADD_PLAYER != 0 {
+                entry.name.net_encode(writer).await?;
+                VarInt::from(entry.properties.len() as i32)
+                    .net_encode(writer)
+                    .await?;
+                for property in &entry.properties {
+                    Property {
+                        name: property.name.clone(),
+                        value: property.value.clone(),
+                        is_signed: property.signature.is_some(),
+                        signature: property.signature.clone(),
+                    }
+                    .net_encode(writer)
+                    .await?;
+                }
+            }
+            if self.actions & UPDATE_GAMEMODE != 0 {
+                VarInt::from(entry.gamemode as i32)
+                    .net_encode(writer)
+                    .await?;
+            }
+            if self.actions & UPDATE_LISTED != 0 {
+                entry.listed.net_encode(writer).await?;
+            }
+            if self.actions & UPDATE_LATENCY != 0 {
+                VarInt::from(entry.latency).net_encode(writer).await?;
+            }
+            if self.actions & UPDATE_DISPLAY_NAME != 0 {
+                entry.display_name.is_some().net_encode(writer).await?;
+                entry.display_name.net_encode(writer).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NetEncode for PlayerInfoUpdate {
+    /// Encodes the actions, then the fields of each entry in the order of their actions, behind
+    /// the length of the packet
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut data = Vec::new();
+        self.encode_data(&mut data).await?;
+        VarInt::from(data.len() as i32).net_encode(writer).await?;
+        writer.write_all(&data).await?;
+        Ok(())
+    }
+}
+
+/// The player info remove packet is sent by the server to remove players from the tab list.
+#[derive(NetEncode)]
+pub struct PlayerInfoRemove {
+    #[encode(default = VarInt::from(0x39))]
+    pub packet_id: VarInt,
+    pub count: VarInt,
+    pub uuids: Vec<u128>,
+}
+
+impl PlayerInfoRemove {
+    pub fn new(uuids: Vec<u128>) -> Self {
+        Self::new_auto(VarInt::from(uuids.len() as i32), uuids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{
+        PlayerInfoEntry, PlayerInfoRemove, PlayerInfoUpdate, ADD_PLAYER, UPDATE_LATENCY,
+        UPDATE_LISTED,
+    };
+    use crate::utils::components::profile::ProfileProperty;
+
+    fn entry() -> PlayerInfoEntry {
+        PlayerInfoEntry {
+            uuid: 1,
+            name: "Steve".to_string(),
+            properties: vec![ProfileProperty {
+                name: "textures".to_string(),
+                value: "e30=".to_string(),
+                signature: Some("c2ln".to_string()),
+            }],
+            gamemode: 1,
+            listed: true,
+            latency: 200,
+            display_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn only_the_fields_of_the_actions_are_sent() {
+        let mut bytes = Vec::new();
+        PlayerInfoUpdate::new(ADD_PLAYER | UPDATE_LISTED, vec![entry()])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x3A, 0x09, 0x01];
+        expected.extend_from_slice(&1u128.to_be_bytes());
+        expected.push(5);
+        expected.extend_from_slice(b"Steve");
+        // The signed skin, forwarded as is
+        expected.extend_from_slice(&[1, 8]);
+        expected.extend_from_slice(b"textures");
+        expected.push(4);
+        expected.extend_from_slice(b"e30=");
+        expected.extend_from_slice(&[1, 4]);
+        expected.extend_from_slice(b"c2ln");
+        // Listed
+        expected.push(1);
+        assert_eq!(bytes[0] as usize, expected.len());
+        assert_eq!(bytes[1..], expected);
+
+        let mut bytes = Vec::new();
+        PlayerInfoUpdate::new(UPDATE_LATENCY, vec![entry()])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // 200 as a VarInt
+        assert_eq!(bytes[20..], [0xC8, 0x01]);
+    }
+
+    #[tokio::test]
+    async fn removed_players_are_listed_by_uuid() {
+        let mut bytes = Vec::new();
+        PlayerInfoRemove::new(vec![7, 8])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert_eq!(bytes.len(), 3 + 2 * 16);
+        assert_eq!(bytes[..3], [34, 0x39, 0x02]);
+        assert_eq!(bytes[3..19], 7u128.to_be_bytes());
+    }
+}
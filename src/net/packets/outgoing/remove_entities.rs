@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The remove entities packet is sent by the server when entities leave the view of the player.
+#[derive(NetEncode)]
+pub struct RemoveEntities {
+    #[encode(default = VarInt::from(0x3E))]
+    pub packet_id: VarInt,
+    pub count: VarInt,
+    pub entity_ids: Vec<VarInt>,
+}
+
+impl RemoveEntities {
+    pub fn new(entity_ids: &[i32]) -> Self {
+        Self::new_auto(
+            VarInt::from(entity_ids.len() as i32),
+            entity_ids.iter().map(|&id| VarInt::from(id)).collect(),
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The resource pack packet is sent by the server to offer a resource pack to the client, which
+/// answers with Resource Pack Response packets as the pack is accepted and loaded.
+#[derive(NetEncode)]
+pub struct ResourcePack {
+    #[encode(default = VarInt::from(0x40))]
+    pub packet_id: VarInt,
+    pub url: String,
+    /// Hex encoded SHA-1 of the pack, lets the client reuse a downloaded copy
+    pub hash: String,
+    /// Disconnects the client if it declines the pack
+    pub forced: bool,
+    pub has_prompt: bool,
+    /// JSON text component shown in the prompt, only encoded when `has_prompt` is set
+    pub prompt: Option<String>,
+}
+
+impl ResourcePack {
+    pub fn new(url: String, hash: String, forced: bool, prompt: Option<&str>) -> Self {
+        Self::new_auto(
+            url,
+            hash,
+            forced,
+            prompt.is_some(),
+            prompt.map(|prompt| serde_json::json!({ "text": prompt }).to_string()),
+        )
+    }
+}
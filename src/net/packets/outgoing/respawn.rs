@@ -0,0 +1,116 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::position::Position;
+use crate::world::dimension::Dimension;
+
+/// Keep the attributes of the player through the respawn
+pub const KEEP_ATTRIBUTES: u8 = 0x01;
+/// Keep the entity metadata of the player through the respawn
+pub const KEEP_METADATA: u8 = 0x02;
+/// Keep everything, as when changing dimension
+pub const KEEP_ALL_DATA: u8 = KEEP_ATTRIBUTES | KEEP_METADATA;
+
+/// The respawn packet is sent by the server to respawn a player, or to move it to another
+/// dimension. The client recreates its player entity and drops its world, chunks included.
+///
+/// The dimension fields are those of the login play packet.
+#[derive(NetEncode)]
+pub struct Respawn {
+    #[encode(default = VarInt::from(0x41))]
+    pub packet_id: VarInt,
+    /// Dimension type of the registry codec
+    pub dimension_type: String,
+    pub dimension_name: String,
+    /// First 8 bytes of the SHA-256 of the seed, for biome noise
+    pub seed_hash: i64,
+    pub gamemode: u8,
+    /// -1 for none
+    pub previous_gamemode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    /// Which data the client keeps, see [`KEEP_ATTRIBUTES`] and [`KEEP_METADATA`]
+    pub data_kept: u8,
+    pub has_death_location: bool,
+    pub death_dimension_name: Option<String>,
+    pub death_location: Option<Position>,
+    pub portal_cooldown: VarInt,
+}
+
+impl Respawn {
+    /// Respawn in `dimension`, showing the compass where the player last died
+    pub fn new(
+        dimension: &Dimension,
+        gamemode: u8,
+        previous_gamemode: i8,
+        data_kept: u8,
+        death_location: Option<(&Dimension, Position)>,
+    ) -> Self {
+        let (death_dimension_name, death_location) = death_location
+            .map(|(dimension, location)| (dimension.identifier(), location))
+            .unzip();
+        Self::new_auto(
+            dimension.type_identifier().to_string(),
+            dimension.identifier(),
+            0,
+            gamemode,
+            previous_gamemode,
+            false,
+            false,
+            data_kept,
+            death_location.is_some(),
+            death_dimension_name,
+            death_location,
+            VarInt::new(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{Respawn, KEEP_ALL_DATA};
+    use crate::utils::encoding::position::Position;
+    use crate::world::dimension::Dimension;
+
+    #[tokio::test]
+    async fn death_locations_follow_the_flags() {
+        let mut bytes = Vec::new();
+        Respawn::new(&Dimension::Nether, 0, 1, KEEP_ALL_DATA, None)
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        let name = b"minecraft:the_nether";
+        let mut expected = vec![0x41, name.len() as u8];
+        expected.extend_from_slice(name);
+        expected.push(name.len() as u8);
+        expected.extend_from_slice(name);
+        expected.extend_from_slice(&0i64.to_be_bytes());
+        // Game modes, debug, flat, data kept, no death location and the portal cooldown
+        expected.extend_from_slice(&[0, 1, 0, 0, 0x03, 0, 0]);
+        assert_eq!(bytes[0] as usize, expected.len());
+        assert_eq!(bytes[1..], expected);
+
+        let mut bytes = Vec::new();
+        Respawn::new(
+            &Dimension::from("mining"),
+            0,
+            -1,
+            0,
+            Some((&Dimension::Overworld, Position::new(1, 2, 3))),
+        )
+        .net_encode(&mut bytes)
+        .await
+        .unwrap();
+        // Custom dimensions are of the overworld type
+        assert_eq!(bytes[3..22], *b"minecraft:overworld");
+        let death = b"minecraft:overworld";
+        let start = bytes.len() - 1 - 8 - death.len() - 1 - 1;
+        // Has a death location, its dimension and position
+        assert_eq!(bytes[start..start + 2], [1, death.len() as u8]);
+        assert_eq!(bytes[start + 2..start + 2 + death.len()], *death);
+        assert_eq!(bytes[bytes.len() - 1], 0);
+    }
+}
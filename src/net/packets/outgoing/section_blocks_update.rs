@@ -0,0 +1,104 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// Section at `x`, `y`, `z`, in sections, packed as x in the 22 high bits, then z in 22 bits and
+/// y in the 20 low bits
+pub fn pack_section((x, y, z): (i32, i32, i32)) -> i64 {
+    ((x as i64 & 0x3FFFFF) << 42) | ((z as i64 & 0x3FFFFF) << 20) | (y as i64 & 0xFFFFF)
+}
+
+/// Block of state `state_id` at `x`, `y`, `z` within its section, packed as the state id above
+/// 12 bits of x, then z, then y
+pub fn pack_block(state_id: i32, (x, y, z): (u8, u8, u8)) -> i64 {
+    ((state_id as i64) << 12) | ((x as i64 & 0xF) << 8) | ((z as i64 & 0xF) << 4) | (y as i64 & 0xF)
+}
+
+/// The update section blocks packet is sent by the server to change several blocks of a chunk
+/// section at once.
+#[derive(NetEncode)]
+pub struct SectionBlocksUpdate {
+    #[encode(default = VarInt::from(0x43))]
+    pub packet_id: VarInt,
+    pub section: i64,
+    pub count: VarInt,
+    pub blocks: Vec<Varlong>,
+}
+
+impl SectionBlocksUpdate {
+    /// Blocks of the section `section`, as their position within it and their new state id
+    pub fn new(section: (i32, i32, i32), blocks: &[((u8, u8, u8), i32)]) -> Self {
+        Self::new_auto(
+            pack_section(section),
+            VarInt::from(blocks.len() as i32),
+            blocks
+                .iter()
+                .map(|&(position, state_id)| Varlong::from(pack_block(state_id, position)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{pack_block, pack_section, SectionBlocksUpdate};
+
+    /// Decodes a section position the way the protocol documents it
+    fn unpack_section(section: i64) -> (i32, i32, i32) {
+        (
+            (section >> 42) as i32,
+            (section << 44 >> 44) as i32,
+            (section << 22 >> 42) as i32,
+        )
+    }
+
+    /// Decodes a block record the way the protocol documents it
+    fn unpack_block(block: i64) -> (i32, (u8, u8, u8)) {
+        let position = (block & 0xFFF) as u16;
+        (
+            (block >> 12) as i32,
+            (
+                (position >> 8) as u8,
+                (position & 0xF) as u8,
+                ((position >> 4) & 0xF) as u8,
+            ),
+        )
+    }
+
+    #[test]
+    fn sections_round_trip() {
+        for section in [(0, 0, 0), (1, -4, 2), (-1, 19, -1), (-1875000, -5, 1874999)] {
+            assert_eq!(unpack_section(pack_section(section)), section);
+        }
+        assert_eq!(pack_section((1, 2, 3)), (1 << 42) | (3 << 20) | 2);
+        assert_eq!(pack_section((0, -1, 0)), 0xFFFFF);
+    }
+
+    #[test]
+    fn blocks_pack_the_state_above_x_z_y() {
+        // Stone at 1, 2, 3 within the section
+        assert_eq!(pack_block(1, (1, 2, 3)), 0x1132);
+        assert_eq!(unpack_block(0x1132), (1, (1, 2, 3)));
+        assert_eq!(
+            unpack_block(pack_block(24134, (15, 0, 7))),
+            (24134, (15, 0, 7))
+        );
+    }
+
+    #[tokio::test]
+    async fn blocks_are_varlongs() {
+        let mut bytes = Vec::new();
+        SectionBlocksUpdate::new((0, 0, 0), &[((0, 0, 0), 1), ((0, 1, 0), 0)])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // Length, id and section position
+        assert_eq!(bytes[..2], [13, 0x43]);
+        assert_eq!(bytes[2..10], [0; 8]);
+        // 0x1000 and 0x1
+        assert_eq!(bytes[10..], [0x02, 0x80, 0x20, 0x01]);
+    }
+}
@@ -0,0 +1,20 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The set action bar text packet is sent by the server to show a message above the hotbar.
+#[derive(NetEncode)]
+pub struct SetActionBarText {
+    #[encode(default = VarInt::from(0x46))]
+    pub packet_id: VarInt,
+    /// JSON text component
+    pub text: String,
+}
+
+impl SetActionBarText {
+    pub fn new(text: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(text.into().to_json())
+    }
+}
@@ -0,0 +1,63 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::position::Position;
+
+/// Stage removing the cracks of a block, any stage beyond 9 does
+pub const CLEAR_STAGE: u8 = 0xFF;
+
+/// The set block destroy stage packet is sent by the server to show the cracks of a block being
+/// dug by another player. Each player digs one block at a time, a new stage of the same entity
+/// replaces the cracks it shows.
+#[derive(NetEncode)]
+pub struct SetBlockDestroyStage {
+    #[encode(default = VarInt::from(0x07))]
+    pub packet_id: VarInt,
+    /// Entity digging the block
+    pub entity_id: VarInt,
+    pub location: Position,
+    /// From 0 to 9, see [`CLEAR_STAGE`]
+    pub destroy_stage: u8,
+}
+
+impl SetBlockDestroyStage {
+    pub fn new(entity_id: i32, location: Position, destroy_stage: u8) -> Self {
+        Self::new_auto(entity_id.into(), location, destroy_stage)
+    }
+
+    /// Remove the cracks shown for `entity_id`
+    pub fn clear(entity_id: i32, location: Position) -> Self {
+        Self::new(entity_id, location, CLEAR_STAGE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::SetBlockDestroyStage;
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn stage_follows_the_location() {
+        let mut bytes = Vec::new();
+        SetBlockDestroyStage::new(300, Position::new(1, 64, -1), 4)
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+
+        let location: u64 = (1 << 38) | ((-1i64 as u64 & 0x3FFFFFF) << 12) | 64;
+        let mut expected = vec![12, 0x07, 0xAC, 0x02];
+        expected.extend_from_slice(&location.to_be_bytes());
+        expected.push(4);
+        assert_eq!(bytes, expected);
+
+        let mut bytes = Vec::new();
+        SetBlockDestroyStage::clear(300, Position::new(1, 64, -1))
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert_eq!(bytes[12], 0xFF);
+    }
+}
@@ -0,0 +1,22 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// The set border lerp size packet is sent by the server to grow or shrink the world border
+/// over time.
+#[derive(NetEncode)]
+pub struct SetBorderLerpSize {
+    #[encode(default = VarInt::from(0x48))]
+    pub packet_id: VarInt,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// Time to reach the new diameter, in milliseconds
+    pub speed: Varlong,
+}
+
+impl SetBorderLerpSize {
+    pub fn new(old_diameter: f64, new_diameter: f64, speed: i64) -> Self {
+        Self::new_auto(old_diameter, new_diameter, Varlong::from(speed))
+    }
+}
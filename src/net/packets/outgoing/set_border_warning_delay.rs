@@ -0,0 +1,19 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The set border warning delay packet is sent by the server to warn players of a shrinking
+/// border this many seconds before it reaches them.
+#[derive(NetEncode)]
+pub struct SetBorderWarningDelay {
+    #[encode(default = VarInt::from(0x4A))]
+    pub packet_id: VarInt,
+    /// In seconds
+    pub warning_time: VarInt,
+}
+
+impl SetBorderWarningDelay {
+    pub fn new(warning_time: i32) -> Self {
+        Self::new_auto(warning_time.into())
+    }
+}
@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The set border warning distance packet is sent by the server to tint the screen of players
+/// within this many blocks of the border.
+#[derive(NetEncode)]
+pub struct SetBorderWarningDistance {
+    #[encode(default = VarInt::from(0x4B))]
+    pub packet_id: VarInt,
+    pub warning_blocks: VarInt,
+}
+
+impl SetBorderWarningDistance {
+    pub fn new(warning_blocks: i32) -> Self {
+        Self::new_auto(warning_blocks.into())
+    }
+}
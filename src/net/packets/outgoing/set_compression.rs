@@ -0,0 +1,19 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Sent during login to enable compression, every packet after it uses the compressed framing.
+///
+/// Packets whose data is at least `threshold` bytes long are compressed.
+#[derive(NetEncode)]
+pub struct SetCompression {
+    #[encode(default = VarInt::from(0x03))]
+    pub packet_id: VarInt,
+    pub threshold: VarInt,
+}
+
+impl SetCompression {
+    pub fn new(threshold: usize) -> Self {
+        Self::new_auto(VarInt::new(threshold as i32))
+    }
+}
@@ -0,0 +1,52 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::slot::Slot;
+
+/// The set container content packet is sent by the server to replace every slot of a window,
+/// along with the stack held by the cursor.
+#[derive(NetEncode)]
+pub struct SetContainerContent {
+    #[encode(default = VarInt::from(0x12))]
+    pub packet_id: VarInt,
+    pub window_id: u8,
+    pub state_id: VarInt,
+    pub count: VarInt,
+    pub slots: Vec<Slot>,
+    pub carried: Slot,
+}
+
+impl SetContainerContent {
+    pub fn new(window_id: u8, state_id: i32, slots: Vec<Slot>, carried: Slot) -> Self {
+        Self::new_auto(
+            window_id,
+            state_id.into(),
+            VarInt::new(slots.len() as i32),
+            slots,
+            carried,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::SetContainerContent;
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    #[tokio::test]
+    async fn carried_stack_follows_the_slots() {
+        let stone = Slot {
+            item: Some(ItemStack::new(1, 64)),
+        };
+        let mut bytes = Vec::new();
+        SetContainerContent::new(0, 300, vec![Slot::empty(), stone], Slot::empty())
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // Window, state id as a VarInt, two slots, then the empty cursor
+        assert_eq!(bytes, [11, 0x12, 0, 0xAC, 0x02, 2, 0, 1, 1, 64, 0, 0]);
+    }
+}
@@ -0,0 +1,51 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::slot::Slot;
+
+/// Window and slot of the stack held by the cursor
+pub const CARRIED_WINDOW: i8 = -1;
+pub const CARRIED_SLOT: i16 = -1;
+
+/// The set container slot packet is sent by the server to replace a single slot of a window.
+#[derive(NetEncode)]
+pub struct SetContainerSlot {
+    #[encode(default = VarInt::from(0x14))]
+    pub packet_id: VarInt,
+    pub window_id: i8,
+    pub state_id: VarInt,
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl SetContainerSlot {
+    pub fn new(window_id: i8, state_id: i32, slot: i16, item: Slot) -> Self {
+        Self::new_auto(window_id, state_id.into(), slot, item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{SetContainerSlot, CARRIED_SLOT, CARRIED_WINDOW};
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    #[tokio::test]
+    async fn cursor_is_window_and_slot_minus_one() {
+        let mut bytes = Vec::new();
+        SetContainerSlot::new(
+            CARRIED_WINDOW,
+            7,
+            CARRIED_SLOT,
+            Slot {
+                item: Some(ItemStack::new(1, 3)),
+            },
+        )
+        .net_encode(&mut bytes)
+        .await
+        .unwrap();
+        assert_eq!(bytes, [9, 0x14, 0xFF, 7, 0xFF, 0xFF, 1, 1, 3, 0]);
+    }
+}
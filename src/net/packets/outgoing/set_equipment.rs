@@ -0,0 +1,141 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::inventory::{Inventory, ARMOR_START, OFFHAND_SLOT};
+use crate::utils::encoding::slot::Slot;
+
+pub const MAIN_HAND: u8 = 0;
+pub const OFF_HAND: u8 = 1;
+pub const BOOTS: u8 = 2;
+pub const LEGGINGS: u8 = 3;
+pub const CHESTPLATE: u8 = 4;
+pub const HELMET: u8 = 5;
+
+/// Set on the slot of every entry but the last
+const HAS_NEXT: u8 = 0x80;
+
+#[derive(NetEncode)]
+pub struct Equipment {
+    /// Equipment slot, the top bit is set when another entry follows
+    pub slot: u8,
+    pub item: Slot,
+}
+
+/// The set equipment packet is sent by the server to show the items held or worn by an entity.
+#[derive(NetEncode)]
+pub struct SetEquipment {
+    #[encode(default = VarInt::from(0x55))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub equipment: Vec<Equipment>,
+}
+
+impl SetEquipment {
+    pub fn new(entity_id: i32, equipment: Vec<(u8, Slot)>) -> Self {
+        let last = equipment.len().saturating_sub(1);
+        let equipment = equipment
+            .into_iter()
+            .enumerate()
+            .map(|(index, (slot, item))| Equipment {
+                slot: if index < last { slot | HAS_NEXT } else { slot },
+                item,
+            })
+            .collect();
+        Self::new_auto(entity_id.into(), equipment)
+    }
+
+    /// Items a player holds in hotbar slot `held` and its offhand, and wears from `inventory`,
+    /// `None` when it has nothing to show
+    pub fn of_inventory(entity_id: i32, inventory: &Inventory, held: u8) -> Option<Self> {
+        let armor = [HELMET, CHESTPLATE, LEGGINGS, BOOTS]
+            .into_iter()
+            .enumerate()
+            .map(|(index, slot)| (slot, &inventory.slots[ARMOR_START + index]));
+        let equipment: Vec<(u8, Slot)> = [
+            (MAIN_HAND, inventory.hotbar(held)),
+            (OFF_HAND, &inventory.slots[OFFHAND_SLOT]),
+        ]
+        .into_iter()
+        .chain(armor)
+        .filter(|(_, item)| item.item.is_some())
+        .map(|(slot, item)| (slot, item.clone()))
+        .collect();
+        if equipment.is_empty() {
+            return None;
+        }
+        Some(Self::new(entity_id, equipment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{SetEquipment, CHESTPLATE, HELMET, MAIN_HAND, OFF_HAND};
+    use crate::utils::components::inventory::{Inventory, ARMOR_START, HOTBAR_START};
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    fn stack(item_id: i32, count: i8) -> Slot {
+        Slot {
+            item: Some(ItemStack::new(item_id, count)),
+        }
+    }
+
+    #[tokio::test]
+    async fn single_slot_has_no_continuation_bit() {
+        let mut bytes = Vec::new();
+        SetEquipment::new(7, vec![(MAIN_HAND, stack(1, 64))])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // Slot 0 without the top bit, then stone, 64 of it and no NBT
+        assert_eq!(bytes, [7, 0x55, 7, 0x00, 1, 1, 64, 0]);
+
+        let mut bytes = Vec::new();
+        SetEquipment::new(7, vec![(OFF_HAND, Slot::empty())])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert_eq!(bytes, [4, 0x55, 7, 0x01, 0]);
+    }
+
+    #[tokio::test]
+    async fn every_slot_but_the_last_has_the_continuation_bit() {
+        let mut bytes = Vec::new();
+        SetEquipment::new(
+            7,
+            vec![
+                (MAIN_HAND, stack(818, 1)),
+                (OFF_HAND, Slot::empty()),
+                (HELMET, stack(14, 1)),
+            ],
+        )
+        .net_encode(&mut bytes)
+        .await
+        .unwrap();
+        let mut expected = vec![15, 0x55, 7];
+        // Main hand, a diamond sword
+        expected.extend_from_slice(&[0x80, 1, 0xB2, 0x06, 1, 0]);
+        // Empty offhand
+        expected.extend_from_slice(&[0x81, 0]);
+        // Helmet, the last entry
+        expected.extend_from_slice(&[0x05, 1, 14, 1, 0]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn only_filled_slots_of_the_inventory_are_shown() {
+        let mut inventory = Inventory::default();
+        assert!(SetEquipment::of_inventory(7, &inventory, 0).is_none());
+
+        inventory.slots[HOTBAR_START + 3] = stack(818, 1);
+        inventory.slots[ARMOR_START + 1] = stack(14, 1);
+        let packet = SetEquipment::of_inventory(7, &inventory, 3).unwrap();
+        let slots: Vec<u8> = packet.equipment.iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, [MAIN_HAND | 0x80, CHESTPLATE]);
+        // Another slot is selected, only the armor is left
+        let packet = SetEquipment::of_inventory(7, &inventory, 0).unwrap();
+        assert_eq!(packet.equipment.len(), 1);
+    }
+}
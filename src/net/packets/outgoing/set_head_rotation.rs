@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::spawn_entity::angle;
+
+/// The set head rotation packet is sent by the server to turn the head of an entity, which
+/// isn't sent when spawning players.
+#[derive(NetEncode)]
+pub struct SetHeadRotation {
+    #[encode(default = VarInt::from(0x42))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub head_yaw: u8,
+}
+
+impl SetHeadRotation {
+    pub fn new(entity_id: i32, head_yaw: f32) -> Self {
+        Self::new_auto(entity_id.into(), angle(head_yaw))
+    }
+}
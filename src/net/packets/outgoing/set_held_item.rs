@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The set held item packet is sent by the server to select a hotbar slot of the player.
+#[derive(NetEncode)]
+pub struct SetHeldItemOut {
+    #[encode(default = VarInt::from(0x4D))]
+    pub packet_id: VarInt,
+    /// Hotbar slot, 0 to 8
+    pub slot: i8,
+}
+
+impl SetHeldItemOut {
+    pub fn new(slot: i8) -> Self {
+        Self::new_auto(slot)
+    }
+}
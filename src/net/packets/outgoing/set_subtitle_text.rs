@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The set subtitle text packet is sent by the server to set the subtitle shown with the next
+/// title.
+#[derive(NetEncode)]
+pub struct SetSubtitleText {
+    #[encode(default = VarInt::from(0x5D))]
+    pub packet_id: VarInt,
+    /// JSON text component
+    pub text: String,
+}
+
+impl SetSubtitleText {
+    pub fn new(text: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(text.into().to_json())
+    }
+}
@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The set tab list header and footer packet is sent by the server to show text above and below
+/// the players of the tab list, an empty text hides it.
+#[derive(NetEncode)]
+pub struct SetTabListHeaderFooter {
+    #[encode(default = VarInt::from(0x65))]
+    pub packet_id: VarInt,
+    /// JSON text component
+    pub header: String,
+    /// JSON text component
+    pub footer: String,
+}
+
+impl SetTabListHeaderFooter {
+    pub fn new(header: &ChatComponent, footer: &ChatComponent) -> Self {
+        Self::new_auto(header.to_json(), footer.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::SetTabListHeaderFooter;
+    use crate::utils::chat::ChatComponent;
+
+    #[tokio::test]
+    async fn header_comes_before_the_footer() {
+        let mut bytes = Vec::new();
+        SetTabListHeaderFooter::new(&ChatComponent::from("Top"), &ChatComponent::from(""))
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x65, 14];
+        expected.extend_from_slice(br#"{"text":"Top"}"#);
+        expected.push(11);
+        expected.extend_from_slice(br#"{"text":""}"#);
+        assert_eq!(bytes[0] as usize, expected.len());
+        assert_eq!(bytes[1..], expected);
+    }
+}
@@ -0,0 +1,20 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The set title animation times packet is sent by the server to set how long the next titles
+/// fade in, stay and fade out, in ticks.
+#[derive(NetEncode)]
+pub struct SetTitleAnimationTimes {
+    #[encode(default = VarInt::from(0x60))]
+    pub packet_id: VarInt,
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+impl SetTitleAnimationTimes {
+    pub fn new(fade_in: i32, stay: i32, fade_out: i32) -> Self {
+        Self::new_auto(fade_in, stay, fade_out)
+    }
+}
@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The set title text packet is sent by the server to show a title in the middle of the screen,
+/// with the subtitle and times sent before it.
+#[derive(NetEncode)]
+pub struct SetTitleText {
+    #[encode(default = VarInt::from(0x5F))]
+    pub packet_id: VarInt,
+    /// JSON text component
+    pub text: String,
+}
+
+impl SetTitleText {
+    pub fn new(text: impl Into<ChatComponent>) -> Self {
+        Self::new_auto(text.into().to_json())
+    }
+}
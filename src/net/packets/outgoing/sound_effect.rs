@@ -0,0 +1,96 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::registries::{Sound, SoundCategory};
+
+/// Coordinate in 1/8 of a block, as sent for the position of sounds
+pub fn fixed_point(coordinate: f64) -> i32 {
+    (coordinate * 8.0) as i32
+}
+
+/// The sound effect packet is sent by the server to play a sound at a position.
+#[derive(NetEncode)]
+pub struct SoundEffect {
+    #[encode(default = VarInt::from(0x62))]
+    pub packet_id: VarInt,
+    pub sound: Sound,
+    pub category: SoundCategory,
+    /// Position of the sound, see [`fixed_point`]
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// 1.0 is the normal volume, louder sounds are heard from further away
+    pub volume: f32,
+    /// Between 0.5 and 2.0
+    pub pitch: f32,
+    /// Seed of the variant of the sound picked by the client
+    pub seed: i64,
+}
+
+impl SoundEffect {
+    pub fn new(
+        sound: Sound,
+        category: SoundCategory,
+        (x, y, z): (f64, f64, f64),
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) -> Self {
+        Self::new_auto(
+            sound,
+            category,
+            fixed_point(x),
+            fixed_point(y),
+            fixed_point(z),
+            volume,
+            pitch,
+            seed,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{fixed_point, SoundEffect};
+    use crate::utils::registries::{Sound, SoundCategory};
+
+    #[test]
+    fn positions_are_in_eighths_of_a_block() {
+        assert_eq!(fixed_point(1.0), 8);
+        assert_eq!(fixed_point(-2.5), -20);
+        assert_eq!(fixed_point(0.1), 0);
+    }
+
+    #[tokio::test]
+    async fn sound_effects_follow_the_sound() {
+        let mut bytes = Vec::new();
+        SoundEffect::new(
+            Sound::UiButtonClick,
+            SoundCategory::Player,
+            (0.5, 64.0, -1.0),
+            1.0,
+            2.0,
+            7,
+        )
+        .net_encode(&mut bytes)
+        .await
+        .unwrap();
+
+        let name = b"minecraft:ui.button.click";
+        assert_eq!(bytes[0] as usize, bytes.len() - 1);
+        assert_eq!(bytes[1..4], [0x62, 0, name.len() as u8]);
+        // After the name and its lack of a fixed range
+        let fields = 4 + name.len() + 1;
+        let mut expected = vec![7];
+        expected.extend_from_slice(&4i32.to_be_bytes());
+        expected.extend_from_slice(&512i32.to_be_bytes());
+        expected.extend_from_slice(&(-8i32).to_be_bytes());
+        expected.extend_from_slice(&1f32.to_be_bytes());
+        expected.extend_from_slice(&2f32.to_be_bytes());
+        expected.extend_from_slice(&7i64.to_be_bytes());
+        assert_eq!(bytes[fields..], expected);
+    }
+}
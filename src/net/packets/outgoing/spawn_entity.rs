@@ -0,0 +1,115 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Entity type of players, in the entity type registry
+pub const PLAYER_ENTITY_TYPE: i32 = 122;
+
+/// Fastest velocity sent to clients, in blocks per tick
+const MAX_VELOCITY: f64 = 3.9;
+
+/// Angle in steps of 1/256 of a full turn, as sent for the rotations of entities
+pub fn angle(degrees: f32) -> u8 {
+    (degrees * 256.0 / 360.0).floor() as i32 as u8
+}
+
+/// Velocity in 1/8000 of a block per tick, clamped to what clients accept
+pub fn velocity(blocks_per_tick: f64) -> i16 {
+    (blocks_per_tick.clamp(-MAX_VELOCITY, MAX_VELOCITY) * 8000.0) as i16
+}
+
+/// The spawn entity packet is sent by the server when an entity comes into view of the player.
+///
+/// 1.20.1 clients don't create players from it, see
+/// [`SpawnPlayer`](crate::net::packets::outgoing::spawn_player::SpawnPlayer).
+#[derive(NetEncode)]
+pub struct SpawnEntity {
+    #[encode(default = VarInt::from(0x01))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub uuid: u128,
+    pub entity_type: VarInt,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub pitch: u8,
+    pub yaw: u8,
+    pub head_yaw: u8,
+    /// Meaning depends on the entity type, e.g. the block state of a falling block
+    pub data: VarInt,
+    pub velocity_x: i16,
+    pub velocity_y: i16,
+    pub velocity_z: i16,
+}
+
+impl SpawnEntity {
+    /// Entity at `x`, `y`, `z`, looking along `yaw` and `pitch`, standing still
+    pub fn new(
+        entity_id: i32,
+        uuid: u128,
+        entity_type: i32,
+        (x, y, z): (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+        data: i32,
+    ) -> Self {
+        Self::new_auto(
+            entity_id.into(),
+            uuid,
+            entity_type.into(),
+            x,
+            y,
+            z,
+            angle(pitch),
+            angle(yaw),
+            angle(yaw),
+            data.into(),
+            0,
+            0,
+            0,
+        )
+    }
+
+    /// Start moving at `velocity`, in blocks per tick
+    pub fn with_velocity(mut self, (x, y, z): (f64, f64, f64)) -> Self {
+        self.velocity_x = velocity(x);
+        self.velocity_y = velocity(y);
+        self.velocity_z = velocity(z);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{angle, velocity, SpawnEntity, PLAYER_ENTITY_TYPE};
+
+    #[test]
+    fn rotations_and_velocities_are_scaled() {
+        assert_eq!(angle(0.0), 0);
+        assert_eq!(angle(90.0), 64);
+        assert_eq!(angle(-90.0), 192);
+        assert_eq!(angle(450.0), 64);
+        assert_eq!(velocity(0.5), 4000);
+        assert_eq!(velocity(-10.0), velocity(-3.9));
+    }
+
+    #[tokio::test]
+    async fn fields_follow_the_protocol_layout() {
+        let mut bytes = Vec::new();
+        SpawnEntity::new(5, 9, PLAYER_ENTITY_TYPE, (1.0, 2.0, 3.0), 180.0, 45.0, 0)
+            .with_velocity((0.0, 0.25, 0.0))
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // Length, id, entity id, uuid, type
+        assert_eq!(bytes[..3], [53, 0x01, 0x05]);
+        assert_eq!(bytes[3..19], 9u128.to_be_bytes());
+        assert_eq!(bytes[19], 122);
+        assert_eq!(bytes[20..28], 1f64.to_be_bytes());
+        // Pitch, yaw, head yaw and data
+        assert_eq!(bytes[44..48], [32, 128, 128, 0]);
+        assert_eq!(bytes[48..], [0, 0, 0x07, 0xD0, 0, 0]);
+    }
+}
@@ -0,0 +1,32 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::spawn_entity::angle;
+
+/// The spawn player packet is sent by the server when another player comes into view of the
+/// player, 1.20.1 clients only spawn players from it.
+#[derive(NetEncode)]
+pub struct SpawnPlayer {
+    #[encode(default = VarInt::from(0x03))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub uuid: u128,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: u8,
+    pub pitch: u8,
+}
+
+impl SpawnPlayer {
+    pub fn new(
+        entity_id: i32,
+        uuid: u128,
+        (x, y, z): (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+    ) -> Self {
+        Self::new_auto(entity_id.into(), uuid, x, y, z, angle(yaw), angle(pitch))
+    }
+}
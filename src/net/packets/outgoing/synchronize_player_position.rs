@@ -3,6 +3,10 @@ use crate::utils::encoding::position::Position;
 use ferrumc_codec::network_types::varint::VarInt;
 use ferrumc_macros::NetEncode;
 
+/// Flags of the fields added to the current rotation of the player instead of replacing it
+const RELATIVE_YAW: u8 = 0x08;
+const RELATIVE_PITCH: u8 = 0x10;
+
 #[derive(NetEncode)]
 pub struct SynchronizePlayerPosition {
     #[encode(default = VarInt::from(0x3C))]
@@ -17,7 +21,7 @@ pub struct SynchronizePlayerPosition {
 }
 
 impl SynchronizePlayerPosition {
-    pub fn new(position: &Position, rotation: &Rotation) -> Self {
+    pub fn new(position: &Position, rotation: &Rotation, teleport_id: i32) -> Self {
         Self {
             packet_id: VarInt::from(0x3C),
             x: position.x as f64,
@@ -26,7 +30,21 @@ impl SynchronizePlayerPosition {
             yaw: rotation.yaw,
             pitch: rotation.pitch,
             flags: 0, // Absolute position & rotation
-            teleport_id: VarInt::from(0),
+            teleport_id: VarInt::from(teleport_id),
+        }
+    }
+
+    /// Move the player to the exact coordinates, without turning it
+    pub fn position_only(x: f64, y: f64, z: f64, teleport_id: i32) -> Self {
+        Self {
+            packet_id: VarInt::from(0x3C),
+            x,
+            y,
+            z,
+            yaw: 0.0,
+            pitch: 0.0,
+            flags: RELATIVE_YAW | RELATIVE_PITCH,
+            teleport_id: VarInt::from(teleport_id),
         }
     }
 }
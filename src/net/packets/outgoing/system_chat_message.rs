@@ -0,0 +1,27 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::ChatComponent;
+
+/// The system chat message packet is sent by the server to show a message in the chat, or above
+/// the hotbar. The content is a JSON text component.
+#[derive(NetEncode)]
+pub struct SystemChatMessage {
+    #[encode(default = VarInt::from(0x64))]
+    pub packet_id: VarInt,
+    pub content: String,
+    /// Shown above the hotbar instead of in the chat
+    pub overlay: bool,
+}
+
+impl SystemChatMessage {
+    pub fn new(content: &ChatComponent, overlay: bool) -> Self {
+        Self::new_auto(content.to_json(), overlay)
+    }
+
+    /// Message of plain text shown in the chat
+    pub fn from_text(text: &str) -> Self {
+        Self::new(&ChatComponent::text(text), false)
+    }
+}
@@ -0,0 +1,30 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use simdnbt::owned::NbtCompound;
+
+use ferrumc_macros::NetEncode;
+
+use crate::world::blocks::write_nbt;
+
+/// NBT of an empty response, a lone TAG_End
+const NO_NBT: u8 = 0;
+
+/// The tag query response packet is sent by the server to answer a query of the NBT of a block
+/// entity or an entity, with the transaction id of the query.
+#[derive(NetEncode)]
+pub struct TagQueryResponse {
+    #[encode(default = VarInt::from(0x66))]
+    pub packet_id: VarInt,
+    pub transaction_id: VarInt,
+    pub nbt: Vec<u8>,
+}
+
+impl TagQueryResponse {
+    /// Answer query `transaction_id` with `nbt`, `None` when there is nothing to show
+    pub fn new(transaction_id: VarInt, nbt: Option<NbtCompound>) -> Self {
+        let nbt = match nbt {
+            Some(compound) => write_nbt(compound),
+            None => vec![NO_NBT],
+        };
+        Self::new_auto(transaction_id, nbt)
+    }
+}
@@ -0,0 +1,19 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The unload chunk packet is sent by the server when a chunk leaves the view distance of the
+/// player, the client forgets it.
+#[derive(NetEncode)]
+pub struct UnloadChunk {
+    #[encode(default = VarInt::from(0x1E))]
+    pub packet_id: VarInt,
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl UnloadChunk {
+    pub fn new(chunk_x: i32, chunk_z: i32) -> Self {
+        Self::new_auto(chunk_x, chunk_z)
+    }
+}
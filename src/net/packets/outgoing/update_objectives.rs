@@ -0,0 +1,103 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::chat::{truncate, ChatComponent};
+
+/// Longest objective name accepted by clients
+pub const MAX_OBJECTIVE_NAME_LENGTH: usize = 16;
+
+/// Modes of an [`UpdateObjectives`]
+pub const CREATE_OBJECTIVE: i8 = 0;
+pub const REMOVE_OBJECTIVE: i8 = 1;
+pub const UPDATE_OBJECTIVE: i8 = 2;
+
+/// How the scores of an objective are shown, as numbers or hearts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderType {
+    #[default]
+    Integer = 0,
+    Hearts = 1,
+}
+
+/// The update objectives packet is sent by the server to create, rename or remove a scoreboard
+/// objective.
+#[derive(NetEncode)]
+pub struct UpdateObjectives {
+    #[encode(default = VarInt::from(0x58))]
+    pub packet_id: VarInt,
+    pub objective_name: String,
+    pub mode: i8,
+    /// JSON text component shown as the title, not sent on removal
+    pub display_name: Option<String>,
+    pub render_type: Option<VarInt>,
+}
+
+impl UpdateObjectives {
+    pub fn create(
+        objective_name: &str,
+        display_name: impl Into<ChatComponent>,
+        render_type: RenderType,
+    ) -> Self {
+        Self::with_display(CREATE_OBJECTIVE, objective_name, display_name, render_type)
+    }
+
+    pub fn update(
+        objective_name: &str,
+        display_name: impl Into<ChatComponent>,
+        render_type: RenderType,
+    ) -> Self {
+        Self::with_display(UPDATE_OBJECTIVE, objective_name, display_name, render_type)
+    }
+
+    pub fn remove(objective_name: &str) -> Self {
+        Self::new_auto(
+            truncate(objective_name, MAX_OBJECTIVE_NAME_LENGTH).to_string(),
+            REMOVE_OBJECTIVE,
+            None,
+            None,
+        )
+    }
+
+    fn with_display(
+        mode: i8,
+        objective_name: &str,
+        display_name: impl Into<ChatComponent>,
+        render_type: RenderType,
+    ) -> Self {
+        Self::new_auto(
+            truncate(objective_name, MAX_OBJECTIVE_NAME_LENGTH).to_string(),
+            mode,
+            Some(display_name.into().to_json()),
+            Some(VarInt::from(render_type as i32)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{RenderType, UpdateObjectives};
+
+    #[tokio::test]
+    async fn removals_only_send_the_name() {
+        let mut bytes = Vec::new();
+        UpdateObjectives::remove("a_very_long_objective_name")
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        let mut expected = vec![19, 0x58, 16];
+        expected.extend_from_slice(b"a_very_long_obje");
+        expected.push(1);
+        assert_eq!(bytes, expected);
+
+        let mut bytes = Vec::new();
+        UpdateObjectives::create("side", "Title", RenderType::Hearts)
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert_eq!(bytes[7], 0);
+        assert_eq!(bytes[bytes.len() - 1], 1);
+    }
+}
@@ -0,0 +1,46 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::update_objectives::MAX_OBJECTIVE_NAME_LENGTH;
+use crate::utils::chat::truncate;
+
+/// Longest name of the holder of a score accepted by clients
+pub const MAX_SCORE_HOLDER_LENGTH: usize = 40;
+
+const UPDATE_SCORE: i32 = 0;
+const REMOVE_SCORE: i32 = 1;
+
+/// The update score packet is sent by the server to set or remove the score of an entry of an
+/// objective.
+#[derive(NetEncode)]
+pub struct UpdateScore {
+    #[encode(default = VarInt::from(0x5B))]
+    pub packet_id: VarInt,
+    /// Username of a player, or any text on sidebars
+    pub entity_name: String,
+    pub action: VarInt,
+    pub objective_name: String,
+    /// Not sent on removal
+    pub value: Option<VarInt>,
+}
+
+impl UpdateScore {
+    pub fn update(entity_name: &str, objective_name: &str, value: i32) -> Self {
+        Self::new_auto(
+            truncate(entity_name, MAX_SCORE_HOLDER_LENGTH).to_string(),
+            VarInt::from(UPDATE_SCORE),
+            truncate(objective_name, MAX_OBJECTIVE_NAME_LENGTH).to_string(),
+            Some(VarInt::from(value)),
+        )
+    }
+
+    pub fn remove(entity_name: &str, objective_name: &str) -> Self {
+        Self::new_auto(
+            truncate(entity_name, MAX_SCORE_HOLDER_LENGTH).to_string(),
+            VarInt::from(REMOVE_SCORE),
+            truncate(objective_name, MAX_OBJECTIVE_NAME_LENGTH).to_string(),
+            None,
+        )
+    }
+}
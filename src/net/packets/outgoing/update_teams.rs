@@ -0,0 +1,152 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::update_score::MAX_SCORE_HOLDER_LENGTH;
+use crate::utils::chat::{truncate, ChatComponent};
+
+/// Longest team name accepted by clients
+pub const MAX_TEAM_NAME_LENGTH: usize = 16;
+
+/// Modes of an [`UpdateTeams`]
+const CREATE_TEAM: i8 = 0;
+const REMOVE_TEAM: i8 = 1;
+const UPDATE_TEAM: i8 = 2;
+const ADD_ENTITIES: i8 = 3;
+const REMOVE_ENTITIES: i8 = 4;
+
+/// Friendly flags of a team
+pub const ALLOW_FRIENDLY_FIRE: i8 = 0x01;
+pub const SEE_INVISIBLE_TEAMMATES: i8 = 0x02;
+
+/// Display of a team, shown around the names of its members
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamInfo {
+    pub display_name: ChatComponent,
+    pub friendly_flags: i8,
+    /// `always`, `hideForOtherTeams`, `hideForOwnTeam` or `never`
+    pub name_tag_visibility: String,
+    /// `always`, `pushOtherTeams`, `pushOwnTeam` or `never`
+    pub collision_rule: String,
+    /// Formatting code of the color of the names, 21 to reset it
+    pub color: i32,
+    pub prefix: ChatComponent,
+    pub suffix: ChatComponent,
+}
+
+impl Default for TeamInfo {
+    fn default() -> Self {
+        Self {
+            display_name: ChatComponent::default(),
+            friendly_flags: ALLOW_FRIENDLY_FIRE,
+            name_tag_visibility: "always".to_string(),
+            collision_rule: "always".to_string(),
+            color: 21,
+            prefix: ChatComponent::default(),
+            suffix: ChatComponent::default(),
+        }
+    }
+}
+
+/// The update teams packet is sent by the server to create, change or remove a team, or to
+/// change its members.
+#[derive(NetEncode)]
+pub struct UpdateTeams {
+    #[encode(default = VarInt::from(0x5A))]
+    pub packet_id: VarInt,
+    pub team_name: String,
+    pub mode: i8,
+    /// Fields of the [`TeamInfo`], only sent on creation and updates
+    pub display_name: Option<String>,
+    pub friendly_flags: Option<i8>,
+    pub name_tag_visibility: Option<String>,
+    pub collision_rule: Option<String>,
+    pub color: Option<VarInt>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    /// Members, not sent on removal and updates
+    pub entity_count: Option<VarInt>,
+    pub entities: Vec<String>,
+}
+
+impl UpdateTeams {
+    pub fn create(team_name: &str, info: &TeamInfo, entities: &[&str]) -> Self {
+        Self::packet(CREATE_TEAM, team_name, Some(info), Some(entities))
+    }
+
+    pub fn remove(team_name: &str) -> Self {
+        Self::packet(REMOVE_TEAM, team_name, None, None)
+    }
+
+    pub fn update(team_name: &str, info: &TeamInfo) -> Self {
+        Self::packet(UPDATE_TEAM, team_name, Some(info), None)
+    }
+
+    pub fn add_entities(team_name: &str, entities: &[&str]) -> Self {
+        Self::packet(ADD_ENTITIES, team_name, None, Some(entities))
+    }
+
+    pub fn remove_entities(team_name: &str, entities: &[&str]) -> Self {
+        Self::packet(REMOVE_ENTITIES, team_name, None, Some(entities))
+    }
+
+    fn packet(
+        mode: i8,
+        team_name: &str,
+        info: Option<&TeamInfo>,
+        entities: Option<&[&str]>,
+    ) -> Self {
+        let entities: Option<Vec<String>> = entities.map(|entities| {
+            entities
+                .iter()
+                .map(|entity| truncate(entity, MAX_SCORE_HOLDER_LENGTH).to_string())
+                .collect()
+        });
+        Self::new_auto(
+            truncate(team_name, MAX_TEAM_NAME_LENGTH).to_string(),
+            mode,
+            info.map(|info| info.display_name.to_json()),
+            info.map(|info| info.friendly_flags),
+            info.map(|info| info.name_tag_visibility.clone()),
+            info.map(|info| info.collision_rule.clone()),
+            info.map(|info| VarInt::from(info.color)),
+            info.map(|info| info.prefix.to_json()),
+            info.map(|info| info.suffix.to_json()),
+            entities
+                .as_ref()
+                .map(|entities| VarInt::from(entities.len() as i32)),
+            entities.unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{TeamInfo, UpdateTeams};
+
+    #[tokio::test]
+    async fn members_are_only_sent_with_their_modes() {
+        let mut bytes = Vec::new();
+        UpdateTeams::add_entities("red", &["Steve", "Alex"])
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        let mut expected = vec![18, 0x5A, 3];
+        expected.extend_from_slice(b"red");
+        expected.extend_from_slice(&[3, 2, 5]);
+        expected.extend_from_slice(b"Steve");
+        expected.push(4);
+        expected.extend_from_slice(b"Alex");
+        assert_eq!(bytes, expected);
+
+        let mut bytes = Vec::new();
+        UpdateTeams::update("red", &TeamInfo::default())
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        // Ends with the suffix, no member count
+        assert!(bytes.ends_with(br#"{"text":""}"#));
+    }
+}
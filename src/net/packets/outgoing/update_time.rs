@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// The update time packet is sent by the server every second to keep the time of the client in
+/// sync.
+#[derive(NetEncode)]
+pub struct UpdateTime {
+    #[encode(default = VarInt::from(0x5E))]
+    pub packet_id: VarInt,
+    /// Ticks since the world was created
+    pub world_age: i64,
+    /// Ticks since the start of the first day, negative when the sun stands still
+    pub time_of_day: i64,
+}
+
+impl UpdateTime {
+    pub fn new(world_age: i64, time_of_day: i64) -> Self {
+        Self::new_auto(world_age, time_of_day)
+    }
+}
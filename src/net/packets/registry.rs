@@ -0,0 +1,389 @@
+//! Handlers of the incoming packets
+//!
+//! Every packet of `incoming/` is registered once at startup with [`register_packet!`], which
+//! maps its connection state and id to a handler decoding then handling it. Handlers are kept
+//! in a flat array per state, indexed by packet id. Tests and plugins can add handlers, or
+//! replace the built-in ones, with [`PacketRegistry::register`].
+
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::net::packets::ConnectionId;
+use crate::net::{drop_conn, State};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+type PacketFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+type PacketHandler =
+    Arc<dyn Fn(ConnectionId, Cursor<Vec<u8>>, GlobalState) -> PacketFuture + Send + Sync>;
+
+/// Packet ids are read as a single byte
+const PACKET_IDS: usize = 256;
+/// Handshake, status, login and play
+const STATES: usize = 4;
+
+/// Register the packet `$module::$packet` of `incoming/` under the state `$state` and id `$id`
+///
+/// ```ignore
+/// register_packet!(registry, Play, 0x14, set_player_position::SetPlayerPosition);
+/// ```
+#[macro_export]
+macro_rules! register_packet {
+    ($registry:expr, $state:ident, $id:expr, $module:ident :: $packet:ident) => {
+        $registry.register(
+            $crate::net::State::$state,
+            $id,
+            concat!(stringify!($module), "::", stringify!($packet)),
+            |conn_id, mut cursor, state| async move {
+                use $crate::net::packets::IncomingPacket;
+
+                let packet =
+                    $crate::net::packets::incoming::$module::$packet::net_decode(&mut cursor)
+                        .await?;
+                packet.handle(conn_id, state).await
+            },
+        )
+    };
+}
+
+/// What happens to packets without a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownPacketPolicy {
+    /// Skip the packet, its whole length was already read
+    #[default]
+    Skip,
+    /// Disconnect the sender
+    Disconnect,
+}
+
+#[derive(Clone)]
+struct Registered {
+    name: &'static str,
+    handler: PacketHandler,
+}
+
+pub struct PacketRegistry {
+    /// Handlers of each state, indexed by packet id
+    states: [RwLock<Vec<Option<Registered>>>; STATES],
+    fallback: UnknownPacketPolicy,
+}
+
+/// Index of `state` in the handler arrays, no packet is expected in an unknown state
+fn state_index(state: &State) -> Option<usize> {
+    match state {
+        State::Handshake => Some(0),
+        State::Status => Some(1),
+        State::Login => Some(2),
+        State::Play => Some(3),
+        State::Unknown => None,
+    }
+}
+
+impl PacketRegistry {
+    /// Registry with the packets of `incoming/`, packets of unknown ids are handled with
+    /// `fallback`
+    pub fn new(fallback: UnknownPacketPolicy) -> Self {
+        let registry = Self {
+            states: std::array::from_fn(|_| RwLock::new(vec![None; PACKET_IDS])),
+            fallback,
+        };
+        register_builtin_packets(&registry);
+        registry
+    }
+
+    /// Handle the packets of id `packet_id` received in `state` with `handler`, called with the
+    /// entity id of the sender, the packet without its id and the server state. Replaces the
+    /// previous handler of the packet, returns its name.
+    pub fn register<F, Fut>(
+        &self,
+        state: State,
+        packet_id: u8,
+        name: &'static str,
+        handler: F,
+    ) -> Option<&'static str>
+    where
+        F: Fn(ConnectionId, Cursor<Vec<u8>>, GlobalState) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let index = state_index(&state)?;
+        let handler: PacketHandler =
+            Arc::new(move |conn_id, cursor, state| Box::pin(handler(conn_id, cursor, state)));
+        let previous =
+            self.states[index].write()[packet_id as usize].replace(Registered { name, handler });
+        previous.map(|registered| registered.name)
+    }
+
+    /// Stop handling the packets of id `packet_id` in `state`, returns whether they had a
+    /// handler
+    pub fn unregister(&self, state: &State, packet_id: u8) -> bool {
+        state_index(state)
+            .and_then(|index| self.states[index].write()[packet_id as usize].take())
+            .is_some()
+    }
+
+    /// Name of the handler of the packets of id `packet_id` in `state`
+    pub fn handler_name(&self, state: &State, packet_id: u8) -> Option<&'static str> {
+        let index = state_index(state)?;
+        self.states[index].read()[packet_id as usize]
+            .as_ref()
+            .map(|registered| registered.name)
+    }
+
+    /// Number of registered handlers, over every state
+    pub fn len(&self) -> usize {
+        self.states
+            .iter()
+            .map(|handlers| handlers.read().iter().flatten().count())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pass a packet to its handler, packets without one go to the fallback
+    pub async fn handle(
+        &self,
+        packet_id: u8,
+        conn_id: ConnectionId,
+        conn_state: &State,
+        cursor: Cursor<Vec<u8>>,
+        state: GlobalState,
+    ) -> Result<()> {
+        // Cloned out so the array isn't locked while the handler runs
+        let registered = state_index(conn_state)
+            .and_then(|index| self.states[index].read()[packet_id as usize].clone());
+        if let Some(registered) = registered {
+            return (registered.handler)(conn_id, cursor, state).await;
+        }
+
+        match self.fallback {
+            UnknownPacketPolicy::Skip => {
+                warn!(
+                    "No packet found for ID: 0x{:02X} in state: {}",
+                    packet_id, conn_state
+                );
+                Ok(())
+            }
+            UnknownPacketPolicy::Disconnect => {
+                warn!(
+                    "Disconnecting entity {}, it sent unknown packet 0x{:02X} in state {}",
+                    conn_id, packet_id, conn_state
+                );
+                drop_conn(conn_id, state).await
+            }
+        }
+    }
+}
+
+/// The packets of `incoming/`, each registered exactly once
+fn register_builtin_packets(registry: &PacketRegistry) {
+    register_packet!(registry, Handshake, 0x00, handshake::Handshake);
+
+    register_packet!(registry, Status, 0x00, status::Status);
+    register_packet!(registry, Status, 0x01, ping::Ping);
+
+    register_packet!(registry, Login, 0x00, login_start::LoginStart);
+    register_packet!(
+        registry,
+        Login,
+        0x01,
+        encryption_response::EncryptionResponse
+    );
+    register_packet!(
+        registry,
+        Login,
+        0x02,
+        login_query_response::LoginQueryResponse
+    );
+
+    register_packet!(registry, Play, 0x00, confirm_teleport::ConfirmTeleport);
+    register_packet!(
+        registry,
+        Play,
+        0x01,
+        query_block_entity::QueryBlockEntityTag
+    );
+    register_packet!(
+        registry,
+        Play,
+        0x02,
+        change_difficulty::ChangeDifficultyRequest
+    );
+    register_packet!(
+        registry,
+        Play,
+        0x03,
+        message_acknowledgment::MessageAcknowledgment
+    );
+    register_packet!(registry, Play, 0x04, chat_command::ChatCommand);
+    register_packet!(registry, Play, 0x05, chat_message::PacketChatMessage);
+    register_packet!(registry, Play, 0x06, player_session::PlayerSession);
+    register_packet!(registry, Play, 0x07, client_command::ClientCommand);
+    register_packet!(registry, Play, 0x08, client_info::ClientInfo);
+    register_packet!(
+        registry,
+        Play,
+        0x09,
+        command_suggestions_request::CommandSuggestionsRequest
+    );
+    register_packet!(registry, Play, 0x0B, click_container::ClickContainer);
+    register_packet!(registry, Play, 0x0C, close_container::CloseContainer);
+    register_packet!(registry, Play, 0x0D, plugin_message::PluginMessage);
+    register_packet!(registry, Play, 0x0E, edit_book::EditBook);
+    register_packet!(registry, Play, 0x0F, query_entity::QueryEntityTag);
+    register_packet!(registry, Play, 0x10, interact_entity::InteractEntity);
+    register_packet!(registry, Play, 0x12, keep_alive::KeepAlivePacketIn);
+    register_packet!(registry, Play, 0x13, lock_difficulty::LockDifficulty);
+    register_packet!(registry, Play, 0x14, set_player_position::SetPlayerPosition);
+    register_packet!(
+        registry,
+        Play,
+        0x15,
+        set_player_pos_and_rotate::SetPlayerPosAndRotate
+    );
+    register_packet!(registry, Play, 0x16, set_player_rotation::SetPlayerRotation);
+    register_packet!(registry, Play, 0x1A, pick_item::PickItem);
+    register_packet!(registry, Play, 0x1C, player_abilities::PlayerAbilities);
+    register_packet!(registry, Play, 0x1D, player_action::PlayerAction);
+    register_packet!(registry, Play, 0x1E, player_command::PlayerCommand);
+    register_packet!(registry, Play, 0x1F, player_input::PlayerInput);
+    register_packet!(registry, Play, 0x20, pong_play::PongPlay);
+    register_packet!(registry, Play, 0x23, rename_item::RenameItem);
+    register_packet!(
+        registry,
+        Play,
+        0x24,
+        resource_pack_response::ResourcePackResponse
+    );
+    register_packet!(registry, Play, 0x26, select_trade::SelectTrade);
+    register_packet!(registry, Play, 0x28, set_held_item::SetHeldItem);
+    register_packet!(
+        registry,
+        Play,
+        0x29,
+        program_command_block::ProgramCommandBlock
+    );
+    register_packet!(registry, Play, 0x2B, set_creative_slot::SetCreativeSlot);
+    register_packet!(registry, Play, 0x2D, set_structure_block::SetStructureBlock);
+    register_packet!(registry, Play, 0x2E, update_sign::UpdateSign);
+    register_packet!(registry, Play, 0x2F, swing_arm::SwingArm);
+    register_packet!(registry, Play, 0x31, use_item_on::UseItemOn);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use super::{PacketRegistry, UnknownPacketPolicy};
+    use crate::net::State;
+    use crate::tests::{test_connection, test_state};
+
+    fn state_of(name: &str) -> State {
+        match name {
+            "handshake" => State::Handshake,
+            "status" => State::Status,
+            "login" => State::Login,
+            "play" => State::Play,
+            _ => panic!("Unknown packet state {name}"),
+        }
+    }
+
+    #[test]
+    fn every_incoming_packet_is_registered_once() {
+        let registry = PacketRegistry::new(UnknownPacketPolicy::Skip);
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/net/packets/incoming");
+
+        let mut packets = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let module = path.file_stem().unwrap().to_str().unwrap().to_string();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let Some(start) = source.find("#[packet(") else {
+                continue;
+            };
+            let attribute = &source[start..];
+            let attribute = &attribute[..attribute.find(")]").unwrap()];
+            let id = attribute.split("packet_id = 0x").nth(1).unwrap();
+            let id = u8::from_str_radix(&id[..2], 16).unwrap();
+            let conn_state = attribute.split("state = \"").nth(1).unwrap();
+            let conn_state = state_of(&conn_state[..conn_state.find('"').unwrap()]);
+            let name = source[start..].split("pub struct ").nth(1).unwrap();
+            let name = &name[..name.find(|c: char| !c.is_alphanumeric()).unwrap()];
+
+            assert_eq!(
+                registry.handler_name(&conn_state, id),
+                Some(format!("{module}::{name}").as_str()),
+                "0x{id:02X} in state {conn_state}"
+            );
+            packets += 1;
+        }
+        assert_eq!(registry.len(), packets);
+    }
+
+    #[tokio::test]
+    async fn registered_handlers_replace_the_builtin_ones() {
+        let state = test_state().await;
+        let registry = PacketRegistry::new(UnknownPacketPolicy::Skip);
+        let called = Arc::new(AtomicBool::new(false));
+
+        let flag = called.clone();
+        let previous = registry.register(State::Play, 0x12, "test", move |_, _, _| {
+            let flag = flag.clone();
+            async move {
+                flag.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+        });
+        assert_eq!(previous, Some("keep_alive::KeepAlivePacketIn"));
+
+        registry
+            .handle(0x12, 0, &State::Play, Cursor::new(Vec::new()), state)
+            .await
+            .unwrap();
+        assert!(called.load(Ordering::Relaxed));
+        assert!(registry.unregister(&State::Play, 0x12));
+        assert_eq!(registry.handler_name(&State::Play, 0x12), None);
+    }
+
+    #[tokio::test]
+    async fn unknown_packets_disconnect_when_configured() {
+        let state = test_state().await;
+        let (conn_id, _client) = test_connection(&state).await;
+
+        let skipping = PacketRegistry::new(UnknownPacketPolicy::Skip);
+        skipping
+            .handle(
+                0xFF,
+                conn_id,
+                &State::Play,
+                Cursor::new(Vec::new()),
+                state.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(state.connections.get_connection(conn_id).is_ok());
+
+        let disconnecting = PacketRegistry::new(UnknownPacketPolicy::Disconnect);
+        disconnecting
+            .handle(
+                0xFF,
+                conn_id,
+                &State::Play,
+                Cursor::new(Vec::new()),
+                state.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(state.connections.get_connection(conn_id).is_err());
+    }
+}
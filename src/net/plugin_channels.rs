@@ -0,0 +1,95 @@
+//! Handlers of the plugin message channels
+//!
+//! Clients send custom payloads on namespaced channels, `minecraft:brand` right after login and
+//! many more with mods installed. Server code registers a handler per channel name in the
+//! [`PluginChannelRegistry`] of the server state, payloads on other channels are ignored.
+
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tracing::{debug, trace};
+
+use crate::state::GlobalState;
+use crate::utils::components::client_brand::ClientBrand;
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+
+type ChannelFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+type ChannelHandler = Arc<dyn Fn(u32, Vec<u8>, GlobalState) -> ChannelFuture + Send + Sync>;
+
+pub struct PluginChannelRegistry {
+    handlers: DashMap<String, ChannelHandler>,
+}
+
+impl PluginChannelRegistry {
+    /// Registry with the built-in channels
+    pub fn new() -> Self {
+        let registry = Self {
+            handlers: DashMap::new(),
+        };
+        registry.register(BRAND_CHANNEL, on_brand);
+        registry
+    }
+
+    /// Handle the payloads sent on `channel`, called with the entity id of the sender, the
+    /// payload and the server state. Replaces the previous handler of the channel.
+    pub fn register<F, Fut>(&self, channel: impl Into<String>, handler: F)
+    where
+        F: Fn(u32, Vec<u8>, GlobalState) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let handler: ChannelHandler =
+            Arc::new(move |entity_id, data, state| Box::pin(handler(entity_id, data, state)));
+        self.handlers.insert(channel.into(), handler);
+    }
+
+    /// Stop handling `channel`, returns whether it had a handler
+    pub fn unregister(&self, channel: &str) -> bool {
+        self.handlers.remove(channel).is_some()
+    }
+
+    pub fn is_registered(&self, channel: &str) -> bool {
+        self.handlers.contains_key(channel)
+    }
+
+    /// Pass a payload to the handler of its channel, unregistered channels are ignored
+    pub async fn handle(
+        &self,
+        entity_id: u32,
+        channel: &str,
+        data: Vec<u8>,
+        state: GlobalState,
+    ) -> Result<()> {
+        // Cloned out so the map isn't locked while the handler runs
+        let Some(handler) = self.handlers.get(channel).map(|handler| handler.clone()) else {
+            trace!(
+                "Ignoring plugin message on unregistered channel {}",
+                channel
+            );
+            return Ok(());
+        };
+        handler(entity_id, data, state).await
+    }
+}
+
+impl Default for PluginChannelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stores the client brand on the player
+async fn on_brand(entity_id: u32, data: Vec<u8>, state: GlobalState) -> Result<()> {
+    let brand = String::net_decode(&mut Cursor::new(data)).await?;
+    debug!("Entity {} is using the {} client", entity_id, brand);
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, ClientBrand::new(*brand));
+    Ok(())
+}
@@ -0,0 +1,272 @@
+//! Protection against clients flooding the server
+//!
+//! The read loop of each connection refuses packets declaring a length above the configured
+//! maximum before reading them, and spends a token of a [`RateLimiter`] for each packet it
+//! reads. Connections breaking either limit are disconnected, and their address can be banned
+//! for a while. What was refused is counted in the [`RateLimits`] of the server.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::net::packets::outgoing::disconnect::Disconnect;
+use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
+use crate::net::packets::ConnectionId;
+use crate::net::{drop_conn, State};
+use crate::state::GlobalState;
+use crate::utils::config::RateLimit;
+use crate::utils::prelude::*;
+
+pub const PACKET_TOO_LARGE: &str = "Packet too large";
+pub const TOO_MANY_PACKETS: &str = "Sending packets too fast";
+
+/// Budget of a packet of the play state, instead of the one shared by all the packets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PacketBudget {
+    pub packet_id: u8,
+    pub per_second: u32,
+    /// Packets that can be sent at once
+    pub burst: u32,
+}
+
+/// Tokens refilling at a constant rate, up to a capacity
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    per_second: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket of `burst` tokens, refilling `per_second` tokens a second
+    pub fn new(per_second: u32, burst: u32, now: Instant) -> Self {
+        Self {
+            capacity: burst as f64,
+            tokens: burst as f64,
+            per_second: per_second as f64,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, returns whether there was one left
+    pub fn take(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Packet budgets of a connection, owned by its read loop
+#[derive(Debug)]
+pub struct RateLimiter {
+    packets: TokenBucket,
+    overrides: HashMap<u8, TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimit, now: Instant) -> Self {
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|budget| {
+                (
+                    budget.packet_id,
+                    TokenBucket::new(budget.per_second, budget.burst, now),
+                )
+            })
+            .collect();
+        Self {
+            packets: TokenBucket::new(config.packets_per_second, config.burst, now),
+            overrides,
+        }
+    }
+
+    /// Spend the budget of a packet received in `state`, returns whether it was within it
+    ///
+    /// Packets with an override are only limited by it. Overrides only apply to the play state,
+    /// the ids of the other states meaning other packets.
+    pub fn allow(&mut self, state: &State, packet_id: u8, now: Instant) -> bool {
+        match self.overrides.get_mut(&packet_id) {
+            Some(bucket) if *state == State::Play => bucket.take(now),
+            _ => self.packets.take(now),
+        }
+    }
+}
+
+/// Counters of what the limits refused, for diagnostics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitCounters {
+    /// Packets refused for declaring a length above the maximum
+    pub oversized_packets: u64,
+    /// Packets refused for exceeding a budget
+    pub limited_packets: u64,
+    /// Connections dropped for breaking a limit
+    pub disconnects: u64,
+    /// Connections refused because their address was banned
+    pub refused_connections: u64,
+}
+
+/// Counters and temporary bans shared by all the connections
+#[derive(Debug, Default)]
+pub struct RateLimits {
+    oversized_packets: AtomicU64,
+    limited_packets: AtomicU64,
+    disconnects: AtomicU64,
+    refused_connections: AtomicU64,
+    bans: DashMap<IpAddr, Instant>,
+}
+
+impl RateLimits {
+    pub fn counters(&self) -> RateLimitCounters {
+        RateLimitCounters {
+            oversized_packets: self.oversized_packets.load(Ordering::Relaxed),
+            limited_packets: self.limited_packets.load(Ordering::Relaxed),
+            disconnects: self.disconnects.load(Ordering::Relaxed),
+            refused_connections: self.refused_connections.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn record_oversized(&self) {
+        self.oversized_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_limited(&self) {
+        self.limited_packets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Ban `address` until `duration` from now
+    pub fn ban(&self, address: IpAddr, duration: Duration) {
+        self.bans.insert(address, Instant::now() + duration);
+    }
+
+    /// Whether `address` is banned, counting the connection as refused if it is
+    pub fn refuse(&self, address: IpAddr) -> bool {
+        let banned = match self.bans.get(&address) {
+            Some(until) => *until > Instant::now(),
+            None => return false,
+        };
+        if banned {
+            self.refused_connections.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.bans.remove(&address);
+        }
+        banned
+    }
+
+    /// Addresses currently banned, with when their ban ends
+    pub fn bans(&self) -> Vec<(IpAddr, Instant)> {
+        let now = Instant::now();
+        self.bans
+            .iter()
+            .filter(|ban| *ban.value() > now)
+            .map(|ban| (*ban.key(), *ban.value()))
+            .collect()
+    }
+}
+
+/// Disconnect `conn_id` for breaking a limit, banning its address if configured to
+pub async fn disconnect_offender(
+    conn_id: ConnectionId,
+    address: IpAddr,
+    reason: &str,
+    config: &RateLimit,
+    state: GlobalState,
+) -> Result<()> {
+    warn!("Disconnecting {} ({}): {}", conn_id, address, reason);
+    state
+        .rate_limits
+        .disconnects
+        .fetch_add(1, Ordering::Relaxed);
+    if config.ban_seconds > 0 {
+        state
+            .rate_limits
+            .ban(address, Duration::from_secs(config.ban_seconds));
+    }
+
+    {
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        // Nothing to show the reason with in the other states
+        let sent = match conn.state {
+            State::Play => conn.send_packet(Disconnect::from_text(reason)).await,
+            State::Login => conn.send_packet(LoginDisconnect::from_text(reason)).await,
+            _ => Ok(()),
+        };
+        if let Err(e) = sent {
+            warn!("Failed to send the disconnect reason to {}: {}", conn_id, e);
+        }
+    }
+    drop_conn(conn_id, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimiter, RateLimits, TokenBucket};
+    use crate::net::State;
+    use crate::utils::config::RateLimit;
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10, 2, start);
+        assert!(bucket.take(start));
+        assert!(bucket.take(start));
+        assert!(!bucket.take(start));
+
+        // A token every 100ms, never more than the burst
+        assert!(bucket.take(start + Duration::from_millis(100)));
+        assert!(!bucket.take(start + Duration::from_millis(150)));
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.take(later));
+        assert!(bucket.take(later));
+        assert!(!bucket.take(later));
+    }
+
+    #[test]
+    fn overrides_only_limit_their_packet() {
+        let start = Instant::now();
+        let config = RateLimit::default();
+        let mut limiter = RateLimiter::new(&config, start);
+        let chat = config.overrides[0].clone();
+
+        for _ in 0..chat.burst {
+            assert!(limiter.allow(&State::Play, chat.packet_id, start));
+        }
+        assert!(!limiter.allow(&State::Play, chat.packet_id, start));
+        // Movement and the packets of the same id in other states are unaffected
+        assert!(limiter.allow(&State::Play, 0x14, start));
+        assert!(limiter.allow(&State::Login, chat.packet_id, start));
+    }
+
+    #[test]
+    fn bans_expire() {
+        let limits = RateLimits::default();
+        let address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        assert!(!limits.refuse(address));
+
+        limits.ban(address, Duration::from_secs(60));
+        assert!(limits.refuse(address));
+        assert_eq!(limits.counters().refused_connections, 1);
+
+        limits.ban(address, Duration::ZERO);
+        assert!(!limits.refuse(address));
+        assert!(limits.bans().is_empty());
+    }
+}
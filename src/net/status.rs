@@ -0,0 +1,267 @@
+//! The status shown in the server list
+//!
+//! [`StatusBuilder`] assembles the JSON answered to status requests. The favicon is read once at
+//! startup by [`load_favicon`], the players are read from the world on every request.
+
+use base64::Engine;
+use rand::prelude::IndexedRandom;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::net::SUPPORTED_PROTOCOL_RANGE;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::{GAME_VERSION, PROTOCOL_VERSION};
+use crate::utils::prelude::*;
+
+/// Most players the server list shows the names of, as vanilla
+pub const MAX_PLAYER_SAMPLE: usize = 12;
+/// Size of the favicon, in pixels
+pub const FAVICON_SIZE: u32 = 64;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonResponse<'a> {
+    version: &'a Version,
+    players: &'a Players,
+    description: &'a Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    favicon: Option<&'a str>,
+    enforces_secure_chat: bool,
+    previews_chat: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Version {
+    pub name: String,
+    pub protocol: i32,
+}
+
+impl Version {
+    /// Version shown to a client of `protocol_version`: its own when it is supported, so it shows
+    /// the server as compatible, or the one the server speaks
+    pub(crate) fn advertised_to(protocol_version: i32) -> Self {
+        Version {
+            name: GAME_VERSION.to_string(),
+            protocol: if SUPPORTED_PROTOCOL_RANGE.contains(&protocol_version) {
+                protocol_version
+            } else {
+                PROTOCOL_VERSION
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Players {
+    max: i32,
+    online: usize,
+    sample: Vec<Sample>,
+}
+
+#[derive(Debug, Serialize)]
+struct Sample {
+    name: String,
+    id: String,
+}
+
+/// Builder of the status answered to a client
+#[derive(Debug)]
+pub struct StatusBuilder {
+    version: Version,
+    description: Value,
+    players: Players,
+    favicon: Option<String>,
+    enforces_secure_chat: bool,
+    previews_chat: bool,
+}
+
+impl StatusBuilder {
+    /// An empty status, shown to a client of `protocol_version`
+    pub fn new(protocol_version: i32) -> Self {
+        Self {
+            version: Version::advertised_to(protocol_version),
+            description: Value::String(String::new()),
+            players: Players::default(),
+            favicon: None,
+            enforces_secure_chat: false,
+            previews_chat: false,
+        }
+    }
+
+    /// The status of the server: a random MOTD of the config, its players and the favicon
+    /// loaded at startup
+    pub async fn from_state(protocol_version: i32, state: &GlobalState) -> Result<Self> {
+        let config = get_global_config();
+
+        let mut players = Vec::new();
+        let mut query = state.world.query::<&Player>();
+        while let Some((_, player)) = query.next().await {
+            players.push((player.username.clone(), player.uuid));
+        }
+        let online = players.len();
+        if config.status.hide_players {
+            players.clear();
+        }
+
+        let motd = config.motd.choose(&mut rand::thread_rng());
+        Ok(Self::new(protocol_version)
+            .motd(motd.map_or("", String::as_str))
+            .max_players(config.max_players)
+            .players(online, players)
+            .favicon(state.favicon.clone())
+            .secure_chat(
+                config.status.enforces_secure_chat,
+                config.status.previews_chat,
+            ))
+    }
+
+    /// Set the description, either JSON chat or text with legacy `§` color codes
+    pub fn motd(mut self, motd: &str) -> Self {
+        let json = match motd.trim_start().chars().next() {
+            Some('{' | '[') => serde_json::from_str(motd).ok(),
+            _ => None,
+        };
+        self.description = json.unwrap_or_else(|| serde_json::json!({ "text": motd }));
+        self
+    }
+
+    pub fn max_players(mut self, max_players: i32) -> Self {
+        self.players.max = max_players;
+        self
+    }
+
+    /// Set the number of players online and the names shown, at most [`MAX_PLAYER_SAMPLE`] of
+    /// `sample` picked at random
+    pub fn players(mut self, online: usize, sample: Vec<(String, u128)>) -> Self {
+        self.players.online = online;
+        self.players.sample = sample
+            .choose_multiple(&mut rand::thread_rng(), MAX_PLAYER_SAMPLE)
+            .map(|(name, uuid)| Sample {
+                name: name.clone(),
+                id: Uuid::from_u128(*uuid).hyphenated().to_string(),
+            })
+            .collect();
+        self
+    }
+
+    /// Set the favicon, a data URL of the PNG as made by [`load_favicon`]
+    pub fn favicon(mut self, favicon: Option<String>) -> Self {
+        self.favicon = favicon;
+        self
+    }
+
+    pub fn secure_chat(mut self, enforces_secure_chat: bool, previews_chat: bool) -> Self {
+        self.enforces_secure_chat = enforces_secure_chat;
+        self.previews_chat = previews_chat;
+        self
+    }
+
+    /// The JSON of the status
+    pub fn build(&self) -> Result<String> {
+        serde_json::to_string(&JsonResponse {
+            version: &self.version,
+            players: &self.players,
+            description: &self.description,
+            favicon: self.favicon.as_deref(),
+            enforces_secure_chat: self.enforces_secure_chat,
+            previews_chat: self.previews_chat,
+        })
+        .map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+/// Read the favicon at `path`, returns it as a data URL, or `None` if there is no file
+///
+/// The favicon has to be a PNG of [`FAVICON_SIZE`] pixels squared, the client ignores others.
+pub async fn load_favicon(path: &str) -> Result<Option<String>> {
+    let data = match tokio::fs::read(path).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let (width, height) = png_dimensions(&data)
+        .ok_or_else(|| Error::Generic(format!("The favicon {} is not a PNG", path)))?;
+    if (width, height) != (FAVICON_SIZE, FAVICON_SIZE) {
+        return Err(Error::Generic(format!(
+            "The favicon {} is {}x{} pixels instead of {}x{}",
+            path, width, height, FAVICON_SIZE, FAVICON_SIZE
+        )));
+    }
+
+    let data = base64::engine::general_purpose::STANDARD.encode(&data);
+    Ok(Some(format!("data:image/png;base64,{}", data)))
+}
+
+/// Width and height of the PNG in `data`, read from its header
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    // Signature, then the length and type of the header chunk
+    if data.len() < 24 || data[..8] != PNG_SIGNATURE || data[12..16] != *b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+    let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{png_dimensions, StatusBuilder, Version, PNG_SIGNATURE};
+
+    #[test]
+    fn supported_clients_see_their_own_version() {
+        assert_eq!(Version::advertised_to(763).protocol, 763);
+        let version = Version::advertised_to(47);
+        assert_eq!(version.protocol, 763);
+        assert_eq!(version.name, "1.20.1");
+    }
+
+    #[test]
+    fn status_holds_at_most_12_players() {
+        let players = (0..20).map(|i| (format!("Player{}", i), i)).collect();
+        let status = StatusBuilder::new(763)
+            .motd("§aA server")
+            .max_players(20)
+            .players(20, players)
+            .secure_chat(true, false)
+            .build()
+            .unwrap();
+        let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+
+        assert_eq!(status["description"]["text"], "§aA server");
+        assert_eq!(status["players"]["online"], 20);
+        assert_eq!(status["players"]["sample"].as_array().unwrap().len(), 12);
+        assert_eq!(status["enforcesSecureChat"], true);
+        assert_eq!(status["previewsChat"], false);
+        assert!(status.get("favicon").is_none());
+    }
+
+    #[test]
+    fn json_motds_are_kept() {
+        let status = StatusBuilder::new(763)
+            .motd(r#"{"text":"A server","color":"gold"}"#)
+            .build()
+            .unwrap();
+        let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+        assert_eq!(status["description"]["color"], "gold");
+
+        // Not JSON after all
+        let status = StatusBuilder::new(763).motd("[A server").build().unwrap();
+        assert!(status.contains(r#""description":{"text":"[A server"}"#));
+    }
+
+    #[test]
+    fn png_dimensions_are_read_from_the_header() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&64u32.to_be_bytes());
+        png.extend_from_slice(&32u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png), Some((64, 32)));
+        assert_eq!(png_dimensions(b"GIF89a"), None);
+    }
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::{error, info};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::database::Database;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Periodically backs up the database, see `backup_interval` in the config
+#[derive(AutoGenName)]
+pub struct BackupSystem;
+
+#[async_trait]
+impl System for BackupSystem {
+    async fn run(&self, state: GlobalState) {
+        let config = &get_global_config().database;
+        if config.backup_interval == 0 {
+            return;
+        }
+
+        let dir = match state.world.resource::<Database>().await {
+            Ok(database) => database.backup_dir(&config.backup_dir),
+            Err(e) => {
+                error!("Not backing up the world: {}", e);
+                return;
+            }
+        };
+        info!(
+            "Backing up the world every {} minutes into {}",
+            config.backup_interval,
+            dir.display()
+        );
+
+        let mut interval = tokio::time::interval(Duration::from_secs(config.backup_interval * 60));
+        // The first tick completes immediately, there is nothing worth backing up at startup
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let backup = match state.world.resource::<Database>().await {
+                Ok(database) => database.backup_rotate(&dir, config.backup_keep).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = backup {
+                error!("Scheduled backup failed: {}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::database::Database;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Periodically persists the chunks modified since the last save, see `save_interval` in the
+/// config. The remaining ones are saved when the server shuts down. Also checks the space left
+/// on the database volume, see `min_free_space`.
+#[derive(AutoGenName)]
+pub struct ChunkSaveSystem;
+
+#[async_trait]
+impl System for ChunkSaveSystem {
+    async fn run(&self, state: GlobalState) {
+        let save_interval = get_global_config().database.save_interval.max(1);
+        let mut interval = tokio::time::interval(Duration::from_secs(save_interval));
+
+        loop {
+            interval.tick().await;
+            let database = match state.world.resource::<Database>().await {
+                Ok(database) => database,
+                Err(e) => {
+                    error!("Failed to save modified chunks: {}", e);
+                    continue;
+                }
+            };
+
+            // New chunks are refused while the disk is almost full, modified ones are still saved
+            if let Err(e) = database.check_disk_space().await {
+                warn!(
+                    "Unable to check the space left on the database volume: {}",
+                    e
+                );
+            }
+            match database.save_dirty().await {
+                Ok(0) => {}
+                Ok(saved) => debug!("Saved {} modified chunks", saved),
+                Err(e) => error!("Failed to save modified chunks: {}", e),
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
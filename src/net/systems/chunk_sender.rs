@@ -1,20 +1,23 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use ferrumc_codec::enc::NetEncode;
 use tokio::sync::RwLock;
 use tracing::{debug, error, warn};
 
-use crate::net::packets::incoming::client_info::ClientInfo;
-use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+use crate::net::packets::incoming::client_info::{clamp_view_distance, ClientInfo};
+use crate::net::packets::outgoing::chunk_data::ChunkData;
 use crate::net::packets::outgoing::set_center_chunk::SetCenterChunk;
+use crate::net::packets::outgoing::unload_chunk::UnloadChunk;
 use crate::net::systems::System;
+use crate::net::utils::packet_queue::PacketQueue;
 use crate::net::{Connection, ConnectionWrapper};
 use crate::state::GlobalState;
-use crate::utils::components::last_chunk_tx_pos::LastChunkTxPos;
+use crate::utils::components::loaded_chunks::LoadedChunks;
 use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
+use crate::world::dimension::{ChunkPos, Dimension};
 use ferrumc_macros::AutoGenName;
 
 pub const DEFAULT_CHUNK_RADIUS: i8 = 16;
@@ -38,12 +41,7 @@ impl System for ChunkSender {
             send_to.into_iter().for_each(|(entity_id, player)| {
                 debug!("Sending chunk to player: {}", player.get_username());
                 drop(player);
-                let state = state.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = ChunkSender::send_chunks_to_player(state, entity_id).await {
-                        error!("Failed to send chunk to player: {}", e);
-                    }
-                });
+                ChunkSender::send_chunks_in_background(state.clone(), entity_id);
             });
         }
     }
@@ -54,38 +52,40 @@ impl System for ChunkSender {
 }
 
 impl ChunkSender {
+    /// Send the chunks a player is missing once it entered another chunk
     pub async fn send_chunks_to_player_if_needed(
         state: GlobalState,
         entity_id: impl TryInto<usize>,
-        current_pos: (i32, i32),
+        current_chunk: (i32, i32),
     ) -> Result<()> {
         let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
 
-        let mut last_chunk_tx_pos = state
+        let center = state
             .world
-            .get_component_storage()
-            .get_mut_or_insert_with::<LastChunkTxPos>(entity_id, Default::default)
-            .await;
-
-        let client_info = state.world.get_component::<ClientInfo>(entity_id).await?;
-
-        let distance = last_chunk_tx_pos.distance_to(current_pos.0, current_pos.1);
-
-        if distance < (client_info.view_distance as f64 / 5f64) {
+            .get_component::<LoadedChunks>(entity_id)
+            .await
+            .ok()
+            .and_then(|loaded| loaded.center);
+        if center == Some(current_chunk) {
             return Ok(());
         }
 
-        last_chunk_tx_pos.set_last_chunk_tx_pos(current_pos.0, current_pos.1);
+        ChunkSender::send_chunks_in_background(state, entity_id);
+
+        Ok(())
+    }
 
-        let state_clone = state.clone();
+    /// Send the chunks a player is missing without waiting for them to be sent
+    pub fn send_chunks_in_background(state: GlobalState, entity_id: usize) {
         tokio::spawn(async move {
-            if let Err(e) = ChunkSender::send_chunks_to_player(state_clone, entity_id).await {
+            if let Err(e) = ChunkSender::send_chunks_to_player(state, entity_id).await {
                 error!("Failed to send chunk to player: {}", e);
             }
         });
-
-        Ok(())
     }
+
+    /// Center the client on the chunk of the player, unload the chunks that left its view
+    /// distance and send those that entered it, nearest first
     pub async fn send_chunks_to_player(
         state: GlobalState,
         entity_id: impl TryInto<usize>,
@@ -104,11 +104,13 @@ impl ChunkSender {
             .ok();
 
         let pos = c_pos.clone();
-        let view_distance: i8 = c_info
-            .as_ref()
-            .map_or(DEFAULT_CHUNK_RADIUS, |c| c.view_distance);
+        // Clients configured before joining haven't been clamped yet
+        let view_distance: i8 = c_info.as_ref().map_or(DEFAULT_CHUNK_RADIUS, |c| {
+            clamp_view_distance(c.view_distance, get_global_config().max_view_distance)
+        });
         let conn = c_conn.0.clone();
 
+        drop(c_info);
         drop(c_pos);
         drop(c_conn);
 
@@ -120,66 +122,88 @@ impl ChunkSender {
 
         drop(player);
 
-        ChunkSender::send_set_center_chunk(&pos, conn.clone()).await?;
-        ChunkSender::send_chunk_data_to_player(state.clone(), &pos, view_distance, conn.clone())
-            .await?;
+        let dimension = Player::dimension(entity_id, &state).await;
+        let center = (pos.x >> 4, pos.z >> 4);
+        let (previous_center, (loaded, unloaded)) = {
+            let mut loaded_chunks = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<LoadedChunks>(entity_id, Default::default)
+                .await;
+            let previous_center = loaded_chunks.center;
+            (
+                previous_center,
+                loaded_chunks.update(center, view_distance as i32),
+            )
+        };
+
+        let mut packet_queue = PacketQueue::new();
+        if previous_center != Some(center) {
+            packet_queue
+                .queue(SetCenterChunk::new(center.0, center.1))
+                .await?;
+        }
+        for (x, z) in &unloaded {
+            packet_queue.queue(UnloadChunk::new(*x, *z)).await?;
+        }
+        {
+            let conn_read = conn.read().await;
+            conn_read.send_packets(packet_queue).await?;
+        }
+
+        ChunkSender::send_chunk_data_to_player(state, entity_id, &dimension, loaded, conn).await?;
 
         Ok(())
     }
 
+    /// Send `chunks` of `dimension` in order, those that couldn't be sent are sent with the next
+    /// update
     async fn send_chunk_data_to_player(
         state: GlobalState,
-        pos: &Position,
-        player_view_distance: i8,
+        entity_id: usize,
+        dimension: &Dimension,
+        chunks: Vec<(i32, i32)>,
         conn: Arc<RwLock<Connection>>,
     ) -> Result<()> {
         let start = std::time::Instant::now();
 
-        let pos_x = pos.x;
-        let pos_z = pos.z;
-
-        let chunk_radius = player_view_distance as i32;
-
-        'x: for x in -chunk_radius..=chunk_radius {
-            for z in -chunk_radius..=chunk_radius {
-                let Ok(packet) =
-                    ChunkDataAndUpdateLight::new(state.clone(), (pos_x >> 4) + x, (pos_z >> 4) + z)
-                        .await
-                else {
-                    continue;
-                };
-                let conn_read = conn.read().await;
-                if let Err(e) = conn_read.send_packet(packet).await {
-                    warn!("Failed to send chunk to player: {} ; Cancelling.", e);
-                    break 'x;
+        let total = chunks.len();
+        let mut unsent = Vec::new();
+        let mut chunks = chunks.into_iter();
+        for (x, z) in chunks.by_ref() {
+            let pos = ChunkPos::new(x, z, dimension.clone());
+            let Ok(packet) = ChunkData::load_at(&state, &pos).await else {
+                unsent.push((x, z));
+                continue;
+            };
+            let conn_read = conn.read().await;
+            if let Err(e) = conn_read.send_packet(packet).await {
+                warn!("Failed to send chunk to player: {} ; Cancelling.", e);
+                unsent.push((x, z));
+                break;
+            }
+        }
+        unsent.extend(chunks);
+
+        if !unsent.is_empty() {
+            if let Ok(mut loaded_chunks) = state
+                .world
+                .get_component_storage()
+                .get_mut::<LoadedChunks>(entity_id)
+                .await
+            {
+                for chunk in &unsent {
+                    loaded_chunks.forget(*chunk);
                 }
             }
         }
 
-        // check the size of a single chunk and multiply it by the number of chunks sent
-        let sample_chunk =
-            ChunkDataAndUpdateLight::new(state.clone(), pos_x >> 4, pos_z >> 4).await?;
-        let mut vec = vec![];
-        sample_chunk.net_encode(&mut vec).await?;
-        let chunk_rad_axis = chunk_radius * 2 + 1;
         debug!(
-                "Send {}({}x{}) chunks to player in {:?}. Approximately {} kb of data (~{} kb per chunk)",
-                chunk_rad_axis * chunk_rad_axis,
-                chunk_rad_axis,
-                chunk_rad_axis,
-                start.elapsed(),
-                vec.len() as i32 * chunk_rad_axis * chunk_rad_axis / 1024,
-                vec.len() as i32 / 1024
-            );
-
-        Ok(())
-    }
-    async fn send_set_center_chunk(pos: &Position, conn: Arc<RwLock<Connection>>) -> Result<()> {
-        let packet = SetCenterChunk::new(pos.x >> 4, pos.z >> 4);
-
-        let read_guard = conn.read().await;
-
-        read_guard.send_packet(packet).await?;
+            "Sent {} chunks to player in {:?}, {} couldn't be sent",
+            total,
+            start.elapsed(),
+            unsent.len()
+        );
 
         Ok(())
     }
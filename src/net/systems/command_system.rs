@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::outgoing::commands::Commands;
+use crate::net::systems::System;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+
+/// Sends the command tree to every player whenever the
+/// [`CommandRegistry`](crate::events::command_events::registry::CommandRegistry) changes,
+/// joining players get it with the login
+#[derive(AutoGenName)]
+pub struct CommandSystem;
+
+#[async_trait]
+impl System for CommandSystem {
+    async fn run(&self, state: GlobalState) {
+        loop {
+            state.commands.changed().await;
+            debug!("The commands changed, sending them again");
+            if let Err(e) = broadcast(Commands::from_registry(&state.commands), &state).await {
+                warn!("Failed to send the commands: {}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -29,6 +29,10 @@ impl ConnectionHandler {
             let (stream, _) = state.server_stream.accept().await?;
             debug!("Accepted connection from {:?}", stream.peer_addr()?);
             let addy = stream.peer_addr()?;
+            if state.rate_limits.refuse(addy.ip()) {
+                debug!("Refused connection from banned address {}", addy.ip());
+                continue;
+            }
             tokio::task::spawn(
                 Self::handle_connection(state.clone(), stream)
                     .instrument(info_span!("conn", %addy).or_current()),
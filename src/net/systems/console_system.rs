@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::io::BufRead;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::database::Database;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+
+/// Runs the commands typed in the server console
+#[derive(AutoGenName)]
+pub struct ConsoleSystem;
+
+#[async_trait]
+impl System for ConsoleSystem {
+    async fn run(&self, state: GlobalState) {
+        // Reading stdin blocks, a plain thread keeps it from holding the runtime on shutdown
+        let (tx, mut rx) = mpsc::channel::<String>(8);
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.blocking_send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(line) = rx.recv().await {
+            run_command(line.trim(), &state).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+async fn run_command(command: &str, state: &GlobalState) {
+    if let Some(path) = command.strip_prefix("import ") {
+        import_chunk(path.trim(), state).await;
+        return;
+    }
+    match command {
+        "" => {}
+        "compact" => {
+            info!("Compacting the database, chunk loading pauses until it's done...");
+            let report = match state.world.resource::<Database>().await {
+                Ok(database) => database.compact().await,
+                Err(e) => Err(e),
+            };
+            match report {
+                Ok(report) => info!(
+                    "Database compacted, {} KiB reclaimed",
+                    report.reclaimed() / 1024
+                ),
+                Err(e) => error!("Database compaction failed: {}", e),
+            }
+        }
+        "help" => info!("Available commands: compact, import <chunk.json>, help"),
+        _ => warn!(
+            "Unknown command \"{}\", type \"help\" for the list of commands",
+            command
+        ),
+    }
+}
+
+async fn import_chunk(path: &str, state: &GlobalState) {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            error!("Could not open {}: {}", path, e);
+            return;
+        }
+    };
+    let imported = match state.world.resource::<Database>().await {
+        Ok(database) => {
+            database
+                .import_chunk_json(std::io::BufReader::new(file))
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match imported {
+        Ok(pos) => info!("Imported chunk {} from {}", pos, path),
+        Err(e) => error!("Could not import {}: {}", path, e),
+    }
+}
@@ -0,0 +1,237 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, warn};
+
+use crate::net::packets::outgoing::entity_metadata::EntityMetadataBuilder;
+use crate::net::packets::outgoing::entity_movement::EntityMovement;
+use crate::net::packets::outgoing::remove_entities::RemoveEntities;
+use crate::net::packets::outgoing::set_equipment::SetEquipment;
+use crate::net::packets::outgoing::set_head_rotation::SetHeadRotation;
+use crate::net::packets::outgoing::spawn_player::SpawnPlayer;
+use crate::net::utils::broadcast::TRACKING_RANGE;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::components::grounded::Grounded;
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::last_broadcast::LastBroadcast;
+use crate::utils::components::movement::MovementTracker;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Spawns the players coming within [`TRACKING_RANGE`] blocks of each player on its client, and
+/// removes those moving out of range or leaving. Sends the trackers of each player how it moved.
+///
+/// Ranges are checked every tick of the [`GameTickSystem`](super::game_tick_system::GameTickSystem)
+/// rather than on each move, so the cost only grows with the number of players. Only players
+/// with [`TrackedEntities`] take part, they get it once the others were sent their profile in
+/// the tab list.
+pub struct EntityTracker;
+
+impl EntityTracker {
+    /// Send the spawns and removals of the players entering and leaving the view of each player
+    pub async fn track(state: &GlobalState) -> Result<()> {
+        let mut players = Vec::new();
+        let mut trackers: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut query = state
+            .world
+            .query::<(&Player, &Position, &TrackedEntities)>();
+        while let Some((id, (_, position, tracked))) = query.next().await {
+            players.push((id, position.x, position.z));
+            for &entity in &tracked.entities {
+                trackers.entry(entity).or_default().push(id);
+            }
+        }
+
+        // Before spawning anyone, so the new trackers start from the broadcast positions
+        for &(entity, _, _) in &players {
+            let viewers = trackers.get(&entity).map_or(&[][..], Vec::as_slice);
+            if let Err(e) = Self::broadcast_movement(entity, viewers, state).await {
+                debug!("Failed to broadcast the movement of {}: {}", entity, e);
+            }
+        }
+
+        for &(viewer, x, z) in &players {
+            let visible: HashSet<usize> = players
+                .iter()
+                .filter(|&&(other, other_x, other_z)| {
+                    other != viewer
+                        && (other_x - x).abs() <= TRACKING_RANGE
+                        && (other_z - z).abs() <= TRACKING_RANGE
+                })
+                .map(|&(other, _, _)| other)
+                .collect();
+            let (spawned, removed) = {
+                let Ok(mut tracked) = state
+                    .world
+                    .get_component_mut::<TrackedEntities>(viewer)
+                    .await
+                else {
+                    continue;
+                };
+                tracked.update(visible)
+            };
+            if spawned.is_empty() && removed.is_empty() {
+                continue;
+            }
+            if let Err(e) = Self::send_changes(viewer, &spawned, &removed, state).await {
+                debug!("Failed to update the entities seen by {}: {}", viewer, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Send how `entity` moved since the last tick to the players tracking it
+    async fn broadcast_movement(
+        entity: usize,
+        viewers: &[usize],
+        state: &GlobalState,
+    ) -> Result<()> {
+        let now = Self::broadcast_position(entity, state).await?;
+        let last = {
+            let mut last = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<LastBroadcast>(entity, || now)
+                .await;
+            std::mem::replace(&mut *last, now)
+        };
+        if viewers.is_empty() {
+            return Ok(());
+        }
+        let on_ground = state
+            .world
+            .get_component::<Grounded>(entity)
+            .await
+            .is_ok_and(|grounded| grounded.is_grounded);
+        let Some(movement) = EntityMovement::between(entity as i32, &last, &now, on_ground) else {
+            return Ok(());
+        };
+
+        let mut bytes = Vec::new();
+        movement.net_encode(&mut bytes).await?;
+        if now.yaw != last.yaw {
+            SetHeadRotation::new_auto(VarInt::from(entity as i32), now.yaw)
+                .net_encode(&mut bytes)
+                .await?;
+        }
+        for &viewer in viewers {
+            let Ok(conn) = state.connections.get_connection(viewer as u32) else {
+                continue;
+            };
+            let conn = conn.read().await;
+            if let Err(e) = conn.send_packet(bytes.clone()).await {
+                warn!(
+                    "Failed to send the movement of {} to {}: {}",
+                    entity, viewer, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Where the players tracking `entity` see it, its last accepted move
+    async fn broadcast_position(entity: usize, state: &GlobalState) -> Result<LastBroadcast> {
+        let component_storage = state.world.get_component_storage();
+        let position = component_storage.get::<Position>(entity).await?.clone();
+        let exact = component_storage
+            .get::<MovementTracker>(entity)
+            .await
+            .ok()
+            .and_then(|tracker| tracker.last())
+            .unwrap_or((
+                position.x as f64 + 0.5,
+                position.y as f64,
+                position.z as f64 + 0.5,
+            ));
+        let rotation = component_storage
+            .get::<Rotation>(entity)
+            .await
+            .map(|rotation| rotation.clone())
+            .unwrap_or(Rotation::new(0.0, 0.0));
+        Ok(LastBroadcast::new(exact, rotation.yaw, rotation.pitch))
+    }
+
+    async fn send_changes(
+        viewer: usize,
+        spawned: &[usize],
+        removed: &[usize],
+        state: &GlobalState,
+    ) -> Result<()> {
+        let mut packet_queue = PacketQueue::new();
+        if !removed.is_empty() {
+            let removed: Vec<i32> = removed.iter().map(|&id| id as i32).collect();
+            packet_queue.queue(RemoveEntities::new(&removed)).await?;
+        }
+        for &entity in spawned {
+            // Left in the meantime, its removal is sent on the next tick
+            if let Err(e) = Self::queue_spawn(entity, &mut packet_queue, state).await {
+                debug!("Not spawning entity {} for {}: {}", entity, viewer, e);
+            }
+        }
+
+        let conn = state.connections.get_connection(viewer as u32)?;
+        let conn = conn.read().await;
+        conn.send_packets(packet_queue).await
+    }
+
+    /// Queue the packets spawning the player `entity`, facing where it looks, with its metadata
+    /// and what it holds and wears
+    async fn queue_spawn(
+        entity: usize,
+        packet_queue: &mut PacketQueue,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let component_storage = state.world.get_component_storage();
+        let uuid = component_storage.get::<Player>(entity).await?.uuid;
+        // Later moves are relative to it
+        let broadcast = match component_storage.get::<LastBroadcast>(entity).await {
+            Ok(broadcast) => *broadcast,
+            Err(_) => Self::broadcast_position(entity, state).await?,
+        };
+        let (x, y, z) = broadcast.position();
+        let metadata = component_storage
+            .get::<EntityMetadataBuilder>(entity)
+            .await
+            .map(|metadata| metadata.full(entity as i32))
+            .ok();
+        let held = component_storage
+            .get::<HeldItem>(entity)
+            .await
+            .map_or(0, |held_item| held_item.slot);
+        let equipment = component_storage
+            .get::<Inventory>(entity)
+            .await
+            .ok()
+            .and_then(|inventory| SetEquipment::of_inventory(entity as i32, &inventory, held));
+
+        packet_queue
+            .queue(SpawnPlayer::new_auto(
+                VarInt::from(entity as i32),
+                uuid,
+                x,
+                y,
+                z,
+                broadcast.yaw,
+                broadcast.pitch,
+            ))
+            .await?;
+        packet_queue
+            .queue(SetHeadRotation::new_auto(
+                VarInt::from(entity as i32),
+                broadcast.yaw,
+            ))
+            .await?;
+        if let Some(metadata) = metadata {
+            packet_queue.queue(metadata).await?;
+        }
+        if let Some(equipment) = equipment {
+            packet_queue.queue(equipment).await?;
+        }
+        Ok(())
+    }
+}
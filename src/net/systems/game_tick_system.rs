@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use tracing::error;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::database::Database;
+use crate::ecs::resources::{Res, ResMut};
+use crate::ecs::scheduler::{Schedule, Scheduler, SystemDescriptor};
+use crate::ecs::tick_driver::TickDriver;
+use crate::net::packets::incoming::client_info::ClientInfo;
+use crate::net::systems::entity_tracker::EntityTracker;
+use crate::net::systems::time_system::TimeSystem;
+use crate::net::systems::System;
+use crate::net::utils::block_changes::BlockChangeBatcher;
+use crate::net::utils::digging::DigTracker;
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::digging::Digging;
+use crate::utils::components::grounded::Grounded;
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::last_broadcast::LastBroadcast;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::tracked_entities::TrackedEntities;
+use crate::utils::config::Config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::tick_rate::{TickRate, TARGET_TPS};
+use crate::world::time::TimeOfDay;
+use crate::world::weather::Weather;
+
+/// Runs the systems of each game tick [`TARGET_TPS`] times per second, see [`Schedule`]
+///
+/// The time and weather advance first, then the digs, whose broken blocks are sent with the
+/// other block changes of the tick. The players in view are tracked alongside.
+#[derive(AutoGenName)]
+pub struct GameTickSystem;
+
+#[async_trait]
+impl System for GameTickSystem {
+    async fn run(&self, state: GlobalState) {
+        let schedule = match Self::schedule() {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                error!("Failed to schedule the game tick: {}", e);
+                return;
+            }
+        };
+        TickDriver::new(TARGET_TPS as u32)
+            .run(&schedule, state)
+            .await;
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl GameTickSystem {
+    fn schedule() -> Result<Schedule<GlobalState>> {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add_system(
+                SystemDescriptor::new("time", |state: GlobalState| async move {
+                    TimeSystem::tick(&state).await
+                })
+                .params::<(ResMut<TimeOfDay>, ResMut<Weather>, ResMut<TickRate>)>()
+                .queries::<(&Player, &ConnectionWrapper)>(),
+            )?
+            .add_system(
+                SystemDescriptor::new("digs", |state: GlobalState| async move {
+                    DigTracker::tick(&state).await
+                })
+                .after("time")
+                .params::<(Res<TimeOfDay>, Res<Database>, Res<BlockChangeBatcher>)>()
+                .reads::<HeldItem>()
+                .reads::<Inventory>()
+                .queries::<(&Player, &mut Digging)>(),
+            )?
+            .add_system(
+                SystemDescriptor::new("block_changes", |state: GlobalState| async move {
+                    let block_changes = state.world.resource_mut::<BlockChangeBatcher>().await?;
+                    block_changes.flush(&state).await
+                })
+                .after("digs")
+                .params::<(ResMut<BlockChangeBatcher>, Res<Config>)>()
+                .queries::<(&Player, &Position, &ConnectionWrapper)>()
+                .reads::<ClientInfo>(),
+            )?
+            .add_system(
+                SystemDescriptor::new("entity_tracker", |state: GlobalState| async move {
+                    EntityTracker::track(&state).await
+                })
+                .queries::<(&Player, &Position, &mut TrackedEntities)>()
+                .reads::<Grounded>()
+                .reads::<Rotation>()
+                .reads::<HeldItem>()
+                .reads::<Inventory>()
+                .writes::<LastBroadcast>(),
+            )?;
+        scheduler.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameTickSystem;
+
+    #[test]
+    fn digs_are_flushed_in_the_same_tick() {
+        let schedule = GameTickSystem::schedule().unwrap();
+        assert_eq!(
+            schedule.batches(),
+            [
+                vec!["time", "entity_tracker"],
+                vec!["digs"],
+                vec!["block_changes"]
+            ]
+        );
+    }
+}
@@ -5,10 +5,19 @@ use tracing::{debug_span, info, Instrument};
 use crate::state::GlobalState;
 use crate::utils::prelude::*;
 
+pub mod backup_system;
+pub mod chunk_save_system;
 pub mod chunk_sender;
+pub mod command_system;
 pub mod connection_handler;
-pub mod keep_alive_system;
+pub mod console_system;
+pub mod entity_tracker;
+pub mod game_tick_system;
+pub mod player_save_system;
+pub mod tab_list_system;
 pub mod tick_system;
+pub mod time_system;
+pub mod world_border_system;
 
 #[async_trait]
 pub trait System: Send + Sync {
@@ -19,9 +28,16 @@ pub trait System: Send + Sync {
 
 pub static ALL_SYSTEMS: &[&dyn System] = &[
     &tick_system::TickSystem,
-    &keep_alive_system::KeepAliveSystem,
     &chunk_sender::ChunkSender,
+    &game_tick_system::GameTickSystem,
+    &world_border_system::WorldBorderSystem,
+    &tab_list_system::TabListSystem,
+    &command_system::CommandSystem,
     &connection_handler::ConnectionHandler,
+    &backup_system::BackupSystem,
+    &chunk_save_system::ChunkSaveSystem,
+    &player_save_system::PlayerSaveSystem,
+    &console_system::ConsoleSystem,
 ];
 
 pub async fn start_all_systems(state: GlobalState) -> Result<()> {
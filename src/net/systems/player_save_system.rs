@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::database::players::PlayerData;
+use crate::database::Database;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+use crate::utils::components::abilities::Abilities;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::init;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Periodically saves the state of every connected player, see `save_interval` in the config.
+/// Players are also saved when they disconnect.
+#[derive(AutoGenName)]
+pub struct PlayerSaveSystem;
+
+#[async_trait]
+impl System for PlayerSaveSystem {
+    async fn run(&self, state: GlobalState) {
+        let save_interval = get_global_config().database.save_interval.max(1);
+        let mut interval = tokio::time::interval(Duration::from_secs(save_interval));
+        let mut query = state.world.query::<&Player>();
+
+        loop {
+            interval.tick().await;
+
+            let mut entities = Vec::new();
+            while let Some((entity, _)) = query.next().await {
+                entities.push(entity);
+            }
+            for entity in entities.iter() {
+                if let Err(e) = save_player(*entity, &state).await {
+                    warn!("Failed to save player {}: {}", entity, e);
+                }
+            }
+            debug!("Saved {} players", entities.len());
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// Save the state of the player `entity` into the database
+pub async fn save_player(entity: impl TryInto<usize>, state: &GlobalState) -> Result<()> {
+    let entity = entity.try_into().map_err(|_| Error::ConversionError)?;
+    let world = &state.world;
+
+    let uuid = world.get_component::<Player>(entity).await?.uuid;
+    let position = world.get_component::<Position>(entity).await?.clone();
+    let rotation = world.get_component::<Rotation>(entity).await?.clone();
+    let gamemode = match world.get_component::<Gamemode>(entity).await {
+        Ok(gamemode) => gamemode.mode,
+        Err(_) => init::DEFAULT_GAMEMODE,
+    };
+    let abilities = match world.get_component::<Abilities>(entity).await {
+        Ok(abilities) => abilities.flags,
+        Err(_) => 0,
+    };
+
+    let player = PlayerData {
+        uuid,
+        x: position.x as f64,
+        y: position.y as f64,
+        z: position.z as f64,
+        yaw: rotation.yaw,
+        pitch: rotation.pitch,
+        dimension: "overworld".to_string(),
+        gamemode,
+        abilities,
+        last_seen: PlayerData::now(),
+    };
+    state
+        .world
+        .resource::<Database>()
+        .await?
+        .save_player(&player)
+        .await
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::net::utils::tab_list::TabList;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Refreshes the header and footer of the tab list of every player, see
+/// [`TabList::refresh_header_footer`]
+///
+/// Disabled when both the `player_list.header` and `player_list.footer` configs are empty.
+#[derive(AutoGenName)]
+pub struct TabListSystem;
+
+#[async_trait]
+impl System for TabListSystem {
+    async fn run(&self, state: GlobalState) {
+        let config = get_global_config();
+        if config.player_list.header.is_empty() && config.player_list.footer.is_empty() {
+            debug!("The tab list has no header nor footer");
+            return;
+        }
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(config.player_list.refresh_ms.max(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = TabList::refresh_header_footer(config, &state).await {
+                warn!("Failed to refresh the tab list header and footer: {}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,83 @@
+use crate::ecs::resources::{Res, ResMut};
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::net::utils::broadcast::broadcast;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::tick_rate::TickRate;
+use crate::world::time::TimeOfDay;
+use crate::world::weather::Weather;
+
+/// Ticks between two broadcasts of the time, a second
+const TIME_BROADCAST_TICKS: i64 = 20;
+
+/// Advances the time of day and the weather every tick of the
+/// [`GameTickSystem`](super::game_tick_system::GameTickSystem), see
+/// [`TimeOfDay`](crate::world::time::TimeOfDay) and [`Weather`](crate::world::weather::Weather)
+///
+/// The time is sent to the players every second, the weather as soon as it changes.
+pub struct TimeSystem;
+
+impl TimeSystem {
+    pub async fn tick(state: &GlobalState) -> Result<()> {
+        let (time, mut weather, tick_rate) = state
+            .world
+            .fetch::<(ResMut<TimeOfDay>, ResMut<Weather>, ResMut<TickRate>)>()
+            .await?;
+        tick_rate.record();
+        time.tick();
+        let events = weather.tick(&mut rand::thread_rng());
+        let time_packet = (time.world_age() % TIME_BROADCAST_TICKS == 0).then(|| time.packet());
+        // Players joining meanwhile read them
+        drop((time, weather, tick_rate));
+
+        if !events.is_empty() {
+            let mut packet_queue = PacketQueue::new();
+            for event in events {
+                packet_queue.queue(event).await?;
+            }
+            broadcast(packet_queue, state).await?;
+        }
+        if let Some(time_packet) = time_packet {
+            broadcast(time_packet, state).await?;
+        }
+        Ok(())
+    }
+
+    /// Queue the time and weather of the world, for a player joining it
+    pub async fn queue_world_state(
+        packet_queue: &mut PacketQueue,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let (time, weather) = state
+            .world
+            .fetch::<(Res<TimeOfDay>, Res<Weather>)>()
+            .await?;
+        packet_queue.queue(time.packet()).await?;
+        let events: Vec<GameEvent> = weather.join_events();
+        for event in events {
+            packet_queue.queue(event).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeSystem;
+    use crate::tests::test_state;
+    use crate::world::tick_rate::TickRate;
+    use crate::world::time::TimeOfDay;
+
+    #[tokio::test]
+    async fn ticks_advance_the_resources_of_the_world() {
+        let state = test_state().await;
+        TimeSystem::tick(&state).await.unwrap();
+        TimeSystem::tick(&state).await.unwrap();
+
+        let time = state.world.resource::<TimeOfDay>().await.unwrap();
+        assert_eq!(time.world_age(), 2);
+        assert_eq!(time.time_of_day(), 2);
+        assert!(state.world.resource::<TickRate>().await.unwrap().tps() > 0.0);
+    }
+}
@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::net::utils::metadata::update_health;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::health::Health;
+use crate::utils::components::movement::MovementTracker;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Time between two hurts of the players beyond the border, half a second like vanilla
+/// players recovering from damage
+const DAMAGE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Hurts the players beyond the safe zone of the world border, see
+/// [`WorldBorder::damage`](crate::world::border::WorldBorder::damage)
+///
+/// Creative and spectator players aren't hurt. Disabled by the `world_border.damage` config.
+#[derive(AutoGenName)]
+pub struct WorldBorderSystem;
+
+#[async_trait]
+impl System for WorldBorderSystem {
+    async fn run(&self, state: GlobalState) {
+        if !get_global_config().world_border.damage {
+            debug!("World border damage is disabled");
+            return;
+        }
+        let mut interval = tokio::time::interval(DAMAGE_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = WorldBorderSystem::damage_players(&state).await {
+                warn!("Failed to hurt the players beyond the world border: {}", e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl WorldBorderSystem {
+    pub async fn damage_players(state: &GlobalState) -> Result<()> {
+        let mut damaged = Vec::new();
+        let mut query = state.world.query::<(&Player, &Position, &Health)>();
+        while let Some((id, (_, position, _))) = query.next().await {
+            let (x, _, z) = state
+                .world
+                .get_component::<MovementTracker>(id)
+                .await
+                .ok()
+                .and_then(|tracker| tracker.last())
+                .unwrap_or((position.x as f64 + 0.5, 0.0, position.z as f64 + 0.5));
+            let border = state.world_borders.get(&Player::dimension(id, state).await);
+            if let Some(damage) = border.damage(x, z) {
+                damaged.push((id, damage));
+            }
+        }
+
+        for (id, damage) in damaged {
+            // Creative and spectator
+            if state
+                .world
+                .get_component::<Gamemode>(id)
+                .await
+                .is_ok_and(|gamemode| gamemode.mode % 2 == 1)
+            {
+                continue;
+            }
+            {
+                let Ok(mut health) = state.world.get_component_mut::<Health>(id).await else {
+                    continue;
+                };
+                if health.damage(damage) {
+                    debug!("Entity {} died beyond the world border", id);
+                }
+            }
+            if let Err(e) = update_health(id as u32, state).await {
+                debug!("Failed to show the health of {}: {}", id, e);
+            }
+        }
+        Ok(())
+    }
+}
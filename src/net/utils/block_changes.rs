@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use ferrumc_codec::enc::NetEncode;
+use parking_lot::Mutex;
+use tracing::warn;
+
+use crate::net::packets::incoming::client_info::{clamp_view_distance, ClientInfo};
+use crate::net::packets::outgoing::block_update::BlockUpdate;
+use crate::net::packets::outgoing::section_blocks_update::SectionBlocksUpdate;
+use crate::net::systems::chunk_sender::DEFAULT_CHUNK_RADIUS;
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::Config;
+use crate::utils::encoding::position::Position;
+use crate::Result;
+
+/// Section coordinates, in sections
+type Section = (i32, i32, i32);
+/// Position of a block within its section
+type SectionBlock = (u8, u8, u8);
+
+/// Collects the blocks changed during a tick, to send them to the players at once
+///
+/// Changes are grouped by chunk section, a section with a single change is sent as a block
+/// update and the others as a section blocks update. Only the players that were sent the chunk
+/// of a section get it.
+#[derive(Debug, Default)]
+pub struct BlockChangeBatcher {
+    changes: Mutex<HashMap<Section, HashMap<SectionBlock, i32>>>,
+}
+
+impl BlockChangeBatcher {
+    /// Record the block at `location` becoming `state_id`, a later change of the same block
+    /// within the tick replaces it
+    pub fn record(&self, location: &Position, state_id: i32) {
+        let (x, y, z) = (location.x, location.y as i32, location.z);
+        let section = (x >> 4, y >> 4, z >> 4);
+        let block = ((x & 0xF) as u8, (y & 0xF) as u8, (z & 0xF) as u8);
+        self.changes
+            .lock()
+            .entry(section)
+            .or_default()
+            .insert(block, state_id);
+    }
+
+    /// Changes recorded since the last call, by section
+    fn take(&self) -> HashMap<Section, HashMap<SectionBlock, i32>> {
+        std::mem::take(&mut *self.changes.lock())
+    }
+
+    /// Send the changes recorded since the last flush to the players that have their chunks
+    pub async fn flush(&self, state: &GlobalState) -> Result<()> {
+        let changes = self.take();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let max_view_distance = state.world.resource::<Config>().await?.max_view_distance;
+        let mut viewers = Vec::new();
+        let mut query = state
+            .world
+            .query::<(&Player, &Position, &ConnectionWrapper)>();
+        while let Some((id, (_, position, conn))) = query.next().await {
+            let view_distance = state
+                .world
+                .get_component::<ClientInfo>(id)
+                .await
+                .map_or(DEFAULT_CHUNK_RADIUS, |info| {
+                    clamp_view_distance(info.view_distance, max_view_distance)
+                });
+            viewers.push((
+                (position.x >> 4, position.z >> 4),
+                view_distance as i32,
+                conn.0.clone(),
+            ));
+        }
+
+        for (section, blocks) in changes {
+            let bytes = encode_changes(section, &blocks).await?;
+            for ((center_x, center_z), view_distance, conn) in &viewers {
+                if (section.0 - center_x).abs() > *view_distance
+                    || (section.2 - center_z).abs() > *view_distance
+                {
+                    continue;
+                }
+                let conn = conn.read().await;
+                if let Err(e) = conn.send_packet(bytes.clone()).await {
+                    warn!("Failed to send block changes to {}: {}", conn.id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Block update of the only block changed in `section`, or a section blocks update of all of
+/// them
+async fn encode_changes(section: Section, blocks: &HashMap<SectionBlock, i32>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if let [(&(x, y, z), &state_id)] = blocks.iter().collect::<Vec<_>>()[..] {
+        let location = Position::new(
+            section.0 * 16 + x as i32,
+            (section.1 * 16 + y as i32) as i16,
+            section.2 * 16 + z as i32,
+        );
+        BlockUpdate::new(location, state_id)
+            .net_encode(&mut bytes)
+            .await?;
+    } else {
+        let blocks: Vec<(SectionBlock, i32)> = blocks
+            .iter()
+            .map(|(&block, &state_id)| (block, state_id))
+            .collect();
+        SectionBlocksUpdate::new(section, &blocks)
+            .net_encode(&mut bytes)
+            .await?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_changes, BlockChangeBatcher};
+    use crate::utils::encoding::position::Position;
+
+    #[tokio::test]
+    async fn changes_are_grouped_by_section() {
+        let batcher = BlockChangeBatcher::default();
+        batcher.record(&Position::new(17, -60, -1), 1);
+        batcher.record(&Position::new(0, 64, 0), 1);
+        batcher.record(&Position::new(15, 79, 15), 1);
+        // Replaces the first change of the block
+        batcher.record(&Position::new(0, 64, 0), 9);
+
+        let changes = batcher.take();
+        assert_eq!(changes.len(), 2);
+        let single = &changes[&(1, -4, -1)];
+        assert_eq!(single[&(1, 4, 15)], 1);
+        let several = &changes[&(0, 4, 0)];
+        assert_eq!(several.len(), 2);
+        assert_eq!(several[&(0, 0, 0)], 9);
+        assert!(batcher.take().is_empty());
+
+        let bytes = encode_changes((1, -4, -1), single).await.unwrap();
+        // A block update at 17, -60, -1
+        assert_eq!(bytes[1], 0x0A);
+        let location = u64::from_be_bytes(bytes[2..10].try_into().unwrap()) as i64;
+        assert_eq!(
+            (location >> 38, location << 52 >> 52, location << 26 >> 38),
+            (17, -60, -1)
+        );
+        let bytes = encode_changes((0, 4, 0), several).await.unwrap();
+        assert_eq!(bytes[1], 0x43);
+    }
+}
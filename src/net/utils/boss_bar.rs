@@ -0,0 +1,290 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use ferrumc_codec::enc::NetEncode;
+use rand::random;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::net::packets::outgoing::boss_bar::{
+    BossBarAction, BossBarColor, BossBarPacket, BossBarStyle,
+};
+use crate::net::packets::ConnectionId;
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::chat::ChatComponent;
+use crate::utils::components::player::Player;
+use crate::Result;
+
+/// Health of a boss bar within `0.0..=1.0`, an invalid health empties it
+pub fn clamp_health(health: f32) -> f32 {
+    if health.is_nan() {
+        0.0
+    } else {
+        health.clamp(0.0, 1.0)
+    }
+}
+
+/// Boss bar shown at the top of the screen of its subscribers
+///
+/// Subscribers are kept by uuid, so a player rejoining gets its bars back, see
+/// [`BossBarRegistry::resend`]. Each change only sends its own action to them.
+#[derive(Debug, Clone)]
+pub struct BossBar {
+    uuid: u128,
+    title: ChatComponent,
+    health: f32,
+    color: BossBarColor,
+    style: BossBarStyle,
+    flags: u8,
+    subscribers: HashSet<u128>,
+}
+
+impl BossBar {
+    pub fn new(title: impl Into<ChatComponent>, color: BossBarColor, style: BossBarStyle) -> Self {
+        Self {
+            uuid: random(),
+            title: title.into(),
+            health: 1.0,
+            color,
+            style,
+            flags: 0,
+            subscribers: HashSet::new(),
+        }
+    }
+
+    pub fn uuid(&self) -> u128 {
+        self.uuid
+    }
+
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    /// Whether the player `uuid` is shown the bar
+    pub fn is_subscribed(&self, uuid: u128) -> bool {
+        self.subscribers.contains(&uuid)
+    }
+
+    /// Show the bar to the player `entity_id`
+    pub async fn add_player(&mut self, entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        let uuid = state.world.get_component::<Player>(entity_id).await?.uuid;
+        if self.subscribers.insert(uuid) {
+            self.send_to(entity_id, self.add_packet(), state).await?;
+        }
+        Ok(())
+    }
+
+    /// Hide the bar from the player `entity_id`
+    pub async fn remove_player(
+        &mut self,
+        entity_id: ConnectionId,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let uuid = state.world.get_component::<Player>(entity_id).await?.uuid;
+        if self.subscribers.remove(&uuid) {
+            let packet = BossBarPacket::new(self.uuid, BossBarAction::Remove);
+            self.send_to(entity_id, packet, state).await?;
+        }
+        Ok(())
+    }
+
+    /// Fill the bar to `health`, clamped within `0.0..=1.0`
+    pub async fn set_health(&mut self, health: f32, state: &GlobalState) -> Result<()> {
+        let health = clamp_health(health);
+        if health == self.health {
+            return Ok(());
+        }
+        self.health = health;
+        self.broadcast(BossBarAction::UpdateHealth(health), state)
+            .await
+    }
+
+    pub async fn set_title(
+        &mut self,
+        title: impl Into<ChatComponent>,
+        state: &GlobalState,
+    ) -> Result<()> {
+        self.title = title.into();
+        self.broadcast(BossBarAction::UpdateTitle(self.title.clone()), state)
+            .await
+    }
+
+    pub async fn set_style(
+        &mut self,
+        color: BossBarColor,
+        style: BossBarStyle,
+        state: &GlobalState,
+    ) -> Result<()> {
+        if (color, style) == (self.color, self.style) {
+            return Ok(());
+        }
+        (self.color, self.style) = (color, style);
+        self.broadcast(BossBarAction::UpdateStyle { color, style }, state)
+            .await
+    }
+
+    /// Set the flags of the bar, see the flags of the
+    /// [boss bar packet](crate::net::packets::outgoing::boss_bar)
+    pub async fn set_flags(&mut self, flags: u8, state: &GlobalState) -> Result<()> {
+        if flags == self.flags {
+            return Ok(());
+        }
+        self.flags = flags;
+        self.broadcast(BossBarAction::UpdateFlags(flags), state)
+            .await
+    }
+
+    /// Packet showing the bar as it is now
+    fn add_packet(&self) -> BossBarPacket {
+        BossBarPacket::new(
+            self.uuid,
+            BossBarAction::Add {
+                title: self.title.clone(),
+                health: self.health,
+                color: self.color,
+                style: self.style,
+                flags: self.flags,
+            },
+        )
+    }
+
+    async fn send_to(
+        &self,
+        entity_id: ConnectionId,
+        packet: BossBarPacket,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(packet).await
+    }
+
+    /// Send `action` to the subscribers in the world
+    async fn broadcast(&self, action: BossBarAction, state: &GlobalState) -> Result<()> {
+        if self.subscribers.is_empty() {
+            return Ok(());
+        }
+        let mut bytes = Vec::new();
+        BossBarPacket::new(self.uuid, action)
+            .net_encode(&mut bytes)
+            .await?;
+
+        let mut connections = Vec::new();
+        let mut query = state.world.query::<(&Player, &ConnectionWrapper)>();
+        while let Some((_, (player, conn))) = query.next().await {
+            if self.subscribers.contains(&player.uuid) {
+                connections.push(conn.0.clone());
+            }
+        }
+        for conn in connections {
+            let conn = conn.read().await;
+            if let Err(e) = conn.send_packet(bytes.clone()).await {
+                warn!("Failed to update boss bar for {}: {}", conn.id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Boss bars of the server, by uuid
+#[derive(Debug, Default)]
+pub struct BossBarRegistry {
+    bars: DashMap<u128, Arc<Mutex<BossBar>>>,
+}
+
+impl BossBarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep `bar`, returns the handle its changes go through
+    pub fn register(&self, bar: BossBar) -> Arc<Mutex<BossBar>> {
+        let uuid = bar.uuid;
+        let bar = Arc::new(Mutex::new(bar));
+        self.bars.insert(uuid, bar.clone());
+        bar
+    }
+
+    pub fn get(&self, uuid: u128) -> Option<Arc<Mutex<BossBar>>> {
+        self.bars.get(&uuid).map(|bar| bar.clone())
+    }
+
+    /// Forget the bar `uuid` and hide it from its subscribers
+    pub async fn unregister(&self, uuid: u128, state: &GlobalState) -> Result<()> {
+        let Some((_, bar)) = self.bars.remove(&uuid) else {
+            return Ok(());
+        };
+        let bar = bar.lock().await;
+        bar.broadcast(BossBarAction::Remove, state).await
+    }
+
+    /// Show the bars `entity_id` is subscribed to again, once the client forgot them on a
+    /// rejoin or a change of dimension
+    pub async fn resend(&self, entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        // Only players subscribe to bars
+        let Ok(uuid) = state
+            .world
+            .get_component::<Player>(entity_id)
+            .await
+            .map(|player| player.uuid)
+        else {
+            return Ok(());
+        };
+        // Not holding the map across the sends
+        let bars: Vec<_> = self.bars.iter().map(|bar| bar.value().clone()).collect();
+        for bar in bars {
+            let bar = bar.lock().await;
+            if bar.is_subscribed(uuid) {
+                bar.send_to(entity_id, bar.add_packet(), state).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::{clamp_health, BossBar};
+    use crate::net::packets::outgoing::boss_bar::{BossBarColor, BossBarStyle};
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::player::Player;
+
+    #[test]
+    fn health_is_clamped() {
+        assert_eq!(clamp_health(1.5), 1.0);
+        assert_eq!(clamp_health(-0.2), 0.0);
+        assert_eq!(clamp_health(f32::NAN), 0.0);
+        assert_eq!(clamp_health(0.25), 0.25);
+    }
+
+    #[tokio::test]
+    async fn subscribers_only_get_the_changes() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Player::new(42, "Steve".to_string()));
+
+        let mut bar = BossBar::new("Boss", BossBarColor::Red, BossBarStyle::Progress);
+        bar.add_player(entity_id, &state).await.unwrap();
+        assert!(bar.is_subscribed(42));
+        // Add action with the title and every field
+        let mut bytes = vec![0u8; 1 + 1 + 16 + 1 + 1 + 15 + 4 + 3];
+        client.read_exact(&mut bytes).await.unwrap();
+        assert_eq!(bytes[18], 0);
+
+        bar.set_health(3.0, &state).await.unwrap();
+        bar.set_health(0.5, &state).await.unwrap();
+        let mut bytes = vec![0u8; 1 + 1 + 16 + 1 + 4];
+        client.read_exact(&mut bytes).await.unwrap();
+        // Clamped to the full bar it already was, only the second change is sent
+        assert_eq!(bytes[18], 2);
+        assert_eq!(bytes[19..], 0.5f32.to_be_bytes());
+        assert_eq!(bar.health(), 0.5);
+    }
+}
@@ -0,0 +1,131 @@
+use ferrumc_codec::enc::NetEncode;
+use tracing::warn;
+
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::encoding::position::Position;
+use crate::Result;
+
+/// Players see each other within this many blocks, horizontally
+pub const TRACKING_RANGE: i32 = 48;
+
+/// Send a packet to every player in the world
+///
+/// The packet is encoded once, a player whose connection fails is skipped.
+pub async fn broadcast(packet: impl NetEncode, state: &GlobalState) -> Result<()> {
+    let mut bytes = Vec::new();
+    packet.net_encode(&mut bytes).await?;
+
+    let mut query = state.world.query::<(&Player, &ConnectionWrapper)>();
+    while let Some((_, (player, conn))) = query.next().await {
+        let conn = conn.0.read().await;
+        if let Err(e) = conn.send_packet(bytes.clone()).await {
+            warn!("Failed to broadcast packet to {}: {}", player.username, e);
+        }
+    }
+    Ok(())
+}
+
+/// Send a packet to every player in the world but `entity_id`
+pub async fn broadcast_except(
+    packet: impl NetEncode,
+    entity_id: usize,
+    state: &GlobalState,
+) -> Result<()> {
+    let mut bytes = Vec::new();
+    packet.net_encode(&mut bytes).await?;
+
+    let mut query = state.world.query::<(&Player, &ConnectionWrapper)>();
+    while let Some((id, (player, conn))) = query.next().await {
+        if id == entity_id {
+            continue;
+        }
+        let conn = conn.0.read().await;
+        if let Err(e) = conn.send_packet(bytes.clone()).await {
+            warn!("Failed to broadcast packet to {}: {}", player.username, e);
+        }
+    }
+    Ok(())
+}
+
+/// Players that can see `entity_id`, every other player within [`TRACKING_RANGE`] blocks
+///
+/// A naive scan of all the players. They all play in the overworld for now, so there is no
+/// dimension to compare.
+pub async fn trackers(entity_id: usize, state: &GlobalState) -> Result<Vec<usize>> {
+    let position = state
+        .world
+        .get_component::<Position>(entity_id)
+        .await?
+        .clone();
+
+    let mut trackers = Vec::new();
+    let mut query = state.world.query::<(&Player, &Position)>();
+    while let Some((id, (_, other))) = query.next().await {
+        if id != entity_id
+            && (other.x - position.x).abs() <= TRACKING_RANGE
+            && (other.z - position.z).abs() <= TRACKING_RANGE
+        {
+            trackers.push(id);
+        }
+    }
+    Ok(trackers)
+}
+
+/// Send a packet to the players tracking `entity_id`, but not to the entity itself
+pub async fn broadcast_to_trackers(
+    packet: impl NetEncode,
+    entity_id: usize,
+    state: &GlobalState,
+) -> Result<()> {
+    let trackers = trackers(entity_id, state).await?;
+    if trackers.is_empty() {
+        return Ok(());
+    }
+    let mut bytes = Vec::new();
+    packet.net_encode(&mut bytes).await?;
+
+    for tracker in trackers {
+        let Ok(conn) = state
+            .world
+            .get_component::<ConnectionWrapper>(tracker)
+            .await
+        else {
+            continue;
+        };
+        let conn = conn.0.read().await;
+        if let Err(e) = conn.send_packet(bytes.clone()).await {
+            warn!("Failed to send packet to tracker {}: {}", tracker, e);
+        }
+    }
+    Ok(())
+}
+
+/// Send a packet to the players within `radius` blocks of `x, y, z`
+pub async fn broadcast_near(
+    packet: impl NetEncode,
+    (x, y, z): (f64, f64, f64),
+    radius: f64,
+    state: &GlobalState,
+) -> Result<()> {
+    let mut bytes = Vec::new();
+    packet.net_encode(&mut bytes).await?;
+
+    let mut query = state
+        .world
+        .query::<(&Player, &Position, &ConnectionWrapper)>();
+    while let Some((_, (player, position, conn))) = query.next().await {
+        let dx = position.x as f64 + 0.5 - x;
+        let dy = position.y as f64 - y;
+        let dz = position.z as f64 + 0.5 - z;
+        if dx * dx + dy * dy + dz * dz > radius * radius {
+            continue;
+        }
+        let conn = conn.0.read().await;
+        if let Err(e) = conn.send_packet(bytes.clone()).await {
+            warn!("Failed to send packet to {}: {}", player.username, e);
+        }
+    }
+    Ok(())
+}
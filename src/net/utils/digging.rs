@@ -0,0 +1,150 @@
+use tracing::debug;
+
+use crate::events::block_events::{block_at, replace_block};
+use crate::net::packets::outgoing::set_block_destroy_stage::SetBlockDestroyStage;
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::components::digging::{Digging, FINISH_TOLERANCE};
+use crate::utils::components::held_item::HeldItem;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::player::Player;
+use crate::utils::encoding::position::Position;
+use crate::world::conversions::block_state;
+use crate::world::hardness::break_ticks;
+use crate::world::items::item_name;
+use crate::world::time::TimeOfDay;
+use crate::Result;
+
+/// Follows the blocks survival players dig, see [`Digging`]
+///
+/// The break time of a block comes from its hardness and the held tool. The cracks are shown
+/// to the players tracking the digger as the dig progresses, the digger draws its own. The
+/// block breaks once dug long enough, or when the client finishes a dig it is close to.
+pub struct DigTracker;
+
+impl DigTracker {
+    /// Name of the item `entity_id` holds in its main hand
+    async fn held_item(entity_id: u32, state: &GlobalState) -> Option<&'static str> {
+        let slot = state
+            .world
+            .get_component::<HeldItem>(entity_id)
+            .await
+            .map_or(0, |held_item| held_item.slot);
+        let inventory = state
+            .world
+            .get_component::<Inventory>(entity_id)
+            .await
+            .ok()?;
+        let item = inventory.hotbar(slot).item.as_ref()?;
+        item_name(item.item_id.get_val())
+    }
+
+    /// Start digging `location`, a block broken at once breaks right away
+    pub async fn start(entity_id: u32, location: &Position, state: &GlobalState) -> Result<()> {
+        Self::cancel(entity_id, state).await?;
+
+        let block = block_at(state, location).await?;
+        let Some(name) = block_state(block).map(|block| block.name.as_str()) else {
+            return Ok(());
+        };
+        let held = Self::held_item(entity_id, state).await;
+        match break_ticks(name, held) {
+            None => debug!("Entity {} started digging unbreakable {}", entity_id, name),
+            Some(0) => {
+                replace_block(state, location, 0, |block| block != 0).await?;
+            }
+            Some(ticks) => {
+                let now = state.world.resource::<TimeOfDay>().await?.world_age();
+                let digging = Digging::new(location.clone(), now, ticks);
+                state
+                    .world
+                    .get_component_storage()
+                    .insert(entity_id, digging);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the dig of `entity_id` and remove its cracks, if it is digging
+    pub async fn cancel(entity_id: u32, state: &GlobalState) -> Result<()> {
+        let location = {
+            let Ok(digging) = state.world.get_component::<Digging>(entity_id).await else {
+                return Ok(());
+            };
+            digging.location.clone()
+        };
+
+        state
+            .world
+            .get_component_storage()
+            .remove::<Digging>(entity_id as usize)?;
+        broadcast_to_trackers(
+            SetBlockDestroyStage::clear(entity_id as i32, location),
+            entity_id as usize,
+            state,
+        )
+        .await
+    }
+
+    /// Break the block the client finished digging, if the server saw it dig most of it
+    pub async fn finish(entity_id: u32, location: &Position, state: &GlobalState) -> Result<()> {
+        let now = state.world.resource::<TimeOfDay>().await?.world_age();
+        let accepted = state
+            .world
+            .get_component::<Digging>(entity_id)
+            .await
+            .is_ok_and(|digging| {
+                digging.location == *location && digging.progress(now) >= FINISH_TOLERANCE
+            });
+        if !accepted {
+            debug!(
+                "Entity {} finished digging {} too early",
+                entity_id, location
+            );
+            return Self::cancel(entity_id, state).await;
+        }
+        Self::break_block(entity_id, location, state).await
+    }
+
+    /// Remove the dig of `entity_id` and its cracks, then break its block
+    async fn break_block(entity_id: u32, location: &Position, state: &GlobalState) -> Result<()> {
+        Self::cancel(entity_id, state).await?;
+        replace_block(state, location, 0, |block| block != 0).await?;
+        Ok(())
+    }
+
+    /// Show the new stages of the digs to the trackers of the diggers, and break the blocks dug
+    /// long enough
+    pub async fn tick(state: &GlobalState) -> Result<()> {
+        let now = state.world.resource::<TimeOfDay>().await?.world_age();
+        let mut stages = Vec::new();
+        let mut done = Vec::new();
+        let mut query = state.world.query::<(&Player, &mut Digging)>();
+        while let Some((id, (_, mut digging))) = query.next().await {
+            if digging.is_done(now) {
+                done.push((id, digging.location.clone()));
+            } else if let Some(stage) = digging.next_stage(now) {
+                stages.push((id, digging.location.clone(), stage));
+            }
+        }
+
+        for (id, location, stage) in stages {
+            // Skip diggers leaving in the meantime
+            if let Err(e) = broadcast_to_trackers(
+                SetBlockDestroyStage::new(id as i32, location, stage),
+                id,
+                state,
+            )
+            .await
+            {
+                debug!("Failed to show the dig of {}: {}", id, e);
+            }
+        }
+        for (id, location) in done {
+            if let Err(e) = Self::break_block(id as u32, &location, state).await {
+                debug!("Failed to break the block dug by {}: {}", id, e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use tokio::task::AbortHandle;
+use tracing::{debug, trace, warn};
+
+use crate::net::kick_conn;
+use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::keep_alive::KeepAlive;
+use crate::Result;
+
+/// Time between two keep alives
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// Time a keep alive has to be answered in before the player is kicked
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time between two checks of the keep alives of a player
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Send keep alives to `conn_id` and kick it once one stays unanswered for too long
+///
+/// The task stops by itself once the connection is gone, and is aborted with the returned handle
+/// when the connection is dropped.
+pub fn spawn_keep_alive(conn_id: ConnectionId, state: GlobalState) -> AbortHandle {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = check_keep_alive(conn_id, &state).await {
+                debug!("Stopping the keep alives of entity {}: {}", conn_id, e);
+                break;
+            }
+        }
+    })
+    .abort_handle()
+}
+
+async fn check_keep_alive(conn_id: ConnectionId, state: &GlobalState) -> Result<()> {
+    let conn = state.connections.get_connection(conn_id)?;
+
+    let keep_alive_id = {
+        let mut keep_alive = state
+            .world
+            .get_component_storage()
+            .get_mut_or_insert_with::<KeepAlive>(conn_id, Default::default)
+            .await;
+        if keep_alive.timed_out(KEEP_ALIVE_TIMEOUT) {
+            None
+        } else if !keep_alive.awaiting && keep_alive.last_sent.elapsed() >= KEEP_ALIVE_INTERVAL {
+            Some(keep_alive.issue())
+        } else {
+            return Ok(());
+        }
+    };
+
+    match keep_alive_id {
+        Some(id) => {
+            trace!("Sending keep alive {} to entity {}", id, conn_id);
+            let conn = conn.read().await;
+            conn.send_packet(KeepAlivePacketOut::new_auto(id)).await
+        }
+        None => {
+            warn!("Entity {} didn't answer its keep alive", conn_id);
+            kick_conn(conn_id, "Timed out", state.clone()).await
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use ferrumc_codec::enc::NetEncode;
+
+use crate::net::packets::outgoing::entity_metadata::{
+    EntityMetadataBuilder, MetadataValue, HEALTH_INDEX,
+};
+use crate::net::packets::ConnectionId;
+use crate::net::utils::broadcast::broadcast_to_trackers;
+use crate::state::GlobalState;
+use crate::utils::components::health::Health;
+use crate::Result;
+
+/// Change the metadata of `entity_id` with `update`, and send the entries that changed to the
+/// entity and the players tracking it
+pub async fn update_metadata(
+    entity_id: ConnectionId,
+    state: &GlobalState,
+    update: impl FnOnce(&mut EntityMetadataBuilder),
+) -> Result<()> {
+    let changes = {
+        let mut metadata = state
+            .world
+            .get_component_storage()
+            .get_mut_or_insert_with::<EntityMetadataBuilder>(entity_id, Default::default)
+            .await;
+        update(&mut metadata);
+        metadata.take_changes(entity_id as i32)
+    };
+    let Some(changes) = changes else {
+        return Ok(());
+    };
+
+    let mut bytes = Vec::new();
+    changes.net_encode(&mut bytes).await?;
+    // Not every entity has a client
+    if let Ok(conn) = state.connections.get_connection(entity_id) {
+        let conn = conn.read().await;
+        conn.send_packet(bytes.clone()).await?;
+    }
+    broadcast_to_trackers(bytes, entity_id as usize, state).await
+}
+
+/// Send the whole metadata of `entity_id` to its own client, which drops it when respawning
+pub async fn resend_metadata(entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+    let Ok(metadata) = state
+        .world
+        .get_component::<EntityMetadataBuilder>(entity_id)
+        .await
+        .map(|metadata| metadata.full(entity_id as i32))
+    else {
+        return Ok(());
+    };
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(metadata).await
+}
+
+/// Show the current health of `entity_id` in its metadata
+pub async fn update_health(entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+    let Ok(health) = state
+        .world
+        .get_component::<Health>(entity_id)
+        .await
+        .map(|health| health.health)
+    else {
+        return Ok(());
+    };
+    update_metadata(entity_id, state, |metadata| {
+        metadata.set(HEALTH_INDEX, MetadataValue::Float(health));
+    })
+    .await
+}
@@ -1 +1,12 @@
+pub mod block_changes;
+pub mod boss_bar;
+pub mod broadcast;
+pub mod digging;
+pub mod keep_alive;
+pub mod metadata;
 pub mod packet_queue;
+pub mod ping;
+pub mod sidebar;
+pub mod sound;
+pub mod tab_list;
+pub mod title;
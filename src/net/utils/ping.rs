@@ -0,0 +1,18 @@
+use crate::net::packets::outgoing::play_ping::PlayPing;
+use crate::state::GlobalState;
+use crate::utils::components::latency::Latency;
+use crate::Result;
+
+/// Send a play ping to `entity_id`, its pong updates the [`Latency`] of the player
+pub async fn send_ping(entity_id: u32, state: &GlobalState) -> Result<()> {
+    let id = state
+        .world
+        .get_component_storage()
+        .get_mut_or_insert_with::<Latency>(entity_id, Default::default)
+        .await
+        .issue();
+
+    let conn = state.connections.get_connection(entity_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(PlayPing::new(id)).await
+}
@@ -0,0 +1,177 @@
+use ferrumc_macros::Component;
+
+use crate::net::packets::outgoing::display_objective::{DisplayObjective, SIDEBAR_SLOT};
+use crate::net::packets::outgoing::update_objectives::{RenderType, UpdateObjectives};
+use crate::net::packets::outgoing::update_score::{UpdateScore, MAX_SCORE_HOLDER_LENGTH};
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::net::Connection;
+use crate::utils::chat::{truncate, ChatComponent};
+use crate::Result;
+
+/// Most lines shown by a sidebar
+pub const MAX_SIDEBAR_LINES: usize = 15;
+
+/// Sidebar shown on the right of the screen of a player, line by line from the top
+///
+/// Each player is sent its own sidebar, so players can be shown different content, keep one
+/// per player as a component. Lines are the entries of a sidebar objective, ending with an
+/// invisible formatting code unique to the line so that lines can repeat. Changing the lines
+/// only sends the scores of the lines that changed.
+#[derive(Debug, Clone, Component)]
+pub struct Sidebar {
+    objective: String,
+    title: ChatComponent,
+    lines: Vec<String>,
+}
+
+impl Sidebar {
+    pub fn new(objective: &str, title: impl Into<ChatComponent>) -> Self {
+        Self {
+            objective: objective.to_string(),
+            title: title.into(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Show the sidebar with its current lines on the client of `conn`
+    pub async fn show(&self, conn: &Connection) -> Result<()> {
+        let mut packet_queue = PacketQueue::new();
+        packet_queue
+            .queue(UpdateObjectives::create(
+                &self.objective,
+                self.title.clone(),
+                RenderType::Integer,
+            ))
+            .await?;
+        for (index, line) in self.lines.iter().enumerate() {
+            packet_queue
+                .queue(UpdateScore::update(
+                    &entry(index, line),
+                    &self.objective,
+                    score(index),
+                ))
+                .await?;
+        }
+        packet_queue
+            .queue(DisplayObjective::new(SIDEBAR_SLOT, &self.objective))
+            .await?;
+        conn.send_packets(packet_queue).await
+    }
+
+    /// Remove the sidebar from the client of `conn`
+    pub async fn hide(&self, conn: &Connection) -> Result<()> {
+        conn.send_packet(UpdateObjectives::remove(&self.objective))
+            .await
+    }
+
+    pub async fn set_title(
+        &mut self,
+        title: impl Into<ChatComponent>,
+        conn: &Connection,
+    ) -> Result<()> {
+        self.title = title.into();
+        conn.send_packet(UpdateObjectives::update(
+            &self.objective,
+            self.title.clone(),
+            RenderType::Integer,
+        ))
+        .await
+    }
+
+    /// Replace the lines, past [`MAX_SIDEBAR_LINES`] they are dropped
+    pub async fn set_lines(&mut self, mut lines: Vec<String>, conn: &Connection) -> Result<()> {
+        lines.truncate(MAX_SIDEBAR_LINES);
+        let (removed, updated) = self.changes(&lines);
+        self.lines = lines;
+
+        let mut packet_queue = PacketQueue::new();
+        for entry in removed {
+            packet_queue
+                .queue(UpdateScore::remove(&entry, &self.objective))
+                .await?;
+        }
+        for (entry, score) in updated {
+            packet_queue
+                .queue(UpdateScore::update(&entry, &self.objective, score))
+                .await?;
+        }
+        conn.send_packets(packet_queue).await
+    }
+
+    /// Replace the line at `index`, adding empty lines up to it
+    pub async fn set_line(
+        &mut self,
+        index: usize,
+        text: impl Into<String>,
+        conn: &Connection,
+    ) -> Result<()> {
+        let mut lines = self.lines.clone();
+        if index >= lines.len() {
+            lines.resize(index + 1, String::new());
+        }
+        lines[index] = text.into();
+        self.set_lines(lines, conn).await
+    }
+
+    /// Entries to remove, and entries to add with their score, to go from the current lines to
+    /// `lines`
+    fn changes(&self, lines: &[String]) -> (Vec<String>, Vec<(String, i32)>) {
+        let mut removed = Vec::new();
+        let mut updated = Vec::new();
+        for index in 0..self.lines.len().max(lines.len()) {
+            let old = self.lines.get(index);
+            let new = lines.get(index);
+            if old == new {
+                continue;
+            }
+            if let Some(old) = old {
+                removed.push(entry(index, old));
+            }
+            if let Some(new) = new {
+                updated.push((entry(index, new), score(index)));
+            }
+        }
+        (removed, updated)
+    }
+}
+
+/// Entry of the line at `index`, the text followed by a formatting code unique to the line
+fn entry(index: usize, text: &str) -> String {
+    format!(
+        "{}§{:x}",
+        truncate(text, MAX_SCORE_HOLDER_LENGTH - 2),
+        index
+    )
+}
+
+/// Score of the line at `index`, sidebars sort their entries by descending score
+fn score(index: usize) -> i32 {
+    (MAX_SIDEBAR_LINES - index) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{entry, Sidebar};
+
+    #[test]
+    fn repeated_lines_get_unique_entries() {
+        assert_eq!(entry(0, ""), "§0");
+        assert_ne!(entry(1, "-----"), entry(2, "-----"));
+        assert_eq!(entry(14, &"x".repeat(50)).chars().count(), 40);
+    }
+
+    #[test]
+    fn only_changed_lines_are_sent() {
+        let mut sidebar = Sidebar::new("info", "Info");
+        sidebar.lines = vec!["Kills: 1".to_string(), "".to_string(), "Gold".to_string()];
+
+        let lines = vec!["Kills: 2".to_string(), "".to_string()];
+        let (removed, updated) = sidebar.changes(&lines);
+        assert_eq!(removed, vec![entry(0, "Kills: 1"), entry(2, "Gold")]);
+        assert_eq!(updated, vec![(entry(0, "Kills: 2"), 15)]);
+    }
+}
@@ -0,0 +1,71 @@
+use rand::random;
+
+use crate::net::packets::outgoing::sound_effect::SoundEffect;
+use crate::net::utils::broadcast::broadcast_near;
+use crate::state::GlobalState;
+use crate::utils::registries::{Sound, SoundCategory};
+use crate::Result;
+
+/// Play `sound` at `position` to the players within `radius` blocks of it
+///
+/// Every player hears the same variant of the sound.
+pub async fn play_sound(
+    sound: Sound,
+    category: SoundCategory,
+    position: (f64, f64, f64),
+    volume: f32,
+    pitch: f32,
+    radius: f64,
+    state: &GlobalState,
+) -> Result<()> {
+    let packet = SoundEffect::new(sound, category, position, volume, pitch, random());
+    broadcast_near(packet, position, radius, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::play_sound;
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::player::Player;
+    use crate::utils::encoding::position::Position;
+    use crate::utils::registries::{Sound, SoundCategory};
+
+    #[tokio::test]
+    async fn only_nearby_players_hear_sounds() {
+        let state = test_state().await;
+        let (near, mut near_client) = test_connection(&state).await;
+        let (far, mut far_client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(near, Player::new(1, "Near".to_string()))
+            .insert(near, Position::new(10, 64, 0))
+            .insert(far, Player::new(2, "Far".to_string()))
+            .insert(far, Position::new(100, 64, 0));
+
+        play_sound(
+            Sound::EntityGenericExplode,
+            SoundCategory::Block,
+            (0.0, 64.0, 0.0),
+            4.0,
+            1.0,
+            16.0,
+            &state,
+        )
+        .await
+        .unwrap();
+
+        let mut header = [0u8; 2];
+        near_client.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[1], 0x62);
+        let mut byte = [0u8; 1];
+        let nothing = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            far_client.read_exact(&mut byte),
+        )
+        .await;
+        assert!(nothing.is_err());
+    }
+}
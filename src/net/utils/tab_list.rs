@@ -0,0 +1,321 @@
+use ferrumc_macros::Component;
+use tracing::debug;
+
+use crate::net::packets::outgoing::player_info::{
+    PlayerInfoEntry, PlayerInfoRemove, PlayerInfoUpdate, ADD_PLAYER, UPDATE_GAMEMODE,
+    UPDATE_LATENCY, UPDATE_LISTED,
+};
+use crate::net::packets::outgoing::set_tab_list_header_footer::SetTabListHeaderFooter;
+use crate::net::packets::ConnectionId;
+use crate::net::utils::broadcast::{broadcast, broadcast_except};
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::chat::ChatComponent;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::latency::Latency;
+use crate::utils::components::player::Player;
+use crate::utils::components::profile::ProfileProperties;
+use crate::utils::config::{PlayerList, ServerConfig};
+use crate::world::tick_rate::TickRate;
+use crate::Result;
+
+/// Actions adding a player to the tab list with everything shown about it
+const JOIN_ACTIONS: u8 = ADD_PLAYER | UPDATE_GAMEMODE | UPDATE_LISTED | UPDATE_LATENCY;
+
+/// Values of the placeholders of the tab list header and footer, see [`PlayerList`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placeholders {
+    pub online: usize,
+    pub max: i32,
+    pub tps: f64,
+    /// Latency of the player the text is rendered for, in milliseconds
+    pub ping: i32,
+}
+
+impl Placeholders {
+    /// `template` with its placeholders replaced, the ticks per second with a decimal
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{online}", &self.online.to_string())
+            .replace("{max}", &self.max.to_string())
+            .replace("{tps}", &format!("{:.1}", self.tps))
+            .replace("{ping}", &self.ping.to_string())
+    }
+}
+
+/// Header and footer of the tab list last sent to a player, they are only sent again once
+/// their rendering changed
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct HeaderFooter {
+    pub header: String,
+    pub footer: String,
+}
+
+/// Keeps the tab list of every player in sync with the players in the world
+///
+/// Newly joined players are sent the whole list, everyone else only what changed.
+pub struct TabList;
+
+impl TabList {
+    /// Entry of `entity_id` in the tab list, from its profile, game mode and latency
+    pub async fn entry(entity_id: ConnectionId, state: &GlobalState) -> Result<PlayerInfoEntry> {
+        let (uuid, name) = {
+            let player = state.world.get_component::<Player>(entity_id).await?;
+            (player.uuid, player.username.clone())
+        };
+        let properties = state
+            .world
+            .get_component::<ProfileProperties>(entity_id)
+            .await
+            .map(|profile| profile.properties.clone())
+            .unwrap_or_default();
+        let gamemode = state
+            .world
+            .get_component::<Gamemode>(entity_id)
+            .await
+            .map(|gamemode| gamemode.mode)
+            .unwrap_or_default();
+        let latency = Self::latency(entity_id, state).await;
+
+        Ok(PlayerInfoEntry {
+            uuid,
+            name,
+            properties,
+            gamemode,
+            listed: true,
+            latency,
+            display_name: None,
+        })
+    }
+
+    /// Round trip time of `entity_id` in milliseconds, 0 until it is measured
+    async fn latency(entity_id: ConnectionId, state: &GlobalState) -> i32 {
+        state
+            .world
+            .get_component::<Latency>(entity_id)
+            .await
+            .ok()
+            .and_then(|latency| latency.rtt)
+            .map(|rtt| rtt.as_millis().min(i32::MAX as u128) as i32)
+            .unwrap_or_default()
+    }
+
+    /// Send the whole tab list to `entity_id`, and add it to the tab list of everyone else
+    pub async fn join(entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        let mut players = Vec::new();
+        let mut query = state.world.query::<&Player>();
+        while let Some((id, _)) = query.next().await {
+            players.push(id);
+        }
+
+        let mut entries = Vec::with_capacity(players.len());
+        for id in players {
+            // Skip players leaving in the meantime
+            if let Ok(entry) = Self::entry(id as ConnectionId, state).await {
+                entries.push(entry);
+            }
+        }
+        let own = Self::entry(entity_id, state).await?;
+        debug!(
+            "Sending the tab list of {} players to entity {}",
+            entries.len(),
+            entity_id
+        );
+
+        {
+            let conn = state.connections.get_connection(entity_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(PlayerInfoUpdate::new(JOIN_ACTIONS, entries))
+                .await?;
+        }
+        broadcast_except(
+            PlayerInfoUpdate::new(JOIN_ACTIONS, vec![own]),
+            entity_id as usize,
+            state,
+        )
+        .await
+    }
+
+    /// Remove the player `uuid` from the tab list of everyone, once it left the world
+    pub async fn leave(uuid: u128, state: &GlobalState) -> Result<()> {
+        broadcast(PlayerInfoRemove::new(vec![uuid]), state).await
+    }
+
+    /// Show the latest latency of `entity_id` to everyone
+    pub async fn update_latency(entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        // Only players are listed
+        let Ok(entry) = Self::entry(entity_id, state).await else {
+            return Ok(());
+        };
+        broadcast(PlayerInfoUpdate::new(UPDATE_LATENCY, vec![entry]), state).await
+    }
+
+    /// Show the current game mode of `entity_id` to everyone
+    pub async fn update_gamemode(entity_id: ConnectionId, state: &GlobalState) -> Result<()> {
+        // Only players are listed
+        let Ok(entry) = Self::entry(entity_id, state).await else {
+            return Ok(());
+        };
+        broadcast(PlayerInfoUpdate::new(UPDATE_GAMEMODE, vec![entry]), state).await
+    }
+
+    /// Render the header and footer of the player list of `config` for every player, and send
+    /// them to those whose rendering changed since they were last sent
+    ///
+    /// `{ping}` differs between players, so each player gets its own rendering.
+    pub async fn refresh_header_footer(config: &ServerConfig, state: &GlobalState) -> Result<()> {
+        let PlayerList { header, footer, .. } = &config.player_list;
+        if header.is_empty() && footer.is_empty() {
+            return Ok(());
+        }
+
+        let mut players = Vec::new();
+        let mut query = state.world.query::<(&Player, &ConnectionWrapper)>();
+        while let Some((id, (_, conn))) = query.next().await {
+            players.push((id, conn.0.clone()));
+        }
+        let mut placeholders = Placeholders {
+            online: players.len(),
+            max: config.max_players,
+            tps: state.world.resource::<TickRate>().await?.tps(),
+            ping: 0,
+        };
+
+        for (id, conn) in players {
+            placeholders.ping = Self::latency(id as ConnectionId, state).await;
+            let rendered = HeaderFooter {
+                header: placeholders.render(header),
+                footer: placeholders.render(footer),
+            };
+            if state
+                .world
+                .get_component::<HeaderFooter>(id)
+                .await
+                .is_ok_and(|sent| *sent == rendered)
+            {
+                continue;
+            }
+
+            {
+                let conn = conn.read().await;
+                if let Err(e) = conn
+                    .send_packet(SetTabListHeaderFooter::new(
+                        &ChatComponent::from(rendered.header.as_str()),
+                        &ChatComponent::from(rendered.footer.as_str()),
+                    ))
+                    .await
+                {
+                    debug!("Failed to send the tab list header to {}: {}", id, e);
+                    continue;
+                }
+            }
+            state.world.get_component_storage().insert(id, rendered);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncReadExt;
+
+    use super::{HeaderFooter, Placeholders, TabList};
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::gamemode::Gamemode;
+    use crate::utils::components::latency::Latency;
+    use crate::utils::components::player::Player;
+    use crate::utils::config::ServerConfig;
+
+    #[tokio::test]
+    async fn entries_show_the_game_mode_and_latency() {
+        let state = test_state().await;
+        let (entity_id, _client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Player::new(42, "Steve".to_string()))
+            .insert(entity_id, Gamemode::new(1));
+
+        let entry = TabList::entry(entity_id, &state).await.unwrap();
+        assert_eq!((entry.uuid, entry.name.as_str()), (42, "Steve"));
+        assert_eq!(entry.gamemode, 1);
+        // Not measured yet
+        assert_eq!(entry.latency, 0);
+        assert!(entry.properties.is_empty());
+
+        let mut latency = Latency::default();
+        latency.record(Duration::from_millis(150));
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, latency);
+        let entry = TabList::entry(entity_id, &state).await.unwrap();
+        assert_eq!(entry.latency, 150);
+    }
+
+    #[test]
+    fn placeholders_are_replaced() {
+        let placeholders = Placeholders {
+            online: 3,
+            max: 20,
+            tps: 19.96,
+            ping: 42,
+        };
+        assert_eq!(
+            placeholders.render("{online}/{max} online, {tps} TPS, {ping}ms {unknown}"),
+            "3/20 online, 20.0 TPS, 42ms {unknown}"
+        );
+    }
+
+    #[tokio::test]
+    async fn header_is_only_sent_once_changed() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Player::new(42, "Steve".to_string()));
+        let mut config = ServerConfig::default();
+        config.player_list.header = "Ping {ping}".to_string();
+
+        TabList::refresh_header_footer(&config, &state)
+            .await
+            .unwrap();
+        {
+            let sent = state
+                .world
+                .get_component::<HeaderFooter>(entity_id)
+                .await
+                .unwrap();
+            assert_eq!(sent.header, "Ping 0");
+            assert!(sent.footer.is_empty());
+        }
+
+        // Nothing changed, nothing is sent
+        TabList::refresh_header_footer(&config, &state)
+            .await
+            .unwrap();
+        let mut latency = Latency::default();
+        latency.record(Duration::from_millis(35));
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, latency);
+        TabList::refresh_header_footer(&config, &state)
+            .await
+            .unwrap();
+
+        let mut expected = Vec::new();
+        for header in [&br#"{"text":"Ping 0"}"#[..], br#"{"text":"Ping 35"}"#] {
+            expected.extend_from_slice(&[header.len() as u8 + 14, 0x65, header.len() as u8]);
+            expected.extend_from_slice(header);
+            expected.push(11);
+            expected.extend_from_slice(br#"{"text":""}"#);
+        }
+        let mut bytes = vec![0u8; expected.len()];
+        client.read_exact(&mut bytes).await.unwrap();
+        assert_eq!(bytes, expected);
+    }
+}
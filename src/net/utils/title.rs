@@ -0,0 +1,109 @@
+use crate::net::packets::outgoing::set_subtitle_text::SetSubtitleText;
+use crate::net::packets::outgoing::set_title_animation_times::SetTitleAnimationTimes;
+use crate::net::packets::outgoing::set_title_text::SetTitleText;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::utils::chat::ChatComponent;
+use crate::Result;
+
+/// Title shown in the middle of the screen of a player, see
+/// [`Connection::send_title`](crate::net::Connection::send_title)
+///
+/// The client shows the title as soon as it gets its text, so the times and the subtitle are
+/// sent before it. A subtitle alone isn't shown, it is left out without a title.
+#[derive(Debug, Clone, Default)]
+pub struct TitleBuilder {
+    title: Option<ChatComponent>,
+    subtitle: Option<ChatComponent>,
+    /// Fade in, stay and fade out, in ticks
+    times: Option<(i32, i32, i32)>,
+}
+
+impl TitleBuilder {
+    pub fn new(title: impl Into<ChatComponent>) -> Self {
+        Self::default().title(title)
+    }
+
+    pub fn title(mut self, title: impl Into<ChatComponent>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn subtitle(mut self, subtitle: impl Into<ChatComponent>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Ticks the title takes to fade in, stays for and takes to fade out, the client keeps its
+    /// last times otherwise
+    pub fn times(mut self, fade_in: i32, stay: i32, fade_out: i32) -> Self {
+        self.times = Some((fade_in, stay, fade_out));
+        self
+    }
+
+    /// Packets showing the title, in the order the client expects them
+    pub async fn packets(&self) -> Result<PacketQueue> {
+        let mut packet_queue = PacketQueue::new();
+        let Some(title) = &self.title else {
+            return Ok(packet_queue);
+        };
+        if let Some((fade_in, stay, fade_out)) = self.times {
+            packet_queue
+                .queue(SetTitleAnimationTimes::new(fade_in, stay, fade_out))
+                .await?;
+        }
+        if let Some(subtitle) = &self.subtitle {
+            packet_queue
+                .queue(SetSubtitleText::new(subtitle.clone()))
+                .await?;
+        }
+        packet_queue.queue(SetTitleText::new(title.clone())).await?;
+        Ok(packet_queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+    use tokio::io::AsyncReadExt;
+
+    use super::TitleBuilder;
+    use crate::tests::{test_connection, test_state};
+
+    #[tokio::test]
+    async fn times_and_subtitle_come_before_the_title() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+
+        let title = TitleBuilder::new("Hi")
+            .subtitle("Welcome")
+            .times(10, 70, 20);
+        {
+            let conn = state.connections.get_connection(entity_id).unwrap();
+            let conn = conn.read().await;
+            conn.send_title(&title).await.unwrap();
+        }
+
+        let mut expected = vec![13, 0x60, 0, 0, 0, 10, 0, 0, 0, 70, 0, 0, 0, 20];
+        expected.extend_from_slice(&[20, 0x5D, 18]);
+        expected.extend_from_slice(br#"{"text":"Welcome"}"#);
+        expected.extend_from_slice(&[15, 0x5F, 13]);
+        expected.extend_from_slice(br#"{"text":"Hi"}"#);
+        let mut bytes = vec![0u8; expected.len()];
+        client.read_exact(&mut bytes).await.unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[tokio::test]
+    async fn subtitles_need_a_title() {
+        let title = TitleBuilder::default().subtitle("Alone").times(1, 2, 3);
+        let mut bytes = Vec::new();
+        title
+            .packets()
+            .await
+            .unwrap()
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        assert!(bytes.is_empty());
+    }
+}
@@ -87,9 +87,39 @@ network_tick_rate = 0
 world = "world"
 
 [database]
-# The cache size in KB. We recommend leaving this at the default value.
-cache_size = 1024
-# The compression algorithm to use. "fast" is recommended for most use cases.
-# "best" is slower but may provide better compression ratio.
-compression = "fast"
+# How the chunk cache capacity is measured: "weighted" counts the memory used by the cached
+# chunks, "entries" counts the number of cached chunks. "weighted" is recommended as chunk sizes
+# vary a lot between worlds.
+cache_mode = "weighted"
+# The cache size, in KB for "weighted" mode or in chunks for "entries" mode.
+cache_size = 65536
+# Drop cached chunks that weren't accessed for this many seconds, 0 keeps them until the cache is full.
+cache_idle_timeout = 300
+# The compression algorithm to use for newly created worlds: "lz4", "zstd", "bzip" or "none".
+# "lz4" is recommended for most use cases. "zstd" is slower but provides a better compression ratio.
+# Existing worlds keep the algorithm they were created with.
+compression = "lz4"
+# How often modified chunks are saved, in seconds. They are also saved when the server stops.
+save_interval = 30
+# Queue chunk writes and commit them in batches instead of one transaction per chunk.
+# Improves saving throughput, but chunks saved in the last interval are lost on a crash.
+write_behind = false
+# How often queued chunks are committed, in milliseconds.
+write_behind_interval = 500
+# Commit as soon as this many chunks are queued.
+write_behind_max_pending = 256
+# Back up the world every this many minutes, 0 disables scheduled backups.
+backup_interval = 0
+# How many scheduled backups to keep, older ones are deleted.
+backup_keep = 5
+# Where scheduled backups are stored, relative to the folder holding the world.
+backup_dir = "backups"
+# Initial size of the database memory map, in MB. It doubles whenever the database is full.
+map_size = 1800
+# The memory map never grows past this size, in MB. Saving chunks fails once it is reached.
+max_map_size = 65536
+# How many chunks are read at once when scanning the whole database. Higher is faster but uses more memory.
+scan_batch_size = 256
+# Refuse new chunks while less than this many MB are left on the disk holding the world, 0 disables the check.
+min_free_space = 512
 "#;
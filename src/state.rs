@@ -1,15 +1,35 @@
-use crate::database::Database;
+use crate::events::command_events::registry::CommandRegistry;
 use crate::ecs::world::World;
+use crate::net::packets::registry::PacketRegistry;
+use crate::net::authentication::ServerKeys;
+use crate::net::plugin_channels::PluginChannelRegistry;
+use crate::net::rate_limit::RateLimits;
+use crate::net::utils::boss_bar::BossBarRegistry;
 use crate::net::ConnectionList;
+use crate::world::border::WorldBorders;
+use crate::world::difficulty::WorldDifficulty;
 use std::sync::Arc;
 use crate::events::creation::dispatcher::EventDispatcher;
 
 pub struct ServerState {
+    /// Entities, and the resources shared by the systems like the database and the time, see
+    /// [`create_world`](crate::create_world)
     pub world: Arc<World>,
     pub connections: ConnectionList,
-    pub database: Database,
     pub server_stream: tokio::net::TcpListener,
     pub event_dispatcher: Arc<EventDispatcher>,
+    pub packets: PacketRegistry,
+    pub plugin_channels: PluginChannelRegistry,
+    pub commands: CommandRegistry,
+    pub difficulty: WorldDifficulty,
+    pub world_borders: WorldBorders,
+    pub rate_limits: RateLimits,
+    /// Boss bars shown to the players, re-sent when they rejoin
+    pub boss_bars: BossBarRegistry,
+    /// Favicon of the server list, as a data URL
+    pub favicon: Option<String>,
+    /// Key pair of the encryption of online mode logins, `None` in offline mode
+    pub server_keys: Option<ServerKeys>,
 }
 
 pub type GlobalState = Arc<ServerState>;
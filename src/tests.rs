@@ -4,11 +4,61 @@ mod nbt_ser;
 pub mod query;
 
 use std::io::Cursor;
+use std::sync::atomic::AtomicU32;
+use std::sync::Arc;
 
+use dashmap::DashMap;
 use ferrumc_codec::network_types::varint::VarInt;
+use tokio::net::{TcpListener, TcpStream};
 
 use ferrumc_macros::NetDecode;
 
+use crate::create_world;
+use crate::database::Database;
+use crate::events::command_events::registry::CommandRegistry;
+use crate::events::creation::dispatcher::EventDispatcher;
+use crate::net::packets::registry::{PacketRegistry, UnknownPacketPolicy};
+use crate::net::plugin_channels::PluginChannelRegistry;
+use crate::net::rate_limit::RateLimits;
+use crate::net::utils::boss_bar::BossBarRegistry;
+use crate::net::{register_connection, ConnectionList};
+use crate::state::{GlobalState, ServerState};
+use crate::world::border::WorldBorders;
+use crate::world::difficulty::WorldDifficulty;
+
+/// Server state backed by an in-memory database, listening on a random local port
+pub(crate) async fn test_state() -> GlobalState {
+    Arc::new(ServerState {
+        world: Arc::new(create_world(Database::new_in_memory())),
+        connections: ConnectionList {
+            connections: DashMap::new(),
+            connection_count: AtomicU32::new(0),
+        },
+        server_stream: TcpListener::bind("127.0.0.1:0").await.unwrap(),
+        event_dispatcher: Arc::new(EventDispatcher::new()),
+        packets: PacketRegistry::new(UnknownPacketPolicy::Skip),
+        plugin_channels: PluginChannelRegistry::new(),
+        commands: CommandRegistry::new(),
+        difficulty: WorldDifficulty::default(),
+        world_borders: WorldBorders::default(),
+        rate_limits: RateLimits::default(),
+        boss_bars: BossBarRegistry::new(),
+        favicon: None,
+        server_keys: None,
+    })
+}
+
+/// Connects a client to the server of `state`, returns the entity id of the connection and the
+/// client socket, which receives the packets sent to it
+pub(crate) async fn test_connection(state: &GlobalState) -> (u32, TcpStream) {
+    let address = state.server_stream.local_addr().unwrap();
+    let client = TcpStream::connect(address).await.unwrap();
+    let (socket, _) = state.server_stream.accept().await.unwrap();
+    let conn = register_connection(socket, state).await;
+    let entity_id = conn.read().await.id;
+    (entity_id, client)
+}
+
 #[tokio::test]
 async fn test_macro_decode() {
     #[derive(NetDecode, Default)]
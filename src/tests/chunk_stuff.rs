@@ -11,13 +11,16 @@ pub async fn dump_heightmaps() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap();
 
     let chunk = state
-        .database
-        .get_chunk(0, 0, "overworld".to_string())
+        .world
+        .resource::<crate::database::Database>()
+        .await
+        .unwrap()
+        .get_chunk(&crate::world::dimension::ChunkPos::overworld(0, 0))
         .await
         .unwrap()
         .unwrap();
 
-    let heightmaps = chunk.heightmaps.unwrap();
+    let heightmaps = chunk.heightmaps.clone().unwrap();
 
     let mut buffer = Vec::new();
     heightmaps.net_encode(&mut buffer).await.unwrap();
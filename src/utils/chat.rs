@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Text component of the JSON chat format, as shown in the chat, titles and disconnect screens
+///
+/// Unset fields are inherited from the parent component by the client.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatComponent {
+    #[serde(flatten)]
+    pub content: ChatContent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ChatColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+    /// Components shown after the content, with its formatting unless they override it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<ChatComponent>,
+}
+
+/// What a component shows, before its extras
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatContent {
+    Text {
+        text: String,
+    },
+    /// Key of the language file of the client, its `%s` replaced by the arguments in order
+    Translate {
+        translate: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        with: Vec<ChatComponent>,
+    },
+}
+
+impl Default for ChatContent {
+    fn default() -> Self {
+        ChatContent::Text {
+            text: String::new(),
+        }
+    }
+}
+
+/// Color of a component, one of the 16 named colors or any RGB color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    /// `0xRRGGBB`
+    Hex(u32),
+}
+
+impl ChatColor {
+    const NAMED: [(ChatColor, &'static str); 16] = [
+        (ChatColor::Black, "black"),
+        (ChatColor::DarkBlue, "dark_blue"),
+        (ChatColor::DarkGreen, "dark_green"),
+        (ChatColor::DarkAqua, "dark_aqua"),
+        (ChatColor::DarkRed, "dark_red"),
+        (ChatColor::DarkPurple, "dark_purple"),
+        (ChatColor::Gold, "gold"),
+        (ChatColor::Gray, "gray"),
+        (ChatColor::DarkGray, "dark_gray"),
+        (ChatColor::Blue, "blue"),
+        (ChatColor::Green, "green"),
+        (ChatColor::Aqua, "aqua"),
+        (ChatColor::Red, "red"),
+        (ChatColor::LightPurple, "light_purple"),
+        (ChatColor::Yellow, "yellow"),
+        (ChatColor::White, "white"),
+    ];
+
+    /// Name of the color, or `#rrggbb`
+    pub fn name(&self) -> String {
+        match self {
+            ChatColor::Hex(rgb) => format!("#{:06x}", rgb & 0xFF_FFFF),
+            named => Self::NAMED
+                .iter()
+                .find(|(color, _)| color == named)
+                .map_or_else(String::new, |(_, name)| name.to_string()),
+        }
+    }
+
+    /// Color of the name `name` or of `#rrggbb`, `None` if it isn't one
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => u32::from_str_radix(hex, 16).ok().map(ChatColor::Hex),
+            Some(_) => None,
+            None => Self::NAMED
+                .iter()
+                .find(|(_, named)| *named == name)
+                .map(|(color, _)| *color),
+        }
+    }
+}
+
+impl Serialize for ChatColor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for ChatColor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ChatColor::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown color {}", name)))
+    }
+}
+
+/// What clicking a component does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "value", rename_all = "snake_case")]
+pub enum ClickEvent {
+    OpenUrl(String),
+    /// Sends the command, or the message, as if the player typed it
+    RunCommand(String),
+    /// Replaces the content of the chat box
+    SuggestCommand(String),
+    /// Page of the open book
+    ChangePage(u32),
+    CopyToClipboard(String),
+}
+
+/// What hovering a component shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "contents", rename_all = "snake_case")]
+pub enum HoverEvent {
+    ShowText(Box<ChatComponent>),
+    ShowItem {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<i32>,
+        /// SNBT of the item
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+    },
+    ShowEntity {
+        #[serde(rename = "type")]
+        kind: String,
+        /// Hyphenated UUID
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<Box<ChatComponent>>,
+    },
+}
+
+impl HoverEvent {
+    pub fn show_text(text: impl Into<ChatComponent>) -> Self {
+        HoverEvent::ShowText(Box::new(text.into()))
+    }
+
+    /// Card of the entity `uuid` of type `kind`, like `minecraft:player`
+    pub fn show_entity(kind: impl Into<String>, uuid: u128, name: Option<ChatComponent>) -> Self {
+        HoverEvent::ShowEntity {
+            kind: kind.into(),
+            id: Uuid::from_u128(uuid).hyphenated().to_string(),
+            name: name.map(Box::new),
+        }
+    }
+}
+
+impl ChatComponent {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: ChatContent::Text { text: text.into() },
+            ..Default::default()
+        }
+    }
+
+    /// Translated by the client, with `with` in place of the `%s` of the translation
+    pub fn translate(key: impl Into<String>, with: Vec<ChatComponent>) -> Self {
+        Self {
+            content: ChatContent::Translate {
+                translate: key.into(),
+                with,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn color(mut self, color: ChatColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn on_click(mut self, event: ClickEvent) -> Self {
+        self.click_event = Some(event);
+        self
+    }
+
+    pub fn on_hover(mut self, event: HoverEvent) -> Self {
+        self.hover_event = Some(event);
+        self
+    }
+
+    /// Append `component` after the content and the previous extras
+    pub fn extra(mut self, component: impl Into<ChatComponent>) -> Self {
+        self.extra.push(component.into());
+        self
+    }
+
+    /// JSON of the component, as sent in the packets
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("text components always serialize")
+    }
+
+    /// Text of the component and its extras without formatting, translations show their key
+    pub fn to_plain_text(&self) -> String {
+        let mut text = match &self.content {
+            ChatContent::Text { text } => text.clone(),
+            ChatContent::Translate { translate, .. } => translate.clone(),
+        };
+        for extra in &self.extra {
+            text.push_str(&extra.to_plain_text());
+        }
+        text
+    }
+
+    /// Component of the JSON `json`, `None` if it isn't one
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+impl From<&str> for ChatComponent {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for ChatComponent {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+/// The first `max_chars` characters of `text`, for the strings the protocol caps in length
+pub fn truncate(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate, ChatColor, ChatComponent, ClickEvent, HoverEvent};
+
+    #[test]
+    fn only_set_fields_are_serialized() {
+        assert_eq!(ChatComponent::from("Bye").to_json(), r#"{"text":"Bye"}"#);
+
+        let component = ChatComponent::text("Kicked")
+            .color(ChatColor::Red)
+            .bold(true)
+            .extra(ChatComponent::text(": spam").italic(true))
+            .extra("!");
+        assert_eq!(
+            component.to_json(),
+            r#"{"text":"Kicked","color":"red","bold":true,"extra":[{"text":": spam","italic":true},{"text":"!"}]}"#
+        );
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let json = ChatComponent::text("\"quoted\"\n").to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["text"], "\"quoted\"\n");
+    }
+
+    #[test]
+    fn translations_take_arguments() {
+        let component = ChatComponent::translate(
+            "chat.type.text",
+            vec![ChatComponent::from("Steve"), ChatComponent::from("hi")],
+        )
+        .color(ChatColor::Hex(0x00AAFF))
+        .underlined(true);
+        assert_eq!(
+            component.to_json(),
+            r##"{"translate":"chat.type.text","with":[{"text":"Steve"},{"text":"hi"}],"color":"#00aaff","underlined":true}"##
+        );
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let component = ChatComponent::text("[0, 64, 0]")
+            .color(ChatColor::Gold)
+            .on_click(ClickEvent::SuggestCommand("/tp 0 64 0".to_string()))
+            .on_hover(HoverEvent::show_entity(
+                "minecraft:player",
+                1,
+                Some(ChatComponent::from("Steve")),
+            ));
+        let json = component.to_json();
+        assert!(json.contains(r#""clickEvent":{"action":"suggest_command","value":"/tp 0 64 0"}"#));
+        assert!(json.contains(
+            r#""hoverEvent":{"action":"show_entity","contents":{"type":"minecraft:player","id":"00000000-0000-0000-0000-000000000001","name":{"text":"Steve"}}}"#
+        ));
+        assert_eq!(ChatComponent::from_json(&json), Some(component));
+
+        let text = ChatComponent::text("hover me").on_hover(HoverEvent::show_text("hi"));
+        assert_eq!(ChatComponent::from_json(&text.to_json()), Some(text));
+    }
+
+    #[test]
+    fn colors_are_named_or_hex() {
+        assert_eq!(ChatColor::LightPurple.name(), "light_purple");
+        assert_eq!(ChatColor::Hex(0xFF).name(), "#0000ff");
+        assert_eq!(ChatColor::from_name("dark_gray"), Some(ChatColor::DarkGray));
+        assert_eq!(
+            ChatColor::from_name("#FFaa00"),
+            Some(ChatColor::Hex(0xFFAA00))
+        );
+        assert_eq!(ChatColor::from_name("#fff"), None);
+        assert_eq!(ChatColor::from_name("pink"), None);
+    }
+
+    #[test]
+    fn truncation_keeps_whole_characters() {
+        assert_eq!(truncate("objective", 16), "objective");
+        assert_eq!(truncate("éééé", 2), "éé");
+        assert_eq!(truncate("", 0), "");
+    }
+}
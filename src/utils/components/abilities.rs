@@ -0,0 +1,110 @@
+use ferrumc_macros::{Component, Getter};
+
+/// Flags of the abilities, as in the player abilities packets
+pub const INVULNERABLE: u8 = 0x01;
+pub const FLYING: u8 = 0x02;
+pub const MAY_FLY: u8 = 0x04;
+pub const CREATIVE: u8 = 0x08;
+
+const DEFAULT_FLYING_SPEED: f32 = 0.05;
+const DEFAULT_WALKING_SPEED: f32 = 0.1;
+
+/// Player abilities, granted by the server from the game mode
+///
+/// The client only decides whether it is flying, and only when it may fly.
+#[derive(Debug, Clone, Component, Getter)]
+pub struct Abilities {
+    pub flags: u8,
+    pub flying_speed: f32,
+    pub walking_speed: f32,
+    /// Abilities the client claimed without having them, since it joined
+    pub violations: u32,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Abilities {
+    pub fn new(flags: u8) -> Self {
+        Self {
+            flags,
+            flying_speed: DEFAULT_FLYING_SPEED,
+            walking_speed: DEFAULT_WALKING_SPEED,
+            violations: 0,
+        }
+    }
+
+    /// Abilities of the game mode `gamemode`, creative players may fly and spectators always do
+    pub fn for_gamemode(gamemode: u8) -> Self {
+        match gamemode {
+            1 => Self::new(INVULNERABLE | MAY_FLY | CREATIVE),
+            3 => Self::new(INVULNERABLE | MAY_FLY | FLYING),
+            _ => Self::new(0),
+        }
+    }
+
+    /// Abilities of a player rejoining in `gamemode`, flying if it was when it left
+    pub fn restored(gamemode: u8, saved_flags: u8) -> Self {
+        let mut abilities = Self::for_gamemode(gamemode);
+        if saved_flags & FLYING != 0 {
+            abilities.set_flying(true);
+        }
+        abilities
+    }
+
+    pub fn may_fly(&self) -> bool {
+        self.flags & MAY_FLY != 0
+    }
+
+    pub fn is_flying(&self) -> bool {
+        self.flags & FLYING != 0
+    }
+
+    /// Start or stop flying, players that may not fly never do
+    pub fn set_flying(&mut self, flying: bool) {
+        if flying && self.may_fly() {
+            self.flags |= FLYING;
+        } else {
+            self.flags &= !FLYING;
+        }
+    }
+
+    /// Whether a client with these abilities may report `flags`, only the flying bit is its own
+    pub fn accepts(&self, flags: u8) -> bool {
+        flags & !FLYING == 0 && (flags & FLYING == 0 || self.may_fly())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Abilities, CREATIVE, FLYING, INVULNERABLE, MAY_FLY};
+
+    #[test]
+    fn only_players_that_may_fly_fly() {
+        let survival = Abilities::for_gamemode(0);
+        assert!(survival.accepts(0));
+        assert!(!survival.accepts(FLYING));
+
+        let creative = Abilities::for_gamemode(1);
+        assert!(creative.accepts(FLYING));
+        assert!(creative.accepts(0));
+        // The other flags are granted by the server
+        assert!(!creative.accepts(FLYING | INVULNERABLE));
+        assert!(!survival.accepts(MAY_FLY));
+        assert!(!survival.accepts(CREATIVE));
+
+        assert!(Abilities::for_gamemode(3).is_flying());
+    }
+
+    #[test]
+    fn saved_flight_needs_the_game_mode_to_allow_it() {
+        assert!(Abilities::restored(1, FLYING | MAY_FLY).is_flying());
+        assert!(!Abilities::restored(1, 0).is_flying());
+        // Flags saved while in creative don't carry over to survival
+        let survival = Abilities::restored(0, FLYING | MAY_FLY | CREATIVE);
+        assert_eq!(survival.flags, 0);
+    }
+}
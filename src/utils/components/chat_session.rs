@@ -0,0 +1,43 @@
+use std::time::Instant;
+
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Chat signing session of a player, kept but not verified since the server doesn't enforce
+/// secure chat
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct ChatSession {
+    pub session_id: u128,
+    /// When the public key expires, in milliseconds since the Unix epoch
+    pub expires_at: i64,
+    /// DER encoded RSA public key of the player
+    pub public_key: Vec<u8>,
+    /// Signature of the public key by Mojang
+    pub key_signature: Vec<u8>,
+}
+
+/// Chat messages the client last acknowledged seeing
+#[derive(Debug, Default, Clone, Component, Getter, Constructor)]
+pub struct ChatAcknowledgment {
+    pub message_count: i32,
+}
+
+/// When the player last sent a chat message, for the cooldown between messages
+#[derive(Debug, Clone, Component)]
+pub struct LastChatMessage {
+    pub sent_at: Instant,
+}
+
+/// Chat messages the player sent, numbering them for the clients of the other players
+#[derive(Debug, Default, Clone, Component)]
+pub struct SentMessages {
+    pub count: i32,
+}
+
+impl SentMessages {
+    /// Index of the message being sent, the number of messages sent before it
+    pub fn next_index(&mut self) -> i32 {
+        let index = self.count;
+        self.count = self.count.wrapping_add(1);
+        index
+    }
+}
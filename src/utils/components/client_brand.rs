@@ -0,0 +1,7 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Client brand (`vanilla`, `fabric`...) sent on the `minecraft:brand` plugin channel
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct ClientBrand {
+    pub brand: String,
+}
@@ -0,0 +1,73 @@
+use ferrumc_macros::Component;
+
+use crate::utils::encoding::position::Position;
+
+/// Fraction of the dig a survival player must have done for its finished dig to be accepted,
+/// leaving room for the client being a few ticks ahead
+pub const FINISH_TOLERANCE: f32 = 0.7;
+
+/// Block a survival player is digging, and since when
+///
+/// The progress is measured in ticks of the world, the stage shown to the other players is
+/// only sent again once it changed.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Digging {
+    pub location: Position,
+    /// World age the dig started at
+    started: i64,
+    /// Ticks the block takes to break, at least 1
+    ticks: u32,
+    /// Stage last shown to the other players
+    shown_stage: Option<u8>,
+}
+
+impl Digging {
+    pub fn new(location: Position, started: i64, ticks: u32) -> Self {
+        Self {
+            location,
+            started,
+            ticks: ticks.max(1),
+            shown_stage: None,
+        }
+    }
+
+    /// Fraction of the block dug at world age `now`, 1.0 once it breaks
+    pub fn progress(&self, now: i64) -> f32 {
+        ((now - self.started).max(0) as f32 / self.ticks as f32).min(1.0)
+    }
+
+    pub fn is_done(&self, now: i64) -> bool {
+        now - self.started >= self.ticks as i64
+    }
+
+    /// Destroy stage at world age `now` if it changed since it was last shown, from 0 to 9
+    pub fn next_stage(&mut self, now: i64) -> Option<u8> {
+        let stage = ((self.progress(now) * 10.0) as u8).min(9);
+        if self.shown_stage == Some(stage) {
+            return None;
+        }
+        self.shown_stage = Some(stage);
+        Some(stage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Digging, FINISH_TOLERANCE};
+    use crate::utils::encoding::position::Position;
+
+    #[test]
+    fn stages_follow_the_progress() {
+        let mut digging = Digging::new(Position::new(0, 64, 0), 100, 20);
+        assert_eq!(digging.next_stage(100), Some(0));
+        assert_eq!(digging.next_stage(101), None);
+        assert_eq!(digging.next_stage(110), Some(5));
+        assert!(digging.progress(114) >= FINISH_TOLERANCE);
+        assert!(!digging.is_done(119));
+        assert_eq!(digging.next_stage(119), Some(9));
+        assert!(digging.is_done(120));
+        // Past the end, the last stage stays
+        assert_eq!(digging.next_stage(130), None);
+        assert_eq!(digging.progress(130), 1.0);
+    }
+}
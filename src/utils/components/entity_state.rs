@@ -0,0 +1,41 @@
+use ferrumc_macros::Component;
+
+/// Metadata flag of a crouching entity
+const SNEAKING_FLAG: u8 = 0x02;
+/// Metadata flag of a sprinting entity
+const SPRINTING_FLAG: u8 = 0x08;
+
+/// Standing pose of the entity metadata
+const POSE_STANDING: i32 = 0;
+/// Crouching pose of the entity metadata
+const POSE_SNEAKING: i32 = 5;
+
+/// Sneaking and sprinting state of a player, as set by Player Command packets
+#[derive(Debug, Default, Clone, Component)]
+pub struct EntityState {
+    pub sneaking: bool,
+    pub sprinting: bool,
+}
+
+impl EntityState {
+    /// Flags byte of the entity metadata
+    pub fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.sneaking {
+            flags |= SNEAKING_FLAG;
+        }
+        if self.sprinting {
+            flags |= SPRINTING_FLAG;
+        }
+        flags
+    }
+
+    /// Pose of the entity metadata
+    pub fn pose(&self) -> i32 {
+        if self.sneaking {
+            POSE_SNEAKING
+        } else {
+            POSE_STANDING
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Food level of a player who isn't hungry
+pub const MAX_FOOD: i32 = 20;
+/// Saturation of a freshly spawned player
+pub const SPAWN_SATURATION: f32 = 5.0;
+
+/// Hunger bar of a player
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct Food {
+    pub food: i32,
+    pub saturation: f32,
+}
+
+impl Default for Food {
+    fn default() -> Self {
+        Self::new(MAX_FOOD, SPAWN_SATURATION)
+    }
+}
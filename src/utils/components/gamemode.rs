@@ -0,0 +1,47 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::net::packets::outgoing::player_abilities::PlayerAbilities;
+use crate::net::packets::ConnectionId;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::net::utils::tab_list::TabList;
+use crate::state::GlobalState;
+use crate::utils::components::abilities::Abilities;
+use crate::utils::prelude::*;
+
+/// Game mode of a player: 0 survival, 1 creative, 2 adventure, 3 spectator
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct Gamemode {
+    pub mode: u8,
+}
+
+/// Switch `entity_id` to the game mode `mode`, with the abilities that go with it
+///
+/// A player flying when switching between creative and spectator keeps flying.
+pub async fn set_gamemode(entity_id: ConnectionId, mode: u8, state: &GlobalState) -> Result<()> {
+    let was_flying = state
+        .world
+        .get_component::<Abilities>(entity_id)
+        .await
+        .is_ok_and(|abilities| abilities.is_flying());
+    let mut abilities = Abilities::for_gamemode(mode);
+    if was_flying {
+        abilities.set_flying(true);
+    }
+
+    let mut packet_queue = PacketQueue::new();
+    packet_queue.queue(GameEvent::change_gamemode(mode)).await?;
+    packet_queue.queue(PlayerAbilities::new(&abilities)).await?;
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, Gamemode::new(mode))
+        .insert(entity_id, abilities);
+
+    {
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packets(packet_queue).await?;
+    }
+    TabList::update_gamemode(entity_id, state).await
+}
@@ -0,0 +1,45 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Health of a full health player, 10 hearts
+pub const MAX_HEALTH: f32 = 20.0;
+
+/// Health points of a living entity, it dies at 0
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct Health {
+    pub health: f32,
+}
+
+impl Health {
+    /// Take `amount` of damage, returns whether the entity died of it
+    pub fn damage(&mut self, amount: f32) -> bool {
+        let alive = self.health > 0.0;
+        self.health = (self.health - amount).max(0.0);
+        alive && self.health == 0.0
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(MAX_HEALTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Health;
+
+    #[test]
+    fn damage_stops_at_zero() {
+        let mut health = Health::new(3.0);
+        assert!(!health.damage(1.0));
+        assert!(health.damage(5.0));
+        assert_eq!(health.health, 0.0);
+        // Already dead
+        assert!(!health.damage(1.0));
+        assert!(health.is_dead());
+    }
+}
@@ -0,0 +1,7 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Hotbar slot selected by a player, 0 to 8
+#[derive(Debug, Default, Clone, Component, Getter, Constructor)]
+pub struct HeldItem {
+    pub slot: u8,
+}
@@ -0,0 +1,297 @@
+use ferrumc_macros::Component;
+
+use crate::utils::encoding::slot::{ItemStack, Slot};
+
+/// Slots of the player inventory: crafting result and grid, armor, main inventory, hotbar and
+/// offhand
+pub const INVENTORY_SIZE: usize = 46;
+/// Output of the 2x2 crafting grid
+pub const CRAFTING_RESULT_SLOT: usize = 0;
+/// Helmet slot, followed by the chestplate, leggings and boots
+pub const ARMOR_START: usize = 5;
+/// First slot of the main inventory, the slots before it are crafting and armor
+pub const MAIN_INVENTORY_START: usize = 9;
+/// First hotbar slot
+pub const HOTBAR_START: usize = 36;
+pub const OFFHAND_SLOT: usize = 45;
+/// Swap button of the offhand, the hotbar buttons are 0 to 8
+const OFFHAND_BUTTON: i8 = 40;
+/// Stack size limit, until per item limits are known
+pub const MAX_STACK_SIZE: i8 = 64;
+
+/// Click mode of a Click Container packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickMode {
+    /// Left or right click on a slot
+    Pickup,
+    /// Shift click
+    QuickMove,
+    /// Swap with a hotbar slot or the offhand
+    Swap,
+    /// Middle click, creative only
+    Clone,
+    Throw,
+    /// Painting items over several slots
+    Drag,
+    /// Double click, gathers matching items
+    PickupAll,
+}
+
+impl ClickMode {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ClickMode::Pickup),
+            1 => Some(ClickMode::QuickMove),
+            2 => Some(ClickMode::Swap),
+            3 => Some(ClickMode::Clone),
+            4 => Some(ClickMode::Throw),
+            5 => Some(ClickMode::Drag),
+            6 => Some(ClickMode::PickupAll),
+            _ => None,
+        }
+    }
+}
+
+/// Content of the player inventory, as known by the server
+///
+/// `state_id` is bumped every time the server sends slots to the client, clicks made from an
+/// older state are resynchronized instead of applied.
+#[derive(Debug, Clone, Component)]
+pub struct Inventory {
+    pub slots: Vec<Slot>,
+    /// Stack held by the cursor
+    pub carried: Slot,
+    pub state_id: i32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![Slot::empty(); INVENTORY_SIZE],
+            carried: Slot::empty(),
+            state_id: 0,
+        }
+    }
+}
+
+impl Inventory {
+    /// Stack in the hotbar slot `slot`, from 0 to 8
+    pub fn hotbar(&self, slot: u8) -> &Slot {
+        &self.slots[HOTBAR_START + (slot as usize).min(8)]
+    }
+
+    /// Bump the state id before sending slots, returns the new id
+    pub fn next_state_id(&mut self) -> i32 {
+        self.state_id = self.state_id.wrapping_add(1);
+        self.state_id
+    }
+
+    /// Apply a click on `slot`, returns false if the click isn't supported and nothing changed
+    ///
+    /// Only pickup clicks on a slot and hotbar swaps are supported for now.
+    pub fn click(&mut self, slot: i16, button: i8, mode: ClickMode) -> bool {
+        let Some(index) = usize::try_from(slot)
+            .ok()
+            .filter(|index| *index < INVENTORY_SIZE && *index != CRAFTING_RESULT_SLOT)
+        else {
+            return false;
+        };
+        match (mode, button) {
+            (ClickMode::Pickup, 0) => self.left_click(index),
+            (ClickMode::Pickup, 1) => self.right_click(index),
+            (ClickMode::Swap, 0..=8) => {
+                self.slots.swap(index, HOTBAR_START + button as usize);
+                true
+            }
+            (ClickMode::Swap, OFFHAND_BUTTON) => {
+                self.slots.swap(index, OFFHAND_SLOT);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pick up the whole stack, put down the carried one or merge both
+    fn left_click(&mut self, index: usize) -> bool {
+        let slot = &mut self.slots[index];
+        match (&mut slot.item, &mut self.carried.item) {
+            (Some(stack), Some(carried)) if stack.stacks_with(carried) => {
+                let moved = carried.count.min(MAX_STACK_SIZE - stack.count).max(0);
+                stack.count += moved;
+                carried.count -= moved;
+                if carried.count == 0 {
+                    self.carried = Slot::empty();
+                }
+            }
+            _ => std::mem::swap(slot, &mut self.carried),
+        }
+        true
+    }
+
+    /// Pick up half of the stack, or put down a single carried item
+    fn right_click(&mut self, index: usize) -> bool {
+        let slot = &mut self.slots[index];
+        match (&mut slot.item, &mut self.carried.item) {
+            (None, None) => {}
+            (Some(stack), None) => {
+                let taken = stack.count - stack.count / 2;
+                stack.count -= taken;
+                self.carried.item = Some(ItemStack {
+                    count: taken,
+                    ..stack.clone()
+                });
+                if stack.count == 0 {
+                    *slot = Slot::empty();
+                }
+            }
+            (None, Some(carried)) => {
+                slot.item = Some(ItemStack {
+                    count: 1,
+                    ..carried.clone()
+                });
+                carried.count -= 1;
+                if carried.count == 0 {
+                    self.carried = Slot::empty();
+                }
+            }
+            (Some(stack), Some(carried)) if stack.stacks_with(carried) => {
+                if stack.count < MAX_STACK_SIZE {
+                    stack.count += 1;
+                    carried.count -= 1;
+                    if carried.count == 0 {
+                        self.carried = Slot::empty();
+                    }
+                }
+            }
+            _ => std::mem::swap(slot, &mut self.carried),
+        }
+        true
+    }
+
+    /// Put the carried stack back in the first empty slot of the main inventory or hotbar,
+    /// returns false if there was no room and the stack was lost
+    pub fn stow_carried(&mut self) -> bool {
+        if self.carried.item.is_none() {
+            return true;
+        }
+        let carried = std::mem::take(&mut self.carried);
+        match self.slots[MAIN_INVENTORY_START..OFFHAND_SLOT]
+            .iter_mut()
+            .find(|slot| slot.item.is_none())
+        {
+            Some(slot) => {
+                *slot = carried;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bring a stack of `item_id` to the hotbar for a pick block, returns the hotbar slot (0 to
+    /// 8) to select and the slots that changed, `None` if there is nothing to pick
+    ///
+    /// A stack already in the hotbar is selected, one in the main inventory is swapped into the
+    /// `hotbar` slot. Creative players get a new item in the `hotbar` slot otherwise.
+    pub fn pick(
+        &mut self,
+        item_id: i32,
+        hotbar: usize,
+        creative: bool,
+    ) -> Option<(usize, Vec<usize>)> {
+        let matches = |slot: &Slot| {
+            slot.item
+                .as_ref()
+                .is_some_and(|stack| stack.item_id.get_val() == item_id && stack.nbt.is_none())
+        };
+        if let Some(index) = self.slots[HOTBAR_START..OFFHAND_SLOT]
+            .iter()
+            .position(matches)
+        {
+            return Some((index, Vec::new()));
+        }
+
+        let target = HOTBAR_START + hotbar;
+        match self.slots[MAIN_INVENTORY_START..HOTBAR_START]
+            .iter()
+            .position(matches)
+        {
+            Some(index) => {
+                let index = MAIN_INVENTORY_START + index;
+                self.slots.swap(index, target);
+                Some((hotbar, vec![index, target]))
+            }
+            None if creative => {
+                self.slots[target] = Slot {
+                    item: Some(ItemStack::new(item_id, 1)),
+                };
+                Some((hotbar, vec![target]))
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClickMode, Inventory, HOTBAR_START};
+    use crate::utils::encoding::slot::{ItemStack, Slot};
+
+    fn stack(item_id: i32, count: i8) -> Slot {
+        Slot {
+            item: Some(ItemStack::new(item_id, count)),
+        }
+    }
+
+    #[test]
+    fn pick_brings_the_stack_to_the_hotbar() {
+        let mut inventory = Inventory::default();
+        inventory.slots[HOTBAR_START + 4] = stack(1, 3);
+        inventory.slots[20] = stack(2, 5);
+
+        assert_eq!(inventory.pick(1, 0, false), Some((4, vec![])));
+        assert_eq!(
+            inventory.pick(2, 0, false),
+            Some((0, vec![20, HOTBAR_START]))
+        );
+        assert_eq!(inventory.slots[HOTBAR_START], stack(2, 5));
+        assert_eq!(inventory.pick(3, 1, false), None);
+        assert_eq!(
+            inventory.pick(3, 1, true),
+            Some((1, vec![HOTBAR_START + 1]))
+        );
+        assert_eq!(inventory.slots[HOTBAR_START + 1], stack(3, 1));
+    }
+
+    #[test]
+    fn pickup_clicks_move_stacks() {
+        let mut inventory = Inventory::default();
+        inventory.slots[10] = stack(1, 9);
+        inventory.slots[11] = stack(1, 60);
+
+        // Half of the stack, rounded up
+        assert!(inventory.click(10, 1, ClickMode::Pickup));
+        assert_eq!(inventory.carried, stack(1, 5));
+        assert_eq!(inventory.slots[10], stack(1, 4));
+
+        // Merging stops at the stack size
+        assert!(inventory.click(11, 0, ClickMode::Pickup));
+        assert_eq!(inventory.slots[11], stack(1, 64));
+        assert_eq!(inventory.carried, stack(1, 1));
+
+        assert!(inventory.click(12, 1, ClickMode::Pickup));
+        assert_eq!(inventory.slots[12], stack(1, 1));
+        assert_eq!(inventory.carried, Slot::empty());
+    }
+
+    #[test]
+    fn hotbar_swaps_and_unsupported_clicks() {
+        let mut inventory = Inventory::default();
+        inventory.slots[20] = stack(3, 1);
+        assert!(inventory.click(20, 2, ClickMode::Swap));
+        assert_eq!(inventory.slots[HOTBAR_START + 2], stack(3, 1));
+
+        assert!(!inventory.click(20, 0, ClickMode::QuickMove));
+        assert!(!inventory.click(0, 0, ClickMode::Pickup));
+        assert!(!inventory.click(-999, 0, ClickMode::Pickup));
+    }
+}
@@ -1,8 +1,76 @@
-use ferrumc_macros::{Component, Constructor};
+use std::time::{Duration, Instant};
 
-#[derive(Component, Constructor, Debug, Clone)]
+use ferrumc_macros::Component;
+use rand::random;
+
+/// Keep alives exchanged with a player, at most one awaits its answer at a time
+#[derive(Component, Debug, Clone)]
 pub struct KeepAlive {
-    pub last_received: std::time::Instant,
-    pub last_sent: std::time::Instant,
+    pub last_received: Instant,
+    pub last_sent: Instant,
+    /// Id of the last keep alive sent
     pub data: i64,
+    /// Whether the last keep alive sent is still unanswered
+    pub awaiting: bool,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            last_received: Instant::now(),
+            last_sent: Instant::now(),
+            data: 0,
+            awaiting: false,
+        }
+    }
+}
+
+impl KeepAlive {
+    /// Record a keep alive sent now, returns its random id
+    pub fn issue(&mut self) -> i64 {
+        self.data = random();
+        self.last_sent = Instant::now();
+        self.awaiting = true;
+        self.data
+    }
+
+    /// Match the answer carrying `id`, returns the round trip time, or `None` if it doesn't
+    /// answer the awaited keep alive
+    pub fn answer(&mut self, id: i64) -> Option<Duration> {
+        if !self.awaiting || id != self.data {
+            return None;
+        }
+        self.awaiting = false;
+        self.last_received = Instant::now();
+        Some(self.last_received - self.last_sent)
+    }
+
+    /// Whether the awaited keep alive was sent more than `timeout` ago
+    pub fn timed_out(&self, timeout: Duration) -> bool {
+        self.awaiting && self.last_sent.elapsed() > timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::KeepAlive;
+
+    #[test]
+    fn only_the_awaited_id_answers() {
+        let mut keep_alive = KeepAlive::default();
+        assert_eq!(keep_alive.answer(0), None);
+
+        let id = keep_alive.issue();
+        assert_eq!(keep_alive.answer(id.wrapping_add(1)), None);
+        assert!(keep_alive.answer(id).is_some());
+        // Answered already
+        assert_eq!(keep_alive.answer(id), None);
+        assert!(!keep_alive.timed_out(Duration::ZERO));
+
+        keep_alive.issue();
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(keep_alive.timed_out(Duration::from_millis(1)));
+    }
 }
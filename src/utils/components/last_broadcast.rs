@@ -0,0 +1,42 @@
+use ferrumc_macros::Component;
+
+use crate::net::packets::outgoing::spawn_entity::angle;
+
+/// Steps of a block in the coordinates of the relative moves
+const POSITION_SCALE: f64 = 4096.0;
+
+/// Position and rotation of an entity as last sent to the players tracking it
+///
+/// Moves are sent relative to it, so the small ones coalesced within a tick add up on the
+/// clients exactly as they do here.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct LastBroadcast {
+    /// Coordinates in 1/4096 of a block
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+    /// Rotations as angles, see [`angle`]
+    pub yaw: u8,
+    pub pitch: u8,
+}
+
+impl LastBroadcast {
+    pub fn new((x, y, z): (f64, f64, f64), yaw: f32, pitch: f32) -> Self {
+        Self {
+            x: (x * POSITION_SCALE).round() as i64,
+            y: (y * POSITION_SCALE).round() as i64,
+            z: (z * POSITION_SCALE).round() as i64,
+            yaw: angle(yaw),
+            pitch: angle(pitch),
+        }
+    }
+
+    /// Coordinates in blocks
+    pub fn position(&self) -> (f64, f64, f64) {
+        (
+            self.x as f64 / POSITION_SCALE,
+            self.y as f64 / POSITION_SCALE,
+            self.z as f64 / POSITION_SCALE,
+        )
+    }
+}
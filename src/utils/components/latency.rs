@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ferrumc_macros::Component;
+
+/// Pings awaiting their pong, older ones are forgotten
+const MAX_PENDING_PINGS: usize = 16;
+
+/// Round trip time of a player, measured with play state Ping and Pong packets
+#[derive(Debug, Default, Clone, Component)]
+pub struct Latency {
+    next_id: i32,
+    pending: VecDeque<(i32, Instant)>,
+    /// Round trip time of the last answered ping
+    pub rtt: Option<Duration>,
+}
+
+impl Latency {
+    /// Record a ping sent now, returns its id
+    pub fn issue(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        if self.pending.len() == MAX_PENDING_PINGS {
+            self.pending.pop_front();
+        }
+        self.pending.push_back((id, Instant::now()));
+        id
+    }
+
+    /// Record a round trip time measured by other means, such as keep alives
+    pub fn record(&mut self, rtt: Duration) {
+        self.rtt = Some(rtt);
+    }
+
+    /// Match the pong of ping `id`, returns the round trip time or `None` for an unknown id
+    pub fn complete(&mut self, id: i32) -> Option<Duration> {
+        let index = self
+            .pending
+            .iter()
+            .position(|(pending, _)| *pending == id)?;
+        let (_, sent) = self.pending.remove(index)?;
+        let rtt = sent.elapsed();
+        self.rtt = Some(rtt);
+        Some(rtt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Latency, MAX_PENDING_PINGS};
+
+    #[test]
+    fn pongs_match_pending_pings() {
+        let mut latency = Latency::default();
+        let first = latency.issue();
+        let second = latency.issue();
+
+        assert!(latency.complete(second).is_some());
+        assert!(latency.complete(second).is_none());
+        assert!(latency.complete(first).is_some());
+        assert!(latency.rtt.is_some());
+
+        for _ in 0..=MAX_PENDING_PINGS {
+            latency.issue();
+        }
+        // The oldest ping was forgotten
+        assert!(latency.complete(2).is_none());
+        assert!(latency.complete(3).is_some());
+    }
+}
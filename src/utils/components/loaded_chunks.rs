@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use ferrumc_macros::Component;
+
+/// Positions of chunks, in the order they are sent or unloaded
+type ChunkList = Vec<(i32, i32)>;
+
+/// Chunks the client of a player holds, see
+/// [`ChunkSender`](crate::net::systems::chunk_sender::ChunkSender)
+#[derive(Debug, Default, Clone, Component)]
+pub struct LoadedChunks {
+    /// Chunk the client loads around, `None` until it is first sent one
+    pub center: Option<(i32, i32)>,
+    pub chunks: HashSet<(i32, i32)>,
+}
+
+impl LoadedChunks {
+    /// Load the chunks within `radius` of `center`, returns those to send, nearest first, and
+    /// those to unload
+    pub fn update(&mut self, center: (i32, i32), radius: i32) -> (ChunkList, ChunkList) {
+        let in_view =
+            |&(x, z): &(i32, i32)| (x - center.0).abs() <= radius && (z - center.1).abs() <= radius;
+        let mut unloaded: ChunkList = self
+            .chunks
+            .iter()
+            .filter(|chunk| !in_view(chunk))
+            .copied()
+            .collect();
+        unloaded.sort_unstable();
+        self.chunks.retain(in_view);
+
+        let mut loaded = Vec::new();
+        for x in center.0 - radius..=center.0 + radius {
+            for z in center.1 - radius..=center.1 + radius {
+                if self.chunks.insert((x, z)) {
+                    loaded.push((x, z));
+                }
+            }
+        }
+        loaded.sort_by_key(|&(x, z)| {
+            let (dx, dz) = ((x - center.0) as i64, (z - center.1) as i64);
+            dx * dx + dz * dz
+        });
+        self.center = Some(center);
+        (loaded, unloaded)
+    }
+
+    /// Forget a chunk that couldn't be sent, so that it is sent again with the next update
+    pub fn forget(&mut self, chunk: (i32, i32)) {
+        self.chunks.remove(&chunk);
+    }
+
+    /// Forget every chunk without unloading them, the client drops its world when it respawns
+    /// or changes dimension
+    pub fn clear(&mut self) {
+        self.center = None;
+        self.chunks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoadedChunks;
+
+    #[test]
+    fn nearest_chunks_are_sent_first() {
+        let mut loaded = LoadedChunks::default();
+        let (sent, unloaded) = loaded.update((10, -3), 2);
+        assert_eq!(sent.len(), 25);
+        assert_eq!(sent[0], (10, -3));
+        assert!(sent[1..5]
+            .iter()
+            .all(|&(x, z)| (x - 10).abs() + (z + 3).abs() == 1));
+        assert_eq!(sent[24].0.abs_diff(10) + sent[24].1.abs_diff(-3), 4);
+        assert!(unloaded.is_empty());
+
+        // Nothing new to send
+        let (sent, unloaded) = loaded.update((10, -3), 2);
+        assert!(sent.is_empty() && unloaded.is_empty());
+    }
+
+    #[test]
+    fn chunks_out_of_view_are_unloaded() {
+        let mut loaded = LoadedChunks::default();
+        loaded.update((0, 0), 2);
+        // One chunk east, a row in and a row out
+        let (sent, unloaded) = loaded.update((1, 0), 2);
+        assert_eq!(sent.len(), 5);
+        assert!(sent.iter().all(|&(x, _)| x == 3));
+        assert_eq!(unloaded.len(), 5);
+        assert!(unloaded.iter().all(|&(x, _)| x == -2));
+
+        // Shrinking the view distance unloads the outer ring
+        let (sent, unloaded) = loaded.update((1, 0), 1);
+        assert!(sent.is_empty());
+        assert_eq!(unloaded.len(), 25 - 9);
+        assert!(unloaded.contains(&(3, 2)));
+        assert!(!unloaded.contains(&(2, 1)));
+    }
+
+    #[test]
+    fn cleared_chunks_are_sent_again() {
+        let mut loaded = LoadedChunks::default();
+        loaded.update((0, 0), 1);
+        loaded.forget((1, 1));
+        assert_eq!(loaded.update((0, 0), 1).0, [(1, 1)]);
+
+        loaded.clear();
+        assert_eq!(loaded.center, None);
+        let (sent, unloaded) = loaded.update((0, 0), 1);
+        assert_eq!(sent.len(), 9);
+        assert!(unloaded.is_empty());
+    }
+}
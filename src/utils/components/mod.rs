@@ -1,5 +1,26 @@
+pub mod abilities;
+pub mod chat_session;
+pub mod client_brand;
+pub mod digging;
+pub mod entity_state;
+pub mod food;
+pub mod gamemode;
 pub mod grounded;
+pub mod health;
+pub mod held_item;
+pub mod inventory;
 pub mod keep_alive;
-pub mod last_chunk_tx_pos;
+pub mod last_broadcast;
+pub mod latency;
+pub mod loaded_chunks;
+pub mod movement;
+pub mod open_window;
+pub mod operator;
+pub mod pending_teleports;
 pub mod player;
+pub mod profile;
+pub mod resource_pack;
 pub mod rotation;
+pub mod tracked_entities;
+pub mod vehicle_input;
+pub mod window_manager;
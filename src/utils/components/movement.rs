@@ -0,0 +1,174 @@
+use ferrumc_macros::Component;
+
+use crate::utils::config::Movement;
+use crate::utils::constants::{WORLD_MAX_Y, WORLD_MIN_Y};
+use crate::utils::encoding::position::Position;
+
+/// Outcome of a position sent by a player, see [`MovementTracker::check`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveCheck {
+    /// The move to apply, its height clamped into the world
+    Accepted { x: f64, y: f64, z: f64 },
+    /// Coordinates that aren't finite numbers
+    Invalid,
+    /// Too far from the last position, the player has to be sent back to it
+    TooFar { x: f64, y: f64, z: f64 },
+}
+
+/// Last accepted position of a player and counters of the moves it sent that were refused
+#[derive(Debug, Default, Clone, Component)]
+pub struct MovementTracker {
+    last: Option<(f64, f64, f64)>,
+    /// Last teleport `last` comes after
+    teleport: i32,
+    rejected_moves: u32,
+    clamped_moves: u32,
+}
+
+impl MovementTracker {
+    /// Check a move to `x`, `y`, `z`
+    ///
+    /// The move is measured from the last accepted one, or from `position`, where the player
+    /// stands, if it was teleported since. `last_teleport` is the id of the last teleport sent
+    /// to the player.
+    pub fn check(
+        &mut self,
+        (x, y, z): (f64, f64, f64),
+        position: &Position,
+        last_teleport: i32,
+        limits: &Movement,
+    ) -> MoveCheck {
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            self.rejected_moves += 1;
+            return MoveCheck::Invalid;
+        }
+
+        let clamped_y = y.clamp(WORLD_MIN_Y as f64, WORLD_MAX_Y as f64);
+        if clamped_y != y {
+            self.clamped_moves += 1;
+        }
+
+        let (from, max_distance) = match self.last {
+            Some(last) if self.teleport == last_teleport => (last, limits.max_distance_per_tick),
+            _ => (
+                (
+                    position.x as f64 + 0.5,
+                    position.y as f64,
+                    position.z as f64 + 0.5,
+                ),
+                limits.max_distance_per_tick + limits.teleport_allowance,
+            ),
+        };
+        let distance =
+            ((x - from.0).powi(2) + (clamped_y - from.1).powi(2) + (z - from.2).powi(2)).sqrt();
+        if distance > max_distance {
+            self.rejected_moves += 1;
+            self.last = Some(from);
+            return MoveCheck::TooFar {
+                x: from.0,
+                y: from.1,
+                z: from.2,
+            };
+        }
+
+        self.last = Some((x, clamped_y, z));
+        self.teleport = last_teleport;
+        MoveCheck::Accepted { x, y: clamped_y, z }
+    }
+
+    /// Check a rotation, only finite angles are accepted
+    pub fn check_rotation(&mut self, yaw: f32, pitch: f32) -> bool {
+        let valid = yaw.is_finite() && pitch.is_finite();
+        if !valid {
+            self.rejected_moves += 1;
+        }
+        valid
+    }
+
+    /// The player was sent to `position` by the teleport `teleport`, later moves are measured
+    /// from there
+    pub fn sent_to(&mut self, position: (f64, f64, f64), teleport: i32) {
+        self.last = Some(position);
+        self.teleport = teleport;
+    }
+
+    /// Last position accepted from the player, `None` until it moves
+    pub fn last(&self) -> Option<(f64, f64, f64)> {
+        self.last
+    }
+
+    /// Number of moves and rotations refused
+    pub fn rejected_moves(&self) -> u32 {
+        self.rejected_moves
+    }
+
+    /// Number of moves accepted once their height was clamped into the world
+    pub fn clamped_moves(&self) -> u32 {
+        self.clamped_moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MoveCheck, MovementTracker};
+    use crate::utils::config::Movement;
+    use crate::utils::encoding::position::Position;
+
+    #[test]
+    fn moves_are_measured_from_the_last_one() {
+        let limits = Movement::default();
+        let spawn = Position::new(0, 100, 0);
+        let mut tracker = MovementTracker::default();
+
+        // Far from the spawn, but within the allowance of the login teleport
+        assert_eq!(
+            tracker.check((12.0, 100.0, 0.5), &spawn, 1, &limits),
+            MoveCheck::Accepted {
+                x: 12.0,
+                y: 100.0,
+                z: 0.5
+            }
+        );
+        assert_eq!(
+            tracker.check((30.0, 100.0, 0.5), &spawn, 1, &limits),
+            MoveCheck::TooFar {
+                x: 12.0,
+                y: 100.0,
+                z: 0.5
+            }
+        );
+        tracker.sent_to((12.0, 100.0, 0.5), 2);
+        assert_eq!(
+            tracker.check((30.0, 100.0, 0.5), &spawn, 2, &limits),
+            MoveCheck::TooFar {
+                x: 12.0,
+                y: 100.0,
+                z: 0.5
+            }
+        );
+        assert_eq!(tracker.rejected_moves(), 2);
+    }
+
+    #[test]
+    fn invalid_coordinates_are_refused_and_heights_clamped() {
+        let limits = Movement::default();
+        let position = Position::new(0, -64, 0);
+        let mut tracker = MovementTracker::default();
+
+        assert_eq!(
+            tracker.check((f64::NAN, 0.0, 0.0), &position, 1, &limits),
+            MoveCheck::Invalid
+        );
+        assert!(!tracker.check_rotation(f32::INFINITY, 0.0));
+        assert_eq!(
+            tracker.check((0.5, -70.0, 0.5), &position, 1, &limits),
+            MoveCheck::Accepted {
+                x: 0.5,
+                y: -64.0,
+                z: 0.5
+            }
+        );
+        assert_eq!(tracker.rejected_moves(), 2);
+        assert_eq!(tracker.clamped_moves(), 1);
+    }
+}
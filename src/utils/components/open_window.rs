@@ -0,0 +1,111 @@
+use crate::utils::encoding::slot::Slot;
+
+/// Kind of a window, which sets its slots and how the client draws it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowType {
+    /// Chest like window of 9 slots per row, from 1 to 6 rows
+    Generic9x(u8),
+    Generic3x3,
+    Anvil,
+    Beacon,
+    BlastFurnace,
+    BrewingStand,
+    Crafting,
+    Enchantment,
+    Furnace,
+    Grindstone,
+    Hopper,
+    Lectern,
+    Loom,
+    Merchant,
+    ShulkerBox,
+    Smithing,
+    Smoker,
+    CartographyTable,
+    Stonecutter,
+}
+
+impl WindowType {
+    /// Id of the window type in the `menu` registry of 1.20.1
+    pub fn id(&self) -> i32 {
+        match self {
+            WindowType::Generic9x(rows) => i32::from((*rows).clamp(1, 6) - 1),
+            WindowType::Generic3x3 => 6,
+            WindowType::Anvil => 7,
+            WindowType::Beacon => 8,
+            WindowType::BlastFurnace => 9,
+            WindowType::BrewingStand => 10,
+            WindowType::Crafting => 11,
+            WindowType::Enchantment => 12,
+            WindowType::Furnace => 13,
+            WindowType::Grindstone => 14,
+            WindowType::Hopper => 15,
+            WindowType::Lectern => 16,
+            WindowType::Loom => 17,
+            WindowType::Merchant => 18,
+            WindowType::ShulkerBox => 19,
+            WindowType::Smithing => 20,
+            WindowType::Smoker => 21,
+            WindowType::CartographyTable => 22,
+            WindowType::Stonecutter => 23,
+        }
+    }
+
+    /// Slots of the container, without the player inventory following them
+    pub fn slot_count(&self) -> usize {
+        match self {
+            WindowType::Generic9x(rows) => 9 * usize::from((*rows).clamp(1, 6)),
+            WindowType::Generic3x3 => 9,
+            WindowType::Beacon | WindowType::Lectern => 1,
+            WindowType::Enchantment | WindowType::Stonecutter => 2,
+            WindowType::Anvil
+            | WindowType::BlastFurnace
+            | WindowType::Furnace
+            | WindowType::Grindstone
+            | WindowType::Merchant
+            | WindowType::Smoker
+            | WindowType::CartographyTable => 3,
+            WindowType::Loom | WindowType::Smithing => 4,
+            WindowType::BrewingStand | WindowType::Hopper => 5,
+            WindowType::Crafting => 10,
+            WindowType::ShulkerBox => 27,
+        }
+    }
+}
+
+/// Window opened over the player inventory, like an anvil or the trades of a villager, see
+/// [`WindowManager`](crate::utils::components::window_manager::WindowManager)
+#[derive(Debug, Clone)]
+pub struct OpenWindow {
+    pub window_id: u8,
+    pub window_type: WindowType,
+    /// Slots of the container, the player inventory follows them in the window
+    pub slots: Vec<Slot>,
+    /// Name typed in an anvil, applied to its output once anvils exist
+    pub pending_rename: Option<String>,
+    /// Trade picked in the list of a villager
+    pub selected_trade: Option<i32>,
+    /// State id of the window, counted apart from the one of the player inventory, see
+    /// [`Inventory`](crate::utils::components::inventory::Inventory)
+    pub state_id: i32,
+}
+
+impl OpenWindow {
+    /// Empty window of `window_type`
+    pub fn new(window_id: u8, window_type: WindowType) -> Self {
+        Self {
+            window_id,
+            window_type,
+            slots: vec![Slot::empty(); window_type.slot_count()],
+            pending_rename: None,
+            selected_trade: None,
+            state_id: 0,
+        }
+    }
+
+    /// Bump the state id before sending slots, returns the new id
+    pub fn next_state_id(&mut self) -> i32 {
+        self.state_id = self.state_id.wrapping_add(1);
+        self.state_id
+    }
+}
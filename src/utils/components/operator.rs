@@ -0,0 +1,37 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::gamemode::Gamemode;
+
+/// Permission level of the operators listed in the config
+pub const MAX_OP_LEVEL: u8 = 4;
+/// Permission level of the game masters, required to query NBT, change the
+/// difficulty or edit command blocks
+pub const GAMEMASTER_OP_LEVEL: u8 = 2;
+/// Game mode required with the game master level to edit command and structure blocks
+const CREATIVE: u8 = 1;
+
+/// Marks a player as a server operator, players without it have permission level 0
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct Operator {
+    pub level: u8,
+}
+
+/// Permission level of `entity_id`
+pub async fn op_level(entity_id: ConnectionId, state: &GlobalState) -> u8 {
+    match state.world.get_component::<Operator>(entity_id).await {
+        Ok(operator) => operator.level,
+        Err(_) => 0,
+    }
+}
+
+/// Whether `entity_id` can edit command and structure blocks, as a game master in creative mode
+pub async fn can_use_game_master_blocks(entity_id: ConnectionId, state: &GlobalState) -> bool {
+    let creative = state
+        .world
+        .get_component::<Gamemode>(entity_id)
+        .await
+        .is_ok_and(|gamemode| gamemode.mode == CREATIVE);
+    creative && op_level(entity_id, state).await >= GAMEMASTER_OP_LEVEL
+}
@@ -0,0 +1,80 @@
+use ferrumc_macros::Component;
+
+/// Outcome of a teleport confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeleportConfirmation {
+    /// The awaited teleport, movement is accepted again
+    Confirmed,
+    /// A teleport replaced by a later one
+    Outdated,
+    /// A teleport that was never issued
+    Unknown,
+}
+
+/// Last teleport sent to a player and whether it was confirmed
+///
+/// Movement received between a teleport and its confirmation was sent from before the teleport,
+/// so it is ignored.
+#[derive(Debug, Default, Clone, Component)]
+pub struct PendingTeleports {
+    last_issued: i32,
+    awaiting: bool,
+    unknown_confirmations: u32,
+}
+
+impl PendingTeleports {
+    /// Id of a new teleport, movement is ignored until it is confirmed
+    pub fn issue(&mut self) -> i32 {
+        self.last_issued = self.last_issued.wrapping_add(1);
+        self.awaiting = true;
+        self.last_issued
+    }
+
+    /// Id of the last teleport sent, 0 before the first one
+    pub fn last_issued(&self) -> i32 {
+        self.last_issued
+    }
+
+    pub fn is_awaiting(&self) -> bool {
+        self.awaiting
+    }
+
+    pub fn confirm(&mut self, teleport_id: i32) -> TeleportConfirmation {
+        if teleport_id == self.last_issued && self.awaiting {
+            self.awaiting = false;
+            TeleportConfirmation::Confirmed
+        } else if (1..=self.last_issued).contains(&teleport_id) {
+            TeleportConfirmation::Outdated
+        } else {
+            self.unknown_confirmations += 1;
+            TeleportConfirmation::Unknown
+        }
+    }
+
+    /// Number of confirmations of teleports that were never issued
+    pub fn unknown_confirmations(&self) -> u32 {
+        self.unknown_confirmations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PendingTeleports, TeleportConfirmation};
+
+    #[test]
+    fn only_the_last_teleport_confirms() {
+        let mut teleports = PendingTeleports::default();
+        let first = teleports.issue();
+        let second = teleports.issue();
+        assert!(teleports.is_awaiting());
+
+        assert_eq!(teleports.confirm(first), TeleportConfirmation::Outdated);
+        assert!(teleports.is_awaiting());
+        assert_eq!(teleports.confirm(second), TeleportConfirmation::Confirmed);
+        assert!(!teleports.is_awaiting());
+
+        assert_eq!(teleports.confirm(second), TeleportConfirmation::Outdated);
+        assert_eq!(teleports.confirm(40), TeleportConfirmation::Unknown);
+        assert_eq!(teleports.unknown_confirmations(), 1);
+    }
+}
@@ -1,5 +1,25 @@
 use ferrumc_macros::{Component, Constructor};
 
+use crate::ecs::entity::EntityKey;
+use crate::net::packets::outgoing::player_abilities::PlayerAbilities;
+use crate::net::packets::outgoing::respawn::{Respawn, KEEP_ALL_DATA, KEEP_METADATA};
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::ConnectionId;
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::systems::time_system::TimeSystem;
+use crate::net::utils::metadata::resend_metadata;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::state::GlobalState;
+use crate::utils::components::abilities::Abilities;
+use crate::utils::components::gamemode::Gamemode;
+use crate::utils::components::loaded_chunks::LoadedChunks;
+use crate::utils::components::pending_teleports::PendingTeleports;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::constants::init;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
 #[derive(Component, Constructor, Debug)]
 pub struct Player {
     pub uuid: u128,
@@ -14,4 +34,129 @@ impl Player {
     pub fn get_username(&self) -> &str {
         &self.username
     }
+
+    /// Dimension `entity_id` is in, players start in the overworld
+    pub async fn dimension(entity_id: impl EntityKey, state: &GlobalState) -> Dimension {
+        state
+            .world
+            .get_component::<Dimension>(entity_id)
+            .await
+            .map_or(Dimension::Overworld, |dimension| dimension.clone())
+    }
+
+    /// Move `entity_id` to `dimension`, where it keeps its position, rotation and data
+    pub async fn change_dimension(
+        entity_id: ConnectionId,
+        dimension: Dimension,
+        state: &GlobalState,
+    ) -> Result<()> {
+        let position = state
+            .world
+            .get_component::<Position>(entity_id)
+            .await?
+            .clone();
+        let rotation = state
+            .world
+            .get_component::<Rotation>(entity_id)
+            .await?
+            .clone();
+        respawn_player(
+            entity_id,
+            dimension,
+            position,
+            rotation,
+            KEEP_ALL_DATA,
+            None,
+            state,
+        )
+        .await
+    }
+}
+
+/// Recreate the player `entity_id` in `dimension`, at `position`
+///
+/// The client drops its world with its player, the border, time, abilities, position and
+/// chunks of the new dimension are sent again. So is the whole metadata, unless it is kept.
+pub async fn respawn_player(
+    entity_id: ConnectionId,
+    dimension: Dimension,
+    position: Position,
+    rotation: Rotation,
+    data_kept: u8,
+    death_location: Option<(Dimension, Position)>,
+    state: &GlobalState,
+) -> Result<()> {
+    let gamemode = state
+        .world
+        .get_component::<Gamemode>(entity_id)
+        .await
+        .map_or(init::DEFAULT_GAMEMODE, |gamemode| gamemode.mode);
+    let abilities = state
+        .world
+        .get_component::<Abilities>(entity_id)
+        .await
+        .map_or_else(
+            |_| Abilities::for_gamemode(gamemode),
+            |abilities| abilities.clone(),
+        );
+    let teleport_id = state
+        .world
+        .get_component_storage()
+        .get_mut_or_insert_with::<PendingTeleports>(entity_id, Default::default)
+        .await
+        .issue();
+
+    let mut packet_queue = PacketQueue::new();
+    packet_queue
+        .queue(Respawn::new(
+            &dimension,
+            gamemode,
+            -1,
+            data_kept,
+            death_location
+                .as_ref()
+                .map(|(dimension, location)| (dimension, location.clone())),
+        ))
+        .await?;
+    // The client forgets the border of its previous world
+    packet_queue
+        .queue(state.world_borders.get(&dimension).initialize_packet())
+        .await?;
+    TimeSystem::queue_world_state(&mut packet_queue, state).await?;
+    packet_queue.queue(PlayerAbilities::new(&abilities)).await?;
+    packet_queue
+        .queue(SynchronizePlayerPosition::new(
+            &position,
+            &rotation,
+            teleport_id,
+        ))
+        .await?;
+
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, dimension)
+        .insert(entity_id, position)
+        .insert(entity_id, rotation);
+    // The client drops its chunks with its world, they aren't unloaded
+    if let Ok(mut loaded_chunks) = state
+        .world
+        .get_component_storage()
+        .get_mut::<LoadedChunks>(entity_id)
+        .await
+    {
+        loaded_chunks.clear();
+    }
+
+    {
+        let conn = state.connections.get_connection(entity_id)?;
+        let conn = conn.read().await;
+        conn.send_packets(packet_queue).await?;
+    }
+    if data_kept & KEEP_METADATA == 0 {
+        resend_metadata(entity_id, state).await?;
+    }
+    ChunkSender::send_chunks_in_background(state.clone(), entity_id as usize);
+    // The client hides its boss bars on respawn
+    state.boss_bars.resend(entity_id, state).await
 }
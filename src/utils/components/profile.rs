@@ -0,0 +1,18 @@
+use ferrumc_macros::Component;
+use serde::Deserialize;
+
+/// Property of a player profile, such as its skin under `textures`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    /// Signed by Mojang, to be checked by the clients
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// Properties of the profile of a player, forwarded by the proxy it connected through
+#[derive(Component, Debug, Clone, Default)]
+pub struct ProfileProperties {
+    pub properties: Vec<ProfileProperty>,
+}
@@ -0,0 +1,36 @@
+use ferrumc_macros::{Component, Constructor, Getter};
+
+/// Answer of a client to the resource pack offered by the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackStatus {
+    Loaded,
+    Declined,
+    FailedDownload,
+    Accepted,
+}
+
+impl ResourcePackStatus {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(ResourcePackStatus::Loaded),
+            1 => Some(ResourcePackStatus::Declined),
+            2 => Some(ResourcePackStatus::FailedDownload),
+            3 => Some(ResourcePackStatus::Accepted),
+            _ => None,
+        }
+    }
+
+    /// Whether the client won't use the pack
+    pub fn is_refused(&self) -> bool {
+        matches!(
+            self,
+            ResourcePackStatus::Declined | ResourcePackStatus::FailedDownload
+        )
+    }
+}
+
+/// Latest resource pack status reported by a player
+#[derive(Debug, Clone, Component, Getter, Constructor)]
+pub struct ResourcePack {
+    pub status: ResourcePackStatus,
+}
@@ -14,4 +14,17 @@ impl Rotation {
     pub fn add_pitch(&mut self, pitch: f32) {
         self.pitch += pitch;
     }
+
+    /// Unit vector the entity looks along
+    pub fn direction(&self) -> (f64, f64, f64) {
+        let (yaw, pitch) = (
+            (self.yaw as f64).to_radians(),
+            (self.pitch as f64).to_radians(),
+        );
+        (
+            -yaw.sin() * pitch.cos(),
+            -pitch.sin(),
+            yaw.cos() * pitch.cos(),
+        )
+    }
 }
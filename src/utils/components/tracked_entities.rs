@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+
+use ferrumc_macros::Component;
+
+/// Entities spawned on the client of a player, see
+/// [`EntityTracker`](crate::net::systems::entity_tracker::EntityTracker)
+#[derive(Debug, Default, Clone, Component)]
+pub struct TrackedEntities {
+    pub entities: HashSet<usize>,
+}
+
+impl TrackedEntities {
+    /// Track the entities now in view, returns those to spawn and those to remove
+    pub fn update(&mut self, visible: HashSet<usize>) -> (Vec<usize>, Vec<usize>) {
+        let mut spawned: Vec<usize> = visible.difference(&self.entities).copied().collect();
+        let mut removed: Vec<usize> = self.entities.difference(&visible).copied().collect();
+        spawned.sort_unstable();
+        removed.sort_unstable();
+        self.entities = visible;
+        (spawned, removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::TrackedEntities;
+
+    #[test]
+    fn only_changes_are_sent() {
+        let mut tracked = TrackedEntities::default();
+        let (spawned, removed) = tracked.update(HashSet::from([1, 2]));
+        assert_eq!(spawned, [1, 2]);
+        assert!(removed.is_empty());
+
+        let (spawned, removed) = tracked.update(HashSet::from([2, 3]));
+        assert_eq!(spawned, [3]);
+        assert_eq!(removed, [1]);
+
+        let (spawned, removed) = tracked.update(HashSet::from([2, 3]));
+        assert!(spawned.is_empty() && removed.is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+use ferrumc_macros::Component;
+
+/// Latest steering input of a player riding a vehicle, `sideways` and `forward` range from -1
+/// to 1
+#[derive(Debug, Default, Clone, Component)]
+pub struct VehicleInput {
+    pub sideways: f32,
+    pub forward: f32,
+    pub jump: bool,
+}
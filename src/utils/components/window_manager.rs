@@ -0,0 +1,130 @@
+use ferrumc_macros::Component;
+
+use crate::net::packets::incoming::click_container::PLAYER_WINDOW;
+use crate::net::packets::outgoing::close_container::CloseContainer;
+use crate::net::packets::outgoing::open_screen::OpenScreen;
+use crate::net::Connection;
+use crate::utils::chat::ChatComponent;
+use crate::utils::components::open_window::{OpenWindow, WindowType};
+use crate::Result;
+
+/// Highest id of a window, the ids wrap around to 1 after it
+pub const MAX_WINDOW_ID: u8 = 99;
+
+/// Windows a player opens over its inventory, at most one at a time
+///
+/// Ids are given from 1 to [`MAX_WINDOW_ID`], 0 being the player inventory which is always open.
+/// Removed along with the other components of the player when it disconnects.
+#[derive(Debug, Default, Component)]
+pub struct WindowManager {
+    /// Id of the last window opened, 0 before the first
+    last_window_id: u8,
+    pub open: Option<OpenWindow>,
+}
+
+impl WindowManager {
+    fn next_window_id(&mut self) -> u8 {
+        self.last_window_id = self.last_window_id % MAX_WINDOW_ID + 1;
+        self.last_window_id
+    }
+
+    /// Whether a click or close of `window_id` refers to a window the player has open
+    pub fn is_open(&self, window_id: u8) -> bool {
+        window_id == PLAYER_WINDOW
+            || self
+                .open
+                .as_ref()
+                .is_some_and(|window| window.window_id == window_id)
+    }
+
+    /// The open window, if its id is `window_id`
+    pub fn window_mut(&mut self, window_id: u8) -> Option<&mut OpenWindow> {
+        self.open
+            .as_mut()
+            .filter(|window| window.window_id == window_id)
+    }
+
+    /// Open an empty window of `window_type` named `title` on `conn`, closing the open one,
+    /// returns its id
+    ///
+    /// The client shows empty slots until a set container content packet fills them.
+    pub async fn open(
+        &mut self,
+        window_type: WindowType,
+        title: impl Into<ChatComponent>,
+        conn: &Connection,
+    ) -> Result<u8> {
+        self.close(conn).await?;
+        let window_id = self.next_window_id();
+        self.open = Some(OpenWindow::new(window_id, window_type));
+        conn.send_packet(OpenScreen::new(window_id, window_type, title))
+            .await?;
+        Ok(window_id)
+    }
+
+    /// Close the open window on `conn`, if there is one
+    pub async fn close(&mut self, conn: &Connection) -> Result<()> {
+        let Some(window) = self.open.take() else {
+            return Ok(());
+        };
+        conn.send_packet(CloseContainer::new(window.window_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::{WindowManager, MAX_WINDOW_ID};
+    use crate::tests::{test_connection, test_state};
+    use crate::utils::components::open_window::WindowType;
+
+    #[test]
+    fn ids_wrap_around_without_zero() {
+        let mut manager = WindowManager::default();
+        assert_eq!(manager.next_window_id(), 1);
+        for _ in 2..MAX_WINDOW_ID {
+            manager.next_window_id();
+        }
+        assert_eq!(manager.next_window_id(), MAX_WINDOW_ID);
+        assert_eq!(manager.next_window_id(), 1);
+    }
+
+    #[tokio::test]
+    async fn opening_a_window_closes_the_previous_one() {
+        let state = test_state().await;
+        let (entity_id, mut client) = test_connection(&state).await;
+        let conn = state.connections.get_connection(entity_id).unwrap();
+        let conn = conn.read().await;
+
+        let mut manager = WindowManager::default();
+        let first = manager
+            .open(WindowType::Generic9x(3), "Chest", &conn)
+            .await
+            .unwrap();
+        assert!(manager.is_open(first));
+        assert_eq!(manager.window_mut(first).unwrap().slots.len(), 27);
+
+        let second = manager
+            .open(WindowType::Crafting, "Crafting", &conn)
+            .await
+            .unwrap();
+        assert!(!manager.is_open(first));
+        assert!(manager.is_open(second));
+        manager.close(&conn).await.unwrap();
+        assert!(!manager.is_open(second));
+        assert!(manager.is_open(0));
+
+        // Open screen, close container of the first window, open screen, close container
+        let mut length = [0u8; 1];
+        let mut ids = Vec::new();
+        for _ in 0..4 {
+            client.read_exact(&mut length).await.unwrap();
+            let mut packet = vec![0u8; length[0] as usize];
+            client.read_exact(&mut packet).await.unwrap();
+            ids.push((packet[0], packet[1]));
+        }
+        assert_eq!(ids, [(0x30, 1), (0x11, 1), (0x30, 2), (0x11, 2)]);
+    }
+}
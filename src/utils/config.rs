@@ -1,13 +1,20 @@
 use std::io::ErrorKind::NotFound;
 use std::io::Write;
+use std::ops::Deref;
 use std::sync::OnceLock;
 
+use crate::database::cache::CacheMode;
+use crate::database::encoding::Compression;
+use crate::net::forwarding::ForwardingMode;
+use crate::net::packets::registry::UnknownPacketPolicy;
+use crate::net::rate_limit::PacketBudget;
+use crate::net::systems::chunk_sender::DEFAULT_CHUNK_RADIUS;
 use crate::utils::constants::{
     DEFAULT_CONFIG_FILE, DEFAULT_MAX_PLAYERS, DEFAULT_MOTD, DEFAULT_SERVER_HOST,
     DEFAULT_SERVER_PORT,
 };
 use crate::utils::error::Error;
-use config::{Config, ConfigError};
+use config::ConfigError;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -18,14 +25,272 @@ pub struct ServerConfig {
     pub motd: Vec<String>,
     pub max_players: i32,
     pub network_tick_rate: u32,
+    /// Farthest chunks are sent to players, in chunks, they may ask for fewer
+    #[serde(default = "default_max_view_distance")]
+    pub max_view_distance: i8,
     pub database: Database,
     pub world: String,
+    /// Usernames of the server operators
+    #[serde(default)]
+    pub ops: Vec<String>,
+    /// What happens to the packets the server has no handler for
+    #[serde(default)]
+    pub unknown_packets: UnknownPacketPolicy,
+    #[serde(default)]
+    pub movement: Movement,
+    #[serde(default)]
+    pub ability_checks: AbilityChecks,
+    #[serde(default)]
+    pub forwarding: Forwarding,
+    #[serde(default)]
+    pub compression: PacketCompression,
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    #[serde(default)]
+    pub status: Status,
+    #[serde(default)]
+    pub authentication: Authentication,
+    #[serde(default)]
+    pub chat: Chat,
+    #[serde(default)]
+    pub world_border: Border,
+    #[serde(default)]
+    pub player_list: PlayerList,
 }
 
+fn default_max_view_distance() -> i8 {
+    DEFAULT_CHUNK_RADIUS
+}
+
+/// Fields missing from the config take their default, so configs written by older versions
+/// keep loading
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Database {
+    pub cache_mode: CacheMode,
     pub cache_size: u32,
-    pub compression: String,
+    pub cache_idle_timeout: u64,
+    pub compression: Compression,
+    pub save_interval: u64,
+    pub write_behind: bool,
+    pub write_behind_interval: u64,
+    pub write_behind_max_pending: usize,
+    pub backup_interval: u64,
+    pub backup_keep: usize,
+    pub backup_dir: String,
+    pub map_size: usize,
+    pub max_map_size: usize,
+    pub scan_batch_size: usize,
+    pub min_free_space: u64,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            cache_mode: CacheMode::default(),
+            cache_size: 65536,
+            cache_idle_timeout: 300,
+            compression: Compression::default(),
+            save_interval: 30,
+            write_behind: false,
+            write_behind_interval: 500,
+            write_behind_max_pending: 256,
+            backup_interval: 0,
+            backup_keep: 5,
+            backup_dir: "backups".to_string(),
+            map_size: 1800,
+            max_map_size: 65536,
+            scan_batch_size: 256,
+            min_free_space: 512,
+        }
+    }
+}
+
+/// Whether players are authenticated by the session server, ignored behind a proxy which does it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Authentication {
+    pub online_mode: bool,
+    /// Base URL of the session server
+    pub session_server: String,
+}
+
+impl Default for Authentication {
+    fn default() -> Self {
+        Self {
+            online_mode: true,
+            session_server: "https://sessionserver.mojang.com".to_string(),
+        }
+    }
+}
+
+/// Format of the chat lines of vanilla, which clients decorate themselves
+pub const DEFAULT_CHAT_FORMAT: &str = "<{name}> {message}";
+
+/// How chat messages are shown to the players
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Chat {
+    /// Line shown for a message, `{name}` is replaced by the username of the sender and
+    /// `{message}` by the message
+    pub format: String,
+    /// Time a player waits between two messages, in milliseconds, 0 for no cooldown
+    pub cooldown_ms: u64,
+}
+
+impl Default for Chat {
+    fn default() -> Self {
+        Self {
+            format: DEFAULT_CHAT_FORMAT.to_string(),
+            cooldown_ms: 0,
+        }
+    }
+}
+
+/// What the world border does to the players beyond it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Border {
+    /// Hurt the players beyond the safe zone of the border
+    pub damage: bool,
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Self { damage: true }
+    }
+}
+
+/// Text shown above and below the players of the tab list
+///
+/// `{online}` is replaced by the players online, `{max}` by the max players, `{tps}` by the
+/// ticks per second and `{ping}` by the latency of the player looking at it, in milliseconds.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerList {
+    /// Empty to show no header
+    pub header: String,
+    /// Empty to show no footer
+    pub footer: String,
+    /// Time between two refreshes of the placeholders, in milliseconds
+    pub refresh_ms: u64,
+}
+
+impl Default for PlayerList {
+    fn default() -> Self {
+        Self {
+            header: String::new(),
+            footer: String::new(),
+            refresh_ms: 5000,
+        }
+    }
+}
+
+/// Player info forwarding of the proxy the server runs behind
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Forwarding {
+    pub mode: ForwardingMode,
+    /// Secret shared with Velocity, used to verify the forwarded player info
+    pub secret: String,
+}
+
+/// Compression of the packets exchanged with the players, enabled during login
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PacketCompression {
+    pub enabled: bool,
+    /// Size from which the data of a packet is compressed, in bytes
+    pub threshold: usize,
+}
+
+impl Default for PacketCompression {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 256,
+        }
+    }
+}
+
+/// Limits of the packets a connection may send, breaking them disconnects it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Length a packet may declare, before decompression
+    pub max_packet_length: usize,
+    pub packets_per_second: u32,
+    /// Packets that can be sent at once
+    pub burst: u32,
+    /// How long the address of a disconnected connection is banned, 0 to not ban it
+    pub ban_seconds: u64,
+    /// Budgets of packets of the play state, by id
+    pub overrides: Vec<PacketBudget>,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_packet_length: 2 * 1024 * 1024,
+            packets_per_second: 500,
+            burst: 1000,
+            ban_seconds: 0,
+            // Chat commands and chat messages
+            overrides: vec![
+                PacketBudget {
+                    packet_id: 0x04,
+                    per_second: 5,
+                    burst: 10,
+                },
+                PacketBudget {
+                    packet_id: 0x05,
+                    per_second: 5,
+                    burst: 10,
+                },
+            ],
+        }
+    }
+}
+
+/// What the server list shows besides the MOTD and the max players
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Status {
+    /// Only show how many players are online, not their names
+    pub hide_players: bool,
+    /// PNG of 64x64 pixels shown next to the server
+    pub favicon: String,
+    pub enforces_secure_chat: bool,
+    pub previews_chat: bool,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            hide_players: false,
+            favicon: "icon-64.png".to_string(),
+            enforces_secure_chat: false,
+            previews_chat: false,
+        }
+    }
+}
+
+/// Limits of the moves sent by players, moves beyond them send the player back
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Movement {
+    /// Distance a player may move between two positions, in blocks
+    pub max_distance_per_tick: f64,
+    /// Extra distance allowed for the first move after a teleport, whose destination is only
+    /// known to the block
+    pub teleport_allowance: f64,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self {
+            max_distance_per_tick: 10.0,
+            teleport_allowance: 4.0,
+        }
+    }
+}
+
+/// What happens to players claiming abilities they weren't granted, like flying in survival
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AbilityChecks {
+    /// Claims after which the player is kicked, 0 to only send it its abilities back
+    pub kick_after: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,14 +301,14 @@ pub struct Server {
 impl ServerConfig {
     /// Load the server configuration from the config file
     pub fn new() -> Result<Self, Error> {
-        let settings = Config::builder()
+        let settings = config::Config::builder()
             .add_source(config::File::with_name("config"))
             .build()
             .or_else(|err| {
                 if is_not_found(&err) {
                     info!("Config file wasn't found, creating a new one.");
                     create_config_file()?;
-                    return Config::builder()
+                    return config::Config::builder()
                         .add_source(config::File::with_name("config"))
                         .build()
                         .map_err(Error::from);
@@ -61,7 +326,7 @@ impl ServerConfig {
                 return if input.trim() == "y" {
                     info!("Creating new config file...");
                     create_config_file()?;
-                    Config::builder()
+                    config::Config::builder()
                         .add_source(config::File::with_name("config"))
                         .build()
                         .map_err(Error::from)
@@ -126,11 +391,21 @@ impl Default for ServerConfig {
             motd: vec![DEFAULT_MOTD.to_string()],
             max_players: DEFAULT_MAX_PLAYERS as i32,
             network_tick_rate: 0,
+            max_view_distance: default_max_view_distance(),
             world: "world".to_string(),
-            database: Database {
-                cache_size: 1024,
-                compression: "fast".to_string(),
-            },
+            database: Database::default(),
+            ops: Vec::new(),
+            unknown_packets: UnknownPacketPolicy::default(),
+            movement: Movement::default(),
+            ability_checks: AbilityChecks::default(),
+            forwarding: Forwarding::default(),
+            compression: PacketCompression::default(),
+            rate_limit: RateLimit::default(),
+            status: Status::default(),
+            authentication: Authentication::default(),
+            chat: Chat::default(),
+            world_border: Border::default(),
+            player_list: PlayerList::default(),
         }
     }
 }
@@ -140,3 +415,47 @@ pub fn get_global_config() -> &'static ServerConfig {
     static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
     CONFIG.get_or_init(|| ServerConfig::new().expect("Failed to load config"))
 }
+
+/// The server configuration as a resource of the world, for the systems to read it like the
+/// other resources they declare, see [`crate::create_world`]
+#[derive(Debug, Clone, Copy)]
+pub struct Config(&'static ServerConfig);
+
+impl Config {
+    pub fn new(config: &'static ServerConfig) -> Self {
+        Self(config)
+    }
+}
+
+impl Deref for Config {
+    type Target = ServerConfig;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+    use crate::database::encoding::Compression;
+
+    #[test]
+    fn database_sections_without_the_new_fields_load() {
+        let database: Database =
+            toml::from_str("cache_size = 1024\ncompression = \"zstd\"\n").unwrap();
+        assert_eq!(database.cache_size, 1024);
+        assert_eq!(database.compression, Compression::Zstd);
+        assert_eq!(database.save_interval, Database::default().save_interval);
+    }
+
+    #[test]
+    fn older_compression_names_load() {
+        // The section written by older versions
+        let database: Database =
+            toml::from_str("cache_size = 1024\ncompression = \"fast\"\n").unwrap();
+        assert_eq!(database.compression, Compression::Lz4);
+        let database: Database = toml::from_str("compression = \"best\"\n").unwrap();
+        assert_eq!(database.compression, Compression::Zstd);
+    }
+}
@@ -5,6 +5,12 @@ pub const DEFAULT_SERVER_HOST: &str = "localhost";
 pub const DEFAULT_SERVER_PORT: u32 = 25565;
 pub const DEFAULT_MOTD: &str = "A FerrumC Server";
 pub const DEFAULT_MAX_PLAYERS: u32 = 20;
+// Version of the game the server speaks the protocol of
+pub const GAME_VERSION: &str = "1.20.1";
+pub const PROTOCOL_VERSION: i32 = 763;
+// Build limits of the overworld, the max is exclusive
+pub const WORLD_MIN_Y: i32 = -64;
+pub const WORLD_MAX_Y: i32 = 320;
 
 pub mod init {
     pub const DEFAULT_SPAWN_X_POS: i32 = 0;
@@ -12,4 +18,5 @@ pub mod init {
     pub const DEFAULT_SPAWN_Z_POS: i32 = 0;
     pub const DEFAULT_SPAWN_YAW: f32 = 0.0;
     pub const DEFAULT_SPAWN_PITCH: f32 = 0.0;
+    pub const DEFAULT_GAMEMODE: u8 = 1;
 }
@@ -1,5 +1,7 @@
 pub mod bitset;
 pub mod position;
+pub mod remaining_bytes;
+pub mod slot;
 pub mod velocity;
 
 /*impl<S: NBTSerialize> Encode for &S {
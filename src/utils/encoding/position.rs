@@ -9,7 +9,7 @@ use ferrumc_macros::Component;
 ///
 /// Check out the [Position::net_encode] and [Position::net_decode]
 /// implementations for more information on how this struct is encoded and decoded
-#[derive(Clone, Component, Debug)]
+#[derive(Clone, Component, Debug, PartialEq)]
 pub struct Position {
     // Encoded as a 26 bit int
     pub x: i32,
@@ -0,0 +1,6 @@
+/// Bytes running until the end of a packet, e.g. a plugin message payload
+///
+/// Unlike `Vec<u8>` it has no length prefix, so it can only be the last field of a packet. Check
+/// out the [RemainingBytes::net_decode] implementation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemainingBytes(pub Vec<u8>);
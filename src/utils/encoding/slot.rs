@@ -0,0 +1,447 @@
+use std::io::Cursor;
+
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use simdnbt::owned::{Nbt, NbtCompound, NbtList, NbtTag};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::utils::chat::ChatComponent;
+use crate::utils::error::Error;
+use crate::world::blocks::write_nbt;
+
+/// Largest item NBT accepted from a client
+pub const MAX_ITEM_NBT_SIZE: usize = 2 * 1024 * 1024;
+/// Deepest nesting of compounds and lists in item NBT
+const MAX_NBT_DEPTH: usize = 512;
+
+const TAG_END: u8 = 0;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+
+/// Item stack in a slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub item_id: VarInt,
+    pub count: i8,
+    /// Encoded NBT of the item (enchantments, name...), kept as received
+    pub nbt: Option<Vec<u8>>,
+}
+
+impl ItemStack {
+    pub fn new(item_id: i32, count: i8) -> Self {
+        Self {
+            item_id: VarInt::from(item_id),
+            count,
+            nbt: None,
+        }
+    }
+
+    /// Whether both stacks hold the same item and can be merged
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item_id == other.item_id && self.nbt == other.nbt
+    }
+}
+
+/// Content of an inventory slot as sent in packets, `None` when it is empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Slot {
+    pub item: Option<ItemStack>,
+}
+
+impl Slot {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+}
+
+impl NetEncode for Slot {
+    /// Encodes the presence of an item, then its id, count and NBT (a single end tag without NBT)
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match &self.item {
+            Some(stack) => {
+                true.net_encode(writer).await?;
+                stack.item_id.net_encode(writer).await?;
+                stack.count.net_encode(writer).await?;
+                match &stack.nbt {
+                    Some(nbt) => writer.write_all(nbt).await?,
+                    None => TAG_END.net_encode(writer).await?,
+                }
+                Ok(())
+            }
+            None => false.net_encode(writer).await,
+        }
+    }
+}
+
+/// Item stack with its NBT parsed, to read or change its display name and enchantments
+///
+/// The tags it doesn't know are kept, converting it back to an [`ItemStack`] loses nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotData {
+    pub item_id: i32,
+    pub count: i8,
+    /// Empty for an item without NBT
+    pub nbt: NbtCompound,
+}
+
+impl SlotData {
+    pub fn new(item_id: i32, count: i8) -> Self {
+        Self {
+            item_id,
+            count,
+            nbt: NbtCompound::new(),
+        }
+    }
+
+    /// JSON text shown instead of the name of the item
+    pub fn display_name(&self) -> Option<String> {
+        let name = self.nbt.compound("display")?.string("Name")?;
+        Some(name.to_str().into_owned())
+    }
+
+    pub fn set_display_name(&mut self, name: &ChatComponent) {
+        if self.nbt.compound("display").is_none() {
+            self.nbt
+                .insert("display", NbtTag::Compound(NbtCompound::new()));
+        }
+        if let Some(display) = self.nbt.compound_mut("display") {
+            display.remove("Name");
+            display.insert("Name", name.to_json());
+        }
+    }
+
+    /// Enchantments of the item and their levels
+    pub fn enchantments(&self) -> Vec<(String, i16)> {
+        self.nbt
+            .list("Enchantments")
+            .and_then(NbtList::compounds)
+            .map(|enchantments| {
+                enchantments
+                    .iter()
+                    .filter_map(|enchantment| {
+                        let id = enchantment.string("id")?.to_str().into_owned();
+                        Some((id, enchantment.short("lvl")?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add the enchantment `id`, or change its level
+    pub fn enchant(&mut self, id: &str, level: i16) {
+        let mut enchantments = match self.nbt.remove("Enchantments") {
+            Some(NbtTag::List(NbtList::Compound(enchantments))) => enchantments,
+            _ => Vec::new(),
+        };
+        enchantments.retain(|enchantment| {
+            enchantment
+                .string("id")
+                .is_none_or(|other| other.to_str() != id)
+        });
+        let mut enchantment = NbtCompound::new();
+        enchantment.insert("id", id);
+        enchantment.insert("lvl", level);
+        enchantments.push(enchantment);
+        self.nbt
+            .insert("Enchantments", NbtList::Compound(enchantments));
+    }
+}
+
+impl TryFrom<&ItemStack> for SlotData {
+    type Error = Error;
+
+    fn try_from(stack: &ItemStack) -> Result<Self, Error> {
+        let nbt = match &stack.nbt {
+            Some(nbt) => match simdnbt::owned::read(&mut Cursor::new(nbt.as_slice()))? {
+                Nbt::Some(nbt) => nbt.into_inner(),
+                Nbt::None => NbtCompound::new(),
+            },
+            None => NbtCompound::new(),
+        };
+        Ok(Self {
+            item_id: stack.item_id.get_val(),
+            count: stack.count,
+            nbt,
+        })
+    }
+}
+
+impl From<SlotData> for ItemStack {
+    fn from(data: SlotData) -> Self {
+        Self {
+            item_id: VarInt::from(data.item_id),
+            count: data.count,
+            nbt: (!data.nbt.is_empty()).then(|| write_nbt(data.nbt)),
+        }
+    }
+}
+
+/// Nesting level being read by [read_item_nbt]
+enum NbtFrame {
+    Compound,
+    List { tag: u8, remaining: i32 },
+}
+
+/// Copy `len` bytes of the reader to `nbt`, failing past [MAX_ITEM_NBT_SIZE]
+async fn copy_nbt<R>(reader: &mut R, nbt: &mut Vec<u8>, len: usize) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin,
+{
+    if nbt.len().saturating_add(len) > MAX_ITEM_NBT_SIZE {
+        return Err(Error::InvalidNbt(format!(
+            "Item NBT is larger than {} bytes",
+            MAX_ITEM_NBT_SIZE
+        )));
+    }
+    let start = nbt.len();
+    nbt.resize(start + len, 0);
+    reader.read_exact(&mut nbt[start..]).await?;
+    Ok(())
+}
+
+/// Copy a length prefix of `size` bytes and return the length
+async fn copy_length<R>(reader: &mut R, nbt: &mut Vec<u8>, size: usize) -> Result<usize, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    copy_nbt(reader, nbt, size).await?;
+    let bytes = &nbt[nbt.len() - size..];
+    let len = match size {
+        2 => u16::from_be_bytes([bytes[0], bytes[1]]) as i64,
+        _ => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64,
+    };
+    usize::try_from(len).map_err(|_| Error::InvalidNbt(format!("Negative NBT length {}", len)))
+}
+
+/// Read the NBT of an item without parsing it into values, `None` for a lone end tag
+///
+/// Only the structure is walked, iteratively so that deeply nested NBT can't overflow the
+/// stack. Anything malformed, nested deeper than 512 levels or larger than
+/// [MAX_ITEM_NBT_SIZE] fails.
+pub async fn read_item_nbt<R>(reader: &mut R) -> Result<Option<Vec<u8>>, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut nbt = Vec::new();
+    copy_nbt(reader, &mut nbt, 1).await?;
+    match nbt[0] {
+        TAG_END => return Ok(None),
+        TAG_COMPOUND => {}
+        tag => {
+            return Err(Error::InvalidNbt(format!(
+                "Item NBT starts with tag {}",
+                tag
+            )))
+        }
+    }
+    // Name of the root compound
+    let len = copy_length(reader, &mut nbt, 2).await?;
+    copy_nbt(reader, &mut nbt, len).await?;
+
+    let mut stack = vec![NbtFrame::Compound];
+    while let Some(frame) = stack.last_mut() {
+        let tag = match frame {
+            NbtFrame::Compound => {
+                copy_nbt(reader, &mut nbt, 1).await?;
+                let tag = nbt[nbt.len() - 1];
+                if tag == TAG_END {
+                    stack.pop();
+                    continue;
+                }
+                let len = copy_length(reader, &mut nbt, 2).await?;
+                copy_nbt(reader, &mut nbt, len).await?;
+                tag
+            }
+            NbtFrame::List { remaining: 0, .. } => {
+                stack.pop();
+                continue;
+            }
+            NbtFrame::List { tag, remaining } => {
+                *remaining -= 1;
+                *tag
+            }
+        };
+
+        match tag {
+            1 => copy_nbt(reader, &mut nbt, 1).await?,
+            2 => copy_nbt(reader, &mut nbt, 2).await?,
+            3 | 5 => copy_nbt(reader, &mut nbt, 4).await?,
+            4 | 6 => copy_nbt(reader, &mut nbt, 8).await?,
+            7 | 11 | 12 => {
+                let len = copy_length(reader, &mut nbt, 4).await?;
+                let size = match tag {
+                    7 => 1,
+                    11 => 4,
+                    _ => 8,
+                };
+                copy_nbt(reader, &mut nbt, len.saturating_mul(size)).await?;
+            }
+            8 => {
+                let len = copy_length(reader, &mut nbt, 2).await?;
+                copy_nbt(reader, &mut nbt, len).await?;
+            }
+            TAG_LIST => {
+                copy_nbt(reader, &mut nbt, 1).await?;
+                let tag = nbt[nbt.len() - 1];
+                let len = copy_length(reader, &mut nbt, 4).await?;
+                if tag == TAG_END && len > 0 {
+                    return Err(Error::InvalidNbt("List of end tags".to_string()));
+                }
+                stack.push(NbtFrame::List {
+                    tag,
+                    remaining: len as i32,
+                });
+            }
+            TAG_COMPOUND => stack.push(NbtFrame::Compound),
+            tag => return Err(Error::InvalidNbt(format!("Unknown NBT tag {}", tag))),
+        }
+        if stack.len() > MAX_NBT_DEPTH {
+            return Err(Error::InvalidNbt(format!(
+                "Item NBT is nested deeper than {} levels",
+                MAX_NBT_DEPTH
+            )));
+        }
+    }
+    Ok(Some(nbt))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::{ItemStack, Slot, SlotData, MAX_ITEM_NBT_SIZE};
+    use crate::utils::chat::ChatComponent;
+    use crate::utils::impls::packet_impls::NetDecode;
+
+    /// Network id of the diamond sword
+    const DIAMOND_SWORD: i32 = 818;
+
+    async fn encode(slot: &Slot) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        slot.net_encode(&mut bytes).await.unwrap();
+        bytes
+    }
+
+    async fn round_trip(slot: Slot) {
+        let mut bytes = Vec::new();
+        slot.net_encode(&mut bytes).await.unwrap();
+        let mut cursor = Cursor::new(bytes);
+        let decoded = Slot::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(*decoded, slot);
+        // Nothing left behind for the next field
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+    }
+
+    /// Root compound holding a string, a list of compounds and an int array
+    fn item_nbt() -> Vec<u8> {
+        let mut nbt = vec![10, 0, 0];
+        nbt.extend([8, 0, 4]);
+        nbt.extend(b"Name");
+        nbt.extend([0, 5]);
+        nbt.extend(b"Sword");
+        nbt.extend([9, 0, 12]);
+        nbt.extend(b"Enchantments");
+        nbt.extend([10, 0, 0, 0, 2]);
+        nbt.extend([2, 0, 3]);
+        nbt.extend(b"lvl");
+        nbt.extend([0, 5, 0]);
+        nbt.extend([0]);
+        nbt.extend([11, 0, 1, b'a', 0, 0, 0, 1, 0, 0, 0, 7]);
+        nbt.push(0);
+        nbt
+    }
+
+    #[tokio::test]
+    async fn slots_round_trip() {
+        round_trip(Slot::empty()).await;
+        round_trip(Slot {
+            item: Some(ItemStack::new(1, 64)),
+        })
+        .await;
+        round_trip(Slot {
+            item: Some(ItemStack {
+                nbt: Some(item_nbt()),
+                ..ItemStack::new(800, 1)
+            }),
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn malformed_nbt_is_rejected() {
+        // Truncated in the middle of the list
+        let mut bytes = vec![1, 1, 1];
+        let nbt = item_nbt();
+        bytes.extend(&nbt[..nbt.len() / 2]);
+        assert!(Slot::net_decode(&mut Cursor::new(bytes)).await.is_err());
+
+        // Byte array larger than the limit
+        let mut bytes = vec![1, 1, 1, 10, 0, 0, 7, 0, 0];
+        bytes.extend((MAX_ITEM_NBT_SIZE as i32).to_be_bytes());
+        assert!(Slot::net_decode(&mut Cursor::new(bytes)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn slots_match_captures() {
+        assert_eq!(encode(&Slot::empty()).await, [0x00]);
+        let stone = Slot {
+            item: Some(ItemStack::new(1, 64)),
+        };
+        assert_eq!(encode(&stone).await, [0x01, 0x01, 0x40, 0x00]);
+
+        let mut sword = SlotData::new(DIAMOND_SWORD, 1);
+        sword.set_display_name(&ChatComponent::from("Excalibur"));
+        let sword = Slot {
+            item: Some(sword.into()),
+        };
+        let name = br#"{"text":"Excalibur"}"#;
+        let mut expected = vec![0x01, 0xB2, 0x06, 0x01];
+        // Unnamed root compound, holding the display compound and its name
+        expected.extend([0x0A, 0x00, 0x00, 0x0A, 0x00, 0x07]);
+        expected.extend(b"display");
+        expected.extend([0x08, 0x00, 0x04]);
+        expected.extend(b"Name");
+        expected.extend([0x00, name.len() as u8]);
+        expected.extend(name);
+        expected.extend([0x00, 0x00]);
+        assert_eq!(encode(&sword).await, expected);
+    }
+
+    #[tokio::test]
+    async fn slot_data_survives_the_network() {
+        let mut sword = SlotData::new(DIAMOND_SWORD, 1);
+        sword.set_display_name(&ChatComponent::from("Sword"));
+        sword.set_display_name(&ChatComponent::from("Excalibur"));
+        sword.enchant("minecraft:sharpness", 4);
+        sword.enchant("minecraft:unbreaking", 3);
+        sword.enchant("minecraft:sharpness", 5);
+        let bytes = encode(&Slot {
+            item: Some(sword.clone().into()),
+        })
+        .await;
+
+        let decoded = Slot::net_decode(&mut Cursor::new(bytes)).await.unwrap();
+        let decoded = SlotData::try_from(decoded.item.as_ref().unwrap()).unwrap();
+        assert_eq!(decoded, sword);
+        assert_eq!(
+            decoded.display_name().as_deref(),
+            Some(r#"{"text":"Excalibur"}"#)
+        );
+        assert_eq!(
+            decoded.enchantments(),
+            [
+                ("minecraft:unbreaking".to_string(), 3),
+                ("minecraft:sharpness".to_string(), 5)
+            ]
+        );
+
+        // Nothing to write for a plain item
+        let stone: ItemStack = SlotData::new(1, 64).into();
+        assert_eq!(stone, ItemStack::new(1, 64));
+    }
+}
@@ -25,6 +25,8 @@ pub enum Error {
     InvalidState(i32),
     #[error("Invalid Connection Metadata: {0}")]
     InvalidConnectionMetadata(String),
+    #[error("Packet of {0} bytes is too large")]
+    PacketTooLarge(i64),
 
     #[error(transparent)]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
@@ -47,6 +49,8 @@ pub enum Error {
     InvalidChunk(i32, i32, String),
     #[error("Chunk already exists at ({0}, {1})")]
     ChunkExists(i32, i32),
+    #[error("Block ({0}, {1}, {2}) is outside of the chunk or the world height")]
+    BlockOutOfBounds(i32, i32, i32),
 
     #[error(transparent)]
     SimdNbtError(#[from] simdnbt::Error),
@@ -59,6 +63,16 @@ pub enum Error {
 
     #[error("Database error: {0}")]
     DatabaseError(String),
+    #[error("Database is opened in read-only mode")]
+    ReadOnly,
+    #[error("Table not found: {0}")]
+    TableMissing(String),
+    #[error("Database transaction failed: {0}")]
+    TxnFailed(String),
+    #[error("Checksum mismatch: stored {stored:08x}, computed {computed:08x}")]
+    ChecksumMismatch { stored: u32, computed: u32 },
+    #[error("Not enough disk space: {available} bytes available, {required} required")]
+    DiskFull { available: u64, required: u64 },
 
     #[error("Invalid directive: {0}")]
     InvalidDirective(String),
@@ -90,6 +104,8 @@ pub enum Error {
     ConversionError,
     #[error(transparent)]
     CompressionError(std::io::Error),
+    #[error("Badly compressed packet: {0}")]
+    BadlyCompressed(String),
 
     #[error("Database error: {0}")]
     LmdbError(#[from] heed::Error),
@@ -105,6 +121,12 @@ impl From<Infallible> for Error {
     }
 }
 
+impl From<tokio::sync::oneshot::error::RecvError> for Error {
+    fn from(_: tokio::sync::oneshot::error::RecvError) -> Self {
+        Error::TxnFailed("Database task ended without returning a result".to_string())
+    }
+}
+
 impl From<Error> for std::io::Error {
     fn from(e: Error) -> std::io::Error {
         std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e))
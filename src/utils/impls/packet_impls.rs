@@ -3,6 +3,8 @@ use ferrumc_codec::network_types::varlong::Varlong;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::utils::encoding::position::Position;
+use crate::utils::encoding::remaining_bytes::RemainingBytes;
+use crate::utils::encoding::slot::{read_item_nbt, ItemStack, Slot};
 use crate::utils::error::Error;
 
 /// This trait is used to decode a type from a byte stream. It is implemented for all types that
@@ -250,6 +252,19 @@ impl NetDecode for u128 {
     }
 }
 
+impl<const N: usize> NetDecode for [u8; N] {
+    /// Decodes a fixed amount of bytes, e.g. a signature or a fixed size BitSet. Unlike a Vec,
+    /// there is no length prefix. Takes out N bytes.
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let mut buf = [0u8; N];
+        bytes.read_exact(&mut buf).await?;
+        Ok(Box::from(buf))
+    }
+}
+
 impl<V: NetDecode + Unpin> NetDecode for Vec<V> {
     /// Decodes a Vec from a byte stream. The first byte(s) is a VarInt representing the length of the
     /// Vec, followed by the elements of the Vec. The elements are decoded in order, and the Vec is
@@ -291,6 +306,42 @@ impl NetDecode for Position {
         Ok(Box::from(pos))
     }
 }
+
+impl NetDecode for RemainingBytes {
+    /// Decodes every byte left in the byte stream. Packets are decoded from a buffer of their own
+    /// length, so this takes out the rest of the packet.
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let mut buf = Vec::new();
+        bytes.read_to_end(&mut buf).await?;
+        Ok(Box::from(RemainingBytes(buf)))
+    }
+}
+impl NetDecode for Slot {
+    /// Decodes a Slot from a byte stream. A bool tells whether the slot holds an item, followed by
+    /// the item id as a VarInt, the count as a byte and the item NBT, read with
+    /// [read_item_nbt] which rejects malformed or oversized NBT.
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        if !*bool::net_decode(bytes).await? {
+            return Ok(Box::from(Slot::empty()));
+        }
+        let item_id = VarInt::read(bytes).await?;
+        let count = bytes.read_i8().await?;
+        let nbt = read_item_nbt(bytes).await?;
+        Ok(Box::from(Slot {
+            item: Some(ItemStack {
+                item_id,
+                count,
+                nbt,
+            }),
+        }))
+    }
+}
 /*
 /// This trait is used to encode a type into a byte stream. It is implemented for all types that
 /// can be encoded into a byte stream. This trait is async, as it is expected that encoding will
@@ -5,6 +5,7 @@ use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
 pub mod binary_utils;
+pub mod chat;
 pub mod components;
 pub mod config;
 pub mod constants;
@@ -13,6 +14,7 @@ pub mod error;
 pub mod hash;
 pub mod impls;
 pub mod prelude;
+pub mod registries;
 
 /// Sets up the logger. Needs to be run before anything else in order for logging to run end.
 pub fn setup_logger() -> Result<()> {
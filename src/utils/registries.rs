@@ -0,0 +1,179 @@
+use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+/// Sound events played by the server
+///
+/// Sounds are sent by name rather than by their id in the sound event registry, the client
+/// resolves the name itself so the ids can't drift from its registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    BlockAnvilLand,
+    BlockChestClose,
+    BlockChestOpen,
+    BlockGrassBreak,
+    BlockLeverClick,
+    BlockNoteBlockHarp,
+    BlockNoteBlockPling,
+    BlockStoneBreak,
+    BlockStonePlace,
+    BlockWoodBreak,
+    EntityArrowHitPlayer,
+    EntityEnderDragonGrowl,
+    EntityExperienceOrbPickup,
+    EntityGenericEat,
+    EntityGenericExplode,
+    EntityItemPickup,
+    EntityPlayerAttackStrong,
+    EntityPlayerDeath,
+    EntityPlayerHurt,
+    EntityPlayerLevelup,
+    UiButtonClick,
+    UiToastChallengeComplete,
+}
+
+impl Sound {
+    /// Name of the sound event, without its namespace
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sound::BlockAnvilLand => "block.anvil.land",
+            Sound::BlockChestClose => "block.chest.close",
+            Sound::BlockChestOpen => "block.chest.open",
+            Sound::BlockGrassBreak => "block.grass.break",
+            Sound::BlockLeverClick => "block.lever.click",
+            Sound::BlockNoteBlockHarp => "block.note_block.harp",
+            Sound::BlockNoteBlockPling => "block.note_block.pling",
+            Sound::BlockStoneBreak => "block.stone.break",
+            Sound::BlockStonePlace => "block.stone.place",
+            Sound::BlockWoodBreak => "block.wood.break",
+            Sound::EntityArrowHitPlayer => "entity.arrow.hit_player",
+            Sound::EntityEnderDragonGrowl => "entity.ender_dragon.growl",
+            Sound::EntityExperienceOrbPickup => "entity.experience_orb.pickup",
+            Sound::EntityGenericEat => "entity.generic.eat",
+            Sound::EntityGenericExplode => "entity.generic.explode",
+            Sound::EntityItemPickup => "entity.item.pickup",
+            Sound::EntityPlayerAttackStrong => "entity.player.attack.strong",
+            Sound::EntityPlayerDeath => "entity.player.death",
+            Sound::EntityPlayerHurt => "entity.player.hurt",
+            Sound::EntityPlayerLevelup => "entity.player.levelup",
+            Sound::UiButtonClick => "ui.button.click",
+            Sound::UiToastChallengeComplete => "ui.toast.challenge_complete",
+        }
+    }
+
+    pub fn identifier(&self) -> String {
+        format!("minecraft:{}", self.name())
+    }
+}
+
+impl NetEncode for Sound {
+    /// Encodes an id of 0, meaning the sound follows by name, then the name without a fixed
+    /// range
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(0).net_encode(writer).await?;
+        self.identifier().net_encode(writer).await?;
+        false.net_encode(writer).await
+    }
+}
+
+/// Volume slider of the client a sound is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoundCategory {
+    #[default]
+    Master = 0,
+    Music = 1,
+    Record = 2,
+    Weather = 3,
+    Block = 4,
+    Hostile = 5,
+    Neutral = 6,
+    Player = 7,
+    Ambient = 8,
+    Voice = 9,
+}
+
+impl NetEncode for SoundCategory {
+    async fn net_encode<W>(&self, writer: &mut W) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(*self as i32).net_encode(writer).await
+    }
+}
+
+/// Particles without data, by id in the particle type registry of 1.20.1
+///
+/// Particles with data are built with
+/// [`ParticleData`](crate::net::packets::outgoing::particle::ParticleData).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleType {
+    AngryVillager = 1,
+    Bubble = 4,
+    Cloud = 5,
+    Crit = 6,
+    DamageIndicator = 7,
+    DragonBreath = 8,
+    Effect = 16,
+    ElderGuardian = 17,
+    EnchantedHit = 18,
+    Enchant = 19,
+    EndRod = 20,
+    ExplosionEmitter = 22,
+    Explosion = 23,
+    SonicBoom = 24,
+    Firework = 26,
+    Flame = 28,
+    CherryLeaves = 29,
+    SoulFireFlame = 33,
+    Soul = 34,
+    Flash = 35,
+    HappyVillager = 36,
+    Composter = 37,
+    Heart = 38,
+    InstantEffect = 39,
+    ItemSlime = 42,
+    ItemSnowball = 43,
+    LargeSmoke = 44,
+    Lava = 45,
+    Note = 47,
+    Poof = 48,
+    Portal = 49,
+    Smoke = 51,
+    Sneeze = 52,
+    Spit = 53,
+    SweepAttack = 55,
+    TotemOfUndying = 56,
+    Splash = 58,
+    Witch = 59,
+}
+
+/// Ids of the particles taking data, in the particle type registry of 1.20.1
+pub const BLOCK_PARTICLE: i32 = 2;
+pub const BLOCK_MARKER_PARTICLE: i32 = 3;
+pub const DUST_PARTICLE: i32 = 14;
+pub const FALLING_DUST_PARTICLE: i32 = 25;
+pub const ITEM_PARTICLE: i32 = 40;
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::NetEncode;
+
+    use super::Sound;
+
+    #[tokio::test]
+    async fn sounds_are_sent_by_name() {
+        let mut bytes = Vec::new();
+        Sound::EntityGenericExplode
+            .net_encode(&mut bytes)
+            .await
+            .unwrap();
+        let name = b"minecraft:entity.generic.explode";
+        assert_eq!(bytes[..2], [0, name.len() as u8]);
+        assert_eq!(&bytes[2..2 + name.len()], name);
+        // No fixed range
+        assert_eq!(bytes[2 + name.len()..], [0]);
+    }
+}
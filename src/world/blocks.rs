@@ -1,21 +1,37 @@
+use std::sync::Arc;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use simdnbt::owned::{BaseNbt, NbtCompound, NbtTag};
 use tracing::debug;
 
+use crate::database::Database;
 use crate::state::GlobalState;
 use crate::utils::binary_utils::read_n_bits_u16;
+use crate::utils::constants::{WORLD_MAX_Y, WORLD_MIN_Y};
 use crate::utils::error::Error;
+use crate::world::chunk_format::{
+    BlockEntity, BlockStates, Chunk, CommandBlockData, Section, SignText, StructureBlockData,
+};
+use crate::world::conversions::block_state;
+use crate::world::dimension::{ChunkPos, Dimension};
+
+/// Number of blocks in a chunk section
+pub const SECTION_BLOCKS: usize = 16 * 16 * 16;
 
 pub async fn read_block(
     state: GlobalState,
     x: i32,
     y: i32,
     z: i32,
-    dimension: String,
+    dimension: Dimension,
 ) -> Result<String, Error> {
     let (chunk_x, chunk_z) = (x / 16, z / 16);
     debug!("Getting chunk: {} {}", chunk_x, chunk_z);
     let chunk = state
-        .database
-        .get_chunk(chunk_x, chunk_z, dimension)
+        .world
+        .resource::<Database>()
+        .await?
+        .get_chunk(&ChunkPos::new(chunk_x, chunk_z, dimension))
         .await?;
     if chunk.is_none() {
         return Err(Error::ChunkNotFound(chunk_x, chunk_z));
@@ -110,13 +126,414 @@ pub async fn read_block(
     }
 }
 
+/// Blocks a ray goes through
+const PASSABLE_BLOCKS: [&str; 5] = [
+    "minecraft:air",
+    "minecraft:cave_air",
+    "minecraft:void_air",
+    "minecraft:water",
+    "minecraft:lava",
+];
+/// Distance between the points a ray is sampled at, in blocks
+const RAY_STEP: f64 = 0.05;
+
+/// First block hit by a ray from `origin` along the unit vector `direction`, within `reach`
+/// blocks, with its id
+///
+/// Air and fluids are passed through. `None` if nothing was hit, or the ray left the loaded
+/// chunks or the world height first.
+pub async fn raycast(
+    state: &GlobalState,
+    origin: (f64, f64, f64),
+    direction: (f64, f64, f64),
+    reach: f64,
+) -> Result<Option<((i32, i32, i32), i32)>, Error> {
+    let mut chunk: Option<Arc<Chunk>> = None;
+    let mut last = None;
+    for step in 0..=(reach / RAY_STEP) as usize {
+        let distance = step as f64 * RAY_STEP;
+        let block = (
+            (origin.0 + direction.0 * distance).floor() as i32,
+            (origin.1 + direction.1 * distance).floor() as i32,
+            (origin.2 + direction.2 * distance).floor() as i32,
+        );
+        if last == Some(block) {
+            continue;
+        }
+        last = Some(block);
+
+        let (x, y, z) = block;
+        if !(WORLD_MIN_Y..WORLD_MAX_Y).contains(&y) {
+            return Ok(None);
+        }
+        let current = match &chunk {
+            Some(current) if (current.x_pos, current.z_pos) == (x >> 4, z >> 4) => current.clone(),
+            _ => {
+                let chunk_pos = ChunkPos::overworld(x >> 4, z >> 4);
+                let database = state.world.resource::<Database>().await?;
+                let Some(loaded) = database.get_chunk(&chunk_pos).await? else {
+                    return Ok(None);
+                };
+                chunk = Some(loaded.clone());
+                loaded
+            }
+        };
+        let id = match current.get_block_id(x, y, z) {
+            Ok(id) => id,
+            // Sections that were never generated hold nothing but air
+            Err(Error::BlockOutOfBounds(..)) => continue,
+            Err(e) => return Err(e),
+        };
+        let passable =
+            block_state(id).is_some_and(|block| PASSABLE_BLOCKS.contains(&block.name.as_str()));
+        if !passable {
+            return Ok(Some((block, id)));
+        }
+    }
+    Ok(None)
+}
+
+impl Section {
+    /// Network ids of every block of the section, indexed by `(y * 16 + z) * 16 + x`
+    pub(crate) fn block_ids(&self) -> Result<Vec<i32>, Error> {
+        let Some(block_states) = &self.block_states else {
+            return Ok(vec![0; SECTION_BLOCKS]);
+        };
+        let palette = block_states.net_palette.as_deref().unwrap_or_default();
+        let Some(data) = &block_states.data else {
+            // Sections without data hold a single block, air for empty sections
+            let id = palette.first().map_or(0, VarInt::get_val);
+            return Ok(vec![id; SECTION_BLOCKS]);
+        };
+
+        let bits = block_states.bits_per_block.unwrap_or_default() as usize;
+        if !(1..=32).contains(&bits) {
+            return Err(Error::MissingBlockStates);
+        }
+        let per_long = 64 / bits;
+        let mask = (1u64 << bits) - 1;
+        (0..SECTION_BLOCKS)
+            .map(|index| {
+                let long = data
+                    .get(index / per_long)
+                    .ok_or(Error::MissingBlockStates)?;
+                let entry = (*long as u64 >> ((index % per_long) * bits)) & mask;
+                palette
+                    .get(entry as usize)
+                    .map(VarInt::get_val)
+                    .ok_or(Error::MissingBlockStates)
+            })
+            .collect()
+    }
+
+    /// Replace every block of the section, rebuilding the palettes and the packed data
+    fn set_block_ids(&mut self, ids: &[i32]) -> Result<(), Error> {
+        let non_air_blocks = ids.iter().filter(|id| **id != 0).count();
+        if non_air_blocks == 0 {
+            self.set_empty();
+            return Ok(());
+        }
+
+        let mut palette = Vec::new();
+        let entries: Vec<usize> = ids
+            .iter()
+            .map(|id| {
+                palette
+                    .iter()
+                    .position(|entry| entry == id)
+                    .unwrap_or_else(|| {
+                        palette.push(*id);
+                        palette.len() - 1
+                    })
+            })
+            .collect();
+
+        // Same packing as the converted chunks: entries never span two longs
+        let bits = ((palette.len() as f32).log2().ceil() as usize).max(4);
+        let per_long = 64 / bits;
+        let mut data = vec![0u64; SECTION_BLOCKS.div_ceil(per_long)];
+        for (index, entry) in entries.into_iter().enumerate() {
+            data[index / per_long] |= (entry as u64) << ((index % per_long) * bits);
+        }
+
+        let states = palette
+            .iter()
+            .map(|id| {
+                block_state(*id)
+                    .cloned()
+                    .ok_or_else(|| Error::Generic(format!("Unknown block id {}", id)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.block_states = Some(BlockStates {
+            non_air_blocks: Some(non_air_blocks as i16),
+            bits_per_block: Some(bits as i8),
+            data: Some(data.into_iter().map(|long| long as i64).collect()),
+            palette: Some(states),
+            net_palette: Some(palette.into_iter().map(VarInt::from).collect()),
+        });
+        Ok(())
+    }
+}
+
+impl Chunk {
+    /// Section and index in it of the block at the world coordinates `x`, `y`, `z`
+    fn block_index(&self, x: i32, y: i32, z: i32) -> Result<(usize, usize), Error> {
+        if x >> 4 != self.x_pos || z >> 4 != self.z_pos {
+            return Err(Error::BlockOutOfBounds(x, y, z));
+        }
+        let section = self
+            .sections
+            .iter()
+            .flatten()
+            .position(|section| section.y as i32 == y >> 4)
+            .ok_or(Error::BlockOutOfBounds(x, y, z))?;
+        let index = ((y & 15) * 256 + (z & 15) * 16 + (x & 15)) as usize;
+        Ok((section, index))
+    }
+
+    /// Network id of the block at the world coordinates `x`, `y`, `z`
+    ///
+    /// The chunk must be in network mode, see [`Chunk::convert_to_net_mode`].
+    pub fn get_block_id(&self, x: i32, y: i32, z: i32) -> Result<i32, Error> {
+        let (section, index) = self.block_index(x, y, z)?;
+        let sections = self.sections.as_ref().ok_or(Error::MissingBlockStates)?;
+        Ok(sections[section].block_ids()?[index])
+    }
+
+    /// Replace the block at the world coordinates `x`, `y`, `z` with the block of network id
+    /// `id`, returns the id of the replaced block
+    ///
+    /// The chunk must be in network mode, see [`Chunk::convert_to_net_mode`].
+    pub fn set_block_id(&mut self, x: i32, y: i32, z: i32, id: i32) -> Result<i32, Error> {
+        let (section, index) = self.block_index(x, y, z)?;
+        let sections = self.sections.as_mut().ok_or(Error::MissingBlockStates)?;
+        let mut ids = sections[section].block_ids()?;
+        let previous = std::mem::replace(&mut ids[index], id);
+        if previous != id {
+            sections[section].set_block_ids(&ids)?;
+            // The data belonged to the replaced block
+            self.remove_block_entity(x, y, z);
+        }
+        Ok(previous)
+    }
+
+    /// Block entity at the world coordinates `x`, `y`, `z`
+    pub fn block_entity(&self, x: i32, y: i32, z: i32) -> Option<&BlockEntity> {
+        self.block_entities
+            .iter()
+            .flatten()
+            .find(|entity| (entity.x, entity.y, entity.z) == (x, y, z))
+    }
+
+    /// Store a block entity, replacing the one at the same coordinates
+    pub fn set_block_entity(&mut self, block_entity: BlockEntity) {
+        let (x, y, z) = (block_entity.x, block_entity.y, block_entity.z);
+        self.remove_block_entity(x, y, z);
+        self.block_entities
+            .get_or_insert_with(Vec::new)
+            .push(block_entity);
+    }
+
+    /// Remove the block entity at the world coordinates `x`, `y`, `z`, returns it
+    pub fn remove_block_entity(&mut self, x: i32, y: i32, z: i32) -> Option<BlockEntity> {
+        let block_entities = self.block_entities.as_mut()?;
+        let index = block_entities
+            .iter()
+            .position(|entity| (entity.x, entity.y, entity.z) == (x, y, z))?;
+        Some(block_entities.swap_remove(index))
+    }
+}
+
+impl SignText {
+    fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        compound.insert("messages", self.messages.clone());
+        if let Some(color) = &self.color {
+            compound.insert("color", color.as_str());
+        }
+        if let Some(glowing) = self.has_glowing_text {
+            compound.insert("has_glowing_text", glowing);
+        }
+        compound
+    }
+}
+
+impl CommandBlockData {
+    fn write_nbt(&self, compound: &mut NbtCompound) {
+        compound.insert("Command", self.command.as_str());
+        compound.insert("TrackOutput", self.track_output);
+        compound.insert("auto", self.auto);
+    }
+}
+
+impl StructureBlockData {
+    pub fn integrity(&self) -> f32 {
+        f32::from_bits(self.integrity_bits as u32)
+    }
+
+    pub fn set_integrity(&mut self, integrity: f32) {
+        self.integrity_bits = integrity.to_bits() as i32;
+    }
+
+    fn write_nbt(&self, compound: &mut NbtCompound) {
+        compound.insert("name", self.name.as_str());
+        compound.insert("author", self.author.as_str());
+        compound.insert("metadata", self.metadata.as_str());
+        compound.insert("mode", self.mode.as_str());
+        compound.insert("posX", self.pos_x);
+        compound.insert("posY", self.pos_y);
+        compound.insert("posZ", self.pos_z);
+        compound.insert("sizeX", self.size_x);
+        compound.insert("sizeY", self.size_y);
+        compound.insert("sizeZ", self.size_z);
+        compound.insert("mirror", self.mirror.as_str());
+        compound.insert("rotation", self.rotation.as_str());
+        compound.insert("integrity", self.integrity());
+        compound.insert("seed", self.seed);
+        compound.insert("ignoreEntities", self.ignore_entities);
+        compound.insert("showair", self.show_air);
+        compound.insert("showboundingbox", self.show_bounding_box);
+    }
+}
+
+impl BlockEntity {
+    /// Empty block entity of type `id` at the world coordinates `x`, `y`, `z`
+    pub fn new(id: &str, x: i32, y: i32, z: i32) -> Self {
+        Self {
+            id: id.to_string(),
+            x,
+            y,
+            z,
+            front_text: None,
+            back_text: None,
+            is_waxed: None,
+            command_block: None,
+            structure_block: None,
+        }
+    }
+
+    /// Data of the block entity as sent to clients, without its id and coordinates
+    pub fn data_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        if let Some(front_text) = &self.front_text {
+            compound.insert("front_text", NbtTag::Compound(front_text.to_nbt()));
+        }
+        if let Some(back_text) = &self.back_text {
+            compound.insert("back_text", NbtTag::Compound(back_text.to_nbt()));
+        }
+        if let Some(is_waxed) = self.is_waxed {
+            compound.insert("is_waxed", is_waxed);
+        }
+        if let Some(command_block) = &self.command_block {
+            command_block.write_nbt(&mut compound);
+        }
+        if let Some(structure_block) = &self.structure_block {
+            structure_block.write_nbt(&mut compound);
+        }
+        compound
+    }
+
+    /// Full data of the block entity, like it is saved by vanilla
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = self.data_nbt();
+        compound.insert("id", self.id.as_str());
+        compound.insert("x", self.x);
+        compound.insert("y", self.y);
+        compound.insert("z", self.z);
+        compound
+    }
+}
+
+/// Network bytes of `compound`, as a nameless root compound
+pub fn write_nbt(compound: NbtCompound) -> Vec<u8> {
+    let mut data = Vec::new();
+    BaseNbt::new("", compound).write(&mut data);
+    data
+}
+
 #[cfg(test)]
 mod tests {
     use tokio::net::TcpListener;
     use tracing::{info, warn};
 
+    use crate::database::chunks::tests::test_chunk;
+    use crate::utils::error::Error;
     use crate::utils::setup_logger;
-    use crate::world::blocks::read_block;
+    use crate::world::blocks::{read_block, write_nbt};
+    use crate::world::chunk_format::{BlockEntity, Palette, Section, SignText};
+    use crate::world::conversions::block_id;
+    use crate::world::dimension::Dimension;
+
+    #[test]
+    fn set_blocks_round_trip() {
+        let mut section = Section {
+            block_states: None,
+            biomes: None,
+            y: -1,
+            block_light: None,
+            sky_light: None,
+        };
+        section.set_empty();
+        let mut chunk = test_chunk(-1, 2);
+        chunk.sections = Some(vec![section]);
+        let stone = block_id(&Palette {
+            name: "minecraft:stone".to_string(),
+            properties: None,
+        })
+        .unwrap();
+
+        assert_eq!(chunk.set_block_id(-3, -5, 40, stone).unwrap(), 0);
+        assert_eq!(chunk.get_block_id(-3, -5, 40).unwrap(), stone);
+        assert_eq!(chunk.get_block_id(-4, -5, 40).unwrap(), 0);
+        let block_states = chunk.sections.as_ref().unwrap()[0].block_states.as_ref();
+        assert_eq!(block_states.unwrap().non_air_blocks, Some(1));
+        chunk.set_block_entity(BlockEntity::new("minecraft:sign", -3, -5, 40));
+        assert!(chunk.block_entity(-3, -5, 40).is_some());
+
+        // Breaking the last block empties the section again, and drops its block entity
+        assert_eq!(chunk.set_block_id(-3, -5, 40, 0).unwrap(), stone);
+        assert!(chunk.block_entity(-3, -5, 40).is_none());
+        let block_states = chunk.sections.as_ref().unwrap()[0].block_states.as_ref();
+        assert_eq!(block_states.unwrap().data, None);
+        assert!(matches!(
+            chunk.get_block_id(-3, 20, 40),
+            Err(Error::BlockOutOfBounds(-3, 20, 40))
+        ));
+    }
+
+    #[test]
+    fn sign_nbt_lists_every_line() {
+        let sign = BlockEntity {
+            id: "minecraft:oak_sign".to_string(),
+            x: 1,
+            y: 64,
+            z: -2,
+            front_text: Some(SignText {
+                messages: vec![r#"{"text":"hi"}"#.to_string(), r#"{"text":""}"#.to_string()],
+                color: Some("black".to_string()),
+                has_glowing_text: Some(0),
+            }),
+            back_text: None,
+            is_waxed: Some(0),
+            command_block: None,
+            structure_block: None,
+        };
+        let data = write_nbt(sign.to_nbt());
+        let nbt = simdnbt::owned::read(&mut std::io::Cursor::new(data.as_slice()))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(nbt.string("id").unwrap().to_str(), "minecraft:oak_sign");
+        assert_eq!(nbt.int("y"), Some(64));
+        let messages = nbt
+            .compound("front_text")
+            .and_then(|text| text.list("messages"))
+            .and_then(|messages| messages.strings())
+            .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].to_str(), r#"{"text":"hi"}"#);
+        assert!(nbt.compound("back_text").is_none());
+    }
 
     #[tokio::test]
     #[ignore]
@@ -129,7 +546,7 @@ mod tests {
             .unwrap();
         info!(
             "{}",
-            read_block(state, -537, 69, 51, "overworld".to_string())
+            read_block(state, -537, 69, 51, Dimension::Overworld)
                 .await
                 .unwrap()
         );
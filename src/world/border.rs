@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ferrumc_codec::enc::NetEncode;
+use parking_lot::Mutex;
+
+use crate::net::packets::outgoing::initialize_world_border::InitializeWorldBorder;
+use crate::net::packets::outgoing::set_border_center::SetBorderCenter;
+use crate::net::packets::outgoing::set_border_lerp_size::SetBorderLerpSize;
+use crate::net::packets::outgoing::set_border_size::SetBorderSize;
+use crate::net::packets::outgoing::set_border_warning_delay::SetBorderWarningDelay;
+use crate::net::packets::outgoing::set_border_warning_distance::SetBorderWarningDistance;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::error::Error;
+use crate::world::dimension::Dimension;
+
+/// Diameter of the vanilla border, around the whole world
+pub const DEFAULT_DIAMETER: f64 = 59_999_968.0;
+/// Farthest coordinate nether portals teleport to
+const PORTAL_TELEPORT_BOUNDARY: i32 = 29_999_984;
+/// Distance beyond the border players stand in without being hurt, in blocks
+pub const DAMAGE_SAFE_ZONE: f64 = 5.0;
+/// Damage per block beyond the safe zone
+pub const DAMAGE_PER_BLOCK: f64 = 0.2;
+
+/// Border moving from a diameter to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lerp {
+    from: f64,
+    to: f64,
+    start: Instant,
+    duration: Duration,
+}
+
+/// Square border of a dimension, players are kept within it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldBorder {
+    center: (f64, f64),
+    diameter: f64,
+    lerp: Option<Lerp>,
+    /// Players within this many blocks of the border see it tinting their screen
+    warning_blocks: i32,
+    /// A shrinking border tints the screen this many seconds before it reaches the player
+    warning_time: i32,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center: (0.0, 0.0),
+            diameter: DEFAULT_DIAMETER,
+            lerp: None,
+            warning_blocks: 5,
+            warning_time: 15,
+        }
+    }
+}
+
+impl WorldBorder {
+    pub fn center(&self) -> (f64, f64) {
+        self.center
+    }
+
+    /// Diameter at `now`, moving linearly while the border resizes
+    pub fn diameter_at(&self, now: Instant) -> f64 {
+        let Some(lerp) = self.lerp else {
+            return self.diameter;
+        };
+        let elapsed = now.saturating_duration_since(lerp.start);
+        if elapsed >= lerp.duration {
+            return lerp.to;
+        }
+        let progress = elapsed.as_secs_f64() / lerp.duration.as_secs_f64();
+        lerp.from + (lerp.to - lerp.from) * progress
+    }
+
+    pub fn diameter(&self) -> f64 {
+        self.diameter_at(Instant::now())
+    }
+
+    /// Distance between `x, z` and the border, negative outside of it
+    pub fn distance_inside(&self, x: f64, z: f64) -> f64 {
+        let radius = self.diameter() / 2.0;
+        let dx = radius - (x - self.center.0).abs();
+        let dz = radius - (z - self.center.1).abs();
+        dx.min(dz)
+    }
+
+    /// Damage of a player standing at `x, z`, `None` inside the border and its safe zone
+    pub fn damage(&self, x: f64, z: f64) -> Option<f32> {
+        let beyond = -(self.distance_inside(x, z) + DAMAGE_SAFE_ZONE);
+        if beyond <= 0.0 {
+            return None;
+        }
+        Some((beyond * DAMAGE_PER_BLOCK).floor().max(1.0) as f32)
+    }
+
+    /// Packet sending the whole border, a resize in progress continues on the client
+    pub fn initialize_packet(&self) -> InitializeWorldBorder {
+        let now = Instant::now();
+        let (target, remaining) = match self.lerp {
+            Some(lerp) => (
+                lerp.to,
+                lerp.duration
+                    .saturating_sub(now.saturating_duration_since(lerp.start)),
+            ),
+            None => (self.diameter, Duration::ZERO),
+        };
+        InitializeWorldBorder::new(
+            self.center,
+            self.diameter_at(now),
+            target,
+            remaining.as_millis() as i64,
+            PORTAL_TELEPORT_BOUNDARY,
+            self.warning_blocks,
+            self.warning_time,
+        )
+    }
+}
+
+/// World borders of the dimensions, the default border until one is changed
+///
+/// Changes are sent to the players of the dimension as they happen, the whole border is sent
+/// with [`WorldBorder::initialize_packet`] when a player joins the dimension.
+#[derive(Debug, Default)]
+pub struct WorldBorders {
+    borders: Mutex<HashMap<Dimension, WorldBorder>>,
+}
+
+impl WorldBorders {
+    pub fn get(&self, dimension: &Dimension) -> WorldBorder {
+        self.borders
+            .lock()
+            .get(dimension)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub async fn set_center(
+        &self,
+        dimension: &Dimension,
+        x: f64,
+        z: f64,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        self.update(dimension, |border| border.center = (x, z));
+        Self::send(dimension, SetBorderCenter::new(x, z), state).await
+    }
+
+    /// Resize the border at once
+    pub async fn set_diameter(
+        &self,
+        dimension: &Dimension,
+        diameter: f64,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        self.update(dimension, |border| {
+            border.diameter = diameter;
+            border.lerp = None;
+        });
+        Self::send(dimension, SetBorderSize::new(diameter), state).await
+    }
+
+    /// Resize the border from its current diameter to `diameter` over `duration`
+    pub async fn lerp_diameter(
+        &self,
+        dimension: &Dimension,
+        diameter: f64,
+        duration: Duration,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        if duration.is_zero() {
+            return self.set_diameter(dimension, diameter, state).await;
+        }
+        let start = Instant::now();
+        let from = self.update(dimension, |border| {
+            let from = border.diameter_at(start);
+            border.diameter = diameter;
+            border.lerp = Some(Lerp {
+                from,
+                to: diameter,
+                start,
+                duration,
+            });
+            from
+        });
+        let packet = SetBorderLerpSize::new(from, diameter, duration.as_millis() as i64);
+        Self::send(dimension, packet, state).await
+    }
+
+    pub async fn set_warning_delay(
+        &self,
+        dimension: &Dimension,
+        seconds: i32,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        self.update(dimension, |border| border.warning_time = seconds);
+        Self::send(dimension, SetBorderWarningDelay::new(seconds), state).await
+    }
+
+    pub async fn set_warning_distance(
+        &self,
+        dimension: &Dimension,
+        blocks: i32,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        self.update(dimension, |border| border.warning_blocks = blocks);
+        Self::send(dimension, SetBorderWarningDistance::new(blocks), state).await
+    }
+
+    fn update<R>(&self, dimension: &Dimension, f: impl FnOnce(&mut WorldBorder) -> R) -> R {
+        f(self.borders.lock().entry(dimension.clone()).or_default())
+    }
+
+    /// Send a change of the border of `dimension` to its players
+    async fn send(
+        dimension: &Dimension,
+        packet: impl NetEncode,
+        state: &GlobalState,
+    ) -> Result<(), Error> {
+        // Players all play in the overworld for now
+        if *dimension != Dimension::Overworld {
+            return Ok(());
+        }
+        broadcast(packet, state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Lerp, WorldBorder};
+
+    fn border(diameter: f64) -> WorldBorder {
+        WorldBorder {
+            center: (100.0, 0.0),
+            diameter,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn damage_grows_beyond_the_safe_zone() {
+        let border = border(20.0);
+        assert_eq!(border.distance_inside(100.0, 0.0), 10.0);
+        assert_eq!(border.distance_inside(115.0, 0.0), -5.0);
+        assert_eq!(border.damage(114.0, 0.0), None);
+        assert_eq!(border.damage(115.0, 0.0), None);
+        // At least half a heart past the safe zone
+        assert_eq!(border.damage(116.0, 0.0), Some(1.0));
+        assert_eq!(border.damage(100.0, -40.0), Some(5.0));
+    }
+
+    #[test]
+    fn resizes_move_linearly() {
+        let start = Instant::now();
+        let mut border = border(10.0);
+        border.lerp = Some(Lerp {
+            from: 100.0,
+            to: 10.0,
+            start,
+            duration: Duration::from_secs(10),
+        });
+        assert_eq!(border.diameter_at(start), 100.0);
+        assert_eq!(border.diameter_at(start + Duration::from_secs(5)), 55.0);
+        assert_eq!(border.diameter_at(start + Duration::from_secs(60)), 10.0);
+    }
+}
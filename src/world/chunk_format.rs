@@ -3,6 +3,9 @@ use ferrumc_codec::network_types::varint::VarInt;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::database::encoding::{decode_legacy, Versioned};
+use crate::utils::error::Error;
+
 attribute_alias! {
     #[apply(ChunkDerives)] = #[derive(nbt_lib::NBTSerialize, nbt_lib::NBTDeserialize,
     Debug,
@@ -42,6 +45,7 @@ pub struct Chunk {
     #[nbt(rename = "LastUpdate")]
     pub last_update: Option<i64>,
     pub sections: Option<Vec<Section>>,
+    pub block_entities: Option<Vec<BlockEntity>>,
 }
 
 #[apply(ChunkDerives)]
@@ -128,8 +132,193 @@ pub struct Properties {
     pub axis: Option<String>,
 }
 
+/// Extra data of a block, like the text of a sign, at the world coordinates `x`, `y`, `z`
+///
+/// Only the sign, command block and structure block fields are kept for now, the data of other
+/// block entities is dropped on import.
+#[apply(ChunkDerives)]
+#[derive(deepsize::DeepSizeOf)]
+pub struct BlockEntity {
+    pub id: String,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub front_text: Option<SignText>,
+    pub back_text: Option<SignText>,
+    pub is_waxed: Option<i8>,
+    pub command_block: Option<CommandBlockData>,
+    pub structure_block: Option<StructureBlockData>,
+}
+
+/// Program of a command block, its mode and whether it is conditional are in its block state
+#[apply(ChunkDerives)]
+#[derive(deepsize::DeepSizeOf)]
+pub struct CommandBlockData {
+    pub command: String,
+    pub track_output: i8,
+    /// Whether it runs without redstone
+    pub auto: i8,
+}
+
+/// Settings of a structure block, the names are those of the vanilla NBT
+#[apply(ChunkDerives)]
+#[derive(deepsize::DeepSizeOf)]
+pub struct StructureBlockData {
+    pub name: String,
+    pub author: String,
+    pub metadata: String,
+    /// `SAVE`, `LOAD`, `CORNER` or `DATA`
+    pub mode: String,
+    /// Offset of the structure from the block
+    pub pos_x: i32,
+    pub pos_y: i32,
+    pub pos_z: i32,
+    pub size_x: i32,
+    pub size_y: i32,
+    pub size_z: i32,
+    /// `NONE`, `LEFT_RIGHT` or `FRONT_BACK`
+    pub mirror: String,
+    /// `NONE`, `CLOCKWISE_90`, `CLOCKWISE_180` or `COUNTERCLOCKWISE_90`
+    pub rotation: String,
+    /// Bits of the float integrity, see [`StructureBlockData::integrity`]
+    pub integrity_bits: i32,
+    pub seed: i64,
+    pub ignore_entities: i8,
+    pub show_air: i8,
+    pub show_bounding_box: i8,
+}
+
+/// Text on a side of a sign, the 4 lines are JSON text components
+#[apply(ChunkDerives)]
+#[derive(deepsize::DeepSizeOf)]
+pub struct SignText {
+    pub messages: Vec<String>,
+    pub color: Option<String>,
+    pub has_glowing_text: Option<i8>,
+}
+
 #[apply(ChunkDerives)]
 #[derive(deepsize::DeepSizeOf)]
 pub struct Biomes {
     pub palette: Vec<String>,
 }
+
+/// [`Chunk`] at version 1, before block entities were stored
+#[derive(Decode)]
+struct ChunkV1 {
+    dimension: Option<String>,
+    status: String,
+    data_version: i32,
+    heightmaps: Option<Heightmaps>,
+    is_light_on: Option<i8>,
+    inhabited_time: Option<i64>,
+    y_pos: i32,
+    x_pos: i32,
+    z_pos: i32,
+    structures: Option<Structures>,
+    last_update: Option<i64>,
+    sections: Option<Vec<Section>>,
+}
+
+/// [`Chunk`] at version 2, before command and structure blocks were stored
+#[derive(Decode)]
+struct ChunkV2 {
+    dimension: Option<String>,
+    status: String,
+    data_version: i32,
+    heightmaps: Option<Heightmaps>,
+    is_light_on: Option<i8>,
+    inhabited_time: Option<i64>,
+    y_pos: i32,
+    x_pos: i32,
+    z_pos: i32,
+    structures: Option<Structures>,
+    last_update: Option<i64>,
+    sections: Option<Vec<Section>>,
+    block_entities: Option<Vec<BlockEntityV2>>,
+}
+
+/// [`BlockEntity`] at chunk version 2
+#[derive(Decode)]
+struct BlockEntityV2 {
+    id: String,
+    x: i32,
+    y: i32,
+    z: i32,
+    front_text: Option<SignText>,
+    back_text: Option<SignText>,
+    is_waxed: Option<i8>,
+}
+
+impl From<ChunkV1> for Chunk {
+    fn from(chunk: ChunkV1) -> Self {
+        Chunk {
+            dimension: chunk.dimension,
+            status: chunk.status,
+            data_version: chunk.data_version,
+            heightmaps: chunk.heightmaps,
+            is_light_on: chunk.is_light_on,
+            inhabited_time: chunk.inhabited_time,
+            y_pos: chunk.y_pos,
+            x_pos: chunk.x_pos,
+            z_pos: chunk.z_pos,
+            structures: chunk.structures,
+            last_update: chunk.last_update,
+            sections: chunk.sections,
+            block_entities: None,
+        }
+    }
+}
+
+impl From<ChunkV2> for Chunk {
+    fn from(chunk: ChunkV2) -> Self {
+        let block_entities = chunk.block_entities.map(|block_entities| {
+            block_entities
+                .into_iter()
+                .map(|block_entity| BlockEntity {
+                    id: block_entity.id,
+                    x: block_entity.x,
+                    y: block_entity.y,
+                    z: block_entity.z,
+                    front_text: block_entity.front_text,
+                    back_text: block_entity.back_text,
+                    is_waxed: block_entity.is_waxed,
+                    command_block: None,
+                    structure_block: None,
+                })
+                .collect()
+        });
+        Chunk {
+            dimension: chunk.dimension,
+            status: chunk.status,
+            data_version: chunk.data_version,
+            heightmaps: chunk.heightmaps,
+            is_light_on: chunk.is_light_on,
+            inhabited_time: chunk.inhabited_time,
+            y_pos: chunk.y_pos,
+            x_pos: chunk.x_pos,
+            z_pos: chunk.z_pos,
+            structures: chunk.structures,
+            last_update: chunk.last_update,
+            sections: chunk.sections,
+            block_entities,
+        }
+    }
+}
+
+impl Versioned for Chunk {
+    /// Bump when the layout of [`Chunk`] (or of any type it contains) changes
+    const VERSION: u8 = 3;
+
+    fn upgrade(version: u8, bytes: &[u8]) -> crate::Result<Self> {
+        // Each older version gets a frozen copy of its layout, decoded with `decode_legacy` and
+        // converted to the next version
+        match version {
+            1 => Ok(decode_legacy::<ChunkV1>(bytes)?.into()),
+            2 => Ok(decode_legacy::<ChunkV2>(bytes)?.into()),
+            _ => Err(Error::DeserializationError(format!(
+                "Unknown chunk format version {version}"
+            ))),
+        }
+    }
+}
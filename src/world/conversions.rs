@@ -1,10 +1,12 @@
+use crate::net::packets::incoming::login_start::NBT_CODEC;
 use crate::utils::error::Error;
 use crate::world::chunk_format::{BlockStates, Chunk, Palette, Section};
 use ferrumc_codec::enc::NetEncode;
 use ferrumc_codec::network_types::varint::VarInt;
 use hashbrown::HashMap;
 use lazy_static::lazy_static;
-use std::io::Read;
+use simdnbt::owned::Nbt;
+use std::io::{Cursor, Read};
 use tokio::io::AsyncWrite;
 use tracing::trace;
 
@@ -23,6 +25,43 @@ lazy_static! {
     };
     static ref BLOCK2ID: HashMap<Palette, i32> =
         ID2BLOCK.iter().map(|(k, v)| (v.clone(), *k)).collect();
+    static ref BIOME2ID: HashMap<String, i32> = biome_ids(NBT_CODEC);
+}
+
+/// Ids of the biomes of the registry codec sent to the clients, by name
+fn biome_ids(codec: &[u8]) -> HashMap<String, i32> {
+    let Ok(Nbt::Some(codec)) = simdnbt::owned::read(&mut Cursor::new(codec)) else {
+        return HashMap::new();
+    };
+    let biomes = codec
+        .compound("minecraft:worldgen/biome")
+        .and_then(|registry| registry.list("value"))
+        .and_then(|biomes| biomes.compounds())
+        .unwrap_or_default();
+    biomes
+        .iter()
+        .filter_map(|biome| Some((biome.string("name")?.to_string(), biome.int("id")?)))
+        .collect()
+}
+
+/// Network id of a biome, `None` if it is not in the registry codec
+pub fn biome_id(name: &str) -> Option<i32> {
+    BIOME2ID.get(name).copied()
+}
+
+/// Number of biomes in the registry codec
+pub fn biome_count() -> usize {
+    BIOME2ID.len()
+}
+
+/// Network id of a block state, `None` if it is not in the block mappings
+pub fn block_id(block: &Palette) -> Option<i32> {
+    BLOCK2ID.get(block).copied()
+}
+
+/// Block state of a network id, `None` if it is not in the block mappings
+pub fn block_state(id: i32) -> Option<&'static Palette> {
+    ID2BLOCK.get(&id)
 }
 
 impl Section {
@@ -0,0 +1,122 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use tracing::warn;
+
+use crate::database::Database;
+use crate::utils::error::Error;
+
+/// Key of the difficulty in the settings table, the difficulty id then whether it is locked
+const DIFFICULTY_SETTING: &str = "difficulty";
+
+/// Difficulty of the world, numbered like the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Peaceful = 0,
+    Easy = 1,
+    #[default]
+    Normal = 2,
+    Hard = 3,
+}
+
+impl Difficulty {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Difficulty::Peaceful),
+            1 => Some(Difficulty::Easy),
+            2 => Some(Difficulty::Normal),
+            3 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Difficulty shared by every player of the world, operators can change it until it is locked
+#[derive(Debug)]
+pub struct WorldDifficulty {
+    difficulty: AtomicU8,
+    locked: AtomicBool,
+}
+
+impl WorldDifficulty {
+    pub fn new(difficulty: Difficulty, locked: bool) -> Self {
+        Self {
+            difficulty: AtomicU8::new(difficulty.id()),
+            locked: AtomicBool::new(locked),
+        }
+    }
+
+    /// Difficulty saved in `database`, the default one if none was saved yet
+    pub async fn load(database: &Database) -> Self {
+        match database.load_setting(DIFFICULTY_SETTING).await {
+            Ok(Some(value)) => match value.as_slice() {
+                [id, locked] => {
+                    Self::new(Difficulty::from_id(*id).unwrap_or_default(), *locked != 0)
+                }
+                _ => {
+                    warn!("Invalid difficulty setting {:?}, using the default", value);
+                    Self::default()
+                }
+            },
+            Ok(None) => Self::default(),
+            Err(e) => {
+                warn!("Failed to load the difficulty: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the current difficulty to `database`
+    pub async fn save(&self, database: &Database) -> Result<(), Error> {
+        let value = vec![self.difficulty().id(), self.is_locked() as u8];
+        database.save_setting(DIFFICULTY_SETTING, value).await
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_id(self.difficulty.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Change the difficulty, returns whether it changed, it can't once locked
+    pub fn set(&self, difficulty: Difficulty) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        self.difficulty.swap(difficulty.id(), Ordering::Relaxed) != difficulty.id()
+    }
+
+    /// Lock the difficulty, or unlock it
+    pub fn set_locked(&self, locked: bool) {
+        self.locked.store(locked, Ordering::Relaxed);
+    }
+}
+
+impl Default for WorldDifficulty {
+    fn default() -> Self {
+        Self::new(Difficulty::default(), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Difficulty, WorldDifficulty};
+
+    #[test]
+    fn locked_difficulty_stays() {
+        let difficulty = WorldDifficulty::default();
+        assert_eq!(difficulty.difficulty(), Difficulty::Normal);
+        assert!(difficulty.set(Difficulty::Hard));
+        assert!(!difficulty.set(Difficulty::Hard));
+
+        difficulty.set_locked(true);
+        assert!(!difficulty.set(Difficulty::Peaceful));
+        assert_eq!(difficulty.difficulty(), Difficulty::Hard);
+        assert_eq!(Difficulty::from_id(4), None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ferrumc_macros::Component;
+
+use crate::database::keys::chunk_key;
+
+/// A world dimension, also the component of the dimension a player is in
+///
+/// The vanilla dimensions don't allocate, custom ones share their name through an `Arc`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Component)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+    Custom(Arc<str>),
+}
+
+impl Dimension {
+    /// Name of the dimension, as stored in [`Chunk::dimension`](crate::world::chunk_format::Chunk)
+    pub fn name(&self) -> &str {
+        match self {
+            Dimension::Overworld => "overworld",
+            Dimension::Nether => "the_nether",
+            Dimension::End => "the_end",
+            Dimension::Custom(name) => name,
+        }
+    }
+
+    /// Namespaced name of the dimension, as sent to the client
+    pub fn identifier(&self) -> String {
+        match self.name() {
+            name if name.contains(':') => name.to_string(),
+            name => format!("minecraft:{name}"),
+        }
+    }
+
+    /// Dimension type of the registry codec, custom dimensions are shaped like the overworld
+    pub fn type_identifier(&self) -> &'static str {
+        match self {
+            Dimension::Nether => "minecraft:the_nether",
+            Dimension::End => "minecraft:the_end",
+            Dimension::Overworld | Dimension::Custom(_) => "minecraft:overworld",
+        }
+    }
+}
+
+impl From<&str> for Dimension {
+    fn from(name: &str) -> Self {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "overworld" => Dimension::Overworld,
+            "the_nether" => Dimension::Nether,
+            "the_end" => Dimension::End,
+            _ => Dimension::Custom(Arc::from(name)),
+        }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Position of a chunk, used as the key of every chunk API of the database
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+    pub dimension: Dimension,
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, z: i32, dimension: Dimension) -> Self {
+        Self { x, z, dimension }
+    }
+
+    /// Position of a chunk in the overworld
+    pub fn overworld(x: i32, z: i32) -> Self {
+        Self::new(x, z, Dimension::Overworld)
+    }
+
+    /// Key of this chunk in the database
+    pub fn key(&self) -> u128 {
+        chunk_key(self.dimension.name(), self.x, self.z)
+    }
+}
+
+impl fmt::Display for ChunkPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}) in {}", self.x, self.z, self.dimension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkPos, Dimension};
+
+    #[test]
+    fn custom_dimensions_share_keys_with_vanilla_names() {
+        assert_eq!(Dimension::from("minecraft:the_nether"), Dimension::Nether);
+        assert_eq!(
+            ChunkPos::new(3, -4, Dimension::from("the_end")).key(),
+            ChunkPos::new(3, -4, Dimension::End).key()
+        );
+        assert_ne!(
+            ChunkPos::overworld(3, -4).key(),
+            ChunkPos::new(3, -4, Dimension::from("custom")).key()
+        );
+    }
+
+    #[test]
+    fn identifiers_are_namespaced() {
+        assert_eq!(Dimension::Nether.identifier(), "minecraft:the_nether");
+        assert_eq!(Dimension::from("mining").identifier(), "minecraft:mining");
+        assert_eq!(
+            Dimension::from("custom:mining").identifier(),
+            "custom:mining"
+        );
+        assert_eq!(
+            Dimension::from("custom:mining").type_identifier(),
+            "minecraft:overworld"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::database::Database;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::{BlockStates, Chunk, Palette};
+use crate::world::dimension::{ChunkPos, Dimension};
+use flate2::write::ZlibEncoder;
+use nbt_lib::NBTSerialize;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info, warn};
+
+/// Chunks per region side
+const REGION_SIZE: i32 = 32;
+const SECTOR_SIZE: usize = 4096;
+/// Location table + timestamp table
+const HEADER_SECTORS: usize = 2;
+/// Compression id of zlib in the chunk payload header
+const ZLIB_COMPRESSION: u8 = 2;
+
+impl Chunk {
+    /// Strips the network only data and restores what vanilla expects to find on disk.
+    fn convert_to_disk_mode(&mut self) {
+        self.dimension = None;
+        for section in self.sections.iter_mut().flatten() {
+            let block_states = section.block_states.get_or_insert(BlockStates {
+                non_air_blocks: None,
+                bits_per_block: None,
+                data: None,
+                palette: None,
+                net_palette: None,
+            });
+            block_states.non_air_blocks = None;
+            block_states.bits_per_block = None;
+            block_states.net_palette = None;
+            // Sections emptied during the network conversion lose their palette
+            if block_states.palette.is_none() {
+                block_states.data = None;
+                block_states.palette = Some(vec![Palette {
+                    name: "minecraft:air".to_string(),
+                    properties: None,
+                }]);
+            }
+        }
+    }
+}
+
+/// Encodes the 32x32 chunks of a region as an Anvil `.mca` file.
+///
+/// `chunks` is indexed by `(x & 31) + (z & 31) * 32`, missing chunks are left as empty entries
+/// in the location table.
+fn encode_region(chunks: Vec<Option<Arc<Chunk>>>, timestamp: u32) -> Result<Vec<u8>> {
+    let mut header = vec![0u8; HEADER_SECTORS * SECTOR_SIZE];
+    let mut body = Vec::new();
+
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let Some(chunk) = chunk else {
+            continue;
+        };
+        // Converted in place if no one else holds the chunk, copied otherwise
+        let mut chunk = Arc::unwrap_or_clone(chunk);
+        chunk.convert_to_disk_mode();
+
+        let mut nbt = Vec::new();
+        chunk.nbt_serialize(&mut nbt)?;
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&nbt)?;
+        let compressed = encoder.finish()?;
+
+        // 4 bytes length, 1 byte compression type, then the payload, padded to a full sector
+        let length = compressed.len() + 1;
+        let sectors = (length + 4).div_ceil(SECTOR_SIZE);
+        if sectors > u8::MAX as usize {
+            warn!(
+                "(Skipped) Chunk {} {} is too large for a region file ({} sectors)",
+                chunk.x_pos, chunk.z_pos, sectors
+            );
+            continue;
+        }
+
+        let offset = HEADER_SECTORS + body.len() / SECTOR_SIZE;
+        header[index * 4..index * 4 + 3].copy_from_slice(&(offset as u32).to_be_bytes()[1..]);
+        header[index * 4 + 3] = sectors as u8;
+        header[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]
+            .copy_from_slice(&timestamp.to_be_bytes());
+
+        body.extend_from_slice(&(length as u32).to_be_bytes());
+        body.push(ZLIB_COMPRESSION);
+        body.extend_from_slice(&compressed);
+        body.resize(body.len().next_multiple_of(SECTOR_SIZE), 0);
+    }
+
+    header.extend_from_slice(&body);
+    Ok(header)
+}
+
+impl Database {
+    /// Exports the region `r.<region_x>.<region_z>` of `dimension` to an Anvil `.mca` file at
+    /// `out`.
+    ///
+    /// Fails if `out` already exists, unless `overwrite` is set.
+    pub async fn export_region(
+        &self,
+        dimension: &Dimension,
+        region_x: i32,
+        region_z: i32,
+        out: &Path,
+        overwrite: bool,
+    ) -> Result<()> {
+        let positions = (0..REGION_SIZE)
+            .flat_map(|z| (0..REGION_SIZE).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                ChunkPos::new(
+                    region_x * REGION_SIZE + x,
+                    region_z * REGION_SIZE + z,
+                    dimension.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let chunks = self.batch_get(&positions).await?;
+        let exported = chunks.iter().filter(|chunk| chunk.is_some()).count();
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or_default();
+        let region =
+            tokio::task::spawn_blocking(move || encode_region(chunks, timestamp)).await??;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .create_new(!overwrite)
+            .open(out)
+            .await
+            .map_err(|e| {
+                Error::Generic(format!(
+                    "Could not create region file {}: {}",
+                    out.display(),
+                    e
+                ))
+            })?;
+        file.write_all(&region).await?;
+        file.flush().await?;
+
+        debug!("Wrote {} bytes to {}", region.len(), out.display());
+        info!(
+            "Exported {} chunks of region {} {} ({}) to {}",
+            exported,
+            region_x,
+            region_z,
+            dimension,
+            out.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_region;
+    use crate::world::chunk_format::Chunk;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn test_chunk(x: i32, z: i32) -> Chunk {
+        Chunk {
+            dimension: Some("overworld".to_string()),
+            status: "full".to_string(),
+            data_version: 3465,
+            heightmaps: None,
+            is_light_on: None,
+            inhabited_time: None,
+            y_pos: -4,
+            x_pos: x,
+            z_pos: z,
+            structures: None,
+            last_update: None,
+            sections: None,
+            block_entities: None,
+        }
+    }
+
+    #[test]
+    fn region_roundtrip() {
+        let mut chunks = vec![None; 1024];
+        chunks[0] = Some(Arc::new(test_chunk(0, 0)));
+        chunks[5 + 7 * 32] = Some(Arc::new(test_chunk(5, 7)));
+
+        let region = encode_region(chunks, 0).unwrap();
+        assert_eq!(region.len() % 4096, 0);
+
+        let mut region = fastanvil::Region::from_stream(Cursor::new(region)).unwrap();
+        assert!(region.read_chunk(0, 0).unwrap().is_some());
+        assert!(region.read_chunk(5, 7).unwrap().is_some());
+        assert!(region.read_chunk(1, 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Hardness of the blocks and speed of the tools digging them
+//!
+//! Hardnesses are those of 1.20.1 for the common natural and building blocks, other blocks
+//! are assumed to break like a block of hardness [`DEFAULT_HARDNESS`] with any tool.
+
+/// Hardness of the blocks missing from the table
+pub const DEFAULT_HARDNESS: f32 = 1.0;
+
+/// Kinds of tools digging some blocks faster
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+}
+
+impl Tool {
+    /// Tool of the item `name` and its digging speed, `None` for items that aren't tools
+    pub fn of_item(name: &str) -> Option<(Tool, f32)> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        let (tier, kind) = name.rsplit_once('_')?;
+        let tool = match kind {
+            "pickaxe" => Tool::Pickaxe,
+            "axe" => Tool::Axe,
+            "shovel" => Tool::Shovel,
+            "hoe" => Tool::Hoe,
+            _ => return None,
+        };
+        let speed = match tier {
+            "wooden" => 2.0,
+            "stone" => 4.0,
+            "iron" => 6.0,
+            "diamond" => 8.0,
+            "netherite" => 9.0,
+            "golden" => 12.0,
+            _ => return None,
+        };
+        Some((tool, speed))
+    }
+}
+
+/// How hard the block `name` is to break and the tool digging it faster, `None` for blocks
+/// that can't be broken
+///
+/// Blocks that drop nothing without their tool also need it to be dug at full speed, see
+/// [`break_ticks`].
+fn properties(name: &str) -> Option<(f32, Option<Tool>, bool)> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    Some(match name {
+        "air" | "cave_air" | "void_air" | "water" | "lava" | "bedrock" | "barrier"
+        | "end_portal" | "end_portal_frame" | "nether_portal" | "command_block"
+        | "structure_block" | "jigsaw" | "light" => return None,
+        "grass" | "tall_grass" | "fern" | "large_fern" | "dead_bush" | "seagrass"
+        | "tall_seagrass" | "dandelion" | "poppy" | "blue_orchid" | "allium" | "azure_bluet"
+        | "red_tulip" | "orange_tulip" | "white_tulip" | "pink_tulip" | "oxeye_daisy"
+        | "cornflower" | "lily_of_the_valley" | "sunflower" | "lilac" | "rose_bush" | "peony"
+        | "brown_mushroom" | "red_mushroom" | "sugar_cane" | "lily_pad" | "torch"
+        | "wall_torch" | "redstone_wire" | "wheat" | "carrots" | "potatoes" | "beetroots" => {
+            (0.0, None, false)
+        }
+        name if name.ends_with("_sapling") => (0.0, None, false),
+        "snow" => (0.1, Some(Tool::Shovel), true),
+        "snow_block" => (0.2, Some(Tool::Shovel), true),
+        name if name.ends_with("_leaves") => (0.2, Some(Tool::Hoe), false),
+        "glass" | "glass_pane" => (0.3, None, false),
+        name if name.ends_with("_stained_glass") => (0.3, None, false),
+        "netherrack" => (0.4, Some(Tool::Pickaxe), true),
+        "dirt" | "coarse_dirt" | "rooted_dirt" | "mud" | "podzol" | "sand" | "red_sand"
+        | "soul_sand" | "soul_soil" => (0.5, Some(Tool::Shovel), false),
+        "ice" | "packed_ice" => (0.5, Some(Tool::Pickaxe), false),
+        "grass_block" | "mycelium" | "gravel" | "clay" | "farmland" | "dirt_path" => {
+            (0.6, Some(Tool::Shovel), false)
+        }
+        "calcite" => (0.75, Some(Tool::Pickaxe), true),
+        "sandstone" | "red_sandstone" => (0.8, Some(Tool::Pickaxe), true),
+        name if name.ends_with("_wool") => (0.8, None, false),
+        "stone" | "granite" | "polished_granite" | "diorite" | "polished_diorite" | "andesite"
+        | "polished_andesite" | "tuff" | "stone_bricks" | "dripstone_block" => {
+            (1.5, Some(Tool::Pickaxe), true)
+        }
+        "bookshelf" => (1.5, Some(Tool::Axe), false),
+        "cobblestone" | "mossy_cobblestone" | "bricks" => (2.0, Some(Tool::Pickaxe), true),
+        name if name.ends_with("_planks")
+            || name.ends_with("_log")
+            || name.ends_with("_wood")
+            || name.ends_with("_stem")
+            || name.ends_with("_hyphae") =>
+        {
+            (2.0, Some(Tool::Axe), false)
+        }
+        "crafting_table" | "chest" | "trapped_chest" | "barrel" => (2.5, Some(Tool::Axe), false),
+        "deepslate" | "polished_deepslate" => (3.0, Some(Tool::Pickaxe), true),
+        "cobbled_deepslate" => (3.5, Some(Tool::Pickaxe), true),
+        "furnace" => (3.5, Some(Tool::Pickaxe), true),
+        name if name.starts_with("deepslate_") && name.ends_with("_ore") => {
+            (4.5, Some(Tool::Pickaxe), true)
+        }
+        name if name.ends_with("_ore") => (3.0, Some(Tool::Pickaxe), true),
+        "gold_block" => (3.0, Some(Tool::Pickaxe), true),
+        "iron_block" | "diamond_block" | "coal_block" | "copper_block" => {
+            (5.0, Some(Tool::Pickaxe), true)
+        }
+        "obsidian" | "crying_obsidian" | "netherite_block" => (50.0, Some(Tool::Pickaxe), true),
+        _ => (DEFAULT_HARDNESS, None, false),
+    })
+}
+
+/// Hardness of the block `name`, `None` for blocks that can't be broken
+pub fn hardness(name: &str) -> Option<f32> {
+    properties(name).map(|(hardness, ..)| hardness)
+}
+
+/// Ticks it takes to break the block `name` by digging it with the item `held`, 0 for blocks
+/// broken at once and `None` for blocks that can't be broken
+///
+/// Like vanilla without enchantments or effects: the tool of the block multiplies the speed,
+/// and blocks needing a tool take more than three times longer without it. Tiers aren't
+/// checked, any tier of the tool harvests the block.
+pub fn break_ticks(name: &str, held: Option<&str>) -> Option<u32> {
+    let (hardness, tool, needs_tool) = properties(name)?;
+    if hardness == 0.0 {
+        return Some(0);
+    }
+    let held = held.and_then(Tool::of_item);
+    let right_tool = tool.is_some() && held.map(|(held, _)| held) == tool;
+    let speed = match held {
+        Some((_, speed)) if right_tool => speed,
+        _ => 1.0,
+    };
+    let divisor = if needs_tool && !right_tool {
+        100.0
+    } else {
+        30.0
+    };
+    // Within a tick of damage, the block breaks as soon as it is hit
+    let ticks = hardness * divisor / speed;
+    if ticks <= 1.0 {
+        return Some(0);
+    }
+    Some(ticks.ceil() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{break_ticks, hardness, Tool};
+
+    #[test]
+    fn tools_are_named_after_their_tier() {
+        assert_eq!(
+            Tool::of_item("minecraft:diamond_pickaxe"),
+            Some((Tool::Pickaxe, 8.0))
+        );
+        assert_eq!(
+            Tool::of_item("minecraft:wooden_axe"),
+            Some((Tool::Axe, 2.0))
+        );
+        assert_eq!(Tool::of_item("minecraft:iron_sword"), None);
+        assert_eq!(Tool::of_item("minecraft:stone"), None);
+    }
+
+    #[test]
+    fn break_times_follow_the_hardness_and_tool() {
+        assert_eq!(hardness("minecraft:stone"), Some(1.5));
+        assert_eq!(hardness("minecraft:deepslate_iron_ore"), Some(4.5));
+        assert_eq!(hardness("minecraft:bedrock"), None);
+
+        // 7.5 seconds by hand, 1.15 with a wooden pickaxe
+        assert_eq!(break_ticks("minecraft:stone", None), Some(150));
+        assert_eq!(
+            break_ticks("minecraft:stone", Some("minecraft:wooden_pickaxe")),
+            Some(23)
+        );
+        // The wrong tool is no faster
+        assert_eq!(
+            break_ticks("minecraft:stone", Some("minecraft:diamond_shovel")),
+            Some(150)
+        );
+        assert_eq!(break_ticks("minecraft:dirt", None), Some(15));
+        assert_eq!(
+            break_ticks("minecraft:dirt", Some("minecraft:golden_shovel")),
+            Some(2)
+        );
+        assert_eq!(break_ticks("minecraft:poppy", None), Some(0));
+        assert_eq!(break_ticks("minecraft:bedrock", None), None);
+    }
+}
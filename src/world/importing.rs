@@ -1,30 +1,30 @@
-use crate::database::encoding::ZstdCodec;
+use crate::database::encoding::{ChunkCodec, Compression};
+use crate::database::Database;
 use crate::state::GlobalState;
-use crate::utils::hash::hash;
 use crate::utils::prelude::*;
 use crate::world::chunk_format::Chunk;
+use crate::world::dimension::{ChunkPos, Dimension};
 use fastanvil::{ChunkData, Region};
 use indicatif::{ProgressBar, ProgressStyle};
 use nbt_lib::NBTDeserializeBytes;
 use rayon::prelude::*;
 use std::env;
 use std::fs::File;
-use std::io::Cursor;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 const DEFAULT_BATCH_SIZE: u8 = 150;
 
-/// A serialized chunk is a tuple of the chunk's hash and the compressed chunk data
-/// (hash, compressed_chunk_data)
-pub struct SerializedChunk(u64, Vec<u8>);
+/// A serialized chunk is a tuple of the chunk's key and the compressed chunk data
+/// (key, compressed_chunk_data)
+pub struct SerializedChunk(u128, Vec<u8>);
 
 impl SerializedChunk {
-    pub fn new(hash: u64, data: Vec<u8>) -> Self {
+    pub fn new(hash: u128, data: Vec<u8>) -> Self {
         Self(hash, data)
     }
-    pub fn hash(&self) -> u64 {
+    pub fn hash(&self) -> u128 {
         self.0
     }
 
@@ -70,7 +70,7 @@ async fn get_total_chunks(dir: &PathBuf) -> Result<usize> {
     let files = std::fs::read_dir(dir)?;
     let regions: Vec<Region<File>> = files
         .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().is_file() && entry.path().extension() == Some("mca".as_ref()))
+        .filter(|entry| is_region_file(&entry.path()))
         .filter_map(|entry| match File::open(entry.path()) {
             Ok(file) => Region::from_stream(file).ok(),
             Err(_) => {
@@ -89,108 +89,258 @@ async fn get_total_chunks(dir: &PathBuf) -> Result<usize> {
         .sum())
 }
 
+/// Outcome of an import, counted per chunk
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportStats {
+    /// Chunks written to the database
+    pub imported: usize,
+    /// Chunks that were readable but could not be converted (e.g. not fully generated)
+    pub skipped: usize,
+    /// Chunks whose region entry or NBT could not be read
+    pub corrupt: usize,
+}
+
+impl ImportStats {
+    pub fn total(&self) -> usize {
+        self.imported + self.skipped + self.corrupt
+    }
+}
+
+impl std::ops::AddAssign for ImportStats {
+    fn add_assign(&mut self, other: Self) {
+        self.imported += other.imported;
+        self.skipped += other.skipped;
+        self.corrupt += other.corrupt;
+    }
+}
+
+/// Reads a single Anvil chunk and serializes it for the database.
+///
+/// Returns `Err` if the chunk NBT is corrupt and `Ok(None)` if the chunk was read but can't be
+/// converted to network mode, in which case it is skipped.
 async fn process_chunk(
     chunk_data: Vec<u8>,
-    file_name: &str,
-    bar: Arc<ProgressBar>,
-) -> Result<SerializedChunk> {
-    let mut chunk = Chunk::read_from_bytes(&mut Cursor::new(chunk_data)).map_err(|e| {
-        bar.abandon_with_message(format!("Chunk {} failed to import", file_name));
-        Error::Generic(format!("Could not read chunk {} {}", e, file_name))
-    })?;
-
-    chunk.convert_to_net_mode().map_err(|e| {
-        bar.abandon_with_message(format!(
-            "Chunk {} {} failed to import",
-            chunk.x_pos, chunk.z_pos
-        ));
-        Error::Generic(format!(
-            "Could not convert chunk {} {} to network mode: {}",
+    dimension: Dimension,
+    compression: Compression,
+) -> Result<Option<SerializedChunk>> {
+    let mut chunk = Chunk::read_from_bytes(&mut Cursor::new(chunk_data))
+        .map_err(|e| Error::Generic(format!("Could not read chunk: {}", e)))?;
+
+    if let Err(e) = chunk.convert_to_net_mode() {
+        debug!(
+            "Skipping chunk {} {}, could not convert to network mode: {}",
             chunk.x_pos, chunk.z_pos, e
-        ))
-    })?;
+        );
+        return Ok(None);
+    }
 
-    chunk.dimension = Some("overworld".to_string());
+    chunk.dimension = Some(dimension.name().to_string());
+    let key = ChunkPos::new(chunk.x_pos, chunk.z_pos, dimension).key();
 
-    let hash = hash((
-        chunk
-            .dimension
-            .as_ref()
-            .unwrap_or_else(|| panic!("Invalid chunk @ ({},{})", chunk.x_pos, chunk.z_pos)),
-        chunk.x_pos,
-        chunk.z_pos,
-    ));
-    let chunk_data = ZstdCodec::compress_data(chunk)
+    let chunk_data = ChunkCodec::compress_data(chunk, compression)
         .await
         .expect("Failed to compress chunk");
 
-    Ok(SerializedChunk::new(hash, chunk_data))
+    Ok(Some(SerializedChunk::new(key, chunk_data)))
 }
 
-//noinspection RsBorrowChecker
-pub async fn import_regions(state: GlobalState) -> Result<()> {
-    let dir = get_import_directory()?;
-    debug!("Starting import from: {}", dir.display());
-
-    let start = std::time::Instant::now();
-    info!("Analyzing world data... (this won't take long)");
-
-    let total_chunks = get_total_chunks(&dir).await?;
-    info!("Preparing to import {} chunks", total_chunks);
-    info!("This process may take a while for large worlds. Please be patient.");
+fn is_region_file(path: &Path) -> bool {
+    path.is_file() && path.extension() == Some("mca".as_ref())
+}
 
-    let batch_size = get_batch_size() as usize;
-    let bar = Arc::new(create_progress_bar(total_chunks));
+/// Imports every `.mca` file in `dir` into `dimension`, inserting chunks in batches of
+/// `batch_size` per write transaction.
+///
+/// Unreadable region files and corrupt chunks are logged and counted, not fatal. Only a failing
+/// database write aborts the import.
+async fn import_region_files(
+    database: &Database,
+    dir: &Path,
+    dimension: &Dimension,
+    batch_size: usize,
+    bar: Option<&ProgressBar>,
+) -> Result<ImportStats> {
+    let compression = database.compression();
+    let mut stats = ImportStats::default();
+    database.register_dimension(dimension).await?;
 
     let mut region_files = tokio::fs::read_dir(dir)
         .await
-        .map_err(|_| Error::Generic("Could not read the imports directory".to_string()))?;
+        .map_err(|_| Error::Generic(format!("Could not read the directory {}", dir.display())))?;
 
     while let Some(dir_file) = region_files.next_entry().await? {
-        let file_name = dir_file.file_name();
-        let file_name = file_name.to_str().unwrap_or("unknown file");
-        let file = File::open(dir_file.path())?;
-        let mut region = Region::from_stream(file)?;
+        let path = dir_file.path();
+        if !is_region_file(&path) {
+            continue;
+        }
+        let file_name = dir_file.file_name().to_string_lossy().to_string();
+
+        let region = File::open(&path)
+            .map_err(Error::from)
+            .and_then(|file| Region::from_stream(file).map_err(Error::from));
+        let mut region = match region {
+            Ok(region) => region,
+            Err(e) => {
+                warn!("(Skipped) Could not read region file {}: {}", file_name, e);
+                continue;
+            }
+        };
+
+        let mut region_stats = ImportStats::default();
+        let mut chunks: Vec<ChunkData> = Vec::new();
+        for chunk in region.iter() {
+            match chunk {
+                Ok(chunk) => chunks.push(chunk),
+                Err(e) => {
+                    warn!("Corrupt chunk entry in {}: {}", file_name, e);
+                    region_stats.corrupt += 1;
+                }
+            }
+        }
+        if let Some(bar) = bar {
+            bar.inc(region_stats.corrupt as u64);
+        }
 
-        let mut chunks: Vec<ChunkData> = region.iter().filter_map(|chunk| chunk.ok()).collect();
         while !chunks.is_empty() {
             let chunk_batch: Vec<ChunkData> = chunks
                 .drain(..std::cmp::min(batch_size, chunks.len()))
                 .collect();
+            let batch_len = chunk_batch.len();
 
             let processed_chunks_futures: Vec<_> = chunk_batch
                 .into_iter()
                 .map(|chunk| {
-                    let data = chunk.data.clone();
-                    let bar_clone = Arc::clone(&bar);
-                    let file_name = file_name.to_string();
-                    tokio::spawn(async move {
-                        match process_chunk(data, &file_name, Arc::clone(&bar_clone)).await {
-                            Ok(processed) => {
-                                bar_clone.inc(1);
-                                Some(processed)
-                            }
-                            Err(e) => {
-                                warn!("Failed to process chunk: {}. Skipping.", e);
-                                None
-                            }
-                        }
-                    })
+                    tokio::spawn(process_chunk(chunk.data, dimension.clone(), compression))
                 })
                 .collect();
 
-            let processed_chunks: Vec<SerializedChunk> =
-                futures::future::join_all(processed_chunks_futures)
-                    .await
-                    .into_iter()
-                    .filter_map(|result| result.ok().flatten())
-                    .collect();
+            let mut processed_chunks = Vec::with_capacity(batch_len);
+            for result in futures::future::join_all(processed_chunks_futures).await {
+                match result {
+                    Ok(Ok(Some(chunk))) => processed_chunks.push(chunk),
+                    Ok(Ok(None)) => region_stats.skipped += 1,
+                    Ok(Err(e)) => {
+                        warn!("Corrupt chunk in {}: {}", file_name, e);
+                        region_stats.corrupt += 1;
+                    }
+                    Err(e) => {
+                        warn!("Failed to process chunk in {}: {}", file_name, e);
+                        region_stats.corrupt += 1;
+                    }
+                }
+            }
 
-            insert_chunks(&state, processed_chunks, &bar).await?;
+            region_stats.imported += processed_chunks.len();
+            insert_chunks(database, processed_chunks, bar).await?;
+            if let Some(bar) = bar {
+                bar.inc(batch_len as u64);
+            }
         }
+
+        debug!(
+            "Imported {}: {} imported, {} skipped, {} corrupt",
+            file_name, region_stats.imported, region_stats.skipped, region_stats.corrupt
+        );
+        stats += region_stats;
     }
 
-    finalize_import(&bar, total_chunks, start.elapsed());
+    Ok(stats)
+}
+
+impl Database {
+    /// Imports an Anvil world directory into `dimension`.
+    ///
+    /// `path` may either be the dimension folder containing `region/` or the region folder
+    /// itself. Corrupt chunks are skipped rather than aborting the import; the returned
+    /// [`ImportStats`] tell how many chunks ended up in each bucket.
+    pub async fn import_region_dir(
+        &self,
+        path: &Path,
+        dimension: &Dimension,
+    ) -> Result<ImportStats> {
+        let region_dir = path.join("region");
+        let dir = if region_dir.is_dir() {
+            region_dir
+        } else {
+            path.to_path_buf()
+        };
+
+        info!("Importing {} into {}", dir.display(), dimension);
+        let start = std::time::Instant::now();
+        let stats =
+            import_region_files(self, &dir, dimension, DEFAULT_BATCH_SIZE as usize, None).await?;
+        info!(
+            "Imported {} chunks into {} in {} ({} skipped, {} corrupt)",
+            stats.imported,
+            dimension,
+            format_duration(start.elapsed()),
+            stats.skipped,
+            stats.corrupt
+        );
+
+        Ok(stats)
+    }
+
+    /// Imports a single chunk from JSON, like the `chunk.json` written by the `dump_chunk` test.
+    ///
+    /// `dimension`, `x_pos` and `z_pos` must be present, the other fields follow [`Chunk`].
+    /// Invalid JSON fails with an error naming the offending field. The chunk is inserted like
+    /// any other, replacing the chunk already at its position.
+    pub async fn import_chunk_json(&self, reader: impl Read) -> Result<ChunkPos> {
+        let value: serde_json::Value = serde_json::from_reader(reader)
+            .map_err(|e| Error::DeserializationError(format!("Invalid chunk JSON: {}", e)))?;
+        for field in ["dimension", "x_pos", "z_pos"] {
+            if value.get(field).filter(|value| !value.is_null()).is_none() {
+                return Err(Error::DeserializationError(format!(
+                    "Chunk JSON is missing `{}`",
+                    field
+                )));
+            }
+        }
+        let mut chunk: Chunk = serde_path_to_error::deserialize(value).map_err(|e| {
+            Error::DeserializationError(format!(
+                "Invalid chunk JSON at `{}`: {}",
+                e.path(),
+                e.inner()
+            ))
+        })?;
+
+        let dimension = Dimension::from(chunk.dimension.as_deref().unwrap_or_default());
+        // Keys use the plain dimension name, "minecraft:overworld" must land in "overworld"
+        chunk.dimension = Some(dimension.name().to_string());
+        let pos = ChunkPos::new(chunk.x_pos, chunk.z_pos, dimension);
+        self.insert_chunk(chunk).await?;
+        debug!("Imported chunk {} from JSON", pos);
+        Ok(pos)
+    }
+}
+
+//noinspection RsBorrowChecker
+pub async fn import_regions(state: GlobalState) -> Result<()> {
+    let dir = get_import_directory()?;
+    debug!("Starting import from: {}", dir.display());
+
+    let start = std::time::Instant::now();
+    info!("Analyzing world data... (this won't take long)");
+
+    let total_chunks = get_total_chunks(&dir).await?;
+    info!("Preparing to import {} chunks", total_chunks);
+    info!("This process may take a while for large worlds. Please be patient.");
+
+    let batch_size = get_batch_size() as usize;
+    let bar = create_progress_bar(total_chunks);
+
+    let database = state.world.resource::<Database>().await?;
+    let stats = import_region_files(
+        &database,
+        &dir,
+        &Dimension::Overworld,
+        batch_size,
+        Some(&bar),
+    )
+    .await?;
+
+    finalize_import(&bar, stats, start.elapsed());
     Ok(())
 }
 
@@ -218,40 +368,75 @@ fn create_progress_bar(total_chunks: usize) -> ProgressBar {
 }
 
 async fn insert_chunks(
-    state: &GlobalState,
+    database: &Database,
     queued_chunks: Vec<SerializedChunk>,
-    bar: &ProgressBar,
+    bar: Option<&ProgressBar>,
 ) -> Result<()> {
-    state
-        .database
-        .batch_insert(queued_chunks)
-        .await
-        .map_err(|e| {
+    database.batch_insert(queued_chunks).await.map_err(|e| {
+        if let Some(bar) = bar {
             bar.abandon_with_message("Chunk insertion failed".to_string());
-            Error::Generic(format!("Could not insert chunks: {}", e))
-        })?;
+        }
+        Error::Generic(format!("Could not insert chunks: {}", e))
+    })?;
     Ok(())
 }
 
-fn finalize_import(bar: &ProgressBar, total_chunks: usize, elapsed: std::time::Duration) {
+fn finalize_import(bar: &ProgressBar, stats: ImportStats, elapsed: std::time::Duration) {
     bar.finish_with_message(format!(
         "Import complete! {} chunks processed.",
-        total_chunks
+        stats.total()
     ));
     info!(
-        "Successfully imported {} chunks in {}",
-        total_chunks,
-        format_duration(elapsed)
+        "Successfully imported {} chunks in {} ({} skipped, {} corrupt)",
+        stats.imported,
+        format_duration(elapsed),
+        stats.skipped,
+        stats.corrupt
     );
 }
 
 #[cfg(test)]
 mod test {
     use crate::create_state;
+    use crate::database::chunks::tests::{test_chunk, test_database};
     use crate::utils::prelude::*;
     use crate::utils::setup_logger;
+    use crate::world::dimension::{ChunkPos, Dimension};
     use tokio::net::TcpListener;
 
+    #[tokio::test]
+    async fn chunk_json_round_trips() {
+        let database = test_database().await;
+        let mut chunk = test_chunk(4, -2);
+        chunk.dimension = Some("minecraft:the_nether".to_string());
+        let json = serde_json::to_vec(&chunk).unwrap();
+
+        let pos = database.import_chunk_json(json.as_slice()).await.unwrap();
+        assert_eq!(pos, ChunkPos::new(4, -2, Dimension::Nether));
+        let imported = database.get_chunk(&pos).await.unwrap().unwrap();
+        assert_eq!(imported.sections, chunk.sections);
+    }
+
+    #[tokio::test]
+    async fn malformed_chunk_json_names_the_field() {
+        let database = test_database().await;
+        let mut json = serde_json::to_value(test_chunk(0, 0)).unwrap();
+        json["z_pos"] = serde_json::Value::Null;
+        let err = database
+            .import_chunk_json(json.to_string().as_bytes())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("`z_pos`"), "{}", err);
+
+        json["z_pos"] = 0.into();
+        json["data_version"] = "3465".into();
+        let err = database
+            .import_chunk_json(json.to_string().as_bytes())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("`data_version`"), "{}", err);
+    }
+
     #[tokio::test]
     #[ignore]
     async fn get_chunk_at() -> Result<()> {
@@ -261,8 +446,10 @@ mod test {
         let state = create_state(listener).await?;
 
         let chunk = state
-            .database
-            .get_chunk(0, 0, "overworld".to_string())
+            .world
+            .resource::<crate::database::Database>()
+            .await?
+            .get_chunk(&ChunkPos::overworld(0, 0))
             .await?
             .unwrap();
 
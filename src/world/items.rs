@@ -0,0 +1,178 @@
+//! Item registry
+//!
+//! Network ids of the items, as numbered by the `item` registry of 1.20.1. Only the items of
+//! the first natural and ore blocks and a few others are known for now, the others have no id.
+
+/// Item names, the index is the network id
+const ITEMS: &[&str] = &[
+    "minecraft:air",
+    "minecraft:stone",
+    "minecraft:granite",
+    "minecraft:polished_granite",
+    "minecraft:diorite",
+    "minecraft:polished_diorite",
+    "minecraft:andesite",
+    "minecraft:polished_andesite",
+    "minecraft:deepslate",
+    "minecraft:cobbled_deepslate",
+    "minecraft:polished_deepslate",
+    "minecraft:calcite",
+    "minecraft:tuff",
+    "minecraft:dripstone_block",
+    "minecraft:grass_block",
+    "minecraft:dirt",
+    "minecraft:coarse_dirt",
+    "minecraft:podzol",
+    "minecraft:rooted_dirt",
+    "minecraft:mud",
+    "minecraft:crimson_nylium",
+    "minecraft:warped_nylium",
+    "minecraft:cobblestone",
+    "minecraft:oak_planks",
+    "minecraft:spruce_planks",
+    "minecraft:birch_planks",
+    "minecraft:jungle_planks",
+    "minecraft:acacia_planks",
+    "minecraft:cherry_planks",
+    "minecraft:dark_oak_planks",
+    "minecraft:mangrove_planks",
+    "minecraft:bamboo_planks",
+    "minecraft:crimson_planks",
+    "minecraft:warped_planks",
+    "minecraft:bamboo_mosaic",
+    "minecraft:oak_sapling",
+    "minecraft:spruce_sapling",
+    "minecraft:birch_sapling",
+    "minecraft:jungle_sapling",
+    "minecraft:acacia_sapling",
+    "minecraft:cherry_sapling",
+    "minecraft:dark_oak_sapling",
+    "minecraft:mangrove_propagule",
+    "minecraft:bedrock",
+    "minecraft:sand",
+    "minecraft:suspicious_sand",
+    "minecraft:suspicious_gravel",
+    "minecraft:red_sand",
+    "minecraft:gravel",
+    "minecraft:coal_ore",
+    "minecraft:deepslate_coal_ore",
+    "minecraft:iron_ore",
+    "minecraft:deepslate_iron_ore",
+    "minecraft:copper_ore",
+    "minecraft:deepslate_copper_ore",
+    "minecraft:gold_ore",
+    "minecraft:deepslate_gold_ore",
+    "minecraft:redstone_ore",
+    "minecraft:deepslate_redstone_ore",
+    "minecraft:emerald_ore",
+    "minecraft:deepslate_emerald_ore",
+    "minecraft:lapis_ore",
+    "minecraft:deepslate_lapis_ore",
+    "minecraft:diamond_ore",
+    "minecraft:deepslate_diamond_ore",
+    "minecraft:nether_gold_ore",
+    "minecraft:nether_quartz_ore",
+    "minecraft:ancient_debris",
+    "minecraft:coal_block",
+    "minecraft:raw_iron_block",
+    "minecraft:raw_copper_block",
+    "minecraft:raw_gold_block",
+    "minecraft:amethyst_block",
+    "minecraft:budding_amethyst",
+    "minecraft:iron_block",
+    "minecraft:copper_block",
+    "minecraft:gold_block",
+    "minecraft:diamond_block",
+    "minecraft:netherite_block",
+];
+
+/// Items past the end of [`ITEMS`], with their network id
+const OTHER_ITEMS: &[(&str, i32)] = &[
+    ("minecraft:wooden_sword", 798),
+    ("minecraft:wooden_shovel", 799),
+    ("minecraft:wooden_pickaxe", 800),
+    ("minecraft:wooden_axe", 801),
+    ("minecraft:wooden_hoe", 802),
+    ("minecraft:stone_sword", 803),
+    ("minecraft:stone_shovel", 804),
+    ("minecraft:stone_pickaxe", 805),
+    ("minecraft:stone_axe", 806),
+    ("minecraft:stone_hoe", 807),
+    ("minecraft:golden_sword", 808),
+    ("minecraft:golden_shovel", 809),
+    ("minecraft:golden_pickaxe", 810),
+    ("minecraft:golden_axe", 811),
+    ("minecraft:golden_hoe", 812),
+    ("minecraft:iron_sword", 813),
+    ("minecraft:iron_shovel", 814),
+    ("minecraft:iron_pickaxe", 815),
+    ("minecraft:iron_axe", 816),
+    ("minecraft:iron_hoe", 817),
+    ("minecraft:diamond_sword", 818),
+    ("minecraft:diamond_shovel", 819),
+    ("minecraft:diamond_pickaxe", 820),
+    ("minecraft:diamond_axe", 821),
+    ("minecraft:diamond_hoe", 822),
+    ("minecraft:netherite_sword", 823),
+    ("minecraft:netherite_shovel", 824),
+    ("minecraft:netherite_pickaxe", 825),
+    ("minecraft:netherite_axe", 826),
+    ("minecraft:netherite_hoe", 827),
+    ("minecraft:writable_book", 1069),
+    ("minecraft:written_book", 1070),
+];
+
+/// Network id of the item `name`, `None` if it isn't known
+pub fn item_id(name: &str) -> Option<i32> {
+    ITEMS
+        .iter()
+        .position(|item| *item == name)
+        .map(|id| id as i32)
+        .or_else(|| {
+            OTHER_ITEMS
+                .iter()
+                .find(|(item, _)| *item == name)
+                .map(|(_, id)| *id)
+        })
+}
+
+/// Name of the item of id `id`
+pub fn item_name(id: i32) -> Option<&'static str> {
+    usize::try_from(id)
+        .ok()
+        .and_then(|id| ITEMS.get(id).copied())
+        .or_else(|| {
+            OTHER_ITEMS
+                .iter()
+                .find(|(_, item_id)| *item_id == id)
+                .map(|(item, _)| *item)
+        })
+}
+
+/// Item of the block `name`, `None` for air and blocks without a known item
+pub fn block_item(name: &str) -> Option<i32> {
+    item_id(name).filter(|id| *id != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_item, item_id, item_name};
+
+    #[test]
+    fn blocks_map_to_their_item() {
+        assert_eq!(block_item("minecraft:stone"), Some(1));
+        assert_eq!(block_item("minecraft:grass_block"), Some(14));
+        assert_eq!(item_name(22), Some("minecraft:cobblestone"));
+        assert_eq!(block_item("minecraft:air"), None);
+        assert_eq!(block_item("minecraft:water"), None);
+    }
+
+    #[test]
+    fn items_past_the_blocks_are_known() {
+        assert_eq!(item_id("minecraft:writable_book"), Some(1069));
+        assert_eq!(item_name(1070), Some("minecraft:written_book"));
+        assert_eq!(item_name(1071), None);
+        assert_eq!(item_id("minecraft:diamond_sword"), Some(818));
+        assert_eq!(item_name(827), Some("minecraft:netherite_hoe"));
+    }
+}
@@ -1,7 +1,16 @@
 pub mod blocks;
+pub mod border;
 pub mod chunk_format;
 pub mod conversions;
+pub mod difficulty;
+pub mod dimension;
+pub mod exporting;
+pub mod hardness;
 pub mod importing;
+pub mod items;
+pub mod tick_rate;
+pub mod time;
+pub mod weather;
 
 /// Since we don't know the exact amount of bytes, the first byte is the number of u8s in the last i64,
 /// so we know when to stop reading bytes from the last i64
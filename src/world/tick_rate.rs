@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Ticks run per second when the server keeps up
+pub const TARGET_TPS: f64 = 20.0;
+/// Ticks the rate is measured over, the last 5 seconds at full speed
+const SAMPLE_TICKS: usize = 100;
+
+/// Ticks per second the world actually runs at, measured over its last ticks
+///
+/// [`TimeSystem`](crate::net::systems::time_system::TimeSystem) records every tick, an
+/// overloaded server skips some and falls below [`TARGET_TPS`].
+#[derive(Debug, Default)]
+pub struct TickRate {
+    ticks: Mutex<VecDeque<Instant>>,
+}
+
+impl TickRate {
+    /// Record a tick run now
+    pub fn record(&self) {
+        self.record_at(Instant::now());
+    }
+
+    fn record_at(&self, instant: Instant) {
+        let mut ticks = self.ticks.lock();
+        if ticks.len() == SAMPLE_TICKS {
+            ticks.pop_front();
+        }
+        ticks.push_back(instant);
+    }
+
+    /// Ticks per second over the recorded ticks, at most [`TARGET_TPS`], which is also assumed
+    /// until two ticks were recorded
+    pub fn tps(&self) -> f64 {
+        let ticks = self.ticks.lock();
+        let (Some(first), Some(last)) = (ticks.front(), ticks.back()) else {
+            return TARGET_TPS;
+        };
+        let elapsed = last.duration_since(*first);
+        if elapsed == Duration::ZERO {
+            return TARGET_TPS;
+        }
+        ((ticks.len() - 1) as f64 / elapsed.as_secs_f64()).min(TARGET_TPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{TickRate, SAMPLE_TICKS, TARGET_TPS};
+
+    #[test]
+    fn rate_is_measured_over_the_last_ticks() {
+        let tick_rate = TickRate::default();
+        assert_eq!(tick_rate.tps(), TARGET_TPS);
+
+        let start = Instant::now();
+        // Every 100ms, half the target
+        for tick in 0..10 {
+            tick_rate.record_at(start + Duration::from_millis(tick * 100));
+        }
+        assert!((tick_rate.tps() - 10.0).abs() < 1e-9);
+
+        // Faster than the target is capped
+        let start = start + Duration::from_secs(1);
+        for tick in 0..SAMPLE_TICKS as u64 {
+            tick_rate.record_at(start + Duration::from_millis(tick * 10));
+        }
+        assert_eq!(tick_rate.tps(), TARGET_TPS);
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+use crate::net::packets::outgoing::update_time::UpdateTime;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::error::Error;
+
+/// Length of a day, in ticks
+pub const TICKS_PER_DAY: i64 = 24000;
+
+/// Age and time of day of the world, advanced by
+/// [`TimeSystem`](crate::net::systems::time_system::TimeSystem) 20 times per second
+#[derive(Debug)]
+pub struct TimeOfDay {
+    world_age: AtomicI64,
+    time_of_day: AtomicI64,
+    /// The `doDaylightCycle` game rule, the sun stands still without it
+    daylight_cycle: AtomicBool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            world_age: AtomicI64::new(0),
+            time_of_day: AtomicI64::new(0),
+            daylight_cycle: AtomicBool::new(true),
+        }
+    }
+}
+
+impl TimeOfDay {
+    pub fn world_age(&self) -> i64 {
+        self.world_age.load(Ordering::Relaxed)
+    }
+
+    /// Ticks since the start of the first day, the day is `time_of_day % TICKS_PER_DAY`
+    pub fn time_of_day(&self) -> i64 {
+        self.time_of_day.load(Ordering::Relaxed)
+    }
+
+    pub fn daylight_cycle(&self) -> bool {
+        self.daylight_cycle.load(Ordering::Relaxed)
+    }
+
+    pub fn set_daylight_cycle(&self, enabled: bool) {
+        self.daylight_cycle.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Advance a tick, the time of day only moves with the daylight cycle
+    pub fn tick(&self) {
+        self.world_age.fetch_add(1, Ordering::Relaxed);
+        if self.daylight_cycle() {
+            self.time_of_day.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Packet sending the time, a negative time of day stops the sun on the client
+    pub fn packet(&self) -> UpdateTime {
+        let mut time_of_day = self.time_of_day();
+        if !self.daylight_cycle() {
+            // -0 would still move
+            time_of_day = if time_of_day == 0 { -1 } else { -time_of_day };
+        }
+        UpdateTime::new(self.world_age(), time_of_day)
+    }
+
+    /// Jump to `time_of_day`, like `/time set`, and send it to every player at once
+    pub async fn set_time(&self, time_of_day: i64, state: &GlobalState) -> Result<(), Error> {
+        self.time_of_day.store(time_of_day, Ordering::Relaxed);
+        broadcast(self.packet(), state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeOfDay;
+
+    #[test]
+    fn the_sun_stands_still_without_the_daylight_cycle() {
+        let time = TimeOfDay::default();
+        time.set_daylight_cycle(false);
+        time.tick();
+        assert_eq!((time.world_age(), time.time_of_day()), (1, 0));
+        assert_eq!(time.packet().time_of_day, -1);
+
+        time.set_daylight_cycle(true);
+        for _ in 0..6000 {
+            time.tick();
+        }
+        assert_eq!(time.packet().time_of_day, 6000);
+        time.set_daylight_cycle(false);
+        assert_eq!(time.packet().time_of_day, -6000);
+    }
+}
@@ -0,0 +1,109 @@
+use rand::Rng;
+
+use crate::net::packets::outgoing::game_event::GameEvent;
+
+/// Ticks a clear sky lasts, like vanilla
+const CLEAR_DURATION: std::ops::Range<i32> = 12_000..180_000;
+/// Ticks a rain lasts, like vanilla
+const RAIN_DURATION: std::ops::Range<i32> = 12_000..24_000;
+/// Change of the rain level per tick, the rain takes 5 seconds to start or stop
+const RAIN_LEVEL_STEP: f32 = 0.01;
+
+/// Rain of the world, alternating with clear skies of random durations
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    raining: bool,
+    /// Ticks until the weather changes
+    ticks_left: i32,
+    /// How hard it rains, from 0.0 to 1.0, moving towards the weather
+    rain_level: f32,
+}
+
+impl Weather {
+    /// A clear sky for a random duration
+    pub fn new(rng: &mut impl Rng) -> Self {
+        Self {
+            raining: false,
+            ticks_left: rng.gen_range(CLEAR_DURATION),
+            rain_level: 0.0,
+        }
+    }
+
+    pub fn is_raining(&self) -> bool {
+        self.raining
+    }
+
+    /// Advance a tick, returns the game events telling the players how the weather changed
+    pub fn tick(&mut self, rng: &mut impl Rng) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        self.ticks_left -= 1;
+        if self.ticks_left <= 0 {
+            self.raining = !self.raining;
+            self.ticks_left = if self.raining {
+                rng.gen_range(RAIN_DURATION)
+            } else {
+                rng.gen_range(CLEAR_DURATION)
+            };
+            events.push(if self.raining {
+                GameEvent::begin_raining()
+            } else {
+                GameEvent::end_raining()
+            });
+        }
+
+        let target = if self.raining { 1.0 } else { 0.0 };
+        if self.rain_level != target {
+            self.rain_level = if self.raining {
+                (self.rain_level + RAIN_LEVEL_STEP).min(1.0)
+            } else {
+                (self.rain_level - RAIN_LEVEL_STEP).max(0.0)
+            };
+            events.push(GameEvent::rain_level(self.rain_level));
+        }
+        events
+    }
+
+    /// Game events showing the current weather to a player joining the world
+    pub fn join_events(&self) -> Vec<GameEvent> {
+        if !self.raining && self.rain_level == 0.0 {
+            return Vec::new();
+        }
+        vec![
+            GameEvent::begin_raining(),
+            GameEvent::rain_level(self.rain_level),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Weather, RAIN_DURATION};
+    use crate::net::packets::outgoing::game_event::{BEGIN_RAINING, RAIN_LEVEL_CHANGE};
+
+    #[test]
+    fn rain_starts_progressively() {
+        let mut rng = rand::thread_rng();
+        let mut weather = Weather {
+            raining: false,
+            ticks_left: 1,
+            rain_level: 0.0,
+        };
+
+        let events = weather.tick(&mut rng);
+        assert!(weather.is_raining());
+        assert!(RAIN_DURATION.contains(&weather.ticks_left));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, BEGIN_RAINING);
+        assert_eq!(
+            (events[1].event, events[1].value),
+            (RAIN_LEVEL_CHANGE, 0.01)
+        );
+
+        for _ in 0..200 {
+            weather.tick(&mut rng);
+        }
+        assert_eq!(weather.rain_level, 1.0);
+        // Raining at full level, nothing changes
+        assert!(weather.tick(&mut rng).is_empty());
+    }
+}